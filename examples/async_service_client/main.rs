@@ -4,7 +4,7 @@ use futures::{FutureExt as StdFutureExt, StreamExt, TryFutureExt};
 
 use log::debug;
 use ros2_client::prelude::*;
-use rustdds::{dds::WriteError, policy, QosPolicies, QosPolicyBuilder};
+use rustdds::dds::WriteError;
 use serde::{Deserialize, Serialize};
 use smol::future::FutureExt;
 
@@ -46,7 +46,6 @@ fn main() {
 
     debug!(">>> ros2_service starting...");
     let mut node = create_node();
-    let service_qos = create_qos();
 
     debug!(">>> ros2_service node started");
 
@@ -55,17 +54,17 @@ fn main() {
             ServiceMapping::Enhanced,
             &Name::new("/", "add_two_ints").unwrap(),
             &ServiceTypeName::new("example_interfaces", "AddTwoInts"),
-            service_qos.clone(),
-            service_qos,
+            None,
+            None,
         )
         .unwrap();
 
-    // Running node.spinner().spin() is similar to calling .spin() in the sense that
+    // Running the Spinner is similar to calling .spin() in the sense that
     // it runs background processes, such as graph event processing.
     //
     // However, in ros2-client Node does not call application back, but instead the
     // ROS application is implemented using async code.
-    smol::spawn(node.spinner().unwrap().spin()).detach();
+    node.spin_in_background().unwrap();
 
     debug!(">>> ros2_service client created");
 
@@ -129,18 +128,6 @@ fn main() {
     smol::block_on(main_loop);
 }
 
-fn create_qos() -> QosPolicies {
-    let service_qos: QosPolicies = {
-        QosPolicyBuilder::new()
-            .reliability(policy::Reliability::Reliable {
-                max_blocking_time: rustdds::Duration::from_millis(100),
-            })
-            .history(policy::History::KeepLast { depth: 1 })
-            .build()
-    };
-    service_qos
-}
-
 fn create_node() -> Node {
     let context = Context::new().unwrap();
     context
@@ -1,7 +1,7 @@
 use futures::StreamExt;
 
 use log::debug;
-use ros2_client::prelude::{dds::*, *};
+use ros2_client::prelude::*;
 use serde::{Deserialize, Serialize};
 
 // This is an example / test program.
@@ -30,7 +30,6 @@ fn main() {
 
     debug!(">>> ros2_service starting...");
     let mut node = create_node();
-    let service_qos = create_qos();
 
     println!(">>> ros2_service node started");
 
@@ -39,8 +38,8 @@ fn main() {
             ServiceMapping::Enhanced,
             &Name::new("/", "add_two_ints").unwrap(),
             &ServiceTypeName::new("example_interfaces", "AddTwoInts"),
-            service_qos.clone(),
-            service_qos,
+            None,
+            None,
         )
         .unwrap();
 
@@ -66,26 +65,6 @@ fn main() {
     // It would return the count of requestes processed, if the stream would end.
 } // main
 
-fn create_qos() -> QosPolicies {
-    let service_qos: QosPolicies = {
-        QosPolicyBuilder::new()
-            .history(History::KeepLast { depth: 10 })
-            .reliability(Reliability::Reliable {
-                max_blocking_time: DdsDuration::from_millis(100),
-            })
-            .durability(Durability::Volatile)
-            .deadline(Deadline(DdsDuration::INFINITE))
-            .lifespan(Lifespan {
-                duration: DdsDuration::INFINITE,
-            })
-            .liveliness(Liveliness::Automatic {
-                lease_duration: DdsDuration::INFINITE,
-            })
-            .build()
-    };
-    service_qos
-}
-
 fn create_node() -> Node {
     let context = Context::new().unwrap();
     context
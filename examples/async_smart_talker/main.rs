@@ -46,7 +46,7 @@ fn main() {
             .repeat(2);
 
     // We must run Spinner to have all the Node functions
-    smol::spawn(node.spinner().unwrap().spin()).detach();
+    node.spin_in_background().unwrap();
 
     // Dump all status events to console
     //
@@ -3,7 +3,6 @@ use std::{env, time::Duration};
 use futures::TryFutureExt;
 use ros2_client::prelude::{dds::*, *};
 
-use rustdds::{policy, QosPolicies, QosPolicyBuilder};
 use smol::future::FutureExt;
 
 fn main() {
@@ -20,11 +19,10 @@ fn main() {
 
     println!(">>> ros2_service starting...");
     let mut node = create_node();
-    let service_qos = create_qos();
 
     // Start background spinner.
     // E.g. waiting for server does not work without this.
-    smol::spawn(node.spinner().unwrap().spin()).detach();
+    node.spin_in_background().unwrap();
 
     println!(">>> ros2_service node started");
 
@@ -38,8 +36,8 @@ fn main() {
             ServiceMapping::Enhanced,
             &service_name,
             &ServiceTypeName::new("rcl_interfaces", "GetParameters"),
-            service_qos.clone(),
-            service_qos,
+            None,
+            None,
         )
         .unwrap();
 
@@ -85,18 +83,6 @@ fn main() {
     });
 }
 
-fn create_qos() -> QosPolicies {
-    let service_qos: QosPolicies = {
-        QosPolicyBuilder::new()
-            .reliability(policy::Reliability::Reliable {
-                max_blocking_time: rustdds::Duration::from_millis(100),
-            })
-            .history(policy::History::KeepLast { depth: 1 })
-            .build()
-    };
-    service_qos
-}
-
 fn create_node() -> Node {
     let context = Context::new().unwrap();
     context
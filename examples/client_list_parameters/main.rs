@@ -19,9 +19,7 @@ fn main() {
 
     // Start background spinner.
     // E.g. waiting for server does not work without this.
-    smol::spawn(node.spinner().unwrap().spin()).detach();
-
-    let service_qos = create_qos();
+    node.spin_in_background().unwrap();
 
     println!(">>> ros2_service node started");
 
@@ -35,8 +33,8 @@ fn main() {
             ServiceMapping::Enhanced,
             &service_name,
             &ServiceTypeName::new("rcl_interfaces", "ListParameters"),
-            service_qos.clone(),
-            service_qos,
+            None,
+            None,
         )
         .unwrap();
 
@@ -73,18 +71,6 @@ fn main() {
     });
 }
 
-fn create_qos() -> QosPolicies {
-    let service_qos: QosPolicies = {
-        QosPolicyBuilder::new()
-            .reliability(Reliability::Reliable {
-                max_blocking_time: rustdds::Duration::from_millis(100),
-            })
-            .history(History::KeepLast { depth: 1 })
-            .build()
-    };
-    service_qos
-}
-
 fn create_node() -> Node {
     let context = Context::new().unwrap();
     context
@@ -10,7 +10,7 @@ pub fn main() {
         )
         .unwrap();
 
-    smol::spawn(node.spinner().unwrap().spin()).detach();
+    node.spin_in_background().unwrap();
 
     let status_event_stream = node.status_receiver().for_each(|event| async move {
         println!("{:?}", event);
@@ -66,17 +66,11 @@ fn main() {
         )
         .unwrap();
 
-    smol::spawn(node.spinner().unwrap().spin()).detach();
+    node.spin_in_background().unwrap();
 
     let service_qos = create_qos();
 
-    let fibonacci_action_qos = ActionClientQosPolicies {
-        goal_service: service_qos.clone(),
-        result_service: service_qos.clone(),
-        cancel_service: service_qos.clone(),
-        feedback_subscription: service_qos.clone(),
-        status_subscription: service_qos,
-    };
+    let fibonacci_action_qos = ActionClientQosPolicies::uniform(service_qos);
 
     let fibonacci_action_client = node
         .create_action_client::<FibonacciAction>(
@@ -67,13 +67,13 @@ fn main() {
             .build()
     };
 
-    let fibonacci_action_qos = action::ActionServerQosPolicies {
-        goal_service: service_qos.clone(),
-        result_service: service_qos.clone(),
-        cancel_service: service_qos,
-        feedback_publisher: publisher_qos.clone(),
-        status_publisher: publisher_qos,
-    };
+    let fibonacci_action_qos = action::ActionServerQosPolicies::new(
+        service_qos.clone(),
+        service_qos.clone(),
+        service_qos,
+        publisher_qos.clone(),
+        publisher_qos,
+    );
 
     let mut fibonacci_action_server = action::AsyncActionServer::new(
         node.create_action_server::<FibonacciAction>(
@@ -1,7 +1,7 @@
 use std::time::{Duration, Instant};
 
 use mio::{Events, Poll, PollOpt, Ready, Token};
-use ros2_client::prelude::{dds::*, *};
+use ros2_client::prelude::*;
 use serde::{Deserialize, Serialize};
 
 const RESPONSE_TOKEN: Token = Token(7); // Just an arbitrary value
@@ -33,7 +33,6 @@ fn main() {
 
     println!(">>> ros2_service starting...");
     let mut node = create_node();
-    let service_qos = create_qos();
 
     println!(">>> ros2_service node started");
 
@@ -42,8 +41,8 @@ fn main() {
             ServiceMapping::Enhanced,
             &Name::new("/", "add_two_ints").unwrap(),
             &ServiceTypeName::new("example_interfaces", "AddTwoInts"),
-            service_qos.clone(),
-            service_qos,
+            None,
+            None,
         )
         .unwrap();
 
@@ -95,18 +94,6 @@ fn main() {
     }
 }
 
-fn create_qos() -> QosPolicies {
-    let service_qos: QosPolicies = {
-        QosPolicyBuilder::new()
-            .reliability(Reliability::Reliable {
-                max_blocking_time: rustdds::Duration::from_millis(100),
-            })
-            .history(History::KeepLast { depth: 1 })
-            .build()
-    };
-    service_qos
-}
-
 fn create_node() -> Node {
     let context = Context::new().unwrap();
     context
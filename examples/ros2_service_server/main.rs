@@ -1,6 +1,6 @@
 use log::error;
 use mio::{Events, Poll, PollOpt, Ready, Token};
-use ros2_client::prelude::{dds::*, *};
+use ros2_client::prelude::*;
 
 use serde::{Deserialize, Serialize};
 
@@ -30,7 +30,6 @@ fn main() {
 
     println!(">>> ros2_service starting...");
     let mut node = create_node();
-    let service_qos = create_qos();
 
     println!(">>> ros2_service node started");
 
@@ -39,8 +38,8 @@ fn main() {
             ServiceMapping::Enhanced,
             &Name::new("/", "add_two_ints").unwrap(),
             &ServiceTypeName::new("example_interfaces", "AddTwoInts"),
-            service_qos.clone(),
-            service_qos,
+            None,
+            None,
         )
         .unwrap();
 
@@ -90,26 +89,6 @@ fn main() {
     } // loop
 } // main
 
-fn create_qos() -> QosPolicies {
-    let service_qos: QosPolicies = {
-        QosPolicyBuilder::new()
-            .history(History::KeepLast { depth: 10 })
-            .reliability(Reliability::Reliable {
-                max_blocking_time: DdsDuration::from_millis(100),
-            })
-            .durability(Durability::Volatile)
-            .deadline(Deadline(DdsDuration::INFINITE))
-            .lifespan(Lifespan {
-                duration: DdsDuration::INFINITE,
-            })
-            .liveliness(Liveliness::Automatic {
-                lease_duration: DdsDuration::INFINITE,
-            })
-            .build()
-    };
-    service_qos
-}
-
 fn create_node() -> Node {
     let context = Context::new().unwrap();
     context
@@ -1,7 +1,7 @@
 use std::{convert::TryInto, time::Duration};
 
 use chrono::{DateTime, Utc};
-use ros2_client::{interfaces::builtin_interfaces::Time, prelude::*};
+use ros2_client::{prelude::*, time::clock_publisher::ClockPublisher};
 
 pub fn main() {
     log4rs::init_file("log4rs.yaml", Default::default()).unwrap();
@@ -47,20 +47,10 @@ pub fn main() {
     //
     // ros2 param describe --spin-time=5 /time_broadcaster my_param
 
-    let clock_publisher = node
-        .create_publisher::<Time>(
-            &node
-                .create_topic(
-                    &Name::new("/", "clock").unwrap(),
-                    MessageTypeName::new("builtin_interfaces", "Time"),
-                    &DEFAULT_PUBLISHER_QOS,
-                )
-                .unwrap(),
-            None,
-        )
-        .unwrap();
+    let start_time = node.time_now();
+    let mut clock_publisher = ClockPublisher::new(&mut node, start_time).unwrap();
 
-    smol::spawn(node.spinner().unwrap().spin()).detach();
+    node.spin_in_background().unwrap();
 
     // Define at which rates simlated time proceeds vs. real time.
     // Tiacks below are equal in length.
@@ -68,14 +58,14 @@ pub fn main() {
     let sim_time_tick = Duration::from_millis(1000);
     let real_time_tick = Duration::from_millis(2000);
 
-    let mut sim_time = node.time_now();
-
     smol::block_on(async move {
+        clock_publisher.publish().unwrap();
         loop {
-            println!("tick {:?}", DateTime::<Utc>::from(sim_time));
-            clock_publisher.publish(sim_time.into()).unwrap();
-            sim_time = sim_time + sim_time_tick.try_into().unwrap();
+            println!("tick {:?}", DateTime::<Utc>::from(clock_publisher.time()));
             smol::Timer::after(real_time_tick).await;
+            clock_publisher
+                .advance(sim_time_tick.try_into().unwrap())
+                .unwrap();
         }
     });
 }
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use ros2_client::{interfaces::builtin_interfaces::Time, prelude::*};
+use ros2_client::{interfaces::rosgraph_msgs::Clock, prelude::*};
 
 pub fn main() {
     log4rs::init_file("log4rs.yaml", Default::default()).unwrap();
@@ -14,17 +14,17 @@ pub fn main() {
         )
         .unwrap();
 
-    smol::spawn(node.spinner().unwrap().spin()).detach();
+    node.spin_in_background().unwrap();
 
     node.set_parameter("use_sim_time", ParameterValue::Boolean(true))
         .unwrap();
 
     let clock_publisher = node
-        .create_publisher::<Time>(
+        .create_publisher::<Clock>(
             &node
                 .create_topic(
                     &Name::new("/", "clock").unwrap(),
-                    MessageTypeName::new("builtin_interfaces", "Time"),
+                    MessageTypeName::new("rosgraph_msgs", "Clock"),
                     &DEFAULT_PUBLISHER_QOS,
                 )
                 .unwrap(),
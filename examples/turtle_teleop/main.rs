@@ -242,8 +242,8 @@ fn ros2_loop(
             ServiceMapping::Enhanced,
             &Name::new("/", "reset").unwrap(),
             &empty_srv_type,
-            service_qos.clone(),
-            service_qos.clone(),
+            Some(service_qos.clone()),
+            Some(service_qos.clone()),
         )
         .unwrap();
 
@@ -256,8 +256,8 @@ fn ros2_loop(
             ServiceMapping::Enhanced,
             &Name::new("/turtle1", "set_pen").unwrap(),
             &set_pen_srv_type,
-            service_qos.clone(),
-            service_qos.clone(),
+            Some(service_qos.clone()),
+            Some(service_qos.clone()),
         )
         .unwrap();
 
@@ -288,8 +288,8 @@ fn ros2_loop(
             ServiceMapping::Enhanced,
             &Name::new("/", "spawn").unwrap(),
             &spawn_srv_type,
-            service_qos.clone(),
-            service_qos.clone(),
+            Some(service_qos.clone()),
+            Some(service_qos.clone()),
         )
         .unwrap();
 
@@ -310,8 +310,8 @@ fn ros2_loop(
             ServiceMapping::Enhanced,
             &Name::new("/", "kill").unwrap(),
             &kill_srv_type,
-            service_qos.clone(),
-            service_qos.clone(),
+            Some(service_qos.clone()),
+            Some(service_qos.clone()),
         )
         .unwrap();
 
@@ -346,14 +346,7 @@ fn ros2_loop(
     type RotateAbsoluteAction =
         Action<RotateAbsoluteGoal, RotateAbsoluteResult, RotateAbsoluteFeedback>;
 
-    //TODO: There should be an easier way to do this.
-    let rotate_action_qos = ActionClientQosPolicies {
-        goal_service: service_qos.clone(),
-        result_service: service_qos.clone(),
-        cancel_service: service_qos.clone(),
-        feedback_subscription: service_qos.clone(),
-        status_subscription: service_qos,
-    };
+    let rotate_action_qos = ActionClientQosPolicies::uniform(service_qos);
 
     let mut rotate_action_client = ros_node
         .create_action_client::<RotateAbsoluteAction>(
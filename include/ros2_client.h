@@ -0,0 +1,63 @@
+/*
+ * C API of ros2-client, available with the Cargo feature "ffi".
+ *
+ * Messages are CDR little-endian encoded, without the 4-byte encapsulation
+ * header. Subscriptions drop messages in other encodings. Functions that fail
+ * return NULL or -1, and ros2_client_last_error() tells why.
+ */
+
+#ifndef ROS2_CLIENT_H
+#define ROS2_CLIENT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct ContextHandle ros2_client_context;
+typedef struct NodeHandle ros2_client_node;
+typedef struct PublisherHandle ros2_client_publisher;
+typedef struct SubscriptionHandle ros2_client_subscription;
+
+/* Called from a background thread with each received message. The buffer is
+ * valid only during the call, and may have up to 3 bytes of padding. */
+typedef void (*ros2_client_message_callback)(const uint8_t *data, size_t len,
+                                             void *user_data);
+
+/* Reason of the latest failure on this thread, or NULL. Valid until the next
+ * failing call on this thread. */
+const char *ros2_client_last_error(void);
+
+ros2_client_context *ros2_client_context_new(void);
+void ros2_client_context_free(ros2_client_context *context);
+
+/* Creates a Node and runs its Spinner in a background thread. */
+ros2_client_node *ros2_client_node_new(const ros2_client_context *context,
+                                       const char *namespace_,
+                                       const char *name);
+void ros2_client_node_free(ros2_client_node *node);
+
+/* type_name is e.g. "std_msgs/msg/String". */
+ros2_client_publisher *ros2_client_publisher_new(ros2_client_node *node,
+                                                 const char *topic_name,
+                                                 const char *type_name);
+int ros2_client_publish(const ros2_client_publisher *publisher,
+                        const uint8_t *data, size_t len);
+void ros2_client_publisher_free(ros2_client_publisher *publisher);
+
+ros2_client_subscription *
+ros2_client_subscription_new(ros2_client_node *node, const char *topic_name,
+                             const char *type_name,
+                             ros2_client_message_callback callback,
+                             void *user_data);
+/* After this returns, the callback is not running and is not called again.
+ * Must not be called from the callback. */
+void ros2_client_subscription_free(ros2_client_subscription *subscription);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ROS2_CLIENT_H */
@@ -0,0 +1,285 @@
+//! Derive macros for [`ros2_client`](https://docs.rs/ros2-client).
+//!
+//! Use these through `ros2_client` with feature `derive`, not directly.
+
+use std::{fs, path::PathBuf};
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitStr,
+    Type,
+};
+
+mod msg_file;
+
+use msg_file::{MsgField, MsgType};
+
+/// Implements `ros2_client::Message`.
+///
+/// See `ros2_client::message::RosMessage` for documentation.
+#[proc_macro_derive(RosMessage, attributes(ros_message))]
+pub fn derive_ros_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(s) => &s.fields,
+        _ => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "RosMessage can only be derived for structs",
+            ))
+        }
+    };
+
+    // Each field type must itself be a Message. Checking this separately gives
+    // an error pointing to the offending field, instead of a vague Serialize or
+    // Deserialize error.
+    let field_asserts = fields.iter().map(|f| {
+        let ty = &f.ty;
+        quote_spanned! { ty.span() => __assert_ros_field::<#ty>(); }
+    });
+
+    let attrs = ros_message_attrs(input)?;
+    let msg_file_check = match &attrs.msg_file {
+        Some(path) => check_against_msg_file(fields, path)?,
+        None => TokenStream2::new(),
+    };
+    let named_message = match &attrs.type_name {
+        Some(type_name) => {
+            let (package, type_name) = split_type_name(type_name)?;
+            quote! {
+                impl #impl_generics ::ros2_client::message::NamedMessage for #name #ty_generics #where_clause {
+                    const PACKAGE_NAME: &'static str = #package;
+                    const TYPE_NAME: &'static str = #type_name;
+                }
+            }
+        }
+        None => TokenStream2::new(),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::ros2_client::message::Message for #name #ty_generics #where_clause {}
+        #named_message
+
+        const _: () = {
+            fn __assert_ros_field<T: ::ros2_client::message::Message>() {}
+            #[allow(dead_code)]
+            fn __check_ros_fields #impl_generics () #where_clause {
+                #(#field_asserts)*
+            }
+            #msg_file_check
+        };
+    })
+}
+
+#[derive(Default)]
+struct RosMessageAttrs {
+    msg_file: Option<LitStr>,
+    type_name: Option<LitStr>,
+}
+
+// Finds `#[ros_message(msg_file = "...", type_name = "...")]`
+fn ros_message_attrs(input: &DeriveInput) -> syn::Result<RosMessageAttrs> {
+    let mut result = RosMessageAttrs::default();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("ros_message"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("msg_file") {
+                result.msg_file = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("type_name") {
+                result.type_name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown ros_message attribute, expected `msg_file` or `type_name`"))
+            }
+        })?;
+    }
+    Ok(result)
+}
+
+// Splits "package/msg/Type" into package and type name.
+fn split_type_name(lit: &LitStr) -> syn::Result<(String, String)> {
+    let value = lit.value();
+    match value.split('/').collect::<Vec<_>>()[..] {
+        [package, "msg", type_name] if !package.is_empty() && !type_name.is_empty() => {
+            Ok((package.to_owned(), type_name.to_owned()))
+        }
+        _ => Err(syn::Error::new(
+            lit.span(),
+            format!("type_name must be like \"package/msg/Type\", got {value:?}"),
+        )),
+    }
+}
+
+fn check_against_msg_file(fields: &Fields, path_lit: &LitStr) -> syn::Result<TokenStream2> {
+    let span = path_lit.span();
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(span, "CARGO_MANIFEST_DIR is not set"))?;
+    let path = PathBuf::from(manifest_dir).join(path_lit.value());
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| syn::Error::new(span, format!("Cannot read {}: {e}", path.display())))?;
+    let msg_fields = msg_file::parse(&contents)
+        .map_err(|e| syn::Error::new(span, format!("{}: {e}", path.display())))?;
+
+    let named = match fields {
+        Fields::Named(named) => &named.named,
+        Fields::Unit if msg_fields.is_empty() => return Ok(include_for_rebuild(&path)),
+        _ => {
+            return Err(syn::Error::new(
+                span,
+                "msg_file can only be checked against a struct with named fields",
+            ))
+        }
+    };
+
+    if named.len() != msg_fields.len() {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "struct has {} fields, but {} has {}: {}",
+                named.len(),
+                path.display(),
+                msg_fields.len(),
+                msg_fields
+                    .iter()
+                    .map(|f| f.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+
+    for (field, msg_field) in named.iter().zip(msg_fields.iter()) {
+        let ident = field.ident.as_ref().unwrap().to_string();
+        let ident = ident.trim_start_matches("r#");
+        if ident != msg_field.name {
+            return Err(syn::Error::new(
+                field.span(),
+                format!(
+                    "field `{ident}` is at the position of field `{}` in {} (line {})",
+                    msg_field.name,
+                    path.display(),
+                    msg_field.line
+                ),
+            ));
+        }
+        check_field_type(&field.ty, msg_field)?;
+    }
+
+    Ok(include_for_rebuild(&path))
+}
+
+// Makes the compiler re-run the check when the .msg file changes.
+fn include_for_rebuild(path: &std::path::Path) -> TokenStream2 {
+    let path = path.display().to_string();
+    quote! { const _: &[u8] = include_bytes!(#path); }
+}
+
+fn check_field_type(ty: &Type, msg_field: &MsgField) -> syn::Result<()> {
+    if type_matches(ty, &msg_field.ty) {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            ty.span(),
+            format!(
+                "field `{}` is declared as `{}` (line {}), expected Rust type like `{}`",
+                msg_field.name,
+                msg_field.ty_text,
+                msg_field.line,
+                msg_field.ty.rust_type()
+            ),
+        ))
+    }
+}
+
+fn type_matches(ty: &Type, expected: &MsgType) -> bool {
+    match (ty, expected) {
+        (Type::Paren(p), _) => type_matches(&p.elem, expected),
+        (Type::Group(g), _) => type_matches(&g.elem, expected),
+        (Type::Array(array), MsgType::Array(elem, len)) => {
+            let len_ok = match &array.len {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(i), ..
+                }) => i.base10_parse::<usize>().is_ok_and(|n| n == *len),
+                // A named constant or expression. Cannot evaluate, so trust it.
+                _ => true,
+            };
+            len_ok && type_matches(&array.elem, elem)
+        }
+        (Type::Path(p), _) => {
+            let Some(last) = p.path.segments.last() else {
+                return false;
+            };
+            let ident = last.ident.to_string();
+            match expected {
+                // Not byte buffer types like Bytes: CDR serializes them
+                // without the length of a sequence.
+                MsgType::Sequence(elem) => {
+                    ident == "Vec"
+                        && single_type_argument(last).is_some_and(|inner| type_matches(inner, elem))
+                }
+                MsgType::Named(name) => ident == *name,
+                MsgType::Array(..) => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn single_type_argument(segment: &syn::PathSegment) -> Option<&Type> {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            syn::GenericArgument::Type(t) => Some(t),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_name_split() {
+        let lit = |s: &str| LitStr::new(s, Span::call_site());
+        assert_eq!(
+            split_type_name(&lit("std_msgs/msg/Header")).unwrap(),
+            ("std_msgs".to_owned(), "Header".to_owned())
+        );
+        assert!(split_type_name(&lit("std_msgs/Header")).is_err());
+        assert!(split_type_name(&lit("std_msgs/srv/Header")).is_err());
+        assert!(split_type_name(&lit("/msg/Header")).is_err());
+    }
+
+    #[test]
+    fn field_types() {
+        let matches = |rust: &str, msg: &str| {
+            let fields = msg_file::parse(&format!("{msg} field")).unwrap();
+            type_matches(&syn::parse_str(rust).unwrap(), &fields[0].ty)
+        };
+        assert!(matches("f64", "float64"));
+        assert!(matches("Vec<u8>", "uint8[]"));
+        assert!(matches("[i32; 3]", "int32[3]"));
+        assert!(!matches("[i32; 4]", "int32[3]"));
+        assert!(!matches("Vec<i8>", "uint8[]"));
+        // Not serialized as CDR sequences
+        assert!(!matches("Bytes", "uint8[]"));
+        assert!(!matches("serde_bytes::ByteBuf", "uint8[]"));
+    }
+}
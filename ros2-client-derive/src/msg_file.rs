@@ -0,0 +1,140 @@
+// Minimal parser for ROS 2 .msg files: Only field names and types are
+// extracted. Constants, default values and comments are skipped.
+
+pub struct MsgField {
+    pub name: String,
+    pub ty: MsgType,
+    // Type as written in the .msg file
+    pub ty_text: String,
+    pub line: usize,
+}
+
+// Type in terms of the corresponding Rust type. Named types are compared by
+// the last path segment only, because the Rust module layout is up to the
+// user.
+pub enum MsgType {
+    Named(String),
+    Sequence(Box<MsgType>),
+    Array(Box<MsgType>, usize),
+}
+
+impl MsgType {
+    pub fn rust_type(&self) -> String {
+        match self {
+            MsgType::Named(n) => n.clone(),
+            MsgType::Sequence(elem) => format!("Vec<{}>", elem.rust_type()),
+            MsgType::Array(elem, len) => format!("[{}; {len}]", elem.rust_type()),
+        }
+    }
+}
+
+pub fn parse(contents: &str) -> Result<Vec<MsgField>, String> {
+    let mut fields = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line_no = i + 1;
+        let line = strip_comment(line);
+        let mut tokens = line.split_whitespace();
+        let Some(ty_text) = tokens.next() else {
+            continue; // empty line
+        };
+        let name = tokens
+            .next()
+            .ok_or_else(|| format!("line {line_no}: missing field name"))?;
+        let rest = tokens.next();
+        if name.contains('=') || rest.is_some_and(|r| r.starts_with('=')) {
+            continue; // constant
+        }
+        fields.push(MsgField {
+            name: name.to_string(),
+            ty: parse_type(ty_text).map_err(|e| format!("line {line_no}: {e}"))?,
+            ty_text: ty_text.to_string(),
+            line: line_no,
+        });
+    }
+    Ok(fields)
+}
+
+// Comments start with '#', but '#' may also appear in string values.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_type(text: &str) -> Result<MsgType, String> {
+    if let Some(open) = text.find('[') {
+        let base = parse_base_type(&text[..open])?;
+        let spec = text[open..]
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(|| format!("malformed array type `{text}`"))?;
+        if spec.is_empty() || spec.starts_with("<=") {
+            Ok(MsgType::Sequence(Box::new(base)))
+        } else {
+            let len = spec
+                .parse()
+                .map_err(|_| format!("malformed array size in `{text}`"))?;
+            Ok(MsgType::Array(Box::new(base), len))
+        }
+    } else {
+        parse_base_type(text)
+    }
+}
+
+fn parse_base_type(text: &str) -> Result<MsgType, String> {
+    let rust = match text {
+        "bool" => "bool",
+        "byte" | "char" | "uint8" => "u8",
+        "float32" => "f32",
+        "float64" => "f64",
+        "int8" => "i8",
+        "int16" => "i16",
+        "int32" => "i32",
+        "int64" => "i64",
+        "uint16" => "u16",
+        "uint32" => "u32",
+        "uint64" => "u64",
+        "string" => "String",
+        "wstring" => "WString",
+        t if t.starts_with("string<=") => "String",
+        t if t.starts_with("wstring<=") => "WString",
+        "" => return Err("missing type".to_string()),
+        complex => complex.rsplit('/').next().unwrap_or(complex),
+    };
+    Ok(MsgType::Named(rust.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_constants_and_comments() {
+        let msg = "# header comment\n\
+                   int32 FOO=3\n\
+                   string BAR = \"a#b\"\n\
+                   float64 x  # trailing comment\n\
+                   geometry_msgs/Point[3] points\n\
+                   string<=10 name \"default\"\n";
+        let fields = parse(msg).unwrap();
+        let summary: Vec<_> = fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.ty.rust_type(), f.line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("x", "f64".to_string(), 4),
+                ("points", "[Point; 3]".to_string(), 5),
+                ("name", "String".to_string(), 6),
+            ]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Helper for using the same Action on several Action Servers.
+//!
+//! An [`ActionClientSet`] holds one [`ActionClient`] per server, e.g. one per
+//! robot in a fleet, identified by a key of the application's choosing. Goals
+//! can be sent to a selected server, to any server that accepts the goal, or
+//! to all servers. Feedback and results from several servers can be
+//! collected together.
+
+use std::collections::BTreeMap;
+
+use futures::{
+    future::join_all,
+    stream::{select_all, FusedStream, StreamExt},
+};
+use rustdds::dds::ReadResult;
+
+use crate::{
+    action::{goal::GoalStatusEnum, ActionClient, ActionTypes, GoalId, SendGoalResponse},
+    message::Message,
+    service::client::CallServiceError,
+};
+
+/// A set of [`ActionClient`]s for the same Action type, keyed by `K`.
+pub struct ActionClientSet<K, A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone,
+    A::ResultType: Message + Clone,
+    A::FeedbackType: Message,
+{
+    clients: BTreeMap<K, ActionClient<A>>,
+}
+
+impl<K, A> Default for ActionClientSet<K, A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone,
+    A::ResultType: Message + Clone,
+    A::FeedbackType: Message,
+{
+    fn default() -> Self {
+        ActionClientSet {
+            clients: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K, A> ActionClientSet<K, A>
+where
+    K: Ord + Clone,
+    A: ActionTypes,
+    A::GoalType: Message + Clone + 'static,
+    A::ResultType: Message + Clone + 'static,
+    A::FeedbackType: Message + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a client. Returns the previous client with the same key, if any.
+    pub fn insert(&mut self, key: K, client: ActionClient<A>) -> Option<ActionClient<A>> {
+        self.clients.insert(key, client)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<ActionClient<A>> {
+        self.clients.remove(key)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&ActionClient<A>> {
+        self.clients.get(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.clients.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Send a goal to the server identified by `key`.
+    ///
+    /// Returns `None` if there is no such server in the set.
+    pub async fn async_send_goal_to(
+        &self,
+        key: &K,
+        goal: A::GoalType,
+    ) -> Option<Result<(GoalId, SendGoalResponse), CallServiceError<()>>> {
+        match self.clients.get(key) {
+            Some(client) => Some(client.async_send_goal(goal).await),
+            None => None,
+        }
+    }
+
+    /// Offer the goal to servers one at a time, in key order, until one
+    /// accepts it.
+    ///
+    /// Returns the key of the accepting server and the goal id, or `None` if
+    /// all servers rejected the goal or could not be reached.
+    pub async fn async_send_goal_any(&self, goal: A::GoalType) -> Option<(K, GoalId)> {
+        for (key, client) in &self.clients {
+            match client.async_send_goal(goal.clone()).await {
+                Ok((goal_id, SendGoalResponse { accepted: true, .. })) => {
+                    return Some((key.clone(), goal_id))
+                }
+                Ok(_) => log::debug!("Goal rejected by a server, trying the next one."),
+                Err(e) => log::warn!("Sending goal failed: {e:?}"),
+            }
+        }
+        None
+    }
+
+    /// Send the goal to all servers concurrently.
+    pub async fn async_send_goal_all(
+        &self,
+        goal: A::GoalType,
+    ) -> Vec<(K, Result<(GoalId, SendGoalResponse), CallServiceError<()>>)> {
+        join_all(self.clients.iter().map(|(key, client)| {
+            let goal = goal.clone();
+            async move { (key.clone(), client.async_send_goal(goal).await) }
+        }))
+        .await
+    }
+
+    /// Feedback for the given goals, merged into one stream.
+    ///
+    /// Goals whose key is not in the set are ignored.
+    pub fn feedback_stream<'a>(
+        &'a self,
+        goals: impl IntoIterator<Item = (K, GoalId)>,
+    ) -> impl FusedStream<Item = (K, GoalId, ReadResult<A::FeedbackType>)> + 'a
+    where
+        K: 'a,
+    {
+        select_all(goals.into_iter().filter_map(|(key, goal_id)| {
+            self.clients.get(&key).map(|client| {
+                client
+                    .feedback_stream(goal_id)
+                    .map(move |feedback| (key.clone(), goal_id, feedback))
+                    .boxed_local()
+            })
+        }))
+    }
+
+    /// Request the results of the given goals concurrently, and wait for all
+    /// of them.
+    ///
+    /// Goals whose key is not in the set are ignored.
+    #[allow(clippy::type_complexity)]
+    pub async fn async_request_results(
+        &self,
+        goals: impl IntoIterator<Item = (K, GoalId)>,
+    ) -> Vec<(
+        K,
+        GoalId,
+        Result<(GoalStatusEnum, A::ResultType), CallServiceError<()>>,
+    )> {
+        join_all(goals.into_iter().filter_map(|(key, goal_id)| {
+            self.clients.get(&key).map(|client| async move {
+                let result = client.async_request_result(goal_id).await;
+                (key, goal_id, result)
+            })
+        }))
+        .await
+    }
+}
@@ -11,6 +11,8 @@ use crate::{
 };
 
 /// An identifier for an arbitrary 'goal'. Related to [`Action`]s.
+///
+/// See [`UUID`] for text formatting, parsing and conversions.
 pub type GoalId = UUID;
 
 /// From [GoalInfo](https://docs.ros2.org/foxy/api/action_msgs/msg/GoalInfo.html)
@@ -26,7 +28,7 @@ pub struct GoalInfo {
 impl Message for GoalInfo {}
 
 /// The status of a goal's completion within an Action.
-#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, Debug)]
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, Eq, Debug)]
 #[repr(i8)]
 pub enum GoalStatusEnum {
     /// The goal's status is unknown, or the Action Server has not yet
@@ -0,0 +1,188 @@
+//! Introspection of Action traffic.
+//!
+//! Actions consist of three Services (send goal, cancel goal, get result) and
+//! two Topics. Like introspection of plain Services in ROS 2 Iron and later,
+//! an [`ActionIntrospection`] publishes a
+//! [`ServiceEvent`](crate::interfaces::service_msgs::ServiceEvent) on
+//! `<action_name>/_action/<service>/_service_event` for every request and
+//! response that an [`ActionClient`](super::ActionClient) or
+//! [`ActionServer`](super::ActionServer) sends or receives.
+//!
+//! Create with [`Node::create_action_introspection`](crate::node::Node::create_action_introspection)
+//! and attach to a client or server with `set_introspection`.
+
+use crate::{
+    action::{
+        goal::{CancelGoalRequest, CancelGoalResponse},
+        ActionTypes, GetResultRequest, GetResultResponse, SendGoalRequest, SendGoalResponse,
+    },
+    interfaces::service_msgs::{
+        IntrospectionState, ServiceEvent, ServiceEventInfo, ServiceEventType,
+    },
+    message::Message,
+    node::pubsub::Publisher,
+    service::request_id::RmwRequestId,
+};
+
+/// Publishers for Action Service events.
+pub struct ActionIntrospection<A: ActionTypes> {
+    state: IntrospectionState,
+    pub(crate) send_goal_events:
+        Publisher<ServiceEvent<SendGoalRequest<A::GoalType>, SendGoalResponse>>,
+    pub(crate) cancel_goal_events: Publisher<ServiceEvent<CancelGoalRequest, CancelGoalResponse>>,
+    pub(crate) get_result_events:
+        Publisher<ServiceEvent<GetResultRequest, GetResultResponse<A::ResultType>>>,
+}
+
+impl<A: ActionTypes> ActionIntrospection<A> {
+    pub(crate) fn new(
+        state: IntrospectionState,
+        send_goal_events: Publisher<ServiceEvent<SendGoalRequest<A::GoalType>, SendGoalResponse>>,
+        cancel_goal_events: Publisher<ServiceEvent<CancelGoalRequest, CancelGoalResponse>>,
+        get_result_events: Publisher<
+            ServiceEvent<GetResultRequest, GetResultResponse<A::ResultType>>,
+        >,
+    ) -> Self {
+        ActionIntrospection {
+            state,
+            send_goal_events,
+            cancel_goal_events,
+            get_result_events,
+        }
+    }
+
+    pub fn state(&self) -> IntrospectionState {
+        self.state
+    }
+
+    /// Change what is published. [`IntrospectionState::Off`] stops publishing,
+    /// but keeps the event topics.
+    pub fn set_state(&mut self, state: IntrospectionState) {
+        self.state = state;
+    }
+
+    pub(crate) fn send_goal_request(
+        &self,
+        event_type: ServiceEventType,
+        request_id: RmwRequestId,
+        request: &SendGoalRequest<A::GoalType>,
+    ) {
+        self.publish(
+            &self.send_goal_events,
+            event_type,
+            request_id,
+            Some(request),
+            None,
+        );
+    }
+
+    pub(crate) fn send_goal_response(
+        &self,
+        event_type: ServiceEventType,
+        request_id: RmwRequestId,
+        response: &SendGoalResponse,
+    ) {
+        self.publish(
+            &self.send_goal_events,
+            event_type,
+            request_id,
+            None,
+            Some(response),
+        );
+    }
+
+    pub(crate) fn cancel_goal_request(
+        &self,
+        event_type: ServiceEventType,
+        request_id: RmwRequestId,
+        request: &CancelGoalRequest,
+    ) {
+        self.publish(
+            &self.cancel_goal_events,
+            event_type,
+            request_id,
+            Some(request),
+            None,
+        );
+    }
+
+    pub(crate) fn cancel_goal_response(
+        &self,
+        event_type: ServiceEventType,
+        request_id: RmwRequestId,
+        response: &CancelGoalResponse,
+    ) {
+        self.publish(
+            &self.cancel_goal_events,
+            event_type,
+            request_id,
+            None,
+            Some(response),
+        );
+    }
+
+    pub(crate) fn get_result_request(
+        &self,
+        event_type: ServiceEventType,
+        request_id: RmwRequestId,
+        request: &GetResultRequest,
+    ) {
+        self.publish(
+            &self.get_result_events,
+            event_type,
+            request_id,
+            Some(request),
+            None,
+        );
+    }
+
+    pub(crate) fn get_result_response(
+        &self,
+        event_type: ServiceEventType,
+        request_id: RmwRequestId,
+        response: &GetResultResponse<A::ResultType>,
+    ) {
+        self.publish(
+            &self.get_result_events,
+            event_type,
+            request_id,
+            None,
+            Some(response),
+        );
+    }
+
+    fn publish<Req, Resp>(
+        &self,
+        publisher: &Publisher<ServiceEvent<Req, Resp>>,
+        event_type: ServiceEventType,
+        request_id: RmwRequestId,
+        request: Option<&Req>,
+        response: Option<&Resp>,
+    ) where
+        Req: Message + Clone,
+        Resp: Message + Clone,
+    {
+        let with_contents = match self.state {
+            IntrospectionState::Off => return,
+            IntrospectionState::Metadata => false,
+            IntrospectionState::Contents => true,
+        };
+        let event = ServiceEvent {
+            info: ServiceEventInfo::new(event_type, request_id),
+            request: request
+                .filter(|_| with_contents)
+                .cloned()
+                .into_iter()
+                .collect(),
+            response: response
+                .filter(|_| with_contents)
+                .cloned()
+                .into_iter()
+                .collect(),
+        };
+        publisher
+            .publish(event)
+            .map_err(|e| e.forget_data())
+            .unwrap_or_else(|e| log::error!("Publishing action introspection event: {e:?}"));
+    }
+}
@@ -11,16 +11,21 @@
 use std::{
     collections::{btree_map::Entry, BTreeMap},
     marker::PhantomData,
+    sync::Mutex,
+    task::Poll,
+    time::Duration,
 };
 
 use futures::{
     pin_mut,
-    stream::{FusedStream, StreamExt},
-    Future,
+    stream::{FusedStream, Stream, StreamExt},
+    Future, FutureExt,
 };
+use futures_timer::Delay;
 use rustdds::{
     dds::{ReadError, ReadResult, WriteError, WriteResult},
-    QosPolicies,
+    policy::{Durability, History, Reliability},
+    QosPolicies, QosPolicyBuilder,
 };
 use serde::{Deserialize, Serialize};
 
@@ -33,14 +38,28 @@ use crate::{
     message::Message,
     prelude::{Name, Publisher, Subscription},
     service::{
+        call_trace::{CallEvent, CallTracer},
         client::{CallServiceError, Client},
         request_id::RmwRequestId,
         server::Server,
         AService,
     },
+    time::steady_time::SteadyTime,
 };
 
+pub mod fleet;
 pub mod goal;
+#[cfg(feature = "introspection")]
+pub mod introspection;
+
+#[cfg(feature = "introspection")]
+use crate::interfaces::service_msgs::ServiceEventType;
+#[cfg(feature = "no-default-runtime")]
+use crate::readiness::Readiness;
+#[cfg(feature = "introspection")]
+use introspection::ActionIntrospection;
+#[cfg(feature = "no-default-runtime")]
+use std::{io, task::Waker};
 
 /// A trait to define an Action type
 pub trait ActionTypes {
@@ -113,28 +132,238 @@ where
     }
 }
 
-//TODO: Make fields private, add constructor and accessors.
-
 /// Collection of QoS policies required for an Action client.
+///
+/// The [`Default`] follows the ROS 2 defaults for Actions: Reliable services
+/// and feedback with history depth 10, and Reliable TransientLocal status with
+/// history depth 1.
 #[derive(Debug, Clone)]
-#[expect(missing_docs)]
 pub struct ActionClientQosPolicies {
-    pub goal_service: QosPolicies,
-    pub result_service: QosPolicies,
-    pub cancel_service: QosPolicies,
-    pub feedback_subscription: QosPolicies,
-    pub status_subscription: QosPolicies,
+    pub(crate) goal_service: QosPolicies,
+    pub(crate) result_service: QosPolicies,
+    pub(crate) cancel_service: QosPolicies,
+    pub(crate) feedback_subscription: QosPolicies,
+    pub(crate) status_subscription: QosPolicies,
+}
+
+impl ActionClientQosPolicies {
+    /// Specify all policies.
+    pub fn new(
+        goal_service: QosPolicies,
+        result_service: QosPolicies,
+        cancel_service: QosPolicies,
+        feedback_subscription: QosPolicies,
+        status_subscription: QosPolicies,
+    ) -> Self {
+        ActionClientQosPolicies {
+            goal_service,
+            result_service,
+            cancel_service,
+            feedback_subscription,
+            status_subscription,
+        }
+    }
+
+    /// Use the same policies for all services and topics.
+    pub fn uniform(qos: QosPolicies) -> Self {
+        Self::new(qos.clone(), qos.clone(), qos.clone(), qos.clone(), qos)
+    }
+
+    pub fn with_goal_service(self, goal_service: QosPolicies) -> Self {
+        ActionClientQosPolicies {
+            goal_service,
+            ..self
+        }
+    }
+
+    pub fn with_result_service(self, result_service: QosPolicies) -> Self {
+        ActionClientQosPolicies {
+            result_service,
+            ..self
+        }
+    }
+
+    pub fn with_cancel_service(self, cancel_service: QosPolicies) -> Self {
+        ActionClientQosPolicies {
+            cancel_service,
+            ..self
+        }
+    }
+
+    pub fn with_feedback_subscription(self, feedback_subscription: QosPolicies) -> Self {
+        ActionClientQosPolicies {
+            feedback_subscription,
+            ..self
+        }
+    }
+
+    pub fn with_status_subscription(self, status_subscription: QosPolicies) -> Self {
+        ActionClientQosPolicies {
+            status_subscription,
+            ..self
+        }
+    }
+
+    pub fn goal_service(&self) -> &QosPolicies {
+        &self.goal_service
+    }
+
+    pub fn result_service(&self) -> &QosPolicies {
+        &self.result_service
+    }
+
+    pub fn cancel_service(&self) -> &QosPolicies {
+        &self.cancel_service
+    }
+
+    pub fn feedback_subscription(&self) -> &QosPolicies {
+        &self.feedback_subscription
+    }
+
+    pub fn status_subscription(&self) -> &QosPolicies {
+        &self.status_subscription
+    }
+}
+
+impl Default for ActionClientQosPolicies {
+    fn default() -> Self {
+        Self::new(
+            default_action_service_qos(),
+            default_action_service_qos(),
+            default_action_service_qos(),
+            default_feedback_qos(),
+            default_status_qos(),
+        )
+    }
 }
 
 /// Collection of QoS policies requires for an Action server
+///
+/// The [`Default`] is the same as for [`ActionClientQosPolicies`].
 #[derive(Debug, Clone)]
-#[expect(missing_docs)]
 pub struct ActionServerQosPolicies {
-    pub goal_service: QosPolicies,
-    pub result_service: QosPolicies,
-    pub cancel_service: QosPolicies,
-    pub feedback_publisher: QosPolicies,
-    pub status_publisher: QosPolicies,
+    pub(crate) goal_service: QosPolicies,
+    pub(crate) result_service: QosPolicies,
+    pub(crate) cancel_service: QosPolicies,
+    pub(crate) feedback_publisher: QosPolicies,
+    pub(crate) status_publisher: QosPolicies,
+}
+
+impl ActionServerQosPolicies {
+    /// Specify all policies.
+    pub fn new(
+        goal_service: QosPolicies,
+        result_service: QosPolicies,
+        cancel_service: QosPolicies,
+        feedback_publisher: QosPolicies,
+        status_publisher: QosPolicies,
+    ) -> Self {
+        ActionServerQosPolicies {
+            goal_service,
+            result_service,
+            cancel_service,
+            feedback_publisher,
+            status_publisher,
+        }
+    }
+
+    /// Use the same policies for all services and topics.
+    pub fn uniform(qos: QosPolicies) -> Self {
+        Self::new(qos.clone(), qos.clone(), qos.clone(), qos.clone(), qos)
+    }
+
+    pub fn with_goal_service(self, goal_service: QosPolicies) -> Self {
+        ActionServerQosPolicies {
+            goal_service,
+            ..self
+        }
+    }
+
+    pub fn with_result_service(self, result_service: QosPolicies) -> Self {
+        ActionServerQosPolicies {
+            result_service,
+            ..self
+        }
+    }
+
+    pub fn with_cancel_service(self, cancel_service: QosPolicies) -> Self {
+        ActionServerQosPolicies {
+            cancel_service,
+            ..self
+        }
+    }
+
+    pub fn with_feedback_publisher(self, feedback_publisher: QosPolicies) -> Self {
+        ActionServerQosPolicies {
+            feedback_publisher,
+            ..self
+        }
+    }
+
+    pub fn with_status_publisher(self, status_publisher: QosPolicies) -> Self {
+        ActionServerQosPolicies {
+            status_publisher,
+            ..self
+        }
+    }
+
+    pub fn goal_service(&self) -> &QosPolicies {
+        &self.goal_service
+    }
+
+    pub fn result_service(&self) -> &QosPolicies {
+        &self.result_service
+    }
+
+    pub fn cancel_service(&self) -> &QosPolicies {
+        &self.cancel_service
+    }
+
+    pub fn feedback_publisher(&self) -> &QosPolicies {
+        &self.feedback_publisher
+    }
+
+    pub fn status_publisher(&self) -> &QosPolicies {
+        &self.status_publisher
+    }
+}
+
+impl Default for ActionServerQosPolicies {
+    fn default() -> Self {
+        Self::new(
+            default_action_service_qos(),
+            default_action_service_qos(),
+            default_action_service_qos(),
+            default_feedback_qos(),
+            default_status_qos(),
+        )
+    }
+}
+
+// These follow rmw_qos_profile_services_default and
+// rcl_action_qos_profile_status_default in ROS 2.
+fn default_action_service_qos() -> QosPolicies {
+    QosPolicyBuilder::new()
+        .reliability(Reliability::Reliable {
+            max_blocking_time: rustdds::Duration::from_millis(100),
+        })
+        .durability(Durability::Volatile)
+        .history(History::KeepLast { depth: 10 })
+        .build()
+}
+
+fn default_feedback_qos() -> QosPolicies {
+    default_action_service_qos()
+}
+
+fn default_status_qos() -> QosPolicies {
+    QosPolicyBuilder::new()
+        .reliability(Reliability::Reliable {
+            max_blocking_time: rustdds::Duration::from_millis(100),
+        })
+        .durability(Durability::TransientLocal)
+        .history(History::KeepLast { depth: 1 })
+        .build()
 }
 
 /// A request message for the goal sending service.
@@ -187,6 +416,10 @@ pub struct FeedbackMessage<Feedback> {
 }
 impl<F: Message> Message for FeedbackMessage<F> {}
 
+// Upper limit for result responses received, but not yet claimed by
+// receive_result or async_request_result.
+const MAX_BUFFERED_RESULTS: usize = 256;
+
 /// An Action Client.
 pub struct ActionClient<A>
 where
@@ -208,6 +441,47 @@ where
     pub(crate) my_status_subscription: Subscription<goal::GoalStatusArray>,
 
     pub(crate) my_action_name: Name,
+
+    // Latest known state of each goal, according to received status arrays
+    pub(crate) goal_states: Mutex<BTreeMap<GoalId, GoalStatusEnum>>,
+
+    // Result responses that were received while waiting for another request's
+    // response. Several results may be requested at the same time, so these
+    // must not be discarded.
+    pub(crate) result_responses: Mutex<BTreeMap<RmwRequestId, GetResultResponse<A::ResultType>>>,
+
+    // Pending async_request_result calls, to hand over responses received by
+    // another call.
+    pub(crate) result_waiters:
+        Mutex<BTreeMap<RmwRequestId, async_channel::Sender<GetResultResponse<A::ResultType>>>>,
+
+    #[cfg(feature = "introspection")]
+    pub(crate) introspection: Option<ActionIntrospection<A>>,
+
+    pub(crate) call_tracer: Option<CallTracer>,
+}
+
+/// An event of a goal, from [`ActionClient::goal_events`].
+#[derive(Clone, Debug)]
+pub enum GoalEvent<F> {
+    /// Feedback from the Action Server.
+    Feedback(F),
+    /// The goal reached a new state.
+    StateChanged(GoalStatusEnum),
+    /// No feedback or status update has arrived for `elapsed`. The Action
+    /// Server has likely crashed.
+    Stalled { elapsed: Duration },
+}
+
+/// A change in the state of a goal, as observed by an [`ActionClient`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GoalStateTransition {
+    /// The goal that changed state.
+    pub goal_id: GoalId,
+    /// Previously known state, or `None` if the goal was not known before.
+    pub from: Option<GoalStatusEnum>,
+    /// New state.
+    pub to: GoalStatusEnum,
 }
 
 impl<A> ActionClient<A>
@@ -217,7 +491,7 @@ where
     A::ResultType: Message + Clone,
     A::FeedbackType: Message,
 {
-    /// Returns the Action name.
+    /// Returns the Action name, after remapping.
     pub fn name(&self) -> &Name {
         &self.my_action_name
     }
@@ -253,6 +527,26 @@ where
         &mut self.my_status_subscription
     }
 
+    /// Publish events about the requests and responses of this client.
+    ///
+    /// `None` disables introspection.
+    #[cfg(feature = "introspection")]
+    pub fn set_introspection(&mut self, introspection: Option<ActionIntrospection<A>>) {
+        self.introspection = introspection;
+    }
+
+    /// Returns the introspection settings, if enabled.
+    #[cfg(feature = "introspection")]
+    pub fn introspection(&mut self) -> Option<&mut ActionIntrospection<A>> {
+        self.introspection.as_mut()
+    }
+
+    // Copy of the request for introspection, taken only if needed.
+    #[cfg(feature = "introspection")]
+    fn copy_for_introspection<T: Clone>(&self, request: &T) -> Option<T> {
+        self.introspection.as_ref().map(|_| request.clone())
+    }
+
     /// Returns the IDs for both the Request and the Goal.
     ///
     /// The Request ID can be used to recognize the correct response from the
@@ -264,9 +558,18 @@ where
         <A as ActionTypes>::GoalType: 'static,
     {
         let goal_id = UUID::new_random();
-        self.my_goal_client
-            .send_request(SendGoalRequest { goal_id, goal })
-            .map(|req_id| (req_id, goal_id))
+        let request = SendGoalRequest { goal_id, goal };
+        #[cfg(feature = "introspection")]
+        let copy = self.copy_for_introspection(&request);
+        if let Some(tracer) = &self.call_tracer {
+            tracer.goal(CallEvent::GoalSent, goal_id);
+        }
+        let req_id = self.my_goal_client.send_request(request)?;
+        #[cfg(feature = "introspection")]
+        if let (Some(i), Some(request)) = (&self.introspection, copy) {
+            i.send_goal_request(ServiceEventType::RequestSent, req_id, &request);
+        }
+        Ok((req_id, goal_id))
     }
 
     /// Attempts to receive a response for the specified goal request.
@@ -286,7 +589,11 @@ where
                 Ok(Some((incoming_req_id, resp))) if incoming_req_id == req_id =>
                 // received the expected answer
                 {
-                    break Ok(Some(resp))
+                    #[cfg(feature = "introspection")]
+                    if let Some(i) = &self.introspection {
+                        i.send_goal_response(ServiceEventType::ResponseReceived, req_id, &resp);
+                    }
+                    break Ok(Some(resp));
                 }
                 Ok(Some((incoming_req_id, _resp))) => {
                     // got someone else's answer. Try again.
@@ -313,9 +620,26 @@ where
         <A as ActionTypes>::GoalType: 'static,
     {
         let goal_id = UUID::new_random();
-        let send_goal_response =
-            Client::async_call_service(&self.my_goal_client, SendGoalRequest { goal_id, goal })
-                .await?;
+        let request = SendGoalRequest { goal_id, goal };
+        #[cfg(feature = "introspection")]
+        let copy = self.copy_for_introspection(&request);
+        if let Some(tracer) = &self.call_tracer {
+            tracer.goal(CallEvent::GoalSent, goal_id);
+        }
+        let req_id = self.my_goal_client.async_send_request(request).await?;
+        #[cfg(feature = "introspection")]
+        if let (Some(i), Some(request)) = (&self.introspection, copy) {
+            i.send_goal_request(ServiceEventType::RequestSent, req_id, &request);
+        }
+        let send_goal_response = self.my_goal_client.async_receive_response(req_id).await?;
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.introspection {
+            i.send_goal_response(
+                ServiceEventType::ResponseReceived,
+                req_id,
+                &send_goal_response,
+            );
+        }
         Ok((goal_id, send_goal_response))
     }
 
@@ -331,12 +655,18 @@ where
     /// - If the goal ID is not zero and timestamp is not zero, cancel the goal with
     ///   the given ID and all goals accepted at or before the timestamp.
     fn cancel_goal_raw(&self, goal_id: GoalId, timestamp: Time) -> WriteResult<RmwRequestId, ()> {
-        let goal_info = GoalInfo {
-            goal_id,
-            stamp: timestamp,
+        let request = CancelGoalRequest {
+            goal_info: GoalInfo {
+                goal_id,
+                stamp: timestamp,
+            },
         };
-        self.my_cancel_client
-            .send_request(CancelGoalRequest { goal_info })
+        let req_id = self.my_cancel_client.send_request(request.clone())?;
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.introspection {
+            i.cancel_goal_request(ServiceEventType::RequestSent, req_id, &request);
+        }
+        Ok(req_id)
     }
 
     /// Cancels a goal with the given ID.
@@ -366,7 +696,15 @@ where
 
                 // we got the expected answer!
                 Some((incoming_req_id, resp)) if incoming_req_id == cancel_request_id => {
-                    break Ok(Some(resp))
+                    #[cfg(feature = "introspection")]
+                    if let Some(i) = &self.introspection {
+                        i.cancel_goal_response(
+                            ServiceEventType::ResponseReceived,
+                            cancel_request_id,
+                            &resp,
+                        );
+                    }
+                    break Ok(Some(resp));
                 }
 
                 // got someone else's answer. try again.
@@ -381,12 +719,28 @@ where
         goal_id: GoalId,
         timestamp: Time,
     ) -> impl Future<Output = Result<CancelGoalResponse, CallServiceError<()>>> + '_ {
-        let goal_info = GoalInfo {
-            goal_id,
-            stamp: timestamp,
+        let request = CancelGoalRequest {
+            goal_info: GoalInfo {
+                goal_id,
+                stamp: timestamp,
+            },
         };
-        self.my_cancel_client
-            .async_call_service(CancelGoalRequest { goal_info })
+        async move {
+            let req_id = self
+                .my_cancel_client
+                .async_send_request(request.clone())
+                .await?;
+            #[cfg(feature = "introspection")]
+            if let Some(i) = &self.introspection {
+                i.cancel_goal_request(ServiceEventType::RequestSent, req_id, &request);
+            }
+            let response = self.my_cancel_client.async_receive_response(req_id).await?;
+            #[cfg(feature = "introspection")]
+            if let Some(i) = &self.introspection {
+                i.cancel_goal_response(ServiceEventType::ResponseReceived, req_id, &response);
+            }
+            Ok(response)
+        }
     }
 
     /// Requests the Result for the goal with the given ID.
@@ -394,11 +748,24 @@ where
     where
         <A as ActionTypes>::ResultType: 'static,
     {
-        self.my_result_client
-            .send_request(GetResultRequest { goal_id })
+        let req_id = self
+            .my_result_client
+            .send_request(GetResultRequest { goal_id })?;
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.introspection {
+            i.get_result_request(
+                ServiceEventType::RequestSent,
+                req_id,
+                &GetResultRequest { goal_id },
+            );
+        }
+        Ok(req_id)
     }
 
     /// Attempts to receive the result for the specified request.
+    ///
+    /// Results for other requests received meanwhile are kept, so several
+    /// results can be requested at the same time.
     pub fn receive_result(
         &self,
         result_request_id: RmwRequestId,
@@ -406,8 +773,25 @@ where
     where
         <A as ActionTypes>::ResultType: 'static,
     {
+        if let Some(GetResultResponse { status, result }) = self
+            .result_responses
+            .lock()
+            .unwrap()
+            .remove(&result_request_id)
+        {
+            return Ok(Some((status, result)));
+        }
         loop {
-            match self.my_result_client.receive_response()? {
+            let received = self.my_result_client.receive_response()?;
+            #[cfg(feature = "introspection")]
+            if let (Some(i), Some((incoming_req_id, response))) = (&self.introspection, &received) {
+                i.get_result_response(
+                    ServiceEventType::ResponseReceived,
+                    *incoming_req_id,
+                    response,
+                );
+            }
+            match received {
                 // not yet
                 None => break Ok(None),
 
@@ -418,8 +802,10 @@ where
                     break Ok(Some((status, result)))
                 }
 
-                // got someone else's answer. try again.
-                Some(_) => continue,
+                // got someone else's answer. Keep it and try again.
+                Some((incoming_req_id, response)) => {
+                    self.route_result_response(incoming_req_id, response)
+                }
             }
         }
     }
@@ -429,6 +815,10 @@ where
     /// Result should be requested as soon as a goal is accepted, but will only
     /// be received when the Server informs that the goal has either Succeeded,
     /// or has been Canceled/Aborted.
+    ///
+    /// It is safe to request the results of several goals concurrently. A
+    /// response received by one call is handed over to the call that is waiting
+    /// for it.
     pub async fn async_request_result(
         &self,
         goal_id: GoalId,
@@ -436,13 +826,99 @@ where
     where
         <A as ActionTypes>::ResultType: 'static,
     {
-        let GetResultResponse { status, result } = self
+        let req_id = self
             .my_result_client
-            .async_call_service(GetResultRequest { goal_id })
+            .async_send_request(GetResultRequest { goal_id })
             .await?;
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.introspection {
+            i.get_result_request(
+                ServiceEventType::RequestSent,
+                req_id,
+                &GetResultRequest { goal_id },
+            );
+        }
+        let (sender, receiver) = async_channel::bounded(1);
+        self.result_waiters.lock().unwrap().insert(req_id, sender);
+        // Unregisters the waiter also if this future is dropped while waiting.
+        let _guard = AbandonResultOnDrop {
+            client: self,
+            request_id: req_id,
+        };
+
+        // The response may have been received by someone else before we
+        // registered, so check the buffer only after registering.
+        let buffered = self.result_responses.lock().unwrap().remove(&req_id);
+        let response = match buffered {
+            Some(response) => Ok(response),
+            None => self.wait_result_response(req_id, &receiver).await,
+        };
+
+        let GetResultResponse { status, result } = response?;
         Ok((status, result))
     }
 
+    async fn wait_result_response(
+        &self,
+        req_id: RmwRequestId,
+        receiver: &async_channel::Receiver<GetResultResponse<A::ResultType>>,
+    ) -> Result<GetResultResponse<A::ResultType>, CallServiceError<()>>
+    where
+        <A as ActionTypes>::ResultType: 'static,
+    {
+        loop {
+            let handed_over = receiver.recv();
+            let received = self.my_result_client.async_receive_any_response();
+            pin_mut!(handed_over, received);
+            match futures::future::select(handed_over, received).await {
+                futures::future::Either::Left((Ok(response), _)) => return Ok(response),
+                // Cannot happen, because the sender is in self.result_waiters
+                futures::future::Either::Left((Err(_), _)) => {}
+                futures::future::Either::Right((received, _)) => {
+                    let (incoming_req_id, response) = received?;
+                    #[cfg(feature = "introspection")]
+                    if let Some(i) = &self.introspection {
+                        i.get_result_response(
+                            ServiceEventType::ResponseReceived,
+                            incoming_req_id,
+                            &response,
+                        );
+                    }
+                    if incoming_req_id == req_id {
+                        return Ok(response);
+                    }
+                    self.route_result_response(incoming_req_id, response);
+                }
+            }
+        }
+    }
+
+    // Hand over a result response to the waiting async call, or store it for
+    // receive_result.
+    fn route_result_response(
+        &self,
+        req_id: RmwRequestId,
+        response: GetResultResponse<A::ResultType>,
+    ) {
+        log::debug!("Result response for another request {req_id:?}. Keeping it.");
+        let response = match self.result_waiters.lock().unwrap().remove(&req_id) {
+            Some(waiter) => match waiter.try_send(response) {
+                Ok(()) => return,
+                // The waiting call was dropped.
+                Err(e) => e.into_inner(),
+            },
+            None => response,
+        };
+        let mut result_responses = self.result_responses.lock().unwrap();
+        if result_responses.len() >= MAX_BUFFERED_RESULTS {
+            // Nobody seems to be asking for these. Drop the oldest one.
+            if let Some((dropped, _)) = result_responses.pop_first() {
+                log::warn!("Too many unclaimed result responses. Dropping {dropped:?}.");
+            }
+        }
+        result_responses.insert(req_id, response);
+    }
+
     /// Attempts to receive a Feedback message for the goal with the given ID.
     pub fn receive_feedback(&self, goal_id: GoalId) -> ReadResult<Option<A::FeedbackType>>
     where
@@ -493,6 +969,71 @@ where
             })
     }
 
+    /// Returns the latest known state of a goal.
+    ///
+    /// The state is tracked from the status arrays published by the Action
+    /// Server. The cache is updated whenever status is received through this
+    /// client, i.e. by any of the `receive_status`, `status_stream`, or
+    /// `goal_state_transitions` family of methods, or
+    /// [`update_goal_states`](Self::update_goal_states).
+    ///
+    /// Returns `None` if the goal is not (or no longer) listed by the server.
+    /// Servers drop goals from the status array some time after they have
+    /// reached a terminal state.
+    pub fn goal_state(&self, goal_id: GoalId) -> Option<GoalStatusEnum> {
+        self.goal_states.lock().unwrap().get(&goal_id).copied()
+    }
+
+    /// Takes all status messages received so far, updates the goal state
+    /// cache, and returns the observed transitions.
+    ///
+    /// This does not block.
+    pub fn update_goal_states(&self) -> ReadResult<Vec<GoalStateTransition>> {
+        let mut transitions = Vec::new();
+        while let Some((status_array, _)) = self.my_status_subscription.take()? {
+            transitions.extend(self.record_goal_states(&status_array));
+        }
+        Ok(transitions)
+    }
+
+    /// Async Stream of goal state transitions.
+    ///
+    /// This consumes status arrays from the status Subscription, updates the
+    /// goal state cache, and yields a [`GoalStateTransition`] for every goal
+    /// whose state changed.
+    pub fn goal_state_transitions(
+        &self,
+    ) -> impl FusedStream<Item = ReadResult<GoalStateTransition>> + '_ {
+        self.my_status_subscription
+            .async_stream()
+            .map(move |result| match result {
+                Err(e) => vec![Err(e)],
+                Ok((gsa, _mi)) => self.record_goal_states(&gsa).into_iter().map(Ok).collect(),
+            })
+            .flat_map(futures::stream::iter)
+    }
+
+    // Replaces the cache contents with the goals listed in `status_array`, and
+    // returns the transitions.
+    fn record_goal_states(&self, status_array: &goal::GoalStatusArray) -> Vec<GoalStateTransition> {
+        let mut goal_states = self.goal_states.lock().unwrap();
+        let mut previous = std::mem::take(&mut *goal_states);
+        let mut transitions = Vec::new();
+        for goal_status in &status_array.status_list {
+            let goal_id = goal_status.goal_info.goal_id;
+            let from = previous.remove(&goal_id);
+            if from != Some(goal_status.status) {
+                transitions.push(GoalStateTransition {
+                    goal_id,
+                    from,
+                    to: goal_status.status,
+                });
+            }
+            goal_states.insert(goal_id, goal_status.status);
+        }
+        transitions
+    }
+
     /// Attempts to receive the status of all Goals.
     ///
     /// Note that this doesn't take a Goal ID. Thus, it reports all Goal
@@ -506,7 +1047,12 @@ where
             .inspect_err(|e| {
                 tracing::error!("Action status subscription failed to deser. message. (see: {e})");
             })
-            .map(|res| res.map(|(status_array, _)| status_array))
+            .map(|res| {
+                res.map(|(status_array, _)| {
+                    self.record_goal_states(&status_array);
+                    status_array
+                })
+            })
     }
 
     /// Attempts to receive the status of all Goals, asynchronously.
@@ -521,6 +1067,7 @@ where
                     );
                 })?;
 
+        self.record_goal_states(&status_array);
         Ok(status_array)
     }
 
@@ -531,7 +1078,12 @@ where
     ) -> impl FusedStream<Item = ReadResult<goal::GoalStatusArray>> + '_ {
         self.my_status_subscription
             .async_stream()
-            .map(|result| result.map(|(gsa, _mi)| gsa))
+            .map(move |result| {
+                result.map(|(gsa, _mi)| {
+                    self.record_goal_states(&gsa);
+                    gsa
+                })
+            })
     }
 
     /// Returns the status stream for the specfied goal.
@@ -553,8 +1105,161 @@ where
                 }
             })
     }
+
+    /// Async Stream of feedback and state changes of a goal, with a watchdog.
+    ///
+    /// Yields [`GoalEvent::Stalled`] each time neither feedback nor a status
+    /// update for the goal has arrived for `stall_timeout`, counting from the
+    /// creation of the Stream. This usually means that the Action Server has
+    /// crashed or lost connectivity, so the caller may cancel the goal and
+    /// retry. The Stream goes on after a stall, and ends after the goal
+    /// reaches a terminal state.
+    ///
+    /// Servers typically publish status only when some goal changes state,
+    /// so `stall_timeout` should be longer than the interval of feedback
+    /// from the server.
+    ///
+    /// This consumes feedback and status of other goals, like
+    /// [`feedback_stream`](Self::feedback_stream) and
+    /// [`status_stream`](Self::status_stream).
+    pub fn goal_events(
+        &self,
+        goal_id: GoalId,
+        stall_timeout: Duration,
+    ) -> impl FusedStream<Item = ReadResult<GoalEvent<A::FeedbackType>>> + '_
+    where
+        <A as ActionTypes>::FeedbackType: 'static,
+    {
+        let feedback = self
+            .feedback_stream(goal_id)
+            .map(|result| result.map(GoalEvent::Feedback));
+        let status = self
+            .status_stream(goal_id)
+            .map(|result| result.map(|goal_status| GoalEvent::StateChanged(goal_status.status)));
+        watch_goal_events(futures::stream::select(feedback, status), stall_timeout)
+    }
 } // impl
 
+// Unregisters a pending async_request_result call when dropped, and abandons
+// the request so that a late response is not buffered.
+struct AbandonResultOnDrop<'a, A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone,
+    A::ResultType: Message + Clone + 'static,
+    A::FeedbackType: Message,
+{
+    client: &'a ActionClient<A>,
+    request_id: RmwRequestId,
+}
+
+impl<A> Drop for AbandonResultOnDrop<'_, A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone,
+    A::ResultType: Message + Clone + 'static,
+    A::FeedbackType: Message,
+{
+    fn drop(&mut self) {
+        let client = self.client;
+        client
+            .result_waiters
+            .lock()
+            .unwrap()
+            .remove(&self.request_id);
+        client
+            .result_responses
+            .lock()
+            .unwrap()
+            .remove(&self.request_id);
+        client.my_result_client.abandon_request(self.request_id);
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl<A> Readiness for ActionClient<A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone + 'static,
+    A::ResultType: Message + Clone + 'static,
+    A::FeedbackType: Message + 'static,
+{
+    fn register_waker(&self, waker: &Waker) {
+        self.my_goal_client.register_waker(waker);
+        self.my_cancel_client.register_waker(waker);
+        self.my_result_client.register_waker(waker);
+        self.my_feedback_subscription.register_waker(waker);
+        self.my_status_subscription.register_waker(waker);
+    }
+}
+
+// All the DDS readers are registered with the same token.
+#[cfg(feature = "no-default-runtime")]
+impl<A> mio_08::event::Source for ActionClient<A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone + 'static,
+    A::ResultType: Message + Clone + 'static,
+    A::FeedbackType: Message + 'static,
+{
+    fn register(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::register(&mut self.my_goal_client, registry, token, interests)?;
+        mio_08::event::Source::register(&mut self.my_cancel_client, registry, token, interests)?;
+        mio_08::event::Source::register(&mut self.my_result_client, registry, token, interests)?;
+        mio_08::event::Source::register(
+            &mut self.my_feedback_subscription,
+            registry,
+            token,
+            interests,
+        )?;
+        mio_08::event::Source::register(
+            &mut self.my_status_subscription,
+            registry,
+            token,
+            interests,
+        )?;
+        Ok(())
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::reregister(&mut self.my_goal_client, registry, token, interests)?;
+        mio_08::event::Source::reregister(&mut self.my_cancel_client, registry, token, interests)?;
+        mio_08::event::Source::reregister(&mut self.my_result_client, registry, token, interests)?;
+        mio_08::event::Source::reregister(
+            &mut self.my_feedback_subscription,
+            registry,
+            token,
+            interests,
+        )?;
+        mio_08::event::Source::reregister(
+            &mut self.my_status_subscription,
+            registry,
+            token,
+            interests,
+        )?;
+        Ok(())
+    }
+
+    fn deregister(&mut self, registry: &mio_08::Registry) -> io::Result<()> {
+        mio_08::event::Source::deregister(&mut self.my_goal_client, registry)?;
+        mio_08::event::Source::deregister(&mut self.my_cancel_client, registry)?;
+        mio_08::event::Source::deregister(&mut self.my_result_client, registry)?;
+        mio_08::event::Source::deregister(&mut self.my_feedback_subscription, registry)?;
+        mio_08::event::Source::deregister(&mut self.my_status_subscription, registry)?;
+        Ok(())
+    }
+}
+
 // Example topic names and types at DDS level:
 
 // rq/turtle1/rotate_absolute/_action/send_goalRequest :
@@ -598,6 +1303,11 @@ where
     pub(crate) my_status_publisher: Publisher<goal::GoalStatusArray>,
 
     pub(crate) my_action_name: Name,
+
+    #[cfg(feature = "introspection")]
+    pub(crate) introspection: Option<ActionIntrospection<A>>,
+
+    pub(crate) call_tracer: Option<CallTracer>,
 }
 
 impl<A> ActionServer<A>
@@ -607,7 +1317,7 @@ where
     A::ResultType: Message + Clone,
     A::FeedbackType: Message,
 {
-    /// Returns the Action name.
+    /// Returns the Action name, after remapping.
     pub fn name(&self) -> &Name {
         &self.my_action_name
     }
@@ -639,6 +1349,26 @@ where
     }
 
     /// Returns a mutable reference to the Status Publisher.
+    pub fn status_publisher(&mut self) -> &mut Publisher<goal::GoalStatusArray> {
+        &mut self.my_status_publisher
+    }
+
+    /// Publish events about the requests and responses of this server.
+    ///
+    /// `None` disables introspection.
+    #[cfg(feature = "introspection")]
+    pub fn set_introspection(&mut self, introspection: Option<ActionIntrospection<A>>) {
+        self.introspection = introspection;
+    }
+
+    /// Returns the introspection settings, if enabled.
+    #[cfg(feature = "introspection")]
+    pub fn introspection(&mut self) -> Option<&mut ActionIntrospection<A>> {
+        self.introspection.as_mut()
+    }
+
+    /// Returns a mutable reference to the Status Publisher.
+    #[deprecated(note = "Use `status_publisher` instead.")]
     pub fn my_status_publisher(&mut self) -> &mut Publisher<goal::GoalStatusArray> {
         &mut self.my_status_publisher
     }
@@ -648,7 +1378,12 @@ where
     where
         <A as ActionTypes>::GoalType: 'static,
     {
-        self.my_goal_server.receive_request()
+        let received = self.my_goal_server.receive_request()?;
+        #[cfg(feature = "introspection")]
+        if let (Some(i), Some((req_id, request))) = (&self.introspection, &received) {
+            i.send_goal_request(ServiceEventType::RequestReceived, *req_id, request);
+        }
+        Ok(received)
     }
 
     /// Send a response for the specified goal request
@@ -660,6 +1395,10 @@ where
     where
         <A as ActionTypes>::GoalType: 'static,
     {
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.introspection {
+            i.send_goal_response(ServiceEventType::ResponseSent, req_id, &resp);
+        }
         self.my_goal_server.send_response(req_id, resp)
     }
 
@@ -667,7 +1406,12 @@ where
     pub fn receive_cancel_request(
         &self,
     ) -> ReadResult<Option<(RmwRequestId, goal::CancelGoalRequest)>> {
-        self.my_cancel_server.receive_request()
+        let received = self.my_cancel_server.receive_request()?;
+        #[cfg(feature = "introspection")]
+        if let (Some(i), Some((req_id, request))) = (&self.introspection, &received) {
+            i.cancel_goal_request(ServiceEventType::RequestReceived, *req_id, request);
+        }
+        Ok(received)
     }
 
     /// Responds to a received cancel request by sending a cancel response.
@@ -676,6 +1420,10 @@ where
         req_id: RmwRequestId,
         resp: goal::CancelGoalResponse,
     ) -> WriteResult<(), ()> {
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.introspection {
+            i.cancel_goal_response(ServiceEventType::ResponseSent, req_id, &resp);
+        }
         self.my_cancel_server.send_response(req_id, resp)
     }
 
@@ -684,7 +1432,12 @@ where
     where
         <A as ActionTypes>::ResultType: 'static,
     {
-        self.my_result_server.receive_request()
+        let received = self.my_result_server.receive_request()?;
+        #[cfg(feature = "introspection")]
+        if let (Some(i), Some((req_id, request))) = (&self.introspection, &received) {
+            i.get_result_request(ServiceEventType::RequestReceived, *req_id, request);
+        }
+        Ok(received)
     }
 
     /// Send a result message to the Client.
@@ -696,6 +1449,10 @@ where
     where
         <A as ActionTypes>::ResultType: 'static,
     {
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.introspection {
+            i.get_result_response(ServiceEventType::ResponseSent, result_request_id, &resp);
+        }
         self.my_result_server.send_response(result_request_id, resp)
     }
 
@@ -718,6 +1475,62 @@ where
     }
 } // impl
 
+#[cfg(feature = "no-default-runtime")]
+impl<A> Readiness for ActionServer<A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone + 'static,
+    A::ResultType: Message + Clone + 'static,
+    A::FeedbackType: Message + 'static,
+{
+    fn register_waker(&self, waker: &Waker) {
+        self.my_goal_server.register_waker(waker);
+        self.my_cancel_server.register_waker(waker);
+        self.my_result_server.register_waker(waker);
+    }
+}
+
+// All the DDS readers are registered with the same token.
+#[cfg(feature = "no-default-runtime")]
+impl<A> mio_08::event::Source for ActionServer<A>
+where
+    A: ActionTypes,
+    A::GoalType: Message + Clone + 'static,
+    A::ResultType: Message + Clone + 'static,
+    A::FeedbackType: Message + 'static,
+{
+    fn register(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::register(&mut self.my_goal_server, registry, token, interests)?;
+        mio_08::event::Source::register(&mut self.my_cancel_server, registry, token, interests)?;
+        mio_08::event::Source::register(&mut self.my_result_server, registry, token, interests)?;
+        Ok(())
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::reregister(&mut self.my_goal_server, registry, token, interests)?;
+        mio_08::event::Source::reregister(&mut self.my_cancel_server, registry, token, interests)?;
+        mio_08::event::Source::reregister(&mut self.my_result_server, registry, token, interests)?;
+        Ok(())
+    }
+
+    fn deregister(&mut self, registry: &mio_08::Registry) -> io::Result<()> {
+        mio_08::event::Source::deregister(&mut self.my_goal_server, registry)?;
+        mio_08::event::Source::deregister(&mut self.my_cancel_server, registry)?;
+        mio_08::event::Source::deregister(&mut self.my_result_server, registry)?;
+        Ok(())
+    }
+}
+
 /// One of many handles to a goal.  
 pub trait GoalHandle {
     /// Returns the goal ID.
@@ -851,6 +1664,73 @@ where
     status: GoalStatusEnum,
     accepted_time: Option<builtin_interfaces::Time>,
     goal: A::GoalType,
+    last_feedback: Option<SteadyTime>,
+}
+
+// The events of a goal from `updates`, with duplicate states left out and
+// GoalEvent::Stalled added after `stall_timeout` without updates. Ends after
+// a terminal state.
+fn watch_goal_events<F>(
+    updates: impl Stream<Item = ReadResult<GoalEvent<F>>>,
+    stall_timeout: Duration,
+) -> impl FusedStream<Item = ReadResult<GoalEvent<F>>> {
+    let mut updates = Box::pin(updates);
+    let mut timer = Delay::new(stall_timeout);
+    let mut last_update = SteadyTime::now();
+    let mut last_state = None;
+    let mut finished = false;
+    futures::stream::poll_fn(move |cx| {
+        if finished {
+            return Poll::Ready(None);
+        }
+        loop {
+            match updates.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(event))) => {
+                    last_update = SteadyTime::now();
+                    timer.reset(stall_timeout);
+                    if let GoalEvent::StateChanged(state) = event {
+                        if last_state == Some(state) {
+                            // Still alive, but nothing new to report.
+                            continue;
+                        }
+                        last_state = Some(state);
+                        finished = matches!(
+                            state,
+                            GoalStatusEnum::Succeeded
+                                | GoalStatusEnum::Canceled
+                                | GoalStatusEnum::Aborted
+                        );
+                    }
+                    return Poll::Ready(Some(Ok(event)));
+                }
+                Poll::Ready(other) => return Poll::Ready(other),
+                Poll::Pending => break,
+            }
+        }
+        match timer.poll_unpin(cx) {
+            Poll::Ready(()) => {
+                timer.reset(stall_timeout);
+                Poll::Ready(Some(Ok(GoalEvent::Stalled {
+                    elapsed: last_update.elapsed().as_saturating_duration(),
+                })))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .fuse()
+}
+
+// Is it time to publish feedback of a goal, whose previous feedback was
+// published at `last_feedback`?
+fn feedback_due(
+    last_feedback: Option<SteadyTime>,
+    now: SteadyTime,
+    min_interval: Option<Duration>,
+) -> bool {
+    match (last_feedback, min_interval) {
+        (Some(last), Some(min_interval)) => (now - last).as_saturating_duration() >= min_interval,
+        _ => true,
+    }
 }
 
 /// An asynchronous Action Server.
@@ -864,6 +1744,7 @@ where
     actionserver: ActionServer<A>,
     goals: BTreeMap<GoalId, AsyncGoal<A>>,
     result_requests: BTreeMap<GoalId, RmwRequestId>,
+    min_feedback_interval: Option<Duration>,
 }
 
 impl<A> AsyncActionServer<A>
@@ -879,6 +1760,34 @@ where
             actionserver,
             goals: BTreeMap::new(),
             result_requests: BTreeMap::new(),
+            min_feedback_interval: None,
+        }
+    }
+
+    /// Creates a new [`Self`] that publishes feedback of each goal at most
+    /// `max_rate` times per second.
+    ///
+    /// [`Self::publish_feedback`] silently drops feedback that comes too soon
+    /// after the previous feedback of the same goal. This keeps a tight
+    /// execute loop from flooding the network with feedback.
+    pub fn with_max_feedback_rate(actionserver: ActionServer<A>, max_rate: f64) -> Self {
+        AsyncActionServer {
+            min_feedback_interval: Some(Duration::from_secs_f64(1.0 / max_rate)),
+            ..Self::new(actionserver)
+        }
+    }
+
+    /// Publish events about the requests and responses of this server.
+    ///
+    /// See [`ActionServer::set_introspection`].
+    #[cfg(feature = "introspection")]
+    pub fn set_introspection(&mut self, introspection: Option<ActionIntrospection<A>>) {
+        self.actionserver.set_introspection(introspection);
+    }
+
+    fn trace_goal(&self, goal_id: GoalId, event: CallEvent) {
+        if let Some(tracer) = &self.actionserver.call_tracer {
+            tracer.goal(event, goal_id);
         }
     }
 
@@ -899,12 +1808,17 @@ where
                 .my_goal_server
                 .async_receive_request()
                 .await?;
+            #[cfg(feature = "introspection")]
+            if let Some(i) = &self.actionserver.introspection {
+                i.send_goal_request(ServiceEventType::RequestReceived, req_id, &goal_request);
+            }
             match self.goals.entry(goal_request.goal_id) {
                 e @ Entry::Vacant(_) => {
                     e.or_insert(AsyncGoal {
                         status: GoalStatusEnum::Unknown,
                         goal: goal_request.goal,
                         accepted_time: None,
+                        last_feedback: None,
                     });
                     break (req_id, goal_request.goal_id);
                 }
@@ -947,8 +1861,9 @@ where
                     let mut_o = o.into_mut();
                     mut_o.status = GoalStatusEnum::Accepted;
                     mut_o.accepted_time = Some(now);
+                    self.trace_goal(handle.inner.goal_id, CallEvent::GoalAccepted);
                     self.publish_statuses().await;
-                    self.actionserver.my_goal_server.send_response(
+                    self.actionserver.send_goal_response(
                         handle.req_id,
                         SendGoalResponse {
                             accepted: true,
@@ -991,13 +1906,14 @@ where
                         status: GoalStatusEnum::Unknown,
                         ..
                     } => {
-                        self.actionserver.my_goal_server.send_response(
+                        self.actionserver.send_goal_response(
                             handle.req_id,
                             SendGoalResponse {
                                 accepted: false,
                                 stamp: builtin_interfaces::Time::now(),
                             },
                         )?;
+                        self.trace_goal(handle.inner.goal_id, CallEvent::GoalRejected);
                         //o.into_mut().0 = GoalStatusEnum::Rejected; -- there is no such state
                         //self.publish_statuses().await; -- this is not reported
                         Ok(())
@@ -1032,6 +1948,7 @@ where
                     ..
                 } => {
                     o.into_mut().status = GoalStatusEnum::Executing;
+                    self.trace_goal(handle.inner.goal_id, CallEvent::GoalExecuting);
                     self.publish_statuses().await;
                     Ok(ExecutingGoalHandle {
                         inner: handle.inner,
@@ -1053,6 +1970,9 @@ where
     }
 
     /// Publish feedback on how the execution is proceeding.
+    ///
+    /// If the server was created with [`Self::with_max_feedback_rate`],
+    /// feedback exceeding the rate is dropped.
     pub async fn publish_feedback(
         &mut self,
         handle: ExecutingGoalHandle<A::GoalType>,
@@ -1063,10 +1983,16 @@ where
             Entry::Occupied(o) => match o.get() {
                 AsyncGoal {
                     status: GoalStatusEnum::Executing,
+                    last_feedback,
                     ..
                 } => {
+                    let now = SteadyTime::now();
+                    if !feedback_due(*last_feedback, now, self.min_feedback_interval) {
+                        return Ok(());
+                    }
                     self.actionserver
                         .send_feedback(handle.inner.goal_id, feedback)?;
+                    o.into_mut().last_feedback = Some(now);
                     Ok(())
                 }
                 AsyncGoal {
@@ -1119,8 +2045,12 @@ where
                 pin_mut!(res_reqs);
                 loop {
                     // result request was not yet here. Keep receiving until we get it.
-                    let (req_id, GetResultRequest { goal_id }) =
-                        res_reqs.select_next_some().await?;
+                    let (req_id, request) = res_reqs.select_next_some().await?;
+                    #[cfg(feature = "introspection")]
+                    if let Some(i) = &self.actionserver.introspection {
+                        i.get_result_request(ServiceEventType::RequestReceived, req_id, &request);
+                    }
+                    let GetResultRequest { goal_id } = request;
                     if goal_id == handle.inner.goal_id {
                         break req_id;
                     } else {
@@ -1155,6 +2085,14 @@ where
                         ..
                     } => {
                         o.into_mut().status = result_status;
+                        self.trace_goal(
+                            handle.inner.goal_id,
+                            match result_status {
+                                GoalStatusEnum::Succeeded => CallEvent::GoalSucceeded,
+                                GoalStatusEnum::Canceled => CallEvent::GoalCanceled,
+                                _ => CallEvent::GoalAborted,
+                            },
+                        );
                         self.publish_statuses().await;
                         self.actionserver.send_result(
                             req_id,
@@ -1219,6 +2157,7 @@ where
                     ..
                 } => {
                     o.into_mut().status = GoalStatusEnum::Aborted;
+                    self.trace_goal(handle.goal_id, CallEvent::GoalAborted);
                     self.publish_statuses().await;
                     Ok(())
                 }
@@ -1240,12 +2179,50 @@ where
     /// The server should now respond either by accepting (some of) the
     /// cancel requests or rejecting all of them. The GoalIds that are requested
     /// to be cancelled can be currently at either accepted or executing state.
+    ///
+    /// Requests that name a single goal that is unknown or already in a
+    /// terminal state are answered automatically with
+    /// [`UnknownGoal`](goal::CancelGoalResponseEnum::UnknownGoal) or
+    /// [`GoalTerminated`](goal::CancelGoalResponseEnum::GoalTerminated), and
+    /// are not returned to the application.
     pub async fn receive_cancel_request(&self) -> ReadResult<CancelHandle> {
-        let (req_id, CancelGoalRequest { goal_info }) = self
-            .actionserver
-            .my_cancel_server
-            .async_receive_request()
-            .await?;
+        let (req_id, goal_info) = loop {
+            let (req_id, request) = self
+                .actionserver
+                .my_cancel_server
+                .async_receive_request()
+                .await?;
+            #[cfg(feature = "introspection")]
+            if let Some(i) = &self.actionserver.introspection {
+                i.cancel_goal_request(ServiceEventType::RequestReceived, req_id, &request);
+            }
+            let CancelGoalRequest { goal_info } = request;
+            match self.invalid_cancel_request_code(&goal_info) {
+                None => break (req_id, goal_info),
+                Some(return_code) => {
+                    log::debug!(
+                        "Cancel request for goal {:?}: {:?}",
+                        goal_info.goal_id,
+                        return_code
+                    );
+                    let response = goal::CancelGoalResponse {
+                        return_code,
+                        goals_canceling: Vec::new(),
+                    };
+                    #[cfg(feature = "introspection")]
+                    if let Some(i) = &self.actionserver.introspection {
+                        i.cancel_goal_response(ServiceEventType::ResponseSent, req_id, &response);
+                    }
+                    self.actionserver
+                        .my_cancel_server
+                        .async_send_response(req_id, response)
+                        .await
+                        .unwrap_or_else(|e| {
+                            log::error!("AsyncActionServer::receive_cancel_request: {e:?}")
+                        });
+                }
+            }
+        };
 
         #[allow(clippy::type_complexity)] // How would you refactor this type?
         let goal_filter: Box<dyn FnMut(&(&GoalId, &AsyncGoal<A>)) -> bool> = match goal_info {
@@ -1269,11 +2246,6 @@ where
             }),
         };
 
-        // TODO:
-        // Should check if the specified GoalId was unknown to us
-        // or already terminated.
-        // In those case outright send a negative response and not return to the
-        // application.
         let cancel_handle = CancelHandle {
             req_id,
             goals: self
@@ -1293,6 +2265,27 @@ where
         Ok(cancel_handle)
     }
 
+    // Return code for a cancel request that names only a single goal, and
+    // cannot be accepted. Requests that include goals by timestamp are left for
+    // the application, since they may match other goals.
+    fn invalid_cancel_request_code(
+        &self,
+        goal_info: &GoalInfo,
+    ) -> Option<goal::CancelGoalResponseEnum> {
+        if goal_info.goal_id == GoalId::ZERO || goal_info.stamp != Time::ZERO {
+            return None;
+        }
+        match self.goals.get(&goal_info.goal_id) {
+            None => Some(goal::CancelGoalResponseEnum::UnknownGoal),
+            Some(AsyncGoal {
+                status:
+                    GoalStatusEnum::Succeeded | GoalStatusEnum::Canceled | GoalStatusEnum::Aborted,
+                ..
+            }) => Some(goal::CancelGoalResponseEnum::GoalTerminated),
+            Some(_) => None,
+        }
+    }
+
     /// Respond to action client's cancel requests.
     /// The iterator of goals should list those GoalIds that will start canceling.
     /// For the other GoalIds, the cancel is not accepted and they do not change
@@ -1316,6 +2309,7 @@ where
             self.goals
                 .entry(goal_info.goal_id)
                 .and_modify(|gg| gg.status = GoalStatusEnum::Canceling);
+            self.trace_goal(goal_info.goal_id, CallEvent::GoalCanceling);
         }
         self.publish_statuses().await;
 
@@ -1328,6 +2322,14 @@ where
             goals_canceling: canceling_goals,
         };
 
+        #[cfg(feature = "introspection")]
+        if let Some(i) = &self.actionserver.introspection {
+            i.cancel_goal_response(
+                ServiceEventType::ResponseSent,
+                cancel_handle.req_id,
+                &response,
+            );
+        }
         self.actionserver
             .my_cancel_server
             .async_send_response(cancel_handle.req_id, response)
@@ -1371,3 +2373,138 @@ where
             .unwrap_or_else(|e| log::error!("AsyncActionServer::publish_statuses: {:?}", e));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use rustdds::{SequenceNumber, GUID};
+
+    use super::*;
+    use crate::{
+        interfaces::example_interfaces::{
+            fibonacci_action_type_name, FibonacciAction, FibonacciResult,
+        },
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+        service::ServiceMapping,
+        time::steady_time::TimeDiff,
+    };
+
+    #[test]
+    fn feedback_rate_limit() {
+        let t0 = SteadyTime::now();
+        let min_interval = Some(Duration::from_millis(100));
+
+        assert!(feedback_due(None, t0, min_interval));
+        assert!(!feedback_due(
+            Some(t0),
+            t0 + TimeDiff::from_millis(30),
+            min_interval
+        ));
+        assert!(feedback_due(
+            Some(t0),
+            t0 + TimeDiff::from_millis(100),
+            min_interval
+        ));
+        assert!(feedback_due(Some(t0), t0, None));
+    }
+
+    #[test]
+    fn abandoned_result_requests() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_result_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let client = node
+            .create_action_client::<FibonacciAction>(
+                ServiceMapping::Enhanced,
+                &Name::new("/rustdds", "test_result").unwrap(),
+                &fibonacci_action_type_name(),
+                ActionClientQosPolicies::default(),
+            )
+            .unwrap();
+
+        // A dropped call leaves no waiter behind.
+        assert!(client
+            .async_request_result(GoalId::new_random())
+            .now_or_never()
+            .is_none());
+        assert!(client.result_waiters.lock().unwrap().is_empty());
+        assert!(client.my_result_client.pending_requests().is_empty());
+
+        // Unclaimed responses are bounded.
+        for n in 0..=MAX_BUFFERED_RESULTS as i64 {
+            client.route_result_response(
+                RmwRequestId {
+                    writer_guid: GUID::from_bytes([1; 16]),
+                    sequence_number: SequenceNumber::from(n),
+                },
+                GetResultResponse {
+                    status: GoalStatusEnum::Succeeded,
+                    result: FibonacciResult { sequence: vec![] },
+                },
+            );
+        }
+        assert_eq!(
+            client.result_responses.lock().unwrap().len(),
+            MAX_BUFFERED_RESULTS
+        );
+    }
+
+    #[test]
+    fn goal_events_stall() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded::<ReadResult<GoalEvent<i32>>>();
+        let stall_timeout = Duration::from_millis(50);
+        let mut events = watch_goal_events(receiver, stall_timeout);
+
+        // Stalls repeatedly while the server is silent.
+        for _ in 0..2 {
+            match block_on(events.next()) {
+                Some(Ok(GoalEvent::Stalled { elapsed })) => assert!(elapsed >= stall_timeout),
+                other => panic!("Expected a stall, got {other:?}"),
+            }
+        }
+        sender.unbounded_send(Ok(GoalEvent::Feedback(1))).unwrap();
+        assert!(matches!(
+            block_on(events.next()),
+            Some(Ok(GoalEvent::Feedback(1)))
+        ));
+        assert!(!events.is_terminated());
+    }
+
+    #[test]
+    fn goal_events_finish() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        for event in [
+            GoalEvent::StateChanged(GoalStatusEnum::Executing),
+            GoalEvent::StateChanged(GoalStatusEnum::Executing),
+            GoalEvent::Feedback(1),
+            GoalEvent::StateChanged(GoalStatusEnum::Executing),
+            GoalEvent::StateChanged(GoalStatusEnum::Succeeded),
+            GoalEvent::Feedback(2),
+        ] {
+            sender.unbounded_send(Ok(event)).unwrap();
+        }
+        let events: Vec<GoalEvent<i32>> =
+            block_on(watch_goal_events(receiver, Duration::from_secs(10)).collect::<Vec<_>>())
+                .into_iter()
+                .map(Result::unwrap)
+                .collect();
+
+        // Repeated states are left out, and nothing follows the terminal state,
+        // even though the sender is still open.
+        assert!(matches!(
+            events[..],
+            [
+                GoalEvent::StateChanged(GoalStatusEnum::Executing),
+                GoalEvent::Feedback(1),
+                GoalEvent::StateChanged(GoalStatusEnum::Succeeded),
+            ]
+        ));
+        drop(sender);
+    }
+}
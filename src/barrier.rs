@@ -0,0 +1,219 @@
+//! Waiting for startup dependencies
+//!
+//! A Node often cannot do anything useful until some other parts of the
+//! system are up, e.g. a sensor driver is publishing, or a map server is
+//! available. [`wait_for`] waits until all given [`Requirement`]s are met, or
+//! a timeout expires, so that the dependencies can be stated explicitly
+//! instead of sleeping for a while.
+//!
+//! ```ignore
+//! let _spin_handle = node.spin_in_background()?;
+//! barrier::wait_for(
+//!     &mut node,
+//!     &[
+//!         Requirement::Publisher(Name::new("/", "scan")?),
+//!         Requirement::Service(Name::new("/", "map_server/map")?),
+//!         Requirement::TfFrame("base_link".to_owned()),
+//!     ],
+//!     Duration::from_secs(10),
+//! )
+//! .await?;
+//! ```
+
+use std::{collections::BTreeSet, fmt, thread, time::Duration};
+
+use futures::{channel::oneshot, pin_mut, FutureExt, StreamExt};
+use rustdds::{
+    dds::{CreateError, ReadResult},
+    policy::{Durability, History, Reliability},
+    QosPolicies, QosPolicyBuilder,
+};
+
+use crate::{
+    interfaces::tf2_msgs::TFMessage,
+    node::{context::DEFAULT_SUBSCRIPTION_QOS, pubsub::Subscription, Node},
+    prelude::Name,
+};
+
+/// Something that must be present before a Node can proceed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Requirement {
+    /// Some remote Publisher exists on the Topic.
+    Publisher(Name),
+    /// Some remote Subscription exists on the Topic.
+    Subscriber(Name),
+    /// A Server for the Service is available.
+    Service(Name),
+    /// The coordinate frame has been published on `/tf` or `/tf_static`,
+    /// either as a parent or a child frame.
+    TfFrame(String),
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Requirement::Publisher(n) => write!(f, "publisher on {n}"),
+            Requirement::Subscriber(n) => write!(f, "subscriber on {n}"),
+            Requirement::Service(n) => write!(f, "service {n}"),
+            Requirement::TfFrame(frame) => write!(f, "TF frame {frame:?}"),
+        }
+    }
+}
+
+/// Error from [`wait_for`]
+#[derive(Debug)]
+pub enum BarrierError {
+    /// The Node has no Spinner, so Discovery results would never arrive.
+    NoSpinner,
+    /// Creating the `/tf` or `/tf_static` Subscription failed.
+    Create(CreateError),
+    /// The timeout expired. These requirements were not met.
+    Timeout(Vec<Requirement>),
+}
+
+impl fmt::Display for BarrierError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BarrierError::NoSpinner => write!(f, "Node has no Spinner"),
+            BarrierError::Create(e) => write!(f, "Cannot subscribe to TF: {e}"),
+            BarrierError::Timeout(unmet) => {
+                write!(f, "Timed out waiting for: ")?;
+                for (i, r) in unmet.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{r}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for BarrierError {}
+
+impl From<CreateError> for BarrierError {
+    fn from(e: CreateError) -> Self {
+        BarrierError::Create(e)
+    }
+}
+
+/// Wait until all `requirements` are met, or `timeout` expires.
+///
+/// Topics and Services are found by Discovery, so the Node must have a
+/// [`Spinner`](crate::node::Spinner) running. If TF frames are required, this
+/// subscribes to `/tf` and `/tf_static` for the duration of the wait.
+pub async fn wait_for(
+    node: &mut Node,
+    requirements: &[Requirement],
+    timeout: Duration,
+) -> Result<(), BarrierError> {
+    if !node.have_spinner() {
+        return Err(BarrierError::NoSpinner);
+    }
+
+    let need_tf = requirements
+        .iter()
+        .any(|r| matches!(r, Requirement::TfFrame(_)));
+    let tf_subscriptions = if need_tf {
+        Some(TfSubscriptions::new(node)?)
+    } else {
+        None
+    };
+    let mut tf_frames = BTreeSet::new();
+
+    // Subscribe to events before checking, so that nothing is missed in
+    // between.
+    let mut status_events = Box::pin(node.status_receiver());
+
+    // There is no runtime-independent async timer, so a thread does the
+    // waiting.
+    let (timeout_sender, timeout_receiver) = oneshot::channel::<()>();
+    thread::spawn(move || {
+        thread::sleep(timeout);
+        let _ = timeout_sender.send(());
+    });
+    let timeout_receiver = timeout_receiver.fuse();
+    pin_mut!(timeout_receiver);
+
+    loop {
+        let unmet = unmet_requirements(node, requirements, &tf_frames);
+        if unmet.is_empty() {
+            return Ok(());
+        }
+
+        let tf_message = async {
+            match &tf_subscriptions {
+                Some(tf) => tf.next().await,
+                None => std::future::pending().await,
+            }
+        }
+        .fuse();
+        pin_mut!(tf_message);
+
+        futures::select! {
+            _ = status_events.select_next_some() => {}
+            tf_message = tf_message => {
+                if let Ok(tf_message) = tf_message {
+                    for t in tf_message.transforms {
+                        tf_frames.insert(t.header.frame_id);
+                        tf_frames.insert(t.child_frame_id);
+                    }
+                }
+            }
+            _ = timeout_receiver => return Err(BarrierError::Timeout(unmet)),
+        }
+    }
+}
+
+fn unmet_requirements(
+    node: &Node,
+    requirements: &[Requirement],
+    tf_frames: &BTreeSet<String>,
+) -> Vec<Requirement> {
+    requirements
+        .iter()
+        .filter(|r| match r {
+            Requirement::Publisher(topic) => node.count_publishers(topic) == 0,
+            Requirement::Subscriber(topic) => node.count_subscribers(topic) == 0,
+            Requirement::Service(service) => !node.service_is_available(service),
+            Requirement::TfFrame(frame) => !tf_frames.contains(frame),
+        })
+        .cloned()
+        .collect()
+}
+
+struct TfSubscriptions {
+    tf: Subscription<TFMessage>,
+    tf_static: Subscription<TFMessage>,
+}
+
+impl TfSubscriptions {
+    fn new(node: &mut Node) -> Result<Self, CreateError> {
+        // /tf_static is TransientLocal, so that late joiners get it.
+        let static_qos: QosPolicies = QosPolicyBuilder::new()
+            .durability(Durability::TransientLocal)
+            .reliability(Reliability::Reliable {
+                max_blocking_time: rustdds::Duration::from_millis(100),
+            })
+            .history(History::KeepLast { depth: 1 })
+            .build();
+        let tf_topic = node.create_topic_for::<TFMessage>(
+            &Name::new("/", "tf").unwrap(),
+            &DEFAULT_SUBSCRIPTION_QOS,
+        )?;
+        let tf_static_topic =
+            node.create_topic_for::<TFMessage>(&Name::new("/", "tf_static").unwrap(), &static_qos)?;
+        Ok(TfSubscriptions {
+            tf: node.create_subscription(&tf_topic, None)?,
+            tf_static: node.create_subscription(&tf_static_topic, None)?,
+        })
+    }
+
+    async fn next(&self) -> ReadResult<TFMessage> {
+        futures::select! {
+            r = self.tf.async_take().fuse() => r.map(|(m, _)| m),
+            r = self.tf_static.async_take().fuse() => r.map(|(m, _)| m),
+        }
+    }
+}
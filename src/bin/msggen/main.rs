@@ -48,12 +48,19 @@ fn main() -> io::Result<()> {
 
         let type_name = std::path::Path::new(input_file_name)
             .file_stem()
-            .ok_or(io::Error::new(
-                io::ErrorKind::Other,
-                "Input file did not have base name?",
-            ))?
+            .ok_or(io::Error::other("Input file did not have base name?"))?
             .to_string_lossy()
             .into_owned();
+        // The package is known if the file is in a "<package>/msg" directory.
+        let msg_dir = std::path::Path::new(input_file_name)
+            .canonicalize()?
+            .parent()
+            .map(PathBuf::from);
+        let package_name = msg_dir
+            .as_deref()
+            .filter(|dir| dir.file_name() == Some(OsStr::new("msg")))
+            .and_then(|dir| dir.parent()?.file_name())
+            .map(|name| name.to_string_lossy().into_owned());
 
         let input = io::read_to_string(input_file)?;
 
@@ -61,23 +68,30 @@ fn main() -> io::Result<()> {
 
         match arg_matches.get_one::<String>("output") {
             None => {
-                print_struct_definition(&mut io::stdout(), &type_name, &msg.1)?;
+                print_struct_definition(
+                    &mut io::stdout(),
+                    package_name.as_deref(),
+                    &type_name,
+                    &msg.1,
+                )?;
             }
             Some(out_file_name) => {
                 let mut out_file = fs::File::create(out_file_name)?;
-                print_struct_definition(&mut out_file, &type_name, &msg.1)?;
+                print_struct_definition(
+                    &mut out_file,
+                    package_name.as_deref(),
+                    &type_name,
+                    &msg.1,
+                )?;
             }
         }
     } else if let Some(ros2_types_requested) = arg_matches.get_many::<String>("type") {
         let output_dir = arg_matches
             .get_one::<String>("output")
-            .ok_or(io::Error::new(io::ErrorKind::Other, "Output dir required"))?;
+            .ok_or(io::Error::other("Output dir required"))?;
         let workspace_dir = arg_matches
             .get_one::<String>("workspace")
-            .ok_or(io::Error::new(
-                io::ErrorKind::Other,
-                "ROS 2 workspace dir required",
-            ))?;
+            .ok_or(io::Error::other("ROS 2 workspace dir required"))?;
 
         // Use colcon to determine what we need to translate
         let mut pkgs = Vec::new();
@@ -122,7 +136,7 @@ fn main() -> io::Result<()> {
                 let msg =
                     parser::msg_spec(type_def).unwrap_or_else(|e| panic!("Parse error: {:?}", e));
                 // TODO: msg.0 should be empty string here, warn if not.
-                print_struct_definition(&mut out_file, ros2type, &msg.1)?;
+                print_struct_definition(&mut out_file, Some(&pkg.name), ros2type, &msg.1)?;
             }
         }
     } else {
@@ -144,10 +158,9 @@ use std::{ffi::OsStr, path::PathBuf};
 use bstr::ByteSlice;
 
 fn list_packges_with_msgs(workspace_dir: &str, ros2_abs_type: &str) -> io::Result<Vec<RosPkg>> {
-    let (package_name, _type_name) = ros2_abs_type.rsplit_once('/').ok_or(io::Error::new(
-        io::ErrorKind::Other,
-        "Need package_name/type_name",
-    ))?;
+    let (package_name, _type_name) = ros2_abs_type
+        .rsplit_once('/')
+        .ok_or(io::Error::other("Need package_name/type_name"))?;
 
     let cwd = std::env::current_dir()?;
     std::env::set_current_dir(workspace_dir)?;
@@ -212,18 +225,17 @@ fn list_packges_with_msgs(workspace_dir: &str, ros2_abs_type: &str) -> io::Resul
         println!("Got {} packages", result.len());
         Ok(result)
     } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!(
-                "Colcon failure: {}\nHave you run local_setup.bash?",
-                String::from_utf8_lossy(&colcon_output.stderr)
-            ),
-        ))
+        Err(io::Error::other(format!(
+            "Colcon failure: {}\nHave you run local_setup.bash?",
+            String::from_utf8_lossy(&colcon_output.stderr)
+        )))
     }
 }
 
+// Without `package`, only Message is implemented, not NamedMessage.
 fn print_struct_definition<W: io::Write>(
     w: &mut W,
+    package: Option<&str>,
     name: &str,
     lines: &[(Option<Item>, Option<Comment>)],
 ) -> io::Result<()> {
@@ -232,10 +244,10 @@ fn print_struct_definition<W: io::Write>(
 
     let not_yet = lines
         .iter()
-        .take_while(|p| p.0.as_ref().map_or(true, is_not_field));
+        .take_while(|p| p.0.as_ref().is_none_or(is_not_field));
     let got_field = lines
         .iter()
-        .skip_while(|p| p.0.as_ref().map_or(true, is_not_field));
+        .skip_while(|p| p.0.as_ref().is_none_or(is_not_field));
 
     for (item, comment) in not_yet {
         match (item, comment) {
@@ -296,6 +308,13 @@ fn print_struct_definition<W: io::Write>(
         }
     }
     writeln!(w, "}}")?;
+    writeln!(w, "impl ros2_client::message::Message for {name} {{}}")?;
+    if let Some(package) = package {
+        writeln!(w, "impl ros2_client::message::NamedMessage for {name} {{")?;
+        writeln!(w, "  const PACKAGE_NAME: &'static str = \"{package}\";")?;
+        writeln!(w, "  const TYPE_NAME: &'static str = \"{name}\";")?;
+        writeln!(w, "}}")?;
+    }
     Ok(())
 }
 
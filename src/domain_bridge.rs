@@ -0,0 +1,529 @@
+//! Forwarding Topics and Services between DDS Domains
+//!
+//! A process may create several [`Context`]s, each on its own Domain Id, e.g.
+//! with [`ContextOptions::domain_id`](crate::prelude::ContextOptions::domain_id).
+//! A [`DomainBridge`] connects two of them: It forwards the messages of
+//! configured Topics from one Domain to the other, and makes configured
+//! Services of one Domain callable from the other. This is like the
+//! [`domain_bridge`](https://github.com/ros2/domain_bridge) package of ROS 2.
+//!
+//! Messages are forwarded in serialized form, so the bridge does not need
+//! the message types, only their names. For the same reason, only
+//! little-endian CDR can be forwarded. Big-endian messages are dropped.
+//!
+//! The bridge does no work on its own. Call [`DomainBridge::poll`]
+//! periodically, or whenever one of its Subscriptions has data.
+//!
+//! ```ignore
+//! let robot = Context::with_options(ContextOptions::new().domain_id(1))?;
+//! let fleet = Context::with_options(ContextOptions::new().domain_id(2))?;
+//! let mut bridge = DomainBridge::new(&robot, &fleet, "robot_bridge")?;
+//! bridge.bridge_topic(
+//!     &Name::parse("/odom")?,
+//!     MessageTypeName::new("nav_msgs", "Odometry"),
+//!     BridgeTopicOptions::new().max_rate(10.0),
+//! )?;
+//! bridge.bridge_service(
+//!     &Name::parse("/reset")?,
+//!     &ServiceTypeName::new("std_srvs", "Trigger"),
+//! )?;
+//! loop {
+//!     bridge.poll();
+//!     std::thread::sleep(Duration::from_millis(10));
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use log::{debug, warn};
+use rustdds::{
+    dds::{CreateError, CreateResult, ReadError},
+    no_key::{Decode, DefaultDecoder, DeserializerAdapter, SerializerAdapter},
+    QosPolicies, RepresentationIdentifier, GUID,
+};
+
+use crate::{
+    node::{
+        context::{Context, DEFAULT_PUBLISHER_QOS, DEFAULT_SERVICE_QOS},
+        pubsub::Subscription,
+        rate_limit::RateLimitedPublisher,
+        Node, NodeOptions,
+    },
+    prelude::{MessageTypeName, Name, NodeCreateError, NodeName, ServiceTypeName},
+    service::{
+        generic::{GenericClient, GenericServer, SerializedPayload},
+        request_id::RmwRequestId,
+    },
+};
+
+// Forwarded requests are forgotten if there is no response in this time.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How to forward a Topic
+#[derive(Clone, Debug, Default)]
+pub struct BridgeTopicOptions {
+    qos: Option<QosPolicies>,
+    max_rate: Option<f64>,
+    bidirectional: bool,
+}
+
+impl BridgeTopicOptions {
+    pub fn new() -> BridgeTopicOptions {
+        BridgeTopicOptions::default()
+    }
+
+    /// QoS of the bridge Subscription and Publisher. The default is
+    /// [`DEFAULT_PUBLISHER_QOS`].
+    pub fn qos(self, qos: QosPolicies) -> BridgeTopicOptions {
+        BridgeTopicOptions {
+            qos: Some(qos),
+            ..self
+        }
+    }
+
+    /// Forward at most `max_rate` messages per second. Messages in between
+    /// are dropped, except the latest, which is forwarded when due.
+    pub fn max_rate(self, max_rate: f64) -> BridgeTopicOptions {
+        BridgeTopicOptions {
+            max_rate: Some(max_rate),
+            ..self
+        }
+    }
+
+    /// Forward also in the reverse direction.
+    pub fn bidirectional(self, bidirectional: bool) -> BridgeTopicOptions {
+        BridgeTopicOptions {
+            bidirectional,
+            ..self
+        }
+    }
+}
+
+/// Forwards Topics and Services between two Contexts. See the
+/// [module documentation](self).
+pub struct DomainBridge {
+    from_node: Node,
+    to_node: Node,
+    topics: Vec<TopicForwarder>,
+    services: Vec<ServiceForwarder>,
+}
+
+impl DomainBridge {
+    /// Create a bridge from Context `from` to Context `to`.
+    ///
+    /// The bridge creates a Node called `name` in both Contexts.
+    pub fn new(from: &Context, to: &Context, name: &str) -> Result<DomainBridge, NodeCreateError> {
+        let node_name = NodeName::new("/", name).map_err(|e| CreateError::BadParameter {
+            reason: format!("Bridge name {name:?}: {e}"),
+        })?;
+        let node_options = || {
+            let options = NodeOptions::new().use_global_arguments(false);
+            #[cfg(feature = "rosout")]
+            let options = options.enable_rosout(false);
+            options
+        };
+        Ok(DomainBridge {
+            from_node: from.new_node(node_name.clone(), node_options())?,
+            to_node: to.new_node(node_name, node_options())?,
+            topics: Vec::new(),
+            services: Vec::new(),
+        })
+    }
+
+    /// Forward messages of Topic `topic_name` from the `from` Domain to the
+    /// `to` Domain.
+    pub fn bridge_topic(
+        &mut self,
+        topic_name: &Name,
+        type_name: MessageTypeName,
+        options: BridgeTopicOptions,
+    ) -> CreateResult<()> {
+        let forward = TopicForwarder::new(
+            &mut self.from_node,
+            &mut self.to_node,
+            topic_name,
+            &type_name,
+            &options,
+        )?;
+        if options.bidirectional {
+            let reverse = TopicForwarder::new(
+                &mut self.to_node,
+                &mut self.from_node,
+                topic_name,
+                &type_name,
+                &options,
+            )?;
+            // Do not send back what the other direction has forwarded.
+            let forward_writer = forward.publisher.publisher().guid();
+            let reverse_writer = reverse.publisher.publisher().guid();
+            self.topics.push(forward.ignoring(reverse_writer));
+            self.topics.push(reverse.ignoring(forward_writer));
+        } else {
+            self.topics.push(forward);
+        }
+        Ok(())
+    }
+
+    /// Make Service `service_name`, served in the `from` Domain, available in
+    /// the `to` Domain.
+    pub fn bridge_service(
+        &mut self,
+        service_name: &Name,
+        service_type_name: &ServiceTypeName,
+    ) -> CreateResult<()> {
+        let client = self.from_node.create_generic_client(
+            self.from_node.service_mapping_for(service_name),
+            service_name,
+            service_type_name,
+            DEFAULT_SERVICE_QOS.clone(),
+            DEFAULT_SERVICE_QOS.clone(),
+        )?;
+        let server = self.to_node.create_generic_server(
+            self.to_node.service_mapping_for(service_name),
+            service_name,
+            service_type_name,
+            DEFAULT_SERVICE_QOS.clone(),
+            DEFAULT_SERVICE_QOS.clone(),
+        )?;
+        self.services.push(ServiceForwarder {
+            client,
+            server,
+            pending: BTreeMap::new(),
+        });
+        Ok(())
+    }
+
+    /// Forward everything that has arrived. Returns the number of messages,
+    /// requests and responses forwarded.
+    pub fn poll(&mut self) -> usize {
+        let topics: usize = self.topics.iter().map(TopicForwarder::poll).sum();
+        let services: usize = self.services.iter_mut().map(ServiceForwarder::poll).sum();
+        topics + services
+    }
+}
+
+struct TopicForwarder {
+    topic_name: String,
+    subscription: Subscription<SerializedPayload, RawAdapter>,
+    publisher: RateLimitedPublisher<SerializedPayload, RawAdapter>,
+    ignored_writer: Option<GUID>,
+}
+
+impl TopicForwarder {
+    fn new(
+        from_node: &mut Node,
+        to_node: &mut Node,
+        topic_name: &Name,
+        type_name: &MessageTypeName,
+        options: &BridgeTopicOptions,
+    ) -> CreateResult<TopicForwarder> {
+        let qos = options.qos.as_ref().unwrap_or(&DEFAULT_PUBLISHER_QOS);
+        let from_topic = from_node.create_topic(topic_name, type_name.clone(), qos)?;
+        let to_topic = to_node.create_topic(topic_name, type_name.clone(), qos)?;
+        let subscription = from_node.create_subscription_with_adapter(&from_topic, None)?;
+        let publisher = to_node.create_publisher_with_adapter(&to_topic, None)?;
+        let publisher = match options.max_rate {
+            Some(rate) => RateLimitedPublisher::with_max_rate(publisher, rate),
+            None => RateLimitedPublisher::new(publisher, Duration::ZERO),
+        };
+        Ok(TopicForwarder {
+            topic_name: subscription.topic_name(),
+            subscription,
+            publisher,
+            ignored_writer: None,
+        })
+    }
+
+    fn ignoring(self, writer: GUID) -> TopicForwarder {
+        TopicForwarder {
+            ignored_writer: Some(writer),
+            ..self
+        }
+    }
+
+    fn poll(&self) -> usize {
+        let mut count = 0;
+        loop {
+            match self.subscription.take() {
+                Ok(Some((message, info))) => {
+                    if Some(info.writer_guid()) == self.ignored_writer {
+                        continue;
+                    }
+                    match self.publisher.publish(message) {
+                        Ok(true) => count += 1,
+                        Ok(false) => {} // rate limited
+                        Err(e) => warn!("Bridge {}: {e:?}", self.topic_name),
+                    }
+                }
+                Ok(None) => break,
+                // E.g. big-endian, which RawAdapter does not accept. The
+                // sample is consumed, so go on with the next one.
+                Err(ReadError::Deserialization { reason }) => {
+                    debug!("Bridge {}: dropping message: {reason}", self.topic_name);
+                }
+                Err(e) => {
+                    warn!("Bridge {}: {e:?}", self.topic_name);
+                    break;
+                }
+            }
+        }
+        match self.publisher.publish_pending() {
+            Ok(true) => count += 1,
+            Ok(false) => {}
+            Err(e) => warn!("Bridge {}: {e:?}", self.topic_name),
+        }
+        count
+    }
+}
+
+struct ServiceForwarder {
+    client: GenericClient,
+    server: GenericServer,
+    // Request id of the client -> request id of the server, and when sent
+    pending: BTreeMap<RmwRequestId, (RmwRequestId, Instant)>,
+}
+
+impl ServiceForwarder {
+    fn poll(&mut self) -> usize {
+        let mut count = 0;
+        loop {
+            match self.server.receive_request() {
+                Ok(Some((server_id, request))) => match self.client.send_request(request) {
+                    Ok(client_id) => {
+                        self.pending.insert(client_id, (server_id, Instant::now()));
+                        count += 1;
+                    }
+                    Err(e) => warn!("Bridge {}: {e:?}", self.client.service_name()),
+                },
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Bridge {}: {e:?}", self.server.service_name());
+                    break;
+                }
+            }
+        }
+        loop {
+            match self.client.receive_response() {
+                Ok(Some((client_id, response))) => {
+                    // Responses to others' requests are not ours to forward.
+                    let Some((server_id, _)) = self.pending.remove(&client_id) else {
+                        continue;
+                    };
+                    match self.server.send_response(server_id, response) {
+                        Ok(()) => count += 1,
+                        Err(e) => warn!("Bridge {}: {e:?}", self.server.service_name()),
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Bridge {}: {e:?}", self.client.service_name());
+                    break;
+                }
+            }
+        }
+        self.pending
+            .retain(|_, (_, sent)| sent.elapsed() < REQUEST_TIMEOUT);
+        count
+    }
+}
+
+// Passes serialized messages through unchanged.
+//
+// Only little-endian CDR is accepted, because the written representation
+// identifier cannot vary per sample, and byte-swapping would need the type.
+pub(crate) struct RawAdapter {}
+
+impl SerializerAdapter<SerializedPayload> for RawAdapter {
+    type Error = Infallible;
+
+    fn output_encoding() -> RepresentationIdentifier {
+        RepresentationIdentifier::CDR_LE
+    }
+
+    fn to_bytes(value: &SerializedPayload) -> Result<Bytes, Infallible> {
+        Ok(value.data.clone())
+    }
+}
+
+impl DeserializerAdapter<SerializedPayload> for RawAdapter {
+    type Error = Infallible;
+    type Decoded = SerializedPayload;
+
+    fn supported_encodings() -> &'static [RepresentationIdentifier] {
+        &[RepresentationIdentifier::CDR_LE]
+    }
+
+    fn transform_decoded(decoded: SerializedPayload) -> SerializedPayload {
+        decoded
+    }
+}
+
+impl DefaultDecoder<SerializedPayload> for RawAdapter {
+    type Decoder = RawDecoder;
+    const DECODER: RawDecoder = RawDecoder {
+        phantom: PhantomData,
+    };
+}
+
+#[derive(Clone)]
+pub(crate) struct RawDecoder {
+    phantom: PhantomData<()>,
+}
+
+impl Decode<SerializedPayload> for RawDecoder {
+    type Error = Infallible;
+
+    fn decode_bytes(
+        self,
+        input_bytes: &[u8],
+        encoding: RepresentationIdentifier,
+    ) -> Result<SerializedPayload, Infallible> {
+        Ok(SerializedPayload {
+            encoding,
+            data: Bytes::copy_from_slice(input_bytes),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interfaces::std_msgs::Header, prelude::ContextOptions};
+
+    #[test]
+    fn forwards_between_domains() {
+        let robot = Context::with_options(ContextOptions::new().domain_id(71)).unwrap();
+        let fleet = Context::with_options(ContextOptions::new().domain_id(72)).unwrap();
+        let mut bridge = DomainBridge::new(&robot, &fleet, "test_bridge").unwrap();
+        let topic_name = Name::new("/", "bridged").unwrap();
+        let type_name = MessageTypeName::new("std_msgs", "Header");
+        bridge
+            .bridge_topic(&topic_name, type_name.clone(), BridgeTopicOptions::new())
+            .unwrap();
+
+        let mut robot_node = robot
+            .new_node(
+                NodeName::new("/", "robot_node").unwrap(),
+                NodeOptions::new().enable_rosout(false),
+            )
+            .unwrap();
+        let topic = robot_node
+            .create_topic(&topic_name, type_name.clone(), &DEFAULT_PUBLISHER_QOS)
+            .unwrap();
+        let publisher = robot_node.create_publisher::<Header>(&topic, None).unwrap();
+
+        let mut fleet_node = fleet
+            .new_node(
+                NodeName::new("/", "fleet_node").unwrap(),
+                NodeOptions::new().enable_rosout(false),
+            )
+            .unwrap();
+        let topic = fleet_node
+            .create_topic(&topic_name, type_name, &DEFAULT_PUBLISHER_QOS)
+            .unwrap();
+        let subscription = fleet_node
+            .create_subscription::<Header>(&topic, None)
+            .unwrap();
+
+        let message = Header {
+            stamp: crate::interfaces::builtin_interfaces::Time::from_nanos(7),
+            frame_id: "odom".to_owned(),
+        };
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let received = loop {
+            publisher.publish(message.clone()).unwrap();
+            bridge.poll();
+            if let Some((received, _)) = subscription.take().unwrap() {
+                break received;
+            }
+            assert!(Instant::now() < deadline, "nothing forwarded");
+            std::thread::sleep(Duration::from_millis(50));
+        };
+        assert_eq!(received, message);
+    }
+
+    // Writes a Header as big-endian CDR
+    struct BigEndianHeader;
+
+    impl SerializerAdapter<Header> for BigEndianHeader {
+        type Error = Infallible;
+
+        fn output_encoding() -> RepresentationIdentifier {
+            RepresentationIdentifier::CDR_BE
+        }
+
+        fn to_bytes(value: &Header) -> Result<Bytes, Infallible> {
+            let nanos = value.stamp.to_nanos();
+            let mut bytes = Vec::new();
+            bytes.extend((nanos.div_euclid(1_000_000_000) as i32).to_be_bytes());
+            bytes.extend((nanos.rem_euclid(1_000_000_000) as u32).to_be_bytes());
+            bytes.extend((value.frame_id.len() as u32 + 1).to_be_bytes());
+            bytes.extend(value.frame_id.as_bytes());
+            bytes.push(0);
+            Ok(Bytes::from(bytes))
+        }
+    }
+
+    #[test]
+    fn drops_big_endian() {
+        let robot = Context::with_options(ContextOptions::new().domain_id(73)).unwrap();
+        let fleet = Context::with_options(ContextOptions::new().domain_id(74)).unwrap();
+        let mut bridge = DomainBridge::new(&robot, &fleet, "test_be_bridge").unwrap();
+        let topic_name = Name::new("/", "bridged_be").unwrap();
+        let type_name = MessageTypeName::new("std_msgs", "Header");
+        bridge
+            .bridge_topic(&topic_name, type_name.clone(), BridgeTopicOptions::new())
+            .unwrap();
+
+        let mut robot_node = robot
+            .new_node(
+                NodeName::new("/", "robot_be_node").unwrap(),
+                NodeOptions::new().enable_rosout(false),
+            )
+            .unwrap();
+        let topic = robot_node
+            .create_topic(&topic_name, type_name.clone(), &DEFAULT_PUBLISHER_QOS)
+            .unwrap();
+        let big_endian = robot_node
+            .create_publisher_with_adapter::<Header, BigEndianHeader>(&topic, None)
+            .unwrap();
+        let little_endian = robot_node.create_publisher::<Header>(&topic, None).unwrap();
+
+        let mut fleet_node = fleet
+            .new_node(
+                NodeName::new("/", "fleet_be_node").unwrap(),
+                NodeOptions::new().enable_rosout(false),
+            )
+            .unwrap();
+        let topic = fleet_node
+            .create_topic(&topic_name, type_name, &DEFAULT_PUBLISHER_QOS)
+            .unwrap();
+        let subscription = fleet_node
+            .create_subscription::<Header>(&topic, None)
+            .unwrap();
+
+        let header = |frame_id: &str| Header {
+            stamp: crate::interfaces::builtin_interfaces::Time::from_nanos(7),
+            frame_id: frame_id.to_owned(),
+        };
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            big_endian.publish(header("big")).unwrap();
+            little_endian.publish(header("little")).unwrap();
+            bridge.poll();
+            while let Some((message, _)) = subscription.take().unwrap() {
+                received.push(message);
+            }
+            assert!(Instant::now() < deadline, "nothing forwarded");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(received.iter().all(|m| *m == header("little")));
+    }
+}
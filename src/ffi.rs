@@ -0,0 +1,462 @@
+//! C API for embedding ros2-client in non-Rust applications
+//!
+//! This is a small alternative to `rcl` for programs that only need to
+//! publish and subscribe. Messages are passed as serialized buffers: the CDR
+//! little-endian encoding of the message, without the 4-byte encapsulation
+//! header. The C declarations are in `include/ros2_client.h`. Build the
+//! library with e.g.
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! A Node runs its Spinner in a background thread. Subscription callbacks are
+//! called from a background thread of each Subscription, one message at a
+//! time.
+//!
+//! Functions that fail return `NULL` or a negative value. The reason is then
+//! available from [`ros2_client_last_error`] on the same thread.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    fmt, ptr, slice, thread,
+};
+
+use bytes::Bytes;
+use futures::{executor, pin_mut, select, FutureExt, StreamExt};
+use log::warn;
+use rustdds::RepresentationIdentifier;
+
+use crate::{
+    domain_bridge::RawAdapter,
+    node::{
+        context::{Context, DEFAULT_PUBLISHER_QOS, DEFAULT_SUBSCRIPTION_QOS},
+        pubsub::{Publisher, Subscription},
+        spin_handle::SpinHandle,
+        Node, NodeOptions,
+    },
+    prelude::{MessageTypeName, Name, NodeName},
+    service::generic::SerializedPayload,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: impl fmt::Display) {
+    let message = CString::new(error.to_string().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+// Reports the error and returns NULL
+fn fail<T>(error: impl fmt::Display) -> *mut T {
+    set_last_error(error);
+    ptr::null_mut()
+}
+
+// Safety: `s` must be NULL or point to a NUL-terminated string.
+unsafe fn str_arg<'a>(s: *const c_char, what: &str) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err(format!("{what} is NULL"));
+    }
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| format!("{what} is not UTF-8: {e}"))
+}
+
+/// A ROS 2 Context, i.e. a DDS DomainParticipant
+pub struct ContextHandle {
+    context: Context,
+}
+
+/// A Node with its Spinner running
+pub struct NodeHandle {
+    // Dropped after the Spinner is stopped
+    node: Node,
+    spin_handle: Option<SpinHandle>,
+    context: Context,
+}
+
+/// A Publisher of serialized messages
+pub struct PublisherHandle {
+    publisher: Publisher<SerializedPayload, RawAdapter>,
+}
+
+/// A Subscription delivering serialized messages to a callback
+pub struct SubscriptionHandle {
+    stop_sender: async_channel::Sender<()>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// Called with each received message. The buffer is valid only during the
+/// call. It may have up to 3 bytes of padding after the message.
+pub type MessageCallback =
+    unsafe extern "C" fn(data: *const u8, len: usize, user_data: *mut c_void);
+
+// The callback and its argument, moved to the Subscription thread
+struct Callback {
+    function: MessageCallback,
+    user_data: *mut c_void,
+}
+
+// Safety: The caller of `ros2_client_subscription_new` promises that
+// `user_data` can be used from another thread.
+unsafe impl Send for Callback {}
+
+/// The reason of the latest failure on this thread, or NULL.
+///
+/// The string is valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn ros2_client_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Create a Context on the default DDS Domain. Returns NULL on failure.
+#[no_mangle]
+pub extern "C" fn ros2_client_context_new() -> *mut ContextHandle {
+    match Context::new() {
+        Ok(context) => Box::into_raw(Box::new(ContextHandle { context })),
+        Err(e) => fail(e),
+    }
+}
+
+/// Free a Context. The DomainParticipant is closed when its Nodes are also
+/// freed.
+///
+/// # Safety
+///
+/// `context` must be NULL or returned by [`ros2_client_context_new`], and
+/// not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_context_free(context: *mut ContextHandle) {
+    if !context.is_null() {
+        drop(unsafe { Box::from_raw(context) });
+    }
+}
+
+/// Create a Node and start its Spinner. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `context` must be a valid Context. `namespace` and `name` must be
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_node_new(
+    context: *const ContextHandle,
+    namespace: *const c_char,
+    name: *const c_char,
+) -> *mut NodeHandle {
+    let Some(context) = (unsafe { context.as_ref() }) else {
+        return fail("Context is NULL");
+    };
+    let node_name = match unsafe { (str_arg(namespace, "Namespace"), str_arg(name, "Name")) } {
+        (Ok(namespace), Ok(name)) => NodeName::new(namespace, name).map_err(|e| e.to_string()),
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    };
+    let node = node_name.and_then(|node_name| {
+        context
+            .context
+            .new_node(node_name, NodeOptions::new())
+            .map_err(|e| e.to_string())
+    });
+    let mut node = match node {
+        Ok(node) => node,
+        Err(e) => return fail(e),
+    };
+    match node.spin_in_background() {
+        Ok(spin_handle) => Box::into_raw(Box::new(NodeHandle {
+            node,
+            spin_handle: Some(spin_handle),
+            context: context.context.clone(),
+        })),
+        Err(e) => fail(e),
+    }
+}
+
+/// Stop the Spinner and free the Node.
+///
+/// # Safety
+///
+/// `node` must be NULL or returned by [`ros2_client_node_new`], and not
+/// freed already.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_node_free(node: *mut NodeHandle) {
+    if node.is_null() {
+        return;
+    }
+    let mut node = unsafe { Box::from_raw(node) };
+    if let Some(spin_handle) = node.spin_handle.take() {
+        spin_handle.stop();
+        if let Err(e) = spin_handle.join() {
+            warn!("Spinner of {}: {e}", node.node.fully_qualified_name());
+        }
+    }
+}
+
+// Topic of the given name and type, e.g. "/chatter" and "std_msgs/msg/String"
+unsafe fn topic_args<'a>(
+    node: *mut NodeHandle,
+    topic_name: *const c_char,
+    type_name: *const c_char,
+) -> Result<(&'a mut NodeHandle, Name, MessageTypeName), String> {
+    let node = unsafe { node.as_mut() }.ok_or("Node is NULL")?;
+    let topic_name = unsafe { str_arg(topic_name, "Topic name") }?;
+    let type_name = unsafe { str_arg(type_name, "Type name") }?;
+    let name = Name::parse(topic_name).map_err(|e| format!("Topic name {topic_name:?}: {e}"))?;
+    let type_name = type_name
+        .parse()
+        .map_err(|e| format!("Type name {type_name:?}: {e}"))?;
+    Ok((node, name, type_name))
+}
+
+/// Create a Publisher. `type_name` is e.g. `"std_msgs/msg/String"`. Returns
+/// NULL on failure.
+///
+/// # Safety
+///
+/// `node` must be a valid Node. `topic_name` and `type_name` must be
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_publisher_new(
+    node: *mut NodeHandle,
+    topic_name: *const c_char,
+    type_name: *const c_char,
+) -> *mut PublisherHandle {
+    let (node, name, type_name) = match unsafe { topic_args(node, topic_name, type_name) } {
+        Ok(args) => args,
+        Err(e) => return fail(e),
+    };
+    let publisher = node
+        .node
+        .create_topic(&name, type_name, &DEFAULT_PUBLISHER_QOS)
+        .and_then(|topic| node.node.create_publisher_with_adapter(&topic, None));
+    match publisher {
+        Ok(publisher) => Box::into_raw(Box::new(PublisherHandle { publisher })),
+        Err(e) => fail(e),
+    }
+}
+
+/// Publish a serialized message. Returns 0 on success and -1 on failure.
+///
+/// # Safety
+///
+/// `publisher` must be a valid Publisher, and `data` must point to `len`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_publish(
+    publisher: *const PublisherHandle,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    let Some(publisher) = (unsafe { publisher.as_ref() }) else {
+        set_last_error("Publisher is NULL");
+        return -1;
+    };
+    if data.is_null() && len > 0 {
+        set_last_error("Data is NULL");
+        return -1;
+    }
+    let data = if len == 0 {
+        Bytes::new()
+    } else {
+        Bytes::copy_from_slice(unsafe { slice::from_raw_parts(data, len) })
+    };
+    let payload = SerializedPayload {
+        encoding: RepresentationIdentifier::CDR_LE,
+        data,
+    };
+    match publisher.publisher.publish(payload) {
+        Ok(()) => 0,
+        Err(e) => {
+            set_last_error(format!("{e:?}"));
+            -1
+        }
+    }
+}
+
+/// Free a Publisher.
+///
+/// # Safety
+///
+/// `publisher` must be NULL or returned by [`ros2_client_publisher_new`],
+/// and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_publisher_free(publisher: *mut PublisherHandle) {
+    if !publisher.is_null() {
+        drop(unsafe { Box::from_raw(publisher) });
+    }
+}
+
+/// Create a Subscription that calls `callback` with each received message
+/// and `user_data`. Returns NULL on failure.
+///
+/// The callback is called from a background thread. Messages that are not
+/// little-endian CDR are dropped with a warning.
+///
+/// # Safety
+///
+/// `node` must be a valid Node. `topic_name` and `type_name` must be
+/// NUL-terminated strings. `callback` must be NULL or safe to call from
+/// another thread with `user_data`, until the Subscription is freed.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_subscription_new(
+    node: *mut NodeHandle,
+    topic_name: *const c_char,
+    type_name: *const c_char,
+    callback: Option<MessageCallback>,
+    user_data: *mut c_void,
+) -> *mut SubscriptionHandle {
+    let Some(callback) = callback else {
+        return fail("Callback is NULL");
+    };
+    let (node, name, type_name) = match unsafe { topic_args(node, topic_name, type_name) } {
+        Ok(args) => args,
+        Err(e) => return fail(e),
+    };
+    let subscription: Subscription<SerializedPayload, RawAdapter> = match node
+        .node
+        .create_topic(&name, type_name, &DEFAULT_SUBSCRIPTION_QOS)
+        .and_then(|topic| node.node.create_subscription_with_adapter(&topic, None))
+    {
+        Ok(subscription) => subscription,
+        Err(e) => return fail(e),
+    };
+    let thread_reservation = match node.context.reserve_thread() {
+        Ok(reservation) => reservation,
+        Err(e) => return fail(e),
+    };
+    let callback = Callback {
+        function: callback,
+        user_data,
+    };
+    let (stop_sender, stop_receiver) = async_channel::bounded(1);
+    let spawned = thread::Builder::new()
+        .name(format!("subscription {}", subscription.topic_name()))
+        .spawn(move || {
+            let _thread_reservation = thread_reservation;
+            let callback = callback;
+            executor::block_on(async {
+                let messages = subscription.async_stream();
+                let stop = stop_receiver.recv().fuse();
+                pin_mut!(messages, stop);
+                loop {
+                    select! {
+                        message = messages.select_next_some() => match message {
+                            // Safety: promised by the caller of
+                            // ros2_client_subscription_new
+                            Ok((payload, _info)) => unsafe {
+                                (callback.function)(
+                                    payload.data.as_ptr(),
+                                    payload.data.len(),
+                                    callback.user_data,
+                                );
+                            },
+                            // Includes other encodings than CDR_LE, which
+                            // RawAdapter does not accept.
+                            Err(e) => warn!("Subscription {}: {e}", subscription.topic_name()),
+                        },
+                        _ = stop => break,
+                    }
+                }
+            });
+        });
+    match spawned {
+        Ok(thread) => Box::into_raw(Box::new(SubscriptionHandle {
+            stop_sender,
+            thread: Some(thread),
+        })),
+        Err(e) => fail(format!("Cannot start Subscription thread: {e}")),
+    }
+}
+
+/// Free a Subscription. When this returns, the callback is no longer
+/// running, and is not called again.
+///
+/// # Safety
+///
+/// `subscription` must be NULL or returned by
+/// [`ros2_client_subscription_new`], and not freed already. This must not be
+/// called from the callback.
+#[no_mangle]
+pub unsafe extern "C" fn ros2_client_subscription_free(subscription: *mut SubscriptionHandle) {
+    if subscription.is_null() {
+        return;
+    }
+    let mut subscription = unsafe { Box::from_raw(subscription) };
+    let _ = subscription.stop_sender.try_send(());
+    if let Some(thread) = subscription.thread.take() {
+        let _ = thread.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::*;
+
+    unsafe extern "C" fn collect(data: *const u8, len: usize, user_data: *mut c_void) {
+        let received = unsafe { &*(user_data as *const Mutex<Vec<Vec<u8>>>) };
+        let data = unsafe { slice::from_raw_parts(data, len) };
+        received.lock().unwrap().push(data.to_vec());
+    }
+
+    #[test]
+    fn publish_and_subscribe() {
+        let received = Arc::new(Mutex::new(Vec::<Vec<u8>>::new()));
+        // std_msgs/String "abc". Received messages may have padding at the
+        // end, so this is a multiple of 4 bytes long.
+        let message = [4, 0, 0, 0, b'a', b'b', b'c', 0];
+        unsafe {
+            let context = ros2_client_context_new();
+            assert!(!context.is_null());
+            let node = ros2_client_node_new(context, c"/".as_ptr(), c"ffi_test_node".as_ptr());
+            assert!(!node.is_null());
+            let bad = ros2_client_publisher_new(node, c"/ffi test".as_ptr(), c"x".as_ptr());
+            assert!(bad.is_null());
+            assert!(!ros2_client_last_error().is_null());
+
+            let type_name = c"std_msgs/msg/String".as_ptr();
+            let publisher = ros2_client_publisher_new(node, c"/ffi_test".as_ptr(), type_name);
+            assert!(!publisher.is_null());
+            let subscription = ros2_client_subscription_new(
+                node,
+                c"/ffi_test".as_ptr(),
+                type_name,
+                Some(collect),
+                Arc::as_ptr(&received) as *mut c_void,
+            );
+            assert!(!subscription.is_null());
+            let no_callback = ros2_client_subscription_new(
+                node,
+                c"/ffi_test".as_ptr(),
+                type_name,
+                None,
+                ptr::null_mut(),
+            );
+            assert!(no_callback.is_null());
+            assert!(!ros2_client_last_error().is_null());
+
+            // The endpoints may not be matched yet.
+            for _ in 0..50 {
+                assert_eq!(
+                    ros2_client_publish(publisher, message.as_ptr(), message.len()),
+                    0
+                );
+                thread::sleep(Duration::from_millis(100));
+                if !received.lock().unwrap().is_empty() {
+                    break;
+                }
+            }
+            ros2_client_subscription_free(subscription);
+            ros2_client_publisher_free(publisher);
+            ros2_client_node_free(node);
+            ros2_client_context_free(context);
+        }
+        assert_eq!(received.lock().unwrap().first(), Some(&message.to_vec()));
+    }
+}
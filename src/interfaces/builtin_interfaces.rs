@@ -27,7 +27,7 @@
 use log::{error, warn};
 use serde::{Deserialize, Serialize};
 
-use crate::{message::Message, prelude::ROSTime};
+use crate::{message::Message, prelude::ROSTime, time::ros_time::OutOfRangeError};
 
 /// Over-the wire representation of a timestamp.
 ///
@@ -180,9 +180,8 @@ mod repr {
     impl Message for Time {}
 }
 
-// NOTE:
-// This may panic, if the source ROSTime is unreasonably far in the past or
-// future. If this is not ok, then TryFrom should be implemented and used.
+// Both are i64 nanoseconds, so this is lossless. Note that serialization
+// saturates seconds to i32, see `repr::Time` conversion above.
 impl From<ROSTime> for Time {
     fn from(rt: ROSTime) -> Time {
         Time::from_nanos(rt.to_nanos())
@@ -195,6 +194,38 @@ impl From<Time> for ROSTime {
     }
 }
 
+// The remaining conversions go through ROSTime, which has the same range.
+
+impl From<Time> for chrono::DateTime<chrono::Utc> {
+    fn from(t: Time) -> chrono::DateTime<chrono::Utc> {
+        ROSTime::from(t).into()
+    }
+}
+
+impl TryFrom<chrono::DateTime<chrono::Utc>> for Time {
+    type Error = OutOfRangeError;
+
+    fn try_from(t: chrono::DateTime<chrono::Utc>) -> Result<Time, OutOfRangeError> {
+        ROSTime::try_from(t).map(Time::from)
+    }
+}
+
+impl TryFrom<Time> for std::time::SystemTime {
+    type Error = OutOfRangeError;
+
+    fn try_from(t: Time) -> Result<std::time::SystemTime, OutOfRangeError> {
+        ROSTime::from(t).try_into()
+    }
+}
+
+impl TryFrom<std::time::SystemTime> for Time {
+    type Error = OutOfRangeError;
+
+    fn try_from(t: std::time::SystemTime) -> Result<Time, OutOfRangeError> {
+        ROSTime::try_from(t).map(Time::from)
+    }
+}
+
 // TODO: Implement constructors and conversions to/from usual Rust time formats
 // Note that this type does not specify a zero point in time.
 
@@ -0,0 +1,71 @@
+//! Defines message types `DiagnosticArray`, `DiagnosticStatus` and `KeyValue`.
+//! See [diagnostic_msgs](https://index.ros.org/p/diagnostic_msgs/)
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interfaces::std_msgs::Header,
+    message::{Message, NamedMessage},
+};
+
+/// From [DiagnosticArray](https://github.com/ros2/common_interfaces/blob/rolling/diagnostic_msgs/msg/DiagnosticArray.msg)
+///
+/// Usually published on Topic `/diagnostics`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticArray {
+    pub header: Header,
+    pub status: Vec<DiagnosticStatus>,
+}
+impl Message for DiagnosticArray {}
+impl NamedMessage for DiagnosticArray {
+    const PACKAGE_NAME: &'static str = "diagnostic_msgs";
+    const TYPE_NAME: &'static str = "DiagnosticArray";
+}
+
+/// From [DiagnosticStatus](https://github.com/ros2/common_interfaces/blob/rolling/diagnostic_msgs/msg/DiagnosticStatus.msg)
+///
+/// Status of an individual component of the robot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticStatus {
+    /// One of the level constants below
+    pub level: u8,
+    /// Description of the test or component reporting
+    pub name: String,
+    pub message: String,
+    /// Hardware unique string
+    pub hardware_id: String,
+    pub values: Vec<KeyValue>,
+}
+impl Message for DiagnosticStatus {}
+impl NamedMessage for DiagnosticStatus {
+    const PACKAGE_NAME: &'static str = "diagnostic_msgs";
+    const TYPE_NAME: &'static str = "DiagnosticStatus";
+}
+
+impl DiagnosticStatus {
+    pub const OK: u8 = 0;
+    pub const WARN: u8 = 1;
+    pub const ERROR: u8 = 2;
+    pub const STALE: u8 = 3;
+}
+
+/// From [KeyValue](https://github.com/ros2/common_interfaces/blob/rolling/diagnostic_msgs/msg/KeyValue.msg)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: String,
+}
+impl Message for KeyValue {}
+impl NamedMessage for KeyValue {
+    const PACKAGE_NAME: &'static str = "diagnostic_msgs";
+    const TYPE_NAME: &'static str = "KeyValue";
+}
+
+impl KeyValue {
+    pub fn new(key: &str, value: String) -> KeyValue {
+        KeyValue {
+            key: key.to_owned(),
+            value,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Defines Service type `AddTwoInts` and Action type `Fibonacci`. See
+//! [example_interfaces](https://index.ros.org/p/example_interfaces/)
+//!
+//! These are the types of the ROS 2 tutorials and demo programs, e.g.
+//! `ros2 run demo_nodes_cpp add_two_ints_server` and
+//! `ros2 run examples_rclpy_minimal_action_server server`. The Fibonacci
+//! Action of `test_msgs` has the same definition.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "actions")]
+use crate::{action::Action, prelude::ActionTypeName};
+use crate::{
+    message::Message,
+    service::{NamedService, Service},
+};
+
+/// From [AddTwoInts](https://github.com/ros2/example_interfaces/blob/rolling/srv/AddTwoInts.srv)
+pub struct AddTwoInts;
+
+impl Service for AddTwoInts {
+    type Request = AddTwoIntsRequest;
+    type Response = AddTwoIntsResponse;
+
+    fn request_type_name(&self) -> &str {
+        "AddTwoIntsRequest"
+    }
+
+    fn response_type_name(&self) -> &str {
+        "AddTwoIntsResponse"
+    }
+}
+
+impl NamedService for AddTwoInts {
+    const PACKAGE_NAME: &'static str = "example_interfaces";
+    const TYPE_NAME: &'static str = "AddTwoInts";
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddTwoIntsRequest {
+    pub a: i64,
+    pub b: i64,
+}
+impl Message for AddTwoIntsRequest {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddTwoIntsResponse {
+    pub sum: i64,
+}
+impl Message for AddTwoIntsResponse {}
+
+/// From [Fibonacci](https://github.com/ros2/example_interfaces/blob/rolling/action/Fibonacci.action)
+///
+/// Create the Action Client or Server with [`fibonacci_action_type_name`].
+#[cfg(feature = "actions")]
+pub type FibonacciAction = Action<FibonacciGoal, FibonacciResult, FibonacciFeedback>;
+
+#[cfg(feature = "actions")]
+pub fn fibonacci_action_type_name() -> ActionTypeName {
+    ActionTypeName::new("example_interfaces", "Fibonacci")
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FibonacciGoal {
+    pub order: i32,
+}
+impl Message for FibonacciGoal {}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FibonacciResult {
+    pub sequence: Vec<i32>,
+}
+impl Message for FibonacciResult {}
+
+/// The sequence computed so far
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FibonacciFeedback {
+    pub sequence: Vec<i32>,
+}
+impl Message for FibonacciFeedback {}
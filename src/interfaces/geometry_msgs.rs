@@ -0,0 +1,99 @@
+//! Defines message types for coordinate transforms. See [geometry_msgs](https://index.ros.org/p/geometry_msgs/)
+//!
+//! Only the types needed to publish transforms are defined here.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interfaces::std_msgs::Header,
+    message::{Message, NamedMessage},
+};
+
+/// From [Vector3](https://github.com/ros2/common_interfaces/blob/rolling/geometry_msgs/msg/Vector3.msg)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vector3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+impl Message for Vector3 {}
+impl NamedMessage for Vector3 {
+    const PACKAGE_NAME: &'static str = "geometry_msgs";
+    const TYPE_NAME: &'static str = "Vector3";
+}
+
+/// From [Quaternion](https://github.com/ros2/common_interfaces/blob/rolling/geometry_msgs/msg/Quaternion.msg)
+///
+/// Orientation in free space. The default value is the identity rotation.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Quaternion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+impl Message for Quaternion {}
+impl NamedMessage for Quaternion {
+    const PACKAGE_NAME: &'static str = "geometry_msgs";
+    const TYPE_NAME: &'static str = "Quaternion";
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        }
+    }
+}
+
+impl Quaternion {
+    /// Rotation from fixed-axis roll, pitch and yaw angles, in radians, as in
+    /// URDF.
+    ///
+    /// The rotations are applied first about X (roll), then Y (pitch), then Z
+    /// (yaw).
+    pub fn from_rpy(roll: f64, pitch: f64, yaw: f64) -> Quaternion {
+        let (sr, cr) = (roll / 2.0).sin_cos();
+        let (sp, cp) = (pitch / 2.0).sin_cos();
+        let (sy, cy) = (yaw / 2.0).sin_cos();
+        Quaternion {
+            x: sr * cp * cy - cr * sp * sy,
+            y: cr * sp * cy + sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+            w: cr * cp * cy + sr * sp * sy,
+        }
+    }
+}
+
+/// From [Transform](https://github.com/ros2/common_interfaces/blob/rolling/geometry_msgs/msg/Transform.msg)
+///
+/// Transform between two coordinate frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    pub translation: Vector3,
+    pub rotation: Quaternion,
+}
+impl Message for Transform {}
+impl NamedMessage for Transform {
+    const PACKAGE_NAME: &'static str = "geometry_msgs";
+    const TYPE_NAME: &'static str = "Transform";
+}
+
+/// From [TransformStamped](https://github.com/ros2/common_interfaces/blob/rolling/geometry_msgs/msg/TransformStamped.msg)
+///
+/// Transform from coordinate frame `header.frame_id` to frame
+/// `child_frame_id`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransformStamped {
+    pub header: Header,
+    pub child_frame_id: String,
+    pub transform: Transform,
+}
+impl Message for TransformStamped {}
+impl NamedMessage for TransformStamped {
+    const PACKAGE_NAME: &'static str = "geometry_msgs";
+    const TYPE_NAME: &'static str = "TransformStamped";
+}
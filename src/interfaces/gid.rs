@@ -1,5 +1,6 @@
-use std::fmt;
+use std::{fmt, str::FromStr};
 
+use cdr_encoding_size::*;
 use rustdds::*;
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +33,61 @@ impl fmt::Debug for Gid {
     }
 }
 
+/// Formats as lowercase hex digits, without separators. This is stable, and
+/// can be parsed back with `FromStr`.
+impl fmt::Display for Gid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for Gid {
+    type Err = ParseIdError;
+    fn from_str(s: &str) -> Result<Gid, ParseIdError> {
+        if s.len() != 2 * GID_LENGTH || !s.is_ascii() {
+            return Err(ParseIdError(format!(
+                "Gid must be {} hex digits: {s:?}",
+                2 * GID_LENGTH
+            )));
+        }
+        let mut bytes = [0; GID_LENGTH];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+                .map_err(|e| ParseIdError(format!("Bad hex digits in Gid {s:?}: {e}")))?;
+        }
+        Ok(Gid(bytes))
+    }
+}
+
+impl Gid {
+    pub const fn from_bytes(bytes: [u8; GID_LENGTH]) -> Gid {
+        Gid(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; GID_LENGTH] {
+        &self.0
+    }
+}
+
+/// Error from parsing a [`Gid`] or
+/// [`RmwRequestId`](crate::service::request_id::RmwRequestId) from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIdError(String);
+
+impl fmt::Display for ParseIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseIdError {}
+
+impl ParseIdError {
+    pub(crate) fn new(reason: String) -> Self {
+        ParseIdError(reason)
+    }
+}
+
 impl From<GUID> for Gid {
     fn from(guid: GUID) -> Self {
         Gid(std::array::from_fn(|i| {
@@ -47,3 +103,12 @@ impl From<Gid> for GUID {
 }
 
 impl Key for Gid {}
+
+#[test]
+fn gid_string_round_trip() {
+    let gid = Gid(std::array::from_fn(|i| (i * 17) as u8));
+    let s = gid.to_string();
+    assert_eq!(s.len(), 2 * GID_LENGTH);
+    assert_eq!(s.parse::<Gid>(), Ok(gid));
+    assert!("0102".parse::<Gid>().is_err());
+}
@@ -0,0 +1,254 @@
+//! Defines the types of managed (lifecycle) Nodes. See
+//! [lifecycle_msgs](https://index.ros.org/p/lifecycle_msgs/)
+//!
+//! These are used by [`LifecycleNode`](crate::node::lifecycle::LifecycleNode),
+//! and e.g. by the `nav2` lifecycle manager and `ros2 lifecycle`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    message::{Message, NamedMessage},
+    service::{NamedService, Service},
+};
+
+/// From [State](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/msg/State.msg)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    pub id: u8,
+    pub label: String,
+}
+impl Message for State {}
+
+impl NamedMessage for State {
+    const PACKAGE_NAME: &'static str = "lifecycle_msgs";
+    const TYPE_NAME: &'static str = "State";
+}
+
+impl State {
+    pub const PRIMARY_STATE_UNKNOWN: u8 = 0;
+    pub const PRIMARY_STATE_UNCONFIGURED: u8 = 1;
+    pub const PRIMARY_STATE_INACTIVE: u8 = 2;
+    pub const PRIMARY_STATE_ACTIVE: u8 = 3;
+    pub const PRIMARY_STATE_FINALIZED: u8 = 4;
+    pub const TRANSITION_STATE_CONFIGURING: u8 = 10;
+    pub const TRANSITION_STATE_CLEANINGUP: u8 = 11;
+    pub const TRANSITION_STATE_SHUTTINGDOWN: u8 = 12;
+    pub const TRANSITION_STATE_ACTIVATING: u8 = 13;
+    pub const TRANSITION_STATE_DEACTIVATING: u8 = 14;
+    pub const TRANSITION_STATE_ERRORPROCESSING: u8 = 15;
+
+    pub fn new(id: u8, label: &str) -> State {
+        State {
+            id,
+            label: label.to_owned(),
+        }
+    }
+}
+
+/// From [Transition](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/msg/Transition.msg)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub id: u8,
+    pub label: String,
+}
+impl Message for Transition {}
+
+impl NamedMessage for Transition {
+    const PACKAGE_NAME: &'static str = "lifecycle_msgs";
+    const TYPE_NAME: &'static str = "Transition";
+}
+
+impl Transition {
+    pub const TRANSITION_CREATE: u8 = 0;
+    pub const TRANSITION_CONFIGURE: u8 = 1;
+    pub const TRANSITION_CLEANUP: u8 = 2;
+    pub const TRANSITION_ACTIVATE: u8 = 3;
+    pub const TRANSITION_DEACTIVATE: u8 = 4;
+    pub const TRANSITION_UNCONFIGURED_SHUTDOWN: u8 = 5;
+    pub const TRANSITION_INACTIVE_SHUTDOWN: u8 = 6;
+    pub const TRANSITION_ACTIVE_SHUTDOWN: u8 = 7;
+    pub const TRANSITION_DESTROY: u8 = 8;
+
+    pub const TRANSITION_ON_CONFIGURE_SUCCESS: u8 = 10;
+    pub const TRANSITION_ON_CONFIGURE_FAILURE: u8 = 11;
+    pub const TRANSITION_ON_CONFIGURE_ERROR: u8 = 12;
+    pub const TRANSITION_ON_CLEANUP_SUCCESS: u8 = 20;
+    pub const TRANSITION_ON_CLEANUP_FAILURE: u8 = 21;
+    pub const TRANSITION_ON_CLEANUP_ERROR: u8 = 22;
+    pub const TRANSITION_ON_ACTIVATE_SUCCESS: u8 = 30;
+    pub const TRANSITION_ON_ACTIVATE_FAILURE: u8 = 31;
+    pub const TRANSITION_ON_ACTIVATE_ERROR: u8 = 32;
+    pub const TRANSITION_ON_DEACTIVATE_SUCCESS: u8 = 40;
+    pub const TRANSITION_ON_DEACTIVATE_FAILURE: u8 = 41;
+    pub const TRANSITION_ON_DEACTIVATE_ERROR: u8 = 42;
+    pub const TRANSITION_ON_SHUTDOWN_SUCCESS: u8 = 50;
+    pub const TRANSITION_ON_SHUTDOWN_FAILURE: u8 = 51;
+    pub const TRANSITION_ON_SHUTDOWN_ERROR: u8 = 52;
+    pub const TRANSITION_ON_ERROR_SUCCESS: u8 = 60;
+    pub const TRANSITION_ON_ERROR_FAILURE: u8 = 61;
+    pub const TRANSITION_ON_ERROR_ERROR: u8 = 62;
+
+    pub const TRANSITION_CALLBACK_SUCCESS: u8 = 97;
+    pub const TRANSITION_CALLBACK_FAILURE: u8 = 98;
+    pub const TRANSITION_CALLBACK_ERROR: u8 = 99;
+
+    pub fn new(id: u8, label: &str) -> Transition {
+        Transition {
+            id,
+            label: label.to_owned(),
+        }
+    }
+}
+
+/// From [TransitionDescription](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/msg/TransitionDescription.msg)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionDescription {
+    pub transition: Transition,
+    pub start_state: State,
+    pub goal_state: State,
+}
+impl Message for TransitionDescription {}
+
+/// From [TransitionEvent](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/msg/TransitionEvent.msg)
+///
+/// Published on `<node name>/transition_event` for every change of state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionEvent {
+    /// Nanoseconds
+    pub timestamp: u64,
+    pub transition: Transition,
+    pub start_state: State,
+    pub goal_state: State,
+}
+impl Message for TransitionEvent {}
+
+impl NamedMessage for TransitionEvent {
+    const PACKAGE_NAME: &'static str = "lifecycle_msgs";
+    const TYPE_NAME: &'static str = "TransitionEvent";
+}
+
+/// From [ChangeState](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/srv/ChangeState.srv)
+pub struct ChangeState;
+
+impl Service for ChangeState {
+    type Request = ChangeStateRequest;
+    type Response = ChangeStateResponse;
+
+    fn request_type_name(&self) -> &str {
+        "ChangeStateRequest"
+    }
+
+    fn response_type_name(&self) -> &str {
+        "ChangeStateResponse"
+    }
+}
+
+impl NamedService for ChangeState {
+    const PACKAGE_NAME: &'static str = "lifecycle_msgs";
+    const TYPE_NAME: &'static str = "ChangeState";
+}
+
+/// The transition is chosen by `id`, or by `label` if `id` is 0 and `label`
+/// is not empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeStateRequest {
+    pub transition: Transition,
+}
+impl Message for ChangeStateRequest {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeStateResponse {
+    pub success: bool,
+}
+impl Message for ChangeStateResponse {}
+
+/// From [GetState](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/srv/GetState.srv)
+pub struct GetState;
+
+impl Service for GetState {
+    type Request = EmptyRequest;
+    type Response = GetStateResponse;
+
+    fn request_type_name(&self) -> &str {
+        "GetStateRequest"
+    }
+
+    fn response_type_name(&self) -> &str {
+        "GetStateResponse"
+    }
+}
+
+impl NamedService for GetState {
+    const PACKAGE_NAME: &'static str = "lifecycle_msgs";
+    const TYPE_NAME: &'static str = "GetState";
+}
+
+/// The request of the Services that take no arguments. DDS types must have
+/// at least one member, so ROS 2 adds a dummy byte.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmptyRequest {
+    structure_needs_at_least_one_member: u8,
+}
+impl Message for EmptyRequest {}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GetStateResponse {
+    pub current_state: State,
+}
+impl Message for GetStateResponse {}
+
+/// From [GetAvailableStates](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/srv/GetAvailableStates.srv)
+pub struct GetAvailableStates;
+
+impl Service for GetAvailableStates {
+    type Request = EmptyRequest;
+    type Response = GetAvailableStatesResponse;
+
+    fn request_type_name(&self) -> &str {
+        "GetAvailableStatesRequest"
+    }
+
+    fn response_type_name(&self) -> &str {
+        "GetAvailableStatesResponse"
+    }
+}
+
+impl NamedService for GetAvailableStates {
+    const PACKAGE_NAME: &'static str = "lifecycle_msgs";
+    const TYPE_NAME: &'static str = "GetAvailableStates";
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GetAvailableStatesResponse {
+    pub available_states: Vec<State>,
+}
+impl Message for GetAvailableStatesResponse {}
+
+/// From [GetAvailableTransitions](https://github.com/ros2/rcl_interfaces/blob/rolling/lifecycle_msgs/srv/GetAvailableTransitions.srv)
+///
+/// This is also the type of the `get_transition_graph` Service.
+pub struct GetAvailableTransitions;
+
+impl Service for GetAvailableTransitions {
+    type Request = EmptyRequest;
+    type Response = GetAvailableTransitionsResponse;
+
+    fn request_type_name(&self) -> &str {
+        "GetAvailableTransitionsRequest"
+    }
+
+    fn response_type_name(&self) -> &str {
+        "GetAvailableTransitionsResponse"
+    }
+}
+
+impl NamedService for GetAvailableTransitions {
+    const PACKAGE_NAME: &'static str = "lifecycle_msgs";
+    const TYPE_NAME: &'static str = "GetAvailableTransitions";
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GetAvailableTransitionsResponse {
+    pub available_transitions: Vec<TransitionDescription>,
+}
+impl Message for GetAvailableTransitionsResponse {}
@@ -1,7 +1,16 @@
 pub mod basic_types_interface;
 pub mod builtin_interfaces;
+pub mod diagnostic_msgs;
+pub mod example_interfaces;
+pub mod geometry_msgs;
 pub mod gid;
+pub mod lifecycle_msgs;
 pub mod names;
 pub mod rcl_interfaces;
+pub mod rosgraph_msgs;
+pub mod service_msgs;
+pub mod std_msgs;
+pub mod std_srvs;
+pub mod tf2_msgs;
 pub mod unique_identifier_msgs;
 pub mod wide_string;
@@ -3,8 +3,13 @@
 //! * Service types, e.g. `turtlesim/Spawn`
 //! * action types, e.g. `turtlesim/RotateAbsolute`
 //! *
+//!
+//! It also has functions to convert Topic, Service and type names between
+//! ROS 2 and DDS notation, e.g. [`ros_type_to_dds`] and [`dds_name_to_ros`].
 
-use std::fmt;
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 // TODO:
 // Conform fully to https://design.ros2.org/articles/topic_and_service_names.html
@@ -100,6 +105,8 @@ pub enum NameError {
     Empty,
     BadChar(char),
     BadSlash(String, String),
+    /// Type name is not of the form `package/Type` or `package/prefix/Type`
+    BadTypeName(String),
 }
 
 impl fmt::Display for NameError {
@@ -111,12 +118,64 @@ impl fmt::Display for NameError {
                 f,
                 "Invalid placement of seprator slashes. namespace={ns}  name={n}"
             ),
+            NameError::BadTypeName(t) => write!(f, "Bad type name: {t:?}"),
         }
     }
 }
 
 impl std::error::Error for NameError {}
 
+// Names are serialized as strings, in the same format as Display, and
+// deserialized using FromStr.
+macro_rules! impl_name_conversions {
+    ($t:ty) => {
+        impl TryFrom<&str> for $t {
+            type Error = NameError;
+            fn try_from(s: &str) -> Result<Self, NameError> {
+                s.parse()
+            }
+        }
+
+        impl Serialize for $t {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $t {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+impl_name_conversions!(NodeName);
+impl_name_conversions!(Name);
+impl_name_conversions!(MessageTypeName);
+impl_name_conversions!(ServiceTypeName);
+impl_name_conversions!(ActionTypeName);
+
+impl fmt::Display for NodeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.fully_qualified_name())
+    }
+}
+
+/// Parse a fully qualified Node name, e.g. `/namespace/node`. A name without
+/// slashes is taken to be in the root namespace `/`.
+impl FromStr for NodeName {
+    type Err = NameError;
+    fn from_str(s: &str) -> Result<NodeName, NameError> {
+        match s.rsplit_once('/') {
+            None => NodeName::new("/", s),
+            Some(("", base_name)) => NodeName::new("/", base_name),
+            Some((namespace, base_name)) => NodeName::new(namespace, base_name),
+        }
+    }
+}
+
 /// Names for Topics, Services
 ///
 /// See [Names](https://wiki.ros.org/Names) for ROS 1.
@@ -270,6 +329,57 @@ impl Name {
     pub fn is_absolute(&self) -> bool {
         self.absolute
     }
+
+    /// Is this a hidden name, i.e. does any of its components start with an
+    /// underscore? E.g. `/_internal/foo` and `/foo/_bar` are hidden.
+    ///
+    /// Tools such as `ros2 topic list` do not show hidden Topics by default.
+    pub fn is_hidden(&self) -> bool {
+        self.preceeding_tokens
+            .iter()
+            .chain(std::iter::once(&self.base_name))
+            .any(|tok| tok.starts_with('_'))
+    }
+
+    /// Hidden version of this name, made by prefixing the base name with an
+    /// underscore, e.g. `/foo/bar` becomes `/foo/_bar`.
+    ///
+    /// If the base name already starts with an underscore, the name is
+    /// returned unchanged.
+    pub fn to_hidden(&self) -> Name {
+        let mut hidden = self.clone();
+        if !hidden.base_name.starts_with('_') {
+            hidden.base_name.insert(0, '_');
+        }
+        hidden
+    }
+
+    /// Absolute form of this name, as seen from a Node in namespace
+    /// `node_namespace`.
+    pub(crate) fn to_absolute(&self, node_namespace: &str) -> Name {
+        if self.absolute {
+            return self.clone();
+        }
+        let mut preceeding_tokens: Vec<String> = node_namespace
+            .split('/')
+            .filter(|tok| !tok.is_empty())
+            .map(str::to_owned)
+            .collect();
+        preceeding_tokens.extend(self.preceeding_tokens.iter().cloned());
+        Name {
+            base_name: self.base_name.clone(),
+            preceeding_tokens,
+            absolute: true,
+        }
+    }
+}
+
+/// Same as [`Name::parse`]
+impl FromStr for Name {
+    type Err = NameError;
+    fn from_str(s: &str) -> Result<Name, NameError> {
+        Name::parse(s)
+    }
 }
 
 impl fmt::Display for Name {
@@ -291,7 +401,7 @@ impl fmt::Display for Name {
 /// Note that this is not for naming Topics, but data types of Topics.
 ///
 /// See [Names](https://wiki.ros.org/Names) Section 1.2 Package Resource Names.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MessageTypeName {
     prefix: String, // typically "msg", but may be "action". What should this part be called?
     //TODO: String is strictly UTF-8, but ROS2 uses just byte strings that are recommended to be
@@ -326,6 +436,28 @@ impl MessageTypeName {
         self.ros2_type_name.as_str()
     }
 
+    /// Type name in ROS 2 notation, e.g. `std_msgs/msg/String`
+    pub fn ros_type(&self) -> String {
+        format!(
+            "{}/{}/{}",
+            self.ros2_package_name, self.prefix, self.ros2_type_name
+        )
+    }
+
+    /// Parse a type name used over DDS, e.g. `std_msgs::msg::dds_::String_`.
+    pub fn from_dds_type(dds_type: &str) -> Option<Self> {
+        let ros_type = dds_type_to_ros(dds_type)?;
+        let mut parts = ros_type.split('/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(package), Some(prefix), Some(type_name)) => Some(MessageTypeName::new_prefix(
+                package,
+                type_name,
+                prefix.to_owned(),
+            )),
+            _ => None,
+        }
+    }
+
     /// Convert to type name used over DDS
     pub fn dds_msg_type(&self) -> String {
         slash_to_colons(
@@ -343,8 +475,54 @@ fn slash_to_colons(s: String) -> String {
     s.replace('/', "::")
 }
 
+// Split a type name `package/Type` or `package/prefix/Type` into
+// (package, prefix, Type). The two-part form gets `default_prefix`.
+fn split_type_name<'a>(
+    s: &'a str,
+    default_prefix: &'a str,
+) -> Result<(&'a str, &'a str, &'a str), NameError> {
+    let parts: Vec<&str> = s.split('/').collect();
+    let (package, prefix, type_name) = match parts.as_slice() {
+        [package, type_name] => (*package, default_prefix, *type_name),
+        [package, prefix, type_name] => (*package, *prefix, *type_name),
+        _ => return Err(NameError::BadTypeName(s.to_owned())),
+    };
+    for part in [package, prefix, type_name] {
+        if part.is_empty() {
+            return Err(NameError::BadTypeName(s.to_owned()));
+        }
+        if let Some(bad) = part
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || *c == '_'))
+        {
+            return Err(NameError::BadChar(bad));
+        }
+    }
+    Ok((package, prefix, type_name))
+}
+
+/// Formats as e.g. `std_msgs/msg/String`
+impl fmt::Display for MessageTypeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ros_type())
+    }
+}
+
+/// Parse `package/msg/Type` or `package/Type`
+impl FromStr for MessageTypeName {
+    type Err = NameError;
+    fn from_str(s: &str) -> Result<MessageTypeName, NameError> {
+        let (package, prefix, type_name) = split_type_name(s, "msg")?;
+        Ok(MessageTypeName::new_prefix(
+            package,
+            type_name,
+            prefix.to_owned(),
+        ))
+    }
+}
+
 /// Similar to [`MessageTypeName`], but names a Service type.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ServiceTypeName {
     prefix: String,
     msg: MessageTypeName,
@@ -373,7 +551,8 @@ impl ServiceTypeName {
         self.msg.type_name()
     }
 
-    pub(crate) fn dds_request_type(&self) -> String {
+    /// Convert to type name of the request message used over DDS
+    pub fn dds_request_type(&self) -> String {
         slash_to_colons(
             self.package_name().to_owned()
                 + "/"
@@ -384,7 +563,8 @@ impl ServiceTypeName {
         )
     }
 
-    pub(crate) fn dds_response_type(&self) -> String {
+    /// Convert to type name of the response message used over DDS
+    pub fn dds_response_type(&self) -> String {
         slash_to_colons(
             self.package_name().to_owned()
                 + "/"
@@ -396,8 +576,34 @@ impl ServiceTypeName {
     }
 }
 
+/// Formats as e.g. `example_interfaces/srv/AddTwoInts`
+impl fmt::Display for ServiceTypeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.package_name(),
+            self.prefix,
+            self.type_name()
+        )
+    }
+}
+
+/// Parse `package/srv/Type` or `package/Type`
+impl FromStr for ServiceTypeName {
+    type Err = NameError;
+    fn from_str(s: &str) -> Result<ServiceTypeName, NameError> {
+        let (package, prefix, type_name) = split_type_name(s, "srv")?;
+        Ok(ServiceTypeName::new_prefix(
+            package,
+            type_name,
+            prefix.to_owned(),
+        ))
+    }
+}
+
 /// Similar to [`MessageTypeName`], but names an Action type.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ActionTypeName(MessageTypeName);
 
 impl ActionTypeName {
@@ -413,6 +619,7 @@ impl ActionTypeName {
         self.0.type_name()
     }
 
+    #[cfg(feature = "actions")]
     pub(crate) fn dds_action_topic(&self, topic: &str) -> MessageTypeName {
         MessageTypeName::new_prefix(
             self.package_name(),
@@ -423,6 +630,7 @@ impl ActionTypeName {
         // &self.type_name())
     }
 
+    #[cfg(feature = "actions")]
     pub(crate) fn dds_action_service(&self, srv: &str) -> ServiceTypeName {
         ServiceTypeName::new_prefix(
             self.package_name(),
@@ -432,6 +640,148 @@ impl ActionTypeName {
     }
 }
 
+/// Formats as e.g. `turtlesim/action/RotateAbsolute`
+impl fmt::Display for ActionTypeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/action/{}", self.package_name(), self.type_name())
+    }
+}
+
+/// Parse `package/action/Type` or `package/Type`
+impl FromStr for ActionTypeName {
+    type Err = NameError;
+    fn from_str(s: &str) -> Result<ActionTypeName, NameError> {
+        match split_type_name(s, "action")? {
+            (package, "action", type_name) => Ok(ActionTypeName::new(package, type_name)),
+            _ => Err(NameError::BadTypeName(s.to_owned())),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------
+// Name mangling between ROS 2 and DDS
+//
+// See [Topic and Service name mapping to DDS](https://design.ros2.org/articles/topic_and_service_names.html)
+// -------------------------------------------------------------------------------------
+
+/// What a DDS Topic is used for in ROS 2. This is encoded as a prefix of the
+/// DDS Topic name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DdsTopicKind {
+    /// Ordinary ROS 2 Topic. Prefix `rt/`.
+    Topic,
+    /// Service request. Prefix `rq/`, suffix `Request`.
+    ServiceRequest,
+    /// Service response. Prefix `rr/`, suffix `Reply`.
+    ServiceReply,
+}
+
+impl DdsTopicKind {
+    /// Name prefix, without the separating slash
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            DdsTopicKind::Topic => "rt",
+            DdsTopicKind::ServiceRequest => "rq",
+            DdsTopicKind::ServiceReply => "rr",
+        }
+    }
+
+    /// Name suffix
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            DdsTopicKind::Topic => "",
+            DdsTopicKind::ServiceRequest => "Request",
+            DdsTopicKind::ServiceReply => "Reply",
+        }
+    }
+}
+
+/// Convert a fully qualified ROS 2 Topic or Service name to a DDS Topic name.
+///
+/// E.g. `/chatter` becomes `rt/chatter` for [`DdsTopicKind::Topic`], and
+/// `/add_two_ints` becomes `rq/add_two_intsRequest` for
+/// [`DdsTopicKind::ServiceRequest`]. A missing leading slash is accepted.
+pub fn ros_name_to_dds(kind: DdsTopicKind, ros_name: &str) -> String {
+    format!(
+        "{}/{}{}",
+        kind.prefix(),
+        ros_name.strip_prefix('/').unwrap_or(ros_name),
+        kind.suffix()
+    )
+}
+
+/// Convert a DDS Topic name to a fully qualified ROS 2 Topic or Service name.
+///
+/// This is the inverse of [`ros_name_to_dds`]. Returns `None` if the name does
+/// not have a ROS 2 prefix, e.g. because the Topic is not used by ROS 2.
+pub fn dds_name_to_ros(dds_name: &str) -> Option<(DdsTopicKind, String)> {
+    [
+        DdsTopicKind::Topic,
+        DdsTopicKind::ServiceRequest,
+        DdsTopicKind::ServiceReply,
+    ]
+    .into_iter()
+    .find_map(|kind| {
+        let rest = dds_name
+            .strip_prefix(kind.prefix())?
+            .strip_prefix('/')?
+            .strip_suffix(kind.suffix())?;
+        if rest.is_empty() {
+            None
+        } else {
+            Some((kind, format!("/{rest}")))
+        }
+    })
+}
+
+/// Is the ROS 2 Topic or Service name (in string form) hidden?
+///
+/// See [`Name::is_hidden`].
+pub fn is_hidden_name(ros_name: &str) -> bool {
+    ros_name.split('/').any(|tok| tok.starts_with('_'))
+}
+
+// ROS 2 name of a DDS Topic, or the DDS name itself if it is not a ROS 2 Topic.
+pub(crate) fn ros_name_of_dds_topic(dds_name: &str) -> String {
+    dds_name_to_ros(dds_name).map_or_else(|| dds_name.to_owned(), |(_kind, name)| name)
+}
+
+/// Convert a ROS 2 type name to a DDS type name.
+///
+/// E.g. `std_msgs/msg/String` becomes `std_msgs::msg::dds_::String_`.
+/// The two-part form `std_msgs/String` is taken to mean a message type.
+/// Returns `None` if the name does not have two or three parts.
+pub fn ros_type_to_dds(ros_type: &str) -> Option<String> {
+    let parts: Vec<&str> = ros_type.split('/').collect();
+    let (package, prefix, type_name) = match parts.as_slice() {
+        [package, type_name] => (*package, "msg", *type_name),
+        [package, prefix, type_name] => (*package, *prefix, *type_name),
+        _ => return None,
+    };
+    if [package, prefix, type_name].iter().any(|p| p.is_empty()) {
+        return None;
+    }
+    Some(format!("{package}::{prefix}::dds_::{type_name}_"))
+}
+
+/// Convert a DDS type name to a ROS 2 type name.
+///
+/// This is the inverse of [`ros_type_to_dds`], e.g.
+/// `std_msgs::msg::dds_::String_` becomes `std_msgs/msg/String`. Returns
+/// `None` if the name does not follow the ROS 2 convention.
+pub fn dds_type_to_ros(dds_type: &str) -> Option<String> {
+    let parts: Vec<&str> = dds_type.split("::").collect();
+    match parts.as_slice() {
+        [package, prefix, "dds_", type_name] if !package.is_empty() && !prefix.is_empty() => {
+            match type_name.strip_suffix('_') {
+                Some(t) if !t.is_empty() => Some(format!("{package}/{prefix}/{t}")),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 // -------------------------------------------------------------------------------------
 // -------------------------------------------------------------------------------------
 
@@ -477,3 +827,82 @@ fn test_name_parse() {
     assert!(!Name::parse("a/nn").unwrap().is_absolute());
     assert!(Name::parse("/a/nn").unwrap().is_absolute());
 }
+
+#[test]
+fn test_name_mangling() {
+    assert_eq!(
+        ros_name_to_dds(DdsTopicKind::Topic, "/ns/chatter"),
+        "rt/ns/chatter"
+    );
+    assert_eq!(
+        ros_name_to_dds(DdsTopicKind::ServiceRequest, "/add_two_ints"),
+        "rq/add_two_intsRequest"
+    );
+    assert_eq!(
+        dds_name_to_ros("rr/add_two_intsReply"),
+        Some((DdsTopicKind::ServiceReply, "/add_two_ints".to_owned()))
+    );
+    assert_eq!(
+        dds_name_to_ros("rt/ns/chatter"),
+        Some((DdsTopicKind::Topic, "/ns/chatter".to_owned()))
+    );
+    assert_eq!(dds_name_to_ros("DCPSParticipant"), None);
+    assert_eq!(dds_name_to_ros("rt/"), None);
+
+    assert_eq!(
+        ros_type_to_dds("std_msgs/msg/String").as_deref(),
+        Some("std_msgs::msg::dds_::String_")
+    );
+    assert_eq!(
+        ros_type_to_dds("std_msgs/String").as_deref(),
+        Some("std_msgs::msg::dds_::String_")
+    );
+    assert_eq!(ros_type_to_dds("String"), None);
+    assert_eq!(
+        dds_type_to_ros("example_interfaces::srv::dds_::AddTwoInts_Request_").as_deref(),
+        Some("example_interfaces/srv/AddTwoInts_Request")
+    );
+    assert_eq!(dds_type_to_ros("std_msgs::msg::String"), None);
+
+    let t = MessageTypeName::new("std_msgs", "String");
+    assert_eq!(
+        MessageTypeName::from_dds_type(&t.dds_msg_type()).map(|m| m.ros_type()),
+        Some(t.ros_type())
+    );
+    assert_eq!(
+        ServiceTypeName::new("example_interfaces", "AddTwoInts").dds_response_type(),
+        "example_interfaces::srv::dds_::AddTwoInts_Response_"
+    );
+}
+
+#[test]
+fn test_name_string_conversions() {
+    let node: NodeName = "/ns/talker".parse().unwrap();
+    assert_eq!(node, NodeName::new("/ns", "talker").unwrap());
+    assert_eq!(node.to_string(), "/ns/talker");
+    assert_eq!("talker".parse::<NodeName>().unwrap().to_string(), "/talker");
+    assert!("ns/talker".parse::<NodeName>().is_err());
+
+    let name = Name::try_from("a/b/c").unwrap();
+    assert!(!name.is_hidden());
+    assert_eq!(name.to_hidden().to_string(), "a/b/_c");
+    assert!(name.to_hidden().is_hidden());
+    assert!(is_hidden_name("/turtle1/rotate_absolute/_action/status"));
+    assert_eq!(name.to_string().parse::<Name>().unwrap(), name);
+
+    let t: MessageTypeName = "std_msgs/String".parse().unwrap();
+    assert_eq!(t.to_string(), "std_msgs/msg/String");
+    assert_eq!(t.to_string().parse::<MessageTypeName>().unwrap(), t);
+    assert!("std_msgs/msg/String/x".parse::<MessageTypeName>().is_err());
+    assert!("std_msgs//String".parse::<MessageTypeName>().is_err());
+
+    let t: ServiceTypeName = "example_interfaces/srv/AddTwoInts".parse().unwrap();
+    assert_eq!(t, ServiceTypeName::new("example_interfaces", "AddTwoInts"));
+    assert_eq!(t.to_string(), "example_interfaces/srv/AddTwoInts");
+
+    let t: ActionTypeName = "turtlesim/RotateAbsolute".parse().unwrap();
+    assert_eq!(t.to_string(), "turtlesim/action/RotateAbsolute");
+    assert!("turtlesim/msg/RotateAbsolute"
+        .parse::<ActionTypeName>()
+        .is_err());
+}
@@ -20,9 +20,10 @@ pub type SetParametersService = AService<SetParametersRequest, SetParametersResp
 pub type DescribeParametersService =
     AService<DescribeParametersRequest, DescribeParametersResponse>;
 
-// This is structurally identical to SetParamtersService, but the operation
-// of the service is slightly different.
-pub type SetParametersAtomicallyService = AService<SetParametersRequest, SetParametersResponse>;
+// The request is the same as in SetParametersService, but there is only one
+// result for all the Parameters.
+pub type SetParametersAtomicallyService =
+    AService<SetParametersRequest, SetParametersAtomicallyResponse>;
 
 #[allow(non_snake_case)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,7 +82,12 @@ pub struct SetParametersResponse {
 }
 impl Message for SetParametersResponse {}
 
-pub type SetParametersAtomicallyResponse = SetParametersResponse;
+// https://github.com/ros2/rcl_interfaces/blob/humble/rcl_interfaces/srv/SetParametersAtomically.srv
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetParametersAtomicallyResponse {
+    pub result: parameters::raw::SetParametersResult,
+}
+impl Message for SetParametersAtomicallyResponse {}
 
 // https://github.com/ros2/rcl_interfaces/blob/humble/rcl_interfaces/srv/DescribeParameters.srv
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,3 +101,26 @@ pub struct DescribeParametersResponse {
     pub values: Vec<parameters::raw::ParameterDescriptor>,
 }
 impl Message for DescribeParametersResponse {}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{no_key::SerializerAdapter, CDRSerializerAdapter};
+
+    use super::*;
+
+    #[test]
+    fn set_parameters_atomically_response_layout() {
+        // SetParametersAtomically.srv replies with a single
+        // SetParametersResult, not a sequence of them.
+        let response = SetParametersAtomicallyResponse {
+            result: parameters::raw::SetParametersResult {
+                successful: false,
+                reason: "ro".to_string(),
+            },
+        };
+        let bytes =
+            CDRSerializerAdapter::<SetParametersAtomicallyResponse>::to_bytes(&response).unwrap();
+        // bool, padding to 4, string length including NUL, string
+        assert_eq!(&bytes[..], &[0, 0, 0, 0, 3, 0, 0, 0, b'r', b'o', 0]);
+    }
+}
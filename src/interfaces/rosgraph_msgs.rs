@@ -0,0 +1,37 @@
+//! Defines message type `Clock`. See [rosgraph_msgs](https://index.ros.org/p/rosgraph_msgs/)
+//!
+//! This is the type of Topic `/clock`, which carries simulated time.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interfaces::builtin_interfaces::Time,
+    message::{Message, NamedMessage},
+    prelude::ROSTime,
+};
+
+/// From [Clock](https://github.com/ros2/rcl_interfaces/blob/rolling/rosgraph_msgs/msg/Clock.msg)
+///
+/// Published on Topic `/clock` by simulators, or other sources of simulated
+/// time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clock {
+    pub clock: Time,
+}
+impl Message for Clock {}
+impl NamedMessage for Clock {
+    const PACKAGE_NAME: &'static str = "rosgraph_msgs";
+    const TYPE_NAME: &'static str = "Clock";
+}
+
+impl From<ROSTime> for Clock {
+    fn from(t: ROSTime) -> Clock {
+        Clock { clock: t.into() }
+    }
+}
+
+impl From<Clock> for ROSTime {
+    fn from(c: Clock) -> ROSTime {
+        c.clock.into()
+    }
+}
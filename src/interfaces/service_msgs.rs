@@ -0,0 +1,76 @@
+//! Defines message types for Service introspection. See [service_msgs](https://index.ros.org/p/service_msgs/)
+//!
+//! These are available in ROS 2 Iron and later. Each Service may publish a
+//! [`ServiceEvent`] on topic `<service_name>/_service_event` whenever a request
+//! or response is sent or received.
+
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::{
+    interfaces::builtin_interfaces::Time, message::Message, service::request_id::RmwRequestId,
+};
+
+/// Suffix for the introspection topic name, appended to the Service name.
+pub const SERVICE_EVENT_TOPIC_SUFFIX: &str = "_service_event";
+
+/// What happened to a request or response.
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum ServiceEventType {
+    RequestSent = 0,
+    RequestReceived = 1,
+    ResponseSent = 2,
+    ResponseReceived = 3,
+}
+
+/// From [ServiceEventInfo](https://github.com/ros2/rcl_interfaces/blob/rolling/service_msgs/msg/ServiceEventInfo.msg)
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ServiceEventInfo {
+    pub event_type: ServiceEventType,
+    /// Time when the event occurred
+    pub stamp: Time,
+    /// GID of the Client that sent the request
+    pub client_gid: [u8; 16],
+    /// Sequence number of the request, used together with `client_gid` to
+    /// match requests and responses.
+    pub sequence_number: i64,
+}
+impl Message for ServiceEventInfo {}
+
+impl ServiceEventInfo {
+    /// Event information for the request identified by `request_id`, occurring
+    /// now.
+    pub fn new(event_type: ServiceEventType, request_id: RmwRequestId) -> Self {
+        ServiceEventInfo {
+            event_type,
+            stamp: Time::now(),
+            client_gid: request_id.writer_guid.to_bytes(),
+            sequence_number: request_id.sequence_number.into(),
+        }
+    }
+}
+
+/// Generic form of the `<Service>_Event` message types.
+///
+/// Either `request` or `response` has one element if contents are
+/// introspected. Both are empty if only metadata is introspected.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ServiceEvent<Req, Resp> {
+    pub info: ServiceEventInfo,
+    pub request: Vec<Req>,
+    pub response: Vec<Resp>,
+}
+impl<Req: Message, Resp: Message> Message for ServiceEvent<Req, Resp> {}
+
+/// How much information about Service traffic is published.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum IntrospectionState {
+    /// Nothing is published.
+    #[default]
+    Off,
+    /// Only [`ServiceEventInfo`] is published.
+    Metadata,
+    /// Also request and response contents are published.
+    Contents,
+}
@@ -0,0 +1,27 @@
+//! Defines message type `Header`. See [std_msgs](https://index.ros.org/p/std_msgs/)
+//!
+//! Only the types that are commonly embedded in other messages are defined
+//! here.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interfaces::builtin_interfaces::Time,
+    message::{Message, NamedMessage},
+};
+
+/// From [Header](https://github.com/ros2/common_interfaces/blob/rolling/std_msgs/msg/Header.msg)
+///
+/// Standard metadata for timestamped data in a particular coordinate frame.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    /// Time of the data, usually the time it was acquired
+    pub stamp: Time,
+    /// Coordinate frame of the data
+    pub frame_id: String,
+}
+impl Message for Header {}
+impl NamedMessage for Header {
+    const PACKAGE_NAME: &'static str = "std_msgs";
+    const TYPE_NAME: &'static str = "Header";
+}
@@ -0,0 +1,25 @@
+//! Defines Service type `Trigger`. See [std_srvs](https://index.ros.org/p/std_srvs/)
+
+use serde::{Deserialize, Serialize};
+
+use crate::{message::Message, service::AService};
+
+/// From [Trigger](https://github.com/ros2/common_interfaces/blob/rolling/std_srvs/srv/Trigger.srv)
+pub type TriggerService = AService<TriggerRequest, TriggerResponse>;
+
+/// The request is empty, but DDS types must have at least one member, so ROS 2
+/// adds a dummy byte.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerRequest {
+    structure_needs_at_least_one_member: u8,
+}
+impl Message for TriggerRequest {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerResponse {
+    /// Indicate successful run of triggered service
+    pub success: bool,
+    /// Informational, e.g. for error messages
+    pub message: String,
+}
+impl Message for TriggerResponse {}
@@ -0,0 +1,21 @@
+//! Defines message type `TFMessage`. See [tf2_msgs](https://index.ros.org/p/tf2_msgs/)
+//!
+//! This is the type of Topics `/tf` and `/tf_static`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interfaces::geometry_msgs::TransformStamped,
+    message::{Message, NamedMessage},
+};
+
+/// From [TFMessage](https://github.com/ros2/geometry2/blob/rolling/tf2_msgs/msg/TFMessage.msg)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TFMessage {
+    pub transforms: Vec<TransformStamped>,
+}
+impl Message for TFMessage {}
+impl NamedMessage for TFMessage {
+    const PACKAGE_NAME: &'static str = "tf2_msgs";
+    const TYPE_NAME: &'static str = "TFMessage";
+}
@@ -1,17 +1,30 @@
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::message::Message;
+use crate::message::{Message, NamedMessage};
 
+/// From [UUID](https://github.com/ros2/unique_identifier_msgs/blob/rolling/msg/UUID.msg)
+///
+/// Formatted with [`Display`](fmt::Display) as canonical hyphenated UUID text,
+/// e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`, and parsed from the same
+/// format (or without hyphens) with [`FromStr`].
+///
+/// The ordering is byte-wise lexicographic, i.e. the same as the ordering of
+/// the text form. It does not reflect creation order, as random (v4) UUIDs are
+/// used.
 // deriving also Copy here is a bit on the expensive side, but makes life easier
-#[derive(Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct UUID {
     #[serde(with = "uuid::serde::compact")] // straightforward binary serialization, not text
     pub uuid: Uuid,
 }
 impl Message for UUID {}
+impl NamedMessage for UUID {
+    const PACKAGE_NAME: &'static str = "unique_identifier_msgs";
+    const TYPE_NAME: &'static str = "UUID";
+}
 
 impl fmt::Debug for UUID {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -19,6 +32,32 @@ impl fmt::Debug for UUID {
     }
 }
 
+impl fmt::Display for UUID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.uuid.as_hyphenated(), f)
+    }
+}
+
+impl FromStr for UUID {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(UUID::from)
+    }
+}
+
+impl From<Uuid> for UUID {
+    fn from(uuid: Uuid) -> Self {
+        UUID { uuid }
+    }
+}
+
+impl From<UUID> for Uuid {
+    fn from(id: UUID) -> Self {
+        id.uuid
+    }
+}
+
 impl UUID {
     pub const ZERO: UUID = UUID { uuid: Uuid::nil() };
 
@@ -27,14 +66,34 @@ impl UUID {
             uuid: Uuid::new_v4(),
         }
     }
-}
 
-// #[cfg(test)]
-// mod tests {
+    /// Construct from the 16 bytes of the UUID, as sent over the wire.
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        UUID {
+            uuid: Uuid::from_bytes(bytes),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        self.uuid.as_bytes()
+    }
+}
 
-//   #[test]
-//   fn test_serialize() {
-//     let
-//   }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-// }
+    #[test]
+    fn text_round_trip() {
+        let text = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let id: UUID = text.parse().unwrap();
+        assert_eq!(id.to_string(), text);
+        assert_eq!(
+            "67e5504410b1426f9247bb680e5fe0c8".parse::<UUID>().unwrap(),
+            id
+        );
+        assert!("not-a-uuid".parse::<UUID>().is_err());
+        assert_eq!(UUID::from(Uuid::from(id)), id);
+        assert_eq!(UUID::from_bytes(*id.as_bytes()), id);
+    }
+}
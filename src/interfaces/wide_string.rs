@@ -10,6 +10,8 @@ use serde::{
 };
 use widestring::Utf16String;
 
+use crate::message::Message;
+
 /// UTF-16 strings, as required by the ROS type system.
 ///
 /// We just wrap a pre-existing library to get proper Serialize and Deserialize.
@@ -26,6 +28,8 @@ impl WString {
     }
 }
 
+impl Message for WString {}
+
 impl Default for WString {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,659 @@
+//! Wire format conformance tests
+//!
+//! Reference CDR payloads of ROS 2 interface types, in the layout rclcpp
+//! uses on the wire. Applications that generate or hand-write message types can
+//! check in their own tests that the types produce and accept the same bytes:
+//!
+//! ```ignore
+//! #[test]
+//! fn my_log_type_matches_ros2() {
+//!     let expected = MyLog { stamp: MyTime { sec: 1_700_000_000, nanosec: 500_000_000 },
+//!         level: 20, name: "talker".into(), msg: "Hello".into(),
+//!         file: "talker.cpp".into(), function: "main".into(), line: 42 };
+//!     interop::LOG.verify(&expected).unwrap();
+//! }
+//! ```
+//!
+//! The value encoded in each payload is documented with the payload. All
+//! payloads are little-endian classic CDR (`CDR_LE`), which is what ROS 2
+//! uses by default. Service payloads contain only the request or response,
+//! as the request id travels in the RTPS message.
+//!
+//! The tests of this module also run the
+//! [`example_interfaces`](crate::interfaces::example_interfaces) AddTwoInts
+//! Service and Fibonacci Action end to end against the rclcpp and rclpy
+//! tutorial programs. Those tests need a sourced ROS 2 installation with the
+//! demo and example packages, and are skipped if `ros2` is not found.
+//!
+//! This requires feature `interop-tests`.
+
+use std::{error::Error, fmt};
+
+use rustdds::{
+    no_key::SerializerAdapter, serialization::deserialize_from_cdr_with_rep_id,
+    CDRSerializerAdapter, RepresentationIdentifier,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Serialized sample of a ROS 2 type
+#[derive(Clone, Copy, Debug)]
+pub struct GoldenPayload {
+    /// Short name of the test case
+    pub name: &'static str,
+    /// ROS 2 type name, e.g. `rcl_interfaces/msg/Log`
+    pub type_name: &'static str,
+    /// Serialized payload, starting with the 4-byte encapsulation header
+    pub bytes: &'static [u8],
+}
+
+impl GoldenPayload {
+    /// Representation identifier from the encapsulation header
+    pub fn representation(&self) -> RepresentationIdentifier {
+        // Cannot fail, as every payload has a header.
+        RepresentationIdentifier::from_bytes(self.bytes).unwrap_or(RepresentationIdentifier::CDR_LE)
+    }
+
+    /// The serialized data without the encapsulation header, and without the
+    /// padding announced in the header options
+    pub fn data(&self) -> &'static [u8] {
+        let padding = usize::from(self.bytes[3] & 0x03);
+        &self.bytes[4..self.bytes.len() - padding]
+    }
+
+    /// Check that the payload decodes to `expected`, and that `expected`
+    /// serializes to the payload.
+    pub fn verify<M>(&self, expected: &M) -> Result<(), InteropError>
+    where
+        M: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+    {
+        let (decoded, _) =
+            deserialize_from_cdr_with_rep_id::<M>(self.data(), self.representation()).map_err(
+                |e| InteropError::Decode {
+                    payload: self.name,
+                    reason: e.to_string(),
+                },
+            )?;
+        if decoded != *expected {
+            return Err(InteropError::ValueMismatch {
+                payload: self.name,
+                decoded: format!("{decoded:?}"),
+                expected: format!("{expected:?}"),
+            });
+        }
+        let encoded =
+            CDRSerializerAdapter::<M>::to_bytes(expected).map_err(|e| InteropError::Encode {
+                payload: self.name,
+                reason: e.to_string(),
+            })?;
+        if encoded != self.data() {
+            return Err(InteropError::BytesMismatch {
+                payload: self.name,
+                encoded: encoded.to_vec(),
+                golden: self.data().to_vec(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Reason why a type does not match a [`GoldenPayload`]
+#[derive(Clone, Debug)]
+pub enum InteropError {
+    /// The payload could not be decoded as the type.
+    Decode {
+        payload: &'static str,
+        reason: String,
+    },
+    /// The payload decoded to a different value.
+    ValueMismatch {
+        payload: &'static str,
+        decoded: String,
+        expected: String,
+    },
+    /// The expected value could not be serialized.
+    Encode {
+        payload: &'static str,
+        reason: String,
+    },
+    /// The expected value serialized to different bytes.
+    BytesMismatch {
+        payload: &'static str,
+        encoded: Vec<u8>,
+        golden: Vec<u8>,
+    },
+}
+
+impl fmt::Display for InteropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InteropError::Decode { payload, reason } => {
+                write!(f, "{payload}: cannot decode golden payload: {reason}")
+            }
+            InteropError::ValueMismatch {
+                payload,
+                decoded,
+                expected,
+            } => write!(
+                f,
+                "{payload}: golden payload decoded to {decoded}, expected {expected}"
+            ),
+            InteropError::Encode { payload, reason } => {
+                write!(f, "{payload}: cannot serialize expected value: {reason}")
+            }
+            InteropError::BytesMismatch {
+                payload,
+                encoded,
+                golden,
+            } => write!(
+                f,
+                "{payload}: serialized to {encoded:02x?}, golden payload is {golden:02x?}"
+            ),
+        }
+    }
+}
+
+impl Error for InteropError {}
+
+/// `action_msgs/msg/GoalStatusArray`, as published on the status Topic of an
+/// Action.
+///
+/// One status: goal id bytes `0, 1, ..., 15`, stamp `1_700_000_000` s
+/// `500_000_000` ns, status `STATUS_EXECUTING` (2).
+///
+/// The payload is padded to a multiple of four bytes, and the padding is
+/// announced in the encapsulation options, as Fast DDS does.
+pub const GOAL_STATUS_ARRAY: GoldenPayload = GoldenPayload {
+    name: "goal_status_array",
+    type_name: "action_msgs/msg/GoalStatusArray",
+    bytes: &[
+        0x00, 0x01, 0x00, 0x03, // CDR_LE, 3 bytes of padding at the end
+        0x01, 0x00, 0x00, 0x00, // status_list length
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, // goal_id
+        0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, //
+        0x00, 0xf1, 0x53, 0x65, // stamp.sec
+        0x00, 0x65, 0xcd, 0x1d, // stamp.nanosec
+        0x02, // status
+        0x00, 0x00, 0x00, // padding
+    ],
+};
+
+/// `example_interfaces/action/Fibonacci_SendGoal_Request`
+///
+/// Goal id bytes `0, 1, ..., 15`, goal `order: 10`.
+pub const FIBONACCI_SEND_GOAL_REQUEST: GoldenPayload = GoldenPayload {
+    name: "fibonacci_send_goal_request",
+    type_name: "example_interfaces/action/Fibonacci_SendGoal_Request",
+    bytes: &[
+        0x00, 0x01, 0x00, 0x00, // CDR_LE
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, // goal_id
+        0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, //
+        0x0a, 0x00, 0x00, 0x00, // goal.order
+    ],
+};
+
+/// `example_interfaces/srv/AddTwoInts_Request`
+///
+/// `a: 2, b: 3`.
+pub const ADD_TWO_INTS_REQUEST: GoldenPayload = GoldenPayload {
+    name: "add_two_ints_request",
+    type_name: "example_interfaces/srv/AddTwoInts_Request",
+    bytes: &[
+        0x00, 0x01, 0x00, 0x00, // CDR_LE
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // a
+        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // b
+    ],
+};
+
+/// `rcl_interfaces/msg/ParameterValue`
+///
+/// String parameter `"hello"`. All other fields have their default values.
+pub const PARAMETER_VALUE_STRING: GoldenPayload = GoldenPayload {
+    name: "parameter_value_string",
+    type_name: "rcl_interfaces/msg/ParameterValue",
+    bytes: &[
+        0x00, 0x01, 0x00, 0x00, // CDR_LE
+        0x04, // type: PARAMETER_STRING
+        0x00, // bool_value
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // padding
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // integer_value
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // double_value
+        0x06, 0x00, 0x00, 0x00, b'h', b'e', b'l', b'l', b'o', 0x00, // string_value
+        0x00, 0x00, // padding
+        0x00, 0x00, 0x00, 0x00, // byte_array_value length
+        0x00, 0x00, 0x00, 0x00, // bool_array_value length
+        0x00, 0x00, 0x00, 0x00, // integer_array_value length
+        0x00, 0x00, 0x00, 0x00, // double_array_value length
+        0x00, 0x00, 0x00, 0x00, // string_array_value length
+    ],
+};
+
+/// `rcl_interfaces/msg/Log`, as published on `/rosout`.
+///
+/// stamp `1_700_000_000` s `500_000_000` ns, level `INFO` (20), name
+/// `"talker"`, msg `"Hello"`, file `"talker.cpp"`, function `"main"`, line
+/// 42.
+pub const LOG: GoldenPayload = GoldenPayload {
+    name: "log",
+    type_name: "rcl_interfaces/msg/Log",
+    bytes: &[
+        0x00, 0x01, 0x00, 0x00, // CDR_LE
+        0x00, 0xf1, 0x53, 0x65, // stamp.sec
+        0x00, 0x65, 0xcd, 0x1d, // stamp.nanosec
+        0x14, // level
+        0x00, 0x00, 0x00, // padding
+        0x07, 0x00, 0x00, 0x00, b't', b'a', b'l', b'k', b'e', b'r', 0x00, // name
+        0x00, // padding
+        0x06, 0x00, 0x00, 0x00, b'H', b'e', b'l', b'l', b'o', 0x00, // msg
+        0x00, 0x00, // padding
+        0x0b, 0x00, 0x00, 0x00, b't', b'a', b'l', b'k', b'e', b'r', b'.', b'c', b'p', b'p',
+        0x00, // file
+        0x00, // padding
+        0x05, 0x00, 0x00, 0x00, b'm', b'a', b'i', b'n', 0x00, // function
+        0x00, 0x00, 0x00, // padding
+        0x2a, 0x00, 0x00, 0x00, // line
+    ],
+};
+
+/// All golden payloads
+pub const GOLDEN_PAYLOADS: &[GoldenPayload] = &[
+    GOAL_STATUS_ARRAY,
+    FIBONACCI_SEND_GOAL_REQUEST,
+    ADD_TWO_INTS_REQUEST,
+    PARAMETER_VALUE_STRING,
+    LOG,
+];
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        io,
+        process::{Child, Command, Stdio},
+        sync::Mutex,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use futures::{executor::block_on, pin_mut, select, FutureExt};
+    use futures_timer::Delay;
+    use rustdds::Timestamp;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        action::{
+            goal::{GoalInfo, GoalStatus, GoalStatusArray, GoalStatusEnum},
+            ActionClientQosPolicies, ActionServerQosPolicies, AsyncActionServer, GoalEndStatus,
+            SendGoalRequest,
+        },
+        interfaces::{
+            builtin_interfaces::Time,
+            example_interfaces::{
+                fibonacci_action_type_name, AddTwoInts, AddTwoIntsRequest, AddTwoIntsResponse,
+                FibonacciAction, FibonacciFeedback, FibonacciGoal, FibonacciResult,
+            },
+            names::{Name, NodeName},
+            unique_identifier_msgs::UUID,
+        },
+        log::Log,
+        node::{context::Context, Node, NodeOptions},
+        service::{
+            parameters::{raw, ParameterValue},
+            Client, Server, Service, ServiceMapping,
+        },
+    };
+
+    // For types without PartialEq: `value` must serialize to the payload, and
+    // the payload must decode to something that serializes the same way.
+    fn check_bytes<M: Serialize + DeserializeOwned>(golden: &GoldenPayload, value: &M) {
+        let encode = |m: &M| CDRSerializerAdapter::<M>::to_bytes(m).unwrap();
+        assert_eq!(encode(value), golden.data(), "{}", golden.name);
+        let (decoded, _) =
+            deserialize_from_cdr_with_rep_id::<M>(golden.data(), golden.representation()).unwrap();
+        assert_eq!(encode(&decoded), golden.data(), "{}", golden.name);
+    }
+
+    fn goal_id() -> UUID {
+        UUID {
+            uuid: Uuid::from_bytes(std::array::from_fn(|i| i as u8)),
+        }
+    }
+
+    #[test]
+    fn crate_types_match() {
+        check_bytes(
+            &GOAL_STATUS_ARRAY,
+            &GoalStatusArray {
+                status_list: vec![GoalStatus {
+                    goal_info: GoalInfo {
+                        goal_id: goal_id(),
+                        stamp: Time::from_nanos(1_700_000_000_500_000_000),
+                    },
+                    status: GoalStatusEnum::Executing,
+                }],
+            },
+        );
+
+        check_bytes(
+            &FIBONACCI_SEND_GOAL_REQUEST,
+            &SendGoalRequest {
+                goal_id: goal_id(),
+                goal: 10_i32,
+            },
+        );
+        check_bytes(
+            &FIBONACCI_SEND_GOAL_REQUEST,
+            &SendGoalRequest {
+                goal_id: goal_id(),
+                goal: FibonacciGoal { order: 10 },
+            },
+        );
+
+        check_bytes(
+            &PARAMETER_VALUE_STRING,
+            &raw::ParameterValue::from(ParameterValue::from("hello")),
+        );
+
+        // Log uses an RTPS Timestamp, which has the same layout as
+        // builtin_interfaces/Time.
+        check_bytes(
+            &LOG,
+            &Log {
+                timestamp: Timestamp::from_ticks((1_700_000_000 << 32) | 500_000_000),
+                level: Log::INFO,
+                name: "talker".to_owned(),
+                msg: "Hello".to_owned(),
+                file: "talker.cpp".to_owned(),
+                function: "main".to_owned(),
+                line: 42,
+            },
+        );
+    }
+
+    #[test]
+    fn verify() {
+        ADD_TWO_INTS_REQUEST
+            .verify(&AddTwoIntsRequest { a: 2, b: 3 })
+            .unwrap();
+        assert!(matches!(
+            ADD_TWO_INTS_REQUEST.verify(&AddTwoIntsRequest { a: 3, b: 2 }),
+            Err(InteropError::ValueMismatch { .. })
+        ));
+
+        // Decodes to the expected value, but leaves bytes unread.
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Short {
+            a: i64,
+        }
+        let err = ADD_TWO_INTS_REQUEST.verify(&Short { a: 2 }).unwrap_err();
+        assert!(matches!(err, InteropError::BytesMismatch { .. }), "{err}");
+    }
+
+    // End-to-end tests. The in-process ones run a ros2-client Client or
+    // Server against a ros2-client counterpart. The others run the ROS 2
+    // tutorial programs with `ros2 run`, and are skipped when `ros2` is not
+    // on the PATH.
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    // The ROS 2 programs use fixed Service and Action names, so the tests
+    // using them must not run concurrently.
+    static ROS2_RUN: Mutex<()> = Mutex::new(());
+
+    fn within<F: Future>(timeout: Duration, future: F) -> F::Output {
+        block_on(async {
+            let future = future.fuse();
+            pin_mut!(future);
+            select! {
+                output = future => output,
+                _ = Delay::new(timeout).fuse() => panic!("timed out after {timeout:?}"),
+            }
+        })
+    }
+
+    // `Client::wait_for_service` can miss a match that happens while it
+    // starts, so wait in short rounds.
+    async fn wait_for_service<S>(client: &Client<S>, node: &Node)
+    where
+        S: Service + 'static,
+    {
+        loop {
+            let waiting = client.wait_for_service(node).fuse();
+            pin_mut!(waiting);
+            select! {
+                _ = waiting => return,
+                _ = Delay::new(Duration::from_millis(100)).fuse() => {}
+            }
+        }
+    }
+
+    fn test_node(name: &str) -> Node {
+        Context::new()
+            .unwrap()
+            .new_node(
+                NodeName::new("/ros2_client_interop", name).unwrap(),
+                NodeOptions::new().enable_rosout(false),
+            )
+            .unwrap()
+    }
+
+    fn fibonacci(order: i32) -> Vec<i32> {
+        let mut sequence = vec![0, 1];
+        for i in 1..order as usize {
+            sequence.push(sequence[i] + sequence[i - 1]);
+        }
+        sequence
+    }
+
+    async fn serve_add_two_ints(server: &Server<AddTwoInts>) -> AddTwoIntsRequest {
+        let (req_id, request) = server.async_receive_request().await.unwrap();
+        let response = AddTwoIntsResponse {
+            sum: request.a + request.b,
+        };
+        server.async_send_response(req_id, response).await.unwrap();
+        request
+    }
+
+    async fn serve_fibonacci(server: &mut AsyncActionServer<FibonacciAction>) -> FibonacciGoal {
+        let new_goal = server.receive_new_goal().await.unwrap();
+        let goal = *server.get_new_goal(new_goal).unwrap();
+        let accepted = server.accept_goal(new_goal).await.unwrap();
+        let executing = server.start_executing_goal(accepted).await.unwrap();
+        let mut sequence = vec![0, 1];
+        for i in 1..goal.order as usize {
+            sequence.push(sequence[i] + sequence[i - 1]);
+            let feedback = FibonacciFeedback {
+                sequence: sequence.clone(),
+            };
+            server.publish_feedback(executing, feedback).await.unwrap();
+        }
+        server
+            .send_result_response(
+                executing,
+                GoalEndStatus::Succeeded,
+                FibonacciResult { sequence },
+            )
+            .await
+            .unwrap();
+        goal
+    }
+
+    fn add_two_ints_client(node: &mut Node, name: &str, a: i64, b: i64) -> AddTwoIntsResponse {
+        let client = node
+            .create_client_for::<AddTwoInts>(&Name::new("/", name).unwrap())
+            .unwrap();
+        within(TIMEOUT, async {
+            wait_for_service(&client, node).await;
+            client
+                .async_call_service(AddTwoIntsRequest { a, b })
+                .await
+                .unwrap()
+        })
+    }
+
+    fn fibonacci_client(node: &mut Node, name: &str, order: i32) -> FibonacciResult {
+        let mut client = node
+            .create_action_client::<FibonacciAction>(
+                ServiceMapping::Enhanced,
+                &Name::new("/", name).unwrap(),
+                &fibonacci_action_type_name(),
+                ActionClientQosPolicies::default(),
+            )
+            .unwrap();
+        within(TIMEOUT, wait_for_service(client.goal_client(), node));
+        within(TIMEOUT, wait_for_service(client.result_client(), node));
+        within(TIMEOUT, async {
+            let (goal_id, response) = client
+                .async_send_goal(FibonacciGoal { order })
+                .await
+                .unwrap();
+            assert!(response.accepted);
+            let (status, result) = client.async_request_result(goal_id).await.unwrap();
+            assert_eq!(status, GoalStatusEnum::Succeeded);
+            result
+        })
+    }
+
+    fn fibonacci_server(node: &mut Node, name: &str) -> AsyncActionServer<FibonacciAction> {
+        AsyncActionServer::new(
+            node.create_action_server::<FibonacciAction>(
+                ServiceMapping::Enhanced,
+                &Name::new("/", name).unwrap(),
+                &fibonacci_action_type_name(),
+                ActionServerQosPolicies::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn add_two_ints_in_process() {
+        let mut server_node = test_node("add_two_ints_server");
+        let mut node = test_node("add_two_ints_client");
+        let _spinner = node.spin_in_background().unwrap();
+        let name = "interop_add_two_ints";
+        let server = server_node
+            .create_server_for::<AddTwoInts>(&Name::new("/", name).unwrap())
+            .unwrap();
+        let served = thread::spawn(move || within(TIMEOUT, serve_add_two_ints(&server)));
+
+        assert_eq!(add_two_ints_client(&mut node, name, 2, 3).sum, 5);
+        assert_eq!(served.join().unwrap(), AddTwoIntsRequest { a: 2, b: 3 });
+    }
+
+    #[test]
+    fn fibonacci_in_process() {
+        let mut server_node = test_node("fibonacci_server");
+        let mut node = test_node("fibonacci_client");
+        let _spinner = node.spin_in_background().unwrap();
+        let name = "interop_fibonacci";
+        let mut server = fibonacci_server(&mut server_node, name);
+        let served = thread::spawn(move || within(TIMEOUT, serve_fibonacci(&mut server)));
+
+        let result = fibonacci_client(&mut node, name, 10);
+        assert_eq!(result.sequence, fibonacci(10));
+        assert_eq!(result.sequence.last(), Some(&55));
+        assert_eq!(served.join().unwrap(), FibonacciGoal { order: 10 });
+    }
+
+    // A program started with `ros2 run`, killed when dropped
+    struct Ros2Run(Child);
+
+    impl Ros2Run {
+        fn start(package: &str, executable: &str) -> Option<Ros2Run> {
+            let child = Command::new("ros2")
+                .args(["run", package, executable])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .spawn();
+            match child {
+                Ok(child) => Some(Ros2Run(child)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    eprintln!("`ros2` not found, skipping {package} {executable}");
+                    None
+                }
+                Err(e) => panic!("Cannot run {package} {executable}: {e}"),
+            }
+        }
+
+        fn wait_success(&mut self) {
+            let start = Instant::now();
+            loop {
+                if let Some(status) = self.0.try_wait().unwrap() {
+                    assert!(status.success(), "{status}");
+                    return;
+                }
+                assert!(start.elapsed() < TIMEOUT, "still running after {TIMEOUT:?}");
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+
+    impl Drop for Ros2Run {
+        fn drop(&mut self) {
+            self.0.kill().ok();
+            self.0.wait().ok();
+        }
+    }
+
+    #[test]
+    fn add_two_ints_with_rclcpp_server() {
+        let _lock = ROS2_RUN.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(_server) = Ros2Run::start("demo_nodes_cpp", "add_two_ints_server") else {
+            return;
+        };
+        let mut node = test_node("add_two_ints_client");
+        let _spinner = node.spin_in_background().unwrap();
+
+        assert_eq!(add_two_ints_client(&mut node, "add_two_ints", 2, 3).sum, 5);
+    }
+
+    #[test]
+    fn add_two_ints_with_rclpy_client() {
+        let _lock = ROS2_RUN.lock().unwrap_or_else(|e| e.into_inner());
+        let mut node = test_node("add_two_ints_server");
+        let server = node
+            .create_server_for::<AddTwoInts>(&Name::new("/", "add_two_ints").unwrap())
+            .unwrap();
+        let Some(mut client) = Ros2Run::start("examples_rclpy_minimal_client", "client") else {
+            return;
+        };
+
+        // The client asks for 41 + 1.
+        let request = within(TIMEOUT, serve_add_two_ints(&server));
+        assert_eq!(request, AddTwoIntsRequest { a: 41, b: 1 });
+        client.wait_success();
+    }
+
+    #[test]
+    fn fibonacci_with_rclpy_server() {
+        let _lock = ROS2_RUN.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(_server) = Ros2Run::start("examples_rclpy_minimal_action_server", "server") else {
+            return;
+        };
+        let mut node = test_node("fibonacci_client");
+        let _spinner = node.spin_in_background().unwrap();
+
+        let result = fibonacci_client(&mut node, "fibonacci", 10);
+        assert_eq!(result.sequence, fibonacci(10));
+    }
+
+    #[test]
+    fn fibonacci_with_rclcpp_client() {
+        let _lock = ROS2_RUN.lock().unwrap_or_else(|e| e.into_inner());
+        let mut node = test_node("fibonacci_server");
+        let mut server = fibonacci_server(&mut node, "fibonacci");
+        let Some(mut client) = Ros2Run::start(
+            "examples_rclcpp_minimal_action_client",
+            "action_client_not_composable",
+        ) else {
+            return;
+        };
+
+        // The client asks for order 10, and exits when it has the result.
+        let goal = within(TIMEOUT, serve_fibonacci(&mut server));
+        assert_eq!(goal, FibonacciGoal { order: 10 });
+        client.wait_success();
+    }
+}
@@ -0,0 +1,434 @@
+//! Starting Nodes from a launch description
+//!
+//! A [`LaunchDescription`] lists the Nodes of one process, with their
+//! parameters and remapping rules. It is read from TOML or YAML, and
+//! [`Launcher::launch`] creates the Nodes in a [`Supervisor`]. This covers the
+//! common "one process, several Nodes" deployment without the Python launch
+//! system.
+//!
+//! A Node may name a component. Components are the application code of a
+//! Node: Functions registered with [`Launcher::register_component`], which
+//! create the Publishers, Subscriptions, etc. of the Node and return the task
+//! that runs it. A Node without a component only spins, e.g. to serve its
+//! parameters.
+//!
+//! ```toml
+//! [[node]]
+//! name = "talker"
+//! namespace = "/demo"
+//! component = "talker"
+//! remappings = ["chatter:=/demo/talk"]
+//! respawn = true
+//!
+//! [node.parameters]
+//! rate = 10.0
+//! greeting = "hello"
+//! ```
+//!
+//! The same in YAML:
+//!
+//! ```yaml
+//! nodes:
+//!   - name: talker
+//!     namespace: /demo
+//!     component: talker
+//!     remappings: ["chatter:=/demo/talk"]
+//!     respawn: true
+//!     parameters:
+//!       rate: 10.0
+//!       greeting: hello
+//! ```
+//!
+//! This requires feature `launch`.
+//!
+//! ```ignore
+//! let mut launcher = Launcher::new();
+//! launcher.register_component("talker", |node, heartbeat| {
+//!     let publisher = node.advertise::<String>("chatter")?;
+//!     Ok(async move { ... }.boxed())
+//! });
+//! let description = LaunchDescription::from_toml(&std::fs::read_to_string("demo.toml")?)?;
+//! let mut supervisor = Supervisor::new();
+//! launcher.launch(&Context::new()?, &description, &mut supervisor)?;
+//! loop {
+//!     supervisor.poll();
+//!     std::thread::sleep(Duration::from_millis(100));
+//! }
+//! ```
+
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use futures::future::BoxFuture;
+use rustdds::dds::{CreateError, CreateResult};
+use serde::Deserialize;
+
+use crate::{
+    node::{
+        context::Context,
+        remapping::RemapRule,
+        supervisor::{Heartbeat, NodeIndex, RestartOptions, RestartPolicy, Supervisor},
+        Node, NodeCreateError, NodeOptions,
+    },
+    prelude::{NodeName, ParameterValue},
+};
+
+/// Error in reading a launch description or in launching it
+#[derive(Debug)]
+pub enum LaunchError {
+    /// The description is not valid TOML or YAML, or does not have the
+    /// expected structure.
+    Parse(String),
+    /// The name or namespace of a Node is not valid. The values are the Node
+    /// name and the reason.
+    BadNodeName(String, String),
+    /// A remapping rule is not valid. The values are the Node name and the
+    /// reason.
+    BadRemapping(String, String),
+    /// The component of a Node has not been registered. The values are the
+    /// Node name and the component name.
+    UnknownComponent(String, String),
+    /// Node creation failed. The first value is the Node name.
+    NodeCreate(String, NodeCreateError),
+    /// A task of the Node could not be started. The first value is the Node
+    /// name.
+    Task(String, CreateError),
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LaunchError::Parse(e) => write!(f, "Bad launch description: {e}"),
+            LaunchError::BadNodeName(node, e) => write!(f, "Node {node:?}: bad name: {e}"),
+            LaunchError::BadRemapping(node, e) => write!(f, "Node {node:?}: {e}"),
+            LaunchError::UnknownComponent(node, component) => {
+                write!(f, "Node {node:?}: unknown component {component:?}")
+            }
+            LaunchError::NodeCreate(node, e) => write!(f, "Node {node:?}: {e}"),
+            LaunchError::Task(node, e) => write!(f, "Node {node:?}: cannot start task: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
+/// The Nodes of a process
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LaunchDescription {
+    /// Written as `[[node]]` tables in TOML, or as a `nodes` list in YAML.
+    #[serde(default, alias = "node")]
+    pub nodes: Vec<NodeDescription>,
+}
+
+impl LaunchDescription {
+    pub fn from_toml(description: &str) -> Result<LaunchDescription, LaunchError> {
+        toml::from_str(description).map_err(|e| LaunchError::Parse(e.to_string()))
+    }
+
+    pub fn from_yaml(description: &str) -> Result<LaunchDescription, LaunchError> {
+        serde_yaml::from_str(description).map_err(|e| LaunchError::Parse(e.to_string()))
+    }
+}
+
+/// One Node of a [`LaunchDescription`]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeDescription {
+    /// Base name of the Node
+    pub name: String,
+    /// The default is the root namespace `/`.
+    #[serde(default = "root_namespace")]
+    pub namespace: String,
+    /// Name of a component registered with [`Launcher::register_component`]
+    #[serde(default)]
+    pub component: Option<String>,
+    /// Declared parameters and their initial values
+    #[serde(default)]
+    pub parameters: BTreeMap<String, LaunchParameterValue>,
+    /// Remapping rules in the command line format `from:=to`
+    #[serde(default)]
+    pub remappings: Vec<String>,
+    /// Restart the Spinner and the component when they fail. The default is
+    /// `false`, as in ROS 2 launch.
+    #[serde(default)]
+    pub respawn: bool,
+    /// The default is `true`.
+    #[serde(default = "default_true")]
+    pub enable_rosout: bool,
+}
+
+fn root_namespace() -> String {
+    "/".to_owned()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A parameter value in a launch description
+///
+/// The type is inferred from the value, as in ROS 2 parameter files. An empty
+/// array is a `StringArray`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum LaunchParameterValue {
+    Boolean(bool),
+    Integer(i64),
+    Double(f64),
+    String(String),
+    BooleanArray(Vec<bool>),
+    IntegerArray(Vec<i64>),
+    DoubleArray(Vec<f64>),
+    StringArray(Vec<String>),
+}
+
+impl From<LaunchParameterValue> for ParameterValue {
+    fn from(value: LaunchParameterValue) -> ParameterValue {
+        match value {
+            LaunchParameterValue::Boolean(b) => ParameterValue::Boolean(b),
+            LaunchParameterValue::Integer(i) => ParameterValue::Integer(i),
+            LaunchParameterValue::Double(d) => ParameterValue::Double(d),
+            LaunchParameterValue::String(s) => ParameterValue::String(s),
+            LaunchParameterValue::BooleanArray(a) if a.is_empty() => {
+                ParameterValue::StringArray(Vec::new())
+            }
+            LaunchParameterValue::BooleanArray(a) => ParameterValue::BooleanArray(a),
+            LaunchParameterValue::IntegerArray(a) => ParameterValue::IntegerArray(a),
+            LaunchParameterValue::DoubleArray(a) => ParameterValue::DoubleArray(a),
+            LaunchParameterValue::StringArray(a) => ParameterValue::StringArray(a),
+        }
+    }
+}
+
+type ComponentFactory = Arc<
+    dyn Fn(&mut Node, Heartbeat) -> CreateResult<BoxFuture<'static, Result<(), String>>>
+        + Send
+        + Sync,
+>;
+
+/// Creates the Nodes of a [`LaunchDescription`]. See the
+/// [module documentation](self).
+#[derive(Default)]
+pub struct Launcher {
+    components: BTreeMap<String, ComponentFactory>,
+}
+
+impl Launcher {
+    pub fn new() -> Launcher {
+        Launcher::default()
+    }
+
+    /// Register component `name`.
+    ///
+    /// `factory` is called for each Node that uses the component, and again
+    /// whenever the Node is respawned. It gets the Node and the [`Heartbeat`]
+    /// of the task, as in [`Supervisor::add_task`].
+    pub fn register_component<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&mut Node, Heartbeat) -> CreateResult<BoxFuture<'static, Result<(), String>>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.components.insert(name.to_owned(), Arc::new(factory));
+    }
+
+    /// Names of the registered components
+    pub fn components(&self) -> Vec<String> {
+        self.components.keys().cloned().collect()
+    }
+
+    /// Create the Nodes of `description` in `context`, and start their
+    /// Spinners and components in `supervisor`.
+    ///
+    /// The whole description is checked before any Node is created, so that
+    /// a misspelled component or remapping does not leave half of the Nodes
+    /// running. Node creation itself may still fail in the middle.
+    pub fn launch(
+        &self,
+        context: &Context,
+        description: &LaunchDescription,
+        supervisor: &mut Supervisor,
+    ) -> Result<Vec<NodeIndex>, LaunchError> {
+        let prepared = description
+            .nodes
+            .iter()
+            .map(|node| self.prepare(node))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut indices = Vec::with_capacity(prepared.len());
+        for (description, node_name, options, component) in prepared {
+            let restart = RestartOptions::new().policy(if description.respawn {
+                RestartPolicy::OnFailure
+            } else {
+                RestartPolicy::Never
+            });
+            let node = context
+                .new_node(node_name, options)
+                .map_err(|e| LaunchError::NodeCreate(description.name.clone(), e))?;
+            let index = supervisor
+                .add_node(node, Some(restart.clone()))
+                .map_err(|e| LaunchError::Task(description.name.clone(), e))?;
+            if let (Some(name), Some(factory)) = (&description.component, component) {
+                supervisor
+                    .add_task(index, name, restart, move |node, heartbeat| {
+                        factory(node, heartbeat)
+                    })
+                    .map_err(|e| LaunchError::Task(description.name.clone(), e))?;
+            }
+            indices.push(index);
+        }
+        Ok(indices)
+    }
+
+    // Check a Node description and make its NodeOptions.
+    fn prepare<'a>(
+        &self,
+        description: &'a NodeDescription,
+    ) -> Result<
+        (
+            &'a NodeDescription,
+            NodeName,
+            NodeOptions,
+            Option<ComponentFactory>,
+        ),
+        LaunchError,
+    > {
+        let node_name = NodeName::new(&description.namespace, &description.name)
+            .map_err(|e| LaunchError::BadNodeName(description.name.clone(), e.to_string()))?;
+        let component = match &description.component {
+            Some(component) => Some(self.components.get(component).cloned().ok_or_else(|| {
+                LaunchError::UnknownComponent(description.name.clone(), component.clone())
+            })?),
+            None => None,
+        };
+        // Command line arguments of the process are meant for one Node, not all
+        // of them.
+        let mut options = NodeOptions::new()
+            .use_global_arguments(false)
+            .enable_rosout(description.enable_rosout);
+        for rule in &description.remappings {
+            let rule = RemapRule::parse(rule)
+                .map_err(|e| LaunchError::BadRemapping(description.name.clone(), e))?;
+            options = options.remap(rule);
+        }
+        for (name, value) in &description.parameters {
+            options = options.declare_parameter(name, value.clone().into());
+        }
+        Ok((description, node_name, options, component))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_and_yaml_agree() {
+        let toml = r#"
+            [[node]]
+            name = "talker"
+            namespace = "/demo"
+            component = "talker"
+            remappings = ["chatter:=/demo/talk"]
+            respawn = true
+
+            [node.parameters]
+            rate = 10.0
+            count = 3
+            greeting = "hello"
+            topics = ["a", "b"]
+
+            [[node]]
+            name = "listener"
+        "#;
+        let yaml = r#"
+nodes:
+  - name: talker
+    namespace: /demo
+    component: talker
+    remappings: ["chatter:=/demo/talk"]
+    respawn: true
+    parameters:
+      rate: 10.0
+      count: 3
+      greeting: hello
+      topics: [a, b]
+  - name: listener
+"#;
+        let from_toml = LaunchDescription::from_toml(toml).unwrap();
+        let from_yaml = LaunchDescription::from_yaml(yaml).unwrap();
+        assert_eq!(from_toml, from_yaml);
+
+        let talker = &from_toml.nodes[0];
+        assert_eq!(
+            talker.parameters["rate"],
+            LaunchParameterValue::Double(10.0)
+        );
+        assert_eq!(talker.parameters["count"], LaunchParameterValue::Integer(3));
+        assert_eq!(
+            ParameterValue::from(talker.parameters["topics"].clone()),
+            ParameterValue::StringArray(vec!["a".to_owned(), "b".to_owned()])
+        );
+
+        let listener = &from_toml.nodes[1];
+        assert_eq!(listener.namespace, "/");
+        assert_eq!(listener.component, None);
+        assert!(!listener.respawn);
+        assert!(listener.enable_rosout);
+    }
+
+    #[test]
+    fn unknown_field_is_rejected() {
+        let toml = r#"
+            [[node]]
+            name = "talker"
+            respawm = true
+        "#;
+        assert!(matches!(
+            LaunchDescription::from_toml(toml),
+            Err(LaunchError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn bad_descriptions_are_rejected_before_launch() {
+        let launcher = Launcher::new();
+        let description = |toml: &str| LaunchDescription::from_toml(toml).unwrap();
+
+        let unknown_component = description(
+            r#"
+            [[node]]
+            name = "talker"
+            component = "talker"
+        "#,
+        );
+        assert!(matches!(
+            launcher.prepare(&unknown_component.nodes[0]),
+            Err(LaunchError::UnknownComponent(_, _))
+        ));
+
+        let bad_remapping = description(
+            r#"
+            [[node]]
+            name = "talker"
+            remappings = ["chatter"]
+        "#,
+        );
+        assert!(matches!(
+            launcher.prepare(&bad_remapping.nodes[0]),
+            Err(LaunchError::BadRemapping(_, _))
+        ));
+
+        let bad_name = description(
+            r#"
+            [[node]]
+            name = "1talker"
+        "#,
+        );
+        assert!(matches!(
+            launcher.prepare(&bad_name.nodes[0]),
+            Err(LaunchError::BadNodeName(_, _))
+        ));
+    }
+}
@@ -46,9 +46,7 @@
 //!     // print into the ROS 2 domain's `rosout` topic.
 //!     //
 //!     // Or, in other words, we can print to a shared 'console'.
-//!     let node_options = NodeOptions::new()
-//!         .enable_rosout(true) // this is on by default but shh...
-//!         .declare_parameter("is_cool", ParameterValue::Boolean(true));
+//!     let node_options = NodeOptions::new().enable_rosout(true); // this is on by default but shh...
 //!
 //!     // Finally, we can make the node:
 //!     ctx.new_node(node_name, node_options)
@@ -128,6 +126,17 @@
 //! - DDS
 //!   - ✅ Discovery (ROS Graph update events, async)
 //!   - ✅ QoS
+//!     - ❌ Partition (not implemented in RustDDS)
+//! - Other transports
+//!   - ❌ Zenoh (`rmw_zenoh`). `ros2-client` is built directly on RustDDS.
+//!   - ❌ Shared memory (e.g. iceoryx). RustDDS communicates over UDP, also
+//!     within a host.
+//!
+//! Actions, Parameters, `rosout` and Action introspection are behind the
+//! default Cargo features `actions`, `parameters`, `rosout` and
+//! `introspection`. Binaries that only need Topics and Services, such as
+//! sensor bridges on embedded Linux, can leave them out with
+//! `default-features = false`.
 //!
 //! ## Compatibility (with ROS 2 Releases)
 //!
@@ -154,19 +163,42 @@
 //!
 //! This crate is licensed under the Apache License, Version 2.0. See the [LICENSE file](./LICENSE) for additional information.
 
+#[cfg(feature = "actions")]
 pub mod action;
+pub mod barrier;
+pub mod domain_bridge;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod interfaces;
+#[cfg(feature = "interop-tests")]
+pub mod interop;
+#[cfg(feature = "launch")]
+pub mod launch;
 pub mod log;
+#[cfg(feature = "mcap")]
+pub mod mcap;
 pub mod message;
 pub mod node;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod qos;
+#[cfg(feature = "no-default-runtime")]
+pub mod readiness;
+#[cfg(feature = "urdf")]
+pub mod robot_state;
+#[cfg(feature = "rosout-monitor")]
+pub mod rosout_monitor;
+#[cfg(feature = "scenario")]
+pub mod scenario;
 pub mod service;
 pub mod time;
 pub mod topic;
 
 /// Common types in this crate.
 pub mod prelude {
+    #[cfg(feature = "actions")]
     pub use crate::action::{Action, ActionTypes, GoalHandle as _};
-    pub use crate::message::{message_info::MessageInfo, Message};
+    pub use crate::message::{message_info::MessageInfo, Message, NamedMessage};
     pub use crate::topic::Topic;
 
     pub use crate::interfaces::{
@@ -179,21 +211,27 @@ pub mod prelude {
         client::CallServiceError,
         client::Client,
         parameters::{Parameter, ParameterValue},
+        request_id::RmwRequestId,
         server::Server,
-        AService, Service, ServiceMapping,
+        AService, NamedService, Service, ServiceMapping,
     };
 
     pub use crate::node::{
-        context::{Context, ContextOptions, DEFAULT_PUBLISHER_QOS, DEFAULT_SUBSCRIPTION_QOS},
+        context::{
+            Context, ContextOptions, TransportLimits, DEFAULT_CLIENT_RESPONSE_QOS,
+            DEFAULT_PUBLISHER_QOS, DEFAULT_SERVICE_QOS, DEFAULT_SUBSCRIPTION_QOS,
+        },
         pubsub::{Publisher, Subscription},
+        spin_handle::SpinHandle,
+        stream_ext::MessageStreamExt,
         Node, NodeCreateError, NodeEvent, NodeOptions, Spinner,
     };
 
     // time
-    pub use crate::time::{ros_time::ROSTime, ros_time::SystemTime};
+    pub use crate::time::{ros_time::ROSTime, ros_time::SystemTime, steady_time::SteadyTime};
 
     // logging
-    pub use crate::log::{Log, LogLevel};
+    pub use crate::log::{Log, LogLevel, LogSink};
     pub use crate::rosout;
 
     /// Stuff related to the [`rustdds`] crate. In a separate prelude since
@@ -201,7 +239,10 @@ pub mod prelude {
     pub mod dds {
         pub use rustdds::{
             dds::WriteError,
-            policy::{Deadline, Durability, History, Lifespan, Liveliness, Reliability},
+            policy::{
+                Deadline, Durability, History, LatencyBudget, Lifespan, Liveliness, Ownership,
+                Reliability, ResourceLimits,
+            },
             DomainParticipant, Duration as DdsDuration, QosPolicies, QosPolicyBuilder, Timestamp,
         };
     }
@@ -3,6 +3,9 @@
 use rustdds::*;
 use serde::{Deserialize, Serialize};
 
+#[cfg(unix)]
+pub mod sink;
+
 /// Log message structure, communicated over the rosout Topic.
 ///
 /// [Log](https://github.com/ros2/rcl_interfaces/blob/master/rcl_interfaces/msg/Log.msg)
@@ -71,3 +74,48 @@ pub enum LogLevel {
 }
 
 //impl From<u8> for Level
+
+/// Destination for log messages written by a Node, besides rosout
+///
+/// See [`NodeOptions::log_sink`](crate::node::NodeOptions::log_sink). The
+/// `sink` module has implementations for syslog and journald.
+pub trait LogSink: Send + Sync {
+    fn write(&self, log: &Log);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::node::{
+        context::{tests::test_node, Context},
+        NodeOptions,
+    };
+
+    #[test]
+    fn log_sink() {
+        struct Collect(Mutex<Vec<Log>>);
+        impl LogSink for Collect {
+            fn write(&self, log: &Log) {
+                self.0.lock().unwrap().push(log.clone());
+            }
+        }
+
+        let sink = Arc::new(Collect(Mutex::new(Vec::new())));
+        let context = Context::new().unwrap();
+        let node = test_node(
+            &context,
+            "test_log_sink_node",
+            NodeOptions::new()
+                .enable_rosout(false)
+                .log_sink(sink.clone()),
+        );
+        crate::rosout!(node, LogLevel::Warn, "low battery {}", 12);
+        let logs = sink.0.lock().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].level, Log::WARN);
+        assert_eq!(logs[0].name, "test_log_sink_node");
+        assert_eq!(logs[0].msg, "low battery 12");
+    }
+}
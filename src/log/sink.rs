@@ -0,0 +1,190 @@
+//! Forwarding log messages to syslog or journald
+//!
+//! On robots managed by systemd, `/rosout` is often not collected by anyone.
+//! A [`LogSink`] given to
+//! [`NodeOptions::log_sink`](crate::node::NodeOptions::log_sink) receives every
+//! message the Node writes with the [`rosout`](crate::rosout!) macro, whether
+//! rosout is enabled or not.
+//!
+//! [`SyslogSink`] and [`JournaldSink`] also implement [`log::Log`], so they
+//! can be installed as the global logger to forward the warnings of this crate
+//! and RustDDS as well:
+//!
+//! ```ignore
+//! let sink = Arc::new(JournaldSink::new()?);
+//! log::set_boxed_logger(Box::new(JournaldSink::new()?))?;
+//! log::set_max_level(log::LevelFilter::Warn);
+//! let node = context.new_node(node_name, NodeOptions::new().log_sink(sink))?;
+//! ```
+
+use std::{ffi::CString, io, os::unix::net::UnixDatagram};
+
+use rustdds::Timestamp;
+
+use super::{Log, LogSink};
+
+// Syslog priority of a ROS 2 severity level
+fn priority(level: u8) -> libc::c_int {
+    match level {
+        l if l >= Log::FATAL => libc::LOG_CRIT,
+        l if l >= Log::ERROR => libc::LOG_ERR,
+        l if l >= Log::WARN => libc::LOG_WARNING,
+        l if l >= Log::INFO => libc::LOG_INFO,
+        _ => libc::LOG_DEBUG,
+    }
+}
+
+// A `log` crate record as a rosout message
+fn log_from_record(record: &log::Record) -> Log {
+    Log {
+        timestamp: Timestamp::now(),
+        level: match record.level() {
+            log::Level::Error => Log::ERROR,
+            log::Level::Warn => Log::WARN,
+            log::Level::Info => Log::INFO,
+            log::Level::Debug | log::Level::Trace => Log::DEBUG,
+        },
+        name: record.target().to_owned(),
+        msg: record.args().to_string(),
+        file: record.file().unwrap_or_default().to_owned(),
+        function: String::new(),
+        line: record.line().unwrap_or(0),
+    }
+}
+
+/// Writes log messages to the system log with `syslog(3)`.
+///
+/// Messages are prefixed with the logger name, and the facility is
+/// `LOG_USER`.
+#[derive(Clone, Debug, Default)]
+pub struct SyslogSink {}
+
+impl SyslogSink {
+    pub fn new() -> SyslogSink {
+        SyslogSink {}
+    }
+}
+
+impl LogSink for SyslogSink {
+    fn write(&self, log: &Log) {
+        let text = format!("[{}] {}", log.name, log.msg).replace('\0', "");
+        // Cannot fail, as NUL bytes were removed.
+        let text = CString::new(text).unwrap_or_default();
+        // Safety: The format string takes exactly one C string argument.
+        unsafe {
+            libc::syslog(
+                libc::LOG_USER | priority(log.level),
+                c"%s".as_ptr(),
+                text.as_ptr(),
+            );
+        }
+    }
+}
+
+impl log::Log for SyslogSink {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.write(&log_from_record(record));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Writes log messages to the systemd journal, using its native protocol.
+///
+/// Besides `MESSAGE` and `PRIORITY`, entries have fields `ROS_LOGGER_NAME`,
+/// `CODE_FILE`, `CODE_LINE` and `CODE_FUNC`, so that e.g.
+/// `journalctl ROS_LOGGER_NAME=talker` shows the messages of one logger.
+#[derive(Debug)]
+pub struct JournaldSink {
+    socket: UnixDatagram,
+}
+
+impl JournaldSink {
+    /// Path of the journald socket
+    pub const SOCKET_PATH: &'static str = "/run/systemd/journal/socket";
+
+    /// Connect to journald. This fails if journald is not running.
+    pub fn new() -> io::Result<JournaldSink> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(Self::SOCKET_PATH)?;
+        Ok(JournaldSink { socket })
+    }
+}
+
+// Journal entry in the native protocol. Values containing newlines use the
+// binary form with an explicit length.
+fn journal_entry(log: &Log) -> Vec<u8> {
+    let mut entry = Vec::new();
+    let mut field = |name: &str, value: &str| {
+        entry.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            entry.push(b'\n');
+            entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            entry.push(b'=');
+        }
+        entry.extend_from_slice(value.as_bytes());
+        entry.push(b'\n');
+    };
+    field("MESSAGE", &log.msg);
+    field("PRIORITY", &priority(log.level).to_string());
+    field("ROS_LOGGER_NAME", &log.name);
+    if !log.file.is_empty() {
+        field("CODE_FILE", &log.file);
+        field("CODE_LINE", &log.line.to_string());
+    }
+    if !log.function.is_empty() {
+        field("CODE_FUNC", &log.function);
+    }
+    entry
+}
+
+impl LogSink for JournaldSink {
+    fn write(&self, log: &Log) {
+        // There is nowhere to report the error, as this is the log.
+        let _ = self.socket.send(&journal_entry(log));
+    }
+}
+
+impl log::Log for JournaldSink {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.write(&log_from_record(record));
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_entry_format() {
+        let log = Log {
+            timestamp: Timestamp::ZERO,
+            level: Log::WARN,
+            name: "talker".to_owned(),
+            msg: "two\nlines".to_owned(),
+            file: "main.rs".to_owned(),
+            function: String::new(),
+            line: 7,
+        };
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&9u64.to_le_bytes());
+        expected.extend_from_slice(b"two\nlines\n");
+        expected.extend_from_slice(b"PRIORITY=4\nROS_LOGGER_NAME=talker\n");
+        expected.extend_from_slice(b"CODE_FILE=main.rs\nCODE_LINE=7\n");
+        assert_eq!(journal_entry(&log), expected);
+
+        assert_eq!(priority(Log::FATAL), libc::LOG_CRIT);
+        assert_eq!(priority(Log::DEBUG), libc::LOG_DEBUG);
+    }
+}
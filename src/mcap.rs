@@ -0,0 +1,364 @@
+//! Streaming [MCAP](https://mcap.dev) writer
+//!
+//! [`McapWriter`] writes received messages in the MCAP container format, as
+//! used by rosbag2 and Foxglove, to any [`Write`] sink. The output is written
+//! incrementally, without chunks or summary section, so it can be streamed
+//! over a socket to a live viewer as well as written to a file.
+//!
+//! Messages are stored in serialized form, as returned by
+//! [`Subscription::take_serialized`](crate::node::pubsub::Subscription::take_serialized),
+//! so they do not need to be deserialized and serialized again.
+//!
+//! Messages can be compressed per channel, see [`Compression`]. Compressed
+//! messages are written in chunks, which delays them until a chunk is full or
+//! the writer is flushed.
+//!
+//! This requires feature `mcap`.
+//!
+//! ```ignore
+//! let mut mcap = McapWriter::new(TcpStream::connect("viewer:9000")?)?;
+//! let channel = mcap.add_channel(&subscription.topic_name(), &type_name, Some(msg_definition))?;
+//! loop {
+//!     if let Some((data, info)) = subscription.take_serialized()? {
+//!         mcap.write_message(channel, &data, &info)?;
+//!     }
+//! }
+//! ```
+
+use std::io::{self, Write};
+
+use crate::{interfaces::names::MessageTypeName, message::message_info::MessageInfo};
+
+const MAGIC: &[u8] = b"\x89MCAP0\r\n";
+
+// Record opcodes
+const OP_HEADER: u8 = 0x01;
+const OP_FOOTER: u8 = 0x02;
+const OP_SCHEMA: u8 = 0x03;
+const OP_CHANNEL: u8 = 0x04;
+const OP_MESSAGE: u8 = 0x05;
+const OP_CHUNK: u8 = 0x06;
+const OP_DATA_END: u8 = 0x0F;
+
+// Uncompressed size at which a chunk is written out
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Compression of the messages of an MCAP channel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Messages are written immediately, uncompressed.
+    #[default]
+    None,
+    /// Messages are collected into chunks of about 1 MiB, which are compressed
+    /// with LZ4. Requires feature `mcap-lz4`.
+    #[cfg(feature = "mcap-lz4")]
+    Lz4,
+}
+
+struct Channel {
+    topic_name: String,
+    compression: Compression,
+    chunk: Chunk,
+}
+
+// Message records waiting to be written as a chunk
+#[derive(Default)]
+struct Chunk {
+    records: Vec<u8>,
+    start_time: u64,
+    end_time: u64,
+}
+
+/// Writes messages in MCAP format to `W`.
+///
+/// Call [`finish`](Self::finish) at the end, so that readers see a complete
+/// file. If the stream is cut off, the messages written so far are still
+/// readable by most MCAP readers.
+pub struct McapWriter<W: Write> {
+    out: W,
+    schemas: Vec<String>,   // index + 1 is schema id
+    channels: Vec<Channel>, // index + 1 is channel id
+    sequence: u32,
+}
+
+impl<W: Write> McapWriter<W> {
+    /// Starts the stream by writing the MCAP magic and header.
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(MAGIC)?;
+        let mut header = Vec::new();
+        put_string(&mut header, "ros2"); // profile
+        put_string(
+            &mut header,
+            concat!("ros2-client ", env!("CARGO_PKG_VERSION")),
+        );
+        write_record(&mut out, OP_HEADER, &header)?;
+        Ok(McapWriter {
+            out,
+            schemas: Vec::new(),
+            channels: Vec::new(),
+            sequence: 0,
+        })
+    }
+
+    /// Declares a Topic, and returns the channel id to use in
+    /// [`write_message`](Self::write_message).
+    ///
+    /// `message_definition` is the `.msg` definition of the type, including
+    /// the definitions of nested types as in rosbag2. Viewers need it to
+    /// decode messages. If it is `None`, the channel is written without a
+    /// schema.
+    ///
+    /// Declaring the same Topic again returns the existing channel.
+    pub fn add_channel(
+        &mut self,
+        topic_name: &str,
+        type_name: &MessageTypeName,
+        message_definition: Option<&str>,
+    ) -> io::Result<u16> {
+        if let Some(i) = self
+            .channels
+            .iter()
+            .position(|c| c.topic_name == topic_name)
+        {
+            return Ok(i as u16 + 1);
+        }
+        let schema_id = match message_definition {
+            None => 0,
+            Some(definition) => self.add_schema(&type_name.ros_type(), definition)?,
+        };
+        let channel_id = u16::try_from(self.channels.len() + 1)
+            .map_err(|_| io::Error::other("too many MCAP channels"))?;
+        let mut channel = Vec::new();
+        channel.extend_from_slice(&channel_id.to_le_bytes());
+        channel.extend_from_slice(&schema_id.to_le_bytes());
+        put_string(&mut channel, topic_name);
+        put_string(&mut channel, "cdr"); // message encoding
+        channel.extend_from_slice(&0u32.to_le_bytes()); // empty metadata map
+        write_record(&mut self.out, OP_CHANNEL, &channel)?;
+        self.channels.push(Channel {
+            topic_name: topic_name.to_string(),
+            compression: Compression::None,
+            chunk: Chunk::default(),
+        });
+        Ok(channel_id)
+    }
+
+    /// Sets the compression of messages written to a channel from now on.
+    pub fn set_compression(&mut self, channel_id: u16, compression: Compression) -> io::Result<()> {
+        let channel = channel_mut(&mut self.channels, channel_id)?;
+        write_chunk(&mut self.out, channel)?;
+        channel.compression = compression;
+        Ok(())
+    }
+
+    fn add_schema(&mut self, name: &str, definition: &str) -> io::Result<u16> {
+        if let Some(i) = self.schemas.iter().position(|s| s == name) {
+            return Ok(i as u16 + 1);
+        }
+        let schema_id = u16::try_from(self.schemas.len() + 1)
+            .map_err(|_| io::Error::other("too many MCAP schemas"))?;
+        let mut schema = Vec::new();
+        schema.extend_from_slice(&schema_id.to_le_bytes());
+        put_string(&mut schema, name);
+        put_string(&mut schema, "ros2msg");
+        put_bytes(&mut schema, definition.as_bytes());
+        write_record(&mut self.out, OP_SCHEMA, &schema)?;
+        self.schemas.push(name.to_string());
+        Ok(schema_id)
+    }
+
+    /// Writes a serialized message, including its CDR encapsulation header,
+    /// to a channel returned by [`add_channel`](Self::add_channel).
+    ///
+    /// The log time is the time of reception, and publish time is the source
+    /// timestamp, if present.
+    pub fn write_message(
+        &mut self,
+        channel_id: u16,
+        serialized: &[u8],
+        info: &MessageInfo,
+    ) -> io::Result<()> {
+        let log_time = info.received_time().to_nanos().max(0) as u64;
+        let publish_time = info
+            .source_time()
+            .map_or(log_time, |t| t.to_nanos().max(0) as u64);
+        self.write_message_at(channel_id, serialized, log_time, publish_time)
+    }
+
+    fn write_message_at(
+        &mut self,
+        channel_id: u16,
+        serialized: &[u8],
+        log_time: u64,
+        publish_time: u64,
+    ) -> io::Result<()> {
+        let channel = channel_mut(&mut self.channels, channel_id)?;
+        let mut message = Vec::with_capacity(22 + serialized.len());
+        message.extend_from_slice(&channel_id.to_le_bytes());
+        message.extend_from_slice(&self.sequence.to_le_bytes());
+        message.extend_from_slice(&log_time.to_le_bytes());
+        message.extend_from_slice(&publish_time.to_le_bytes());
+        message.extend_from_slice(serialized);
+        self.sequence = self.sequence.wrapping_add(1);
+        if channel.compression == Compression::None {
+            return write_record(&mut self.out, OP_MESSAGE, &message);
+        }
+        let chunk = &mut channel.chunk;
+        if chunk.records.is_empty() {
+            chunk.start_time = log_time;
+        }
+        chunk.start_time = chunk.start_time.min(log_time);
+        chunk.end_time = chunk.end_time.max(log_time);
+        write_record(&mut chunk.records, OP_MESSAGE, &message)?;
+        if chunk.records.len() >= CHUNK_SIZE {
+            write_chunk(&mut self.out, channel)?;
+        }
+        Ok(())
+    }
+
+    /// Writes pending chunks and flushes the underlying writer, e.g. to send
+    /// buffered data to a viewer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        for channel in &mut self.channels {
+            write_chunk(&mut self.out, channel)?;
+        }
+        self.out.flush()
+    }
+
+    /// Ends the stream, and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        for channel in &mut self.channels {
+            write_chunk(&mut self.out, channel)?;
+        }
+        write_record(&mut self.out, OP_DATA_END, &0u32.to_le_bytes())?; // no CRC
+        let mut footer = Vec::new();
+        footer.extend_from_slice(&0u64.to_le_bytes()); // no summary section
+        footer.extend_from_slice(&0u64.to_le_bytes()); // no summary offsets
+        footer.extend_from_slice(&0u32.to_le_bytes()); // no CRC
+        write_record(&mut self.out, OP_FOOTER, &footer)?;
+        self.out.write_all(MAGIC)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn channel_mut(channels: &mut [Channel], channel_id: u16) -> io::Result<&mut Channel> {
+    usize::from(channel_id)
+        .checked_sub(1)
+        .and_then(|i| channels.get_mut(i))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown MCAP channel {channel_id}"),
+            )
+        })
+}
+
+// Writes out the pending chunk of a channel, if any.
+fn write_chunk(out: &mut impl Write, channel: &mut Channel) -> io::Result<()> {
+    let chunk = std::mem::take(&mut channel.chunk);
+    if chunk.records.is_empty() {
+        return Ok(());
+    }
+    let uncompressed_size = chunk.records.len() as u64;
+    let (compression, records) = match channel.compression {
+        Compression::None => ("", chunk.records),
+        #[cfg(feature = "mcap-lz4")]
+        Compression::Lz4 => {
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(&chunk.records)?;
+            ("lz4", encoder.finish().map_err(io::Error::other)?)
+        }
+    };
+    let mut content = Vec::with_capacity(40 + records.len());
+    content.extend_from_slice(&chunk.start_time.to_le_bytes());
+    content.extend_from_slice(&chunk.end_time.to_le_bytes());
+    content.extend_from_slice(&uncompressed_size.to_le_bytes());
+    content.extend_from_slice(&0u32.to_le_bytes()); // no CRC
+    put_string(&mut content, compression);
+    content.extend_from_slice(&(records.len() as u64).to_le_bytes());
+    content.extend_from_slice(&records);
+    write_record(out, OP_CHUNK, &content)
+}
+
+fn write_record(out: &mut impl Write, opcode: u8, content: &[u8]) -> io::Result<()> {
+    out.write_all(&[opcode])?;
+    out.write_all(&(content.len() as u64).to_le_bytes())?;
+    out.write_all(content)
+}
+
+fn put_string(buf: &mut Vec<u8>, s: &str) {
+    put_bytes(buf, s.as_bytes());
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mcap_records() {
+        let mut mcap = McapWriter::new(Vec::new()).unwrap();
+        let type_name = MessageTypeName::new("std_msgs", "String");
+        let channel = mcap
+            .add_channel("/chatter", &type_name, Some("string data"))
+            .unwrap();
+        assert_eq!(channel, 1);
+        assert_eq!(mcap.add_channel("/chatter", &type_name, None).unwrap(), 1);
+        let out = mcap.finish().unwrap();
+
+        assert!(out.starts_with(MAGIC) && out.ends_with(MAGIC));
+        // Walk the records between the magics
+        let mut opcodes = Vec::new();
+        let mut rest = &out[MAGIC.len()..out.len() - MAGIC.len()];
+        while !rest.is_empty() {
+            opcodes.push(rest[0]);
+            let len = u64::from_le_bytes(rest[1..9].try_into().unwrap()) as usize;
+            rest = &rest[9 + len..];
+        }
+        assert_eq!(
+            opcodes,
+            vec![OP_HEADER, OP_SCHEMA, OP_CHANNEL, OP_DATA_END, OP_FOOTER]
+        );
+    }
+
+    #[cfg(feature = "mcap-lz4")]
+    #[test]
+    fn mcap_lz4_chunks() {
+        let mut mcap = McapWriter::new(Vec::new()).unwrap();
+        let type_name = MessageTypeName::new("std_msgs", "String");
+        let channel = mcap.add_channel("/chatter", &type_name, None).unwrap();
+        mcap.set_compression(channel, Compression::Lz4).unwrap();
+        mcap.write_message_at(channel, &[0, 1, 0, 0, 1, 0, 0, 0, 0], 5, 5)
+            .unwrap();
+        mcap.write_message_at(channel, &[0, 1, 0, 0, 1, 0, 0, 0, 0], 7, 7)
+            .unwrap();
+        let out = mcap.finish().unwrap();
+
+        // Header and channel, then the chunk
+        let mut rest = &out[MAGIC.len()..];
+        for _ in 0..2 {
+            let len = u64::from_le_bytes(rest[1..9].try_into().unwrap()) as usize;
+            rest = &rest[9 + len..];
+        }
+        assert_eq!(rest[0], OP_CHUNK);
+        let chunk = &rest[9..];
+        assert_eq!(&chunk[0..8], &5u64.to_le_bytes()); // start time
+        assert_eq!(&chunk[8..16], &7u64.to_le_bytes()); // end time
+        let uncompressed_size = u64::from_le_bytes(chunk[16..24].try_into().unwrap());
+        assert_eq!(&chunk[28..35], b"\x03\0\0\0lz4");
+        let compressed_len = u64::from_le_bytes(chunk[35..43].try_into().unwrap()) as usize;
+        let mut records = Vec::new();
+        io::Read::read_to_end(
+            &mut lz4_flex::frame::FrameDecoder::new(&chunk[43..43 + compressed_len]),
+            &mut records,
+        )
+        .unwrap();
+        assert_eq!(records.len() as u64, uncompressed_size);
+        assert_eq!(records[0], OP_MESSAGE);
+    }
+}
@@ -0,0 +1,221 @@
+//! Diagnostic CDR deserialization.
+//!
+//! If a hand-written message type does not match the type on the wire, the
+//! normal CDR deserializer typically reports only something like "unexpected
+//! end of input". [`DiagnosticCdrAdapter`] reports instead which field was
+//! being decoded, at which byte offset, and what bytes were left.
+//!
+//! This requires feature `cdr-diagnostics`.
+//!
+//! Usage:
+//! ```ignore
+//! let sub = node.create_subscription_with_adapter::<MyMsg, DiagnosticCdrAdapter<MyMsg>>(
+//!     &topic, None)?;
+//! ```
+//!
+//! Decoding is slower than with the default adapter, so this is meant for
+//! debugging only.
+
+use std::{error::Error, fmt, marker::PhantomData};
+
+use byteorder::{BigEndian, LittleEndian};
+use rustdds::{
+    no_key::{Decode, DefaultDecoder, DeserializerAdapter},
+    serialization::CdrDeserializer,
+    RepresentationIdentifier,
+};
+use serde::de::DeserializeOwned;
+
+use super::encoding::SUPPORTED_REPRESENTATIONS;
+
+// Limit hex dump length in reports
+const MAX_DUMP_BYTES: usize = 256;
+
+/// Structured report of a failed CDR deserialization
+#[derive(Debug, Clone)]
+pub struct CdrDecodeReport {
+    /// Rust type that was being decoded into
+    pub type_name: &'static str,
+    /// Path to the field being decoded.
+    ///
+    /// CDR has no field names, and structs are decoded as sequences of fields,
+    /// so the path consists of field (or element) indices. E.g. `[1][0]` is
+    /// the first field of the second field.
+    pub field_path: String,
+    /// Byte offset from the start of the data (after the encapsulation
+    /// header), where decoding stopped
+    pub offset: usize,
+    /// Total data length in bytes
+    pub total_len: usize,
+    /// Data that was not decoded, at most 256 bytes
+    pub remaining: Vec<u8>,
+    /// Error reported by the CDR deserializer
+    pub reason: String,
+}
+
+impl fmt::Display for CdrDecodeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "CDR decoding of {} failed at field '{}', byte offset {} of {}: {}",
+            self.type_name, self.field_path, self.offset, self.total_len, self.reason
+        )?;
+        write!(f, "Remaining bytes:")?;
+        for (i, chunk) in self.remaining.chunks(16).enumerate() {
+            write!(f, "\n  {:04x}:", self.offset + i * 16)?;
+            for b in chunk {
+                write!(f, " {b:02x}")?;
+            }
+        }
+        if self.offset + self.remaining.len() < self.total_len {
+            write!(f, "\n  ...")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CdrDecodeReport {}
+
+/// Deserialize CDR data from `input_bytes`, or produce a [`CdrDecodeReport`].
+pub fn diagnostic_deserialize<M: DeserializeOwned>(
+    input_bytes: &[u8],
+    encoding: RepresentationIdentifier,
+) -> Result<M, CdrDecodeReport> {
+    match encoding {
+        RepresentationIdentifier::CDR_LE | RepresentationIdentifier::PL_CDR_LE => deserialize_with(
+            &mut CdrDeserializer::<LittleEndian>::new(input_bytes),
+            input_bytes,
+        ),
+        RepresentationIdentifier::CDR_BE | RepresentationIdentifier::PL_CDR_BE => deserialize_with(
+            &mut CdrDeserializer::<BigEndian>::new(input_bytes),
+            input_bytes,
+        ),
+        other => Err(CdrDecodeReport {
+            type_name: std::any::type_name::<M>(),
+            field_path: ".".to_owned(),
+            offset: 0,
+            total_len: input_bytes.len(),
+            remaining: dump_from(input_bytes, 0),
+            reason: format!("Unsupported encoding {other:?}"),
+        }),
+    }
+}
+
+// Generic over the byte order via the CdrDeserializer reference.
+fn deserialize_with<'de, M, BO>(
+    deserializer: &mut CdrDeserializer<'de, BO>,
+    input_bytes: &[u8],
+) -> Result<M, CdrDecodeReport>
+where
+    M: DeserializeOwned,
+    BO: byteorder::ByteOrder,
+{
+    serde_path_to_error::deserialize(&mut *deserializer).map_err(|e| {
+        let offset = deserializer.bytes_consumed();
+        CdrDecodeReport {
+            type_name: std::any::type_name::<M>(),
+            field_path: e.path().to_string(),
+            offset,
+            total_len: input_bytes.len(),
+            remaining: dump_from(input_bytes, offset),
+            reason: e.into_inner().to_string(),
+        }
+    })
+}
+
+fn dump_from(input_bytes: &[u8], offset: usize) -> Vec<u8> {
+    let start = offset.min(input_bytes.len());
+    let end = (start + MAX_DUMP_BYTES).min(input_bytes.len());
+    input_bytes[start..end].to_vec()
+}
+
+/// Deserializer adapter that produces a [`CdrDecodeReport`] on failure.
+///
+/// Use with [`Node::create_subscription_with_adapter`](crate::node::Node::create_subscription_with_adapter).
+/// Accepts the same encodings as [`CdrAdapter`](super::encoding::CdrAdapter).
+pub struct DiagnosticCdrAdapter<M> {
+    phantom: PhantomData<M>,
+}
+
+impl<M> DeserializerAdapter<M> for DiagnosticCdrAdapter<M> {
+    type Error = CdrDecodeReport;
+    type Decoded = M;
+
+    fn supported_encodings() -> &'static [RepresentationIdentifier] {
+        &SUPPORTED_REPRESENTATIONS
+    }
+
+    fn transform_decoded(decoded: M) -> M {
+        decoded
+    }
+}
+
+impl<M: DeserializeOwned> DefaultDecoder<M> for DiagnosticCdrAdapter<M> {
+    type Decoder = DiagnosticCdrDecoder<M>;
+    const DECODER: Self::Decoder = DiagnosticCdrDecoder(PhantomData);
+}
+
+/// Decoder used by [`DiagnosticCdrAdapter`]
+pub struct DiagnosticCdrDecoder<M>(PhantomData<M>);
+
+// derive(Clone) would require M: Clone
+impl<M> Clone for DiagnosticCdrDecoder<M> {
+    fn clone(&self) -> Self {
+        DiagnosticCdrDecoder(PhantomData)
+    }
+}
+
+impl<M: DeserializeOwned> Decode<M> for DiagnosticCdrDecoder<M> {
+    type Error = CdrDecodeReport;
+
+    fn decode_bytes(
+        self,
+        input_bytes: &[u8],
+        encoding: RepresentationIdentifier,
+    ) -> Result<M, CdrDecodeReport> {
+        diagnostic_deserialize(input_bytes, encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Inner {
+        a: u32,
+        b: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Outer {
+        x: u16,
+        inner: Inner,
+    }
+
+    #[test]
+    fn report_points_to_failing_field() {
+        // x = 1, padding, a = 2, then string length 100 but only 3 bytes follow
+        let data = [1, 0, 0, 0, 2, 0, 0, 0, 100, 0, 0, 0, b'a', b'b', b'c'];
+        let report =
+            diagnostic_deserialize::<Outer>(&data, RepresentationIdentifier::CDR_LE).unwrap_err();
+        assert_eq!(report.field_path, "[1][1]"); // Outer.inner.b
+        assert_eq!(report.total_len, data.len());
+    }
+
+    #[test]
+    fn big_endian_parameter_list() {
+        assert!(
+            <DiagnosticCdrAdapter<Outer> as DeserializerAdapter<Outer>>::supported_encodings()
+                .contains(&RepresentationIdentifier::PL_CDR_BE)
+        );
+        let data = [0, 1, 0, 0, 0, 0, 0, 2, 0, 0, 0, 2, b'a', 0];
+        let outer =
+            diagnostic_deserialize::<Outer>(&data, RepresentationIdentifier::PL_CDR_BE).unwrap();
+        assert_eq!((outer.x, outer.inner.a), (1, 2));
+    }
+}
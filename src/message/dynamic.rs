@@ -0,0 +1,781 @@
+//! Messages whose type is known only at run time
+//!
+//! A [`DynamicType`] is parsed from a message definition in the format that
+//! rosbag2 and MCAP call `ros2msg`: the `.msg` file of the type, followed by
+//! the `.msg` files of the nested types, each after a separator line of `=`
+//! characters and a `MSG: package/Type` line. The definitions of
+//! `builtin_interfaces/Time`, `builtin_interfaces/Duration` and
+//! `std_msgs/Header` are built in, so they can be left out.
+//!
+//! Values are [`DynamicValue`] trees. A [`DynamicMessage`] serializes like
+//! the corresponding generated type, so it can be published with an ordinary
+//! [`Publisher`](crate::node::pubsub::Publisher). Received messages are
+//! decoded with [`DynamicType::seed`] and
+//! [`Subscription::take_seed`](crate::node::pubsub::Subscription::take_seed):
+//!
+//! ```ignore
+//! let ty = Arc::new(DynamicType::parse(&type_name, "float64 x\nfloat64 y")?);
+//! let subscription = node.create_subscription::<DynamicValue>(&topic, None)?;
+//! if let Some((value, _info)) = subscription.take_seed(ty.seed())? {
+//!     println!("{value:?}");
+//! }
+//! ```
+//!
+//! Fields missing from a published value are sent as zeros or empty, like
+//! the defaults of generated types. Constants and default values in the
+//! definition are ignored.
+
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use rustdds::{
+    no_key::SerializerAdapter,
+    serialization::{deserialize_from_cdr_with_decoder_and_rep_id, Error},
+    CDRSerializerAdapter, RepresentationIdentifier,
+};
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, SeqAccess, Visitor},
+    ser::{self, SerializeSeq, SerializeTuple},
+    Deserialize, Serialize, Serializer,
+};
+use widestring::Utf16String;
+
+use crate::interfaces::{names::MessageTypeName, wide_string::WString};
+
+const BUILTIN_DEFINITIONS: &[(&str, &str)] = &[
+    ("builtin_interfaces/Time", "int32 sec\nuint32 nanosec"),
+    ("builtin_interfaces/Duration", "int32 sec\nuint32 nanosec"),
+    (
+        "std_msgs/Header",
+        "builtin_interfaces/Time stamp\nstring frame_id",
+    ),
+];
+
+/// Type of a message field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    Bool,
+    Byte,
+    Char,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+    String,
+    WString,
+    /// Nested message, e.g. `geometry_msgs/Point`
+    Message(String),
+    /// Unbounded or bounded sequence
+    Sequence(Box<FieldType>),
+    /// Fixed-size array
+    Array(Box<FieldType>, usize),
+}
+
+impl FieldType {
+    // Arrays and sequences of these are `DynamicValue::Bytes`.
+    fn is_byte(&self) -> bool {
+        matches!(self, FieldType::Byte | FieldType::UInt8)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub ty: FieldType,
+}
+
+/// Value of a message or a field
+///
+/// Integers of all sizes are `Int` or `UInt`. Either is accepted for any
+/// integer field, if the value is in range, and also for floating point
+/// fields.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynamicValue {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    /// Also `wstring`
+    String(String),
+    /// Array or sequence of `byte` or `uint8`
+    Bytes(Vec<u8>),
+    /// Other arrays and sequences
+    List(Vec<DynamicValue>),
+    Message(BTreeMap<String, DynamicValue>),
+}
+
+/// Message type parsed from its definition. See the
+/// [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamicType {
+    type_name: MessageTypeName,
+    root: FieldType,
+    // Keyed by "package/Type"
+    messages: BTreeMap<String, Vec<Field>>,
+}
+
+impl DynamicType {
+    /// Parses the definition of `type_name` and its nested types.
+    pub fn parse(type_name: &MessageTypeName, definition: &str) -> Result<DynamicType, String> {
+        let root = format!("{}/{}", type_name.package_name(), type_name.type_name());
+        let mut sections = vec![(root.clone(), String::new())];
+        let mut lines = definition.lines();
+        while let Some(line) = lines.next() {
+            let line = line.trim_end();
+            if line.len() >= 3 && line.chars().all(|c| c == '=') {
+                let name = lines
+                    .next()
+                    .and_then(|l| l.trim().strip_prefix("MSG:"))
+                    .ok_or("Expected `MSG: <type>` after a separator line")?;
+                sections.push((normalize_type_name(name.trim(), ""), String::new()));
+            } else if let Some((_, text)) = sections.last_mut() {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+
+        let builtins = BUILTIN_DEFINITIONS
+            .iter()
+            .map(|(name, text)| (name.to_string(), text.to_string()));
+        let mut messages = BTreeMap::new();
+        for (name, text) in builtins.chain(sections) {
+            let package = name.split('/').next().unwrap_or_default();
+            let fields = parse_fields(&text, package).map_err(|e| format!("{name}: {e}"))?;
+            messages.insert(name, fields);
+        }
+
+        let ty = DynamicType {
+            type_name: type_name.clone(),
+            root: FieldType::Message(root),
+            messages,
+        };
+        ty.check(&ty.root, &mut Vec::new())?;
+        Ok(ty)
+    }
+
+    // Checks that all nested types are defined and not recursive.
+    fn check<'a>(&'a self, ty: &'a FieldType, path: &mut Vec<&'a str>) -> Result<(), String> {
+        match ty {
+            FieldType::Sequence(element) | FieldType::Array(element, _) => {
+                self.check(element, path)
+            }
+            FieldType::Message(name) => {
+                if path.contains(&name.as_str()) {
+                    return Err(format!("Type {name} contains itself"));
+                }
+                let fields = self
+                    .messages
+                    .get(name)
+                    .ok_or_else(|| format!("No definition for type {name}"))?;
+                path.push(name);
+                for field in fields {
+                    self.check(&field.ty, path)?;
+                }
+                path.pop();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn type_name(&self) -> &MessageTypeName {
+        &self.type_name
+    }
+
+    /// Fields of the message
+    pub fn fields(&self) -> &[Field] {
+        self.fields_of(&self.root)
+    }
+
+    fn fields_of(&self, ty: &FieldType) -> &[Field] {
+        match ty {
+            FieldType::Message(name) => self.messages.get(name).map_or(&[], Vec::as_slice),
+            _ => &[],
+        }
+    }
+
+    /// Message with all fields zero or empty
+    pub fn default_value(&self) -> DynamicValue {
+        self.default_of(&self.root)
+    }
+
+    fn default_of(&self, ty: &FieldType) -> DynamicValue {
+        match ty {
+            FieldType::Bool => DynamicValue::Bool(false),
+            FieldType::Int8 | FieldType::Int16 | FieldType::Int32 | FieldType::Int64 => {
+                DynamicValue::Int(0)
+            }
+            FieldType::Float32 | FieldType::Float64 => DynamicValue::Float(0.0),
+            FieldType::String | FieldType::WString => DynamicValue::String(String::new()),
+            FieldType::Message(_) => DynamicValue::Message(
+                self.fields_of(ty)
+                    .iter()
+                    .map(|f| (f.name.clone(), self.default_of(&f.ty)))
+                    .collect(),
+            ),
+            FieldType::Sequence(element) if element.is_byte() => DynamicValue::Bytes(Vec::new()),
+            FieldType::Sequence(_) => DynamicValue::List(Vec::new()),
+            FieldType::Array(element, len) if element.is_byte() => {
+                DynamicValue::Bytes(vec![0; *len])
+            }
+            FieldType::Array(element, len) => {
+                DynamicValue::List(vec![self.default_of(element); *len])
+            }
+            _ => DynamicValue::UInt(0),
+        }
+    }
+
+    /// Deserializer of received messages of this type
+    pub fn seed(&self) -> DynamicSeed<'_> {
+        DynamicSeed {
+            types: self,
+            ty: &self.root,
+        }
+    }
+
+    /// Decodes a CDR payload without the encapsulation header.
+    pub fn decode(
+        &self,
+        data: &[u8],
+        encoding: RepresentationIdentifier,
+    ) -> Result<DynamicValue, Error> {
+        deserialize_from_cdr_with_decoder_and_rep_id(data, encoding, self.seed()).map(|r| r.0)
+    }
+}
+
+/// Message of a [`DynamicType`], for publishing
+#[derive(Clone, Debug)]
+pub struct DynamicMessage {
+    pub ty: Arc<DynamicType>,
+    pub value: DynamicValue,
+}
+
+impl DynamicMessage {
+    pub fn new(ty: Arc<DynamicType>, value: DynamicValue) -> DynamicMessage {
+        DynamicMessage { ty, value }
+    }
+
+    /// Encodes as little-endian CDR, without the encapsulation header.
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        CDRSerializerAdapter::<DynamicMessage>::to_bytes(self).map(|b| b.to_vec())
+    }
+}
+
+impl Serialize for DynamicMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Typed {
+            types: &self.ty,
+            ty: &self.ty.root,
+            value: &self.value,
+        }
+        .serialize(serializer)
+    }
+}
+
+// Type name as in the definition, e.g. "Point" in package "geometry_msgs",
+// or "geometry_msgs/msg/Point", to "geometry_msgs/Point"
+fn normalize_type_name(name: &str, package: &str) -> String {
+    let parts: Vec<&str> = name.split('/').collect();
+    match parts.as_slice() {
+        [type_name] => format!("{package}/{type_name}"),
+        [package, _, type_name] => format!("{package}/{type_name}"),
+        _ => name.to_owned(),
+    }
+}
+
+fn parse_fields(text: &str, package: &str) -> Result<Vec<Field>, String> {
+    let mut fields = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(ty) = tokens.next() else {
+            continue;
+        };
+        let name = tokens
+            .next()
+            .ok_or_else(|| format!("line {}: missing field name", i + 1))?;
+        if name.contains('=') || tokens.next().is_some_and(|t| t.starts_with('=')) {
+            continue; // constant
+        }
+        fields.push(Field {
+            name: name.to_owned(),
+            ty: parse_type(ty, package).map_err(|e| format!("line {}: {e}", i + 1))?,
+        });
+    }
+    Ok(fields)
+}
+
+fn parse_type(text: &str, package: &str) -> Result<FieldType, String> {
+    if let Some(open) = text.find('[') {
+        let element = Box::new(parse_type(&text[..open], package)?);
+        let spec = text[open..]
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(|| format!("malformed array type `{text}`"))?;
+        return if spec.is_empty() || spec.starts_with("<=") {
+            Ok(FieldType::Sequence(element))
+        } else {
+            let len = spec
+                .parse()
+                .map_err(|_| format!("malformed array size in `{text}`"))?;
+            Ok(FieldType::Array(element, len))
+        };
+    }
+    // Bounded strings, e.g. "string<=10"
+    let base = text.split("<=").next().unwrap_or_default();
+    Ok(match base {
+        "bool" => FieldType::Bool,
+        "byte" => FieldType::Byte,
+        "char" => FieldType::Char,
+        "int8" => FieldType::Int8,
+        "uint8" => FieldType::UInt8,
+        "int16" => FieldType::Int16,
+        "uint16" => FieldType::UInt16,
+        "int32" => FieldType::Int32,
+        "uint32" => FieldType::UInt32,
+        "int64" => FieldType::Int64,
+        "uint64" => FieldType::UInt64,
+        "float32" => FieldType::Float32,
+        "float64" => FieldType::Float64,
+        "string" => FieldType::String,
+        "wstring" => FieldType::WString,
+        _ => FieldType::Message(normalize_type_name(base, package)),
+    })
+}
+
+// Serializes `value` as `ty`.
+struct Typed<'a> {
+    types: &'a DynamicType,
+    ty: &'a FieldType,
+    value: &'a DynamicValue,
+}
+
+impl Typed<'_> {
+    fn integer<T: TryFrom<i128>, E: ser::Error>(&self) -> Result<T, E> {
+        let value = match self.value {
+            DynamicValue::Int(i) => i128::from(*i),
+            DynamicValue::UInt(u) => i128::from(*u),
+            _ => return Err(self.mismatch()),
+        };
+        T::try_from(value)
+            .map_err(|_| E::custom(format!("{value} is out of range for {:?}", self.ty)))
+    }
+
+    fn float<E: ser::Error>(&self) -> Result<f64, E> {
+        match self.value {
+            DynamicValue::Float(f) => Ok(*f),
+            DynamicValue::Int(i) => Ok(*i as f64),
+            DynamicValue::UInt(u) => Ok(*u as f64),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn mismatch<E: ser::Error>(&self) -> E {
+        E::custom(format!("Expected {:?}, got {:?}", self.ty, self.value))
+    }
+
+    fn element<'b>(&'b self, ty: &'b FieldType, value: &'b DynamicValue) -> Typed<'b> {
+        Typed {
+            types: self.types,
+            ty,
+            value,
+        }
+    }
+}
+
+impl Serialize for Typed<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.ty, self.value) {
+            (FieldType::Bool, DynamicValue::Bool(b)) => serializer.serialize_bool(*b),
+            (FieldType::Byte | FieldType::Char | FieldType::UInt8, _) => {
+                serializer.serialize_u8(self.integer()?)
+            }
+            (FieldType::Int8, _) => serializer.serialize_i8(self.integer()?),
+            (FieldType::Int16, _) => serializer.serialize_i16(self.integer()?),
+            (FieldType::UInt16, _) => serializer.serialize_u16(self.integer()?),
+            (FieldType::Int32, _) => serializer.serialize_i32(self.integer()?),
+            (FieldType::UInt32, _) => serializer.serialize_u32(self.integer()?),
+            (FieldType::Int64, _) => serializer.serialize_i64(self.integer()?),
+            (FieldType::UInt64, _) => serializer.serialize_u64(self.integer()?),
+            (FieldType::Float32, _) => serializer.serialize_f32(self.float()? as f32),
+            (FieldType::Float64, _) => serializer.serialize_f64(self.float()?),
+            (FieldType::String, DynamicValue::String(s)) => serializer.serialize_str(s),
+            (FieldType::WString, DynamicValue::String(s)) => {
+                WString::from(Utf16String::from_str(s)).serialize(serializer)
+            }
+            (FieldType::Message(name), DynamicValue::Message(values)) => {
+                let fields = self.types.fields_of(self.ty);
+                if let Some(unknown) = values
+                    .keys()
+                    .find(|k| !fields.iter().any(|f| f.name == **k))
+                {
+                    return Err(ser::Error::custom(format!("{name} has no field {unknown}")));
+                }
+                if fields.is_empty() {
+                    // DDS types must have at least one member, so ROS 2 adds
+                    // a dummy byte.
+                    return serializer.serialize_u8(0);
+                }
+                let mut tuple = serializer.serialize_tuple(fields.len())?;
+                for field in fields {
+                    match values.get(&field.name) {
+                        Some(value) => tuple.serialize_element(&self.element(&field.ty, value))?,
+                        None => {
+                            let value = self.types.default_of(&field.ty);
+                            tuple.serialize_element(&self.element(&field.ty, &value))?
+                        }
+                    }
+                }
+                tuple.end()
+            }
+            (FieldType::Sequence(element), DynamicValue::Bytes(bytes)) if element.is_byte() => {
+                let mut seq = serializer.serialize_seq(Some(bytes.len()))?;
+                for b in bytes {
+                    seq.serialize_element(b)?;
+                }
+                seq.end()
+            }
+            (FieldType::Sequence(element), DynamicValue::List(values)) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(&self.element(element, value))?;
+                }
+                seq.end()
+            }
+            (FieldType::Array(element, len), DynamicValue::Bytes(bytes)) if element.is_byte() => {
+                if bytes.len() != *len {
+                    return Err(ser::Error::custom(format!(
+                        "Expected {len} bytes, got {}",
+                        bytes.len()
+                    )));
+                }
+                let mut tuple = serializer.serialize_tuple(*len)?;
+                for b in bytes {
+                    tuple.serialize_element(b)?;
+                }
+                tuple.end()
+            }
+            (FieldType::Array(element, len), DynamicValue::List(values)) => {
+                if values.len() != *len {
+                    return Err(ser::Error::custom(format!(
+                        "Expected {len} elements, got {}",
+                        values.len()
+                    )));
+                }
+                let mut tuple = serializer.serialize_tuple(*len)?;
+                for value in values {
+                    tuple.serialize_element(&self.element(element, value))?;
+                }
+                tuple.end()
+            }
+            _ => Err(self.mismatch()),
+        }
+    }
+}
+
+/// Deserializes a message or a field of a [`DynamicType`]. See
+/// [`DynamicType::seed`].
+#[derive(Clone, Copy)]
+pub struct DynamicSeed<'a> {
+    types: &'a DynamicType,
+    ty: &'a FieldType,
+}
+
+impl<'de> DeserializeSeed<'de> for DynamicSeed<'_> {
+    type Value = DynamicValue;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<DynamicValue, D::Error> {
+        match self.ty {
+            FieldType::Bool => deserializer.deserialize_bool(PrimitiveVisitor),
+            FieldType::Byte | FieldType::Char | FieldType::UInt8 => {
+                deserializer.deserialize_u8(PrimitiveVisitor)
+            }
+            FieldType::Int8 => deserializer.deserialize_i8(PrimitiveVisitor),
+            FieldType::Int16 => deserializer.deserialize_i16(PrimitiveVisitor),
+            FieldType::UInt16 => deserializer.deserialize_u16(PrimitiveVisitor),
+            FieldType::Int32 => deserializer.deserialize_i32(PrimitiveVisitor),
+            FieldType::UInt32 => deserializer.deserialize_u32(PrimitiveVisitor),
+            FieldType::Int64 => deserializer.deserialize_i64(PrimitiveVisitor),
+            FieldType::UInt64 => deserializer.deserialize_u64(PrimitiveVisitor),
+            FieldType::Float32 => deserializer.deserialize_f32(PrimitiveVisitor),
+            FieldType::Float64 => deserializer.deserialize_f64(PrimitiveVisitor),
+            FieldType::String => deserializer.deserialize_string(PrimitiveVisitor),
+            FieldType::WString => {
+                WString::deserialize(deserializer).map(|w| DynamicValue::String(w.to_string()))
+            }
+            FieldType::Message(_) => {
+                let fields = self.types.fields_of(self.ty);
+                if fields.is_empty() {
+                    u8::deserialize(deserializer)?;
+                    return Ok(DynamicValue::Message(BTreeMap::new()));
+                }
+                deserializer.deserialize_tuple(
+                    fields.len(),
+                    MessageVisitor {
+                        types: self.types,
+                        fields,
+                    },
+                )
+            }
+            FieldType::Sequence(element) => deserializer.deserialize_seq(ListVisitor {
+                types: self.types,
+                element,
+            }),
+            FieldType::Array(element, len) => deserializer.deserialize_tuple(
+                *len,
+                ListVisitor {
+                    types: self.types,
+                    element,
+                },
+            ),
+        }
+    }
+}
+
+struct PrimitiveVisitor;
+
+impl Visitor<'_> for PrimitiveVisitor {
+    type Value = DynamicValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a primitive value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<DynamicValue, E> {
+        Ok(DynamicValue::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<DynamicValue, E> {
+        Ok(DynamicValue::Int(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<DynamicValue, E> {
+        Ok(DynamicValue::UInt(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<DynamicValue, E> {
+        Ok(DynamicValue::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<DynamicValue, E> {
+        Ok(DynamicValue::String(v.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<DynamicValue, E> {
+        Ok(DynamicValue::String(v))
+    }
+}
+
+struct MessageVisitor<'a> {
+    types: &'a DynamicType,
+    fields: &'a [Field],
+}
+
+impl<'de> Visitor<'de> for MessageVisitor<'_> {
+    type Value = DynamicValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a message of {} fields", self.fields.len())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DynamicValue, A::Error> {
+        let mut values = BTreeMap::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let seed = DynamicSeed {
+                types: self.types,
+                ty: &field.ty,
+            };
+            let value = seq
+                .next_element_seed(seed)?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            values.insert(field.name.clone(), value);
+        }
+        Ok(DynamicValue::Message(values))
+    }
+}
+
+struct ListVisitor<'a> {
+    types: &'a DynamicType,
+    element: &'a FieldType,
+}
+
+impl<'de> Visitor<'de> for ListVisitor<'_> {
+    type Value = DynamicValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array or sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DynamicValue, A::Error> {
+        if self.element.is_byte() {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(b) = seq.next_element::<u8>()? {
+                bytes.push(b);
+            }
+            return Ok(DynamicValue::Bytes(bytes));
+        }
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        let seed = DynamicSeed {
+            types: self.types,
+            ty: self.element,
+        };
+        while let Some(value) = seq.next_element_seed(seed)? {
+            values.push(value);
+        }
+        Ok(DynamicValue::List(values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::{builtin_interfaces::Time, std_msgs::Header};
+
+    #[derive(Serialize)]
+    struct Sample {
+        header: Header,
+        flags: [bool; 2],
+        data: Vec<u8>,
+        points: Vec<(f64, f32)>,
+        label: String,
+        count: i16,
+    }
+
+    const DEFINITION: &str = "\
+# A comment
+std_msgs/Header header
+bool[2] flags
+uint8[] data
+Point[<=10] points
+string<=16 label  # bounded
+int16 count 7
+int32 LIMIT=3
+================================================================================
+MSG: test_msgs/msg/Point
+float64 x
+float32 y
+";
+
+    fn sample_type() -> DynamicType {
+        DynamicType::parse(&MessageTypeName::new("test_msgs", "Sample"), DEFINITION).unwrap()
+    }
+
+    #[test]
+    fn parse_definition() {
+        let ty = sample_type();
+        let names: Vec<&str> = ty.fields().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["header", "flags", "data", "points", "label", "count"]
+        );
+        assert_eq!(
+            ty.fields()[3].ty,
+            FieldType::Sequence(Box::new(FieldType::Message("test_msgs/Point".to_owned())))
+        );
+
+        let missing = DynamicType::parse(&MessageTypeName::new("a", "B"), "Missing m");
+        assert_eq!(missing, Err("No definition for type a/Missing".to_owned()));
+        let recursive = DynamicType::parse(&MessageTypeName::new("a", "B"), "B b");
+        assert!(recursive.is_err());
+    }
+
+    #[test]
+    fn same_encoding_as_static_type() {
+        let sample = Sample {
+            header: Header {
+                stamp: Time::from_nanos(5_000_000_006),
+                frame_id: "map".to_owned(),
+            },
+            flags: [true, false],
+            data: vec![1, 2, 3],
+            points: vec![(1.5, -2.0)],
+            label: "abc".to_owned(),
+            count: -4,
+        };
+        let expected = CDRSerializerAdapter::<Sample>::to_bytes(&sample).unwrap();
+
+        let point = |x: f64, y: f64| {
+            DynamicValue::Message(BTreeMap::from([
+                ("x".to_owned(), DynamicValue::Float(x)),
+                ("y".to_owned(), DynamicValue::Float(y)),
+            ]))
+        };
+        let ty = Arc::new(sample_type());
+        let mut value = ty.default_value();
+        let DynamicValue::Message(fields) = &mut value else {
+            panic!("not a message");
+        };
+        fields.insert(
+            "header".to_owned(),
+            DynamicValue::Message(BTreeMap::from([
+                (
+                    "stamp".to_owned(),
+                    DynamicValue::Message(BTreeMap::from([
+                        ("sec".to_owned(), DynamicValue::Int(5)),
+                        ("nanosec".to_owned(), DynamicValue::Int(6)),
+                    ])),
+                ),
+                (
+                    "frame_id".to_owned(),
+                    DynamicValue::String("map".to_owned()),
+                ),
+            ])),
+        );
+        fields.insert(
+            "flags".to_owned(),
+            DynamicValue::List(vec![DynamicValue::Bool(true), DynamicValue::Bool(false)]),
+        );
+        fields.insert("data".to_owned(), DynamicValue::Bytes(vec![1, 2, 3]));
+        fields.insert(
+            "points".to_owned(),
+            DynamicValue::List(vec![point(1.5, -2.0)]),
+        );
+        fields.insert("label".to_owned(), DynamicValue::String("abc".to_owned()));
+        fields.insert("count".to_owned(), DynamicValue::Int(-4));
+
+        let message = DynamicMessage::new(Arc::clone(&ty), value);
+        let encoded = message.encode().unwrap();
+        assert_eq!(encoded, expected.to_vec());
+
+        // Integers are decoded as the signedness of the field.
+        let DynamicValue::Message(mut decoded) = ty
+            .decode(&encoded, RepresentationIdentifier::CDR_LE)
+            .unwrap()
+        else {
+            panic!("not a message");
+        };
+        assert_eq!(decoded["count"], DynamicValue::Int(-4));
+        assert_eq!(
+            decoded["points"],
+            DynamicValue::List(vec![point(1.5, -2.0)])
+        );
+        decoded.remove("header");
+        let DynamicValue::Message(mut original) = message.value else {
+            panic!("not a message");
+        };
+        original.remove("header");
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn encoding_errors() {
+        let ty = Arc::new(sample_type());
+        let with = |name: &str, value| {
+            DynamicMessage::new(
+                Arc::clone(&ty),
+                DynamicValue::Message(BTreeMap::from([(name.to_owned(), value)])),
+            )
+            .encode()
+        };
+        assert!(with("count", DynamicValue::Int(40000)).is_err());
+        assert!(with("count", DynamicValue::String("1".to_owned())).is_err());
+        assert!(with("flags", DynamicValue::List(vec![])).is_err());
+        assert!(with("nonexistent", DynamicValue::Int(1)).is_err());
+        assert!(with("count", DynamicValue::UInt(7)).is_ok());
+    }
+}
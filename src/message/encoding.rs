@@ -0,0 +1,230 @@
+//! Byte order and representation identifiers of received data
+//!
+//! Every DDS sample starts with a representation identifier that tells how
+//! the payload is encoded. ROS 2 implementations normally send little-endian
+//! classic CDR (`CDR_LE`), but on mixed-vendor networks a big-endian host, or
+//! an implementation configured for DDS-XTypes, may send something else.
+//!
+//! [`Encoding::of`] classifies a representation identifier, and
+//! [`Subscription::received_encodings`](crate::node::pubsub::Subscription::received_encodings)
+//! tells which encodings a Subscription has actually received.
+//!
+//! Supported are the classic (XCDR1) identifiers in both byte orders:
+//! `CDR_BE`, `CDR_LE`, `PL_CDR_BE` and `PL_CDR_LE`. As in the default adapter
+//! of RustDDS, a `PL_CDR` payload is decoded as plain CDR, without parsing
+//! parameter IDs. This works when the sender marks a plain CDR payload as
+//! `PL_CDR`, but a real parameter list, as used for mutable types, does not
+//! decode correctly.
+//!
+//! The default adapter of RustDDS does not accept `PL_CDR_BE`, so
+//! Subscriptions that need it should use [`CdrAdapter`]:
+//!
+//! ```ignore
+//! let sub = node.create_subscription_with_adapter::<MyMsg, CdrAdapter<MyMsg>>(
+//!     &topic, None)?;
+//! ```
+//!
+//! XCDR2 encodings align 64-bit values differently, and are not supported by
+//! the RustDDS CDR deserializer. Samples in those encodings are rejected when
+//! received, and reading them fails with a deserialization error.
+
+use std::marker::PhantomData;
+
+use rustdds::{
+    no_key::{Decode, DefaultDecoder, DeserializerAdapter},
+    serialization::{deserialize_from_cdr_with_rep_id, Error},
+    RepresentationIdentifier,
+};
+use serde::de::DeserializeOwned;
+
+/// Representation identifiers that are accepted for decoding. `PL_CDR`
+/// payloads are decoded as plain CDR, see the [module documentation](self).
+pub const SUPPORTED_REPRESENTATIONS: [RepresentationIdentifier; 4] = [
+    RepresentationIdentifier::CDR_LE,
+    RepresentationIdentifier::CDR_BE,
+    RepresentationIdentifier::PL_CDR_LE,
+    RepresentationIdentifier::PL_CDR_BE,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    BigEndian,
+    LittleEndian,
+}
+
+/// Version of the CDR encoding, from DDS-XTypes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CdrVersion {
+    /// Classic CDR, as in DDS before XTypes. This is what ROS 2 uses.
+    Xcdr1,
+    /// Extended CDR version 2
+    Xcdr2,
+}
+
+/// Classification of a CDR representation identifier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    pub representation: RepresentationIdentifier,
+    pub byte_order: ByteOrder,
+    pub version: CdrVersion,
+    /// Parameter list encoding, used for mutable types.
+    ///
+    /// Decoding does not parse parameter lists, see the
+    /// [module documentation](self).
+    pub parameter_list: bool,
+    /// Delimited encoding, used for appendable types in XCDR2
+    pub delimited: bool,
+}
+
+impl Encoding {
+    /// Classify a representation identifier. Returns `None` for identifiers
+    /// that are not CDR, e.g. `XML`, or that are unknown.
+    pub fn of(representation: RepresentationIdentifier) -> Option<Encoding> {
+        use ByteOrder::*;
+        use CdrVersion::*;
+        type R = RepresentationIdentifier;
+
+        let (byte_order, version, parameter_list, delimited) = match representation {
+            R::CDR_BE => (BigEndian, Xcdr1, false, false),
+            R::CDR_LE => (LittleEndian, Xcdr1, false, false),
+            R::PL_CDR_BE => (BigEndian, Xcdr1, true, false),
+            R::PL_CDR_LE => (LittleEndian, Xcdr1, true, false),
+            R::XCDR2_BE | R::CDR2_BE => (BigEndian, Xcdr2, false, false),
+            R::XCDR2_LE | R::CDR2_LE => (LittleEndian, Xcdr2, false, false),
+            R::D_CDR2_BE | R::D_CDR_BE => (BigEndian, Xcdr2, false, true),
+            R::D_CDR2_LE | R::D_CDR_LE => (LittleEndian, Xcdr2, false, true),
+            R::PL_XCDR2_BE | R::PL_CDR2_BE => (BigEndian, Xcdr2, true, false),
+            R::PL_XCDR2_LE | R::PL_CDR2_LE => (LittleEndian, Xcdr2, true, false),
+            _ => return None,
+        };
+        Some(Encoding {
+            representation,
+            byte_order,
+            version,
+            parameter_list,
+            delimited,
+        })
+    }
+
+    /// Are samples in this encoding accepted for decoding?
+    ///
+    /// For parameter list encodings, only plain CDR payloads decode
+    /// correctly.
+    pub fn is_supported(&self) -> bool {
+        SUPPORTED_REPRESENTATIONS.contains(&self.representation)
+    }
+}
+
+/// Deserializer adapter that accepts every encoding in
+/// [`SUPPORTED_REPRESENTATIONS`].
+///
+/// Unlike the default `CDRDeserializerAdapter`, this accepts also the
+/// `PL_CDR_BE` identifier.
+pub struct CdrAdapter<M> {
+    phantom: PhantomData<M>,
+}
+
+impl<M: DeserializeOwned> DeserializerAdapter<M> for CdrAdapter<M> {
+    type Error = Error;
+    type Decoded = M;
+
+    fn supported_encodings() -> &'static [RepresentationIdentifier] {
+        &SUPPORTED_REPRESENTATIONS
+    }
+
+    fn transform_decoded(decoded: Self::Decoded) -> M {
+        decoded
+    }
+}
+
+impl<M: DeserializeOwned> DefaultDecoder<M> for CdrAdapter<M> {
+    type Decoder = CdrDecoder<M>;
+    const DECODER: Self::Decoder = CdrDecoder {
+        phantom: PhantomData,
+    };
+}
+
+/// Decoder of [`CdrAdapter`]
+pub struct CdrDecoder<M> {
+    phantom: PhantomData<M>,
+}
+
+// Derive would require M: Clone.
+impl<M> Clone for CdrDecoder<M> {
+    fn clone(&self) -> Self {
+        CdrDecoder {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<M: DeserializeOwned> Decode<M> for CdrDecoder<M> {
+    type Error = Error;
+
+    fn decode_bytes(
+        self,
+        input_bytes: &[u8],
+        encoding: RepresentationIdentifier,
+    ) -> Result<M, Self::Error> {
+        deserialize_from_cdr_with_rep_id(input_bytes, encoding).map(|(value, _)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::{builtin_interfaces::Time, std_msgs::Header};
+
+    fn header() -> Header {
+        Header {
+            stamp: Time::from_nanos(1_700_000_000_000_000_005),
+            frame_id: "map".to_owned(),
+        }
+    }
+
+    #[test]
+    fn both_byte_orders() {
+        let little = [
+            0x00, 0xf1, 0x53, 0x65, // sec
+            0x05, 0x00, 0x00, 0x00, // nanosec
+            0x04, 0x00, 0x00, 0x00, b'm', b'a', b'p', 0x00, // frame_id
+        ];
+        let big = [
+            0x65, 0x53, 0xf1, 0x00, // sec
+            0x00, 0x00, 0x00, 0x05, // nanosec
+            0x00, 0x00, 0x00, 0x04, b'm', b'a', b'p', 0x00, // frame_id
+        ];
+        for (bytes, representation) in [
+            (&little, RepresentationIdentifier::CDR_LE),
+            (&little, RepresentationIdentifier::PL_CDR_LE),
+            (&big, RepresentationIdentifier::CDR_BE),
+            (&big, RepresentationIdentifier::PL_CDR_BE),
+        ] {
+            let decoded: Header = <CdrAdapter<Header> as DefaultDecoder<Header>>::DECODER
+                .decode_bytes(bytes, representation)
+                .unwrap();
+            assert_eq!(decoded, header(), "{representation:?}");
+        }
+
+        assert!(<CdrAdapter<Header> as DefaultDecoder<Header>>::DECODER
+            .decode_bytes(&little, RepresentationIdentifier::XCDR2_LE)
+            .is_err());
+    }
+
+    #[test]
+    fn classification() {
+        let pl_be = Encoding::of(RepresentationIdentifier::PL_CDR_BE).unwrap();
+        assert_eq!(pl_be.byte_order, ByteOrder::BigEndian);
+        assert_eq!(pl_be.version, CdrVersion::Xcdr1);
+        assert!(pl_be.parameter_list);
+        assert!(pl_be.is_supported());
+
+        let d_le = Encoding::of(RepresentationIdentifier::D_CDR2_LE).unwrap();
+        assert_eq!(d_le.byte_order, ByteOrder::LittleEndian);
+        assert_eq!(d_le.version, CdrVersion::Xcdr2);
+        assert!(d_le.delimited);
+        assert!(!d_le.is_supported());
+
+        assert_eq!(Encoding::of(RepresentationIdentifier::XML), None);
+    }
+}
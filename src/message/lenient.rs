@@ -0,0 +1,464 @@
+//! CDR decoding that tolerates invalid UTF-8 in strings
+//!
+//! ROS 2 strings should be UTF-8, but some publishers put e.g. Latin-1 text
+//! into log or status messages. The default deserializer rejects such
+//! messages, and the Subscription reports a read error for each of them.
+//! [`LenientCdrAdapter`] decodes them instead, replacing invalid byte
+//! sequences with `U+FFFD REPLACEMENT CHARACTER`:
+//!
+//! ```ignore
+//! let sub = node.create_subscription_with_adapter::<Status, LenientCdrAdapter<Status>>(
+//!     &topic, None)?;
+//! ```
+//!
+//! Decoding strings is somewhat slower than with the default adapter.
+
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+use log::debug;
+use rustdds::{
+    no_key::{Decode, DefaultDecoder, DeserializerAdapter},
+    serialization::{deserialize_from_cdr_with_decoder_and_rep_id, Error},
+    RepresentationIdentifier,
+};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+
+use super::encoding::SUPPORTED_REPRESENTATIONS;
+
+/// Deserializer adapter that replaces invalid UTF-8 in strings, instead of
+/// failing.
+///
+/// Accepts the same encodings as [`CdrAdapter`](super::encoding::CdrAdapter).
+pub struct LenientCdrAdapter<M> {
+    phantom: PhantomData<M>,
+}
+
+impl<M: DeserializeOwned> DeserializerAdapter<M> for LenientCdrAdapter<M> {
+    type Error = Error;
+    type Decoded = M;
+
+    fn supported_encodings() -> &'static [RepresentationIdentifier] {
+        &SUPPORTED_REPRESENTATIONS
+    }
+
+    fn transform_decoded(decoded: Self::Decoded) -> M {
+        decoded
+    }
+}
+
+impl<M: DeserializeOwned> DefaultDecoder<M> for LenientCdrAdapter<M> {
+    type Decoder = LenientCdrDecoder<M>;
+    const DECODER: Self::Decoder = LenientCdrDecoder {
+        phantom: PhantomData,
+    };
+}
+
+/// Decoder of [`LenientCdrAdapter`]
+pub struct LenientCdrDecoder<M> {
+    phantom: PhantomData<M>,
+}
+
+// Derive would require M: Clone.
+impl<M> Clone for LenientCdrDecoder<M> {
+    fn clone(&self) -> Self {
+        LenientCdrDecoder {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<M: DeserializeOwned> Decode<M> for LenientCdrDecoder<M> {
+    type Error = Error;
+
+    fn decode_bytes(
+        self,
+        input_bytes: &[u8],
+        encoding: RepresentationIdentifier,
+    ) -> Result<M, Self::Error> {
+        deserialize_from_cdr_with_decoder_and_rep_id(
+            input_bytes,
+            encoding,
+            Lenient(PhantomData::<M>),
+        )
+        .map(|(value, _)| value)
+    }
+}
+
+// Wrapper that passes every call through to the wrapped deserializer, visitor,
+// or access object, but reads strings as bytes. The wrapping has to cover all
+// of them, so that strings nested in structs, sequences and enums are found.
+//
+// A CDR string has the same encoding as a sequence of bytes whose last byte
+// is the NUL terminator, so a string can be read with `deserialize_bytes`.
+struct Lenient<T>(T);
+
+macro_rules! forward_deserialize {
+    ($($method:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+                self.0.$method(Lenient(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Lenient<D> {
+    type Error = D::Error;
+
+    forward_deserialize!(
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_seq,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any
+    );
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_bytes(LossyString(visitor))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_bytes(LossyString(visitor))
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_unit_struct(name, Lenient(visitor))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_newtype_struct(name, Lenient(visitor))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_tuple(len, Lenient(visitor))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_tuple_struct(name, len, Lenient(visitor))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_struct(name, fields, Lenient(visitor))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_enum(name, variants, Lenient(visitor))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Lenient<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(formatter)
+    }
+
+    forward_visit!(
+        visit_bool: bool,
+        visit_i8: i8,
+        visit_i16: i16,
+        visit_i32: i32,
+        visit_i64: i64,
+        visit_i128: i128,
+        visit_u8: u8,
+        visit_u16: u16,
+        visit_u32: u32,
+        visit_u64: u64,
+        visit_u128: u128,
+        visit_f32: f32,
+        visit_f64: f64,
+        visit_char: char,
+        visit_str: &str,
+        visit_borrowed_str: &'de str,
+        visit_string: String,
+        visit_bytes: &[u8],
+        visit_borrowed_bytes: &'de [u8],
+        visit_byte_buf: Vec<u8>
+    );
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.0.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.0.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.0.visit_some(Lenient(deserializer))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.0.visit_newtype_struct(Lenient(deserializer))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.0.visit_seq(Lenient(seq))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.0.visit_map(Lenient(map))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.0.visit_enum(Lenient(data))
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Lenient<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        self.0.deserialize(Lenient(deserializer))
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Lenient<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        self.0.next_element_seed(Lenient(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Lenient<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.0.next_key_seed(Lenient(seed))
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.0.next_value_seed(Lenient(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for Lenient<A> {
+    type Error = A::Error;
+    type Variant = Lenient<A::Variant>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Self::Variant), A::Error> {
+        self.0
+            .variant_seed(Lenient(seed))
+            .map(|(value, variant)| (value, Lenient(variant)))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Lenient<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.0.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        self.0.newtype_variant_seed(Lenient(seed))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        self.0.tuple_variant(len, Lenient(visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.0.struct_variant(fields, Lenient(visitor))
+    }
+}
+
+// Reads the bytes of a string, and passes them to the string visitor with
+// invalid UTF-8 replaced.
+struct LossyString<V>(V);
+
+impl<V> LossyString<V> {
+    fn visit<'de, E: de::Error>(self, bytes: &[u8]) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+        match String::from_utf8_lossy(bytes) {
+            Cow::Borrowed(s) => self.0.visit_str(s),
+            Cow::Owned(s) => {
+                debug!("Replaced invalid UTF-8 in a received string: {s:?}");
+                self.0.visit_string(s)
+            }
+        }
+    }
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for LossyString<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(formatter)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<V::Value, E> {
+        self.visit(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<V::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element::<u8>()? {
+            bytes.push(b);
+        }
+        self.visit(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{no_key::SerializerAdapter, CDRSerializerAdapter};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::log::Log;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Mode {
+        Idle,
+        Named(String),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Status {
+        level: u8,
+        names: Vec<String>,
+        mode: Mode,
+        note: Option<String>,
+    }
+
+    fn encode<M: Serialize>(value: &M) -> Vec<u8> {
+        <CDRSerializerAdapter<M> as SerializerAdapter<M>>::to_bytes(value)
+            .unwrap()
+            .to_vec()
+    }
+
+    fn decode<M: DeserializeOwned>(bytes: &[u8]) -> Result<M, Error> {
+        <LenientCdrAdapter<M> as DefaultDecoder<M>>::DECODER
+            .decode_bytes(bytes, RepresentationIdentifier::CDR_LE)
+    }
+
+    #[test]
+    fn valid_data_is_unchanged() {
+        let status = Status {
+            level: 2,
+            names: vec!["motor".to_owned(), "".to_owned(), "ääni".to_owned()],
+            mode: Mode::Named("auto".to_owned()),
+            note: Some("ok".to_owned()),
+        };
+        let bytes = encode(&status);
+        assert_eq!(decode::<Status>(&bytes).unwrap(), status);
+    }
+
+    #[test]
+    fn latin1_is_replaced() {
+        let mut log = encode(&Log {
+            timestamp: rustdds::Timestamp::ZERO,
+            level: Log::WARN,
+            name: "driver".to_owned(),
+            msg: "Temp 40 C".to_owned(),
+            file: String::new(),
+            function: String::new(),
+            line: 0,
+        });
+        // Turn the space before "C" into a Latin-1 degree sign.
+        let pos = log.windows(2).position(|w| w == b" C").unwrap();
+        log[pos] = 0xb0;
+
+        assert!(
+            rustdds::serialization::deserialize_from_cdr_with_rep_id::<Log>(
+                &log,
+                RepresentationIdentifier::CDR_LE
+            )
+            .is_err()
+        );
+        let decoded: Log = decode(&log).unwrap();
+        assert_eq!(decoded.msg, "Temp 40\u{fffd}C");
+        assert_eq!(decoded.name, "driver");
+    }
+}
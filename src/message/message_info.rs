@@ -1,6 +1,11 @@
 //! Metadata for received `Message`s, such as `Timestamp`s and publisher id.
 use rustdds::{rpc::SampleIdentity, *};
 
+use crate::{
+    interfaces::gid::Gid,
+    time::ros_time::{ROSDuration, ROSTime},
+};
+
 /// Message metadata
 #[derive(Debug, Clone)]
 pub struct MessageInfo {
@@ -20,10 +25,41 @@ impl MessageInfo {
         self.source_timestamp
     }
 
+    /// Time when the message was received, as [`ROSTime`].
+    ///
+    /// This is read from the system clock, not simulated time.
+    pub fn received_time(&self) -> ROSTime {
+        ROSTime::try_from(self.received_timestamp).unwrap_or(ROSTime::ZERO)
+    }
+
+    /// Time when the message was published, as [`ROSTime`].
+    ///
+    /// `None` if the publisher did not send a source timestamp, or it could
+    /// not be converted.
+    pub fn source_time(&self) -> Option<ROSTime> {
+        self.source_timestamp
+            .and_then(|ts| ROSTime::try_from(ts).ok())
+    }
+
+    /// Age of the message at reception, i.e. reception time minus source
+    /// timestamp.
+    ///
+    /// This is meaningful only if the clocks of the publishing and receiving
+    /// hosts are synchronized. The result may be negative, if they are not.
+    pub fn age(&self) -> Option<ROSDuration> {
+        self.source_time()
+            .map(|source_time| self.received_time() - source_time)
+    }
+
     pub fn writer_guid(&self) -> GUID {
         self.publisher
     }
 
+    /// [`Gid`] of the publisher, as ROS 2 tools show it.
+    pub fn writer_gid(&self) -> Gid {
+        self.publisher.into()
+    }
+
     pub fn sample_identity(&self) -> rustdds::rpc::SampleIdentity {
         rustdds::rpc::SampleIdentity {
             writer_guid: self.writer_guid(),
@@ -36,10 +72,30 @@ impl MessageInfo {
     }
 }
 
+impl MessageInfo {
+    // Info of a message that was not received from DDS, but recovered e.g. by
+    // resending. It has the original writer and sequence number.
+    pub(crate) fn recovered(
+        publisher: GUID,
+        sequence_number: SequenceNumber,
+        source_timestamp: Timestamp,
+    ) -> MessageInfo {
+        MessageInfo {
+            received_timestamp: Timestamp::now(),
+            source_timestamp: Some(source_timestamp),
+            sequence_number,
+            publisher,
+            related_sample_identity: None,
+        }
+    }
+}
+
 impl From<&SampleInfo> for MessageInfo {
     fn from(sample_info: &SampleInfo) -> MessageInfo {
         MessageInfo {
-            received_timestamp: Timestamp::ZERO, // TODO!
+            // RustDDS does not record reception time, so we use the time when
+            // the sample is taken from the reader.
+            received_timestamp: Timestamp::now(),
             source_timestamp: sample_info.source_timestamp(),
             sequence_number: sample_info.sample_identity().sequence_number,
             publisher: sample_info.publication_handle(), // DDS has an odd name for this
@@ -51,7 +107,9 @@ impl From<&SampleInfo> for MessageInfo {
 impl<M> From<&rustdds::no_key::DeserializedCacheChange<M>> for MessageInfo {
     fn from(dcc: &rustdds::no_key::DeserializedCacheChange<M>) -> MessageInfo {
         MessageInfo {
-            received_timestamp: Timestamp::ZERO, // TODO!
+            // RustDDS does not record reception time, so we use the time when
+            // the sample is taken from the reader.
+            received_timestamp: Timestamp::now(),
             source_timestamp: dcc.source_timestamp(),
             sequence_number: dcc.sequence_number,
             publisher: dcc.writer_guid(),
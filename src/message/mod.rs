@@ -3,12 +3,95 @@
 
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::interfaces::names::MessageTypeName;
+
+pub mod dynamic;
+pub mod encoding;
+pub mod lenient;
 pub mod message_info;
 
+#[cfg(feature = "cdr-diagnostics")]
+pub mod cdr_diagnostics;
+
+/// Derive macro for [`Message`].
+///
+/// This requires feature `derive`.
+///
+/// Besides implementing [`Message`], the macro checks that every field type
+/// also implements [`Message`], i.e. can be represented in a ROS 2 message.
+///
+/// ```
+/// use ros2_client::message::RosMessage;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(RosMessage, Serialize, Deserialize)]
+/// struct Reading {
+///     temperature: f64,
+///     samples: Vec<u16>,
+/// }
+/// ```
+///
+/// With the attribute `#[ros_message(msg_file = "path/to/Type.msg")]` the
+/// struct is also checked against the `.msg` definition at compile time. The
+/// path is relative to the crate root. Field names, order and types must
+/// match the definition. Nested message types are compared by their type
+/// name only.
+///
+/// With `#[ros_message(type_name = "package/msg/Type")]` the macro also
+/// implements [`NamedMessage`].
+///
+/// ```
+/// use ros2_client::message::{NamedMessage, RosMessage};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(RosMessage, Serialize, Deserialize)]
+/// #[ros_message(type_name = "my_msgs/msg/Reading")]
+/// struct Reading {
+///     temperature: f64,
+/// }
+///
+/// assert_eq!(Reading::message_type_name().ros_type(), "my_msgs/msg/Reading");
+/// ```
+///
+/// Attributes can be combined.
+///
+/// ```ignore
+/// #[derive(RosMessage, Serialize, Deserialize)]
+/// #[ros_message(msg_file = "msg/Temperature.msg", type_name = "sensor_msgs/msg/Temperature")]
+/// struct Temperature {
+///     header: Header,
+///     temperature: f64,
+///     variance: f64,
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub use ros2_client_derive::RosMessage;
+
+/// A [`Message`] that knows its ROS 2 type name.
+///
+/// Topics for such messages can be created with
+/// [`Node::create_topic_for`](crate::node::Node::create_topic_for), which takes the
+/// type name from here. This way the type name cannot disagree with the Rust
+/// type, which would otherwise be noticed only as missing communication.
+///
+/// `#[derive(RosMessage)]` implements this, if the type name is given with
+/// `#[ros_message(type_name = "package/msg/Type")]`.
+pub trait NamedMessage: Message {
+    /// ROS 2 package, e.g. `std_msgs`
+    const PACKAGE_NAME: &'static str;
+    /// Type name within the package, e.g. `Header`
+    const TYPE_NAME: &'static str;
+
+    fn message_type_name() -> MessageTypeName {
+        MessageTypeName::new(Self::PACKAGE_NAME, Self::TYPE_NAME)
+    }
+}
+
 /// Trait to ensure Messages can be (de)serialized
 pub trait Message: Serialize + DeserializeOwned {}
 
 impl Message for () {}
+impl Message for bool {}
 impl Message for String {}
 
 impl Message for i8 {}
@@ -21,4 +104,8 @@ impl Message for u16 {}
 impl Message for u32 {}
 impl Message for u64 {}
 
+impl Message for f32 {}
+impl Message for f64 {}
+
 impl<T: Message> Message for Vec<T> {}
+impl<T: Message, const N: usize> Message for [T; N] where [T; N]: Serialize + DeserializeOwned {}
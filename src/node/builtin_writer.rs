@@ -0,0 +1,67 @@
+//! Writers to the builtin `/rosout` and `/parameter_events` Topics
+
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+use log::warn;
+use rustdds::{dds::CreateResult, Topic, TopicDescription as _};
+use serde::Serialize;
+
+use super::{context::Context, pubsub::Publisher};
+
+// Publisher to a builtin Topic, created on first use
+pub(crate) struct BuiltinWriter<M: Serialize> {
+    context: Context,
+    topic: Topic,
+    // None, if creation failed
+    publisher: OnceLock<(Option<Arc<Publisher<M>>>, Duration)>,
+}
+
+impl<M: Serialize> BuiltinWriter<M> {
+    pub(crate) fn new(context: Context, topic: Topic) -> BuiltinWriter<M> {
+        BuiltinWriter {
+            context,
+            topic,
+            publisher: OnceLock::new(),
+        }
+    }
+
+    // Creates the Publisher now, and fails if that fails.
+    pub(crate) fn create_now(context: Context, topic: Topic) -> CreateResult<BuiltinWriter<M>> {
+        let start = Instant::now();
+        let publisher = context.create_publisher(&topic, None)?;
+        let writer = BuiltinWriter::new(context, topic);
+        let _ = writer
+            .publisher
+            .set((Some(Arc::new(publisher)), start.elapsed()));
+        Ok(writer)
+    }
+
+    // The Publisher, created now if not yet done
+    pub(crate) fn get(&self) -> Option<&Arc<Publisher<M>>> {
+        self.publisher
+            .get_or_init(|| {
+                let start = Instant::now();
+                let publisher = self
+                    .context
+                    .create_publisher(&self.topic, None)
+                    .map(Arc::new)
+                    .inspect_err(|e| warn!("Cannot create writer for {}: {e:?}", self.topic.name()))
+                    .ok();
+                (publisher, start.elapsed())
+            })
+            .0
+            .as_ref()
+    }
+
+    // The Publisher, if it has been created
+    pub(crate) fn created(&self) -> Option<&Arc<Publisher<M>>> {
+        self.publisher.get().and_then(|(p, _)| p.as_ref())
+    }
+
+    pub(crate) fn creation_time(&self) -> Option<Duration> {
+        self.publisher.get().map(|(_, time)| *time)
+    }
+}
@@ -0,0 +1,508 @@
+//! Sending large blobs over a Topic in chunks
+//!
+//! DDS fragments large samples itself, but multi-megabyte samples are
+//! fragile: One lost fragment loses the whole sample, writer and reader
+//! history must hold it in full, and a
+//! [`max_message_size`](super::context::ContextOptions::max_message_size)
+//! may refuse it altogether. [`ChunkedPublisher`] splits a blob, e.g. a map or a firmware
+//! image, into [`Chunk`] messages of bounded size. [`ChunkedSubscription`]
+//! collects the chunks of each transfer, and delivers the blob once all of
+//! them have arrived and its length and CRC-32 checksum match.
+//!
+//! Chunks are ordinary messages, so the Topic QoS decides what happens to
+//! lost ones. Use Reliable QoS with enough history for a whole transfer, e.g.
+//! [`KeepAll`](rustdds::policy::History::KeepAll). Transfers that stay
+//! incomplete are dropped after a timeout.
+//!
+//! ```ignore
+//! let publisher = node.advertise::<Chunk>("/map_blob")?;
+//! let publisher = ChunkedPublisher::new(publisher);
+//! publisher.publish(&map_bytes)?;
+//!
+//! // In another process
+//! let subscription = node.subscribe::<Chunk>("/map_blob")?;
+//! let subscription = ChunkedSubscription::new(subscription);
+//! let (map_bytes, info) = subscription.async_take().await?;
+//! ```
+
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use log::{debug, warn};
+use rustdds::{dds::ReadResult, dds::WriteResult, GUID};
+use serde::{Deserialize, Serialize};
+
+use super::pubsub::{Publisher, Subscription};
+use crate::{
+    message::{Message, NamedMessage},
+    prelude::MessageInfo,
+    time::steady_time::SteadyTime,
+};
+
+/// Default payload size of one [`Chunk`]
+pub const DEFAULT_CHUNK_SIZE: usize = 32 * 1024;
+
+/// Default limit for the size of a blob received by [`ChunkedSubscription`]
+pub const DEFAULT_MAX_BLOB_SIZE: usize = 256 * 1024 * 1024;
+
+/// Default time to wait for the remaining chunks of a transfer
+pub const DEFAULT_TRANSFER_TIMEOUT: Duration = Duration::from_secs(30);
+
+// At most this many incomplete transfers are kept. The oldest is dropped to
+// make room for a new one.
+const MAX_INCOMPLETE_TRANSFERS: usize = 16;
+
+/// One part of a blob sent by [`ChunkedPublisher`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// Identifies the transfer among those of the same Publisher
+    pub transfer_id: u64,
+    /// Position of this chunk, `0..count`
+    pub index: u32,
+    /// Number of chunks in the transfer
+    pub count: u32,
+    /// Length of the whole blob in bytes
+    pub total_size: u64,
+    /// CRC-32 (IEEE) of the whole blob
+    pub crc32: u32,
+    pub data: Vec<u8>,
+}
+impl Message for Chunk {}
+
+impl NamedMessage for Chunk {
+    const PACKAGE_NAME: &'static str = "ros2_client";
+    const TYPE_NAME: &'static str = "Chunk";
+}
+
+/// Publishes blobs as sequences of [`Chunk`]s. See the
+/// [module documentation](self).
+pub struct ChunkedPublisher {
+    publisher: Publisher<Chunk>,
+    chunk_size: usize,
+    next_transfer_id: AtomicU64,
+}
+
+impl ChunkedPublisher {
+    /// Publish blobs with `publisher`, in chunks of [`DEFAULT_CHUNK_SIZE`].
+    pub fn new(publisher: Publisher<Chunk>) -> ChunkedPublisher {
+        ChunkedPublisher {
+            publisher,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            // Random start, so that a restarted Publisher does not repeat ids.
+            next_transfer_id: AtomicU64::new(uuid::Uuid::new_v4().as_u64_pair().0),
+        }
+    }
+
+    /// Set the payload size of one chunk. Zero is treated as one.
+    pub fn chunk_size(self, chunk_size: usize) -> ChunkedPublisher {
+        ChunkedPublisher {
+            chunk_size: chunk_size.max(1),
+            ..self
+        }
+    }
+
+    /// Publish `blob` as one transfer, and return its transfer id.
+    ///
+    /// An error leaves the transfer incomplete. Subscriptions drop it after
+    /// their timeout.
+    pub fn publish(&self, blob: &[u8]) -> WriteResult<u64, ()> {
+        let (transfer_id, chunks) = self.split(blob);
+        for chunk in chunks {
+            self.publisher.publish(chunk).map_err(|e| e.forget_data())?;
+        }
+        Ok(transfer_id)
+    }
+
+    /// Asynchronous version of [`Self::publish`].
+    pub async fn async_publish(&self, blob: &[u8]) -> WriteResult<u64, ()> {
+        let (transfer_id, chunks) = self.split(blob);
+        for chunk in chunks {
+            self.publisher
+                .async_publish(chunk)
+                .await
+                .map_err(|e| e.forget_data())?;
+        }
+        Ok(transfer_id)
+    }
+
+    fn split<'a>(&self, blob: &'a [u8]) -> (u64, impl Iterator<Item = Chunk> + 'a) {
+        let transfer_id = self.next_transfer_id.fetch_add(1, Ordering::Relaxed);
+        (transfer_id, split_blob(transfer_id, blob, self.chunk_size))
+    }
+
+    /// The wrapped Publisher
+    pub fn publisher(&self) -> &Publisher<Chunk> {
+        &self.publisher
+    }
+}
+
+fn split_blob(
+    transfer_id: u64,
+    blob: &[u8],
+    chunk_size: usize,
+) -> impl Iterator<Item = Chunk> + '_ {
+    // An empty blob is still sent as one empty chunk.
+    let count = blob.len().div_ceil(chunk_size).max(1) as u32;
+    let crc32 = crc32(blob);
+    (0..count).map(move |index| {
+        let start = (index as usize * chunk_size).min(blob.len());
+        let end = (start + chunk_size).min(blob.len());
+        Chunk {
+            transfer_id,
+            index,
+            count,
+            total_size: blob.len() as u64,
+            crc32,
+            data: blob[start..end].to_vec(),
+        }
+    })
+}
+
+/// Reassembles blobs published by [`ChunkedPublisher`]. See the
+/// [module documentation](self).
+pub struct ChunkedSubscription {
+    subscription: Subscription<Chunk>,
+    reassembly: Mutex<Reassembly>,
+}
+
+impl ChunkedSubscription {
+    /// Reassemble blobs from `subscription`, with [`DEFAULT_MAX_BLOB_SIZE`]
+    /// and [`DEFAULT_TRANSFER_TIMEOUT`].
+    pub fn new(subscription: Subscription<Chunk>) -> ChunkedSubscription {
+        ChunkedSubscription {
+            subscription,
+            reassembly: Mutex::new(Reassembly::new(
+                DEFAULT_MAX_BLOB_SIZE,
+                DEFAULT_TRANSFER_TIMEOUT,
+            )),
+        }
+    }
+
+    /// Drop transfers that announce a blob larger than `max_blob_size` bytes.
+    pub fn max_blob_size(self, max_blob_size: usize) -> ChunkedSubscription {
+        self.reassembly.lock().unwrap().max_blob_size = max_blob_size;
+        self
+    }
+
+    /// Drop incomplete transfers when no chunk has arrived for `timeout`.
+    pub fn transfer_timeout(self, timeout: Duration) -> ChunkedSubscription {
+        self.reassembly.lock().unwrap().timeout = timeout;
+        self
+    }
+
+    /// Take the next complete blob, if the chunks received so far complete
+    /// one.
+    ///
+    /// The MessageInfo is that of the chunk that completed the blob.
+    pub fn take(&self) -> ReadResult<Option<(Vec<u8>, MessageInfo)>> {
+        while let Some((chunk, info)) = self.subscription.take()? {
+            if let Some(blob) = self.add(chunk, &info) {
+                return Ok(Some((blob, info)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Asynchronous version of [`Self::take`], which waits for a complete
+    /// blob.
+    pub async fn async_take(&self) -> ReadResult<(Vec<u8>, MessageInfo)> {
+        loop {
+            let (chunk, info) = self.subscription.async_take().await?;
+            if let Some(blob) = self.add(chunk, &info) {
+                return Ok((blob, info));
+            }
+        }
+    }
+
+    fn add(&self, chunk: Chunk, info: &MessageInfo) -> Option<Vec<u8>> {
+        self.reassembly
+            .lock()
+            .unwrap()
+            .add(info.writer_guid(), chunk, SteadyTime::now())
+    }
+
+    /// Number of transfers dropped so far, because they timed out, or failed
+    /// the size or checksum check.
+    pub fn failed_transfers(&self) -> u64 {
+        self.reassembly.lock().unwrap().failed
+    }
+
+    /// The wrapped Subscription. Chunks taken with it are not reassembled.
+    pub fn subscription(&self) -> &Subscription<Chunk> {
+        &self.subscription
+    }
+}
+
+// Chunks received so far of one blob
+struct Transfer {
+    count: u32,
+    total_size: u64,
+    crc32: u32,
+    // Only chunks received so far, as `count` comes from the remote writer.
+    chunks: BTreeMap<u32, Vec<u8>>,
+    received_bytes: u64,
+    last_chunk: SteadyTime,
+}
+
+struct Reassembly {
+    transfers: BTreeMap<(GUID, u64), Transfer>,
+    max_blob_size: usize,
+    timeout: Duration,
+    failed: u64,
+}
+
+impl Reassembly {
+    fn new(max_blob_size: usize, timeout: Duration) -> Reassembly {
+        Reassembly {
+            transfers: BTreeMap::new(),
+            max_blob_size,
+            timeout,
+            failed: 0,
+        }
+    }
+
+    // Add a chunk from `writer`. Returns the blob, if this chunk completed it.
+    fn add(&mut self, writer: GUID, chunk: Chunk, now: SteadyTime) -> Option<Vec<u8>> {
+        self.expire(now);
+        let key = (writer, chunk.transfer_id);
+        if !self.transfers.contains_key(&key) {
+            // Every chunk but the only one of an empty blob has data.
+            if chunk.total_size > self.max_blob_size as u64
+                || chunk.count == 0
+                || u64::from(chunk.count) > chunk.total_size.max(1)
+            {
+                warn!(
+                    "Dropping transfer {} of {} bytes in {} chunks, limit is {} bytes",
+                    chunk.transfer_id, chunk.total_size, chunk.count, self.max_blob_size
+                );
+                self.failed += 1;
+                return None;
+            }
+            if self.transfers.len() >= MAX_INCOMPLETE_TRANSFERS {
+                self.drop_oldest();
+            }
+            self.transfers.insert(
+                key,
+                Transfer {
+                    count: chunk.count,
+                    total_size: chunk.total_size,
+                    crc32: chunk.crc32,
+                    chunks: BTreeMap::new(),
+                    received_bytes: 0,
+                    last_chunk: now,
+                },
+            );
+        }
+        let transfer = self.transfers.get_mut(&key)?;
+        if (chunk.count, chunk.total_size, chunk.crc32)
+            != (transfer.count, transfer.total_size, transfer.crc32)
+            || chunk.index >= transfer.count
+        {
+            warn!(
+                "Chunk {} of transfer {} does not match the transfer, dropping it",
+                chunk.index, chunk.transfer_id
+            );
+            self.transfers.remove(&key);
+            self.failed += 1;
+            return None;
+        }
+        transfer.last_chunk = now;
+        if let Entry::Vacant(slot) = transfer.chunks.entry(chunk.index) {
+            transfer.received_bytes += slot.insert(chunk.data).len() as u64;
+            if transfer.received_bytes > transfer.total_size {
+                warn!(
+                    "Transfer {} exceeds its size of {} bytes, dropping it",
+                    chunk.transfer_id, transfer.total_size
+                );
+                self.transfers.remove(&key);
+                self.failed += 1;
+                return None;
+            }
+        }
+        if transfer.chunks.len() < transfer.count as usize {
+            return None;
+        }
+
+        let transfer = self.transfers.remove(&key)?;
+        let blob: Vec<u8> = transfer.chunks.into_values().flatten().collect();
+        if blob.len() as u64 != transfer.total_size || crc32(&blob) != transfer.crc32 {
+            warn!(
+                "Transfer {} failed integrity check: {} of {} bytes received",
+                chunk.transfer_id,
+                blob.len(),
+                transfer.total_size
+            );
+            self.failed += 1;
+            return None;
+        }
+        Some(blob)
+    }
+
+    fn expire(&mut self, now: SteadyTime) {
+        let timeout = self.timeout;
+        let before = self.transfers.len();
+        self.transfers.retain(|(_, transfer_id), transfer| {
+            let alive = (now - transfer.last_chunk).as_saturating_duration() < timeout;
+            if !alive {
+                debug!(
+                    "Transfer {transfer_id} timed out with {} of {} chunks",
+                    transfer.chunks.len(),
+                    transfer.count
+                );
+            }
+            alive
+        });
+        self.failed += (before - self.transfers.len()) as u64;
+    }
+
+    fn drop_oldest(&mut self) {
+        let oldest = self
+            .transfers
+            .iter()
+            .min_by_key(|(_, transfer)| transfer.last_chunk)
+            .map(|(key, _)| *key);
+        if let Some(key) = oldest {
+            self.transfers.remove(&key);
+            self.failed += 1;
+        }
+    }
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, byte| {
+        CRC32_TABLE[((crc ^ u32::from(*byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn split_and_reassemble() {
+        let writer = GUID::from_bytes([1; 16]);
+        let blob: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let mut chunks: Vec<Chunk> = split_blob(7, &blob, 1000).collect();
+        assert_eq!(chunks.len(), 10);
+
+        // Out of order, with a duplicate
+        chunks.reverse();
+        chunks.push(chunks[3].clone());
+        let last = chunks.remove(0);
+
+        let mut reassembly = Reassembly::new(DEFAULT_MAX_BLOB_SIZE, DEFAULT_TRANSFER_TIMEOUT);
+        let now = SteadyTime::now();
+        for chunk in chunks {
+            assert_eq!(reassembly.add(writer, chunk, now), None);
+        }
+        assert_eq!(reassembly.add(writer, last, now), Some(blob));
+        assert_eq!(reassembly.failed, 0);
+        assert!(reassembly.transfers.is_empty());
+
+        // Empty blob
+        let mut empty = split_blob(8, &[], 1000);
+        assert_eq!(
+            reassembly.add(writer, empty.next().unwrap(), now),
+            Some(vec![])
+        );
+        assert!(empty.next().is_none());
+    }
+
+    #[test]
+    fn corrupt_and_stale_transfers() {
+        let writer = GUID::from_bytes([1; 16]);
+        let mut reassembly = Reassembly::new(1000, Duration::from_secs(1));
+        let now = SteadyTime::now();
+
+        let mut chunks: Vec<Chunk> = split_blob(1, &[5; 100], 60).collect();
+        chunks[1].data[0] = 6;
+        for chunk in chunks {
+            assert_eq!(reassembly.add(writer, chunk, now), None);
+        }
+        assert_eq!(reassembly.failed, 1);
+
+        let mut too_large = split_blob(2, &[5; 2000], 60);
+        assert_eq!(reassembly.add(writer, too_large.next().unwrap(), now), None);
+        assert_eq!(reassembly.failed, 2);
+
+        let mut stale = split_blob(3, &[5; 100], 60);
+        assert_eq!(reassembly.add(writer, stale.next().unwrap(), now), None);
+        let later = now + crate::time::steady_time::TimeDiff::from_millis(2000);
+        let mut other = split_blob(4, &[5; 10], 60);
+        assert_eq!(
+            reassembly.add(writer, other.next().unwrap(), later),
+            Some(vec![5; 10])
+        );
+        assert_eq!(reassembly.failed, 3);
+        assert!(reassembly.transfers.is_empty());
+    }
+
+    #[test]
+    fn malicious_transfers() {
+        let writer = GUID::from_bytes([1; 16]);
+        let mut reassembly = Reassembly::new(1000, Duration::from_secs(1));
+        let now = SteadyTime::now();
+
+        // More chunks than bytes
+        let mut chunk = split_blob(1, &[], 60).next().unwrap();
+        chunk.count = u32::MAX;
+        assert_eq!(reassembly.add(writer, chunk, now), None);
+        assert_eq!(reassembly.failed, 1);
+        assert!(reassembly.transfers.is_empty());
+
+        // More data than announced
+        let mut chunks: Vec<Chunk> = split_blob(2, &[5; 100], 60).collect();
+        chunks[0].data = vec![5; 200];
+        assert_eq!(reassembly.add(writer, chunks.remove(0), now), None);
+        assert_eq!(reassembly.failed, 2);
+        assert!(reassembly.transfers.is_empty());
+    }
+
+    #[test]
+    fn chunk_cdr_round_trip() {
+        use rustdds::{
+            no_key::SerializerAdapter, serialization::deserialize_from_cdr_with_rep_id,
+            CDRSerializerAdapter, RepresentationIdentifier,
+        };
+        let chunk = split_blob(9, b"hello", 3).next().unwrap();
+        let bytes = CDRSerializerAdapter::<Chunk>::to_bytes(&chunk).unwrap();
+        let (decoded, _) =
+            deserialize_from_cdr_with_rep_id::<Chunk>(&bytes, RepresentationIdentifier::CDR_LE)
+                .unwrap();
+        assert_eq!(decoded, chunk);
+    }
+}
@@ -1,31 +1,53 @@
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex, Weak},
 };
 //use futures::{pin_mut, StreamExt};
 #[cfg(feature = "security")]
 use std::path::{Path, PathBuf};
 
+use log::warn;
 use rustdds::{
     dds::CreateResult,
     no_key::{self, DeserializerAdapter, SerializerAdapter},
     policy::*,
     DomainParticipant, DomainParticipantBuilder, QosPolicies, QosPolicyBuilder, RTPSEntity as _,
-    Topic, TopicKind,
+    Topic, TopicDescription as _, TopicKind,
 };
+#[cfg(any(feature = "rosout", feature = "parameters"))]
 use serde::Serialize;
 
 use crate::{
-    interfaces::gid::Gid,
+    interfaces::{
+        gid::Gid,
+        names::{dds_name_to_ros, dds_type_to_ros, is_hidden_name, DdsTopicKind},
+    },
     node::{
         entities_info::{NodeEntitiesInfo, ParticipantEntitiesInfo},
         pubsub::{Publisher, Subscription},
+        resources::{self, Reservation, ResourceLedger, ResourceUsage},
+        user_data::UserData,
         Node, NodeOptions,
     },
     prelude::{MessageTypeName, NodeCreateError, NodeName},
+    service::server::DrainServer,
     topic::builtin_topics,
 };
 
+// Drains `servers` concurrently. Returns `true` if all of them made it.
+pub(crate) async fn drain(servers: Vec<Arc<dyn DrainServer>>) -> bool {
+    futures::future::join_all(servers.iter().map(|server| server.drain()))
+        .await
+        .into_iter()
+        .all(|drained| drained)
+}
+
+// Fixed in RustDDS, in rtps::writer and network::udp_listener
+const RUSTDDS_FRAGMENT_SIZE: usize = 1024;
+const RUSTDDS_MAX_DATAGRAM_SIZE: usize = 64 * 1024;
+// Fixed in RustDDS: the event loop and Discovery threads of a DomainParticipant
+const RUSTDDS_THREADS: usize = 2;
+
 lazy_static::lazy_static! {
 /// Basic BestEffort QoS for subscribers
 ///
@@ -59,6 +81,35 @@ lazy_static::lazy_static! {
     .build();
 }
 
+lazy_static::lazy_static! {
+/// QoS for Service requests and responses.
+///
+/// This matches `rmw_qos_profile_services_default` of ROS 2: Reliable,
+/// Volatile, history depth 10.
+  pub static ref DEFAULT_SERVICE_QOS: QosPolicies = QosPolicyBuilder::new()
+    .durability(Durability::Volatile)
+    .deadline(Deadline(rustdds::Duration::INFINITE))
+    .ownership(Ownership::Shared)
+    .reliability(Reliability::Reliable{max_blocking_time: rustdds::Duration::from_millis(100)})
+    .history(History::KeepLast { depth: 10 })
+    .lifespan(Lifespan {
+      duration: rustdds::Duration::INFINITE
+    })
+    .build();
+}
+
+lazy_static::lazy_static! {
+/// QoS for Service responses received by a Client.
+///
+/// This is [`DEFAULT_SERVICE_QOS`] with history depth 100. A response that
+/// does not fit in the history is dropped, and the future waiting for it never
+/// resolves, so the depth should be at least the number of requests in
+/// flight at the same time. History depth does not affect matching with
+/// Servers.
+  pub static ref DEFAULT_CLIENT_RESPONSE_QOS: QosPolicies = DEFAULT_SERVICE_QOS
+    .modify_by(&QosPolicyBuilder::new().history(History::KeepLast { depth: 100 }).build());
+}
+
 #[cfg(feature = "security")]
 struct SecurityConfig {
     /// Path to a directory of configuration files.
@@ -67,9 +118,43 @@ struct SecurityConfig {
     private_key_password: String,
 }
 
+/// How RustDDS sends large messages
+///
+/// RustDDS sends a serialized message that is larger than `fragment_size` as
+/// several DATA_FRAG submessages, which the receiver reassembles. If any
+/// fragment is lost, a Reliable Publisher resends it, but with BestEffort the
+/// whole message is lost. Messages of many fragments are therefore
+/// unreliable and slow on lossy links, even though there is no hard size
+/// limit.
+///
+/// See [`ContextOptions::transport_limits`] and
+/// [`Context::transport_limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransportLimits {
+    /// Size of fragments, in bytes. This is fixed in RustDDS.
+    pub fragment_size: usize,
+    /// Largest UDP datagram that RustDDS receives. This is fixed in RustDDS.
+    pub max_datagram_size: usize,
+    /// Limit for the serialized size of published messages, set with
+    /// [`ContextOptions::max_message_size`]. `None` means no limit.
+    pub max_message_size: Option<usize>,
+}
+
+impl TransportLimits {
+    /// Number of fragments a serialized message of `size` bytes is sent in.
+    /// This is 1 for messages that are not fragmented.
+    pub fn fragment_count(&self, size: usize) -> usize {
+        size.div_ceil(self.fragment_size).max(1)
+    }
+}
+
 /// Builder for configuring a `Context`
 pub struct ContextOptions {
     domain_id: u16,
+    max_message_size: Option<usize>,
+    max_background_threads: Option<usize>,
+    max_buffer_bytes: Option<u64>,
+    user_data: UserData,
     #[cfg(feature = "security")]
     security_config: Option<SecurityConfig>,
 }
@@ -78,11 +163,70 @@ impl ContextOptions {
     pub fn new() -> Self {
         Self {
             domain_id: 0,
+            max_message_size: None,
+            max_background_threads: None,
+            max_buffer_bytes: None,
+            user_data: UserData::new(),
             #[cfg(feature = "security")]
             security_config: None,
         }
     }
 
+    /// Refuse to publish messages whose serialized size exceeds `bytes`.
+    ///
+    /// This applies to Publishers created by the Nodes of the Context. An
+    /// oversized message makes `publish` return
+    /// [`WriteError::Serialization`](rustdds::dds::WriteError::Serialization)
+    /// with the sizes in the reason, instead of being sent as a long train of
+    /// fragments.
+    ///
+    /// With a limit, every message is serialized one more time to check its
+    /// size.
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    /// Allow at most `threads` background threads started by ros2-client. See
+    /// [`resources`].
+    pub fn max_background_threads(mut self, threads: usize) -> Self {
+        self.max_background_threads = Some(threads);
+        self
+    }
+
+    /// Allow the endpoints of the Nodes to reserve at most `bytes` of buffer
+    /// memory. See [`resources`].
+    pub fn max_buffer_bytes(mut self, bytes: u64) -> Self {
+        self.max_buffer_bytes = Some(bytes);
+        self
+    }
+
+    /// USER_DATA of the DomainParticipant. See [`user_data`](super::user_data)
+    /// for why this is not yet sent to peers.
+    pub fn user_data(self, user_data: UserData) -> Self {
+        Self { user_data, ..self }
+    }
+
+    /// Set the security enclave in USER_DATA, as `enclave=<name>;`.
+    ///
+    /// The name must not contain `;`, otherwise this is ignored with a
+    /// warning.
+    pub fn enclave(mut self, enclave: &str) -> Self {
+        self.user_data
+            .set("enclave", enclave)
+            .unwrap_or_else(|e| warn!("ContextOptions::enclave: {e}"));
+        self
+    }
+
+    /// Limits that a Context created with these options will have
+    pub fn transport_limits(&self) -> TransportLimits {
+        TransportLimits {
+            fragment_size: RUSTDDS_FRAGMENT_SIZE,
+            max_datagram_size: RUSTDDS_MAX_DATAGRAM_SIZE,
+            max_message_size: self.max_message_size,
+        }
+    }
+
     /// Set the DDS Domain Id.
     ///
     /// Please refer to the
@@ -123,7 +267,9 @@ impl Default for ContextOptions {
 /// [`NodeEntitiesInfo`]s. Also acts as a wrapper for a RustDDS instance.
 ///
 /// Context is shut down by dropping it, and all of its RosNodes.
-/// There should be no need for `ok()` or `shutdown()` methods.
+/// There should be no need for `ok()` or `shutdown()` methods. To let
+/// Service Servers answer the requests they have already received, await
+/// [`drain_servers`](Self::drain_servers) first.
 #[derive(Clone)]
 pub struct Context {
     inner: Arc<Mutex<ContextInner>>,
@@ -152,7 +298,15 @@ impl Context {
             }
         }
 
-        Self::from_domain_participant(dpb.build()?)
+        let context = Self::from_domain_participant(dpb.build()?)?;
+        {
+            let mut inner = context.inner.lock().unwrap();
+            inner.max_message_size = opt.max_message_size;
+            inner.resources.max_background_threads = opt.max_background_threads;
+            inner.resources.max_buffer_bytes = opt.max_buffer_bytes;
+            inner.user_data = opt.user_data;
+        }
+        Ok(context)
     }
 
     /// Create a new Context from an existing [`DomainParticipant`].
@@ -172,16 +326,158 @@ impl Context {
         Node::new(node_name, options, self.clone())
     }
 
+    /// USER_DATA given in [`ContextOptions`]
+    pub fn user_data(&self) -> UserData {
+        self.inner.lock().unwrap().user_data.clone()
+    }
+
     /// Query which DDS Domain Id we are using.
     pub fn domain_id(&self) -> u16 {
         self.inner.lock().unwrap().domain_participant.domain_id()
     }
 
+    /// Message size limits and fragmentation of this Context
+    pub fn transport_limits(&self) -> TransportLimits {
+        TransportLimits {
+            fragment_size: RUSTDDS_FRAGMENT_SIZE,
+            max_datagram_size: RUSTDDS_MAX_DATAGRAM_SIZE,
+            max_message_size: self.inner.lock().unwrap().max_message_size,
+        }
+    }
+
+    /// Threads and buffer memory used by this Context. See
+    /// [`resources`].
+    pub fn resource_usage(&self) -> ResourceUsage {
+        self.inner.lock().unwrap().resources.usage(RUSTDDS_THREADS)
+    }
+
+    /// Waits until the Service and Action Servers of all Nodes of this Context
+    /// have responded to the requests already received, and the responses
+    /// have been acknowledged. Returns `true` if all Servers made it within
+    /// their [drain timeout](crate::service::Server::set_drain_timeout).
+    ///
+    /// Await this on shutdown, while the Servers are still being served, and
+    /// before dropping them. Once this has started, the Servers discard new
+    /// requests, see [`Server::drain`](crate::service::Server::drain).
+    pub async fn drain_servers(&self) -> bool {
+        drain(self.servers(None)).await
+    }
+
+    // Live Servers of the Node `node_fqn`, or of all Nodes
+    pub(crate) fn servers(&self, node_fqn: Option<&str>) -> Vec<Arc<dyn DrainServer>> {
+        self.inner
+            .lock()
+            .unwrap()
+            .servers
+            .iter()
+            .filter(|(fqn, _)| node_fqn.is_none_or(|node_fqn| node_fqn == fqn))
+            .filter_map(|(_, server)| server.upgrade())
+            .collect()
+    }
+
+    pub(crate) fn register_server(&self, node_fqn: &str, server: Weak<dyn DrainServer>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner
+            .servers
+            .retain(|(_, server)| server.strong_count() > 0);
+        inner.servers.push((node_fqn.to_owned(), server));
+    }
+
+    // Counts a background thread, if within the limit. The thread is counted
+    // until the returned value is dropped.
+    pub(crate) fn reserve_thread(&self) -> CreateResult<ThreadReservation> {
+        self.inner.lock().unwrap().resources.reserve_thread()?;
+        Ok(ThreadReservation {
+            context: self.clone(),
+        })
+    }
+
+    // Reserves the history buffer of an endpoint with `qos`, if within the
+    // limit. Release with `release_buffer`.
+    pub(crate) fn reserve_buffer(
+        &self,
+        topic_name: &str,
+        qos: &QosPolicies,
+    ) -> CreateResult<Reservation> {
+        let mut inner = self.inner.lock().unwrap();
+        let max_sample_size = inner.max_message_size.unwrap_or(RUSTDDS_MAX_DATAGRAM_SIZE);
+        let reservation = resources::reservation(qos, max_sample_size);
+        inner.resources.reserve_buffer(topic_name, reservation)?;
+        Ok(reservation)
+    }
+
+    pub(crate) fn release_buffer(&self, reservation: Reservation) {
+        self.inner
+            .lock()
+            .unwrap()
+            .resources
+            .release_buffer(reservation);
+    }
+
     /// Which topics have been discovered?
     pub fn discovered_topics(&self) -> Vec<rustdds::discovery::DiscoveredTopicData> {
         self.domain_participant().discovered_topics()
     }
 
+    /// Names and types of discovered ROS 2 Topics.
+    ///
+    /// The result maps fully qualified Topic names, e.g. `/chatter`, to ROS 2
+    /// type names, e.g. `std_msgs/msg/String`. Hidden Topics, i.e. those with
+    /// a name component starting with an underscore, are included only if
+    /// `include_hidden` is set. This matches `ros2 topic list
+    /// --include-hidden-topics`.
+    pub fn topic_names_and_types(
+        &self,
+        include_hidden: bool,
+    ) -> BTreeMap<String, BTreeSet<String>> {
+        self.ros_names_and_types(include_hidden, |kind| kind == DdsTopicKind::Topic)
+    }
+
+    /// Names and types of discovered ROS 2 Services.
+    ///
+    /// Like [`Self::topic_names_and_types`], but for Services. The types are
+    /// Service types, e.g. `example_interfaces/srv/AddTwoInts`.
+    pub fn service_names_and_types(
+        &self,
+        include_hidden: bool,
+    ) -> BTreeMap<String, BTreeSet<String>> {
+        self.ros_names_and_types(include_hidden, |kind| kind != DdsTopicKind::Topic)
+            .into_iter()
+            .map(|(name, types)| {
+                let types = types
+                    .into_iter()
+                    .map(|t| {
+                        t.strip_suffix("_Request")
+                            .or_else(|| t.strip_suffix("_Response"))
+                            .unwrap_or(&t)
+                            .to_owned()
+                    })
+                    .collect();
+                (name, types)
+            })
+            .collect()
+    }
+
+    fn ros_names_and_types(
+        &self,
+        include_hidden: bool,
+        kind_filter: impl Fn(DdsTopicKind) -> bool,
+    ) -> BTreeMap<String, BTreeSet<String>> {
+        let mut result: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for topic in self.discovered_topics() {
+            let Some((kind, name)) = dds_name_to_ros(topic.topic_name()) else {
+                continue; // not a ROS 2 Topic
+            };
+            if !kind_filter(kind) || (!include_hidden && is_hidden_name(&name)) {
+                continue;
+            }
+            let type_name =
+                dds_type_to_ros(topic.type_name()).unwrap_or_else(|| topic.type_name().clone());
+            result.entry(name).or_default().insert(type_name);
+        }
+        result
+    }
+
     /// Gets the ParticipantEntitiesInfo describing the current state of
     /// this Context. This is what we send to ROS Discovery.
     pub fn participant_entities_info(&self) -> ParticipantEntitiesInfo {
@@ -201,6 +497,7 @@ impl Context {
     ///
     /// Note: The recommended way to write log messages to ROSOut is via the
     /// [`crate::rosout`] macro.
+    #[cfg(feature = "rosout")]
     pub fn get_rosout_topic(&self) -> Topic {
         self.inner.lock().unwrap().ros_rosout_topic.clone()
     }
@@ -237,6 +534,7 @@ impl Context {
         Ok(topic)
     }
 
+    #[cfg(any(feature = "rosout", feature = "parameters"))]
     pub(crate) fn create_publisher<M>(
         &self,
         topic: &Topic,
@@ -263,7 +561,7 @@ impl Context {
         let datareader = self
             .get_ros_default_subscriber()
             .create_simple_datareader_no_key(topic, qos)?;
-        Ok(Subscription::new(datareader))
+        Ok(Subscription::new(datareader, topic.name()))
     }
 
     pub(crate) fn create_datawriter<M, SA>(
@@ -291,7 +589,7 @@ impl Context {
             .create_simple_datareader_no_key(topic, qos)
     }
 
-    pub(crate) fn update_node(&mut self, node_info: NodeEntitiesInfo) {
+    pub(crate) fn update_node(&self, node_info: NodeEntitiesInfo) {
         self.inner.lock().unwrap().update_node(node_info);
     }
 
@@ -299,6 +597,10 @@ impl Context {
         self.inner.lock().unwrap().remove_node(node_name);
     }
 
+    pub(crate) fn remove_endpoint(&self, node_fqn: &str, gid: Gid) {
+        self.inner.lock().unwrap().remove_endpoint(node_fqn, gid);
+    }
+
     fn get_ros_default_publisher(&self) -> rustdds::Publisher {
         self.inner.lock().unwrap().ros_default_publisher.clone()
     }
@@ -312,6 +614,22 @@ impl Context {
     }
 }
 
+// Held by a background thread, see Context::reserve_thread
+pub(crate) struct ThreadReservation {
+    context: Context,
+}
+
+impl Drop for ThreadReservation {
+    fn drop(&mut self) {
+        self.context
+            .inner
+            .lock()
+            .unwrap()
+            .resources
+            .release_thread();
+    }
+}
+
 struct ContextInner {
     local_nodes: HashMap<String, NodeEntitiesInfo>,
 
@@ -329,7 +647,14 @@ struct ContextInner {
     ros_default_subscriber: rustdds::Subscriber,
 
     ros_parameter_events_topic: Topic,
+    #[cfg(feature = "rosout")]
     ros_rosout_topic: Topic,
+    // From ContextOptions
+    max_message_size: Option<usize>,
+    user_data: UserData,
+    resources: ResourceLedger,
+    // Servers to drain on shutdown, by Node name
+    servers: Vec<(String, Weak<dyn DrainServer>)>,
 }
 
 impl ContextInner {
@@ -356,6 +681,7 @@ impl ContextInner {
             TopicKind::NoKey,
         )?;
 
+        #[cfg(feature = "rosout")]
         let ros_rosout_topic = domain_participant.create_topic(
             builtin_topics::rosout::TOPIC_NAME.to_string(),
             builtin_topics::rosout::TYPE_NAME.to_string(),
@@ -376,7 +702,12 @@ impl ContextInner {
             ros_default_publisher,
             ros_default_subscriber,
             ros_parameter_events_topic,
+            #[cfg(feature = "rosout")]
             ros_rosout_topic,
+            max_message_size: None,
+            user_data: UserData::new(),
+            resources: ResourceLedger::default(),
+            servers: Vec::new(),
         })
     }
 
@@ -404,6 +735,18 @@ impl ContextInner {
         self.broadcast_node_infos();
     }
 
+    /// Removes a Reader or Writer from a Node, and updates our ContextInfo to
+    /// ROS2 network if it was listed.
+    fn remove_endpoint(&mut self, node_fqn: &str, gid: Gid) {
+        let removed = self
+            .local_nodes
+            .get_mut(node_fqn)
+            .is_some_and(|node_info| node_info.remove_endpoint(gid));
+        if removed {
+            self.broadcast_node_infos();
+        }
+    }
+
     fn broadcast_node_infos(&self) {
         let pei = self.participant_entities_info();
         log::debug!("ROS discovery publish: {pei:?}");
@@ -425,14 +768,28 @@ impl Drop for ContextInner {
 // -------------------------------------------------------------------------------------
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    #[cfg(feature = "parameters")]
+    use crate::prelude::ParameterValue;
     use crate::{
-        node::NodeOptions,
-        prelude::{NodeName, ParameterValue},
+        interfaces::std_srvs::TriggerService,
+        node::{Node, NodeOptions},
+        prelude::{
+            dds::{History, QosPolicyBuilder},
+            MessageTypeName, Name, NodeName, ServiceMapping, ServiceTypeName,
+        },
     };
 
     use super::Context;
 
+    /// Creates a Node in namespace `/rustdds` for tests.
+    pub(crate) fn test_node(context: &Context, name: &str, options: NodeOptions) -> Node {
+        context
+            .new_node(NodeName::new("/rustdds", name).unwrap(), options)
+            .unwrap()
+    }
+
+    #[cfg(feature = "parameters")]
     #[test]
     fn test_node_create() {
         let context = Context::new().unwrap();
@@ -443,4 +800,84 @@ mod tests {
             )
             .is_ok();
     }
+
+    #[test]
+    fn test_dropped_endpoints_leave_discovery_info() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_teardown_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let topic = node
+            .create_topic(
+                &Name::new("/", "teardown").unwrap(),
+                MessageTypeName::new("std_msgs", "String"),
+                &super::DEFAULT_PUBLISHER_QOS,
+            )
+            .unwrap();
+        let publisher = node.create_publisher::<String>(&topic, None).unwrap();
+        let subscription = node.create_subscription::<String>(&topic, None).unwrap();
+
+        let node_info = || {
+            context
+                .participant_entities_info()
+                .nodes()
+                .iter()
+                .find(|n| n.name() == "test_teardown_node")
+                .cloned()
+        };
+        let info = node_info().unwrap();
+        assert!(info.writers().contains(&publisher.gid()));
+        assert!(info.readers().contains(&subscription.gid()));
+        assert_eq!(node.publishers()[0].topic_name, "/teardown");
+        assert_eq!(node.subscriptions()[0].type_name, "std_msgs/msg/String");
+
+        let (publisher_gid, subscription_gid) = (publisher.gid(), subscription.gid());
+        publisher.close();
+        drop(subscription);
+        let info = node_info().unwrap();
+        assert!(!info.writers().contains(&publisher_gid));
+        assert!(!info.readers().contains(&subscription_gid));
+        assert!(node.publishers().is_empty() && node.subscriptions().is_empty());
+
+        node.destroy();
+        assert!(node_info().is_none());
+    }
+
+    #[test]
+    fn test_client_response_history_depth() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_client_depth_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let service_name = Name::new("/", "depth_test").unwrap();
+        let service_type_name = ServiceTypeName::new("std_srvs", "Trigger");
+        let client = node
+            .create_client::<TriggerService>(
+                ServiceMapping::Enhanced,
+                &service_name,
+                &service_type_name,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(client.response_history_depth(), Some(100));
+
+        let shallow = QosPolicyBuilder::new()
+            .history(History::KeepLast { depth: 3 })
+            .build();
+        let client = node
+            .create_client::<TriggerService>(
+                ServiceMapping::Enhanced,
+                &service_name,
+                &service_type_name,
+                None,
+                Some(super::DEFAULT_SERVICE_QOS.modify_by(&shallow)),
+            )
+            .unwrap();
+        assert_eq!(client.response_history_depth(), Some(3));
+    }
 }
@@ -0,0 +1,312 @@
+//! Counters of samples written and read by Publishers, Subscriptions, Clients
+//! and Servers
+//!
+//! These are meant for health monitoring, e.g. to be reported by a health
+//! check endpoint. Counting starts when the entity is created, and the
+//! counters never decrease.
+//!
+//! RustDDS does not report the acknowledgement state of written samples, so
+//! pending acknowledgements are not counted.
+//!
+//! Publishers and Subscriptions also report their current [`Bandwidth`], see
+//! [`Node::topic_bandwidth`](super::Node::topic_bandwidth).
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use rustdds::{no_key::Decode, RepresentationIdentifier};
+
+/// Snapshot of the counters of an entity.
+///
+/// The meaning of "written" and "taken" depends on the entity:
+///
+/// | Entity       | written   | taken     | rejected                            |
+/// |--------------|-----------|-----------|-------------------------------------|
+/// | Publisher    | messages  | -         | -                                   |
+/// | Subscription | -         | messages  | not owner (Exclusive Ownership)     |
+/// | Client       | requests  | responses | closed request or unselected Server |
+/// | Server       | responses | requests  | duplicate request                   |
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatusCounters {
+    /// Samples written successfully
+    pub samples_written: u64,
+    /// Serialized size of the samples written. For Publishers this is an
+    /// estimate, because serialization is done inside DDS: the size of every
+    /// [`SIZE_SAMPLE_INTERVAL`]th message is measured, and used for the
+    /// messages until the next measurement.
+    pub bytes_written: u64,
+    /// Writes that failed
+    pub write_errors: u64,
+    /// Samples delivered to the application
+    pub samples_taken: u64,
+    /// Serialized size of all samples received, including rejected ones
+    pub bytes_received: u64,
+    /// Samples received, but not delivered to the application
+    pub samples_rejected: u64,
+    /// Reads that failed, e.g. because a sample could not be deserialized
+    pub read_errors: u64,
+}
+
+/// Publishers measure the serialized size of every this many messages.
+pub const SIZE_SAMPLE_INTERVAL: u64 = 16;
+
+// Rates are measured over windows of at least this length.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Message and byte rates of a Publisher or Subscription
+///
+/// For Publishers, this counts messages written, with the estimated size of
+/// [`StatusCounters::bytes_written`]. For Subscriptions, this counts all
+/// messages received, including rejected ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bandwidth {
+    pub messages_per_sec: f64,
+    pub bytes_per_sec: f64,
+}
+
+// Window over which the latest rates are measured
+#[derive(Debug)]
+struct RateWindow {
+    start: Instant,
+    start_messages: u64,
+    start_bytes: u64,
+    latest: Bandwidth,
+}
+
+// Live counters, updated by the entity
+#[derive(Debug)]
+pub(crate) struct Counters {
+    samples_written: AtomicU64,
+    bytes_written: AtomicU64,
+    // Latest measured size of a written sample
+    sampled_size: AtomicU64,
+    write_errors: AtomicU64,
+    samples_taken: AtomicU64,
+    bytes_received: AtomicU64,
+    samples_rejected: AtomicU64,
+    read_errors: AtomicU64,
+    rate_window: Mutex<RateWindow>,
+    // Representation identifiers of received samples, with counts
+    encodings: Mutex<Vec<(RepresentationIdentifier, u64)>>,
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Counters {
+            samples_written: AtomicU64::default(),
+            bytes_written: AtomicU64::default(),
+            sampled_size: AtomicU64::default(),
+            write_errors: AtomicU64::default(),
+            samples_taken: AtomicU64::default(),
+            bytes_received: AtomicU64::default(),
+            samples_rejected: AtomicU64::default(),
+            read_errors: AtomicU64::default(),
+            rate_window: Mutex::new(RateWindow {
+                start: Instant::now(),
+                start_messages: 0,
+                start_bytes: 0,
+                latest: Bandwidth::default(),
+            }),
+            encodings: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Counters {
+    // Should the size of the next written sample be measured?
+    pub(crate) fn size_sample_due(&self) -> bool {
+        self.samples_written
+            .load(Ordering::Relaxed)
+            .is_multiple_of(SIZE_SAMPLE_INTERVAL)
+    }
+
+    // Counts a write whose size is known only from sampling. `measured_size`
+    // is given when a sample was due.
+    pub(crate) fn count_write_sampled<T, E>(
+        &self,
+        result: &Result<T, E>,
+        measured_size: Option<usize>,
+    ) {
+        if let Some(size) = measured_size {
+            self.sampled_size.store(size as u64, Ordering::Relaxed);
+        }
+        let size = self.sampled_size.load(Ordering::Relaxed);
+        self.count_write(result, size as usize);
+    }
+
+    pub(crate) fn count_write<T, E>(&self, result: &Result<T, E>, bytes: usize) {
+        match result {
+            Ok(_) => {
+                self.samples_written.fetch_add(1, Ordering::Relaxed);
+                self.bytes_written
+                    .fetch_add(bytes as u64, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.write_errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(crate) fn count_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn count_encoding(&self, encoding: RepresentationIdentifier) {
+        let mut encodings = self.encodings.lock().unwrap();
+        match encodings.iter_mut().find(|(e, _)| *e == encoding) {
+            Some((_, count)) => *count += 1,
+            None => encodings.push((encoding, 1)),
+        }
+    }
+
+    pub(crate) fn encodings(&self) -> Vec<(RepresentationIdentifier, u64)> {
+        self.encodings.lock().unwrap().clone()
+    }
+
+    pub(crate) fn count_taken(&self) {
+        self.samples_taken.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn count_rejected(&self) {
+        self.samples_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn count_read_error(&self) {
+        self.read_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Rates over the latest complete window. A new window starts when the
+    // previous one is at least RATE_WINDOW long and the rates are asked for.
+    pub(crate) fn bandwidth(&self) -> Bandwidth {
+        let snapshot = self.snapshot();
+        let messages =
+            snapshot.samples_written + snapshot.samples_taken + snapshot.samples_rejected;
+        let bytes = snapshot.bytes_written + snapshot.bytes_received;
+        self.bandwidth_at(Instant::now(), messages, bytes)
+    }
+
+    fn bandwidth_at(&self, now: Instant, messages: u64, bytes: u64) -> Bandwidth {
+        let mut window = self.rate_window.lock().unwrap();
+        let elapsed = now.saturating_duration_since(window.start);
+        if elapsed >= RATE_WINDOW {
+            let secs = elapsed.as_secs_f64();
+            window.latest = Bandwidth {
+                messages_per_sec: (messages - window.start_messages) as f64 / secs,
+                bytes_per_sec: (bytes - window.start_bytes) as f64 / secs,
+            };
+            window.start = now;
+            window.start_messages = messages;
+            window.start_bytes = bytes;
+        }
+        window.latest
+    }
+
+    pub(crate) fn snapshot(&self) -> StatusCounters {
+        StatusCounters {
+            samples_written: self.samples_written.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            write_errors: self.write_errors.load(Ordering::Relaxed),
+            samples_taken: self.samples_taken.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            samples_rejected: self.samples_rejected.load(Ordering::Relaxed),
+            read_errors: self.read_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Wraps a decoder to count the bytes and encodings it decodes.
+#[derive(Clone)]
+pub(crate) struct CountingDecoder<'a, Dec> {
+    inner: Dec,
+    counters: &'a Counters,
+}
+
+impl<'a, Dec> CountingDecoder<'a, Dec> {
+    pub(crate) fn new(inner: Dec, counters: &'a Counters) -> Self {
+        CountingDecoder { inner, counters }
+    }
+}
+
+impl<D, Dec: Decode<D>> Decode<D> for CountingDecoder<'_, Dec> {
+    type Error = Dec::Error;
+
+    fn decode_bytes(
+        self,
+        input_bytes: &[u8],
+        encoding: RepresentationIdentifier,
+    ) -> Result<D, Self::Error> {
+        self.counters.count_received(input_bytes.len());
+        self.counters.count_encoding(encoding);
+        self.inner.decode_bytes(input_bytes, encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{no_key::DefaultDecoder, CDRDeserializerAdapter};
+
+    use super::*;
+
+    #[test]
+    fn counting() {
+        let counters = Counters::default();
+        let decoder = CountingDecoder::new(
+            <CDRDeserializerAdapter<u32> as DefaultDecoder<u32>>::DECODER,
+            &counters,
+        );
+        let value: u32 = decoder
+            .decode_bytes(&[7, 0, 0, 0], RepresentationIdentifier::CDR_LE)
+            .unwrap();
+        assert_eq!(value, 7);
+        assert_eq!(
+            counters.encodings(),
+            [(RepresentationIdentifier::CDR_LE, 1)]
+        );
+        counters.count_taken();
+        counters.count_write(&Ok::<(), ()>(()), 12);
+        counters.count_write(&Err::<(), ()>(()), 12);
+
+        assert_eq!(
+            counters.snapshot(),
+            StatusCounters {
+                samples_written: 1,
+                bytes_written: 12,
+                write_errors: 1,
+                samples_taken: 1,
+                bytes_received: 4,
+                ..StatusCounters::default()
+            }
+        );
+    }
+
+    #[test]
+    fn sampled_sizes_and_rates() {
+        let counters = Counters::default();
+        for _ in 0..SIZE_SAMPLE_INTERVAL + 1 {
+            let measured = counters.size_sample_due().then_some(10);
+            counters.count_write_sampled(&Ok::<(), ()>(()), measured);
+        }
+        assert_eq!(
+            counters.snapshot().bytes_written,
+            10 * (SIZE_SAMPLE_INTERVAL + 1)
+        );
+
+        let start = counters.rate_window.lock().unwrap().start;
+        // Window not complete yet
+        assert_eq!(counters.bandwidth_at(start, 5, 50), Bandwidth::default());
+        let bandwidth = counters.bandwidth_at(start + Duration::from_secs(2), 10, 100);
+        assert_eq!(bandwidth.messages_per_sec, 5.0);
+        assert_eq!(bandwidth.bytes_per_sec, 50.0);
+        // The latest rates stay until the next window completes.
+        assert_eq!(
+            counters.bandwidth_at(start + Duration::from_millis(2500), 99, 999),
+            bandwidth
+        );
+    }
+}
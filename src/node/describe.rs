@@ -0,0 +1,207 @@
+//! Self-description of a Node for remote debugging
+//!
+//! [`Node::describe`](super::Node::describe) produces a YAML document listing
+//! the entities of the Node, their QoS, the parameters, and version
+//! information. With [`NodeOptions::describe_service`](super::NodeOptions::describe_service)
+//! the same document is available remotely from the hidden Service
+//! `<node name>/_describe`, of type `std_srvs/srv/Trigger`:
+//!
+//! ```text
+//! ros2 service call /my_node/_describe std_srvs/srv/Trigger
+//! ```
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use rustdds::{
+    policy::{Durability, History, Reliability},
+    QosPolicies,
+};
+
+use crate::service::parameters::ParameterValue;
+
+use super::local_entities::{LocalEndpoints, ServiceEndpointDescriptor, TopicEndpointDescriptor};
+
+/// Base name of the describe Service, under the Node name
+pub const DESCRIBE_SERVICE_NAME: &str = "_describe";
+
+// Builds the YAML description. Written by hand, as the structure is simple
+// and fixed.
+pub(crate) fn describe_yaml(
+    fully_qualified_node_name: &str,
+    endpoints: &LocalEndpoints,
+    parameters: &BTreeMap<String, ParameterValue>,
+) -> String {
+    let mut out = String::new();
+    // Writing to a String cannot fail.
+    let _ = writeln!(out, "node: {}", yaml_string(fully_qualified_node_name));
+    let _ = writeln!(out, "ros2_client_version: {}", env!("CARGO_PKG_VERSION"));
+    let executable = std::env::current_exe()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    let _ = writeln!(out, "executable: {}", yaml_string(&executable));
+    let _ = writeln!(out, "process_id: {}", std::process::id());
+
+    write_topic_endpoints(&mut out, "publishers", &endpoints.topic_endpoints(false));
+    write_topic_endpoints(&mut out, "subscriptions", &endpoints.topic_endpoints(true));
+    write_service_endpoints(&mut out, "services", &endpoints.service_endpoints(true));
+    write_service_endpoints(&mut out, "clients", &endpoints.service_endpoints(false));
+
+    if parameters.is_empty() {
+        out.push_str("parameters: {}\n");
+    } else {
+        out.push_str("parameters:\n");
+        for (name, value) in parameters {
+            let _ = writeln!(out, "  {}: {}", yaml_string(name), yaml_value(value));
+        }
+    }
+    out
+}
+
+fn write_topic_endpoints(out: &mut String, key: &str, endpoints: &[TopicEndpointDescriptor]) {
+    if endpoints.is_empty() {
+        let _ = writeln!(out, "{key}: []");
+        return;
+    }
+    let _ = writeln!(out, "{key}:");
+    for e in endpoints {
+        let _ = writeln!(out, "  - topic: {}", yaml_string(&e.topic_name));
+        let _ = writeln!(out, "    type: {}", yaml_string(&e.type_name));
+        let _ = writeln!(out, "    qos: {}", yaml_qos(&e.qos));
+    }
+}
+
+fn write_service_endpoints(out: &mut String, key: &str, endpoints: &[ServiceEndpointDescriptor]) {
+    if endpoints.is_empty() {
+        let _ = writeln!(out, "{key}: []");
+        return;
+    }
+    let _ = writeln!(out, "{key}:");
+    for e in endpoints {
+        let _ = writeln!(out, "  - service: {}", yaml_string(&e.service_name));
+        let _ = writeln!(out, "    type: {}", yaml_string(&e.type_name));
+        let _ = writeln!(out, "    request_qos: {}", yaml_qos(&e.request_qos));
+        let _ = writeln!(out, "    response_qos: {}", yaml_qos(&e.response_qos));
+    }
+}
+
+// The policies that most often explain why endpoints do not match
+fn yaml_qos(qos: &QosPolicies) -> String {
+    let reliability = match qos.reliability() {
+        Some(Reliability::Reliable { .. }) => "reliable",
+        Some(Reliability::BestEffort) => "best_effort",
+        None => "default",
+    };
+    let durability = match qos.durability() {
+        Some(Durability::Volatile) => "volatile",
+        Some(Durability::TransientLocal) => "transient_local",
+        Some(Durability::Transient) => "transient",
+        Some(Durability::Persistent) => "persistent",
+        None => "default",
+    };
+    let history = match qos.history() {
+        Some(History::KeepLast { depth }) => format!("keep_last {depth}"),
+        Some(History::KeepAll) => "keep_all".to_owned(),
+        None => "default".to_owned(),
+    };
+    format!("{{reliability: {reliability}, durability: {durability}, history: {history}}}")
+}
+
+fn yaml_value(value: &ParameterValue) -> String {
+    fn seq<T>(items: &[T], f: impl Fn(&T) -> String) -> String {
+        format!("[{}]", items.iter().map(f).collect::<Vec<_>>().join(", "))
+    }
+    match value {
+        ParameterValue::NotSet => "null".to_owned(),
+        ParameterValue::Boolean(b) => b.to_string(),
+        ParameterValue::Integer(i) => i.to_string(),
+        ParameterValue::Double(d) => yaml_double(*d),
+        ParameterValue::String(s) => yaml_string(s),
+        ParameterValue::ByteArray(a) => seq(a, u8::to_string),
+        ParameterValue::BooleanArray(a) => seq(a, bool::to_string),
+        ParameterValue::IntegerArray(a) => seq(a, i64::to_string),
+        ParameterValue::DoubleArray(a) => seq(a, |d| yaml_double(*d)),
+        ParameterValue::StringArray(a) => seq(a, |s| yaml_string(s)),
+    }
+}
+
+fn yaml_double(d: f64) -> String {
+    if d.is_nan() {
+        ".nan".to_owned()
+    } else if d.is_infinite() {
+        if d > 0.0 { ".inf" } else { "-.inf" }.to_owned()
+    } else {
+        // Debug keeps the decimal point, so the value stays a float in YAML.
+        format!("{d:?}")
+    }
+}
+
+// Double-quoted YAML string
+fn yaml_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "parameters")]
+    use crate::node::{
+        context::{tests::test_node, Context},
+        NodeOptions,
+    };
+
+    #[test]
+    fn yaml_formatting() {
+        assert_eq!(yaml_string("a\"b\\c\n"), r#""a\"b\\c\n""#);
+        assert_eq!(yaml_value(&ParameterValue::Double(1.0)), "1.0");
+        assert_eq!(
+            yaml_value(&ParameterValue::StringArray(vec!["x".to_owned()])),
+            r#"["x"]"#
+        );
+
+        let parameters = BTreeMap::from([("speed".to_owned(), ParameterValue::Integer(3))]);
+        let yaml = describe_yaml("/robot/driver", &LocalEndpoints::default(), &parameters);
+        assert!(yaml.starts_with("node: \"/robot/driver\"\n"));
+        assert!(yaml.contains("publishers: []\n"));
+        assert!(yaml.ends_with("parameters:\n  \"speed\": 3\n"));
+    }
+
+    #[cfg(feature = "parameters")]
+    #[test]
+    fn describe_service() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_describe_node",
+            NodeOptions::new()
+                .enable_rosout(false)
+                .describe_service(true)
+                .declare_parameter("foo", ParameterValue::Integer(7)),
+        );
+        let _spinner = node.spinner().unwrap();
+        let services = node.services();
+        let describe = services
+            .iter()
+            .find(|s| s.service_name == "/rustdds/test_describe_node/_describe")
+            .unwrap();
+        assert_eq!(describe.type_name, "std_srvs/srv/Trigger");
+
+        let description = node.describe();
+        assert!(description.contains("  - service: \"/rustdds/test_describe_node/_describe\"\n"));
+        assert!(description.contains("  \"foo\": 7\n"));
+    }
+}
@@ -0,0 +1,332 @@
+//! Warm start from previously discovered endpoints
+//!
+//! After a restart, DDS Discovery takes a while to find all remote endpoints
+//! again, especially in large graphs. Until then
+//! [`Node::count_publishers`](super::Node::count_publishers),
+//! [`Node::count_subscribers`](super::Node::count_subscribers) and
+//! [`Node::service_is_available`](super::Node::service_is_available) report
+//! too little, and startup logic that waits for them is delayed.
+//!
+//! A Node configured with [`NodeOptions::discovery_cache`](super::NodeOptions::discovery_cache)
+//! saves the remote endpoints it has discovered to a file when it is dropped,
+//! or when [`Node::save_discovery_cache`](super::Node::save_discovery_cache)
+//! is called. On the next start, the file is loaded, and the cached endpoints
+//! are counted as discovered until live Discovery confirms them or the grace
+//! period ends.
+//!
+//! Stale data is invalidated in three ways:
+//! * Endpoints last seen more than `max_age` before loading are ignored.
+//! * Endpoints reported lost by live Discovery are forgotten.
+//! * After the grace period, only live Discovery counts.
+//!
+//! The cache only affects these graph queries. RustDDS does not take initial
+//! peers, so actual matching of Publishers and Subscriptions still waits for
+//! DDS Discovery.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use log::{debug, warn};
+use rustdds::GUID;
+
+use crate::interfaces::gid::Gid;
+
+const HEADER: &str = "# ros2-client discovery cache v1";
+
+/// Where and how long to keep discovered endpoints. See the
+/// [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct DiscoveryCacheConfig {
+    path: PathBuf,
+    max_age: Duration,
+    grace_period: Duration,
+}
+
+impl DiscoveryCacheConfig {
+    /// Cache in file `path`. Endpoints older than one day are ignored, and
+    /// cached endpoints are used for 10 seconds after Node creation.
+    pub fn new(path: impl Into<PathBuf>) -> DiscoveryCacheConfig {
+        DiscoveryCacheConfig {
+            path: path.into(),
+            max_age: Duration::from_secs(24 * 60 * 60),
+            grace_period: Duration::from_secs(10),
+        }
+    }
+
+    /// Ignore endpoints that were last seen longer than `max_age` ago.
+    pub fn max_age(self, max_age: Duration) -> DiscoveryCacheConfig {
+        DiscoveryCacheConfig { max_age, ..self }
+    }
+
+    /// Use cached endpoints for `grace_period` after Node creation.
+    ///
+    /// This should be about the time that Discovery normally takes.
+    pub fn grace_period(self, grace_period: Duration) -> DiscoveryCacheConfig {
+        DiscoveryCacheConfig {
+            grace_period,
+            ..self
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EndpointKind {
+    Reader,
+    Writer,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CachedEndpoint {
+    pub kind: EndpointKind,
+    pub dds_topic_name: String,
+    pub last_seen: SystemTime,
+}
+
+// Cached endpoints of one Node
+pub(crate) struct DiscoveryCache {
+    config: DiscoveryCacheConfig,
+    // Loaded, and not reported lost since
+    endpoints: BTreeMap<GUID, CachedEndpoint>,
+    valid_until: Instant,
+}
+
+impl DiscoveryCache {
+    // Loads the cache file. A missing or broken file gives an empty cache.
+    pub(crate) fn load(config: DiscoveryCacheConfig) -> DiscoveryCache {
+        let endpoints = match fs::read_to_string(&config.path) {
+            Ok(text) => parse(&text, SystemTime::now(), config.max_age),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => {
+                warn!("Cannot read discovery cache {:?}: {e}", config.path);
+                BTreeMap::new()
+            }
+        };
+        debug!(
+            "Loaded {} endpoints from discovery cache {:?}",
+            endpoints.len(),
+            config.path
+        );
+        DiscoveryCache {
+            valid_until: Instant::now() + config.grace_period,
+            config,
+            endpoints,
+        }
+    }
+
+    // Cached endpoints of `kind` on `dds_topic_name` that live Discovery has
+    // not (yet) found, i.e. are not in `live`.
+    pub(crate) fn count(
+        &self,
+        kind: EndpointKind,
+        dds_topic_name: &str,
+        live: &BTreeMap<GUID, String>,
+    ) -> usize {
+        if Instant::now() >= self.valid_until {
+            return 0;
+        }
+        self.endpoints
+            .iter()
+            .filter(|(guid, e)| {
+                e.kind == kind && e.dds_topic_name == dds_topic_name && !live.contains_key(guid)
+            })
+            .count()
+    }
+
+    // Live Discovery has lost `guid`.
+    pub(crate) fn forget(&mut self, guid: GUID) {
+        self.endpoints.remove(&guid);
+    }
+
+    // Writes the live endpoints, and the cached ones not yet expired.
+    pub(crate) fn save(
+        &self,
+        live_readers: &BTreeMap<GUID, String>,
+        live_writers: &BTreeMap<GUID, String>,
+    ) -> io::Result<()> {
+        let now = SystemTime::now();
+        let mut endpoints: BTreeMap<GUID, CachedEndpoint> = self
+            .endpoints
+            .iter()
+            .filter(|(_, e)| {
+                now.duration_since(e.last_seen).unwrap_or_default() <= self.config.max_age
+            })
+            .map(|(guid, e)| (*guid, e.clone()))
+            .collect();
+        for (live, kind) in [
+            (live_readers, EndpointKind::Reader),
+            (live_writers, EndpointKind::Writer),
+        ] {
+            for (guid, dds_topic_name) in live {
+                endpoints.insert(
+                    *guid,
+                    CachedEndpoint {
+                        kind,
+                        dds_topic_name: dds_topic_name.clone(),
+                        last_seen: now,
+                    },
+                );
+            }
+        }
+        // Write and rename, so that a crash does not leave a partial file.
+        let temp_path = self.config.path.with_extension("tmp");
+        fs::write(&temp_path, format(&endpoints))?;
+        fs::rename(&temp_path, &self.config.path)
+    }
+}
+
+// One line per endpoint: kind, last seen (seconds since Unix epoch), GUID and
+// DDS Topic name, separated by spaces.
+pub(crate) fn format(endpoints: &BTreeMap<GUID, CachedEndpoint>) -> String {
+    let mut text = format!("{HEADER}\n");
+    for (guid, e) in endpoints {
+        let kind = match e.kind {
+            EndpointKind::Reader => 'R',
+            EndpointKind::Writer => 'W',
+        };
+        let seen = e.last_seen.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let _ = writeln!(
+            text,
+            "{kind} {} {} {}",
+            seen.as_secs(),
+            Gid::from(*guid),
+            e.dds_topic_name
+        );
+    }
+    text
+}
+
+// Parses the cache file, skipping bad lines and endpoints older than
+// `max_age`.
+pub(crate) fn parse(
+    text: &str,
+    now: SystemTime,
+    max_age: Duration,
+) -> BTreeMap<GUID, CachedEndpoint> {
+    let mut lines = text.lines();
+    if lines.next() != Some(HEADER) {
+        warn!("Discovery cache has unknown format, ignoring it.");
+        return BTreeMap::new();
+    }
+    let mut endpoints = BTreeMap::new();
+    for line in lines {
+        let mut fields = line.splitn(4, ' ');
+        let parsed = (|| {
+            let kind = match fields.next()? {
+                "R" => EndpointKind::Reader,
+                "W" => EndpointKind::Writer,
+                _ => return None,
+            };
+            let seen = UNIX_EPOCH + Duration::from_secs(fields.next()?.parse().ok()?);
+            let gid: Gid = fields.next()?.parse().ok()?;
+            let dds_topic_name = fields.next()?.to_owned();
+            Some((
+                GUID::from(gid),
+                CachedEndpoint {
+                    kind,
+                    dds_topic_name,
+                    last_seen: seen,
+                },
+            ))
+        })();
+        match parsed {
+            Some((_, e)) if now.duration_since(e.last_seen).unwrap_or_default() > max_age => {}
+            Some((guid, e)) => {
+                endpoints.insert(guid, e);
+            }
+            None => debug!("Skipping bad discovery cache line {line:?}"),
+        }
+    }
+    endpoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guid(n: u8) -> GUID {
+        GUID::from_bytes([n; 16])
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let endpoints = BTreeMap::from([
+            (
+                guid(1),
+                CachedEndpoint {
+                    kind: EndpointKind::Writer,
+                    dds_topic_name: "rt/chatter".to_owned(),
+                    last_seen: now,
+                },
+            ),
+            (
+                guid(2),
+                CachedEndpoint {
+                    kind: EndpointKind::Reader,
+                    dds_topic_name: "rq/add_two_intsRequest".to_owned(),
+                    last_seen: now - Duration::from_secs(100),
+                },
+            ),
+        ]);
+        let text = format(&endpoints);
+        assert_eq!(parse(&text, now, Duration::from_secs(1000)), endpoints);
+
+        // Stale entries are dropped.
+        let fresh = parse(&text, now, Duration::from_secs(10));
+        assert_eq!(fresh.keys().collect::<Vec<_>>(), [&guid(1)]);
+
+        // Bad lines are skipped, unknown files ignored.
+        let damaged = format!("{text}W 12 xyz rt/chatter\n");
+        assert_eq!(parse(&damaged, now, Duration::from_secs(1000)), endpoints);
+        assert!(parse("W 1 2 3\n", now, Duration::MAX).is_empty());
+    }
+
+    #[test]
+    fn counts_until_confirmed_or_expired() {
+        let dir = std::env::temp_dir().join(format!("discovery_cache_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = DiscoveryCacheConfig::new(dir.join("cache"));
+
+        let live_writers = BTreeMap::from([(guid(1), "rt/chatter".to_owned())]);
+        DiscoveryCache::load(config.clone())
+            .save(&BTreeMap::new(), &live_writers)
+            .unwrap();
+
+        let mut cache = DiscoveryCache::load(config.clone());
+        assert_eq!(
+            cache.count(EndpointKind::Writer, "rt/chatter", &BTreeMap::new()),
+            1
+        );
+        assert_eq!(
+            cache.count(EndpointKind::Reader, "rt/chatter", &BTreeMap::new()),
+            0
+        );
+        // Already found by live Discovery, so not counted twice
+        assert_eq!(
+            cache.count(EndpointKind::Writer, "rt/chatter", &live_writers),
+            0
+        );
+        cache.forget(guid(1));
+        assert_eq!(
+            cache.count(EndpointKind::Writer, "rt/chatter", &BTreeMap::new()),
+            0
+        );
+
+        let cache = DiscoveryCache::load(config.grace_period(Duration::ZERO));
+        assert_eq!(
+            cache.count(EndpointKind::Writer, "rt/chatter", &BTreeMap::new()),
+            0
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -100,6 +100,25 @@ impl NodeEntitiesInfo {
             self.reader_gid_seq.push(gid);
         }
     }
+
+    /// Gids of the Readers (Subscriptions) of the Node
+    pub fn readers(&self) -> &[Gid] {
+        &self.reader_gid_seq
+    }
+
+    /// Gids of the Writers (Publishers) of the Node
+    pub fn writers(&self) -> &[Gid] {
+        &self.writer_gid_seq
+    }
+
+    /// Removes `gid` from both Readers and Writers. Returns `true` if it was
+    /// present.
+    pub fn remove_endpoint(&mut self, gid: Gid) -> bool {
+        let count = self.reader_gid_seq.len() + self.writer_gid_seq.len();
+        self.reader_gid_seq.retain(|g| *g != gid);
+        self.writer_gid_seq.retain(|g| *g != gid);
+        count != self.reader_gid_seq.len() + self.writer_gid_seq.len()
+    }
 }
 
 impl TryFrom<repr::NodeEntitiesInfo> for NodeEntitiesInfo {
@@ -0,0 +1,242 @@
+//! Simulated network faults for testing
+//!
+//! A [`FaultInjection`] set on a Publisher with
+//! [`Publisher::set_fault_injection`](super::pubsub::Publisher::set_fault_injection)
+//! drops, delays, or reorders the messages it publishes. The faults are
+//! random, but reproducible: The same seed and the same sequence of publish
+//! calls give the same faults. This way the retry and timeout logic of the
+//! receiving side can be tested in-process, without a lossy network.
+//!
+//! Faults are applied when publishing, so all Subscriptions of the Publisher
+//! see the same faults. There are no timers: Delayed messages are written by
+//! later publish calls, or by
+//! [`Publisher::release_delayed`](super::pubsub::Publisher::release_delayed),
+//! once their delay has passed.
+//!
+//! ```ignore
+//! publisher.set_fault_injection(FaultInjection::new().drop(0.1).reorder(0.05).seed(7));
+//! ```
+//!
+//! This requires feature `fault-injection`. It is meant for tests only.
+
+use std::time::{Duration, Instant};
+
+/// Probabilities of faults for each published message
+///
+/// For each message, at most one fault is chosen: drop, delay, or reorder, in
+/// this order. If the probabilities add up to more than one, the later faults
+/// are chosen less often than given.
+#[derive(Clone, Debug, Default)]
+pub struct FaultInjection {
+    drop_probability: f64,
+    delay_probability: f64,
+    delay: Duration,
+    reorder_probability: f64,
+    seed: u64,
+}
+
+impl FaultInjection {
+    /// No faults, seed 0
+    pub fn new() -> FaultInjection {
+        FaultInjection::default()
+    }
+
+    /// Drop messages with this probability.
+    pub fn drop(self, probability: f64) -> FaultInjection {
+        FaultInjection {
+            drop_probability: probability.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Hold messages back for `delay` with this probability.
+    pub fn delay(self, probability: f64, delay: Duration) -> FaultInjection {
+        FaultInjection {
+            delay_probability: probability.clamp(0.0, 1.0),
+            delay,
+            ..self
+        }
+    }
+
+    /// Write messages after the next message with this probability.
+    pub fn reorder(self, probability: f64) -> FaultInjection {
+        FaultInjection {
+            reorder_probability: probability.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Seed of the random number generator
+    pub fn seed(self, seed: u64) -> FaultInjection {
+        FaultInjection { seed, ..self }
+    }
+}
+
+/// Counts of faults injected so far
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FaultCounters {
+    pub dropped: u64,
+    pub delayed: u64,
+    pub reordered: u64,
+}
+
+enum Release {
+    At(Instant),
+    AfterNext,
+}
+
+// Fault state of one Publisher
+pub(crate) struct FaultInjector<M> {
+    config: FaultInjection,
+    rng_state: u64,
+    // Held messages, oldest first
+    held: Vec<(Release, M)>,
+    counters: FaultCounters,
+}
+
+impl<M> FaultInjector<M> {
+    pub(crate) fn new(config: FaultInjection) -> FaultInjector<M> {
+        FaultInjector {
+            rng_state: config.seed,
+            config,
+            held: Vec::new(),
+            counters: FaultCounters::default(),
+        }
+    }
+
+    // SplitMix64, uniform in [0, 1)
+    fn next_random(&mut self) -> f64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    // Decides what happens to `message`. Returns the messages to write now,
+    // in order.
+    pub(crate) fn admit(&mut self, message: M, now: Instant) -> Vec<M> {
+        let mut batch = self.release_due(now);
+        let r = self.next_random();
+        let c = &self.config;
+        if r < c.drop_probability {
+            self.counters.dropped += 1;
+        } else if r < c.drop_probability + c.delay_probability {
+            self.held.push((Release::At(now + c.delay), message));
+            self.counters.delayed += 1;
+        } else if r < c.drop_probability + c.delay_probability + c.reorder_probability {
+            self.held.push((Release::AfterNext, message));
+            self.counters.reordered += 1;
+        } else {
+            batch.push(message);
+            batch.extend(self.release_where(|r| matches!(r, Release::AfterNext)));
+        }
+        batch
+    }
+
+    // Delayed messages whose time has come
+    pub(crate) fn release_due(&mut self, now: Instant) -> Vec<M> {
+        self.release_where(|r| matches!(r, Release::At(at) if *at <= now))
+    }
+
+    pub(crate) fn release_all(&mut self) -> Vec<M> {
+        self.release_where(|_| true)
+    }
+
+    fn release_where(&mut self, release: impl Fn(&Release) -> bool) -> Vec<M> {
+        let (released, kept) = std::mem::take(&mut self.held)
+            .into_iter()
+            .partition(|(r, _)| release(r));
+        self.held = kept;
+        released.into_iter().map(|(_, m)| m).collect()
+    }
+
+    // Takes over the messages held by `other`.
+    pub(crate) fn hold_from(&mut self, other: &mut FaultInjector<M>) {
+        self.held.append(&mut other.held);
+    }
+
+    pub(crate) fn counters(&self) -> FaultCounters {
+        self.counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interfaces::{builtin_interfaces::Time, std_msgs::Header},
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+    };
+
+    #[test]
+    fn faults_are_reproducible() {
+        let config = FaultInjection::new()
+            .drop(0.2)
+            .delay(0.2, Duration::from_millis(10))
+            .reorder(0.2)
+            .seed(42);
+        let now = Instant::now();
+        let run = || {
+            let mut injector = FaultInjector::new(config.clone());
+            let written: Vec<u32> = (0..100).flat_map(|i| injector.admit(i, now)).collect();
+            (written, injector.counters(), injector.held.len())
+        };
+        let (written, counters, held) = run();
+        assert_eq!(run(), (written.clone(), counters, held));
+
+        assert!(counters.dropped > 0 && counters.delayed > 0 && counters.reordered > 0);
+        // Time does not pass, so delayed messages are still held.
+        assert_eq!(written.len() + held + counters.dropped as usize, 100);
+        assert!(held >= counters.delayed as usize);
+        // Some message arrives after a later one.
+        assert!(written.windows(2).any(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn delayed_messages_wait() {
+        let mut injector =
+            FaultInjector::new(FaultInjection::new().delay(1.0, Duration::from_millis(10)));
+        let now = Instant::now();
+        assert!(injector.admit(1, now).is_empty());
+        assert!(injector.release_due(now).is_empty());
+        assert_eq!(injector.release_due(now + Duration::from_millis(10)), [1]);
+        assert!(injector.admit(2, now).is_empty());
+        assert_eq!(injector.release_all(), [2]);
+    }
+
+    #[test]
+    fn publisher_faults() {
+        use std::time::Duration;
+
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_faults_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let publisher = node.advertise::<Header>("/fault_test").unwrap();
+        let header = Header {
+            stamp: Time::ZERO,
+            frame_id: String::new(),
+        };
+
+        publisher.set_fault_injection(FaultInjection::new().drop(1.0));
+        publisher.publish(header.clone()).unwrap();
+        assert_eq!(publisher.fault_counters().dropped, 1);
+        assert_eq!(publisher.status_counters().samples_written, 0);
+
+        // Each message is held until the next publish call.
+        publisher.set_fault_injection(FaultInjection::new().delay(1.0, Duration::ZERO));
+        publisher.publish(header.clone()).unwrap();
+        assert_eq!(publisher.status_counters().samples_written, 0);
+        publisher.publish(header).unwrap();
+        assert_eq!(publisher.status_counters().samples_written, 1);
+        assert_eq!(publisher.clear_fault_injection().len(), 1);
+        assert_eq!(publisher.status_counters().samples_written, 2);
+    }
+}
@@ -0,0 +1,399 @@
+//! Snapshots of the discovered ROS 2 graph, for configuration audits
+//!
+//! [`Node::graph_snapshot`](super::Node::graph_snapshot) collects the Nodes
+//! found by Discovery and all DDS Readers and Writers, including those of
+//! Services and Actions, with their types and full QoS. A [`GraphSnapshot`]
+//! can be saved as JSON with [`GraphSnapshot::to_json`], and loaded back with
+//! [`GraphSnapshot::from_json`], e.g. to compare a fleet of robots against a
+//! reference configuration:
+//!
+//! ```ignore
+//! let reference = GraphSnapshot::from_json(&std::fs::read_to_string("graph.json")?)?;
+//! let diff = reference.diff(&node.graph_snapshot());
+//! for change in &diff.changed_endpoints {
+//!     println!("{}: {:?} changed", change.new.topic, change.changed_fields());
+//! }
+//! ```
+//!
+//! Like [`Node::count_publishers`](super::Node::count_publishers), this needs
+//! a running [`Spinner`](super::Spinner) to see anything remote.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rustdds::{EndpointDescription, QosPolicies, GUID};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    discovery_cache::EndpointKind, entities_info::NodeEntitiesInfo, local_entities::LocalEndpoints,
+};
+use crate::{
+    interfaces::{
+        gid::Gid,
+        names::{dds_type_to_ros, ros_name_of_dds_topic},
+    },
+    time::ros_time::ROSTime,
+};
+
+/// The discovered graph at one point in time. See the
+/// [module documentation](self).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    pub domain_id: u16,
+    /// System time of the snapshot, in nanoseconds since the Unix epoch
+    pub time_ns: i64,
+    /// Fully qualified names of the known Nodes, sorted
+    pub nodes: Vec<String>,
+    /// Sorted by Node, Topic and direction
+    pub endpoints: Vec<EndpointSnapshot>,
+}
+
+/// A DDS Reader or Writer in a [`GraphSnapshot`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointSnapshot {
+    /// Fully qualified name of the Node owning the endpoint, if it is known
+    /// from `ros_discovery_info`
+    pub node: Option<String>,
+    pub is_reader: bool,
+    /// ROS 2 name of the Topic, Service or Action, e.g. `/chatter`
+    pub topic: String,
+    /// DDS Topic name, e.g. `rt/chatter`. This tells Topics and the request
+    /// and response Topics of Services apart.
+    pub dds_topic: String,
+    /// ROS 2 type name, e.g. `std_msgs/msg/String`, or the DDS type name if
+    /// it is not a ROS 2 type
+    pub type_name: String,
+    pub gid: String,
+    pub qos: QosPolicies,
+}
+
+impl EndpointSnapshot {
+    fn new(
+        node: Option<String>,
+        is_reader: bool,
+        gid: Gid,
+        dds_topic: &str,
+        dds_type: &str,
+        qos: QosPolicies,
+    ) -> EndpointSnapshot {
+        EndpointSnapshot {
+            node,
+            is_reader,
+            topic: ros_name_of_dds_topic(dds_topic),
+            dds_topic: dds_topic.to_owned(),
+            type_name: dds_type_to_ros(dds_type).unwrap_or_else(|| dds_type.to_owned()),
+            gid: gid.to_string(),
+            qos,
+        }
+    }
+
+    // Endpoints are matched across snapshots by this, as Gids change when
+    // Nodes restart.
+    fn identity(&self) -> (Option<&str>, bool, &str) {
+        (self.node.as_deref(), self.is_reader, &self.dds_topic)
+    }
+
+    // Same configuration, ignoring the Gid
+    fn same_configuration(&self, other: &EndpointSnapshot) -> bool {
+        self.type_name == other.type_name && self.qos == other.qos
+    }
+}
+
+impl GraphSnapshot {
+    /// Serialize as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<GraphSnapshot> {
+        serde_json::from_str(json)
+    }
+
+    /// What changed from `self` to the `newer` snapshot.
+    ///
+    /// Endpoints are identified by Node, direction and DDS Topic name, not by
+    /// Gid, so restarted Nodes do not show up as changes. An endpoint whose
+    /// type or QoS differs is reported as changed.
+    pub fn diff(&self, newer: &GraphSnapshot) -> GraphDiff {
+        let old_nodes: BTreeSet<&String> = self.nodes.iter().collect();
+        let new_nodes: BTreeSet<&String> = newer.nodes.iter().collect();
+
+        let mut old_endpoints: BTreeMap<_, Vec<&EndpointSnapshot>> = BTreeMap::new();
+        for e in &self.endpoints {
+            old_endpoints.entry(e.identity()).or_default().push(e);
+        }
+        let mut new_endpoints: BTreeMap<_, Vec<&EndpointSnapshot>> = BTreeMap::new();
+        for e in &newer.endpoints {
+            new_endpoints.entry(e.identity()).or_default().push(e);
+        }
+
+        let mut diff = GraphDiff {
+            added_nodes: new_nodes
+                .difference(&old_nodes)
+                .map(|n| (*n).clone())
+                .collect(),
+            removed_nodes: old_nodes
+                .difference(&new_nodes)
+                .map(|n| (*n).clone())
+                .collect(),
+            ..GraphDiff::default()
+        };
+        let identities: BTreeSet<_> = old_endpoints.keys().chain(new_endpoints.keys()).collect();
+        for identity in identities {
+            let mut old = old_endpoints.get(identity).cloned().unwrap_or_default();
+            let mut new = new_endpoints.get(identity).cloned().unwrap_or_default();
+            // Unchanged endpoints cancel out. What is left over on both sides
+            // is paired up as changes.
+            old.retain(|o| match new.iter().position(|n| o.same_configuration(n)) {
+                Some(i) => {
+                    new.remove(i);
+                    false
+                }
+                None => true,
+            });
+            let changed = old.len().min(new.len());
+            for (o, n) in old.iter().zip(&new) {
+                diff.changed_endpoints.push(EndpointChange {
+                    old: (*o).clone(),
+                    new: (*n).clone(),
+                });
+            }
+            diff.removed_endpoints
+                .extend(old[changed..].iter().map(|e| (*e).clone()));
+            diff.added_endpoints
+                .extend(new[changed..].iter().map(|e| (*e).clone()));
+        }
+        diff
+    }
+}
+
+/// Differences between two [`GraphSnapshot`]s
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub added_endpoints: Vec<EndpointSnapshot>,
+    pub removed_endpoints: Vec<EndpointSnapshot>,
+    pub changed_endpoints: Vec<EndpointChange>,
+}
+
+impl GraphDiff {
+    /// No differences
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_endpoints.is_empty()
+            && self.removed_endpoints.is_empty()
+            && self.changed_endpoints.is_empty()
+    }
+
+    /// Serialize as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// An endpoint whose type or QoS differs between snapshots
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointChange {
+    pub old: EndpointSnapshot,
+    pub new: EndpointSnapshot,
+}
+
+impl EndpointChange {
+    /// Names of the changed fields: `type_name`, or the QoS policies, e.g.
+    /// `reliability` or `history`.
+    pub fn changed_fields(&self) -> Vec<&'static str> {
+        let (old, new) = (&self.old.qos, &self.new.qos);
+        let mut fields = Vec::new();
+        if self.old.type_name != self.new.type_name {
+            fields.push("type_name");
+        }
+        let policies = [
+            ("durability", old.durability() != new.durability()),
+            ("presentation", old.presentation() != new.presentation()),
+            ("deadline", old.deadline() != new.deadline()),
+            (
+                "latency_budget",
+                old.latency_budget() != new.latency_budget(),
+            ),
+            ("ownership", old.ownership() != new.ownership()),
+            ("liveliness", old.liveliness() != new.liveliness()),
+            (
+                "time_based_filter",
+                old.time_based_filter() != new.time_based_filter(),
+            ),
+            ("reliability", old.reliability() != new.reliability()),
+            (
+                "destination_order",
+                old.destination_order() != new.destination_order(),
+            ),
+            ("history", old.history() != new.history()),
+            (
+                "resource_limits",
+                old.resource_limits() != new.resource_limits(),
+            ),
+            ("lifespan", old.lifespan() != new.lifespan()),
+        ];
+        fields.extend(
+            policies
+                .into_iter()
+                .filter(|(_, changed)| *changed)
+                .map(|(name, _)| name),
+        );
+        fields
+    }
+}
+
+// Builds the snapshot from what the Node knows. Local endpoints are included
+// even if Discovery does not report them.
+pub(crate) fn collect(
+    domain_id: u16,
+    own_node_name: String,
+    external_nodes: &BTreeMap<Gid, Vec<NodeEntitiesInfo>>,
+    remote_endpoints: &BTreeMap<GUID, (EndpointKind, EndpointDescription)>,
+    local_endpoints: &LocalEndpoints,
+) -> GraphSnapshot {
+    let mut nodes = BTreeSet::new();
+    let mut node_of_endpoint = BTreeMap::new();
+    for info in external_nodes.values().flatten() {
+        let name = info.fully_qualified_name();
+        for gid in info.readers().iter().chain(info.writers()) {
+            node_of_endpoint.insert(*gid, name.clone());
+        }
+        nodes.insert(name);
+    }
+
+    let mut endpoints = BTreeMap::new();
+    for (guid, (kind, description)) in remote_endpoints {
+        let gid = Gid::from(*guid);
+        endpoints.insert(
+            gid,
+            EndpointSnapshot::new(
+                node_of_endpoint.get(&gid).cloned(),
+                *kind == EndpointKind::Reader,
+                gid,
+                &description.topic_name,
+                &description.type_name,
+                description.qos.clone(),
+            ),
+        );
+    }
+    for local in local_endpoints.iter() {
+        endpoints.insert(
+            local.gid,
+            EndpointSnapshot::new(
+                Some(own_node_name.clone()),
+                local.is_reader,
+                local.gid,
+                &local.dds_topic_name,
+                &local.dds_type_name,
+                local.qos.clone(),
+            ),
+        );
+    }
+    nodes.insert(own_node_name);
+
+    let mut endpoints: Vec<EndpointSnapshot> = endpoints.into_values().collect();
+    endpoints.sort_by(|a, b| {
+        (&a.node, &a.topic, &a.dds_topic, a.is_reader, &a.gid).cmp(&(
+            &b.node,
+            &b.topic,
+            &b.dds_topic,
+            b.is_reader,
+            &b.gid,
+        ))
+    });
+    GraphSnapshot {
+        domain_id,
+        time_ns: ROSTime::now().to_nanos(),
+        nodes: nodes.into_iter().collect(),
+        endpoints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::policy::{History, Reliability};
+
+    use super::*;
+    use crate::node::context::DEFAULT_PUBLISHER_QOS;
+
+    fn endpoint(node: &str, topic: &str, gid: &str, qos: &QosPolicies) -> EndpointSnapshot {
+        EndpointSnapshot {
+            node: Some(node.to_owned()),
+            is_reader: false,
+            topic: topic.to_owned(),
+            dds_topic: format!("rt{topic}"),
+            type_name: "std_msgs/msg/String".to_owned(),
+            gid: gid.to_owned(),
+            qos: qos.clone(),
+        }
+    }
+
+    fn snapshot(nodes: &[&str], endpoints: Vec<EndpointSnapshot>) -> GraphSnapshot {
+        GraphSnapshot {
+            domain_id: 0,
+            time_ns: 0,
+            nodes: nodes.iter().map(|n| n.to_string()).collect(),
+            endpoints,
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let snapshot = snapshot(
+            &["/talker"],
+            vec![endpoint(
+                "/talker",
+                "/chatter",
+                "01",
+                &DEFAULT_PUBLISHER_QOS,
+            )],
+        );
+        let json = snapshot.to_json().unwrap();
+        assert!(json.contains("\"dds_topic\": \"rt/chatter\""));
+        assert_eq!(GraphSnapshot::from_json(&json).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn diff() {
+        let qos = DEFAULT_PUBLISHER_QOS.clone();
+        let best_effort = qos.modify_by(
+            &QosPolicies::builder()
+                .reliability(Reliability::BestEffort)
+                .history(History::KeepLast { depth: 7 })
+                .build(),
+        );
+        let old = snapshot(
+            &["/a", "/b"],
+            vec![
+                endpoint("/a", "/x", "01", &qos),
+                endpoint("/a", "/y", "02", &qos),
+                endpoint("/b", "/z", "03", &qos),
+            ],
+        );
+        // /a restarted with new Gids, /b is gone, /c is new.
+        let new = snapshot(
+            &["/a", "/c"],
+            vec![
+                endpoint("/a", "/x", "11", &qos),
+                endpoint("/a", "/y", "12", &best_effort),
+                endpoint("/c", "/z", "13", &qos),
+            ],
+        );
+
+        assert!(old.diff(&old).is_empty());
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_nodes, vec!["/c".to_owned()]);
+        assert_eq!(diff.removed_nodes, vec!["/b".to_owned()]);
+        assert_eq!(diff.added_endpoints.len(), 1);
+        assert_eq!(diff.added_endpoints[0].gid, "13");
+        assert_eq!(diff.removed_endpoints.len(), 1);
+        assert_eq!(diff.removed_endpoints[0].gid, "03");
+        assert_eq!(diff.changed_endpoints.len(), 1);
+        assert_eq!(
+            diff.changed_endpoints[0].changed_fields(),
+            vec!["reliability", "history"]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Detection of a too shallow History depth
+//!
+//! A Subscription with History `KeepLast { depth }` keeps only the latest
+//! `depth` samples of each Publisher. If the application takes samples more
+//! slowly than they arrive, unread samples are overwritten, which shows up as
+//! gaps in the sequence numbers of each Publisher.
+//!
+//! The Subscription looks at these gaps over windows of received samples. If
+//! samples are lost in several consecutive windows, it logs a warning with a
+//! suggested depth. The latest warning is also available from
+//! [`Subscription::history_depth_warning`](super::pubsub::Subscription::history_depth_warning).
+//!
+//! With BestEffort Reliability, gaps may also be caused by network losses,
+//! which a deeper History does not fix.
+
+use std::{collections::BTreeMap, fmt, sync::Mutex};
+
+use log::warn;
+use rustdds::{policy::History, QosPolicies, SequenceNumber, GUID};
+
+// Samples received per observation window
+const WINDOW: u64 = 100;
+// Window is bad, if at least this share of samples was lost.
+const LOSS_THRESHOLD_PERCENT: u64 = 5;
+// Number of consecutive bad windows to warn
+const BAD_WINDOWS: u32 = 3;
+
+/// Warning that a Subscription keeps losing samples, because its History
+/// depth is too small for the rate at which samples are taken.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryDepthWarning {
+    /// ROS 2 Topic name of the Subscription
+    pub topic_name: String,
+    /// Current History depth
+    pub depth: i32,
+    /// Samples received during the observation
+    pub samples_received: u64,
+    /// Samples lost during the observation
+    pub samples_lost: u64,
+    /// Depth that would have covered the largest observed gap
+    pub suggested_depth: i32,
+}
+
+impl fmt::Display for HistoryDepthWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Subscription to {} lost {} of {} samples with History depth {}. Consider depth {}.",
+            self.topic_name,
+            self.samples_lost,
+            self.samples_received + self.samples_lost,
+            self.depth,
+            self.suggested_depth
+        )
+    }
+}
+
+pub(crate) struct HistoryMonitor {
+    topic_name: String,
+    depth: i32,
+    state: Mutex<MonitorState>,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    last_sequence_numbers: BTreeMap<GUID, SequenceNumber>,
+    // current window
+    received: u64,
+    lost: u64,
+    // consecutive bad windows
+    bad_windows: u32,
+    bad_received: u64,
+    bad_lost: u64,
+    max_gap: i64,
+    warning: Option<HistoryDepthWarning>,
+}
+
+impl HistoryMonitor {
+    // Monitoring only makes sense with KeepLast History.
+    pub(crate) fn new(topic_name: String, qos: &QosPolicies) -> Option<HistoryMonitor> {
+        match qos.history() {
+            Some(History::KeepLast { depth }) => Some(HistoryMonitor {
+                topic_name,
+                depth,
+                state: Mutex::new(MonitorState::default()),
+            }),
+            _ => None,
+        }
+    }
+
+    // Record a received sample.
+    pub(crate) fn observe(&self, writer: GUID, sequence_number: SequenceNumber) {
+        let mut state = self.state.lock().unwrap();
+        let previous = state.last_sequence_numbers.insert(writer, sequence_number);
+        // If the sequence number did not increase, the Publisher may have
+        // restarted. Then we cannot tell what was lost.
+        let gap = previous
+            .map(|p| i64::from(sequence_number) - i64::from(p) - 1)
+            .filter(|gap| *gap > 0)
+            .unwrap_or(0);
+        state.received += 1;
+        state.lost += gap as u64;
+        state.max_gap = state.max_gap.max(gap);
+
+        if state.received < WINDOW {
+            return;
+        }
+        let (received, lost) = (state.received, state.lost);
+        state.received = 0;
+        state.lost = 0;
+        if lost * 100 < (received + lost) * LOSS_THRESHOLD_PERCENT {
+            state.bad_windows = 0;
+            state.bad_received = 0;
+            state.bad_lost = 0;
+            state.max_gap = 0;
+            return;
+        }
+        state.bad_windows += 1;
+        state.bad_received += received;
+        state.bad_lost += lost;
+        if state.bad_windows < BAD_WINDOWS {
+            return;
+        }
+
+        let suggested_depth = u32::try_from(i64::from(self.depth) + state.max_gap)
+            .ok()
+            .and_then(u32::checked_next_power_of_two)
+            .and_then(|d| i32::try_from(d).ok())
+            .unwrap_or(i32::MAX);
+        let warning = HistoryDepthWarning {
+            topic_name: self.topic_name.clone(),
+            depth: self.depth,
+            samples_received: state.bad_received,
+            samples_lost: state.bad_lost,
+            suggested_depth,
+        };
+        // Do not repeat the same advice.
+        if state
+            .warning
+            .as_ref()
+            .is_none_or(|w| w.suggested_depth < suggested_depth)
+        {
+            warn!("{warning}");
+        }
+        state.warning = Some(warning);
+        state.bad_windows = 0;
+        state.bad_received = 0;
+        state.bad_lost = 0;
+        state.max_gap = 0;
+    }
+
+    pub(crate) fn warning(&self) -> Option<HistoryDepthWarning> {
+        self.state.lock().unwrap().warning.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::QosPolicyBuilder;
+
+    use super::*;
+
+    #[test]
+    fn warns_about_consistent_loss() {
+        let qos = QosPolicyBuilder::new()
+            .history(History::KeepLast { depth: 4 })
+            .build();
+        let monitor = HistoryMonitor::new("/chatter".to_string(), &qos).unwrap();
+        let writer = GUID::from_bytes([1; 16]);
+
+        // Every other sample is lost, with a few larger gaps.
+        let mut sn = 0;
+        for i in 0..(WINDOW * u64::from(BAD_WINDOWS)) {
+            sn += if i % 50 == 0 { 7 } else { 2 };
+            monitor.observe(writer, SequenceNumber::from(sn));
+        }
+        let warning = monitor.warning().unwrap();
+        assert_eq!(warning.depth, 4);
+        assert_eq!(warning.suggested_depth, 16); // 4 + 6, rounded up
+        assert!(warning.samples_lost > warning.samples_received / 2);
+    }
+}
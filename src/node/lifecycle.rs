@@ -0,0 +1,715 @@
+//! Managed (lifecycle) Nodes
+//!
+//! A [`LifecycleNode`] runs the ROS 2 managed Node state machine on top of a
+//! [`Node`]. It starts in the `unconfigured` state, and moves between
+//! `unconfigured`, `inactive`, `active` and `finalized` through the
+//! transitions `configure`, `activate`, `deactivate`, `cleanup` and
+//! `shutdown`. Each transition calls the corresponding method of
+//! [`LifecycleCallbacks`], whose result decides the next state. See the
+//! [design article](https://design.ros2.org/articles/node_lifecycle.html).
+//!
+//! Like `rclcpp_lifecycle`, the LifecycleNode provides the Services
+//! `<node name>/change_state`, `get_state`, `get_available_states`,
+//! `get_available_transitions` and `get_transition_graph`, and publishes
+//! each change of state on `<node name>/transition_event`. This is what
+//! e.g. the `nav2` lifecycle manager and `ros2 lifecycle set` use. The
+//! Services are served by [`LifecycleNode::serve`], which should run
+//! alongside the [`Spinner`](super::Spinner) of the Node. While it runs, the
+//! application can follow the state through a [`LifecycleStateHandle`].
+//!
+//! ```no_run
+//! use ros2_client::{
+//!     interfaces::{geometry_msgs::Vector3, lifecycle_msgs::State},
+//!     node::lifecycle::{CallbackReturn, LifecycleCallbacks, LifecycleNode},
+//!     prelude::*,
+//! };
+//!
+//! struct Talker {
+//!     publisher: Option<Publisher<Vector3>>,
+//! }
+//!
+//! impl LifecycleCallbacks for Talker {
+//!     fn on_configure(&mut self, node: &mut Node, _previous_state: &State) -> CallbackReturn {
+//!         match node.advertise("/position") {
+//!             Ok(publisher) => {
+//!                 self.publisher = Some(publisher);
+//!                 CallbackReturn::Success
+//!             }
+//!             Err(_) => CallbackReturn::Failure,
+//!         }
+//!     }
+//!
+//!     fn on_cleanup(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+//!         self.publisher = None;
+//!         CallbackReturn::Success
+//!     }
+//! }
+//!
+//! let context = Context::new().unwrap();
+//! let mut node = context
+//!     .new_node(NodeName::new("/", "talker").unwrap(), NodeOptions::new())
+//!     .unwrap();
+//! let _spin_handle = node.spin_in_background().unwrap();
+//! let mut lifecycle_node = LifecycleNode::new(node, Talker { publisher: None }).unwrap();
+//! smol::block_on(lifecycle_node.serve());
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+};
+
+use futures::{pin_mut, select, FutureExt};
+use log::{info, warn};
+use rustdds::{
+    dds::CreateResult,
+    policy::{Durability, History, Reliability},
+    QosPolicyBuilder,
+};
+
+use super::{pubsub::Publisher, Node};
+use crate::{
+    interfaces::lifecycle_msgs::{
+        ChangeState, ChangeStateRequest, ChangeStateResponse, GetAvailableStates,
+        GetAvailableStatesResponse, GetAvailableTransitions, GetAvailableTransitionsResponse,
+        GetState, GetStateResponse, State, Transition, TransitionDescription, TransitionEvent,
+    },
+    prelude::{Name, RmwRequestId},
+    service::server::Server,
+};
+
+/// Base name of the Topic of [`TransitionEvent`]s, under the Node name
+pub const TRANSITION_EVENT_TOPIC_NAME: &str = "transition_event";
+
+const SUCCESS_LABEL: &str = "transition_success";
+const FAILURE_LABEL: &str = "transition_failure";
+const ERROR_LABEL: &str = "transition_error";
+
+const UNCONFIGURED: u8 = State::PRIMARY_STATE_UNCONFIGURED;
+const INACTIVE: u8 = State::PRIMARY_STATE_INACTIVE;
+const ACTIVE: u8 = State::PRIMARY_STATE_ACTIVE;
+const FINALIZED: u8 = State::PRIMARY_STATE_FINALIZED;
+const CONFIGURING: u8 = State::TRANSITION_STATE_CONFIGURING;
+const CLEANINGUP: u8 = State::TRANSITION_STATE_CLEANINGUP;
+const SHUTTINGDOWN: u8 = State::TRANSITION_STATE_SHUTTINGDOWN;
+const ACTIVATING: u8 = State::TRANSITION_STATE_ACTIVATING;
+const DEACTIVATING: u8 = State::TRANSITION_STATE_DEACTIVATING;
+const ERRORPROCESSING: u8 = State::TRANSITION_STATE_ERRORPROCESSING;
+
+// The state machine of rcl_lifecycle: (transition, label, start state, goal
+// state)
+#[rustfmt::skip]
+const TRANSITIONS: &[(u8, &str, u8, u8)] = &[
+    (Transition::TRANSITION_CONFIGURE, "configure", UNCONFIGURED, CONFIGURING),
+    (Transition::TRANSITION_ON_CONFIGURE_SUCCESS, SUCCESS_LABEL, CONFIGURING, INACTIVE),
+    (Transition::TRANSITION_ON_CONFIGURE_FAILURE, FAILURE_LABEL, CONFIGURING, UNCONFIGURED),
+    (Transition::TRANSITION_ON_CONFIGURE_ERROR, ERROR_LABEL, CONFIGURING, ERRORPROCESSING),
+    (Transition::TRANSITION_CLEANUP, "cleanup", INACTIVE, CLEANINGUP),
+    (Transition::TRANSITION_ON_CLEANUP_SUCCESS, SUCCESS_LABEL, CLEANINGUP, UNCONFIGURED),
+    (Transition::TRANSITION_ON_CLEANUP_FAILURE, FAILURE_LABEL, CLEANINGUP, INACTIVE),
+    (Transition::TRANSITION_ON_CLEANUP_ERROR, ERROR_LABEL, CLEANINGUP, ERRORPROCESSING),
+    (Transition::TRANSITION_ACTIVATE, "activate", INACTIVE, ACTIVATING),
+    (Transition::TRANSITION_ON_ACTIVATE_SUCCESS, SUCCESS_LABEL, ACTIVATING, ACTIVE),
+    (Transition::TRANSITION_ON_ACTIVATE_FAILURE, FAILURE_LABEL, ACTIVATING, INACTIVE),
+    (Transition::TRANSITION_ON_ACTIVATE_ERROR, ERROR_LABEL, ACTIVATING, ERRORPROCESSING),
+    (Transition::TRANSITION_DEACTIVATE, "deactivate", ACTIVE, DEACTIVATING),
+    (Transition::TRANSITION_ON_DEACTIVATE_SUCCESS, SUCCESS_LABEL, DEACTIVATING, INACTIVE),
+    (Transition::TRANSITION_ON_DEACTIVATE_FAILURE, FAILURE_LABEL, DEACTIVATING, ACTIVE),
+    (Transition::TRANSITION_ON_DEACTIVATE_ERROR, ERROR_LABEL, DEACTIVATING, ERRORPROCESSING),
+    (Transition::TRANSITION_UNCONFIGURED_SHUTDOWN, "shutdown", UNCONFIGURED, SHUTTINGDOWN),
+    (Transition::TRANSITION_INACTIVE_SHUTDOWN, "shutdown", INACTIVE, SHUTTINGDOWN),
+    (Transition::TRANSITION_ACTIVE_SHUTDOWN, "shutdown", ACTIVE, SHUTTINGDOWN),
+    (Transition::TRANSITION_ON_SHUTDOWN_SUCCESS, SUCCESS_LABEL, SHUTTINGDOWN, FINALIZED),
+    (Transition::TRANSITION_ON_SHUTDOWN_FAILURE, FAILURE_LABEL, SHUTTINGDOWN, FINALIZED),
+    (Transition::TRANSITION_ON_SHUTDOWN_ERROR, ERROR_LABEL, SHUTTINGDOWN, ERRORPROCESSING),
+    (Transition::TRANSITION_ON_ERROR_SUCCESS, SUCCESS_LABEL, ERRORPROCESSING, UNCONFIGURED),
+    (Transition::TRANSITION_ON_ERROR_FAILURE, FAILURE_LABEL, ERRORPROCESSING, FINALIZED),
+    (Transition::TRANSITION_ON_ERROR_ERROR, ERROR_LABEL, ERRORPROCESSING, FINALIZED),
+];
+
+const STATES: &[(u8, &str)] = &[
+    (State::PRIMARY_STATE_UNKNOWN, "unknown"),
+    (UNCONFIGURED, "unconfigured"),
+    (INACTIVE, "inactive"),
+    (ACTIVE, "active"),
+    (FINALIZED, "finalized"),
+    (CONFIGURING, "configuring"),
+    (CLEANINGUP, "cleaningup"),
+    (SHUTTINGDOWN, "shuttingdown"),
+    (ACTIVATING, "activating"),
+    (DEACTIVATING, "deactivating"),
+    (ERRORPROCESSING, "errorprocessing"),
+];
+
+fn state(id: u8) -> State {
+    let label = STATES
+        .iter()
+        .find(|(i, _)| *i == id)
+        .map_or("unknown", |(_, label)| label);
+    State::new(id, label)
+}
+
+fn description(&(id, label, start, goal): &(u8, &str, u8, u8)) -> TransitionDescription {
+    TransitionDescription {
+        transition: Transition::new(id, label),
+        start_state: state(start),
+        goal_state: state(goal),
+    }
+}
+
+// The transition out of `state` chosen by `id`, or by `label` if `id` is 0
+fn find_transition(state: u8, id: u8, label: &str) -> Option<&'static (u8, &'static str, u8, u8)> {
+    TRANSITIONS.iter().find(|(i, l, start, _)| {
+        *start == state
+            && if id == 0 && !label.is_empty() {
+                *l == label
+            } else {
+                *i == id
+            }
+    })
+}
+
+/// Result of a [`LifecycleCallbacks`] method
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallbackReturn {
+    /// The transition succeeded.
+    Success,
+    /// The transition failed, and the Node goes back to the previous state.
+    Failure,
+    /// The Node is broken. [`LifecycleCallbacks::on_error`] is called next.
+    Error,
+}
+
+impl CallbackReturn {
+    fn label(self) -> &'static str {
+        match self {
+            CallbackReturn::Success => SUCCESS_LABEL,
+            CallbackReturn::Failure => FAILURE_LABEL,
+            CallbackReturn::Error => ERROR_LABEL,
+        }
+    }
+}
+
+/// Application code run by the transitions of a [`LifecycleNode`]
+///
+/// Each method gets the Node, e.g. for creating Publishers, and the primary
+/// state the transition started from. Like in `rclcpp_lifecycle`, the
+/// default implementations succeed, except for `on_error`, which moves the
+/// Node to `finalized`.
+pub trait LifecycleCallbacks {
+    fn on_configure(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+        CallbackReturn::Success
+    }
+
+    fn on_cleanup(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+        CallbackReturn::Success
+    }
+
+    fn on_activate(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+        CallbackReturn::Success
+    }
+
+    fn on_deactivate(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+        CallbackReturn::Success
+    }
+
+    fn on_shutdown(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+        CallbackReturn::Success
+    }
+
+    /// Called when another callback returned [`CallbackReturn::Error`].
+    /// `Success` moves the Node to `unconfigured`, otherwise it is
+    /// `finalized`.
+    fn on_error(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+        CallbackReturn::Failure
+    }
+}
+
+/// No application code: all transitions succeed.
+impl LifecycleCallbacks for () {}
+
+/// The requested transition is not available in the current state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransitionError {
+    pub transition: Transition,
+    pub state: State,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let transition = if self.transition.id == 0 && !self.transition.label.is_empty() {
+            self.transition.label.clone()
+        } else {
+            self.transition.id.to_string()
+        };
+        write!(
+            f,
+            "Transition {transition} is not available in state {}",
+            self.state.label
+        )
+    }
+}
+
+impl Error for TransitionError {}
+
+/// A Node with the managed Node state machine. See the
+/// [module documentation](self).
+pub struct LifecycleNode<C: LifecycleCallbacks> {
+    node: Node,
+    callbacks: C,
+    state: LifecycleStateHandle,
+    change_state_server: Server<ChangeState>,
+    get_state_server: Server<GetState>,
+    get_available_states_server: Server<GetAvailableStates>,
+    get_available_transitions_server: Server<GetAvailableTransitions>,
+    get_transition_graph_server: Server<GetAvailableTransitions>,
+    transition_event_publisher: Publisher<TransitionEvent>,
+}
+
+/// The current state of a [`LifecycleNode`], readable while
+/// [`LifecycleNode::serve`] holds the Node. Clones follow the same Node.
+#[derive(Clone, Debug)]
+pub struct LifecycleStateHandle(Arc<AtomicU8>);
+
+impl LifecycleStateHandle {
+    pub fn current_state(&self) -> State {
+        state(self.id())
+    }
+
+    /// Whether the Node is in the `active` state, i.e. should do its work
+    pub fn is_active(&self) -> bool {
+        self.id() == ACTIVE
+    }
+
+    fn id(&self) -> u8 {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn set(&self, id: u8) {
+        self.0.store(id, Ordering::Release);
+    }
+}
+
+// A request received by one of the Servers
+enum LifecycleRequest {
+    ChangeState(RmwRequestId, ChangeStateRequest),
+    GetState(RmwRequestId),
+    GetAvailableStates(RmwRequestId),
+    GetAvailableTransitions(RmwRequestId),
+    GetTransitionGraph(RmwRequestId),
+}
+
+impl<C: LifecycleCallbacks> LifecycleNode<C> {
+    /// Creates the lifecycle Services and the transition event Publisher on
+    /// `node`. The Node starts in the `unconfigured` state.
+    pub fn new(mut node: Node, callbacks: C) -> CreateResult<LifecycleNode<C>> {
+        let node_name = node.fully_qualified_name();
+        let name = |base_name| Name::new(&node_name, base_name).unwrap();
+
+        let change_state_server = node.create_server_for(&name("change_state"))?;
+        let get_state_server = node.create_server_for(&name("get_state"))?;
+        let get_available_states_server = node.create_server_for(&name("get_available_states"))?;
+        let get_available_transitions_server =
+            node.create_server_for(&name("get_available_transitions"))?;
+        let get_transition_graph_server = node.create_server_for(&name("get_transition_graph"))?;
+
+        // Same as rcl_lifecycle
+        let qos = QosPolicyBuilder::new()
+            .durability(Durability::Volatile)
+            .reliability(Reliability::Reliable {
+                max_blocking_time: rustdds::Duration::from_millis(100),
+            })
+            .history(History::KeepLast { depth: 10 })
+            .build();
+        let topic =
+            node.create_topic_for::<TransitionEvent>(&name(TRANSITION_EVENT_TOPIC_NAME), &qos)?;
+        let transition_event_publisher = node.create_publisher(&topic, None)?;
+
+        Ok(LifecycleNode {
+            node,
+            callbacks,
+            state: LifecycleStateHandle(Arc::new(AtomicU8::new(UNCONFIGURED))),
+            change_state_server,
+            get_state_server,
+            get_available_states_server,
+            get_available_transitions_server,
+            get_transition_graph_server,
+            transition_event_publisher,
+        })
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    pub fn node_mut(&mut self) -> &mut Node {
+        &mut self.node
+    }
+
+    pub fn callbacks(&self) -> &C {
+        &self.callbacks
+    }
+
+    pub fn callbacks_mut(&mut self) -> &mut C {
+        &mut self.callbacks
+    }
+
+    /// Removes the lifecycle Services and Publisher, and returns the Node and
+    /// the callbacks.
+    pub fn into_inner(self) -> (Node, C) {
+        (self.node, self.callbacks)
+    }
+
+    pub fn current_state(&self) -> State {
+        self.state.current_state()
+    }
+
+    /// Whether the Node is in the `active` state, i.e. should do its work
+    pub fn is_active(&self) -> bool {
+        self.state.is_active()
+    }
+
+    /// A handle for following the state from other tasks, e.g. while
+    /// [`Self::serve`] runs.
+    pub fn state_handle(&self) -> LifecycleStateHandle {
+        self.state.clone()
+    }
+
+    /// All states of the state machine
+    pub fn available_states(&self) -> Vec<State> {
+        STATES.iter().map(|(id, _)| state(*id)).collect()
+    }
+
+    /// Transitions that can be triggered in the current state
+    pub fn available_transitions(&self) -> Vec<TransitionDescription> {
+        TRANSITIONS
+            .iter()
+            .filter(|(_, _, start, _)| *start == self.state.id())
+            .map(description)
+            .collect()
+    }
+
+    /// All transitions of the state machine
+    pub fn transition_graph(&self) -> Vec<TransitionDescription> {
+        TRANSITIONS.iter().map(description).collect()
+    }
+
+    /// Triggers the transition `transition_id`, e.g.
+    /// [`Transition::TRANSITION_CONFIGURE`], and runs its callback. Returns
+    /// the result of the callback, or of `on_error` if the callback returned
+    /// [`CallbackReturn::Error`].
+    ///
+    /// This is what a `change_state` request does.
+    pub fn trigger_transition(
+        &mut self,
+        transition_id: u8,
+    ) -> Result<CallbackReturn, TransitionError> {
+        self.trigger(transition_id, "")
+    }
+
+    pub fn configure(&mut self) -> Result<CallbackReturn, TransitionError> {
+        self.trigger_transition(Transition::TRANSITION_CONFIGURE)
+    }
+
+    pub fn cleanup(&mut self) -> Result<CallbackReturn, TransitionError> {
+        self.trigger_transition(Transition::TRANSITION_CLEANUP)
+    }
+
+    pub fn activate(&mut self) -> Result<CallbackReturn, TransitionError> {
+        self.trigger_transition(Transition::TRANSITION_ACTIVATE)
+    }
+
+    pub fn deactivate(&mut self) -> Result<CallbackReturn, TransitionError> {
+        self.trigger_transition(Transition::TRANSITION_DEACTIVATE)
+    }
+
+    /// Shuts down from any primary state other than `finalized`.
+    pub fn shutdown(&mut self) -> Result<CallbackReturn, TransitionError> {
+        self.trigger(0, "shutdown")
+    }
+
+    fn trigger(&mut self, id: u8, label: &str) -> Result<CallbackReturn, TransitionError> {
+        let Some(&(id, label, start, goal)) = find_transition(self.state.id(), id, label) else {
+            return Err(TransitionError {
+                transition: Transition::new(id, label),
+                state: self.current_state(),
+            });
+        };
+        self.change_to(id, label, start, goal);
+
+        let previous_state = state(start);
+        let result = self.run_callback(goal, &previous_state);
+        let next_state = self.finish_transition(goal, result);
+        if result == CallbackReturn::Error {
+            let on_error_result = self.run_callback(next_state, &previous_state);
+            self.finish_transition(next_state, on_error_result);
+        }
+        Ok(result)
+    }
+
+    fn run_callback(&mut self, transition_state: u8, previous_state: &State) -> CallbackReturn {
+        let node = &mut self.node;
+        match transition_state {
+            CONFIGURING => self.callbacks.on_configure(node, previous_state),
+            CLEANINGUP => self.callbacks.on_cleanup(node, previous_state),
+            SHUTTINGDOWN => self.callbacks.on_shutdown(node, previous_state),
+            ACTIVATING => self.callbacks.on_activate(node, previous_state),
+            DEACTIVATING => self.callbacks.on_deactivate(node, previous_state),
+            _ => self.callbacks.on_error(node, previous_state),
+        }
+    }
+
+    // Leaves `transition_state` according to the callback result, and
+    // returns the new state.
+    fn finish_transition(&mut self, transition_state: u8, result: CallbackReturn) -> u8 {
+        let &(id, label, start, goal) = find_transition(transition_state, 0, result.label())
+            .expect("every transition state has success, failure and error transitions");
+        self.change_to(id, label, start, goal);
+        goal
+    }
+
+    fn change_to(&mut self, id: u8, label: &str, start: u8, goal: u8) {
+        self.state.set(goal);
+        let start_state = state(start);
+        let goal_state = state(goal);
+        if goal < CONFIGURING {
+            info!(
+                "{}: {} -> {}",
+                self.node.fully_qualified_name(),
+                start_state.label,
+                goal_state.label
+            );
+        }
+        let event = TransitionEvent {
+            timestamp: self.node.time_now().to_nanos().max(0) as u64,
+            transition: Transition::new(id, label),
+            start_state,
+            goal_state,
+        };
+        if let Err(e) = self.transition_event_publisher.publish(event) {
+            warn!("Cannot publish transition event: {e:?}");
+        }
+    }
+
+    /// Serves the lifecycle Services. This never returns, so run it e.g.
+    /// with `select` alongside the application tasks. Transition callbacks
+    /// run within this future. Use [`Self::state_handle`] to read the state
+    /// meanwhile.
+    pub async fn serve(&mut self) {
+        loop {
+            match self.receive_request().await {
+                Ok(request) => self.handle_request(request).await,
+                Err(e) => warn!("Lifecycle Service request: {e:?}"),
+            }
+        }
+    }
+
+    async fn receive_request(&self) -> rustdds::dds::ReadResult<LifecycleRequest> {
+        let change_state = self.change_state_server.async_receive_request().fuse();
+        let get_state = self.get_state_server.async_receive_request().fuse();
+        let get_available_states = self
+            .get_available_states_server
+            .async_receive_request()
+            .fuse();
+        let get_available_transitions = self
+            .get_available_transitions_server
+            .async_receive_request()
+            .fuse();
+        let get_transition_graph = self
+            .get_transition_graph_server
+            .async_receive_request()
+            .fuse();
+        pin_mut!(
+            change_state,
+            get_state,
+            get_available_states,
+            get_available_transitions,
+            get_transition_graph
+        );
+        select! {
+            r = change_state => r.map(|(id, request)| LifecycleRequest::ChangeState(id, request)),
+            r = get_state => r.map(|(id, _)| LifecycleRequest::GetState(id)),
+            r = get_available_states => r.map(|(id, _)| LifecycleRequest::GetAvailableStates(id)),
+            r = get_available_transitions => {
+                r.map(|(id, _)| LifecycleRequest::GetAvailableTransitions(id))
+            }
+            r = get_transition_graph => r.map(|(id, _)| LifecycleRequest::GetTransitionGraph(id)),
+        }
+    }
+
+    async fn handle_request(&mut self, request: LifecycleRequest) {
+        let result = match request {
+            LifecycleRequest::ChangeState(id, request) => {
+                let success = match self.trigger(request.transition.id, &request.transition.label) {
+                    Ok(result) => result == CallbackReturn::Success,
+                    Err(e) => {
+                        warn!("{}: {e}", self.node.fully_qualified_name());
+                        false
+                    }
+                };
+                self.change_state_server
+                    .async_send_response(id, ChangeStateResponse { success })
+                    .await
+            }
+            LifecycleRequest::GetState(id) => {
+                let current_state = self.current_state();
+                self.get_state_server
+                    .async_send_response(id, GetStateResponse { current_state })
+                    .await
+            }
+            LifecycleRequest::GetAvailableStates(id) => {
+                let available_states = self.available_states();
+                self.get_available_states_server
+                    .async_send_response(id, GetAvailableStatesResponse { available_states })
+                    .await
+            }
+            LifecycleRequest::GetAvailableTransitions(id) => {
+                let available_transitions = self.available_transitions();
+                self.get_available_transitions_server
+                    .async_send_response(
+                        id,
+                        GetAvailableTransitionsResponse {
+                            available_transitions,
+                        },
+                    )
+                    .await
+            }
+            LifecycleRequest::GetTransitionGraph(id) => {
+                let available_transitions = self.transition_graph();
+                self.get_transition_graph_server
+                    .async_send_response(
+                        id,
+                        GetAvailableTransitionsResponse {
+                            available_transitions,
+                        },
+                    )
+                    .await
+            }
+        };
+        if let Err(e) = result {
+            warn!("Lifecycle Service response: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Context, NodeName, NodeOptions};
+
+    // Fails to configure once, then succeeds. Activation is an error.
+    #[derive(Default)]
+    struct Flaky {
+        configure_attempts: u32,
+        errors: u32,
+    }
+
+    impl LifecycleCallbacks for Flaky {
+        fn on_configure(&mut self, _node: &mut Node, previous_state: &State) -> CallbackReturn {
+            assert_eq!(previous_state.id, State::PRIMARY_STATE_UNCONFIGURED);
+            self.configure_attempts += 1;
+            if self.configure_attempts == 1 {
+                CallbackReturn::Failure
+            } else {
+                CallbackReturn::Success
+            }
+        }
+
+        fn on_activate(&mut self, _node: &mut Node, _previous_state: &State) -> CallbackReturn {
+            CallbackReturn::Error
+        }
+
+        fn on_error(&mut self, _node: &mut Node, previous_state: &State) -> CallbackReturn {
+            assert_eq!(previous_state.id, State::PRIMARY_STATE_INACTIVE);
+            self.errors += 1;
+            CallbackReturn::Success
+        }
+    }
+
+    #[test]
+    fn transition_table() {
+        for &(_, _, start, goal) in TRANSITIONS {
+            assert_ne!(state(start).label, "unknown");
+            assert_ne!(state(goal).label, "unknown");
+        }
+        for transition_state in
+            State::TRANSITION_STATE_CONFIGURING..=State::TRANSITION_STATE_ERRORPROCESSING
+        {
+            for result in [
+                CallbackReturn::Success,
+                CallbackReturn::Failure,
+                CallbackReturn::Error,
+            ] {
+                assert!(find_transition(transition_state, 0, result.label()).is_some());
+            }
+        }
+        let shutdown = find_transition(State::PRIMARY_STATE_ACTIVE, 0, "shutdown").unwrap();
+        assert_eq!(shutdown.0, Transition::TRANSITION_ACTIVE_SHUTDOWN);
+        assert!(find_transition(State::PRIMARY_STATE_FINALIZED, 0, "shutdown").is_none());
+    }
+
+    #[test]
+    fn state_machine() {
+        let context = Context::new().unwrap();
+        let node = context
+            .new_node(
+                NodeName::new("/", "lifecycle_test_node").unwrap(),
+                NodeOptions::new().enable_rosout(false),
+            )
+            .unwrap();
+        let mut lifecycle_node = LifecycleNode::new(node, Flaky::default()).unwrap();
+        let state_handle = lifecycle_node.state_handle();
+        assert_eq!(
+            lifecycle_node.current_state(),
+            State::new(1, "unconfigured")
+        );
+        assert!(lifecycle_node.activate().is_err());
+
+        assert_eq!(lifecycle_node.configure(), Ok(CallbackReturn::Failure));
+        assert_eq!(
+            lifecycle_node.current_state().id,
+            State::PRIMARY_STATE_UNCONFIGURED
+        );
+        assert_eq!(lifecycle_node.configure(), Ok(CallbackReturn::Success));
+        assert_eq!(
+            lifecycle_node.current_state().id,
+            State::PRIMARY_STATE_INACTIVE
+        );
+        assert_eq!(state_handle.current_state(), lifecycle_node.current_state());
+        let available: Vec<u8> = lifecycle_node
+            .available_transitions()
+            .iter()
+            .map(|t| t.transition.id)
+            .collect();
+        assert_eq!(
+            available,
+            [
+                Transition::TRANSITION_CLEANUP,
+                Transition::TRANSITION_ACTIVATE,
+                Transition::TRANSITION_INACTIVE_SHUTDOWN
+            ]
+        );
+
+        // The error is handled by on_error, which recovers to unconfigured.
+        assert_eq!(lifecycle_node.activate(), Ok(CallbackReturn::Error));
+        assert_eq!(lifecycle_node.callbacks().errors, 1);
+        assert_eq!(
+            lifecycle_node.current_state().id,
+            State::PRIMARY_STATE_UNCONFIGURED
+        );
+
+        assert_eq!(lifecycle_node.shutdown(), Ok(CallbackReturn::Success));
+        assert_eq!(lifecycle_node.current_state().label, "finalized");
+        assert!(lifecycle_node.available_transitions().is_empty());
+
+        // The handle is readable while serve() borrows the LifecycleNode.
+        let serving = lifecycle_node.serve();
+        assert_eq!(state_handle.current_state().label, "finalized");
+        assert!(!state_handle.is_active());
+        drop(serving);
+    }
+}
@@ -0,0 +1,307 @@
+//! Descriptions of the entities created by the local [`Node`](super::Node)
+//!
+//! See [`Node::publishers`](super::Node::publishers),
+//! [`Node::subscriptions`](super::Node::subscriptions),
+//! [`Node::services`](super::Node::services) and
+//! [`Node::clients`](super::Node::clients).
+
+use std::sync::Arc;
+
+use rustdds::QosPolicies;
+
+use crate::interfaces::{
+    gid::Gid,
+    names::{dds_name_to_ros, dds_type_to_ros, DdsTopicKind},
+};
+
+use super::counters::{Bandwidth, Counters};
+
+/// A Publisher or Subscription of the local Node
+#[derive(Clone, Debug)]
+pub struct TopicEndpointDescriptor {
+    pub gid: Gid,
+    /// Fully qualified ROS 2 Topic name, after remapping, e.g. `/chatter`
+    pub topic_name: String,
+    /// ROS 2 type name, e.g. `std_msgs/msg/String`
+    pub type_name: String,
+    pub qos: QosPolicies,
+}
+
+/// Current rates of a Publisher or Subscription of the local Node
+///
+/// See [`Node::topic_bandwidth`](super::Node::topic_bandwidth).
+#[derive(Clone, Debug)]
+pub struct TopicBandwidth {
+    pub endpoint: TopicEndpointDescriptor,
+    /// Publisher or Subscription?
+    pub is_publisher: bool,
+    pub bandwidth: Bandwidth,
+}
+
+/// A Service Server or Client of the local Node
+///
+/// For a Server, the request side is a Subscription and the response side a
+/// Publisher. For a Client, it is the other way around.
+#[derive(Clone, Debug)]
+pub struct ServiceEndpointDescriptor {
+    /// Fully qualified ROS 2 Service name, after remapping, e.g.
+    /// `/add_two_ints`
+    pub service_name: String,
+    /// ROS 2 Service type name, e.g. `example_interfaces/srv/AddTwoInts`
+    pub type_name: String,
+    pub request_gid: Gid,
+    pub request_qos: QosPolicies,
+    pub response_gid: Gid,
+    pub response_qos: QosPolicies,
+}
+
+// A DDS Reader or Writer created via the Node
+#[derive(Clone, Debug)]
+pub(crate) struct LocalEndpoint {
+    pub gid: Gid,
+    pub is_reader: bool,
+    pub dds_topic_name: String,
+    pub dds_type_name: String,
+    pub qos: QosPolicies,
+    // Set by Publishers and Subscriptions after creation
+    pub counters: Option<Arc<Counters>>,
+}
+
+impl LocalEndpoint {
+    fn ros_name(&self) -> Option<(DdsTopicKind, String)> {
+        dds_name_to_ros(&self.dds_topic_name)
+    }
+
+    fn ros_type_name(&self) -> String {
+        dds_type_to_ros(&self.dds_type_name).unwrap_or_else(|| self.dds_type_name.clone())
+    }
+
+    // None, if this is not on an ordinary Topic
+    fn topic_descriptor(&self) -> Option<TopicEndpointDescriptor> {
+        match self.ros_name() {
+            Some((DdsTopicKind::Topic, topic_name)) => Some(TopicEndpointDescriptor {
+                gid: self.gid,
+                topic_name,
+                type_name: self.ros_type_name(),
+                qos: self.qos.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Local endpoints of a Node, in creation order
+#[derive(Default)]
+pub(crate) struct LocalEndpoints {
+    endpoints: Vec<LocalEndpoint>,
+}
+
+impl LocalEndpoints {
+    pub fn insert(&mut self, endpoint: LocalEndpoint) {
+        self.endpoints.push(endpoint);
+    }
+
+    pub fn remove(&mut self, gid: Gid) {
+        self.endpoints.retain(|e| e.gid != gid);
+    }
+
+    pub fn attach_counters(&mut self, gid: Gid, counters: Arc<Counters>) {
+        if let Some(e) = self.endpoints.iter_mut().find(|e| e.gid == gid) {
+            e.counters = Some(counters);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LocalEndpoint> + '_ {
+        self.endpoints.iter()
+    }
+
+    pub fn readers(&self) -> impl Iterator<Item = Gid> + '_ {
+        self.endpoints.iter().filter(|e| e.is_reader).map(|e| e.gid)
+    }
+
+    pub fn writers(&self) -> impl Iterator<Item = Gid> + '_ {
+        self.endpoints
+            .iter()
+            .filter(|e| !e.is_reader)
+            .map(|e| e.gid)
+    }
+
+    // Endpoints on ordinary Topics
+    pub fn topic_endpoints(&self, readers: bool) -> Vec<TopicEndpointDescriptor> {
+        self.endpoints
+            .iter()
+            .filter(|e| e.is_reader == readers)
+            .filter_map(LocalEndpoint::topic_descriptor)
+            .collect()
+    }
+
+    // Publishers and Subscriptions, in creation order
+    pub fn topic_bandwidths(&self) -> Vec<TopicBandwidth> {
+        self.endpoints
+            .iter()
+            .filter_map(|e| {
+                Some(TopicBandwidth {
+                    endpoint: e.topic_descriptor()?,
+                    is_publisher: !e.is_reader,
+                    bandwidth: e.counters.as_ref()?.bandwidth(),
+                })
+            })
+            .collect()
+    }
+
+    // Servers (`servers == true`) or Clients. A Server or Client creates its
+    // request and response endpoints one after the other, so each request side
+    // is paired with the next unused response side of the same Service.
+    pub fn service_endpoints(&self, servers: bool) -> Vec<ServiceEndpointDescriptor> {
+        // Servers read requests, Clients write them.
+        let request_is_reader = servers;
+        let mut used = vec![false; self.endpoints.len()];
+        let mut result = Vec::new();
+        for (i, request) in self.endpoints.iter().enumerate() {
+            let Some((DdsTopicKind::ServiceRequest, service_name)) = request.ros_name() else {
+                continue;
+            };
+            if request.is_reader != request_is_reader {
+                continue;
+            }
+            let response = self
+                .endpoints
+                .iter()
+                .enumerate()
+                .skip(i + 1)
+                .find(|(j, e)| {
+                    !used[*j]
+                        && e.is_reader != request_is_reader
+                        && e.ros_name() == Some((DdsTopicKind::ServiceReply, service_name.clone()))
+                });
+            let Some((j, response)) = response else {
+                continue;
+            };
+            used[j] = true;
+            let type_name = request.ros_type_name();
+            let type_name = type_name
+                .strip_suffix("_Request")
+                .unwrap_or(&type_name)
+                .to_owned();
+            result.push(ServiceEndpointDescriptor {
+                service_name,
+                type_name,
+                request_gid: request.gid,
+                request_qos: request.qos.clone(),
+                response_gid: response.gid,
+                response_qos: response.qos.clone(),
+            });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{QosPolicyBuilder, GUID};
+
+    use super::*;
+    use crate::{
+        interfaces::{builtin_interfaces::Time, std_msgs::Header},
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+    };
+
+    fn endpoint(
+        id: u8,
+        is_reader: bool,
+        dds_topic_name: &str,
+        dds_type_name: &str,
+    ) -> LocalEndpoint {
+        LocalEndpoint {
+            gid: GUID::from_bytes([id; 16]).into(),
+            is_reader,
+            dds_topic_name: dds_topic_name.to_owned(),
+            dds_type_name: dds_type_name.to_owned(),
+            qos: QosPolicyBuilder::new().build(),
+            counters: None,
+        }
+    }
+
+    #[test]
+    fn services_and_topics() {
+        let mut endpoints = LocalEndpoints::default();
+        endpoints.insert(endpoint(
+            1,
+            false,
+            "rt/chatter",
+            "std_msgs::msg::dds_::String_",
+        ));
+        // Server
+        endpoints.insert(endpoint(
+            2,
+            true,
+            "rq/add_two_intsRequest",
+            "example_interfaces::srv::dds_::AddTwoInts_Request_",
+        ));
+        endpoints.insert(endpoint(
+            3,
+            false,
+            "rr/add_two_intsReply",
+            "example_interfaces::srv::dds_::AddTwoInts_Response_",
+        ));
+        // Client
+        endpoints.insert(endpoint(
+            4,
+            false,
+            "rq/add_two_intsRequest",
+            "example_interfaces::srv::dds_::AddTwoInts_Request_",
+        ));
+        endpoints.insert(endpoint(
+            5,
+            true,
+            "rr/add_two_intsReply",
+            "example_interfaces::srv::dds_::AddTwoInts_Response_",
+        ));
+
+        let publishers = endpoints.topic_endpoints(false);
+        assert_eq!(publishers.len(), 1);
+        assert_eq!(publishers[0].topic_name, "/chatter");
+        assert_eq!(publishers[0].type_name, "std_msgs/msg/String");
+        assert!(endpoints.topic_endpoints(true).is_empty());
+
+        for servers in [true, false] {
+            let services = endpoints.service_endpoints(servers);
+            assert_eq!(services.len(), 1);
+            assert_eq!(services[0].service_name, "/add_two_ints");
+            assert_eq!(services[0].type_name, "example_interfaces/srv/AddTwoInts");
+        }
+    }
+
+    #[test]
+    fn topic_bandwidth() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_bandwidth_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let publisher = node.advertise::<Header>("/bandwidth_test").unwrap();
+        let header = Header {
+            stamp: Time::ZERO,
+            frame_id: "base_link".to_owned(),
+        };
+        publisher.publish(header).unwrap();
+        // CDR payload: stamp 8, string length 4, "base_link\0" 10
+        assert_eq!(publisher.status_counters().bytes_written, 22);
+
+        let bandwidths = node.topic_bandwidth();
+        let entry = bandwidths
+            .iter()
+            .find(|t| t.endpoint.topic_name == "/bandwidth_test")
+            .unwrap();
+        assert!(entry.is_publisher);
+        let diagnostics = node.bandwidth_diagnostics();
+        assert!(diagnostics
+            .status
+            .iter()
+            .any(|s| s.name == "/rustdds/test_bandwidth_node: publisher /bandwidth_test"));
+    }
+}
@@ -5,47 +5,111 @@ use std::{
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, Weak,
     },
+    time::{Duration, Instant},
 };
 
 use async_channel::Receiver;
-use context::{Context, DEFAULT_SUBSCRIPTION_QOS};
+use context::{
+    Context, DEFAULT_CLIENT_RESPONSE_QOS, DEFAULT_PUBLISHER_QOS, DEFAULT_SERVICE_QOS,
+    DEFAULT_SUBSCRIPTION_QOS,
+};
 use entities_info::{NodeEntitiesInfo, ParticipantEntitiesInfo};
 use futures::{
     pin_mut, stream, stream::FusedStream, task, task::Poll, Future, FutureExt, Stream, StreamExt,
 };
+use futures_timer::Delay;
 
 use rustdds::{
-    dds::{CreateError, CreateResult},
-    no_key, policy, DomainParticipantStatusEvent, QosPolicies, QosPolicyBuilder, RTPSEntity as _,
-    StatusEvented as _, Timestamp, Topic, TopicKind, GUID,
+    dds::{qos::HasQoSPolicy as _, CreateError, CreateResult, WriteResult},
+    no_key, policy, DomainParticipantStatusEvent, EndpointDescription, QosPolicies,
+    RTPSEntity as _, StatusEvented as _, Timestamp, Topic, TopicDescription as _, TopicKind, GUID,
 };
 use serde::Serialize;
 
+#[cfg(any(feature = "rosout", feature = "parameters"))]
+mod builtin_writer;
+pub mod chunked;
 pub mod context;
+pub mod counters;
+pub mod describe;
+pub mod discovery_cache;
 pub mod entities_info;
+#[cfg(feature = "fault-injection")]
+pub mod fault_injection;
+#[cfg(feature = "graph-snapshot")]
+pub mod graph_snapshot;
+pub mod history_monitor;
+pub mod lifecycle;
+pub mod local_entities;
 pub mod pubsub;
-
+pub mod rate_limit;
+pub mod remapping;
+pub mod resend;
+pub mod resources;
+pub mod snapshot;
+pub mod spin_handle;
+pub mod startup;
+pub mod statistics;
+pub mod stream_ext;
+pub mod supervisor;
+pub mod user_data;
+
+#[cfg(all(feature = "actions", feature = "introspection"))]
+use crate::{
+    action::introspection::ActionIntrospection,
+    interfaces::service_msgs::{IntrospectionState, SERVICE_EVENT_TOPIC_SUFFIX},
+};
+#[cfg(feature = "actions")]
 use crate::{
     action::{
         ActionClient, ActionClientQosPolicies, ActionServer, ActionServerQosPolicies, ActionTypes,
     },
-    interfaces::{builtin_interfaces, gid::Gid, rcl_interfaces},
-    log::{Log, LogLevel},
-    prelude::{
-        ActionTypeName, MessageTypeName, Name, NodeName, Parameter, ParameterValue, ROSTime,
-        ServiceTypeName,
-    },
+    prelude::ActionTypeName,
+};
+#[cfg(feature = "parameters")]
+use crate::{
+    interfaces::rcl_interfaces,
+    prelude::{Parameter, ParameterValue},
     service::{
         parameters::{raw, ParameterDescriptor, SetParametersResult},
-        Client, Server, Service, ServiceMapping,
+        request_id::RmwRequestId,
     },
 };
+use crate::{
+    interfaces::{diagnostic_msgs, gid::Gid, rosgraph_msgs, std_msgs::Header, std_srvs},
+    log::{Log, LogLevel, LogSink},
+    message::NamedMessage,
+    prelude::{MessageTypeName, Name, NodeName, ROSTime, ServiceTypeName},
+    service::{
+        call_trace::{CallTrace, CallTracer},
+        generic::{GenericClient, GenericServer},
+        server::DrainServer,
+        Client, NamedService, Server, Service, ServiceMapping, ServiceMappingTable,
+    },
+};
+#[cfg(any(feature = "rosout", feature = "parameters"))]
+use builtin_writer::BuiltinWriter;
+use counters::Counters;
+use discovery_cache::{DiscoveryCache, DiscoveryCacheConfig, EndpointKind};
+use local_entities::{
+    LocalEndpoint, LocalEndpoints, ServiceEndpointDescriptor, TopicBandwidth,
+    TopicEndpointDescriptor,
+};
 use log::{debug, error, info, trace, warn};
-use pubsub::{Publisher, Subscription};
-
+use pubsub::{OwnershipFilter, Publisher, Subscription};
+use remapping::{RemapRule, Remapping};
+#[cfg(feature = "parameters")]
+use rustdds::{dds::ReadResult, QosPolicyBuilder};
+use spin_handle::SpinHandle;
+use startup::StartupTimes;
+use statistics::{LoadMeter, NodeStatistics};
+
+#[cfg(feature = "parameters")]
 type ParameterFunc = dyn Fn(&str, &ParameterValue) -> SetParametersResult + Send;
+#[cfg(feature = "parameters")]
+type ParameterSetFunc = dyn Fn(&BTreeMap<String, ParameterValue>) -> SetParametersResult + Send;
 
 /// Configuration of [Node]
 /// This is a builder-like struct.
@@ -55,17 +119,42 @@ type ParameterFunc = dyn Fn(&str, &ParameterValue) -> SetParametersResult + Send
 /// they ae always needed and have no reasonable default.
 #[must_use]
 pub struct NodeOptions {
-    #[allow(dead_code)]
     cli_args: Vec<String>,
-    #[allow(dead_code)]
     use_global_arguments: bool, // process-wide command line args
+    remap_rules: Vec<RemapRule>,
+    #[cfg(feature = "rosout")]
     enable_rosout: bool, // use rosout topic for logging?
+    #[cfg(feature = "rosout")]
     enable_rosout_reading: bool,
+    #[cfg(feature = "parameters")]
     start_parameter_services: bool,
+    #[cfg(feature = "parameters")]
     declared_parameters: Vec<Parameter>,
+    #[cfg(feature = "parameters")]
+    read_only_parameters: BTreeSet<String>,
+    #[cfg(feature = "parameters")]
     allow_undeclared_parameters: bool,
+    #[cfg(feature = "parameters")]
     parameter_validator: Option<Box<ParameterFunc>>,
+    #[cfg(feature = "parameters")]
+    parameter_set_validator: Option<Box<ParameterSetFunc>>,
+    #[cfg(feature = "parameters")]
     parameter_set_action: Option<Box<ParameterFunc>>,
+    reject_unbounded_history: bool,
+    #[cfg(all(feature = "parameters", feature = "rosout"))]
+    parameter_audit_log: bool,
+    describe_service: bool,
+    log_sinks: Vec<Arc<dyn LogSink>>,
+    call_trace: Option<Arc<CallTrace>>,
+    default_publisher_qos: Option<QosPolicies>,
+    default_subscription_qos: Option<QosPolicies>,
+    topic_qos_overrides: Vec<(String, QosPolicies)>, // (pattern, qos)
+    service_mapping_table: ServiceMappingTable,
+    discovery_cache: Option<DiscoveryCacheConfig>,
+    #[cfg(any(feature = "rosout", feature = "parameters"))]
+    lazy_builtin_endpoints: bool,
+    statistics_period: Option<Duration>,
+    server_drain_timeout: Option<Duration>,
 }
 
 impl NodeOptions {
@@ -76,15 +165,66 @@ impl NodeOptions {
         NodeOptions {
             cli_args: Vec::new(),
             use_global_arguments: true,
+            remap_rules: Vec::new(),
+            #[cfg(feature = "rosout")]
             enable_rosout: true,
+            #[cfg(feature = "rosout")]
             enable_rosout_reading: false,
+            #[cfg(feature = "parameters")]
             start_parameter_services: true,
+            #[cfg(feature = "parameters")]
             declared_parameters: Vec::new(),
+            #[cfg(feature = "parameters")]
+            read_only_parameters: BTreeSet::new(),
+            #[cfg(feature = "parameters")]
             allow_undeclared_parameters: false,
+            #[cfg(feature = "parameters")]
             parameter_validator: None,
+            #[cfg(feature = "parameters")]
+            parameter_set_validator: None,
+            #[cfg(feature = "parameters")]
             parameter_set_action: None,
+            reject_unbounded_history: false,
+            #[cfg(all(feature = "parameters", feature = "rosout"))]
+            parameter_audit_log: false,
+            describe_service: false,
+            log_sinks: Vec::new(),
+            call_trace: None,
+            default_publisher_qos: None,
+            default_subscription_qos: None,
+            topic_qos_overrides: Vec::new(),
+            service_mapping_table: ServiceMappingTable::default(),
+            discovery_cache: None,
+            #[cfg(any(feature = "rosout", feature = "parameters"))]
+            lazy_builtin_endpoints: false,
+            statistics_period: None,
+            server_drain_timeout: None,
         }
     }
+    /// Command line arguments for this Node.
+    ///
+    /// Remapping rules are read from the `--ros-args` section. See
+    /// [`remapping`].
+    pub fn arguments(self, cli_args: Vec<String>) -> NodeOptions {
+        NodeOptions { cli_args, ..self }
+    }
+
+    /// Should the Node also use the process command line arguments?
+    /// The default is `true`.
+    pub fn use_global_arguments(self, use_global_arguments: bool) -> NodeOptions {
+        NodeOptions {
+            use_global_arguments,
+            ..self
+        }
+    }
+
+    /// Add a name remapping rule. See [`remapping`].
+    pub fn remap(mut self, rule: RemapRule) -> NodeOptions {
+        self.remap_rules.push(rule);
+        self
+    }
+
+    #[cfg(feature = "rosout")]
     pub fn enable_rosout(self, enable_rosout: bool) -> NodeOptions {
         NodeOptions {
             enable_rosout,
@@ -92,6 +232,14 @@ impl NodeOptions {
         }
     }
 
+    /// Without the `rosout` feature there is no rosout writer, so this does
+    /// nothing. It is kept so that code builds with and without the feature.
+    #[cfg(not(feature = "rosout"))]
+    pub fn enable_rosout(self, _enable_rosout: bool) -> NodeOptions {
+        self
+    }
+
+    #[cfg(feature = "rosout")]
     pub fn read_rosout(self, enable_rosout_reading: bool) -> NodeOptions {
         NodeOptions {
             enable_rosout_reading,
@@ -99,6 +247,7 @@ impl NodeOptions {
         }
     }
 
+    #[cfg(feature = "parameters")]
     pub fn declare_parameter(mut self, name: &str, value: ParameterValue) -> NodeOptions {
         self.declared_parameters.push(Parameter {
             name: name.to_owned(),
@@ -108,15 +257,203 @@ impl NodeOptions {
         self
     }
 
+    /// Declare a parameter that cannot be changed after Node creation.
+    ///
+    /// Attempts to set it, either locally or via Parameter Services, are
+    /// rejected.
+    #[cfg(feature = "parameters")]
+    pub fn declare_read_only_parameter(self, name: &str, value: ParameterValue) -> NodeOptions {
+        let mut options = self.declare_parameter(name, value);
+        options.read_only_parameters.insert(name.to_owned());
+        options
+    }
+
+    #[cfg(feature = "parameters")]
     pub fn parameter_validator(mut self, validator: Box<ParameterFunc>) -> NodeOptions {
         self.parameter_validator = Some(validator);
         self
     }
 
+    /// Set a validator that sees all parameters at once.
+    ///
+    /// The validator is given the complete prospective parameter set, i.e.
+    /// current parameters with the requested changes applied. This allows
+    /// checking constraints between parameters, such as "max_speed >=
+    /// min_speed". If the validator returns `Err`, none of the requested
+    /// changes are applied, and the error string is returned to the caller
+    /// as the reason.
+    ///
+    /// This is called after the per-parameter validator set with
+    /// [`Self::parameter_validator`].
+    #[cfg(feature = "parameters")]
+    pub fn parameter_set_validator(mut self, validator: Box<ParameterSetFunc>) -> NodeOptions {
+        self.parameter_set_validator = Some(validator);
+        self
+    }
+
+    #[cfg(feature = "parameters")]
     pub fn parameter_set_action(mut self, action: Box<ParameterFunc>) -> NodeOptions {
         self.parameter_set_action = Some(action);
         self
     }
+
+    /// Refuse to create Publishers and Subscriptions with `KeepAll` history
+    /// and no bound in `ResourceLimits`, as these may use unbounded amounts
+    /// of memory. See [`crate::qos::has_unbounded_history`].
+    ///
+    /// If this is not set, creating them only logs a warning.
+    pub fn reject_unbounded_history(self, reject_unbounded_history: bool) -> NodeOptions {
+        NodeOptions {
+            reject_unbounded_history,
+            ..self
+        }
+    }
+
+    /// Log every parameter change requested via Parameter Services to rosout.
+    ///
+    /// Each entry records the requester (the [`Gid`] of the requesting
+    /// Client), the parameter name, and the old and new values, so that remote
+    /// reconfiguration leaves an audit trail. Entries are written by the
+    /// [`Spinner`], with logger name `<node name>.parameter_audit`.
+    ///
+    /// Rosout must be enabled for this to have any effect.
+    #[cfg(all(feature = "parameters", feature = "rosout"))]
+    pub fn parameter_audit_log(self, parameter_audit_log: bool) -> NodeOptions {
+        NodeOptions {
+            parameter_audit_log,
+            ..self
+        }
+    }
+
+    /// Start the hidden Service `<node name>/_describe`, which returns a YAML
+    /// description of this Node for remote debugging.
+    ///
+    /// The Service is of type `std_srvs/srv/Trigger`, and the description is
+    /// in the `message` field of the response. See [`describe`] for the
+    /// contents. The Service is served by the [`Spinner`].
+    pub fn describe_service(self, describe_service: bool) -> NodeOptions {
+        NodeOptions {
+            describe_service,
+            ..self
+        }
+    }
+
+    /// Also write the messages of the [`rosout`](crate::rosout!) macro to
+    /// `sink`, e.g. syslog or journald. See [`crate::log::sink`].
+    ///
+    /// Messages are written to the sinks even if rosout is not enabled. This
+    /// can be given several times to add several sinks.
+    pub fn log_sink(mut self, sink: Arc<dyn LogSink>) -> NodeOptions {
+        self.log_sinks.push(sink);
+        self
+    }
+
+    /// Record Service calls and Action goals of this Node in `trace`. See
+    /// [`call_trace`](crate::service::call_trace).
+    pub fn call_trace(self, trace: Arc<CallTrace>) -> NodeOptions {
+        NodeOptions {
+            call_trace: Some(trace),
+            ..self
+        }
+    }
+
+    /// Default QoS policies for Publishers created by this Node.
+    ///
+    /// Policies set here override those of the Topic, e.g.
+    /// [`DEFAULT_PUBLISHER_QOS`](context::DEFAULT_PUBLISHER_QOS). Policies in
+    /// [`Self::topic_qos_override`] or given to [`Node::create_publisher`]
+    /// take precedence over these, in this order.
+    pub fn default_publisher_qos(self, qos: QosPolicies) -> NodeOptions {
+        NodeOptions {
+            default_publisher_qos: Some(qos),
+            ..self
+        }
+    }
+
+    /// Default QoS policies for Subscriptions created by this Node.
+    ///
+    /// Like [`Self::default_publisher_qos`], but for Subscriptions.
+    pub fn default_subscription_qos(self, qos: QosPolicies) -> NodeOptions {
+        NodeOptions {
+            default_subscription_qos: Some(qos),
+            ..self
+        }
+    }
+
+    /// Use QoS policies `qos` for Publishers and Subscriptions on Topics
+    /// matching `pattern`.
+    ///
+    /// The pattern is matched against the absolute (remapped) Topic name, see
+    /// [`crate::qos::topic_pattern_matches`]. The policies in `qos` modify
+    /// those of the Topic, but QoS given explicitly at Publisher or
+    /// Subscription creation still takes precedence.
+    ///
+    /// If several patterns match, all are applied in the order they were
+    /// added, so later ones win.
+    pub fn topic_qos_override(mut self, pattern: &str, qos: QosPolicies) -> NodeOptions {
+        self.topic_qos_overrides.push((pattern.to_owned(), qos));
+        self
+    }
+
+    /// Which [`ServiceMapping`] to use for which Service.
+    ///
+    /// The table is consulted by [`Node::service_mapping_for`], and for the
+    /// built-in Parameter Services of the Node. The default is
+    /// [`ServiceMapping::Enhanced`] for all.
+    pub fn service_mapping_table(self, service_mapping_table: ServiceMappingTable) -> NodeOptions {
+        NodeOptions {
+            service_mapping_table,
+            ..self
+        }
+    }
+
+    /// Keep discovered remote endpoints in a file, and use them at the next
+    /// start until Discovery has found them again. See [`discovery_cache`].
+    pub fn discovery_cache(self, config: DiscoveryCacheConfig) -> NodeOptions {
+        NodeOptions {
+            discovery_cache: Some(config),
+            ..self
+        }
+    }
+
+    /// Create the `/rosout` and `/parameter_events` writers only when they are
+    /// first needed, to start the Node faster. See [`startup`].
+    ///
+    /// The writers then appear in ROS Discovery only after their first use,
+    /// and `/rosout` subscribers miss nothing only if they are already
+    /// running. The default is `false`.
+    #[cfg(any(feature = "rosout", feature = "parameters"))]
+    pub fn lazy_builtin_endpoints(self, lazy_builtin_endpoints: bool) -> NodeOptions {
+        NodeOptions {
+            lazy_builtin_endpoints,
+            ..self
+        }
+    }
+
+    /// Publish [`Node::statistics`] every `period` on the hidden Topic
+    /// `<node name>/_statistics`. See [`statistics`].
+    ///
+    /// The statistics are published by the [`Spinner`]. The default is not
+    /// to publish them.
+    pub fn statistics_period(self, period: Duration) -> NodeOptions {
+        NodeOptions {
+            statistics_period: Some(period),
+            ..self
+        }
+    }
+
+    /// Default [drain timeout](crate::service::Server::set_drain_timeout) of
+    /// the Service and Action Servers of this Node.
+    ///
+    /// [`Node::drain_servers`] then waits up to `timeout` for the Servers to
+    /// answer the requests already received, so that Clients do not time out
+    /// during e.g. rolling restarts. The default is not to wait.
+    pub fn server_drain_timeout(self, timeout: Duration) -> NodeOptions {
+        NodeOptions {
+            server_drain_timeout: Some(timeout),
+            ..self
+        }
+    }
 }
 
 impl Default for NodeOptions {
@@ -132,8 +469,18 @@ impl Default for NodeOptions {
 pub enum NodeEvent {
     DDS(DomainParticipantStatusEvent),
     ROS(ParticipantEntitiesInfo),
+    /// A remote endpoint was discovered on a Topic that this Node uses, but
+    /// with a different type name. Such endpoints never match, so this
+    /// usually indicates a misconfiguration.
+    TypeMismatch {
+        topic_name: String,
+        local_type_name: String,
+        remote_type_name: String,
+        remote_endpoint: GUID,
+    },
 }
 
+#[cfg(feature = "parameters")]
 struct ParameterServers {
     get_parameters_server: Server<rcl_interfaces::GetParametersService>,
     get_parameter_types_server: Server<rcl_interfaces::GetParameterTypesService>,
@@ -143,6 +490,63 @@ struct ParameterServers {
     describe_parameters_server: Server<rcl_interfaces::DescribeParametersService>,
 }
 
+// A request received by one of the ParameterServers
+#[cfg(feature = "parameters")]
+enum ParameterRequest {
+    Get(ReadResult<(RmwRequestId, rcl_interfaces::GetParametersRequest)>),
+    GetTypes(ReadResult<(RmwRequestId, rcl_interfaces::GetParameterTypesRequest)>),
+    Set(ReadResult<(RmwRequestId, rcl_interfaces::SetParametersRequest)>),
+    SetAtomically(ReadResult<(RmwRequestId, rcl_interfaces::SetParametersRequest)>),
+    List(ReadResult<(RmwRequestId, rcl_interfaces::ListParametersRequest)>),
+    Describe(ReadResult<(RmwRequestId, rcl_interfaces::DescribeParametersRequest)>),
+}
+
+#[cfg(feature = "parameters")]
+impl ParameterServers {
+    // Requests to all of the Servers, as one Stream
+    fn request_stream(&self) -> stream::SelectAll<stream::BoxStream<'_, ParameterRequest>> {
+        stream::select_all([
+            self.get_parameters_server
+                .receive_request_stream()
+                .map(ParameterRequest::Get)
+                .boxed(),
+            self.get_parameter_types_server
+                .receive_request_stream()
+                .map(ParameterRequest::GetTypes)
+                .boxed(),
+            self.set_parameters_server
+                .receive_request_stream()
+                .map(ParameterRequest::Set)
+                .boxed(),
+            self.set_parameters_atomically_server
+                .receive_request_stream()
+                .map(ParameterRequest::SetAtomically)
+                .boxed(),
+            self.list_parameters_server
+                .receive_request_stream()
+                .map(ParameterRequest::List)
+                .boxed(),
+            self.describe_parameters_server
+                .receive_request_stream()
+                .map(ParameterRequest::Describe)
+                .boxed(),
+        ])
+    }
+}
+
+// Serves Self::describe_service
+struct DescribeServer {
+    server: Server<std_srvs::TriggerService>,
+    endpoints: Arc<Mutex<LocalEndpoints>>,
+}
+
+// Publishes NodeOptions::statistics_period
+struct StatisticsPublisher {
+    publisher: Publisher<diagnostic_msgs::DiagnosticArray>,
+    period: Duration,
+    endpoints: Arc<Mutex<LocalEndpoints>>,
+}
+
 // ----------------------------------------------------------------------------------------------------
 // ----------------------------------------------------------------------------------------------------
 /// Spinner implements Node's background event loop.
@@ -156,22 +560,48 @@ pub struct Spinner {
 
     readers_to_remote_writers: Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>>,
     writers_to_remote_readers: Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>>,
+    writer_ownership_strengths: Arc<Mutex<BTreeMap<GUID, i32>>>,
+    // DDS Topic name -> DDS type name, for Topics used by local endpoints
+    local_topic_types: Arc<Mutex<BTreeMap<String, String>>>,
+    // Remote endpoint GUID -> DDS Topic name, for all discovered endpoints
+    remote_writer_topics: Arc<Mutex<BTreeMap<GUID, String>>>,
+    remote_reader_topics: Arc<Mutex<BTreeMap<GUID, String>>>,
+    remote_endpoints: Arc<Mutex<BTreeMap<GUID, (EndpointKind, EndpointDescription)>>>,
     // Keep track of ros_discovery_info
     external_nodes: Arc<Mutex<BTreeMap<Gid, Vec<NodeEntitiesInfo>>>>,
+    discovery_cache: Option<Arc<Mutex<DiscoveryCache>>>,
     //suppress_node_info_updates: Arc<AtomicBool>, // temporarily suppress sending updates
     status_event_senders: Arc<Mutex<Vec<async_channel::Sender<NodeEvent>>>>,
 
     use_sim_time: Arc<AtomicBool>,
     sim_time: Arc<Mutex<ROSTime>>,
     clock_topic: Topic,
+    #[cfg(feature = "parameters")]
     allow_undeclared_parameters: bool,
 
+    #[cfg(feature = "parameters")]
     parameter_servers: Option<ParameterServers>,
-    parameter_events_writer: Arc<Publisher<raw::ParameterEvent>>,
+    describe_server: Option<DescribeServer>,
+    statistics_publisher: Option<StatisticsPublisher>,
+    spinner_load: Arc<LoadMeter>,
+    #[cfg(feature = "parameters")]
+    parameter_events_writer: Arc<BuiltinWriter<raw::ParameterEvent>>,
+    #[cfg(feature = "parameters")]
     parameters: Arc<Mutex<BTreeMap<String, ParameterValue>>>,
+    #[cfg(feature = "parameters")]
+    read_only_parameters: Arc<Mutex<BTreeSet<String>>>,
+    #[cfg(feature = "parameters")]
+    parameters_frozen: Arc<AtomicBool>,
+    #[cfg(feature = "parameters")]
     parameter_validator: Option<Arc<Mutex<Box<ParameterFunc>>>>,
+    #[cfg(feature = "parameters")]
+    parameter_set_validator: Option<Arc<Mutex<Box<ParameterSetFunc>>>>,
+    #[cfg(feature = "parameters")]
     parameter_set_action: Option<Arc<Mutex<Box<ParameterFunc>>>>,
     fully_qualified_node_name: String,
+    // rosout writer, if parameter changes are to be logged
+    #[cfg(all(feature = "parameters", feature = "rosout"))]
+    parameter_audit_writer: Option<Arc<BuiltinWriter<Log>>>,
 }
 
 async fn next_if_some<S>(s: &mut Option<S>) -> S::Item
@@ -184,8 +614,67 @@ where
     }
 }
 
+// Number of endpoints on the given DDS Topic
+fn count_topic(endpoint_topics: &Mutex<BTreeMap<GUID, String>>, dds_topic_name: &str) -> usize {
+    endpoint_topics
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|t| *t == dds_topic_name)
+        .count()
+}
+
+/// Removes a local endpoint from its Node's entry in ROS Discovery when
+/// dropped.
+///
+/// This is held by the Publisher, Subscription, Client or Server that owns
+/// the DDS Reader or Writer. Dropping the DDS entity already removes it from
+/// DDS Discovery.
+pub(crate) struct EndpointRegistration {
+    gid: Gid,
+    node_fqn: String,
+    endpoints: Weak<Mutex<LocalEndpoints>>,
+    context: Context,
+    buffer_reservation: resources::Reservation,
+}
+
+impl EndpointRegistration {
+    // Make the counters of the owning entity available to the Node.
+    pub(crate) fn attach_counters(&self, counters: Arc<Counters>) {
+        if let Some(endpoints) = self.endpoints.upgrade() {
+            endpoints
+                .lock()
+                .unwrap()
+                .attach_counters(self.gid, counters);
+        }
+    }
+}
+
+impl Drop for EndpointRegistration {
+    fn drop(&mut self) {
+        // If the Node is gone, it has already been removed altogether.
+        if let Some(endpoints) = self.endpoints.upgrade() {
+            endpoints.lock().unwrap().remove(self.gid);
+            self.context.remove_endpoint(&self.node_fqn, self.gid);
+        }
+        self.context.release_buffer(self.buffer_reservation);
+    }
+}
+
+// Topic name given as a string to a convenience constructor
+fn parse_topic_name(topic_name: &str) -> CreateResult<Name> {
+    Name::parse(topic_name).map_err(|e| CreateError::BadParameter {
+        reason: format!("Topic name {topic_name:?}: {e}"),
+    })
+}
+
 impl Spinner {
     pub async fn spin(self) -> CreateResult<()> {
+        let spinner_load = Arc::clone(&self.spinner_load);
+        spinner_load.measure(self.run()).await
+    }
+
+    async fn run(self) -> CreateResult<()> {
         let dds_status_listener = self.ros_context.domain_participant().status_listener();
         let dds_status_stream = dds_status_listener.as_async_status_stream();
         pin_mut!(dds_status_stream);
@@ -199,35 +688,35 @@ impl Spinner {
 
         let ros_clock_reader = self
             .ros_context
-            .create_subscription::<builtin_interfaces::Time>(&self.clock_topic, None)?;
+            .create_subscription::<rosgraph_msgs::Clock>(&self.clock_topic, None)?;
         let ros_clock_stream = ros_clock_reader.async_stream();
         pin_mut!(ros_clock_stream);
 
         // These are Option< impl Stream<_>>
-        let mut get_parameters_stream_opt = self
-            .parameter_servers
-            .as_ref()
-            .map(|s| s.get_parameters_server.receive_request_stream());
-        let mut get_parameter_types_stream_opt = self
-            .parameter_servers
-            .as_ref()
-            .map(|s| s.get_parameter_types_server.receive_request_stream());
-        let mut set_parameters_stream_opt = self
-            .parameter_servers
-            .as_ref()
-            .map(|s| s.set_parameters_server.receive_request_stream());
-        let mut set_parameters_atomically_stream_opt = self
-            .parameter_servers
-            .as_ref()
-            .map(|s| s.set_parameters_atomically_server.receive_request_stream());
-        let mut list_parameter_stream_opt = self
+        #[cfg(feature = "parameters")]
+        let mut parameter_request_stream_opt = self
             .parameter_servers
             .as_ref()
-            .map(|s| s.list_parameters_server.receive_request_stream());
-        let mut describe_parameters_stream_opt = self
-            .parameter_servers
+            .map(ParameterServers::request_stream);
+        // Without Parameter Services, the branch below never fires.
+        #[cfg(not(feature = "parameters"))]
+        let mut parameter_request_stream_opt: Option<
+            stream::Empty<std::convert::Infallible>,
+        > = None;
+        let mut describe_stream_opt = self
+            .describe_server
             .as_ref()
-            .map(|s| s.describe_parameters_server.receive_request_stream());
+            .map(|s| s.server.receive_request_stream());
+        let mut statistics_ticks_opt = self.statistics_publisher.as_ref().map(|s| {
+            let period = s.period;
+            Box::pin(
+                stream::unfold((), move |()| async move {
+                    Delay::new(period).await;
+                    Some(((), ()))
+                })
+                .fuse(),
+            )
+        });
 
         loop {
             futures::select! {
@@ -237,169 +726,66 @@ impl Spinner {
 
               clock_msg = ros_clock_stream.select_next_some() => {
                 match clock_msg {
-                  Ok((time,_msg_info)) => {
+                  Ok((clock,_msg_info)) => {
                     // Simulated time is updated internally unconditionally.
                     // The logic in Node decides if it is used.
-                    *self.sim_time.lock().unwrap() = time.into();
+                    *self.sim_time.lock().unwrap() = clock.into();
                   }
                   Err(e) => warn!("Simulated clock receive error {e:?}")
                 }
               }
 
 
-              get_parameters_request = next_if_some(&mut get_parameters_stream_opt).fuse() => {
-                match get_parameters_request {
-                  Ok( (req_id, req) ) => {
-                    info!("Get parameter request {req:?}");
-                    let values = {
-                      let param_db = self.parameters.lock().unwrap();
-                      req.names.iter()
-                        .map(|name| param_db.get(name.as_str())
-                          .unwrap_or(&ParameterValue::NotSet))
-                        .cloned()
-                        .map( raw::ParameterValue::from)
-                        .collect()
-                    };
-                    info!("Get parameters response: {values:?}");
-
-                    // .unwrap() below should be safe, as we would not be here if the Server did not exist
-                    self.parameter_servers.as_ref().unwrap().get_parameters_server
-                      .async_send_response(req_id, rcl_interfaces::GetParametersResponse{ values })
-                      .await
-                      .unwrap_or_else(|e| warn!("GetParameter response error {e:?}"));
-                  }
-                  Err(e) => warn!("GetParameter request error {e:?}"),
-                }
-              }
-
-              get_parameter_types_request = next_if_some(&mut get_parameter_types_stream_opt).fuse() => {
-                match get_parameter_types_request {
-                  Ok( (req_id, req) ) => {
-                    warn!("Get parameter types request");
-                    let values = {
-                      let param_db = self.parameters.lock().unwrap();
-                      req.names.iter()
-                        .map(|name| param_db.get(name.as_str())
-                          .unwrap_or(&ParameterValue::NotSet))
-                        .map(ParameterValue::to_parameter_type_raw)
-                        .collect()
-                    };
-                    info!("Get parameter types response: {values:?}");
-                    // .unwrap() below should be safe, as we would not be here if the Server did not exist
-                    self.parameter_servers.as_ref().unwrap().get_parameter_types_server
-                      .async_send_response(req_id, rcl_interfaces::GetParameterTypesResponse{ values })
-                      .await
-                      .unwrap_or_else(|e| warn!("GetParameterTypes response error {e:?}"));
-                  }
-                  Err(e) => warn!("GetParameterTypes request error {e:?}"),
-                }
-              }
-
-              set_parameters_request = next_if_some(&mut set_parameters_stream_opt).fuse() => {
-                match set_parameters_request {
-                  Ok( (req_id, req) ) => {
-                    info!("Set parameter request {req:?}");
-                    let results =
-                      req.parameter.iter()
-                        .cloned()
-                        .map( Parameter::from ) // convert from "raw::Parameter"
-                        .map( |Parameter{name, value}| self.set_parameter(&name,value))
-                        .map(|r| r.into()) // to "raw" Result for serialization
-                        .collect();
-                    info!("Set parameters response: {results:?}");
-                    // .unwrap() below should be safe, as we would not be here if the Server did not exist
-                    self.parameter_servers.as_ref().unwrap().set_parameters_server
-                      .async_send_response(req_id, rcl_interfaces::SetParametersResponse{ results })
-                      .await
-                      .unwrap_or_else(|e| warn!("SetParameters response error {e:?}"));
-                  }
-                  Err(e) => warn!("SetParameters request error {e:?}"),
-                }
-              }
-
-              set_parameters_atomically_request = next_if_some(&mut set_parameters_atomically_stream_opt).fuse() => {
-                match set_parameters_atomically_request {
-                  Ok( (req_id, req) ) => {
-                    warn!("Set parameters atomically request {req:?}");
-                    let results =
-                      req.parameter.iter()
-                        .cloned()
-                        .map( Parameter::from ) // convert from "raw::Parameter"
-                        .map( |Parameter{ .. } |
-                            // TODO: Implement atomic setting.
-                            Err("Setting parameters atomically is not implemented.".to_owned())
-                          )
-                        .map(|r| r.into()) // to "raw" Result for serialization
-                        .collect();
-                    warn!("Set parameters atomically response: {results:?}");
-                    // .unwrap() below should be safe, as we would not be here if the Server did not exist
-                    self.parameter_servers.as_ref().unwrap().set_parameters_atomically_server
-                      .async_send_response(req_id, rcl_interfaces::SetParametersAtomicallyResponse{ results })
-                      .await
-                      .unwrap_or_else(|e| warn!("SetParameters response error {e:?}"));
-                  }
-                  Err(e) => warn!("SetParametersAtomically request error {e:?}"),
-                }
+              parameter_request = next_if_some(&mut parameter_request_stream_opt).fuse() => {
+                #[cfg(feature = "parameters")]
+                self.serve_parameter_request(parameter_request).await;
+                #[cfg(not(feature = "parameters"))]
+                match parameter_request {}
               }
 
-              list_parameter_request = next_if_some(&mut list_parameter_stream_opt).fuse() => {
-                match list_parameter_request {
-                  Ok( (req_id, req) ) => {
-                    info!("List parameters request");
-                    let prefixes = req.prefixes;
-                    // TODO: We only generate the "names" part of the ListParametersResponse
-                    // What should we put into `prefixes` ?
-                    let names = {
-                      let param_db = self.parameters.lock().unwrap();
-                      param_db.keys()
-                        .filter_map(|name|
-                          if prefixes.is_empty() ||
-                            prefixes.iter().any(|prefix| name.starts_with(prefix))
-                          {
-                            Some(name.clone())
-                          } else { None }
-                        )
-                        .collect()
-                    };
-                    let result = rcl_interfaces::ListParametersResult{ names, prefixes: vec![] };
+              describe_request = next_if_some(&mut describe_stream_opt).fuse() => {
+                match describe_request {
+                  Ok( (req_id, _req) ) => {
                     // .unwrap() below should be safe, as we would not be here if the Server did not exist
-                    info!("List parameters response: {result:?}");
-                    self.parameter_servers.as_ref().unwrap().list_parameters_server
-                      .async_send_response(req_id, rcl_interfaces::ListParametersResponse{ result })
+                    let describe_server = self.describe_server.as_ref().unwrap();
+                    #[cfg(feature = "parameters")]
+                    let parameters = self.parameters.lock().unwrap().clone();
+                    #[cfg(not(feature = "parameters"))]
+                    let parameters = BTreeMap::new();
+                    let message = describe::describe_yaml(
+                      &self.fully_qualified_node_name,
+                      &describe_server.endpoints.lock().unwrap(),
+                      &parameters,
+                    );
+                    describe_server.server
+                      .async_send_response(req_id, std_srvs::TriggerResponse{ success: true, message })
                       .await
-                      .unwrap_or_else(|e| warn!("ListParameter response error {e:?}"));
+                      .unwrap_or_else(|e| warn!("Describe response error {e:?}"));
                   }
-                  Err(e) => warn!("ListParameter request error {e:?}"),
+                  Err(e) => warn!("Describe request error {e:?}"),
                 }
               }
 
-              describe_parameters_request = next_if_some(&mut describe_parameters_stream_opt).fuse() => {
-                match describe_parameters_request {
-                  Ok( (req_id, req) ) => {
-                    info!("Describe parameters request {req:?}");
-                    let values = {
-                      let parameters = self.parameters.lock().unwrap();
-                      req.names.iter()
-                        .map( |name|
-                          {
-                            if let Some(value) = parameters.get(name) {
-                              ParameterDescriptor::from_value(name, value)
-                            } else {
-                              ParameterDescriptor::unknown(name)
-                            }
-                          })
-                        .map(|r| r.into()) // to "raw" Result for serialization
-                        .collect()
-                    };
-                    info!("Describe parameters response: {values:?}");
-                    // .unwrap() below should be safe, as we would not be here if the Server did not exist
-                    self.parameter_servers.as_ref().unwrap().describe_parameters_server
-                      .async_send_response(req_id, rcl_interfaces::DescribeParametersResponse{ values })
-                      .await
-                      .unwrap_or_else(|e| warn!("DescribeParameters response error {e:?}"));
-                  }
-                  Err(e) => warn!("DescribeParameters request error {e:?}"),
-                }
+              _ = next_if_some(&mut statistics_ticks_opt).fuse() => {
+                // .unwrap() below should be safe, as we would not be here if the Publisher did not exist
+                let statistics_publisher = self.statistics_publisher.as_ref().unwrap();
+                let statistics = NodeStatistics::collect(
+                  &statistics_publisher.endpoints.lock().unwrap(),
+                  self.spinner_load.load(),
+                );
+                let stamp = if self.use_sim_time.load(Ordering::SeqCst) {
+                  *self.sim_time.lock().unwrap()
+                } else {
+                  ROSTime::now()
+                };
+                let message = diagnostic_msgs::DiagnosticArray {
+                  header: Header { stamp: stamp.into(), frame_id: String::new() },
+                  status: vec![statistics.to_diagnostic_status(&self.fully_qualified_node_name)],
+                };
+                statistics_publisher.publisher
+                  .async_publish(message)
+                  .await
+                  .unwrap_or_else(|e| warn!("Statistics publish error {e:?}"));
               }
 
               participant_info_update = ros_discovery_stream.select_next_some() => {
@@ -440,12 +826,38 @@ impl Spinner {
                     in self.writers_to_remote_readers.lock().unwrap().iter_mut() {
                       readers.remove(&guid);
                     }
+                    self.remote_reader_topics.lock().unwrap().remove(&guid);
+                    self.remote_endpoints.lock().unwrap().remove(&guid);
+                    self.forget_cached(guid);
+                  }
+                  DomainParticipantStatusEvent::ReaderDetected { ref reader } => {
+                    self.check_remote_type(reader);
+                    self.remote_reader_topics.lock().unwrap()
+                      .insert(reader.guid, reader.topic_name.clone());
+                    self.remote_endpoints.lock().unwrap()
+                      .insert(reader.guid, (EndpointKind::Reader, reader.clone()));
+                  }
+                  DomainParticipantStatusEvent::WriterDetected { ref writer } => {
+                    self.check_remote_type(writer);
+                    self.remote_writer_topics.lock().unwrap()
+                      .insert(writer.guid, writer.topic_name.clone());
+                    self.remote_endpoints.lock().unwrap()
+                      .insert(writer.guid, (EndpointKind::Writer, writer.clone()));
+                    let mut strengths = self.writer_ownership_strengths.lock().unwrap();
+                    match writer.qos.ownership() {
+                      Some(policy::Ownership::Exclusive { strength }) => strengths.insert(writer.guid, strength),
+                      _ => strengths.remove(&writer.guid),
+                    };
                   }
                   DomainParticipantStatusEvent::WriterLost {guid, ..} => {
                     for ( _local, writers)
                     in self.readers_to_remote_writers.lock().unwrap().iter_mut() {
                       writers.remove(&guid);
                     }
+                    self.writer_ownership_strengths.lock().unwrap().remove(&guid);
+                    self.remote_writer_topics.lock().unwrap().remove(&guid);
+                    self.remote_endpoints.lock().unwrap().remove(&guid);
+                    self.forget_cached(guid);
                   }
 
                   _ => {}
@@ -461,6 +873,37 @@ impl Spinner {
         //}
     } // fn
 
+    fn forget_cached(&self, guid: GUID) {
+        if let Some(cache) = &self.discovery_cache {
+            cache.lock().unwrap().forget(guid);
+        }
+    }
+
+    // Detect remote endpoints on our Topics, but with a different type.
+    // These would silently fail to match.
+    fn check_remote_type(&self, endpoint: &EndpointDescription) {
+        let local_type_name = self
+            .local_topic_types
+            .lock()
+            .unwrap()
+            .get(&endpoint.topic_name)
+            .cloned();
+        if let Some(local_type_name) = local_type_name {
+            if local_type_name != endpoint.type_name {
+                warn!(
+                    "Type mismatch on Topic {}: local type is {}, but {:?} has type {}",
+                    endpoint.topic_name, local_type_name, endpoint.guid, endpoint.type_name
+                );
+                self.send_status_event(&NodeEvent::TypeMismatch {
+                    topic_name: endpoint.topic_name.clone(),
+                    local_type_name,
+                    remote_type_name: endpoint.type_name.clone(),
+                    remote_endpoint: endpoint.guid,
+                });
+            }
+        }
+    }
+
     fn send_status_event(&self, event: &NodeEvent) {
         let mut closed = Vec::new();
         let mut sender_array = self.status_event_senders.lock().unwrap();
@@ -487,7 +930,202 @@ impl Spinner {
         }
     }
 
+    // Answers a request to the Parameter Services.
+    #[cfg(feature = "parameters")]
+    async fn serve_parameter_request(&self, request: ParameterRequest) {
+        // .unwrap() below should be safe, as we would not be here if the Servers did not exist
+        let servers = self.parameter_servers.as_ref().unwrap();
+        match request {
+            ParameterRequest::Get(Ok((req_id, req))) => {
+                info!("Get parameter request {req:?}");
+                let values = {
+                    let param_db = self.parameters.lock().unwrap();
+                    req.names
+                        .iter()
+                        .map(|name| {
+                            param_db
+                                .get(name.as_str())
+                                .unwrap_or(&ParameterValue::NotSet)
+                        })
+                        .cloned()
+                        .map(raw::ParameterValue::from)
+                        .collect()
+                };
+                info!("Get parameters response: {values:?}");
+                servers
+                    .get_parameters_server
+                    .async_send_response(req_id, rcl_interfaces::GetParametersResponse { values })
+                    .await
+                    .unwrap_or_else(|e| warn!("GetParameter response error {e:?}"));
+            }
+            ParameterRequest::Get(Err(e)) => warn!("GetParameter request error {e:?}"),
+
+            ParameterRequest::GetTypes(Ok((req_id, req))) => {
+                warn!("Get parameter types request");
+                let values = {
+                    let param_db = self.parameters.lock().unwrap();
+                    req.names
+                        .iter()
+                        .map(|name| {
+                            param_db
+                                .get(name.as_str())
+                                .unwrap_or(&ParameterValue::NotSet)
+                        })
+                        .map(ParameterValue::to_parameter_type_raw)
+                        .collect()
+                };
+                info!("Get parameter types response: {values:?}");
+                servers
+                    .get_parameter_types_server
+                    .async_send_response(
+                        req_id,
+                        rcl_interfaces::GetParameterTypesResponse { values },
+                    )
+                    .await
+                    .unwrap_or_else(|e| warn!("GetParameterTypes response error {e:?}"));
+            }
+            ParameterRequest::GetTypes(Err(e)) => warn!("GetParameterTypes request error {e:?}"),
+
+            ParameterRequest::Set(Ok((req_id, req))) => {
+                info!("Set parameter request {req:?}");
+                let results = req
+                    .parameter
+                    .iter()
+                    .cloned()
+                    .map(Parameter::from) // convert from "raw::Parameter"
+                    .map(|Parameter { name, value }| {
+                        #[cfg(feature = "rosout")]
+                        let old_value = self.parameters.lock().unwrap().get(&name).cloned();
+                        let result = self.set_parameter(&name, value.clone());
+                        #[cfg(feature = "rosout")]
+                        self.audit_parameter_change(
+                            req_id,
+                            &name,
+                            old_value.as_ref(),
+                            &value,
+                            &result,
+                        );
+                        result
+                    })
+                    .map(|r| r.into()) // to "raw" Result for serialization
+                    .collect();
+                info!("Set parameters response: {results:?}");
+                servers
+                    .set_parameters_server
+                    .async_send_response(req_id, rcl_interfaces::SetParametersResponse { results })
+                    .await
+                    .unwrap_or_else(|e| warn!("SetParameters response error {e:?}"));
+            }
+            ParameterRequest::Set(Err(e)) => warn!("SetParameters request error {e:?}"),
+
+            ParameterRequest::SetAtomically(Ok((req_id, req))) => {
+                info!("Set parameters atomically request {req:?}");
+                let parameters: Vec<Parameter> = req
+                    .parameter
+                    .into_iter()
+                    .map(Parameter::from) // convert from "raw::Parameter"
+                    .collect();
+                #[cfg(feature = "rosout")]
+                let old_values: Vec<Option<ParameterValue>> = {
+                    let current = self.parameters.lock().unwrap();
+                    parameters
+                        .iter()
+                        .map(|p| current.get(&p.name).cloned())
+                        .collect()
+                };
+                let set_result = self.set_parameters_atomically(parameters.clone());
+                #[cfg(feature = "rosout")]
+                for (p, old_value) in parameters.iter().zip(old_values) {
+                    self.audit_parameter_change(
+                        req_id,
+                        &p.name,
+                        old_value.as_ref(),
+                        &p.value,
+                        &set_result,
+                    );
+                }
+                let result: raw::SetParametersResult = set_result.into(); // to "raw" Result for serialization
+                info!("Set parameters atomically response: {result:?}");
+                servers
+                    .set_parameters_atomically_server
+                    .async_send_response(
+                        req_id,
+                        rcl_interfaces::SetParametersAtomicallyResponse { result },
+                    )
+                    .await
+                    .unwrap_or_else(|e| warn!("SetParameters response error {e:?}"));
+            }
+            ParameterRequest::SetAtomically(Err(e)) => {
+                warn!("SetParametersAtomically request error {e:?}")
+            }
+
+            ParameterRequest::List(Ok((req_id, req))) => {
+                info!("List parameters request");
+                let prefixes = req.prefixes;
+                // TODO: We only generate the "names" part of the ListParametersResponse
+                // What should we put into `prefixes` ?
+                let names = {
+                    let param_db = self.parameters.lock().unwrap();
+                    param_db
+                        .keys()
+                        .filter_map(|name| {
+                            if prefixes.is_empty()
+                                || prefixes.iter().any(|prefix| name.starts_with(prefix))
+                            {
+                                Some(name.clone())
+                            } else {
+                                None
+                            }
+                        })
+                        .collect()
+                };
+                let result = rcl_interfaces::ListParametersResult {
+                    names,
+                    prefixes: vec![],
+                };
+                info!("List parameters response: {result:?}");
+                servers
+                    .list_parameters_server
+                    .async_send_response(req_id, rcl_interfaces::ListParametersResponse { result })
+                    .await
+                    .unwrap_or_else(|e| warn!("ListParameter response error {e:?}"));
+            }
+            ParameterRequest::List(Err(e)) => warn!("ListParameter request error {e:?}"),
+
+            ParameterRequest::Describe(Ok((req_id, req))) => {
+                info!("Describe parameters request {req:?}");
+                let values = {
+                    let parameters = self.parameters.lock().unwrap();
+                    req.names
+                        .iter()
+                        .map(|name| {
+                            if let Some(value) = parameters.get(name) {
+                                let mut descriptor = ParameterDescriptor::from_value(name, value);
+                                descriptor.read_only = self.is_parameter_read_only(name);
+                                descriptor
+                            } else {
+                                ParameterDescriptor::unknown(name)
+                            }
+                        })
+                        .map(|r| r.into()) // to "raw" Result for serialization
+                        .collect()
+                };
+                info!("Describe parameters response: {values:?}");
+                servers
+                    .describe_parameters_server
+                    .async_send_response(
+                        req_id,
+                        rcl_interfaces::DescribeParametersResponse { values },
+                    )
+                    .await
+                    .unwrap_or_else(|e| warn!("DescribeParameters response error {e:?}"));
+            }
+            ParameterRequest::Describe(Err(e)) => warn!("DescribeParameters request error {e:?}"),
+        }
+    }
+
     // Keep this function in sync with the same function in Node.
+    #[cfg(feature = "parameters")]
     fn validate_parameter_on_set(&self, name: &str, value: &ParameterValue) -> SetParametersResult {
         match name {
             // built-in parameter check
@@ -505,7 +1143,43 @@ impl Spinner {
         }
     }
 
+    // Write an audit log entry of a parameter change requested via Parameter
+    // Services, if enabled.
+    #[cfg(all(feature = "parameters", feature = "rosout"))]
+    fn audit_parameter_change(
+        &self,
+        request_id: RmwRequestId,
+        name: &str,
+        old_value: Option<&ParameterValue>,
+        new_value: &ParameterValue,
+        result: &SetParametersResult,
+    ) {
+        let Some(writer) = self.parameter_audit_writer.as_ref().and_then(|w| w.get()) else {
+            return;
+        };
+        let (level, outcome) = match result {
+            Ok(()) => (LogLevel::Info, "accepted".to_owned()),
+            Err(reason) => (LogLevel::Warn, format!("rejected: {reason}")),
+        };
+        let msg = format!(
+            "requester={} parameter={name} old={old_value:?} new={new_value:?} {outcome}",
+            Gid::from(request_id.writer_guid)
+        );
+        writer
+            .publish(Log {
+                timestamp: Timestamp::now(),
+                level: level as u8,
+                name: format!("{}.parameter_audit", self.fully_qualified_node_name),
+                msg,
+                file: file!().to_string(),
+                function: "audit_parameter_change".to_string(),
+                line: line!(),
+            })
+            .unwrap_or_else(|e| debug!("Parameter audit log publish failed: {e:?}"));
+    }
+
     // Keep this function in sync with the same function in Node.
+    #[cfg(feature = "parameters")]
     fn execute_parameter_set_actions(
         &self,
         name: &str,
@@ -528,43 +1202,90 @@ impl Spinner {
         }
     }
 
+    // Keep this function in sync with the same function in Node.
+    #[cfg(feature = "parameters")]
+    fn is_parameter_read_only(&self, name: &str) -> bool {
+        self.parameters_frozen.load(Ordering::SeqCst)
+            || self.read_only_parameters.lock().unwrap().contains(name)
+    }
+
+    // Keep this function in sync with the same function in Node.
+    #[cfg(feature = "parameters")]
+    fn validate_parameter_set(
+        &self,
+        prospective_parameters: &BTreeMap<String, ParameterValue>,
+    ) -> SetParametersResult {
+        match self.parameter_set_validator {
+            Some(ref v) => v.lock().unwrap()(prospective_parameters),
+            None => Ok(()),
+        }
+    }
+
     /// Sets a parameter value. Parameter must be declared before setting.
+    #[cfg(feature = "parameters")]
     pub fn set_parameter(&self, name: &str, value: ParameterValue) -> Result<(), String> {
-        let already_set = self.parameters.lock().unwrap().contains_key(name);
-        if self.allow_undeclared_parameters || already_set {
-            self.validate_parameter_on_set(name, &value)?;
-            self.execute_parameter_set_actions(name, &value)?;
+        self.set_parameters_atomically(vec![Parameter {
+            name: name.to_owned(),
+            value,
+        }])
+    }
+
+    // Keep this function in sync with the same function in Node.
+    #[cfg(feature = "parameters")]
+    fn set_parameters_atomically(&self, parameters: Vec<Parameter>) -> SetParametersResult {
+        // Check everything before changing anything.
+        let mut prospective_parameters = self.parameters.lock().unwrap().clone();
+        let mut new_parameters = vec![];
+        let mut changed_parameters = vec![];
+        for Parameter { name, value } in &parameters {
+            if self.is_parameter_read_only(name) {
+                return Err("Parameter '".to_owned() + name + "' is read-only.");
+            }
+            let already_set = prospective_parameters.contains_key(name);
+            if !(self.allow_undeclared_parameters || already_set) {
+                return Err("Setting undeclared parameter '".to_owned()
+                    + name
+                    + "' is not allowed.");
+            }
+            self.validate_parameter_on_set(name, value)?;
 
-            // no errors, prepare for sending notificaiton
             let p = raw::Parameter {
                 name: name.to_string(),
                 value: value.clone().into(),
             };
-            let (new_parameters, changed_parameters) = if already_set {
-                (vec![], vec![p])
+            if already_set {
+                changed_parameters.push(p)
             } else {
-                (vec![p], vec![])
-            };
+                new_parameters.push(p)
+            }
+            prospective_parameters.insert(name.clone(), value.clone());
+        }
+        self.validate_parameter_set(&prospective_parameters)?;
 
-            // actually set the parameter
-            self.parameters
-                .lock()
-                .unwrap()
-                .insert(name.to_owned(), value);
-            // and notify
-            self.parameter_events_writer
-                .publish(raw::ParameterEvent {
-                    timestamp: rustdds::Timestamp::now(), // differs from version in Node!!!
-                    node: self.fully_qualified_node_name.clone(),
-                    new_parameters,
-                    changed_parameters,
-                    deleted_parameters: vec![],
-                })
-                .unwrap_or_else(|e| warn!("undeclare_parameter: {e:?}"));
-            Ok(())
-        } else {
-            Err("Setting undeclared parameter '".to_owned() + name + "' is not allowed.")
+        for Parameter { name, value } in &parameters {
+            self.execute_parameter_set_actions(name, value)?;
         }
+
+        // actually set the parameters
+        self.parameters.lock().unwrap().extend(
+            parameters
+                .into_iter()
+                .map(|Parameter { name, value }| (name, value)),
+        );
+        // and notify
+        let Some(parameter_events_writer) = self.parameter_events_writer.get() else {
+            return Ok(());
+        };
+        parameter_events_writer
+            .publish(raw::ParameterEvent {
+                timestamp: rustdds::Timestamp::now(), // differs from version in Node!!!
+                node: self.fully_qualified_node_name.clone(),
+                new_parameters,
+                changed_parameters,
+                deleted_parameters: vec![],
+            })
+            .unwrap_or_else(|e| warn!("set_parameters: {e:?}"));
+        Ok(())
     }
 } // impl Spinner
 
@@ -576,6 +1297,7 @@ impl Spinner {
 pub enum NodeCreateError {
     DDS(CreateError),
     BadParameter(String),
+    BadRemapping(String),
 }
 
 impl From<CreateError> for NodeCreateError {
@@ -589,6 +1311,7 @@ impl fmt::Display for NodeCreateError {
         match self {
             Self::DDS(create_error) => write!(f, "NodeCreateError::DDS : {create_error}"),
             Self::BadParameter(s) => write!(f, "NodeCreateError::BadParameter : {s}"),
+            Self::BadRemapping(s) => write!(f, "NodeCreateError::BadRemapping : {s}"),
         }
     }
 }
@@ -597,7 +1320,7 @@ impl Error for NodeCreateError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             Self::DDS(create_error) => Some(create_error),
-            Self::BadParameter(_) => None,
+            Self::BadParameter(_) | Self::BadRemapping(_) => None,
         }
     }
 }
@@ -613,18 +1336,20 @@ pub enum ParameterError {
 ///
 /// These are produced by a [`Context`].
 ///
-// TODO: We should notify ROS discovery when readers or writers are removed, but
-// now we do not do that.
+/// Publishers, Subscriptions, Clients and Servers are listed in ROS Discovery
+/// as belonging to this Node until they are dropped. Dropping the Node removes
+/// it from ROS Discovery.
 pub struct Node {
     node_name: NodeName,
     options: NodeOptions,
+    remapping: Remapping,
 
     pub(crate) ros_context: Context,
 
     // sets of Readers and Writers belonging to ( = created via) this Node
-    // These indicate what has been created locally.
-    readers: BTreeSet<Gid>,
-    writers: BTreeSet<Gid>,
+    // These indicate what has been created locally. Shared with the
+    // EndpointRegistrations, which remove their entry on drop.
+    endpoints: Arc<Mutex<LocalEndpoints>>,
 
     suppress_node_info_updates: Arc<AtomicBool>,
     // temporarily suppress sending updates
@@ -634,7 +1359,18 @@ pub struct Node {
     // Map keys are lists of local Subscriptions and Publishers.
     // Map values are lists of matched Publishers / Subscriptions.
     readers_to_remote_writers: Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>>,
+    writer_ownership_strengths: Arc<Mutex<BTreeMap<GUID, i32>>>,
+    // DDS Topic name -> DDS type name, for Topics used by local endpoints
+    local_topic_types: Arc<Mutex<BTreeMap<String, String>>>,
     writers_to_remote_readers: Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>>,
+    // Remote endpoint GUID -> DDS Topic name, for all discovered endpoints,
+    // not only the matched ones.
+    remote_writer_topics: Arc<Mutex<BTreeMap<GUID, String>>>,
+    remote_reader_topics: Arc<Mutex<BTreeMap<GUID, String>>>,
+    // Full descriptions of the same endpoints, for graph snapshots
+    remote_endpoints: Arc<Mutex<BTreeMap<GUID, (EndpointKind, EndpointDescription)>>>,
+    // Endpoints discovered in earlier runs, if enabled
+    discovery_cache: Option<Arc<Mutex<DiscoveryCache>>>,
 
     // Keep track of ros_discovery_info
     external_nodes: Arc<Mutex<BTreeMap<Gid, Vec<NodeEntitiesInfo>>>>,
@@ -644,44 +1380,100 @@ pub struct Node {
     status_event_senders: Arc<Mutex<Vec<async_channel::Sender<NodeEvent>>>>,
 
     // builtin writers and readers
-    rosout_writer: Option<Publisher<Log>>,
+    #[cfg(feature = "rosout")]
+    rosout_writer: Option<Arc<BuiltinWriter<Log>>>,
+    #[cfg(feature = "rosout")]
     rosout_reader: Option<Subscription<Log>>,
 
     // Parameter events (rcl_interfaces)
     // Parameter Services are inside Spinner
-    parameter_events_writer: Arc<Publisher<raw::ParameterEvent>>,
+    #[cfg(feature = "parameters")]
+    parameter_events_writer: Arc<BuiltinWriter<raw::ParameterEvent>>,
 
     // Parameter store
+    #[cfg(feature = "parameters")]
     parameters: Arc<Mutex<BTreeMap<String, ParameterValue>>>,
+    #[cfg(feature = "parameters")]
+    read_only_parameters: Arc<Mutex<BTreeSet<String>>>,
+    #[cfg(feature = "parameters")]
+    parameters_frozen: Arc<AtomicBool>, // all parameters are read-only
     // allow_undeclared_parameters: bool, // this is inside "options"
+    #[cfg(feature = "parameters")]
     parameter_validator: Option<Arc<Mutex<Box<ParameterFunc>>>>,
+    #[cfg(feature = "parameters")]
+    parameter_set_validator: Option<Arc<Mutex<Box<ParameterSetFunc>>>>,
+    #[cfg(feature = "parameters")]
     parameter_set_action: Option<Arc<Mutex<Box<ParameterFunc>>>>,
 
     // simulated ROSTime
     use_sim_time: Arc<AtomicBool>,
     sim_time: Arc<Mutex<ROSTime>>,
+
+    creation_time: Duration,
+    #[cfg(feature = "parameters")]
+    parameter_services_time: Option<Duration>,
+
+    // Busy time of the Spinner
+    spinner_load: Arc<LoadMeter>,
 }
 
 impl Node {
     pub(crate) fn new(
         node_name: NodeName,
-        mut options: NodeOptions,
+        options: NodeOptions,
         ros_context: Context,
     ) -> Result<Node, NodeCreateError> {
-        let paramtopic = ros_context.get_parameter_events_topic();
-        let rosout_topic = ros_context.get_rosout_topic();
-
-        let enable_rosout = options.enable_rosout;
-        let rosout_reader = options.enable_rosout_reading;
+        let start = Instant::now();
+        let mut remap_rules = options.remap_rules.clone();
+        remap_rules.extend(
+            remapping::parse_ros_args(&options.cli_args).map_err(NodeCreateError::BadRemapping)?,
+        );
+        if options.use_global_arguments {
+            let global_args: Vec<String> = std::env::args().collect();
+            remap_rules.extend(
+                remapping::parse_ros_args(&global_args).map_err(NodeCreateError::BadRemapping)?,
+            );
+        }
+        let (node_name, remapping) =
+            Remapping::new(&node_name, &remap_rules).map_err(NodeCreateError::BadRemapping)?;
+
+        #[cfg(feature = "parameters")]
+        let parameter_events_writer = {
+            let paramtopic = ros_context.get_parameter_events_topic();
+            if options.lazy_builtin_endpoints {
+                BuiltinWriter::new(ros_context.clone(), paramtopic)
+            } else {
+                BuiltinWriter::create_now(ros_context.clone(), paramtopic)?
+            }
+        };
 
-        let parameter_events_writer = ros_context.create_publisher(&paramtopic, None)?;
+        #[cfg(feature = "rosout")]
+        let rosout_topic = ros_context.get_rosout_topic();
+        #[cfg(feature = "rosout")]
+        let rosout_writer = if !options.enable_rosout {
+            None
+        } else if options.lazy_builtin_endpoints {
+            Some(BuiltinWriter::new(
+                ros_context.clone(),
+                rosout_topic.clone(),
+            ))
+        } else {
+            Some(BuiltinWriter::create_now(
+                ros_context.clone(),
+                rosout_topic.clone(),
+            )?)
+        };
 
+        #[cfg(feature = "parameters")]
+        let mut options = options;
         // TODO: If there are duplicates, the later one will overwrite the earlier, but
         // there is no warning or error.
+        #[cfg(feature = "parameters")]
         options.declared_parameters.push(Parameter {
             name: "use_sim_time".to_string(),
             value: ParameterValue::Boolean(false),
         });
+        #[cfg(feature = "parameters")]
         let parameters = options
             .declared_parameters
             .iter()
@@ -689,70 +1481,141 @@ impl Node {
             .map(|Parameter { name, value }| (name, value))
             .collect::<BTreeMap<String, ParameterValue>>();
 
+        #[cfg(feature = "parameters")]
+        let read_only_parameters = std::mem::take(&mut options.read_only_parameters);
+
+        #[cfg(feature = "parameters")]
         let parameter_validator = options
             .parameter_validator
             .take()
             .map(|b| Arc::new(Mutex::new(b)));
+        #[cfg(feature = "parameters")]
+        let parameter_set_validator = options
+            .parameter_set_validator
+            .take()
+            .map(|b| Arc::new(Mutex::new(b)));
+        #[cfg(feature = "parameters")]
         let parameter_set_action = options
             .parameter_set_action
             .take()
             .map(|b| Arc::new(Mutex::new(b)));
+        let discovery_cache = options
+            .discovery_cache
+            .clone()
+            .map(|config| Arc::new(Mutex::new(DiscoveryCache::load(config))));
 
         let mut node = Node {
             node_name,
             options,
+            remapping,
             ros_context,
-            readers: BTreeSet::new(),
-            writers: BTreeSet::new(),
+            endpoints: Arc::new(Mutex::new(LocalEndpoints::default())),
             readers_to_remote_writers: Arc::new(Mutex::new(BTreeMap::new())),
+            writer_ownership_strengths: Arc::new(Mutex::new(BTreeMap::new())),
+            local_topic_types: Arc::new(Mutex::new(BTreeMap::new())),
             writers_to_remote_readers: Arc::new(Mutex::new(BTreeMap::new())),
+            remote_writer_topics: Arc::new(Mutex::new(BTreeMap::new())),
+            remote_reader_topics: Arc::new(Mutex::new(BTreeMap::new())),
+            remote_endpoints: Arc::new(Mutex::new(BTreeMap::new())),
+            discovery_cache,
             external_nodes: Arc::new(Mutex::new(BTreeMap::new())),
             suppress_node_info_updates: Arc::new(AtomicBool::new(false)),
             stop_spin_sender: None,
             status_event_senders: Arc::new(Mutex::new(Vec::new())),
-            rosout_writer: None, // Set below
+            #[cfg(feature = "rosout")]
+            rosout_writer: rosout_writer.map(Arc::new),
+            #[cfg(feature = "rosout")]
             rosout_reader: None,
+            #[cfg(feature = "parameters")]
             parameter_events_writer: Arc::new(parameter_events_writer),
+            #[cfg(feature = "parameters")]
             parameters: Arc::new(Mutex::new(parameters)),
+            #[cfg(feature = "parameters")]
+            read_only_parameters: Arc::new(Mutex::new(read_only_parameters)),
+            #[cfg(feature = "parameters")]
+            parameters_frozen: Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "parameters")]
             parameter_validator,
+            #[cfg(feature = "parameters")]
+            parameter_set_validator,
+            #[cfg(feature = "parameters")]
             parameter_set_action,
             use_sim_time: Arc::new(AtomicBool::new(false)),
             sim_time: Arc::new(Mutex::new(ROSTime::ZERO)),
+            creation_time: Duration::ZERO, // Set below
+            #[cfg(feature = "parameters")]
+            parameter_services_time: None,
+            spinner_load: Arc::new(LoadMeter::default()),
         };
 
         node.suppress_node_info_updates(true);
 
-        node.rosout_writer = if enable_rosout {
-            Some(
-                // topic already has QoS defined
-                node.create_publisher(&rosout_topic, None)?,
-            )
-        } else {
-            None
-        };
-        node.rosout_reader = if rosout_reader {
-            Some(node.create_subscription(&rosout_topic, None)?)
-        } else {
-            None
-        };
+        #[cfg(feature = "rosout")]
+        if node.options.enable_rosout_reading {
+            node.rosout_reader = Some(node.create_subscription(&rosout_topic, None)?);
+        }
 
         // returns `Err` if some parameter does not validate.
-        node.parameters
-            .lock()
-            .unwrap()
-            .iter()
-            .try_for_each(|(name, value)| {
-                node.validate_parameter_on_set(name, value)?;
-                node.execute_parameter_set_actions(name, value)?;
-                Ok(())
-            })
-            .map_err(NodeCreateError::BadParameter)?;
+        #[cfg(feature = "parameters")]
+        {
+            node.parameters
+                .lock()
+                .unwrap()
+                .iter()
+                .try_for_each(|(name, value)| {
+                    node.validate_parameter_on_set(name, value)?;
+                    node.execute_parameter_set_actions(name, value)?;
+                    Ok(())
+                })
+                .map_err(NodeCreateError::BadParameter)?;
+            let initial_parameters = node.parameters.lock().unwrap().clone();
+            node.validate_parameter_set(&initial_parameters)
+                .map_err(NodeCreateError::BadParameter)?;
+        }
 
         node.suppress_node_info_updates(false);
 
+        node.creation_time = start.elapsed();
         Ok(node)
     }
 
+    /// How long creating this Node and its builtin endpoints took. See
+    /// [`startup`].
+    pub fn startup_times(&self) -> StartupTimes {
+        #[allow(unused_mut)] // if the builtin endpoints are compiled out
+        let mut times = StartupTimes {
+            node: self.creation_time,
+            ..StartupTimes::default()
+        };
+        #[cfg(feature = "rosout")]
+        {
+            times.rosout_writer = self.rosout_writer.as_ref().and_then(|w| w.creation_time());
+        }
+        #[cfg(feature = "parameters")]
+        {
+            times.parameter_events_writer = self.parameter_events_writer.creation_time();
+            times.parameter_services = self.parameter_services_time;
+        }
+        times
+    }
+
+    // Creates a builtin writer on first use, and then announces it in ROS
+    // Discovery.
+    #[cfg(any(feature = "rosout", feature = "parameters"))]
+    fn builtin_writer<'a, M: Serialize>(
+        &self,
+        writer: &'a BuiltinWriter<M>,
+    ) -> Option<&'a Arc<Publisher<M>>> {
+        if let Some(publisher) = writer.created() {
+            return Some(publisher);
+        }
+        let publisher = writer.get();
+        if publisher.is_some() && !self.suppress_node_info_updates.load(Ordering::SeqCst) {
+            self.ros_context.update_node(self.generate_node_info());
+        }
+        publisher
+    }
+
     /// Return the ROSTime
     ///
     /// It is either the system clock time
@@ -768,6 +1631,172 @@ impl Node {
         ROSTime::now()
     }
 
+    /// Run the Spinner in a background thread.
+    ///
+    /// This is a runtime-independent replacement for spawning
+    /// `node.spinner()?.spin()` on an async executor. The thread runs until
+    /// [`SpinHandle::stop`] is called or the Node is dropped. Like
+    /// [`Self::spinner`], this can be called only once per Node. If the
+    /// thread cannot be started, this returns [`CreateError::OutOfResources`].
+    ///
+    /// ```
+    /// # use ros2_client::prelude::*;
+    /// # let context = Context::new().unwrap();
+    /// # let mut node = context
+    /// #     .new_node(NodeName::new("/", "spin_example").unwrap(), NodeOptions::new())
+    /// #     .unwrap();
+    /// let spin_handle = node.spin_in_background().unwrap();
+    /// // ... use the Node ...
+    /// spin_handle.stop();
+    /// spin_handle.join().unwrap();
+    /// ```
+    pub fn spin_in_background(&mut self) -> CreateResult<SpinHandle> {
+        let thread_name = format!("spinner {}", self.fully_qualified_name());
+        let thread_reservation = self.ros_context.reserve_thread()?;
+        let mut spawned = Ok(());
+        let handle = self.spin_with(|task| {
+            let builder = std::thread::Builder::new().name(thread_name);
+            spawned = builder
+                .spawn(move || {
+                    let _thread_reservation = thread_reservation;
+                    futures::executor::block_on(task)
+                })
+                .map(drop);
+        })?;
+        // If the thread did not start, the Spinner was dropped with the task,
+        // so a new one can be created later.
+        spawned.map_err(|e| CreateError::OutOfResources {
+            reason: format!("Cannot start Spinner thread: {e}"),
+        })?;
+        Ok(handle)
+    }
+
+    /// Run the Spinner as a task spawned by `spawn`.
+    ///
+    /// This lets the Spinner run on the application's async runtime, e.g.
+    /// `node.spin_with(|task| smol::spawn(task).detach())` or
+    /// `node.spin_with(|task| { tokio::spawn(task); })`.
+    pub fn spin_with<F>(&mut self, spawn: F) -> CreateResult<SpinHandle>
+    where
+        F: FnOnce(futures::future::BoxFuture<'static, ()>),
+    {
+        let spinner = self.spinner()?;
+        // spinner() has just set the sender.
+        let stop_sender = self.stop_spin_sender.clone().unwrap();
+        let (handle, task) = SpinHandle::new(spinner, stop_sender);
+        spawn(task);
+        Ok(handle)
+    }
+
+    /// Publishers created by this Node and not yet dropped
+    ///
+    /// This includes the Publishers of Action Servers and Clients, but not
+    /// the built-in ones for `/rosout` and `/parameter_events`.
+    pub fn publishers(&self) -> Vec<TopicEndpointDescriptor> {
+        self.endpoints.lock().unwrap().topic_endpoints(false)
+    }
+
+    /// Subscriptions created by this Node and not yet dropped
+    pub fn subscriptions(&self) -> Vec<TopicEndpointDescriptor> {
+        self.endpoints.lock().unwrap().topic_endpoints(true)
+    }
+
+    /// Service Servers created by this Node and not yet dropped, including the
+    /// parameter Services
+    pub fn services(&self) -> Vec<ServiceEndpointDescriptor> {
+        self.endpoints.lock().unwrap().service_endpoints(true)
+    }
+
+    /// Service Clients created by this Node and not yet dropped
+    pub fn clients(&self) -> Vec<ServiceEndpointDescriptor> {
+        self.endpoints.lock().unwrap().service_endpoints(false)
+    }
+
+    /// YAML description of this Node: its entities with QoS, parameters, and
+    /// version information.
+    ///
+    /// This is what the Service enabled by [`NodeOptions::describe_service`]
+    /// returns.
+    pub fn describe(&self) -> String {
+        #[cfg(feature = "parameters")]
+        let parameters = self.parameters.lock().unwrap();
+        #[cfg(not(feature = "parameters"))]
+        let parameters = BTreeMap::new();
+        describe::describe_yaml(
+            &self.fully_qualified_name(),
+            &self.endpoints.lock().unwrap(),
+            &parameters,
+        )
+    }
+
+    /// Current rates of the Publishers and Subscriptions of this Node
+    ///
+    /// Rates are measured over windows of at least one second, so this should
+    /// be called periodically, e.g. once per second, by one caller. See
+    /// [`Bandwidth`] for what is counted. The built-in endpoints are included.
+    pub fn topic_bandwidth(&self) -> Vec<TopicBandwidth> {
+        self.endpoints.lock().unwrap().topic_bandwidths()
+    }
+
+    /// Entity counts, message rates, dropped samples and Spinner load of this
+    /// Node. See [`statistics`].
+    ///
+    /// Rates and load are measured over windows of at least one second, as
+    /// in [`Self::topic_bandwidth`].
+    pub fn statistics(&self) -> NodeStatistics {
+        NodeStatistics::collect(&self.endpoints.lock().unwrap(), self.spinner_load.load())
+    }
+
+    /// [`Self::topic_bandwidth`] as a diagnostics message, e.g. for Topic
+    /// `/diagnostics`
+    ///
+    /// There is one status per Publisher and Subscription, with values
+    /// `messages_per_sec` and `bytes_per_sec`.
+    pub fn bandwidth_diagnostics(&self) -> diagnostic_msgs::DiagnosticArray {
+        let node_name = self.fully_qualified_name();
+        let status = self
+            .topic_bandwidth()
+            .into_iter()
+            .map(|t| diagnostic_msgs::DiagnosticStatus {
+                level: diagnostic_msgs::DiagnosticStatus::OK,
+                name: format!(
+                    "{node_name}: {} {}",
+                    if t.is_publisher {
+                        "publisher"
+                    } else {
+                        "subscription"
+                    },
+                    t.endpoint.topic_name
+                ),
+                message: String::new(),
+                hardware_id: String::new(),
+                values: vec![
+                    diagnostic_msgs::KeyValue::new(
+                        "messages_per_sec",
+                        format!("{:.2}", t.bandwidth.messages_per_sec),
+                    ),
+                    diagnostic_msgs::KeyValue::new(
+                        "bytes_per_sec",
+                        format!("{:.0}", t.bandwidth.bytes_per_sec),
+                    ),
+                ],
+            })
+            .collect();
+        diagnostic_msgs::DiagnosticArray {
+            header: Header {
+                stamp: self.time_now().into(),
+                frame_id: String::new(),
+            },
+            status,
+        }
+    }
+
+    /// Remove the Node from ROS Discovery and stop its Spinner, if any.
+    ///
+    /// This is the same as dropping the Node. Entities created by the Node
+    /// stay usable, but they are no longer listed as belonging to any Node.
+    pub fn destroy(self) {}
+
     /// Create a Spinner object to execute Node backround tasks.
     ///
     /// An async task should then be created to run the `.spin()` function of
@@ -776,14 +1805,26 @@ impl Node {
     /// E.g. `executor.spawn(node.spinner().spin())`
     ///
     /// The `.spin()` task runs until `Node` is dropped.
+    ///
+    /// [`Self::spin_in_background`] and [`Self::spin_with`] do the spawning and
+    /// also allow stopping the task.
+    ///
+    /// Only one Spinner can exist at a time. A new one can be created after
+    /// the previous one has been dropped, e.g. after its task has failed.
     pub fn spinner(&mut self) -> CreateResult<Spinner> {
-        if self.stop_spin_sender.is_some() {
+        // The channel closes when the Spinner owning the receiver is dropped.
+        if self
+            .stop_spin_sender
+            .as_ref()
+            .is_some_and(|s| !s.is_closed())
+        {
             panic!("Attempted to crate a second spinner.");
         }
         let (stop_spin_sender, stop_spin_receiver) = async_channel::bounded(1);
         self.stop_spin_sender = Some(stop_spin_sender);
 
         //TODO: Check QoS policies against ROS 2 specs or some refernce.
+        #[cfg(feature = "parameters")]
         let service_qos = QosPolicyBuilder::new()
             .reliability(policy::Reliability::Reliable {
                 max_blocking_time: rustdds::Duration::from_millis(100),
@@ -795,49 +1836,56 @@ impl Node {
 
         self.suppress_node_info_updates(true);
 
+        #[cfg(feature = "parameters")]
+        let parameter_services_start = Instant::now();
+        #[cfg(feature = "parameters")]
         let parameter_servers = if self.options.start_parameter_services {
-            let service_mapping = ServiceMapping::Enhanced; //TODO: parameterize
+            // The Spinner publishes parameter events, so create the writer
+            // now, while the Node info is being updated anyway.
+            self.parameter_events_writer.get();
             let get_parameters_server = self.create_server(
-                service_mapping,
+                self.service_mapping_for(&Name::new(&node_name, "get_parameters").unwrap()),
                 &Name::new(&node_name, "get_parameters").unwrap(),
                 &ServiceTypeName::new("rcl_interfaces", "GetParameters"),
-                service_qos.clone(),
-                service_qos.clone(),
+                Some(service_qos.clone()),
+                Some(service_qos.clone()),
             )?;
             let get_parameter_types_server = self.create_server(
-                service_mapping,
+                self.service_mapping_for(&Name::new(&node_name, "get_parameter_types").unwrap()),
                 &Name::new(&node_name, "get_parameter_types").unwrap(),
                 &ServiceTypeName::new("rcl_interfaces", "GetParameterTypes"),
-                service_qos.clone(),
-                service_qos.clone(),
+                Some(service_qos.clone()),
+                Some(service_qos.clone()),
             )?;
             let set_parameters_server = self.create_server(
-                service_mapping,
+                self.service_mapping_for(&Name::new(&node_name, "set_parameters").unwrap()),
                 &Name::new(&node_name, "set_parameters").unwrap(),
                 &ServiceTypeName::new("rcl_interfaces", "SetParameters"),
-                service_qos.clone(),
-                service_qos.clone(),
+                Some(service_qos.clone()),
+                Some(service_qos.clone()),
             )?;
             let set_parameters_atomically_server = self.create_server(
-                service_mapping,
+                self.service_mapping_for(
+                    &Name::new(&node_name, "set_parameters_atomically").unwrap(),
+                ),
                 &Name::new(&node_name, "set_parameters_atomically").unwrap(),
                 &ServiceTypeName::new("rcl_interfaces", "SetParametersAtomically"),
-                service_qos.clone(),
-                service_qos.clone(),
+                Some(service_qos.clone()),
+                Some(service_qos.clone()),
             )?;
             let list_parameters_server = self.create_server(
-                service_mapping,
+                self.service_mapping_for(&Name::new(&node_name, "list_parameters").unwrap()),
                 &Name::new(&node_name, "list_parameters").unwrap(),
                 &ServiceTypeName::new("rcl_interfaces", "ListParameters"),
-                service_qos.clone(),
-                service_qos.clone(),
+                Some(service_qos.clone()),
+                Some(service_qos.clone()),
             )?;
             let describe_parameters_server = self.create_server(
-                service_mapping,
+                self.service_mapping_for(&Name::new(&node_name, "describe_parameters").unwrap()),
                 &Name::new(&node_name, "describe_parameters").unwrap(),
                 &ServiceTypeName::new("rcl_interfaces", "DescribeParameters"),
-                service_qos.clone(),
-                service_qos.clone(),
+                Some(service_qos.clone()),
+                Some(service_qos.clone()),
             )?;
 
             Some(ParameterServers {
@@ -852,9 +1900,53 @@ impl Node {
             None // No parameter services
         };
 
+        #[cfg(feature = "parameters")]
+        if parameter_servers.is_some() {
+            self.parameter_services_time = Some(parameter_services_start.elapsed());
+        }
+        #[cfg(all(feature = "parameters", feature = "rosout"))]
+        if self.options.parameter_audit_log {
+            if let Some(w) = &self.rosout_writer {
+                w.get();
+            }
+        }
+
+        let describe_server = if self.options.describe_service {
+            let name = Name::new(&node_name, describe::DESCRIBE_SERVICE_NAME).unwrap();
+            let server = self.create_server(
+                self.service_mapping_for(&name),
+                &name,
+                &ServiceTypeName::new("std_srvs", "Trigger"),
+                None,
+                None,
+            )?;
+            Some(DescribeServer {
+                server,
+                endpoints: Arc::clone(&self.endpoints),
+            })
+        } else {
+            None
+        };
+
+        let statistics_publisher = match self.options.statistics_period {
+            Some(period) => {
+                let name = Name::new(&node_name, statistics::STATISTICS_TOPIC_NAME).unwrap();
+                let topic = self.create_topic_for::<diagnostic_msgs::DiagnosticArray>(
+                    &name,
+                    &DEFAULT_PUBLISHER_QOS,
+                )?;
+                Some(StatisticsPublisher {
+                    publisher: self.create_publisher(&topic, None)?,
+                    period,
+                    endpoints: Arc::clone(&self.endpoints),
+                })
+            }
+            None => None,
+        };
+
         let clock_topic = self.create_topic(
             &Name::new("/", "clock").unwrap(),
-            MessageTypeName::new("builtin_interfaces", "Time"),
+            MessageTypeName::new("rosgraph_msgs", "Clock"),
             &DEFAULT_SUBSCRIPTION_QOS,
         )?;
 
@@ -864,19 +1956,46 @@ impl Node {
             ros_context: self.ros_context.clone(),
             stop_spin_receiver,
             readers_to_remote_writers: Arc::clone(&self.readers_to_remote_writers),
+            writer_ownership_strengths: Arc::clone(&self.writer_ownership_strengths),
+            local_topic_types: Arc::clone(&self.local_topic_types),
             writers_to_remote_readers: Arc::clone(&self.writers_to_remote_readers),
+            remote_writer_topics: Arc::clone(&self.remote_writer_topics),
+            remote_reader_topics: Arc::clone(&self.remote_reader_topics),
+            remote_endpoints: Arc::clone(&self.remote_endpoints),
             external_nodes: Arc::clone(&self.external_nodes),
+            discovery_cache: self.discovery_cache.as_ref().map(Arc::clone),
             status_event_senders: Arc::clone(&self.status_event_senders),
             use_sim_time: Arc::clone(&self.use_sim_time),
             sim_time: Arc::clone(&self.sim_time),
             clock_topic,
+            #[cfg(feature = "parameters")]
             parameter_servers,
+            describe_server,
+            statistics_publisher,
+            spinner_load: Arc::clone(&self.spinner_load),
+            #[cfg(feature = "parameters")]
             parameter_events_writer: Arc::clone(&self.parameter_events_writer),
+            #[cfg(feature = "parameters")]
             parameters: Arc::clone(&self.parameters),
+            #[cfg(feature = "parameters")]
+            read_only_parameters: Arc::clone(&self.read_only_parameters),
+            #[cfg(feature = "parameters")]
+            parameters_frozen: Arc::clone(&self.parameters_frozen),
+            #[cfg(feature = "parameters")]
             allow_undeclared_parameters: self.options.allow_undeclared_parameters,
+            #[cfg(feature = "parameters")]
             parameter_validator: self.parameter_validator.as_ref().map(Arc::clone),
+            #[cfg(feature = "parameters")]
+            parameter_set_validator: self.parameter_set_validator.as_ref().map(Arc::clone),
+            #[cfg(feature = "parameters")]
             parameter_set_action: self.parameter_set_action.as_ref().map(Arc::clone),
             fully_qualified_node_name: self.fully_qualified_name(),
+            #[cfg(all(feature = "parameters", feature = "rosout"))]
+            parameter_audit_writer: if self.options.parameter_audit_log {
+                self.rosout_writer.as_ref().map(Arc::clone)
+            } else {
+                None
+            },
         })
     }
 
@@ -892,17 +2011,22 @@ impl Node {
     fn generate_node_info(&self) -> NodeEntitiesInfo {
         let mut node_info = NodeEntitiesInfo::new(self.node_name.clone());
 
-        node_info.add_writer(Gid::from(self.parameter_events_writer.guid()));
-        if let Some(row) = &self.rosout_writer {
+        #[cfg(feature = "parameters")]
+        if let Some(pew) = self.parameter_events_writer.created() {
+            node_info.add_writer(Gid::from(pew.guid()));
+        }
+        #[cfg(feature = "rosout")]
+        if let Some(row) = self.rosout_writer.as_ref().and_then(|w| w.created()) {
             node_info.add_writer(Gid::from(row.guid()));
         }
 
-        for reader in &self.readers {
-            node_info.add_reader(*reader);
+        let endpoints = self.endpoints.lock().unwrap();
+        for reader in endpoints.readers() {
+            node_info.add_reader(reader);
         }
 
-        for writer in &self.writers {
-            node_info.add_writer(*writer);
+        for writer in endpoints.writers() {
+            node_info.add_writer(writer);
         }
 
         node_info
@@ -918,18 +2042,57 @@ impl Node {
         }
     }
 
-    fn add_reader(&mut self, reader: Gid) {
-        self.readers.insert(reader);
-        if !self.suppress_node_info_updates.load(Ordering::SeqCst) {
-            self.ros_context.update_node(self.generate_node_info());
-        }
+    fn add_reader(
+        &mut self,
+        reader: Gid,
+        topic: &Topic,
+        qos: &QosPolicies,
+    ) -> CreateResult<EndpointRegistration> {
+        self.add_endpoint(reader, true, topic, qos)
     }
 
-    fn add_writer(&mut self, writer: Gid) {
-        self.writers.insert(writer);
+    fn add_local_topic(&self, topic: &Topic) {
+        self.local_topic_types
+            .lock()
+            .unwrap()
+            .insert(topic.name(), topic.get_type().name().to_owned());
+    }
+
+    fn add_writer(
+        &mut self,
+        writer: Gid,
+        topic: &Topic,
+        qos: &QosPolicies,
+    ) -> CreateResult<EndpointRegistration> {
+        self.add_endpoint(writer, false, topic, qos)
+    }
+
+    fn add_endpoint(
+        &mut self,
+        gid: Gid,
+        is_reader: bool,
+        topic: &Topic,
+        qos: &QosPolicies,
+    ) -> CreateResult<EndpointRegistration> {
+        let buffer_reservation = self.ros_context.reserve_buffer(&topic.name(), qos)?;
+        self.endpoints.lock().unwrap().insert(LocalEndpoint {
+            gid,
+            is_reader,
+            dds_topic_name: topic.name(),
+            dds_type_name: topic.get_type().name().to_owned(),
+            qos: qos.clone(),
+            counters: None,
+        });
         if !self.suppress_node_info_updates.load(Ordering::SeqCst) {
             self.ros_context.update_node(self.generate_node_info());
         }
+        Ok(EndpointRegistration {
+            gid,
+            node_fqn: self.fully_qualified_name(),
+            endpoints: Arc::downgrade(&self.endpoints),
+            context: self.ros_context.clone(),
+            buffer_reservation,
+        })
     }
 
     pub fn base_name(&self) -> &str {
@@ -944,6 +2107,74 @@ impl Node {
         self.node_name.fully_qualified_name()
     }
 
+    /// Waits until the Service and Action Servers of this Node have responded
+    /// to the requests already received, and the responses have been
+    /// acknowledged. Returns `true` if all Servers made it within their
+    /// [drain timeout](crate::service::Server::set_drain_timeout). Once this
+    /// has started, the Servers discard new requests. See also
+    /// [`Context::drain_servers`].
+    pub async fn drain_servers(&self) -> bool {
+        context::drain(self.ros_context.servers(Some(&self.fully_qualified_name()))).await
+    }
+
+    pub(crate) fn server_drain_timeout(&self) -> Option<Duration> {
+        self.options.server_drain_timeout
+    }
+
+    pub(crate) fn register_server(&self, server: Weak<dyn DrainServer>) {
+        self.ros_context
+            .register_server(&self.fully_qualified_name(), server);
+    }
+
+    // Tracer for Service or Action `name`, if tracing is enabled
+    pub(crate) fn call_tracer(&self, name: String) -> Option<CallTracer> {
+        self.options
+            .call_trace
+            .as_ref()
+            .map(|trace| CallTracer::new(trace.clone(), self.fully_qualified_name(), name))
+    }
+
+    /// Add a name remapping rule.
+    ///
+    /// This affects only Topics, Services and Actions created after this call.
+    /// Node name and namespace rules (`__node`, `__ns`) cannot be applied to
+    /// an existing Node, and are rejected.
+    pub fn add_remapping(&mut self, rule: RemapRule) -> Result<(), String> {
+        if ["__node", "__name", "__ns"].contains(&rule.from()) {
+            return Err(format!(
+                "Cannot rename an existing Node: {}:={}",
+                rule.from(),
+                rule.to()
+            ));
+        }
+        if rule.node().is_some_and(|n| n != self.base_name()) {
+            return Ok(()); // Not for us
+        }
+        self.remapping.add(&self.node_name, &rule)
+    }
+
+    /// Apply remapping rules to a Topic, Service or Action name.
+    ///
+    /// Returns the name unchanged, if no rule matches.
+    pub fn remap_name(&self, name: &Name) -> Name {
+        self.remapping
+            .apply(&self.node_name, name)
+            .unwrap_or_else(|| name.clone())
+    }
+
+    /// [`ServiceMapping`] for Service `service_name`, according to
+    /// [`NodeOptions::service_mapping_table`].
+    ///
+    /// The name is remapped and made absolute before looking it up.
+    pub fn service_mapping_for(&self, service_name: &Name) -> ServiceMapping {
+        let name = self
+            .remap_name(service_name)
+            .to_absolute(self.node_name.namespace());
+        self.options
+            .service_mapping_table
+            .mapping_for(&name.to_string())
+    }
+
     pub fn options(&self) -> &NodeOptions {
         &self.options
     }
@@ -952,15 +2183,41 @@ impl Node {
         self.ros_context.domain_id()
     }
 
+    /// Asserts liveliness of all Publishers of this Node, and all others in
+    /// the same [`Context`], that have
+    /// [`Liveliness::ManualByParticipant`](rustdds::policy::Liveliness::ManualByParticipant)
+    /// QoS.
+    ///
+    /// Such Publishers are considered alive only if this is called at least
+    /// once per lease duration, or they publish. Other Publishers are not
+    /// affected. For
+    /// [`Liveliness::ManualByTopic`](rustdds::policy::Liveliness::ManualByTopic),
+    /// use [`Publisher::assert_liveliness`].
+    ///
+    /// An `Err` result means that the assertion could not be sent, because
+    /// Discovery is too busy. Try again later.
+    pub fn assert_liveliness(&self) -> WriteResult<(), ()> {
+        self.ros_context.domain_participant().assert_liveliness()
+    }
+
     // ///////////////////////////////////////////////
     // Parameters
 
+    #[cfg(feature = "parameters")]
     pub fn undeclare_parameter(&self, name: &str) {
+        if self.is_parameter_read_only(name) {
+            warn!("undeclare_parameter: Parameter '{name}' is read-only. Not removed.");
+            return;
+        }
         let prev_value = self.parameters.lock().unwrap().remove(name);
 
         if let Some(deleted_param) = prev_value {
             // a parameter was actually undeclared. Let others know.
-            self.parameter_events_writer
+            let Some(parameter_events_writer) = self.builtin_writer(&self.parameter_events_writer)
+            else {
+                return;
+            };
+            parameter_events_writer
                 .publish(raw::ParameterEvent {
                     timestamp: self.time_now().into(),
                     node: self.fully_qualified_name(),
@@ -976,6 +2233,7 @@ impl Node {
     }
 
     /// Does the parameter exist?
+    #[cfg(feature = "parameters")]
     pub fn has_parameter(&self, name: &str) -> bool {
         self.parameters.lock().unwrap().contains_key(name)
     }
@@ -989,49 +2247,91 @@ impl Node {
     // It thinks they are new on first set.
     // TODO: Setting Parameter to type NotSet counts as parameter deletion. Maybe
     // that needs special handling? At least for notifications.
+    #[cfg(feature = "parameters")]
     pub fn set_parameter(&self, name: &str, value: ParameterValue) -> Result<(), String> {
-        let already_set = self.parameters.lock().unwrap().contains_key(name);
-        if self.options.allow_undeclared_parameters || already_set {
-            self.validate_parameter_on_set(name, &value)?;
-            self.execute_parameter_set_actions(name, &value)?;
+        self.set_parameters_atomically(vec![Parameter {
+            name: name.to_owned(),
+            value,
+        }])
+    }
+
+    /// Sets several parameters at once.
+    ///
+    /// Either all of the parameters are set, or none are. All the parameters
+    /// are first checked individually, and then the prospective parameter set
+    /// is checked by the validator given in
+    /// [`NodeOptions::parameter_set_validator`]. The reason for rejection is
+    /// returned in `Err`.
+    //
+    // Keep this function in sync with the same function in Spinner.
+    #[cfg(feature = "parameters")]
+    pub fn set_parameters_atomically(&self, parameters: Vec<Parameter>) -> SetParametersResult {
+        // Check everything before changing anything.
+        let mut prospective_parameters = self.parameters.lock().unwrap().clone();
+        let mut new_parameters = vec![];
+        let mut changed_parameters = vec![];
+        for Parameter { name, value } in &parameters {
+            if self.is_parameter_read_only(name) {
+                return Err("Parameter '".to_owned() + name + "' is read-only.");
+            }
+            let already_set = prospective_parameters.contains_key(name);
+            if !(self.options.allow_undeclared_parameters || already_set) {
+                return Err("Setting undeclared parameter '".to_owned()
+                    + name
+                    + "' is not allowed.");
+            }
+            self.validate_parameter_on_set(name, value)?;
 
-            // no errors, prepare for sending notificaiton
             let p = raw::Parameter {
                 name: name.to_string(),
                 value: value.clone().into(),
             };
-            let (new_parameters, changed_parameters) = if already_set {
-                (vec![], vec![p])
+            if already_set {
+                changed_parameters.push(p)
             } else {
-                (vec![p], vec![])
-            };
+                new_parameters.push(p)
+            }
+            prospective_parameters.insert(name.clone(), value.clone());
+        }
+        self.validate_parameter_set(&prospective_parameters)?;
 
-            // actually set the parameter
-            self.parameters
-                .lock()
-                .unwrap()
-                .insert(name.to_owned(), value);
-            // and notify
-            self.parameter_events_writer
-                .publish(raw::ParameterEvent {
-                    timestamp: self.time_now().into(),
-                    node: self.fully_qualified_name(),
-                    new_parameters,
-                    changed_parameters,
-                    deleted_parameters: vec![],
-                })
-                .unwrap_or_else(|e| warn!("undeclare_parameter: {e:?}"));
-            Ok(())
-        } else {
-            Err("Setting undeclared parameter '".to_owned() + name + "' is not allowed.")
+        // TODO: If an action fails, the preceding actions have already been
+        // executed, so the parameter set is not really atomic from the
+        // application's point of view.
+        for Parameter { name, value } in &parameters {
+            self.execute_parameter_set_actions(name, value)?;
         }
+
+        // actually set the parameters
+        self.parameters.lock().unwrap().extend(
+            parameters
+                .into_iter()
+                .map(|Parameter { name, value }| (name, value)),
+        );
+        // and notify
+        let Some(parameter_events_writer) = self.builtin_writer(&self.parameter_events_writer)
+        else {
+            return Ok(());
+        };
+        parameter_events_writer
+            .publish(raw::ParameterEvent {
+                timestamp: self.time_now().into(),
+                node: self.fully_qualified_name(),
+                new_parameters,
+                changed_parameters,
+                deleted_parameters: vec![],
+            })
+            .unwrap_or_else(|e| warn!("set_parameters: {e:?}"));
+        Ok(())
     }
 
+    #[cfg(feature = "parameters")]
     pub fn allow_undeclared_parameters(&self) -> bool {
         self.options.allow_undeclared_parameters
     }
 
     /// Gets the value of a parameter, or None is there is no such Parameter.
+    #[cfg(feature = "parameters")]
     pub fn get_parameter(&self, name: &str) -> Option<ParameterValue> {
         self.parameters
             .lock()
@@ -1040,6 +2340,50 @@ impl Node {
             .map(|p| p.to_owned())
     }
 
+    /// Makes a declared parameter read-only.
+    ///
+    /// After this, any attempt to set or undeclare the parameter, either
+    /// locally or via Parameter Services, is rejected. This cannot be undone.
+    #[cfg(feature = "parameters")]
+    pub fn set_parameter_read_only(&self, name: &str) -> Result<(), String> {
+        if self.has_parameter(name) {
+            self.read_only_parameters
+                .lock()
+                .unwrap()
+                .insert(name.to_owned());
+            Ok(())
+        } else {
+            Err("Parameter '".to_owned() + name + "' is not declared.")
+        }
+    }
+
+    /// Makes all parameters read-only, i.e. freezes the Node configuration.
+    ///
+    /// This is intended to be called after initialization is complete, so that
+    /// the configuration of e.g. safety-critical nodes cannot be changed
+    /// remotely. Declaring new parameters is also prevented. This cannot be
+    /// undone.
+    #[cfg(feature = "parameters")]
+    pub fn freeze_parameters(&self) {
+        self.parameters_frozen.store(true, Ordering::SeqCst);
+    }
+
+    /// Has [`Self::freeze_parameters`] been called?
+    #[cfg(feature = "parameters")]
+    pub fn parameters_frozen(&self) -> bool {
+        self.parameters_frozen.load(Ordering::SeqCst)
+    }
+
+    /// Is the parameter read-only, either individually or because all
+    /// parameters are frozen?
+    // Keep this function in sync with the same function in Spinner.
+    #[cfg(feature = "parameters")]
+    pub fn is_parameter_read_only(&self, name: &str) -> bool {
+        self.parameters_frozen.load(Ordering::SeqCst)
+            || self.read_only_parameters.lock().unwrap().contains(name)
+    }
+
+    #[cfg(feature = "parameters")]
     pub fn list_parameters(&self) -> Vec<String> {
         self.parameters
             .lock()
@@ -1055,6 +2399,7 @@ impl Node {
     // changing type.
     // TODO: Setting Parameter to type NotSet counts as parameter deletion. Maybe
     // that needs special handling?
+    #[cfg(feature = "parameters")]
     fn validate_parameter_on_set(&self, name: &str, value: &ParameterValue) -> SetParametersResult {
         match name {
             // built-in parameter check
@@ -1073,6 +2418,19 @@ impl Node {
     }
 
     // Keep this function in sync with the same function in Spinner.
+    #[cfg(feature = "parameters")]
+    fn validate_parameter_set(
+        &self,
+        prospective_parameters: &BTreeMap<String, ParameterValue>,
+    ) -> SetParametersResult {
+        match self.parameter_set_validator {
+            Some(ref v) => v.lock().unwrap()(prospective_parameters),
+            None => Ok(()),
+        }
+    }
+
+    // Keep this function in sync with the same function in Spinner.
+    #[cfg(feature = "parameters")]
     fn execute_parameter_set_actions(
         &self,
         name: &str,
@@ -1114,6 +2472,90 @@ impl Node {
         }
     }
 
+    /// Number of discovered remote Publishers on Topic `topic_name`.
+    ///
+    /// The name is subject to [remapping]. This counts all Publishers found
+    /// by Discovery, whether or not their QoS matches any local Subscription.
+    /// Discovery events are processed by the [`Spinner`], so without one
+    /// nothing is ever found. Publishers from the [`discovery_cache`] are
+    /// also counted, if one is configured.
+    pub fn count_publishers(&self, topic_name: &Name) -> usize {
+        let dds_name = self
+            .remap_name(topic_name)
+            .to_dds_name("rt", &self.node_name, "");
+        self.count_remote(EndpointKind::Writer, &dds_name)
+    }
+
+    /// Number of discovered remote Subscriptions on Topic `topic_name`.
+    ///
+    /// See [`Self::count_publishers`].
+    pub fn count_subscribers(&self, topic_name: &Name) -> usize {
+        let dds_name = self
+            .remap_name(topic_name)
+            .to_dds_name("rt", &self.node_name, "");
+        self.count_remote(EndpointKind::Reader, &dds_name)
+    }
+
+    /// Has a remote Server for Service `service_name` been discovered?
+    ///
+    /// A Server is taken to exist if someone reads the request Topic and
+    /// writes the response Topic of the Service. The name is subject to
+    /// [remapping]. Like [`Self::count_publishers`], this requires a
+    /// [`Spinner`].
+    pub fn service_is_available(&self, service_name: &Name) -> bool {
+        let service_name = self.remap_name(service_name);
+        let request_topic = service_name.to_dds_name("rq", &self.node_name, "Request");
+        let response_topic = service_name.to_dds_name("rr", &self.node_name, "Reply");
+        self.count_remote(EndpointKind::Reader, &request_topic) > 0
+            && self.count_remote(EndpointKind::Writer, &response_topic) > 0
+    }
+
+    // Discovered and cached remote endpoints on the given DDS Topic
+    fn count_remote(&self, kind: EndpointKind, dds_topic_name: &str) -> usize {
+        let live = match kind {
+            EndpointKind::Reader => &self.remote_reader_topics,
+            EndpointKind::Writer => &self.remote_writer_topics,
+        };
+        let cached = self.discovery_cache.as_ref().map_or(0, |cache| {
+            cache
+                .lock()
+                .unwrap()
+                .count(kind, dds_topic_name, &live.lock().unwrap())
+        });
+        count_topic(live, dds_topic_name) + cached
+    }
+
+    /// Snapshot of the discovered graph: Nodes, and all Readers and Writers
+    /// with their types and QoS, including those of this Node.
+    ///
+    /// See [`graph_snapshot`] for exporting and comparing snapshots. Like
+    /// [`Self::count_publishers`], this requires a [`Spinner`].
+    #[cfg(feature = "graph-snapshot")]
+    pub fn graph_snapshot(&self) -> graph_snapshot::GraphSnapshot {
+        graph_snapshot::collect(
+            self.domain_id(),
+            self.fully_qualified_name(),
+            &self.external_nodes.lock().unwrap(),
+            &self.remote_endpoints.lock().unwrap(),
+            &self.endpoints.lock().unwrap(),
+        )
+    }
+
+    /// Write the remote endpoints discovered so far to the
+    /// [`discovery_cache`] file.
+    ///
+    /// This is also done when the Node is dropped. Does nothing if no cache
+    /// is configured.
+    pub fn save_discovery_cache(&self) -> std::io::Result<()> {
+        match &self.discovery_cache {
+            Some(cache) => cache.lock().unwrap().save(
+                &self.remote_reader_topics.lock().unwrap(),
+                &self.remote_writer_topics.lock().unwrap(),
+            ),
+            None => Ok(()),
+        }
+    }
+
     // reader waits for at least one writer to be present
     pub(crate) fn wait_for_writer(&self, reader: GUID) -> impl Future<Output = ()> {
         // TODO: This may contain some synchrnoization hazard
@@ -1163,6 +2605,10 @@ impl Node {
         }
     }
 
+    pub(crate) fn readers_to_remote_writers(&self) -> Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>> {
+        Arc::clone(&self.readers_to_remote_writers)
+    }
+
     pub(crate) fn get_publisher_count(&self, subscription_guid: GUID) -> usize {
         self.readers_to_remote_writers
             .lock()
@@ -1187,9 +2633,16 @@ impl Node {
             })
     }
 
+    // The `/rosout` Topic of the Context
+    #[cfg(feature = "rosout-monitor")]
+    pub(crate) fn rosout_topic(&self) -> Topic {
+        self.ros_context.get_rosout_topic()
+    }
+
     /// Borrow the Subscription to our ROSOut Reader.
     ///
     /// Availability depends on Node configuration.
+    #[cfg(feature = "rosout")]
     pub fn rosout_subscription(&self) -> Option<&Subscription<Log>> {
         self.rosout_reader.as_ref()
     }
@@ -1205,21 +2658,35 @@ impl Node {
         source_function: &str,
         source_line: u32,
     ) {
-        match &self.rosout_writer {
-            None => debug!("Rosout not enabled. msg: {log_msg}"),
-            Some(writer) => {
-                writer
-                    .publish(Log {
-                        timestamp,
-                        level: level as u8,
-                        name: log_name.to_string(),
-                        msg: log_msg.to_string(),
-                        file: source_file.to_string(),
-                        function: source_function.to_string(),
-                        line: source_line,
-                    })
-                    .unwrap_or_else(|e| debug!("Rosout publish failed: {e:?}"));
-            }
+        #[cfg(feature = "rosout")]
+        let rosout_enabled = self.rosout_writer.is_some();
+        #[cfg(not(feature = "rosout"))]
+        let rosout_enabled = false;
+        if !rosout_enabled && self.options.log_sinks.is_empty() {
+            debug!("Rosout not enabled. msg: {log_msg}");
+            return;
+        }
+        let log = Log {
+            timestamp,
+            level: level as u8,
+            name: log_name.to_string(),
+            msg: log_msg.to_string(),
+            file: source_file.to_string(),
+            function: source_function.to_string(),
+            line: source_line,
+        };
+        for sink in &self.options.log_sinks {
+            sink.write(&log);
+        }
+        #[cfg(feature = "rosout")]
+        if let Some(writer) = self
+            .rosout_writer
+            .as_ref()
+            .and_then(|w| self.builtin_writer(w))
+        {
+            writer
+                .publish(log)
+                .unwrap_or_else(|e| debug!("Rosout publish failed: {e:?}"));
         }
     }
 
@@ -1261,11 +2728,35 @@ impl Node {
         let topic_name = topic_name.as_ref();
         let ty_name = type_name.as_ref();
     */
+    ///
+    /// The name is subject to [remapping].
     pub fn create_topic(
         &self,
         topic_name: &Name,
         type_name: MessageTypeName,
         qos: &QosPolicies,
+    ) -> CreateResult<Topic> {
+        self.create_topic_remapped(&self.remap_name(topic_name), type_name, qos)
+    }
+
+    /// Creates ROS2 topic for a [`NamedMessage`].
+    ///
+    /// This is like [`Self::create_topic`], but the message type name comes
+    /// from `M`.
+    pub fn create_topic_for<M: NamedMessage>(
+        &self,
+        topic_name: &Name,
+        qos: &QosPolicies,
+    ) -> CreateResult<Topic> {
+        self.create_topic(topic_name, M::message_type_name(), qos)
+    }
+
+    // Like create_topic, but the name is already remapped.
+    fn create_topic_remapped(
+        &self,
+        topic_name: &Name,
+        type_name: MessageTypeName,
+        qos: &QosPolicies,
     ) -> CreateResult<Topic> {
         let dds_name = topic_name.to_dds_name("rt", &self.node_name, "");
         self.ros_context.create_topic(dds_name, type_name, qos)
@@ -1283,8 +2774,43 @@ impl Node {
         topic: &Topic,
         qos: Option<QosPolicies>,
     ) -> CreateResult<Subscription<D>> {
-        let sub = self.ros_context.create_subscription(topic, qos)?;
-        self.add_reader(sub.guid().into());
+        self.create_subscription_with_adapter(topic, qos)
+    }
+
+    /// Creates ROS2 Subscriber with a custom deserializer adapter.
+    ///
+    /// This is like [`Self::create_subscription`], but the received data is
+    /// decoded by `DA` instead of the default CDR deserializer. This can be
+    /// used e.g. to work around a peer using a slightly different encoding,
+    /// or to receive the data without deserializing it.
+    pub fn create_subscription_with_adapter<D, DA>(
+        &mut self,
+        topic: &Topic,
+        qos: Option<QosPolicies>,
+    ) -> CreateResult<Subscription<D, DA>>
+    where
+        D: 'static,
+        DA: no_key::DeserializerAdapter<D> + 'static,
+    {
+        let qos = self.endpoint_qos(self.options.default_subscription_qos.as_ref(), topic, qos);
+        let mut sub = Subscription::new(
+            self.ros_context.create_simpledatareader(topic, qos)?,
+            topic.name(),
+        );
+        if let Some(policy::Ownership::Exclusive { .. }) = sub.qos().ownership() {
+            sub.set_ownership_filter(OwnershipFilter::new(
+                Arc::clone(&self.readers_to_remote_writers),
+                Arc::clone(&self.writer_ownership_strengths),
+            ));
+        }
+        crate::qos::check_endpoint_qos(
+            &topic.name(),
+            sub.qos(),
+            self.options.reject_unbounded_history,
+        )?;
+        self.add_local_topic(topic);
+        let registration = self.add_reader(sub.guid().into(), topic, sub.qos())?;
+        sub.set_registration(registration);
         Ok(sub)
     }
 
@@ -1301,36 +2827,126 @@ impl Node {
         topic: &Topic,
         qos: Option<QosPolicies>,
     ) -> CreateResult<Publisher<D>> {
-        let p = self.ros_context.create_publisher(topic, qos)?;
-        self.add_writer(p.guid().into());
+        self.create_publisher_with_adapter(topic, qos)
+    }
+
+    /// Creates ROS2 Publisher with a custom serializer adapter.
+    ///
+    /// This is like [`Self::create_publisher`], but the data is encoded by
+    /// `SA` instead of the default CDR serializer.
+    pub fn create_publisher_with_adapter<D, SA>(
+        &mut self,
+        topic: &Topic,
+        qos: Option<QosPolicies>,
+    ) -> CreateResult<Publisher<D, SA>>
+    where
+        SA: no_key::SerializerAdapter<D>,
+    {
+        let qos = self.endpoint_qos(self.options.default_publisher_qos.as_ref(), topic, qos);
+        let mut p = Publisher::new(self.ros_context.create_datawriter(topic, qos)?);
+        p.set_max_message_size(self.ros_context.transport_limits().max_message_size);
+        crate::qos::check_endpoint_qos(
+            &topic.name(),
+            &p.qos(),
+            self.options.reject_unbounded_history,
+        )?;
+        self.add_local_topic(topic);
+        let registration = self.add_writer(p.guid().into(), topic, &p.qos())?;
+        p.set_registration(registration);
         Ok(p)
     }
 
+    // Combine Topic QoS, Node default QoS, pattern overrides and explicitly
+    // given QoS, in increasing order of precedence. `None` means that the
+    // Node has nothing to add, and DDS should use Topic QoS as usual.
+    fn endpoint_qos(
+        &self,
+        node_default: Option<&QosPolicies>,
+        topic: &Topic,
+        qos: Option<QosPolicies>,
+    ) -> Option<QosPolicies> {
+        let topic_name = crate::interfaces::names::ros_name_of_dds_topic(&topic.name());
+        let mut overrides = self
+            .options
+            .topic_qos_overrides
+            .iter()
+            .filter(|(pattern, _)| crate::qos::topic_pattern_matches(pattern, &topic_name))
+            .map(|(_, qos)| qos)
+            .peekable();
+        if node_default.is_none() && overrides.peek().is_none() {
+            return qos;
+        }
+        let base = match node_default {
+            Some(d) => topic.qos().modify_by(d),
+            None => topic.qos(),
+        };
+        let combined = overrides.fold(base, |acc, o| acc.modify_by(o));
+        Some(match qos {
+            Some(q) => combined.modify_by(&q),
+            None => combined,
+        })
+    }
+
     pub(crate) fn create_simpledatareader<D, DA>(
         &mut self,
         topic: &Topic,
         qos: Option<QosPolicies>,
-    ) -> CreateResult<no_key::SimpleDataReader<D, DA>>
+    ) -> CreateResult<(no_key::SimpleDataReader<D, DA>, EndpointRegistration)>
     where
         D: 'static,
         DA: rustdds::no_key::DeserializerAdapter<D> + 'static,
     {
         let r = self.ros_context.create_simpledatareader(topic, qos)?;
-        self.add_reader(r.guid().into());
-        Ok(r)
+        self.add_local_topic(topic);
+        let registration = self.add_reader(r.guid().into(), topic, r.qos())?;
+        Ok((r, registration))
     }
 
     pub(crate) fn create_datawriter<D, SA>(
         &mut self,
         topic: &Topic,
         qos: Option<QosPolicies>,
-    ) -> CreateResult<no_key::DataWriter<D, SA>>
+    ) -> CreateResult<(no_key::DataWriter<D, SA>, EndpointRegistration)>
     where
         SA: rustdds::no_key::SerializerAdapter<D>,
     {
         let w = self.ros_context.create_datawriter(topic, qos)?;
-        self.add_writer(w.guid().into());
-        Ok(w)
+        self.add_local_topic(topic);
+        let registration = self.add_writer(w.guid().into(), topic, &w.qos())?;
+        Ok((w, registration))
+    }
+
+    /// Subscribes to a Topic of [`NamedMessage`] type `M`.
+    ///
+    /// This is a shorthand for creating the Topic with
+    /// [`Self::create_topic_for`] and then a Subscription, using
+    /// [`DEFAULT_SUBSCRIPTION_QOS`]. The name is subject to [remapping].
+    ///
+    /// ```ignore
+    /// let chatter = node.subscribe::<Header>("/chatter")?;
+    /// ```
+    pub fn subscribe<M>(&mut self, topic_name: &str) -> CreateResult<Subscription<M>>
+    where
+        M: NamedMessage + 'static,
+    {
+        let topic =
+            self.create_topic_for::<M>(&parse_topic_name(topic_name)?, &DEFAULT_SUBSCRIPTION_QOS)?;
+        self.create_subscription(&topic, None)
+    }
+
+    /// Creates a Publisher to a Topic of [`NamedMessage`] type `M`.
+    ///
+    /// This is the publishing counterpart of [`Self::subscribe`], using
+    /// [`DEFAULT_PUBLISHER_QOS`](context::DEFAULT_PUBLISHER_QOS).
+    pub fn advertise<M>(&mut self, topic_name: &str) -> CreateResult<Publisher<M>>
+    where
+        M: NamedMessage,
+    {
+        let topic = self.create_topic_for::<M>(
+            &parse_topic_name(topic_name)?,
+            &context::DEFAULT_PUBLISHER_QOS,
+        )?;
+        self.create_publisher(&topic, None)
     }
 
     /// Creates ROS2 Service Client
@@ -1338,9 +2954,41 @@ impl Node {
     /// # Arguments
     ///
     /// * `service_mapping` - ServiceMapping to be used
-    /// * `service_name` -
-    /// * `qos`-
+    /// * `service_name` - This is subject to [remapping].
+    /// * `request_qos` - A `QosPolicies` or an `Option` of one. `None` means
+    ///   [`DEFAULT_SERVICE_QOS`](context::DEFAULT_SERVICE_QOS).
+    /// * `response_qos` - As `request_qos`, but `None` means
+    ///   [`DEFAULT_CLIENT_RESPONSE_QOS`](context::DEFAULT_CLIENT_RESPONSE_QOS).
+    ///   Its history depth limits the number of requests that can be in
+    ///   flight without losing responses.
     pub fn create_client<S>(
+        &mut self,
+        service_mapping: ServiceMapping,
+        service_name: &Name,
+        service_type_name: &ServiceTypeName,
+        request_qos: impl Into<Option<QosPolicies>>,
+        response_qos: impl Into<Option<QosPolicies>>,
+    ) -> CreateResult<Client<S>>
+    where
+        S: Service + 'static,
+        S::Request: Clone,
+    {
+        let service_name = self.remap_name(service_name);
+        self.create_client_remapped(
+            service_mapping,
+            &service_name,
+            service_type_name,
+            request_qos
+                .into()
+                .unwrap_or_else(|| DEFAULT_SERVICE_QOS.clone()),
+            response_qos
+                .into()
+                .unwrap_or_else(|| DEFAULT_CLIENT_RESPONSE_QOS.clone()),
+        )
+    }
+
+    // Like create_client, but the name is already remapped.
+    fn create_client_remapped<S>(
         &mut self,
         service_mapping: ServiceMapping,
         service_name: &Name,
@@ -1390,9 +3038,37 @@ impl Node {
     ///
     /// * `service_mapping` - ServiceMapping to be used. See
     ///   [`Self.create_client`].
-    /// * `service_name` -
-    /// * `qos`-
+    /// * `service_name` - This is subject to [remapping].
+    /// * `request_qos`, `response_qos` - A `QosPolicies` or an `Option` of one.
+    ///   `None` means [`DEFAULT_SERVICE_QOS`](context::DEFAULT_SERVICE_QOS).
     pub fn create_server<S>(
+        &mut self,
+        service_mapping: ServiceMapping,
+        service_name: &Name,
+        service_type_name: &ServiceTypeName,
+        request_qos: impl Into<Option<QosPolicies>>,
+        response_qos: impl Into<Option<QosPolicies>>,
+    ) -> CreateResult<Server<S>>
+    where
+        S: Service + 'static,
+        S::Request: Clone,
+    {
+        let service_name = self.remap_name(service_name);
+        self.create_server_remapped(
+            service_mapping,
+            &service_name,
+            service_type_name,
+            request_qos
+                .into()
+                .unwrap_or_else(|| DEFAULT_SERVICE_QOS.clone()),
+            response_qos
+                .into()
+                .unwrap_or_else(|| DEFAULT_SERVICE_QOS.clone()),
+        )
+    }
+
+    // Like create_server, but the name is already remapped.
+    fn create_server_remapped<S>(
         &mut self,
         service_mapping: ServiceMapping,
         service_name: &Name,
@@ -1435,6 +3111,132 @@ impl Node {
         Ok(s)
     }
 
+    /// Creates ROS2 Service Client for a [`NamedService`].
+    ///
+    /// This is like [`Self::create_client`], but the Service type name comes
+    /// from `S`. The Service mapping is chosen by
+    /// [`Self::service_mapping_for`], and QoS is the default of
+    /// [`Self::create_client`].
+    pub fn create_client_for<S>(&mut self, service_name: &Name) -> CreateResult<Client<S>>
+    where
+        S: NamedService + 'static,
+        S::Request: Clone,
+    {
+        self.create_client(
+            self.service_mapping_for(service_name),
+            service_name,
+            &S::service_type_name(),
+            None,
+            None,
+        )
+    }
+
+    /// Creates ROS2 Service Server for a [`NamedService`].
+    ///
+    /// This is like [`Self::create_server`], but the Service type name comes
+    /// from `S`. The Service mapping is chosen as in
+    /// [`Self::create_client_for`], and QoS is
+    /// [`DEFAULT_SERVICE_QOS`](context::DEFAULT_SERVICE_QOS).
+    pub fn create_server_for<S>(&mut self, service_name: &Name) -> CreateResult<Server<S>>
+    where
+        S: NamedService + 'static,
+        S::Request: Clone,
+    {
+        self.create_server(
+            self.service_mapping_for(service_name),
+            service_name,
+            &S::service_type_name(),
+            None,
+            None,
+        )
+    }
+
+    /// Creates a Service Client that sends and receives serialized messages.
+    ///
+    /// The Service type is given only at run time. See
+    /// [`generic`](crate::service::generic). The arguments are as in
+    /// [`Self::create_client`], but [`ServiceMapping::Basic`] is rejected
+    /// with [`CreateError::BadParameter`].
+    pub fn create_generic_client(
+        &mut self,
+        service_mapping: ServiceMapping,
+        service_name: &Name,
+        service_type_name: &ServiceTypeName,
+        request_qos: QosPolicies,
+        response_qos: QosPolicies,
+    ) -> CreateResult<GenericClient> {
+        let service_name = self.remap_name(service_name);
+        let (rq_topic, rs_topic) = self.create_service_topics(
+            &service_name,
+            service_type_name,
+            &request_qos,
+            &response_qos,
+        )?;
+        GenericClient::new(
+            service_mapping,
+            service_type_name,
+            self,
+            &rq_topic,
+            &rs_topic,
+            Some(request_qos),
+            Some(response_qos),
+        )
+    }
+
+    /// Creates a Service Server that receives and sends serialized messages.
+    ///
+    /// See [`Self::create_generic_client`].
+    pub fn create_generic_server(
+        &mut self,
+        service_mapping: ServiceMapping,
+        service_name: &Name,
+        service_type_name: &ServiceTypeName,
+        request_qos: QosPolicies,
+        response_qos: QosPolicies,
+    ) -> CreateResult<GenericServer> {
+        let service_name = self.remap_name(service_name);
+        let (rq_topic, rs_topic) = self.create_service_topics(
+            &service_name,
+            service_type_name,
+            &request_qos,
+            &response_qos,
+        )?;
+        GenericServer::new(
+            service_mapping,
+            service_type_name,
+            self,
+            &rq_topic,
+            &rs_topic,
+            Some(request_qos),
+            Some(response_qos),
+        )
+    }
+
+    // Request and response Topics for a Service, name already remapped
+    fn create_service_topics(
+        &self,
+        service_name: &Name,
+        service_type_name: &ServiceTypeName,
+        request_qos: &QosPolicies,
+        response_qos: &QosPolicies,
+    ) -> CreateResult<(Topic, Topic)> {
+        let rq_topic = self.ros_context.domain_participant().create_topic(
+            service_name.to_dds_name("rq", &self.node_name, "Request"),
+            service_type_name.dds_request_type(),
+            request_qos,
+            TopicKind::NoKey,
+        )?;
+        let rs_topic = self.ros_context.domain_participant().create_topic(
+            service_name.to_dds_name("rr", &self.node_name, "Reply"),
+            service_type_name.dds_response_type(),
+            response_qos,
+            TopicKind::NoKey,
+        )?;
+        Ok((rq_topic, rs_topic))
+    }
+
+    /// Create an Action client. The Action name is subject to [remapping].
+    #[cfg(feature = "actions")]
     pub fn create_action_client<A>(
         &mut self,
         service_mapping: ServiceMapping,
@@ -1445,13 +3247,14 @@ impl Node {
     where
         A: ActionTypes + 'static,
     {
+        let action_name = &self.remap_name(action_name);
         // action name is e.g. "/turtle1/rotate_absolute"
         // action type name is e.g. "turtlesim/action/RotateAbsolute"
         let services_base_name = action_name.push("_action");
 
         //let goal_service_name = action_name.to_owned() + "/_action/send_goal";
         let goal_service_type = action_type_name.dds_action_service("_SendGoal");
-        let my_goal_client = self.create_client(
+        let my_goal_client = self.create_client_remapped(
             service_mapping,
             //&goal_service_name,
             &services_base_name.push("send_goal"),
@@ -1462,7 +3265,7 @@ impl Node {
 
         //let cancel_service_name = action_name.to_owned() + "/_action/cancel_goal";
         let cancel_goal_type = ServiceTypeName::new("action_msgs", "CancelGoal");
-        let my_cancel_client = self.create_client(
+        let my_cancel_client = self.create_client_remapped(
             service_mapping,
             //&cancel_service_name,
             &services_base_name.push("cancel_goal"),
@@ -1473,7 +3276,7 @@ impl Node {
 
         //let result_service_name = action_name.to_owned() + "/_action/get_result";
         let result_service_type = action_type_name.dds_action_service("_GetResult");
-        let my_result_client = self.create_client(
+        let my_result_client = self.create_client_remapped(
             service_mapping,
             //&result_service_name,
             &services_base_name.push("get_result"),
@@ -1485,7 +3288,7 @@ impl Node {
         let action_topic_namespace = action_name.push("_action");
 
         let feedback_topic_type = action_type_name.dds_action_topic("_FeedbackMessage");
-        let feedback_topic = self.create_topic(
+        let feedback_topic = self.create_topic_remapped(
             &action_topic_namespace.push("feedback"),
             feedback_topic_type,
             &action_qos.feedback_subscription,
@@ -1494,7 +3297,7 @@ impl Node {
             self.create_subscription(&feedback_topic, Some(action_qos.feedback_subscription))?;
 
         //let status_topic_type = ;
-        let status_topic = self.create_topic(
+        let status_topic = self.create_topic_remapped(
             &action_topic_namespace.push("status"),
             MessageTypeName::new("action_msgs", "GoalStatusArray"),
             &action_qos.status_subscription,
@@ -1509,9 +3312,17 @@ impl Node {
             my_feedback_subscription,
             my_status_subscription,
             my_action_name: action_name.clone(),
+            #[cfg(feature = "introspection")]
+            introspection: None,
+            call_tracer: self.call_tracer(action_name.to_string()),
+            goal_states: Mutex::new(BTreeMap::new()),
+            result_responses: Mutex::new(BTreeMap::new()),
+            result_waiters: Mutex::new(BTreeMap::new()),
         })
     }
 
+    /// Create an Action server. The Action name is subject to [remapping].
+    #[cfg(feature = "actions")]
     pub fn create_action_server<A>(
         &mut self,
         service_mapping: ServiceMapping,
@@ -1522,11 +3333,12 @@ impl Node {
     where
         A: ActionTypes + 'static,
     {
+        let action_name = &self.remap_name(action_name);
         let services_base_name = action_name.push("_action");
 
         //let goal_service_name = action_name.to_owned() + "/_action/send_goal";
         let goal_service_type = action_type_name.dds_action_service("_SendGoal");
-        let my_goal_server = self.create_server(
+        let my_goal_server = self.create_server_remapped(
             service_mapping,
             //&goal_service_name,
             &services_base_name.push("send_goal"),
@@ -1537,7 +3349,7 @@ impl Node {
 
         //let cancel_service_name = action_name.to_owned() + "/_action/cancel_goal";
         let cancel_service_type = ServiceTypeName::new("action_msgs", "CancelGoal");
-        let my_cancel_server = self.create_server(
+        let my_cancel_server = self.create_server_remapped(
             service_mapping,
             //&cancel_service_name,
             &services_base_name.push("cancel_goal"),
@@ -1548,7 +3360,7 @@ impl Node {
 
         //let result_service_name = action_name.to_owned() + "/_action/get_result";
         let result_service_type = action_type_name.dds_action_service("_GetResult");
-        let my_result_server = self.create_server(
+        let my_result_server = self.create_server_remapped(
             service_mapping,
             //&result_service_name,
             &services_base_name.push("get_result"),
@@ -1560,7 +3372,7 @@ impl Node {
         let action_topic_namespace = action_name.push("_action");
 
         let feedback_topic_type = action_type_name.dds_action_topic("_FeedbackMessage");
-        let feedback_topic = self.create_topic(
+        let feedback_topic = self.create_topic_remapped(
             &action_topic_namespace.push("feedback"),
             feedback_topic_type,
             &action_qos.feedback_publisher,
@@ -1569,7 +3381,7 @@ impl Node {
             self.create_publisher(&feedback_topic, Some(action_qos.feedback_publisher))?;
 
         let status_topic_type = MessageTypeName::new("action_msgs", "GoalStatusArray");
-        let status_topic = self.create_topic(
+        let status_topic = self.create_topic_remapped(
             &action_topic_namespace.push("status"),
             status_topic_type,
             &action_qos.status_publisher,
@@ -1584,18 +3396,72 @@ impl Node {
             my_feedback_publisher,
             my_status_publisher,
             my_action_name: action_name.clone(),
+            #[cfg(feature = "introspection")]
+            introspection: None,
+            call_tracer: self.call_tracer(action_name.to_string()),
         })
     }
+
+    /// Create publishers for Action introspection events.
+    ///
+    /// The result can be attached to an [`ActionClient`] or [`ActionServer`]
+    /// of the same Action with `set_introspection`. Events are published on
+    /// topics `<action_name>/_action/{send_goal,cancel_goal,get_result}/_service_event`.
+    #[cfg(all(feature = "actions", feature = "introspection"))]
+    pub fn create_action_introspection<A>(
+        &mut self,
+        action_name: &Name,
+        action_type_name: &ActionTypeName,
+        state: IntrospectionState,
+        qos: &QosPolicies,
+    ) -> CreateResult<ActionIntrospection<A>>
+    where
+        A: ActionTypes + 'static,
+    {
+        let action_name = &self.remap_name(action_name);
+        let services_base_name = action_name.push("_action");
+        let event_topic_name = |service: &str| {
+            services_base_name
+                .push(service)
+                .push(SERVICE_EVENT_TOPIC_SUFFIX)
+        };
+
+        let send_goal_topic = self.create_topic_remapped(
+            &event_topic_name("send_goal"),
+            action_type_name.dds_action_topic("_SendGoal_Event"),
+            qos,
+        )?;
+        let cancel_goal_topic = self.create_topic_remapped(
+            &event_topic_name("cancel_goal"),
+            MessageTypeName::new_prefix("action_msgs", "CancelGoal_Event", "srv".to_owned()),
+            qos,
+        )?;
+        let get_result_topic = self.create_topic_remapped(
+            &event_topic_name("get_result"),
+            action_type_name.dds_action_topic("_GetResult_Event"),
+            qos,
+        )?;
+
+        Ok(ActionIntrospection::new(
+            state,
+            self.create_publisher(&send_goal_topic, Some(qos.clone()))?,
+            self.create_publisher(&cancel_goal_topic, Some(qos.clone()))?,
+            self.create_publisher(&get_result_topic, Some(qos.clone()))?,
+        ))
+    }
 } // impl Node
 
 impl Drop for Node {
     fn drop(&mut self) {
-        if let Some(ref stop_spin_sender) = self.stop_spin_sender {
+        if let Some(stop_spin_sender) = self.stop_spin_sender.as_ref().filter(|s| !s.is_closed()) {
             stop_spin_sender
                 .try_send(())
                 .unwrap_or_else(|e| error!("Cannot notify spin task to stop: {e:?}"));
         }
 
+        self.save_discovery_cache()
+            .unwrap_or_else(|e| warn!("Cannot save discovery cache: {e}"));
+
         self.ros_context
             .remove_node(self.fully_qualified_name().as_str());
     }
@@ -1748,3 +3614,66 @@ impl Future for WriterWait<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{
+        policy::{History, Reliability},
+        QosPolicyBuilder,
+    };
+
+    use super::*;
+    use crate::{
+        interfaces::std_msgs::Header,
+        node::context::{tests::test_node, Context},
+    };
+
+    #[test]
+    fn node_default_qos() {
+        let best_effort = QosPolicyBuilder::new()
+            .reliability(Reliability::BestEffort)
+            .build();
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_default_qos_node",
+            NodeOptions::new()
+                .enable_rosout(false)
+                .default_publisher_qos(best_effort)
+                .default_subscription_qos(
+                    QosPolicyBuilder::new()
+                        .history(History::KeepLast { depth: 5 })
+                        .build(),
+                ),
+        );
+        let topic = node
+            .create_topic(
+                &Name::new("/", "default_qos").unwrap(),
+                MessageTypeName::new("std_msgs", "Header"),
+                &DEFAULT_PUBLISHER_QOS,
+            )
+            .unwrap();
+
+        // Node defaults override the Topic QoS ...
+        let publisher = node.create_publisher::<Header>(&topic, None).unwrap();
+        assert_eq!(publisher.qos().reliability(), Some(Reliability::BestEffort));
+        let subscription = node.create_subscription::<Header>(&topic, None).unwrap();
+        assert_eq!(
+            subscription.qos().history(),
+            Some(History::KeepLast { depth: 5 })
+        );
+        assert_eq!(
+            subscription.qos().reliability(),
+            DEFAULT_PUBLISHER_QOS.reliability()
+        );
+
+        // ... but not explicitly given QoS.
+        let publisher = node
+            .create_publisher::<Header>(&topic, Some(DEFAULT_PUBLISHER_QOS.clone()))
+            .unwrap();
+        assert_eq!(
+            publisher.qos().reliability(),
+            DEFAULT_PUBLISHER_QOS.reliability()
+        );
+    }
+}
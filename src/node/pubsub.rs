@@ -1,39 +1,221 @@
-use std::{io, marker::PhantomData};
+use std::{
+    cell::RefCell,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, VecDeque},
+    hash::{Hash, Hasher},
+    io,
+    marker::PhantomData,
+    rc::Rc,
+    sync::{
+        atomic::{self, AtomicI64},
+        Arc, Mutex, MutexGuard, OnceLock,
+    },
+    task,
+    time::Duration,
+};
 
 use futures::{
-    pin_mut,
-    stream::{FusedStream, StreamExt},
+    future, pin_mut,
+    stream::{self, FusedStream, Stream, StreamExt},
     Future,
 };
 use mio::{Evented, Poll, PollOpt, Ready, Token};
 use rustdds::{
-    dds::{ReadError, ReadResult, WriteResult},
-    no_key, read_error_internal,
-    serialization::CdrDeserializeSeedDecoder,
-    RTPSEntity as _, Timestamp, WriteOptions,
+    dds::qos::HasQoSPolicy as _,
+    dds::{ReadError, ReadResult, WriteError, WriteResult},
+    no_key,
+    no_key::{DefaultDecoder, DeserializerAdapter, SerializerAdapter},
+    policy::History,
+    read_error_internal,
+    serialization::{deserialize_from_cdr_with_rep_id, CdrDeserializeSeedDecoder},
+    CDRDeserializerAdapter, CDRSerializerAdapter, QosPolicies, RTPSEntity as _,
+    RepresentationIdentifier, SequenceNumber, Timestamp, TopicDescription as _, WriteOptions,
+    WriteOptionsBuilder, GUID,
 };
-use serde::{de::DeserializeOwned, Serialize};
+use serde::de::DeserializeOwned;
 
-use crate::{interfaces::gid::Gid, prelude::MessageInfo};
+use crate::{
+    interfaces::{gid::Gid, names::ros_name_of_dds_topic},
+    prelude::MessageInfo,
+    time::steady_time::SteadyTime,
+};
 
-use super::Node;
+#[cfg(feature = "fault-injection")]
+use super::fault_injection::{FaultCounters, FaultInjection, FaultInjector};
+use super::{
+    counters::{Bandwidth, Counters, CountingDecoder, StatusCounters},
+    history_monitor::{HistoryDepthWarning, HistoryMonitor},
+    EndpointRegistration, Node,
+};
+#[cfg(feature = "no-default-runtime")]
+use crate::readiness::Readiness;
 
 /// A ROS2 Publisher
 ///
 /// Corresponds to a simplified [`DataWriter`](rustdds::no_key::DataWriter)in
 /// DDS
-pub struct Publisher<M: Serialize> {
-    datawriter: no_key::DataWriterCdr<M>,
+///
+/// The serializer adapter `SA` is CDR by default. Another adapter can be given
+/// in [`Node::create_publisher_with_adapter`].
+pub struct Publisher<M, SA: SerializerAdapter<M> = CDRSerializerAdapter<M>> {
+    datawriter: no_key::DataWriter<M, SA>,
+    // Zero until the first write, as DDS sequence numbers start from one.
+    last_sequence_number: AtomicI64,
+    counters: Arc<Counters>,
+    // From ContextOptions::max_message_size
+    max_message_size: Option<usize>,
+    #[cfg(feature = "fault-injection")]
+    faults: Mutex<Option<FaultInjector<M>>>,
+    // Only held to unregister from the Node on drop
+    _registration: Option<EndpointRegistration>,
 }
 
-impl<M: Serialize> Publisher<M> {
+impl<M, SA: SerializerAdapter<M>> Publisher<M, SA> {
     // These must be created from Node
-    pub(crate) fn new(datawriter: no_key::DataWriterCdr<M>) -> Publisher<M> {
-        Publisher { datawriter }
+    pub(crate) fn new(datawriter: no_key::DataWriter<M, SA>) -> Publisher<M, SA> {
+        Publisher {
+            datawriter,
+            last_sequence_number: AtomicI64::new(0),
+            counters: Arc::default(),
+            max_message_size: None,
+            #[cfg(feature = "fault-injection")]
+            faults: Mutex::new(None),
+            _registration: None,
+        }
+    }
+
+    pub(crate) fn set_registration(&mut self, registration: EndpointRegistration) {
+        registration.attach_counters(Arc::clone(&self.counters));
+        self._registration = Some(registration);
+    }
+
+    pub(crate) fn set_max_message_size(&mut self, max_message_size: Option<usize>) {
+        self.max_message_size = max_message_size;
     }
 
+    /// Remove this Publisher from DDS and from its Node.
+    ///
+    /// This is the same as dropping the Publisher, but states the intent.
+    /// Remote Subscriptions and ROS Discovery see it gone right away.
+    pub fn close(self) {}
+
     pub fn publish(&self, message: M) -> WriteResult<(), M> {
-        self.datawriter.write(message, Some(Timestamp::now()))
+        self.write_tracked(message, Timestamp::now()).map(|_| ())
+    }
+
+    // Like `publish`, but with the given source timestamp. Returns the
+    // sequence number of the message. With fault injection, all messages
+    // written now get `source_timestamp`, and the result is as in
+    // `write_tracked`.
+    pub(crate) fn publish_with_timestamp(
+        &self,
+        message: M,
+        source_timestamp: Timestamp,
+    ) -> WriteResult<SequenceNumber, M> {
+        self.write_tracked(message, source_timestamp)
+    }
+
+    /// Publishes a batch of messages.
+    ///
+    /// Returns the result of each write, in the same order as the messages.
+    /// On success, the result is the sequence number given to the message.
+    /// A failed write does not stop publishing the rest of the batch.
+    pub fn publish_iter(
+        &self,
+        messages: impl IntoIterator<Item = M>,
+    ) -> Vec<WriteResult<SequenceNumber, M>> {
+        messages
+            .into_iter()
+            .map(|message| self.write_tracked(message, Timestamp::now()))
+            .collect()
+    }
+
+    /// Sequence number of the latest message published, or `None` if nothing
+    /// has been published yet.
+    ///
+    /// Sequence numbers are assigned by DDS, and increase by one for each
+    /// message written by this Publisher.
+    pub fn last_sequence_number(&self) -> Option<SequenceNumber> {
+        match self.last_sequence_number.load(atomic::Ordering::Acquire) {
+            0 => None,
+            sn => Some(SequenceNumber::from(sn)),
+        }
+    }
+
+    // Writes may complete in any order, so keep the highest.
+    fn record_sequence_number(&self, sequence_number: SequenceNumber) {
+        self.last_sequence_number
+            .fetch_max(i64::from(sequence_number), atomic::Ordering::AcqRel);
+    }
+
+    #[cfg(not(feature = "fault-injection"))]
+    fn write_tracked(
+        &self,
+        message: M,
+        source_timestamp: Timestamp,
+    ) -> WriteResult<SequenceNumber, M> {
+        self.write_one(message, source_timestamp)
+    }
+
+    // Writes the messages that fault injection lets through now. The result
+    // is that of the last message written. If none was written, it is the
+    // latest sequence number written before.
+    #[cfg(feature = "fault-injection")]
+    fn write_tracked(
+        &self,
+        message: M,
+        source_timestamp: Timestamp,
+    ) -> WriteResult<SequenceNumber, M> {
+        let mut result = Ok(self
+            .last_sequence_number()
+            .unwrap_or_else(SequenceNumber::zero));
+        for message in self.fault_batch(message) {
+            result = self.write_one(message, source_timestamp);
+        }
+        result
+    }
+
+    fn write_one(&self, message: M, source_timestamp: Timestamp) -> WriteResult<SequenceNumber, M> {
+        let write_options = WriteOptionsBuilder::new()
+            .source_timestamp(source_timestamp)
+            .build();
+        let measured_size = self.measure_size(&message);
+        let message = self.check_size(message, measured_size)?;
+        let result = self.datawriter.write_with_options(message, write_options);
+        self.counters.count_write_sampled(&result, measured_size);
+        let sample_identity = result?;
+        self.record_sequence_number(sample_identity.sequence_number);
+        Ok(sample_identity.sequence_number)
+    }
+
+    // Serialized size of `message`, if it is time to measure one, or if there
+    // is a size limit. DDS serializes again when writing, so without a limit
+    // this is done only now and then.
+    fn measure_size(&self, message: &M) -> Option<usize> {
+        if self.max_message_size.is_some() || self.counters.size_sample_due() {
+            SA::to_bytes(message).ok().map(|bytes| bytes.len())
+        } else {
+            None
+        }
+    }
+
+    // Refuse messages over the size limit, before DDS fragments them.
+    fn check_size(&self, message: M, measured_size: Option<usize>) -> WriteResult<M, M> {
+        match (self.max_message_size, measured_size) {
+            (Some(limit), Some(size)) if size > limit => {
+                let result = Err(WriteError::Serialization {
+                    reason: format!(
+                        "Message of {size} bytes on Topic {} exceeds max_message_size of {limit} \
+                         bytes set in ContextOptions. Send it in smaller parts, or raise the \
+                         limit.",
+                        self.topic_name()
+                    ),
+                    data: message,
+                });
+                self.counters.count_write(&result, 0);
+                result
+            }
+            _ => Ok(message),
+        }
     }
 
     // pub(crate) fn publish_with_options(
@@ -44,6 +226,25 @@ impl<M: Serialize> Publisher<M> {
     //   self.datawriter.write_with_options(message, wo)
     // }
 
+    /// Asserts that this Publisher is alive, without publishing.
+    ///
+    /// This is needed with
+    /// [`Liveliness::ManualByTopic`](rustdds::policy::Liveliness::ManualByTopic)
+    /// QoS, where the Publisher must publish or call this at least once per
+    /// lease duration. Otherwise, matched Subscriptions consider it not alive.
+    /// With other Liveliness kinds this only refreshes the manual liveliness
+    /// timestamp. For
+    /// [`Liveliness::ManualByParticipant`](rustdds::policy::Liveliness::ManualByParticipant),
+    /// see [`Node::assert_liveliness`].
+    ///
+    /// Liveliness is enforced by the subscribing side, e.g. a ROS 2 node using
+    /// another DDS implementation with a Liveliness watchdog. Note that RustDDS
+    /// Subscriptions only notice a lost Publisher when the lease of its
+    /// whole DomainParticipant expires, after which
+    /// [`Subscription::get_publisher_count`] drops.
+    ///
+    /// An `Err` result means that the assertion could not be sent, because
+    /// Discovery is too busy. Try again later.
     pub fn assert_liveliness(&self) -> WriteResult<(), ()> {
         self.datawriter.assert_liveliness()
     }
@@ -52,10 +253,35 @@ impl<M: Serialize> Publisher<M> {
         self.datawriter.guid()
     }
 
+    /// Fully qualified ROS 2 Topic name, after remapping, e.g. `/chatter`
+    pub fn topic_name(&self) -> String {
+        ros_name_of_dds_topic(&self.dds_topic_name())
+    }
+
+    /// Name of the underlying DDS Topic, e.g. `rt/chatter`
+    pub fn dds_topic_name(&self) -> String {
+        self.datawriter.topic().name()
+    }
+
     pub fn gid(&self) -> Gid {
         self.guid().into()
     }
 
+    /// QoS policies in effect for this Publisher.
+    pub fn qos(&self) -> QosPolicies {
+        self.datawriter.qos()
+    }
+
+    /// Counts of messages published so far.
+    pub fn status_counters(&self) -> StatusCounters {
+        self.counters.snapshot()
+    }
+
+    /// Current publishing rate, measured over windows of at least one second
+    pub fn bandwidth(&self) -> Bandwidth {
+        self.counters.bandwidth()
+    }
+
     /// Returns the count of currently matched subscribers.
     ///
     /// `my_node` must be the Node that created this Publisher, or the result is
@@ -73,10 +299,111 @@ impl<M: Serialize> Publisher<M> {
         my_node.wait_for_reader(self.guid())
     }
 
+    #[cfg(not(feature = "fault-injection"))]
     pub async fn async_publish(&self, message: M) -> WriteResult<(), M> {
-        self.datawriter
-            .async_write(message, Some(Timestamp::now()))
-            .await
+        self.async_write_one(message).await
+    }
+
+    /// With fault injection, the result is the first error of the messages
+    /// written now.
+    #[cfg(feature = "fault-injection")]
+    pub async fn async_publish(&self, message: M) -> WriteResult<(), M> {
+        let mut result = Ok(());
+        for message in self.fault_batch(message) {
+            let written = self.async_write_one(message).await;
+            if result.is_ok() {
+                result = written;
+            }
+        }
+        result
+    }
+
+    async fn async_write_one(&self, message: M) -> WriteResult<(), M> {
+        let write_options = WriteOptionsBuilder::new()
+            .source_timestamp(Timestamp::now())
+            .build();
+        let measured_size = self.measure_size(&message);
+        let message = self.check_size(message, measured_size)?;
+        let result = self
+            .datawriter
+            .async_write_with_options(message, write_options)
+            .await;
+        self.counters.count_write_sampled(&result, measured_size);
+        let sample_identity = result?;
+        self.record_sequence_number(sample_identity.sequence_number);
+        Ok(())
+    }
+
+    // Messages to write now, instead of `message`
+    #[cfg(feature = "fault-injection")]
+    fn fault_batch(&self, message: M) -> Vec<M> {
+        match self.faults.lock().unwrap().as_mut() {
+            Some(injector) => injector.admit(message, std::time::Instant::now()),
+            None => vec![message],
+        }
+    }
+
+    /// Start injecting faults into the published messages, replacing any
+    /// previous fault injection. See [`super::fault_injection`].
+    ///
+    /// Messages held back by the previous fault injection are kept, and
+    /// released as the new one decides.
+    ///
+    /// This requires feature `fault-injection`.
+    #[cfg(feature = "fault-injection")]
+    pub fn set_fault_injection(&self, faults: FaultInjection) {
+        let mut injector = FaultInjector::new(faults);
+        let mut current = self.faults.lock().unwrap();
+        if let Some(old) = current.as_mut() {
+            injector.hold_from(old);
+        }
+        *current = Some(injector);
+    }
+
+    /// Stop injecting faults, and write all messages held back.
+    ///
+    /// This requires feature `fault-injection`.
+    #[cfg(feature = "fault-injection")]
+    pub fn clear_fault_injection(&self) -> Vec<WriteResult<SequenceNumber, M>> {
+        let held = match self.faults.lock().unwrap().take() {
+            Some(mut injector) => injector.release_all(),
+            None => Vec::new(),
+        };
+        self.write_released(held)
+    }
+
+    /// Write the delayed messages whose delay has passed.
+    ///
+    /// This requires feature `fault-injection`.
+    #[cfg(feature = "fault-injection")]
+    pub fn release_delayed(&self) -> Vec<WriteResult<SequenceNumber, M>> {
+        let due = match self.faults.lock().unwrap().as_mut() {
+            Some(injector) => injector.release_due(std::time::Instant::now()),
+            None => Vec::new(),
+        };
+        self.write_released(due)
+    }
+
+    #[cfg(feature = "fault-injection")]
+    fn write_released(&self, messages: Vec<M>) -> Vec<WriteResult<SequenceNumber, M>> {
+        messages
+            .into_iter()
+            .map(|message| self.write_one(message, Timestamp::now()))
+            .collect()
+    }
+
+    /// Counts of faults injected since the latest
+    /// [`set_fault_injection`](Self::set_fault_injection)
+    ///
+    /// This requires feature `fault-injection`.
+    #[cfg(feature = "fault-injection")]
+    pub fn fault_counters(&self) -> FaultCounters {
+        self.faults
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(FaultInjector::counters)
+            .unwrap_or_default()
     }
 
     #[allow(dead_code)] // This is for async Service implementation. Remove this when it is implemented.
@@ -98,29 +425,156 @@ impl<M: Serialize> Publisher<M> {
 ///
 /// Corresponds to a (simplified) [`DataReader`](rustdds::no_key::DataReader) in
 /// DDS
-pub struct Subscription<M> {
-    datareader: no_key::SimpleDataReaderCdr<M>,
+///
+/// If the Subscription has [Exclusive
+/// Ownership](rustdds::policy::Ownership::Exclusive) QoS, then only messages
+/// from the matched Publisher with the highest ownership strength are
+/// delivered. This requires that the creating Node has a
+/// [`Spinner`](super::Spinner) running.
+///
+/// The deserializer adapter `DA` is CDR by default. Another adapter can be
+/// given in [`Node::create_subscription_with_adapter`].
+///
+/// Messages can be inspected without removing them with [`Self::read`] and
+/// [`Self::read_all`]. These move the received messages into a read buffer
+/// inside the Subscription, from where `take` and the async variants return
+/// them first.
+pub struct Subscription<M, DA: DeserializerAdapter<M> = CDRDeserializerAdapter<M>> {
+    datareader: no_key::SimpleDataReader<M, DA>,
+    dds_topic_name: String, // SimpleDataReader does not give access to its Topic
+    ownership_filter: Option<OwnershipFilter>,
+    duplicate_filter: Option<DuplicateFilter<M>>,
+    counters: Arc<Counters>,
+    history_monitor: Option<HistoryMonitor>, // only for KeepLast History
+    // Messages already taken from DDS by `read`, but not yet by the application
+    read_buffer: Mutex<VecDeque<(M, MessageInfo)>>,
+    read_buffer_limit: Option<usize>, // from History QoS, None = unlimited
+    // Copy of the latest delivered message. Kept only after `latest` has been
+    // called, because only then we know that `M` is `Clone`.
+    latest: Mutex<Option<(M, MessageInfo)>>,
+    latest_cloner: OnceLock<fn(&M) -> M>,
+    pause: Mutex<PauseState>,
+    // Only held to unregister from the Node on drop
+    _registration: Option<EndpointRegistration>,
+}
+
+/// What a paused [`Subscription`] does with incoming messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseMode {
+    /// Messages are received and dropped. Reliable Publishers are not
+    /// affected.
+    Discard,
+    /// Messages are left in the DDS cache, subject to the History QoS policy,
+    /// and delivered after resuming. With Reliable `KeepAll` History, a full
+    /// cache eventually blocks the Publishers.
+    Hold,
+}
+
+type KeyFunc<M> = Box<dyn Fn(&M) -> u64 + Send + Sync>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum DuplicateKey {
+    // Compared exactly
+    Sample(GUID, SequenceNumber),
+    // Hash of a user-defined key
+    Hashed(u64),
+}
+
+// Drops messages whose key was seen within the window
+struct DuplicateFilter<M> {
+    window: Duration,
+    // `None` means writer GUID and sequence number
+    key: Option<KeyFunc<M>>,
+    seen: Mutex<SeenKeys>,
+}
+
+#[derive(Default)]
+struct SeenKeys {
+    // When each key was seen last
+    last_seen: BTreeMap<DuplicateKey, SteadyTime>,
+    // Keys in the order they were seen, for expiring them
+    expiry: VecDeque<(SteadyTime, DuplicateKey)>,
+}
+
+impl<M> DuplicateFilter<M> {
+    fn new(window: Duration, key: Option<KeyFunc<M>>) -> Self {
+        DuplicateFilter {
+            window,
+            key,
+            seen: Mutex::new(SeenKeys::default()),
+        }
+    }
+
+    fn key_of(&self, sample: &M, writer: GUID, sequence_number: SequenceNumber) -> DuplicateKey {
+        match &self.key {
+            Some(key) => DuplicateKey::Hashed(key(sample)),
+            None => DuplicateKey::Sample(writer, sequence_number),
+        }
+    }
+
+    // Returns true if `key` was already seen within the window.
+    fn is_duplicate(&self, key: DuplicateKey, now: SteadyTime) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        while let Some(&(received, expired)) = seen.expiry.front() {
+            if (now - received).as_saturating_duration() <= self.window {
+                break;
+            }
+            seen.expiry.pop_front();
+            // The key may have been seen again since.
+            if seen.last_seen.get(&expired) == Some(&received) {
+                seen.last_seen.remove(&expired);
+            }
+        }
+        seen.expiry.push_back((now, key));
+        seen.last_seen.insert(key, now).is_some()
+    }
+}
+
+fn hash_key<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Default)]
+struct PauseState {
+    mode: Option<PauseMode>,
+    // async consumers waiting for resume
+    wakers: Vec<task::Waker>,
 }
 
 impl<M> Subscription<M>
 where
     M: 'static,
 {
-    // These must be created from Node
-    pub(crate) fn new(datareader: no_key::SimpleDataReaderCdr<M>) -> Subscription<M> {
-        Subscription { datareader }
-    }
-
     pub fn take_seed<'de, S>(&self, seed: S) -> ReadResult<Option<(M, MessageInfo)>>
     where
         S: serde::de::DeserializeSeed<'de, Value = M> + Clone,
         M: 'static,
     {
+        let paused = self.pause_mode();
+        match paused {
+            Some(PauseMode::Hold) => return Ok(None),
+            Some(PauseMode::Discard) => {}
+            None => {
+                if let Some(buffered) = self.read_buffer.lock().unwrap().pop_front() {
+                    return Ok(Some(buffered));
+                }
+            }
+        }
         self.datareader.drain_read_notifications();
-        let decoder = CdrDeserializeSeedDecoder::new(seed, PhantomData::<()>);
-        let ds: Option<no_key::DeserializedCacheChange<M>> =
-            self.datareader.try_take_one_with(decoder)?;
-        Ok(ds.map(dcc_to_value_and_messageinfo))
+        loop {
+            let decoder = CdrDeserializeSeedDecoder::new(seed.clone(), PhantomData::<()>);
+            let ds: Option<no_key::DeserializedCacheChange<M>> = self
+                .datareader
+                .try_take_one_with(CountingDecoder::new(decoder, &self.counters))
+                .inspect_err(|_| self.counters.count_read_error())?;
+            match ds {
+                Some(dcc) if !self.deliver(&dcc) => continue,
+                Some(_) if paused.is_some() => continue, // discard
+                ds => return Ok(ds.map(dcc_to_value_and_messageinfo)),
+            }
+        }
     }
 
     // Returns an async Stream of messages with MessageInfo metadata
@@ -133,25 +587,145 @@ where
         M: 'static,
     {
         let decoder = CdrDeserializeSeedDecoder::new(seed, PhantomData::<()>);
-        self.datareader
-            .as_async_stream_with(decoder)
-            .map(|result| result.map(dcc_to_value_and_messageinfo))
+        self.with_read_buffer(
+            self.datareader
+                .as_async_stream_with(CountingDecoder::new(decoder, &self.counters))
+                .filter(|result| future::ready(self.accepts_result(result)))
+                .map(|result| result.map(dcc_to_value_and_messageinfo)),
+        )
     }
 }
 
 impl<M: 'static + DeserializeOwned> Subscription<M> {
+    /// Takes the next message, but returns it in serialized form.
+    ///
+    /// The bytes are the CDR payload as received, after a 4-byte encapsulation
+    /// header, i.e. the format used by rosbag2. This is useful for archiving
+    /// or auditing. The header is rebuilt from the representation identifier,
+    /// with the options set to zero, because DDS does not pass on the
+    /// original options.
+    ///
+    /// The message is still deserialized, so that messages that
+    /// [`take`](Self::take) would reject are also rejected here.
+    ///
+    /// Messages already moved to the read buffer by [`read`](Self::read) are
+    /// no longer available in serialized form, and are not returned here.
+    pub fn take_serialized(&self) -> ReadResult<Option<(Vec<u8>, MessageInfo)>> {
+        let paused = self.pause_mode();
+        if paused == Some(PauseMode::Hold) {
+            return Ok(None);
+        }
+        self.datareader.drain_read_notifications();
+        loop {
+            let decoder = CapturingDecoder::new();
+            let ds: Option<no_key::DeserializedCacheChange<M>> = self
+                .datareader
+                .try_take_one_with(CountingDecoder::new(decoder.clone(), &self.counters))
+                .inspect_err(|_| self.counters.count_read_error())?;
+            match ds {
+                Some(dcc) if !self.deliver(&dcc) => continue,
+                Some(_) if paused.is_some() => continue, // discard
+                Some(dcc) => {
+                    let serialized = decoder.captured.take().unwrap_or_default();
+                    return Ok(Some((serialized, MessageInfo::from(&dcc))));
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<M, DA> Subscription<M, DA>
+where
+    M: 'static,
+    DA: DefaultDecoder<M> + 'static,
+{
     pub fn take(&self) -> ReadResult<Option<(M, MessageInfo)>> {
+        if self.pause_mode().is_none() {
+            if let Some(buffered) = self.read_buffer.lock().unwrap().pop_front() {
+                return Ok(Some(buffered));
+            }
+        }
+        self.take_from_dds()
+    }
+
+    /// Returns the next message that [`Self::take`] would return, but leaves
+    /// it in the Subscription.
+    pub fn read(&self) -> ReadResult<Option<(M, MessageInfo)>>
+    where
+        M: Clone,
+    {
+        Ok(self.fill_read_buffer()?.front().cloned())
+    }
+
+    /// Returns all messages currently received, oldest first, but leaves
+    /// them in the Subscription.
+    ///
+    /// The number of messages kept is limited by the History QoS policy, as
+    /// in DDS: With `KeepLast`, older messages are dropped when more arrive.
+    pub fn read_all(&self) -> ReadResult<Vec<(M, MessageInfo)>>
+    where
+        M: Clone,
+    {
+        Ok(self.fill_read_buffer()?.iter().cloned().collect())
+    }
+
+    /// Returns the most recently received message, whether or not it has
+    /// already been taken.
+    ///
+    /// This does not remove anything, so other consumers of this Subscription
+    /// still receive all messages. Messages are tracked from the first call
+    /// onwards: Messages taken before that are not seen.
+    pub fn latest(&self) -> ReadResult<Option<(M, MessageInfo)>>
+    where
+        M: Clone,
+    {
+        self.latest_cloner.get_or_init(|| M::clone);
+        let buffer = self.fill_read_buffer()?;
+        let latest = self.latest.lock().unwrap();
+        Ok(latest.clone().or_else(|| buffer.back().cloned()))
+    }
+
+    // Move everything available from DDS to the read buffer.
+    fn fill_read_buffer(&self) -> ReadResult<MutexGuard<'_, VecDeque<(M, MessageInfo)>>> {
+        let mut buffer = self.read_buffer.lock().unwrap();
+        while let Some(message) = self.take_from_dds()? {
+            buffer.push_back(message);
+            if self
+                .read_buffer_limit
+                .is_some_and(|limit| buffer.len() > limit)
+            {
+                buffer.pop_front();
+            }
+        }
+        Ok(buffer)
+    }
+
+    // Returns nothing while paused.
+    fn take_from_dds(&self) -> ReadResult<Option<(M, MessageInfo)>> {
+        let paused = self.pause_mode();
+        if paused == Some(PauseMode::Hold) {
+            return Ok(None);
+        }
         self.datareader.drain_read_notifications();
-        let ds: Option<no_key::DeserializedCacheChange<M>> = self.datareader.try_take_one()?;
-        Ok(ds.map(dcc_to_value_and_messageinfo))
+        loop {
+            let ds: Option<no_key::DeserializedCacheChange<M>> = self
+                .datareader
+                .try_take_one_with(CountingDecoder::new(DA::DECODER, &self.counters))
+                .inspect_err(|_| self.counters.count_read_error())?;
+            match ds {
+                Some(dcc) if !self.deliver(&dcc) => continue,
+                Some(_) if paused.is_some() => continue, // discard
+                ds => return Ok(ds.map(dcc_to_value_and_messageinfo)),
+            }
+        }
     }
 
     pub async fn async_take(&self) -> ReadResult<(M, MessageInfo)> {
-        let async_stream = self.datareader.as_async_stream();
+        let async_stream = self.async_stream();
         pin_mut!(async_stream);
         match async_stream.next().await {
-            Some(Err(e)) => Err(e),
-            Some(Ok(ds)) => Ok(dcc_to_value_and_messageinfo(ds)),
+            Some(result) => result,
             // Stream from SimpleDataReader is not supposed to ever end.
             None => {
                 read_error_internal!(
@@ -163,24 +737,269 @@ impl<M: 'static + DeserializeOwned> Subscription<M> {
 
     /// Returns an async Stream of messages with MessageInfo metadata
     pub fn async_stream(&self) -> impl FusedStream<Item = ReadResult<(M, MessageInfo)>> + '_ {
-        self.datareader
-            .as_async_stream()
-            .map(|result| result.map(dcc_to_value_and_messageinfo))
+        self.with_read_buffer(
+            self.datareader
+                .as_async_stream_with(CountingDecoder::new(DA::DECODER, &self.counters))
+                .filter(|result| future::ready(self.accepts_result(result)))
+                .map(|result| result.map(dcc_to_value_and_messageinfo)),
+        )
+    }
+
+    /// Turns this Subscription into an async Stream of messages with
+    /// MessageInfo metadata.
+    ///
+    /// Unlike [`Self::async_stream`], the Stream owns the Subscription, so it
+    /// is `'static` and can be moved into a spawned task. Dropping the Stream
+    /// drops the Subscription.
+    pub fn into_stream(self) -> impl FusedStream<Item = ReadResult<(M, MessageInfo)>> + 'static {
+        stream::unfold(self, |subscription| async move {
+            let result = subscription.async_take().await;
+            Some((result, subscription))
+        })
+        .fuse()
     }
 }
 
-impl<M> Subscription<M>
+impl<M, DA> Subscription<M, DA>
 where
     M: 'static,
+    DA: DeserializerAdapter<M> + 'static,
 {
+    // These must be created from Node
+    pub(crate) fn new(
+        datareader: no_key::SimpleDataReader<M, DA>,
+        dds_topic_name: String,
+    ) -> Subscription<M, DA> {
+        let history_monitor =
+            HistoryMonitor::new(ros_name_of_dds_topic(&dds_topic_name), datareader.qos());
+        let read_buffer_limit = match datareader.qos().history() {
+            Some(History::KeepLast { depth }) => Some(depth.max(1) as usize),
+            _ => datareader
+                .qos()
+                .resource_limits()
+                .and_then(|rl| usize::try_from(rl.max_samples_per_instance).ok()),
+        };
+        Subscription {
+            datareader,
+            dds_topic_name,
+            ownership_filter: None,
+            duplicate_filter: None,
+            counters: Arc::default(),
+            history_monitor,
+            read_buffer: Mutex::new(VecDeque::new()),
+            read_buffer_limit,
+            latest: Mutex::new(None),
+            latest_cloner: OnceLock::new(),
+            pause: Mutex::new(PauseState::default()),
+            _registration: None,
+        }
+    }
+
+    // Messages in the read buffer come first, then those from `stream`.
+    fn with_read_buffer<'a>(
+        &'a self,
+        stream: impl Stream<Item = ReadResult<(M, MessageInfo)>> + 'a,
+    ) -> impl FusedStream<Item = ReadResult<(M, MessageInfo)>> + 'a {
+        let mut stream = Box::pin(stream);
+        stream::poll_fn(move |cx| {
+            let paused = {
+                let mut pause = self.pause.lock().unwrap();
+                if pause.mode.is_some() && !pause.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                    pause.wakers.push(cx.waker().clone());
+                }
+                pause.mode
+            };
+            match paused {
+                Some(PauseMode::Hold) => task::Poll::Pending,
+                Some(PauseMode::Discard) => loop {
+                    match stream.as_mut().poll_next(cx) {
+                        task::Poll::Ready(Some(Ok(_))) => continue,
+                        other => return other,
+                    }
+                },
+                None => {
+                    let buffered = self.read_buffer.lock().unwrap().pop_front();
+                    match buffered {
+                        Some(buffered) => task::Poll::Ready(Some(Ok(buffered))),
+                        None => stream.as_mut().poll_next(cx),
+                    }
+                }
+            }
+        })
+        .fuse()
+    }
+
+    pub(crate) fn set_ownership_filter(&mut self, ownership_filter: OwnershipFilter) {
+        self.ownership_filter = Some(ownership_filter);
+    }
+
+    /// Discard messages with the same writer and sequence number as one
+    /// delivered within the last `window`. `None` turns deduplication off,
+    /// which is the default.
+    ///
+    /// This drops copies of the same sample that arrive more than once, e.g.
+    /// over redundant network paths. Copies sent by redundant Publishers are
+    /// different samples, see [`Self::set_deduplication_key`].
+    pub fn set_deduplication(&mut self, window: Option<Duration>) {
+        self.duplicate_filter = window.map(|window| DuplicateFilter::new(window, None));
+    }
+
+    /// Discard messages with the same `key` as one delivered within the last
+    /// `window`.
+    ///
+    /// This is for redundant Publishers, e.g. over dual radios, that publish
+    /// the same data, so that both copies arrive. The key should identify the
+    /// data, e.g. the timestamp and frame of a [`Header`](crate::interfaces::std_msgs::Header).
+    ///
+    /// Keys are compared by their 64-bit hash. Different keys with the same
+    /// hash are taken as duplicates, so a distinct message is dropped in the
+    /// rare case of a hash collision.
+    pub fn set_deduplication_key<K, F>(&mut self, window: Duration, key: F)
+    where
+        K: Hash,
+        F: Fn(&M) -> K + Send + Sync + 'static,
+    {
+        self.duplicate_filter = Some(DuplicateFilter::new(
+            window,
+            Some(Box::new(move |message| hash_key(&key(message)))),
+        ));
+    }
+
+    pub(crate) fn set_registration(&mut self, registration: EndpointRegistration) {
+        registration.attach_counters(Arc::clone(&self.counters));
+        self._registration = Some(registration);
+    }
+
+    /// Remove this Subscription from DDS and from its Node.
+    ///
+    /// This is the same as dropping the Subscription, but states the intent.
+    /// Messages not yet taken are lost.
+    pub fn close(self) {}
+
+    // Should a sample from this writer be delivered?
+    fn accepts(&self, writer: GUID) -> bool {
+        self.ownership_filter
+            .as_ref()
+            .is_none_or(|f| f.is_owner(self.datareader.guid(), writer))
+    }
+
+    // Like `accepts`, but also counts the sample as taken or rejected.
+    fn deliver(&self, dcc: &no_key::DeserializedCacheChange<M>) -> bool {
+        if let Some(monitor) = &self.history_monitor {
+            monitor.observe(dcc.writer_guid(), dcc.sequence_number);
+        }
+        let accepted = self.accepts(dcc.writer_guid()) && !self.is_duplicate(dcc);
+        if accepted {
+            self.counters.count_taken();
+            if let Some(clone) = self.latest_cloner.get() {
+                *self.latest.lock().unwrap() = Some((clone(&dcc.sample), MessageInfo::from(dcc)));
+            }
+        } else {
+            self.counters.count_rejected();
+        }
+        accepted
+    }
+
+    fn is_duplicate(&self, dcc: &no_key::DeserializedCacheChange<M>) -> bool {
+        self.duplicate_filter.as_ref().is_some_and(|filter| {
+            let key = filter.key_of(&dcc.sample, dcc.writer_guid(), dcc.sequence_number);
+            filter.is_duplicate(key, SteadyTime::now())
+        })
+    }
+
+    fn accepts_result(&self, result: &ReadResult<no_key::DeserializedCacheChange<M>>) -> bool {
+        match result {
+            Ok(dcc) => self.deliver(dcc),
+            Err(_) => {
+                self.counters.count_read_error();
+                true // errors are always passed through
+            }
+        }
+    }
+
+    /// Stop delivering messages, until [`Self::resume`].
+    ///
+    /// The DDS endpoint stays as it is, so matching and Discovery are not
+    /// affected. While paused, `take` and similar return nothing, and async
+    /// takes and streams wait. `mode` decides what happens to messages that
+    /// arrive meanwhile. With [`PauseMode::Discard`], messages already in the
+    /// read buffer are dropped too.
+    ///
+    /// Note that a paused Subscription may still appear ready when polled via
+    /// `mio`.
+    pub fn pause(&self, mode: PauseMode) {
+        self.pause.lock().unwrap().mode = Some(mode);
+        if mode == PauseMode::Discard {
+            self.read_buffer.lock().unwrap().clear();
+        }
+    }
+
+    /// Resume delivering messages after [`Self::pause`].
+    pub fn resume(&self) {
+        let wakers = {
+            let mut pause = self.pause.lock().unwrap();
+            pause.mode = None;
+            std::mem::take(&mut pause.wakers)
+        };
+        wakers.into_iter().for_each(task::Waker::wake);
+    }
+
+    /// The current pause mode, or `None` if not paused.
+    pub fn pause_mode(&self) -> Option<PauseMode> {
+        self.pause.lock().unwrap().mode
+    }
+
     pub fn guid(&self) -> rustdds::GUID {
         self.datareader.guid()
     }
 
+    /// Fully qualified ROS 2 Topic name, after remapping, e.g. `/chatter`
+    pub fn topic_name(&self) -> String {
+        ros_name_of_dds_topic(&self.dds_topic_name)
+    }
+
+    /// Name of the underlying DDS Topic, e.g. `rt/chatter`
+    pub fn dds_topic_name(&self) -> &str {
+        &self.dds_topic_name
+    }
+
     pub fn gid(&self) -> Gid {
         self.guid().into()
     }
 
+    /// QoS policies in effect for this Subscription.
+    pub fn qos(&self) -> &QosPolicies {
+        self.datareader.qos()
+    }
+
+    /// Counts of messages received so far.
+    pub fn status_counters(&self) -> StatusCounters {
+        self.counters.snapshot()
+    }
+
+    /// Current receiving rate, measured over windows of at least one second
+    pub fn bandwidth(&self) -> Bandwidth {
+        self.counters.bandwidth()
+    }
+
+    /// Representation identifiers of the messages received so far, with the
+    /// number of messages in each, in the order they were first seen.
+    ///
+    /// Use [`Encoding::of`](crate::message::encoding::Encoding::of) to find
+    /// out the byte order and CDR version. Messages in unsupported encodings
+    /// are rejected by DDS before decoding, so they are not counted here.
+    pub fn received_encodings(&self) -> Vec<(RepresentationIdentifier, u64)> {
+        self.counters.encodings()
+    }
+
+    /// Latest warning that unread messages are being overwritten, because
+    /// the History depth is too small. See [`super::history_monitor`].
+    ///
+    /// Always `None` if History is not `KeepLast`.
+    pub fn history_depth_warning(&self) -> Option<HistoryDepthWarning> {
+        self.history_monitor.as_ref().and_then(|m| m.warning())
+    }
+
     /// Returns the count of currently matched Publishers.
     ///
     /// `my_node` must be the Node that created this Subscription, or the result
@@ -199,6 +1018,92 @@ where
     }
 }
 
+/// Arbitrates Exclusive Ownership among the Publishers matched to a
+/// Subscription.
+///
+/// DDS rule: The owner is the alive matched writer with the highest
+/// ownership strength. Ties are broken by choosing the writer with the
+/// lowest GUID.
+#[derive(Clone)]
+pub(crate) struct OwnershipFilter {
+    readers_to_remote_writers: Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>>,
+    writer_ownership_strengths: Arc<Mutex<BTreeMap<GUID, i32>>>,
+}
+
+impl OwnershipFilter {
+    pub(crate) fn new(
+        readers_to_remote_writers: Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>>,
+        writer_ownership_strengths: Arc<Mutex<BTreeMap<GUID, i32>>>,
+    ) -> Self {
+        OwnershipFilter {
+            readers_to_remote_writers,
+            writer_ownership_strengths,
+        }
+    }
+
+    fn is_owner(&self, reader: GUID, writer: GUID) -> bool {
+        let strengths = self.writer_ownership_strengths.lock().unwrap();
+        // Writers with no known strength are treated as strength zero.
+        let strength_of = |w: &GUID| strengths.get(w).copied().unwrap_or(0);
+        let writer_strength = strength_of(&writer);
+        // The sender is included in the candidates, even if we have not
+        // yet seen it matched.
+        self.readers_to_remote_writers
+            .lock()
+            .unwrap()
+            .get(&reader)
+            .into_iter()
+            .flatten()
+            .all(|w| {
+                let s = strength_of(w);
+                s < writer_strength || (s == writer_strength && *w >= writer)
+            })
+    }
+}
+
+// Decodes CDR as usual, but also stores a copy of the serialized message.
+struct CapturingDecoder<M> {
+    captured: Rc<RefCell<Option<Vec<u8>>>>,
+    phantom: PhantomData<M>,
+}
+
+impl<M> CapturingDecoder<M> {
+    fn new() -> Self {
+        CapturingDecoder {
+            captured: Rc::new(RefCell::new(None)),
+            phantom: PhantomData,
+        }
+    }
+}
+
+// derive(Clone) would require M: Clone
+impl<M> Clone for CapturingDecoder<M> {
+    fn clone(&self) -> Self {
+        CapturingDecoder {
+            captured: Rc::clone(&self.captured),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<M: DeserializeOwned> no_key::Decode<M> for CapturingDecoder<M> {
+    type Error = rustdds::serialization::Error;
+
+    fn decode_bytes(
+        self,
+        input_bytes: &[u8],
+        encoding: RepresentationIdentifier,
+    ) -> Result<M, Self::Error> {
+        let mut serialized = Vec::with_capacity(4 + input_bytes.len());
+        serialized.extend_from_slice(&encoding.to_bytes());
+        // The original encapsulation options are not available here.
+        serialized.extend_from_slice(&[0, 0]);
+        serialized.extend_from_slice(input_bytes);
+        *self.captured.borrow_mut() = Some(serialized);
+        deserialize_from_cdr_with_rep_id(input_bytes, encoding).map(|(value, _)| value)
+    }
+}
+
 // helper
 #[inline]
 fn dcc_to_value_and_messageinfo<M>(dcc: no_key::DeserializedCacheChange<M>) -> (M, MessageInfo) {
@@ -206,9 +1111,10 @@ fn dcc_to_value_and_messageinfo<M>(dcc: no_key::DeserializedCacheChange<M>) -> (
     (dcc.into_value(), mi)
 }
 
-impl<D> Evented for Subscription<D>
+impl<D, DA> Evented for Subscription<D, DA>
 where
-    D: DeserializeOwned,
+    D: 'static,
+    DA: DeserializerAdapter<D> + 'static,
 {
     // We just delegate all the operations to datareader, since it
     // already implements Evented
@@ -236,3 +1142,167 @@ where
         self.datareader.deregister(poll)
     }
 }
+
+#[cfg(feature = "no-default-runtime")]
+impl<M, DA> Readiness for Subscription<M, DA>
+where
+    M: 'static,
+    DA: DeserializerAdapter<M> + 'static,
+{
+    fn register_waker(&self, waker: &task::Waker) {
+        let paused = {
+            let mut pause = self.pause.lock().unwrap();
+            if pause.mode.is_some() && !pause.wakers.iter().any(|w| w.will_wake(waker)) {
+                pause.wakers.push(waker.clone());
+            }
+            pause.mode.is_some()
+        };
+        self.datareader.set_waker(Some(waker.clone()));
+        // Messages moved to the read buffer by `read` do not wake anyone.
+        if !paused && !self.read_buffer.lock().unwrap().is_empty() {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl<D, DA> mio_08::event::Source for Subscription<D, DA>
+where
+    D: 'static,
+    DA: DeserializerAdapter<D> + 'static,
+{
+    fn register(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::register(&mut self.datareader, registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::reregister(&mut self.datareader, registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio_08::Registry) -> io::Result<()> {
+        mio_08::event::Source::deregister(&mut self.datareader, registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interfaces::{builtin_interfaces::Time, std_msgs::Header},
+        node::{
+            context::{tests::test_node, Context, ContextOptions, DEFAULT_PUBLISHER_QOS},
+            NodeOptions,
+        },
+        prelude::{
+            dds::{DdsDuration, LatencyBudget, QosPolicyBuilder},
+            MessageTypeName, Name,
+        },
+        time::steady_time::TimeDiff,
+    };
+
+    #[test]
+    fn duplicates_within_window() {
+        let filter = DuplicateFilter::<u32>::new(Duration::from_millis(100), None);
+        let writer = GUID::from_bytes([1; 16]);
+        let t0 = SteadyTime::now();
+
+        let first = filter.key_of(&1, writer, SequenceNumber::from(1));
+        let second = filter.key_of(&1, writer, SequenceNumber::from(2));
+        assert_ne!(first, second);
+        assert!(!filter.is_duplicate(first, t0));
+        assert!(filter.is_duplicate(first, t0 + TimeDiff::from_millis(50)));
+        assert!(!filter.is_duplicate(second, t0 + TimeDiff::from_millis(50)));
+        // Seen last at 50 ms, so forgotten at 200 ms.
+        assert!(!filter.is_duplicate(first, t0 + TimeDiff::from_millis(200)));
+        let seen = filter.seen.lock().unwrap();
+        assert_eq!(seen.last_seen.len(), 1);
+        assert_eq!(seen.expiry.len(), 1);
+    }
+
+    #[test]
+    fn duplicates_by_key() {
+        let filter = DuplicateFilter::<(u32, &str)>::new(
+            Duration::from_secs(1),
+            Some(Box::new(|m: &(u32, &str)| hash_key(&m.0))),
+        );
+        let writer = GUID::from_bytes([1; 16]);
+        let t0 = SteadyTime::now();
+        let radio_a = filter.key_of(&(7, "a"), writer, SequenceNumber::from(1));
+        let radio_b = filter.key_of(&(7, "b"), writer, SequenceNumber::from(5));
+        assert!(!filter.is_duplicate(radio_a, t0));
+        assert!(filter.is_duplicate(radio_b, t0));
+    }
+
+    #[test]
+    fn latency_budget_reaches_endpoints() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_latency_budget_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let topic = node
+            .create_topic(
+                &Name::new("/", "cmd_vel_budget").unwrap(),
+                MessageTypeName::new("std_msgs", "String"),
+                &DEFAULT_PUBLISHER_QOS,
+            )
+            .unwrap();
+        let budget = LatencyBudget {
+            duration: DdsDuration::from_millis(5),
+        };
+        let qos = QosPolicyBuilder::new().latency_budget(budget).build();
+        let publisher = node
+            .create_publisher::<String>(&topic, Some(qos.clone()))
+            .unwrap();
+        let subscription = node
+            .create_subscription::<String>(&topic, Some(qos))
+            .unwrap();
+        assert_eq!(publisher.qos().latency_budget(), Some(budget));
+        assert_eq!(subscription.qos().latency_budget(), Some(budget));
+    }
+
+    #[test]
+    fn max_message_size() {
+        let context = Context::with_options(ContextOptions::new().max_message_size(16)).unwrap();
+        let limits = context.transport_limits();
+        assert_eq!(limits.max_message_size, Some(16));
+        assert_eq!(limits.fragment_count(0), 1);
+        assert_eq!(limits.fragment_count(1025), 2);
+
+        let mut node = test_node(
+            &context,
+            "test_max_size_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let publisher = node.advertise::<Header>("/max_size_test").unwrap();
+        let header = |frame_id: &str| Header {
+            stamp: Time::ZERO,
+            frame_id: frame_id.to_owned(),
+        };
+        assert_eq!(publisher.last_sequence_number(), None);
+        publisher.publish(header("a")).unwrap();
+        assert_eq!(
+            publisher.last_sequence_number(),
+            Some(SequenceNumber::from(1))
+        );
+        match publisher.publish(header("base_link")) {
+            Err(WriteError::Serialization { reason, data }) => {
+                assert!(reason.contains("22 bytes"), "{reason}");
+                assert_eq!(data.frame_id, "base_link");
+            }
+            other => panic!("expected size error, got {other:?}"),
+        }
+        assert_eq!(publisher.status_counters().write_errors, 1);
+    }
+}
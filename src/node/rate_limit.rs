@@ -0,0 +1,154 @@
+//! Publishing at a limited rate
+//!
+//! [`RateLimitedPublisher`] is meant for command Topics driven by a human,
+//! e.g. teleoperation or UI controls, where input events may arrive much
+//! faster than the receiver needs them. Publishing every event would only
+//! load the network.
+
+use std::{sync::Mutex, time::Duration};
+
+use rustdds::{dds::WriteResult, no_key::SerializerAdapter, CDRSerializerAdapter};
+
+use crate::time::steady_time::SteadyTime;
+
+use super::pubsub::Publisher;
+
+/// A [`Publisher`] wrapper that publishes at most once per `min_interval`.
+///
+/// A message published too soon after the previous one is not sent, but kept
+/// as pending. A newer message replaces the pending one, so that only the
+/// latest value is sent. The pending message is sent by
+/// [`Self::publish_pending`], which the application should call when
+/// [`Self::pending_due_in`] says it is due, or simply periodically.
+pub struct RateLimitedPublisher<M, SA: SerializerAdapter<M> = CDRSerializerAdapter<M>> {
+    publisher: Publisher<M, SA>,
+    state: Mutex<RateLimit<M>>,
+}
+
+impl<M, SA: SerializerAdapter<M>> RateLimitedPublisher<M, SA> {
+    pub fn new(publisher: Publisher<M, SA>, min_interval: Duration) -> Self {
+        RateLimitedPublisher {
+            publisher,
+            state: Mutex::new(RateLimit::new(min_interval)),
+        }
+    }
+
+    /// Limit publishing to at most `max_rate` messages per second.
+    pub fn with_max_rate(publisher: Publisher<M, SA>, max_rate: f64) -> Self {
+        Self::new(publisher, Duration::from_secs_f64(1.0 / max_rate))
+    }
+
+    /// Publish `message` now, if enough time has passed since the previous
+    /// one. Otherwise, make it the pending message.
+    ///
+    /// Returns `true` if the message was sent now.
+    pub fn publish(&self, message: M) -> WriteResult<bool, M> {
+        let mut state = self.state.lock().unwrap();
+        state.pending = Some(message);
+        self.send_if_due(&mut state)
+    }
+
+    /// Send the pending message, if there is one and it is due.
+    ///
+    /// Returns `true` if a message was sent.
+    pub fn publish_pending(&self) -> WriteResult<bool, M> {
+        let mut state = self.state.lock().unwrap();
+        self.send_if_due(&mut state)
+    }
+
+    /// Time until the pending message can be sent, or `None` if there is no
+    /// pending message. Zero means that it is due now.
+    pub fn pending_due_in(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        state
+            .pending
+            .as_ref()
+            .map(|_| state.due_in(SteadyTime::now()))
+    }
+
+    /// Drop the pending message, if any.
+    pub fn clear_pending(&self) {
+        self.state.lock().unwrap().pending = None;
+    }
+
+    pub fn min_interval(&self) -> Duration {
+        self.state.lock().unwrap().min_interval
+    }
+
+    /// The wrapped Publisher. Publishing with it bypasses the rate limit.
+    pub fn publisher(&self) -> &Publisher<M, SA> {
+        &self.publisher
+    }
+
+    fn send_if_due(&self, state: &mut RateLimit<M>) -> WriteResult<bool, M> {
+        let now = SteadyTime::now();
+        match state.take_if_due(now) {
+            Some(message) => {
+                self.publisher.publish(message)?;
+                state.last_sent = Some(now);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+// Timing state, separate from the Publisher
+struct RateLimit<M> {
+    min_interval: Duration,
+    last_sent: Option<SteadyTime>,
+    pending: Option<M>,
+}
+
+impl<M> RateLimit<M> {
+    fn new(min_interval: Duration) -> Self {
+        RateLimit {
+            min_interval,
+            last_sent: None,
+            pending: None,
+        }
+    }
+
+    fn due_in(&self, now: SteadyTime) -> Duration {
+        match self.last_sent {
+            None => Duration::ZERO,
+            Some(t) => self
+                .min_interval
+                .saturating_sub((now - t).as_saturating_duration()),
+        }
+    }
+
+    fn take_if_due(&mut self, now: SteadyTime) -> Option<M> {
+        if self.due_in(now).is_zero() {
+            self.pending.take()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::steady_time::TimeDiff;
+
+    #[test]
+    fn keeps_latest_until_due() {
+        let mut rl = RateLimit::new(Duration::from_millis(100));
+        let t0 = SteadyTime::now();
+
+        rl.pending = Some(1);
+        assert_eq!(rl.take_if_due(t0), Some(1));
+        rl.last_sent = Some(t0);
+
+        let t1 = t0 + TimeDiff::from_millis(30);
+        rl.pending = Some(2);
+        assert_eq!(rl.take_if_due(t1), None);
+        rl.pending = Some(3);
+        assert_eq!(rl.due_in(t1), Duration::from_millis(70));
+
+        let t2 = t0 + TimeDiff::from_millis(100);
+        assert_eq!(rl.take_if_due(t2), Some(3));
+        assert_eq!(rl.take_if_due(t2), None);
+    }
+}
@@ -0,0 +1,247 @@
+//! Name remapping
+//!
+//! Remapping rules change the names of Topics, Services and Actions that a
+//! [`Node`](crate::node::Node) creates, so that the same program can be wired
+//! differently without recompiling. The rules can be given on the command line
+//! as in other ROS 2 programs:
+//!
+//! ```text
+//! my_program --ros-args -r chatter:=/talk -r __ns:=/robot1 -r my_node:__node:=other_name
+//! ```
+//!
+//! or programmatically using [`NodeOptions::remap`](crate::node::NodeOptions::remap)
+//! or [`Node::add_remapping`](crate::node::Node::add_remapping).
+//!
+//! The special names `__node` (or `__name`) and `__ns` rename the Node and
+//! change its namespace.
+//!
+//! A rule may be limited to a single Node by prefixing it with the Node's
+//! (original) base name and a colon, e.g. `my_node:chatter:=/talk`.
+//!
+//! If several rules match, the first one is used. Rules given programmatically
+//! come first, then [`NodeOptions::arguments`](crate::node::NodeOptions::arguments),
+//! and then process command line arguments.
+
+use crate::interfaces::names::{Name, NodeName};
+
+/// A single remapping rule, e.g. `foo:=/bar`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemapRule {
+    node: Option<String>,
+    from: String,
+    to: String,
+}
+
+impl RemapRule {
+    /// A rule that renames `from` to `to` in all Nodes.
+    ///
+    /// The names may be relative, in which case they are interpreted in the
+    /// namespace of the Node. Prefix `~/` refers to the Node's private
+    /// namespace.
+    pub fn new(from: &str, to: &str) -> RemapRule {
+        RemapRule {
+            node: None,
+            from: from.to_owned(),
+            to: to.to_owned(),
+        }
+    }
+
+    /// Apply this rule only to the Node with the given base name.
+    pub fn for_node(self, node_base_name: &str) -> RemapRule {
+        RemapRule {
+            node: Some(node_base_name.to_owned()),
+            ..self
+        }
+    }
+
+    /// Parse a rule in the command line format `[node_name:]from:=to`.
+    pub fn parse(rule: &str) -> Result<RemapRule, String> {
+        let (lhs, to) = rule
+            .split_once(":=")
+            .ok_or_else(|| format!("Remapping rule {rule:?} does not contain \":=\""))?;
+        let (node, from) = match lhs.split_once(':') {
+            Some((node, from)) => (Some(node.to_owned()), from),
+            None => (None, lhs),
+        };
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("Remapping rule {rule:?} has an empty name"));
+        }
+        Ok(RemapRule {
+            node,
+            from: from.to_owned(),
+            to: to.to_owned(),
+        })
+    }
+
+    pub fn node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    fn applies_to(&self, node_base_name: &str) -> bool {
+        self.node.as_ref().is_none_or(|n| n == node_base_name)
+    }
+}
+
+// Options of `--ros-args` that take an argument, but are not remappings.
+const ROS_ARGS_WITH_VALUE: &[&str] = &[
+    "-p",
+    "--param",
+    "--params-file",
+    "-e",
+    "--enclave",
+    "--log-level",
+    "--log-config-file",
+];
+
+/// Extract remapping rules from command line arguments.
+///
+/// Only arguments between `--ros-args` and `--` (or the end) are considered.
+/// Other ROS arguments, such as parameters, are skipped.
+pub fn parse_ros_args<S: AsRef<str>>(args: &[S]) -> Result<Vec<RemapRule>, String> {
+    let mut rules = Vec::new();
+    let mut in_ros_args = false;
+    let mut args = args.iter().map(AsRef::as_ref);
+    while let Some(arg) = args.next() {
+        match arg {
+            "--ros-args" => in_ros_args = true,
+            "--" => in_ros_args = false,
+            _ if !in_ros_args => {}
+            "-r" | "--remap" => {
+                let rule = args
+                    .next()
+                    .ok_or_else(|| format!("Missing remapping rule after {arg}"))?;
+                rules.push(RemapRule::parse(rule)?);
+            }
+            a if ROS_ARGS_WITH_VALUE.contains(&a) => {
+                args.next();
+            }
+            _ => {}
+        }
+    }
+    Ok(rules)
+}
+
+/// Remapping rules resolved for a single Node
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Remapping {
+    names: Vec<(Name, Name)>, // absolute names
+}
+
+impl Remapping {
+    /// Apply node name and namespace rules to `node_name`, and resolve the
+    /// rest of the rules relative to the resulting Node name.
+    pub(crate) fn new(
+        node_name: &NodeName,
+        rules: &[RemapRule],
+    ) -> Result<(NodeName, Remapping), String> {
+        let rules: Vec<&RemapRule> = rules
+            .iter()
+            .filter(|r| r.applies_to(node_name.base_name()))
+            .collect();
+
+        let base_name = rules
+            .iter()
+            .find(|r| r.from == "__node" || r.from == "__name")
+            .map_or(node_name.base_name(), |r| &r.to);
+        let namespace = rules
+            .iter()
+            .find(|r| r.from == "__ns")
+            .map_or(node_name.namespace(), |r| &r.to);
+        let node_name = NodeName::new(namespace, base_name).map_err(|e| e.to_string())?;
+
+        let mut remapping = Remapping::default();
+        for rule in rules
+            .iter()
+            .filter(|r| !["__node", "__name", "__ns"].contains(&r.from.as_str()))
+        {
+            remapping.add(&node_name, rule)?;
+        }
+        Ok((node_name, remapping))
+    }
+
+    pub(crate) fn add(&mut self, node_name: &NodeName, rule: &RemapRule) -> Result<(), String> {
+        let from = expand(node_name, &rule.from)?;
+        let to = expand(node_name, &rule.to)?;
+        self.names.push((from, to));
+        Ok(())
+    }
+
+    /// Remapped name, or `None` if no rule matches.
+    pub(crate) fn apply(&self, node_name: &NodeName, name: &Name) -> Option<Name> {
+        let absolute = name.to_absolute(node_name.namespace());
+        self.names
+            .iter()
+            .find(|(from, _)| *from == absolute)
+            .map(|(_, to)| to.clone())
+    }
+}
+
+// Parse name in a rule to an absolute Name
+fn expand(node_name: &NodeName, name: &str) -> Result<Name, String> {
+    let parsed = match name.strip_prefix("~/") {
+        Some(private) => Name::parse(&format!("{}/{private}", node_name.fully_qualified_name())),
+        None => Name::parse(name),
+    };
+    parsed
+        .map(|n| n.to_absolute(node_name.namespace()))
+        .map_err(|e| format!("Bad name {name:?} in remapping rule: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ros_args_remapping() {
+        let args = [
+            "prog",
+            "-r",
+            "ignored:=x",
+            "--ros-args",
+            "-p",
+            "a:=1",
+            "-r",
+            "chatter:=/talk",
+            "--remap",
+            "other_node:foo:=bar",
+            "-r",
+            "__ns:=/robot1",
+            "--",
+            "-r",
+            "not:=this",
+        ];
+        let rules = parse_ros_args(&args).unwrap();
+        assert_eq!(
+            rules,
+            vec![
+                RemapRule::new("chatter", "/talk"),
+                RemapRule::new("foo", "bar").for_node("other_node"),
+                RemapRule::new("__ns", "/robot1"),
+            ]
+        );
+
+        let node_name = NodeName::new("/", "talker").unwrap();
+        let (node_name, remapping) = Remapping::new(&node_name, &rules).unwrap();
+        assert_eq!(node_name.fully_qualified_name(), "/robot1/talker");
+        assert_eq!(
+            remapping
+                .apply(&node_name, &Name::parse("chatter").unwrap())
+                .map(|n| n.to_string()),
+            Some("/talk".to_owned())
+        );
+        assert_eq!(
+            remapping.apply(&node_name, &Name::parse("/chatter").unwrap()),
+            None
+        );
+        assert_eq!(
+            remapping.apply(&node_name, &Name::parse("foo").unwrap()),
+            None
+        );
+    }
+}
@@ -0,0 +1,472 @@
+//! Application-level retransmission over lossy links
+//!
+//! DDS Reliability is sometimes not enough: The Publisher may be BestEffort
+//! to match other Subscriptions, or the link drops so much that reliable
+//! repair traffic gets stuck behind the loss. [`ResendPublisher`] keeps the
+//! latest messages it has published in a cache, and serves them on a hidden
+//! Service `<topic>/_resend`. [`ResendSubscription`] notices gaps in the DDS
+//! sequence numbers of each Publisher, and requests the missing messages
+//! from that Service.
+//!
+//! The Topic itself is unchanged, so other Subscriptions and ROS 2 tools see
+//! the ordinary message type. Recovered messages are delivered when their
+//! resend response arrives, i.e. out of order. Their [`MessageInfo`] has the
+//! original sequence number and source timestamp.
+//!
+//! Neither wrapper does any work on its own: Call
+//! [`ResendPublisher::serve_resend_requests`] periodically, and take
+//! messages with [`ResendSubscription::take`], which also handles the resend
+//! responses.
+//!
+//! ```ignore
+//! let publisher = node.advertise::<Odometry>("/odom")?;
+//! let publisher = ResendPublisher::new(&mut node, publisher, 100)?;
+//! publisher.publish(odometry)?;
+//! publisher.serve_resend_requests()?;
+//!
+//! // In another process
+//! let subscription = node.subscribe::<Odometry>("/odom")?;
+//! let subscription = ResendSubscription::new(&mut node, subscription)?;
+//! while let Some((odometry, info)) = subscription.take()? { ... }
+//! ```
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+};
+
+use log::{debug, warn};
+use rustdds::{
+    dds::{CreateError, CreateResult, ReadResult, WriteResult},
+    no_key::SerializerAdapter,
+    serialization::deserialize_from_cdr_with_rep_id,
+    CDRSerializerAdapter, RepresentationIdentifier, SequenceNumber, Timestamp, GUID,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{
+    pubsub::{Publisher, Subscription},
+    Node,
+};
+use crate::{
+    interfaces::{gid::Gid, names::Name},
+    message::Message,
+    prelude::{MessageInfo, ServiceTypeName},
+    service::{request_id::RmwRequestId, AService, Client, Server, ServiceMapping},
+    time::ros_time::ROSTime,
+};
+
+/// Name of the resend Service, relative to the Topic name
+pub const RESEND_SERVICE_SUFFIX: &str = "_resend";
+
+/// At most this many messages are requested for one gap. Older missing
+/// messages are given up.
+pub const MAX_RESEND_GAP: i64 = 256;
+
+/// Request for messages `first..=last` published by `writer`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResendRequest {
+    pub writer: Gid,
+    pub first: i64,
+    pub last: i64,
+}
+impl Message for ResendRequest {}
+
+/// A message from the resend cache, in serialized form
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResentMessage {
+    pub sequence_number: i64,
+    /// Source time in nanoseconds since the Unix epoch
+    pub source_time: i64,
+    /// Little-endian CDR payload, without encapsulation header
+    pub data: Vec<u8>,
+}
+
+/// The requested messages that were still in the cache
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResendResponse {
+    pub messages: Vec<ResentMessage>,
+}
+impl Message for ResendResponse {}
+
+pub type ResendService = AService<ResendRequest, ResendResponse>;
+
+fn resend_service_type_name() -> ServiceTypeName {
+    ServiceTypeName::new("ros2_client", "Resend")
+}
+
+fn resend_service_name(topic_name: &str) -> CreateResult<Name> {
+    Name::parse(topic_name)
+        .map(|name| name.push(RESEND_SERVICE_SUFFIX))
+        .map_err(|e| CreateError::BadParameter {
+            reason: format!("Bad Topic name {topic_name:?}: {e}"),
+        })
+}
+
+/// A [`Publisher`] that keeps its latest messages for resending. See the
+/// [module documentation](self).
+pub struct ResendPublisher<M: Serialize> {
+    publisher: Publisher<M>,
+    server: Server<ResendService>,
+    cache: Mutex<VecDeque<ResentMessage>>,
+    cache_size: usize,
+}
+
+impl<M: Serialize> ResendPublisher<M> {
+    /// Keep the latest `cache_size` messages of `publisher`, and serve them
+    /// with a Service created in `node`.
+    pub fn new(
+        node: &mut Node,
+        publisher: Publisher<M>,
+        cache_size: usize,
+    ) -> CreateResult<ResendPublisher<M>> {
+        let server = node.create_server::<ResendService>(
+            ServiceMapping::Enhanced,
+            &resend_service_name(&publisher.topic_name())?,
+            &resend_service_type_name(),
+            None,
+            None,
+        )?;
+        Ok(ResendPublisher {
+            publisher,
+            server,
+            cache: Mutex::new(VecDeque::with_capacity(cache_size)),
+            cache_size,
+        })
+    }
+
+    /// Publish `message`, and keep a copy in the cache.
+    pub fn publish(&self, message: M) -> WriteResult<(), M> {
+        let data = CDRSerializerAdapter::<M>::to_bytes(&message).map(|bytes| bytes.to_vec());
+        // The cache is locked over publishing, so that it stays in sequence
+        // number order. The cached time converts back to exactly the
+        // timestamp written.
+        let mut cache = self.cache.lock().unwrap();
+        let source_time = ROSTime::now();
+        let sequence_number = self
+            .publisher
+            .publish_with_timestamp(message, Timestamp::from(source_time))?;
+        match data {
+            Ok(data) => {
+                cache.push_back(ResentMessage {
+                    sequence_number: i64::from(sequence_number),
+                    source_time: source_time.to_nanos(),
+                    data,
+                });
+                while cache.len() > self.cache_size {
+                    cache.pop_front();
+                }
+            }
+            Err(e) => warn!("Cannot cache message for resending: {e}"),
+        }
+        Ok(())
+    }
+
+    /// Answer all pending resend requests for this Publisher.
+    ///
+    /// Returns the number of requests answered. Requests for other
+    /// Publishers of the same Topic are left to them.
+    pub fn serve_resend_requests(&self) -> WriteResult<usize, ()> {
+        let mut answered = 0;
+        loop {
+            let (request_id, request) = match self.server.receive_request() {
+                Ok(Some(received)) => received,
+                Ok(None) => return Ok(answered),
+                Err(e) => {
+                    warn!("Cannot receive resend request: {e}");
+                    return Ok(answered);
+                }
+            };
+            if request.writer != self.publisher.gid() {
+                continue;
+            }
+            let messages: Vec<ResentMessage> = self
+                .cache
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|m| (request.first..=request.last).contains(&m.sequence_number))
+                .cloned()
+                .collect();
+            debug!(
+                "Resending {} of messages {}..={} on {}",
+                messages.len(),
+                request.first,
+                request.last,
+                self.publisher.topic_name()
+            );
+            self.server
+                .send_response(request_id, ResendResponse { messages })?;
+            answered += 1;
+        }
+    }
+
+    /// The wrapped Publisher. Messages published with it are not cached.
+    pub fn publisher(&self) -> &Publisher<M> {
+        &self.publisher
+    }
+}
+
+/// A [`Subscription`] that requests messages lost from a
+/// [`ResendPublisher`]. See the [module documentation](self).
+pub struct ResendSubscription<M> {
+    subscription: Subscription<M>,
+    client: Client<ResendService>,
+    gaps: Mutex<GapTracker>,
+    // Writer of the messages requested by each pending request
+    pending: Mutex<BTreeMap<RmwRequestId, GUID>>,
+    recovered: Mutex<VecDeque<(M, MessageInfo)>>,
+}
+
+impl<M: DeserializeOwned + 'static> ResendSubscription<M> {
+    /// Request lost messages of `subscription` with a Client created in
+    /// `node`.
+    pub fn new(
+        node: &mut Node,
+        subscription: Subscription<M>,
+    ) -> CreateResult<ResendSubscription<M>> {
+        let client = node.create_client::<ResendService>(
+            ServiceMapping::Enhanced,
+            &resend_service_name(&subscription.topic_name())?,
+            &resend_service_type_name(),
+            None,
+            None,
+        )?;
+        Ok(ResendSubscription {
+            subscription,
+            client,
+            gaps: Mutex::new(GapTracker::default()),
+            pending: Mutex::new(BTreeMap::new()),
+            recovered: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    /// Take the next message, either recovered or newly received.
+    ///
+    /// A gap before a received message causes a resend request.
+    pub fn take(&self) -> ReadResult<Option<(M, MessageInfo)>> {
+        self.receive_resends();
+        if let Some(recovered) = self.recovered.lock().unwrap().pop_front() {
+            return Ok(Some(recovered));
+        }
+        let taken = self.subscription.take()?;
+        if let Some((_, info)) = &taken {
+            let missing = self
+                .gaps
+                .lock()
+                .unwrap()
+                .observe(info.writer_guid(), info.sample_identity().sequence_number);
+            if let Some((first, last)) = missing {
+                let request = ResendRequest {
+                    writer: info.writer_gid(),
+                    first,
+                    last,
+                };
+                match self.client.send_request(request) {
+                    Ok(request_id) => {
+                        let mut pending = self.pending.lock().unwrap();
+                        pending.insert(request_id, info.writer_guid());
+                        // Requests that were never answered are forgotten.
+                        if pending.len() > MAX_PENDING_REQUESTS {
+                            pending.pop_first();
+                        }
+                    }
+                    Err(e) => warn!("Cannot request resend of {first}..={last}: {e:?}"),
+                }
+            }
+        }
+        Ok(taken)
+    }
+
+    // Move the messages of received resend responses to `recovered`.
+    fn receive_resends(&self) {
+        loop {
+            let (request_id, response) = match self.client.receive_response() {
+                Ok(Some(received)) => received,
+                Ok(None) => return,
+                Err(e) => {
+                    warn!("Cannot receive resend response: {e}");
+                    return;
+                }
+            };
+            let Some(writer) = self.pending.lock().unwrap().remove(&request_id) else {
+                continue;
+            };
+            let mut recovered = self.recovered.lock().unwrap();
+            for message in response.messages {
+                let decoded = deserialize_from_cdr_with_rep_id::<M>(
+                    &message.data,
+                    RepresentationIdentifier::CDR_LE,
+                );
+                match decoded {
+                    Ok((value, _)) => {
+                        recovered.push_back((
+                            value,
+                            MessageInfo::recovered(
+                                writer,
+                                SequenceNumber::from(message.sequence_number),
+                                Timestamp::from(ROSTime::from_nanos(message.source_time)),
+                            ),
+                        ));
+                    }
+                    Err(e) => warn!("Cannot decode resent message: {e}"),
+                }
+            }
+        }
+    }
+
+    /// The wrapped Subscription. Messages taken with it are not checked for
+    /// gaps.
+    pub fn subscription(&self) -> &Subscription<M> {
+        &self.subscription
+    }
+}
+
+const MAX_PENDING_REQUESTS: usize = 64;
+
+// Latest sequence number from each writer
+#[derive(Default)]
+struct GapTracker {
+    latest: BTreeMap<GUID, i64>,
+}
+
+impl GapTracker {
+    // Record a received sequence number. Returns the missing range before
+    // it, if any.
+    fn observe(&mut self, writer: GUID, sequence_number: SequenceNumber) -> Option<(i64, i64)> {
+        let sequence_number = i64::from(sequence_number);
+        let previous = self.latest.insert(writer, sequence_number);
+        match previous {
+            Some(previous) if sequence_number > previous + 1 => {
+                let first = (previous + 1).max(sequence_number - MAX_RESEND_GAP);
+                Some((first, sequence_number - 1))
+            }
+            // Also a Publisher that restarted, and starts again from 1
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::{
+        interfaces::{builtin_interfaces::Time, std_msgs::Header},
+        node::{
+            context::{tests::test_node, Context},
+            pubsub::PauseMode,
+            NodeOptions,
+        },
+    };
+
+    // Call `f` until it returns something.
+    fn poll<T>(mut f: impl FnMut() -> Option<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(20);
+        loop {
+            if let Some(result) = f() {
+                return result;
+            }
+            assert!(Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    // Take messages until the one with `frame_id`.
+    fn take_frame(
+        mut take: impl FnMut() -> ReadResult<Option<(Header, MessageInfo)>>,
+        frame_id: &str,
+    ) -> MessageInfo {
+        poll(|| match take().unwrap() {
+            Some((header, info)) if header.frame_id == frame_id => Some(info),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn gaps() {
+        let writer = GUID::from_bytes([1; 16]);
+        let mut gaps = GapTracker::default();
+        let mut observe = |sn: i64| gaps.observe(writer, SequenceNumber::from(sn));
+
+        assert_eq!(observe(1), None);
+        assert_eq!(observe(2), None);
+        assert_eq!(observe(5), Some((3, 4)));
+        assert_eq!(observe(1), None); // restarted
+        assert_eq!(observe(1000), Some((1000 - MAX_RESEND_GAP, 999)));
+    }
+
+    #[test]
+    fn recover_dropped_message() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_resend_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let _spinner = node.spin_in_background().unwrap();
+        let publisher = node.advertise::<Header>("/resend_test").unwrap();
+        let publisher = ResendPublisher::new(&mut node, publisher, 10).unwrap();
+        let witness = node.subscribe::<Header>("/resend_test").unwrap();
+        let subscription = node.subscribe::<Header>("/resend_test").unwrap();
+        let subscription = ResendSubscription::new(&mut node, subscription).unwrap();
+        let header = |frame_id: &str| Header {
+            stamp: Time::ZERO,
+            frame_id: frame_id.to_owned(),
+        };
+
+        // Match events between entities of one Node can be missed, so matching
+        // is checked by sending data.
+        let mut taken = (false, false);
+        poll(|| {
+            publisher.publish(header("0")).unwrap();
+            taken.0 |= witness.take().unwrap().is_some();
+            taken.1 |= subscription.take().unwrap().is_some();
+            (taken.0 && taken.1).then_some(())
+        });
+        poll(|| {
+            let request = ResendRequest {
+                writer: publisher.publisher().gid(),
+                first: 0,
+                last: 0,
+            };
+            subscription.client.send_request(request).unwrap();
+            publisher.serve_resend_requests().unwrap();
+            subscription.client.receive_response().unwrap()
+        });
+
+        publisher.publish(header("1")).unwrap();
+        take_frame(|| subscription.take(), "1");
+        take_frame(|| witness.take(), "1");
+
+        // Lose "2" on the resending side only
+        subscription.subscription().pause(PauseMode::Discard);
+        publisher.publish(header("2")).unwrap();
+        let original = take_frame(|| witness.take(), "2");
+        let received = subscription.subscription().status_counters().bytes_received;
+        poll(|| {
+            assert!(subscription.take().unwrap().is_none());
+            let counters = subscription.subscription().status_counters();
+            (counters.bytes_received > received).then_some(())
+        });
+        subscription.subscription().resume();
+
+        // "3" reveals the gap, and "2" is requested.
+        publisher.publish(header("3")).unwrap();
+        take_frame(|| subscription.take(), "3");
+        let info = take_frame(
+            || {
+                publisher.serve_resend_requests().unwrap();
+                subscription.take()
+            },
+            "2",
+        );
+        assert_eq!(
+            info.sample_identity().sequence_number,
+            original.sample_identity().sequence_number
+        );
+        assert_eq!(info.source_timestamp(), original.source_timestamp());
+        assert_eq!(info.writer_gid(), original.writer_gid());
+    }
+}
@@ -0,0 +1,260 @@
+//! Threads and buffer memory used by a Context
+//!
+//! [`Context::resource_usage`](super::context::Context::resource_usage)
+//! reports the background threads of the Context and how much memory the
+//! histories of its endpoints may take. Memory-constrained deployments, such
+//! as companion computers, can cap both with
+//! [`ContextOptions::max_background_threads`](super::context::ContextOptions::max_background_threads)
+//! and
+//! [`ContextOptions::max_buffer_bytes`](super::context::ContextOptions::max_buffer_bytes).
+//! Creating a Spinner thread or an endpoint over the cap fails with
+//! [`CreateError::OutOfResources`].
+//!
+//! Buffer memory is budgeted, not measured: each Publisher, Subscription,
+//! Client and Server endpoint of a Node reserves its history depth times the
+//! largest message size. The largest size is
+//! [`ContextOptions::max_message_size`](super::context::ContextOptions::max_message_size)
+//! if given, and otherwise one UDP datagram, so setting a maximum message
+//! size makes the budget tighter. The builtin `/rosout`, `/parameter_events`
+//! and ROS Discovery endpoints are not counted.
+
+use rustdds::{
+    dds::{CreateError, CreateResult},
+    policy::History,
+    QosPolicies,
+};
+
+/// Thread and buffer use of a Context
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Threads of the DDS DomainParticipant. This is fixed in RustDDS.
+    pub dds_threads: usize,
+    /// Threads started by ros2-client, i.e. Spinners run with
+    /// [`Node::spin_in_background`](super::Node::spin_in_background)
+    pub background_threads: usize,
+    /// Endpoints of the Nodes of the Context
+    pub endpoints: usize,
+    /// Buffer memory reserved by the endpoints, in bytes
+    pub buffer_bytes: u64,
+    /// Endpoints with KeepAll history without resource limits. These are
+    /// not included in `buffer_bytes`, as their memory use has no bound.
+    pub unbounded_endpoints: usize,
+}
+
+// Usage and caps, kept by the Context
+#[derive(Debug, Default)]
+pub(crate) struct ResourceLedger {
+    pub max_background_threads: Option<usize>,
+    pub max_buffer_bytes: Option<u64>,
+    background_threads: usize,
+    endpoints: usize,
+    buffer_bytes: u64,
+    unbounded_endpoints: usize,
+}
+
+/// Buffer reservation of one endpoint. `None` means unbounded.
+pub(crate) type Reservation = Option<u64>;
+
+// Number of samples the history of an endpoint can hold, if bounded
+fn history_capacity(qos: &QosPolicies) -> Option<u64> {
+    match qos.history() {
+        None => Some(1), // KeepLast 1 is the default
+        Some(History::KeepLast { depth }) => Some(depth.max(1) as u64),
+        Some(History::KeepAll) => qos
+            .resource_limits()
+            .and_then(|rl| u64::try_from(rl.max_samples_per_instance).ok()),
+    }
+}
+
+pub(crate) fn reservation(qos: &QosPolicies, max_sample_size: usize) -> Reservation {
+    history_capacity(qos).map(|samples| samples.saturating_mul(max_sample_size as u64))
+}
+
+impl ResourceLedger {
+    pub fn usage(&self, dds_threads: usize) -> ResourceUsage {
+        ResourceUsage {
+            dds_threads,
+            background_threads: self.background_threads,
+            endpoints: self.endpoints,
+            buffer_bytes: self.buffer_bytes,
+            unbounded_endpoints: self.unbounded_endpoints,
+        }
+    }
+
+    pub fn reserve_thread(&mut self) -> CreateResult<()> {
+        if let Some(max) = self.max_background_threads {
+            if self.background_threads >= max {
+                return Err(CreateError::OutOfResources {
+                    reason: format!("Background thread limit {max} reached"),
+                });
+            }
+        }
+        self.background_threads += 1;
+        Ok(())
+    }
+
+    pub fn release_thread(&mut self) {
+        self.background_threads = self.background_threads.saturating_sub(1);
+    }
+
+    pub fn reserve_buffer(
+        &mut self,
+        topic_name: &str,
+        reservation: Reservation,
+    ) -> CreateResult<()> {
+        match (reservation, self.max_buffer_bytes) {
+            (None, Some(_)) => {
+                return Err(CreateError::OutOfResources {
+                    reason: format!(
+                        "Topic {topic_name}: KeepAll history without ResourceLimits does not fit \
+                         in a buffer budget."
+                    ),
+                })
+            }
+            (Some(bytes), Some(max)) if self.buffer_bytes.saturating_add(bytes) > max => {
+                return Err(CreateError::OutOfResources {
+                    reason: format!(
+                        "Topic {topic_name}: {bytes} bytes of history do not fit in the buffer \
+                         budget, {} of {max} bytes already reserved.",
+                        self.buffer_bytes
+                    ),
+                })
+            }
+            _ => (),
+        }
+        self.endpoints += 1;
+        match reservation {
+            Some(bytes) => self.buffer_bytes += bytes,
+            None => self.unbounded_endpoints += 1,
+        }
+        Ok(())
+    }
+
+    pub fn release_buffer(&mut self, reservation: Reservation) {
+        self.endpoints = self.endpoints.saturating_sub(1);
+        match reservation {
+            Some(bytes) => self.buffer_bytes = self.buffer_bytes.saturating_sub(bytes),
+            None => self.unbounded_endpoints = self.unbounded_endpoints.saturating_sub(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{policy::ResourceLimits, QosPolicyBuilder};
+
+    use super::*;
+    use crate::{
+        interfaces::std_msgs::Header,
+        node::{
+            context::{tests::test_node, Context, ContextOptions},
+            NodeOptions,
+        },
+    };
+
+    #[test]
+    fn reservations() {
+        let keep_last = QosPolicyBuilder::new()
+            .history(History::KeepLast { depth: 10 })
+            .build();
+        assert_eq!(reservation(&keep_last, 100), Some(1000));
+        assert_eq!(reservation(&QosPolicies::qos_none(), 100), Some(100));
+        let keep_all = QosPolicyBuilder::new().history(History::KeepAll).build();
+        assert_eq!(reservation(&keep_all, 100), None);
+        let limited = QosPolicyBuilder::new()
+            .history(History::KeepAll)
+            .resource_limits(ResourceLimits {
+                max_samples: 5,
+                max_instances: 1,
+                max_samples_per_instance: 5,
+            })
+            .build();
+        assert_eq!(reservation(&limited, 100), Some(500));
+    }
+
+    #[test]
+    fn budget() {
+        let mut ledger = ResourceLedger {
+            max_background_threads: Some(1),
+            max_buffer_bytes: Some(1000),
+            ..ResourceLedger::default()
+        };
+        ledger.reserve_thread().unwrap();
+        assert!(matches!(
+            ledger.reserve_thread(),
+            Err(CreateError::OutOfResources { .. })
+        ));
+        ledger.release_thread();
+        ledger.reserve_thread().unwrap();
+
+        ledger.reserve_buffer("/a", Some(600)).unwrap();
+        assert!(ledger.reserve_buffer("/b", Some(600)).is_err());
+        assert!(ledger.reserve_buffer("/b", None).is_err());
+        ledger.reserve_buffer("/b", Some(400)).unwrap();
+        assert_eq!(ledger.usage(2).buffer_bytes, 1000);
+        ledger.release_buffer(Some(600));
+        assert_eq!(
+            ledger.usage(2),
+            ResourceUsage {
+                dds_threads: 2,
+                background_threads: 1,
+                endpoints: 1,
+                buffer_bytes: 400,
+                unbounded_endpoints: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn resource_budget() {
+        use rustdds::dds::CreateError;
+
+        let context = Context::with_options(
+            ContextOptions::new()
+                .max_message_size(100)
+                .max_buffer_bytes(250),
+        )
+        .unwrap();
+        let mut node = test_node(
+            &context,
+            "test_budget_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        // History depth 1 and 100 byte messages
+        let first = node.advertise::<Header>("/budget_first").unwrap();
+        let _second = node.advertise::<Header>("/budget_second").unwrap();
+        let usage = context.resource_usage();
+        assert_eq!((usage.endpoints, usage.buffer_bytes), (2, 200));
+        assert!(matches!(
+            node.advertise::<Header>("/budget_third"),
+            Err(CreateError::OutOfResources { .. })
+        ));
+        drop(first);
+        assert_eq!(context.resource_usage().buffer_bytes, 100);
+        node.advertise::<Header>("/budget_third").unwrap();
+
+        let context =
+            Context::with_options(ContextOptions::new().max_background_threads(1)).unwrap();
+        let mut nodes = ["test_threads_1", "test_threads_2"]
+            .map(|name| test_node(&context, name, NodeOptions::new().enable_rosout(false)));
+        let spin_handle = nodes[0].spin_in_background().unwrap();
+        assert_eq!(context.resource_usage().background_threads, 1);
+        assert_eq!(context.resource_usage().dds_threads, 2);
+        assert!(matches!(
+            nodes[1].spin_in_background(),
+            Err(CreateError::OutOfResources { .. })
+        ));
+        spin_handle.stop();
+        spin_handle.join().unwrap();
+        // The thread exits soon after the Spinner
+        let start = std::time::Instant::now();
+        while context.resource_usage().background_threads > 0 {
+            assert!(
+                start.elapsed().as_secs() < 10,
+                "Spinner thread still counted"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        nodes[1].spin_in_background().unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+//! Gathering the current state from several Subscriptions at once
+//!
+//! [`snapshot`] waits until every listed Subscription has a recent message,
+//! and returns the latest message of each. This is for queries like "what
+//! is the current robot state" in scripts and Service handlers:
+//!
+//! ```ignore
+//! let odometry = node.subscribe::<Odometry>("/odom")?;
+//! let battery = node.subscribe::<BatteryState>("/battery_state")?;
+//! let ((odometry, _), (battery, _)) = snapshot(
+//!     &(&odometry, &battery),
+//!     Duration::from_secs(1), // max age of each message
+//!     Duration::from_secs(5), // how long to wait
+//! )
+//! .await?;
+//! ```
+//!
+//! The Subscriptions can be given as a tuple of up to eight, of different
+//! message types, or as a slice of Subscriptions of one type. Messages are
+//! looked at with [`Subscription::latest`], so other consumers of the
+//! Subscriptions still receive them.
+
+use std::{fmt, time::Duration};
+
+use futures::executor;
+use futures_timer::Delay;
+use rustdds::{
+    dds::{ReadError, ReadResult},
+    no_key::DefaultDecoder,
+};
+
+use super::pubsub::Subscription;
+use crate::{message::message_info::MessageInfo, time::ros_time::ROSTime};
+
+// How often the Subscriptions are checked while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Error from [`snapshot`]
+#[derive(Debug)]
+pub enum SnapshotError {
+    Read(ReadError),
+    /// Some Subscriptions had no recent message within the timeout.
+    Timeout {
+        /// Topic names of those Subscriptions
+        missing: Vec<String>,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Read(e) => write!(f, "{e}"),
+            SnapshotError::Timeout { missing } => {
+                write!(f, "No recent message on {}", missing.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<ReadError> for SnapshotError {
+    fn from(e: ReadError) -> SnapshotError {
+        SnapshotError::Read(e)
+    }
+}
+
+/// Subscriptions that can be queried together with [`snapshot`]
+pub trait SnapshotSources {
+    /// The latest message of each Subscription
+    type Output;
+
+    /// Latest messages stamped at or after `oldest`, or the Topic names of
+    /// the Subscriptions that do not have one.
+    fn collect(&self, oldest: ROSTime) -> ReadResult<Result<Self::Output, Vec<String>>>;
+}
+
+fn recent<M, DA>(
+    subscription: &Subscription<M, DA>,
+    oldest: ROSTime,
+) -> ReadResult<Option<(M, MessageInfo)>>
+where
+    M: Clone + 'static,
+    DA: DefaultDecoder<M> + 'static,
+{
+    Ok(subscription.latest()?.filter(|(_, info)| {
+        let stamp = info.source_timestamp().unwrap_or(info.received_timestamp());
+        ROSTime::try_from(stamp).is_ok_and(|stamp| stamp >= oldest)
+    }))
+}
+
+impl<M, DA> SnapshotSources for [&Subscription<M, DA>]
+where
+    M: Clone + 'static,
+    DA: DefaultDecoder<M> + 'static,
+{
+    type Output = Vec<(M, MessageInfo)>;
+
+    fn collect(&self, oldest: ROSTime) -> ReadResult<Result<Self::Output, Vec<String>>> {
+        let mut messages = Vec::with_capacity(self.len());
+        let mut missing = Vec::new();
+        for subscription in self {
+            match recent(subscription, oldest)? {
+                Some(message) => messages.push(message),
+                None => missing.push(subscription.topic_name()),
+            }
+        }
+        Ok(if missing.is_empty() {
+            Ok(messages)
+        } else {
+            Err(missing)
+        })
+    }
+}
+
+macro_rules! impl_snapshot_sources_for_tuple {
+    ($($M:ident $DA:ident $i:tt),+) => {
+        impl<$($M, $DA),+> SnapshotSources for ($(&Subscription<$M, $DA>,)+)
+        where
+            $($M: Clone + 'static, $DA: DefaultDecoder<$M> + 'static,)+
+        {
+            type Output = ($(($M, MessageInfo),)+);
+
+            fn collect(&self, oldest: ROSTime) -> ReadResult<Result<Self::Output, Vec<String>>> {
+                let latest = ($(recent(self.$i, oldest)?,)+);
+                let mut missing = Vec::new();
+                $(
+                    if latest.$i.is_none() {
+                        missing.push(self.$i.topic_name());
+                    }
+                )+
+                if !missing.is_empty() {
+                    return Ok(Err(missing));
+                }
+                Ok(Ok(($(latest.$i.unwrap(),)+)))
+            }
+        }
+    };
+}
+
+impl_snapshot_sources_for_tuple!(M0 DA0 0);
+impl_snapshot_sources_for_tuple!(M0 DA0 0, M1 DA1 1);
+impl_snapshot_sources_for_tuple!(M0 DA0 0, M1 DA1 1, M2 DA2 2);
+impl_snapshot_sources_for_tuple!(M0 DA0 0, M1 DA1 1, M2 DA2 2, M3 DA3 3);
+impl_snapshot_sources_for_tuple!(M0 DA0 0, M1 DA1 1, M2 DA2 2, M3 DA3 3, M4 DA4 4);
+impl_snapshot_sources_for_tuple!(M0 DA0 0, M1 DA1 1, M2 DA2 2, M3 DA3 3, M4 DA4 4, M5 DA5 5);
+impl_snapshot_sources_for_tuple!(
+    M0 DA0 0, M1 DA1 1, M2 DA2 2, M3 DA3 3, M4 DA4 4, M5 DA5 5, M6 DA6 6
+);
+impl_snapshot_sources_for_tuple!(
+    M0 DA0 0, M1 DA1 1, M2 DA2 2, M3 DA3 3, M4 DA4 4, M5 DA5 5, M6 DA6 6, M7 DA7 7
+);
+
+/// Wait until each of `sources` has a message at most `max_age` old, and
+/// return the latest message of each.
+///
+/// Gives up with [`SnapshotError::Timeout`] after `timeout`. The age of a
+/// message is counted from its source timestamp to the start of the call,
+/// so the clocks of the publishing hosts should be synchronized. Messages
+/// without a source timestamp count from when they were taken from DDS.
+pub async fn snapshot<S>(
+    sources: &S,
+    max_age: Duration,
+    timeout: Duration,
+) -> Result<S::Output, SnapshotError>
+where
+    S: SnapshotSources + ?Sized,
+{
+    let start = ROSTime::now();
+    let max_age = i64::try_from(max_age.as_nanos()).unwrap_or(i64::MAX);
+    let oldest = ROSTime::from_nanos(start.to_nanos().saturating_sub(max_age));
+    let mut deadline = Delay::new(timeout);
+    loop {
+        let missing = match sources.collect(oldest)? {
+            Ok(messages) => return Ok(messages),
+            Err(missing) => missing,
+        };
+        if futures::poll!(&mut deadline).is_ready() {
+            return Err(SnapshotError::Timeout { missing });
+        }
+        Delay::new(POLL_INTERVAL).await;
+    }
+}
+
+/// Blocking version of [`snapshot`]
+pub fn snapshot_blocking<S>(
+    sources: &S,
+    max_age: Duration,
+    timeout: Duration,
+) -> Result<S::Output, SnapshotError>
+where
+    S: SnapshotSources + ?Sized,
+{
+    executor::block_on(snapshot(sources, max_age, timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        interfaces::{builtin_interfaces::Time, std_msgs::Header},
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+    };
+
+    #[test]
+    fn latest_messages() {
+        use std::{
+            sync::atomic::{AtomicBool, Ordering},
+            time::Duration,
+        };
+
+        use crate::interfaces::geometry_msgs::Vector3;
+
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_snapshot_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let first = node.subscribe::<Header>("/snapshot_first").unwrap();
+        let second = node.subscribe::<Vector3>("/snapshot_second").unwrap();
+
+        match snapshot_blocking(
+            &(&first, &second),
+            Duration::from_secs(1),
+            Duration::from_millis(50),
+        ) {
+            Err(SnapshotError::Timeout { missing }) => {
+                assert_eq!(missing, vec!["/snapshot_first", "/snapshot_second"])
+            }
+            other => panic!("Expected timeout, got {other:?}"),
+        }
+
+        let header_publisher = node.advertise::<Header>("/snapshot_first").unwrap();
+        let vector_publisher = node.advertise::<Vector3>("/snapshot_second").unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let publishing = {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let header = Header {
+                        stamp: Time::ZERO,
+                        frame_id: "map".to_owned(),
+                    };
+                    header_publisher.publish(header).unwrap();
+                    vector_publisher.publish(Vector3::default()).unwrap();
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            })
+        };
+        let result = snapshot_blocking(
+            &(&first, &second),
+            Duration::from_secs(1),
+            Duration::from_secs(10),
+        );
+        stop.store(true, Ordering::Relaxed);
+        publishing.join().unwrap();
+        let ((header, _), (vector, _)) = result.unwrap();
+        assert_eq!(header.frame_id, "map");
+        assert_eq!(vector, Vector3::default());
+
+        // Slice of one type
+        let messages =
+            snapshot_blocking(&[&first][..], Duration::from_secs(1), Duration::ZERO).unwrap();
+        assert_eq!(messages.len(), 1);
+    }
+}
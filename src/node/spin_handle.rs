@@ -0,0 +1,73 @@
+//! Running the [`Spinner`](super::Spinner) in the background
+//!
+//! Instead of creating a Spinner and spawning its `.spin()` task by hand,
+//! [`Node::spin_in_background`](super::Node::spin_in_background) or
+//! [`Node::spin_with`](super::Node::spin_with) do both and return a
+//! [`SpinHandle`] to stop the task and wait for it.
+
+use futures::{channel::oneshot, executor, future::BoxFuture, FutureExt};
+use rustdds::dds::{CreateError, CreateResult};
+
+use super::Spinner;
+
+/// Handle to a Spinner task started by
+/// [`Node::spin_in_background`](super::Node::spin_in_background) or
+/// [`Node::spin_with`](super::Node::spin_with).
+///
+/// Dropping the handle does not stop the task. The task stops when
+/// [`Self::stop`] is called, or when the Node is dropped.
+pub struct SpinHandle {
+    stop_sender: async_channel::Sender<()>,
+    done_receiver: oneshot::Receiver<CreateResult<()>>,
+}
+
+impl SpinHandle {
+    // Wraps the Spinner into a future that reports its result to the handle.
+    pub(super) fn new(
+        spinner: Spinner,
+        stop_sender: async_channel::Sender<()>,
+    ) -> (SpinHandle, BoxFuture<'static, ()>) {
+        let (done_sender, done_receiver) = oneshot::channel();
+        let task = async move {
+            let result = spinner.spin().await;
+            // The handle may have been dropped already.
+            let _ = done_sender.send(result);
+        }
+        .boxed();
+        (
+            SpinHandle {
+                stop_sender,
+                done_receiver,
+            },
+            task,
+        )
+    }
+
+    /// Ask the Spinner task to stop.
+    ///
+    /// This returns immediately. Use [`Self::join`] or [`Self::async_join`] to
+    /// wait for the task to finish. Stopping an already stopped task does
+    /// nothing.
+    pub fn stop(&self) {
+        // The channel is full or closed if a stop is already pending or the task
+        // is gone, so the error can be ignored.
+        let _ = self.stop_sender.try_send(());
+    }
+
+    /// Wait until the Spinner task has finished, and return its result.
+    ///
+    /// This blocks the calling thread, so it should not be called from async
+    /// code. Use [`Self::async_join`] there.
+    pub fn join(self) -> CreateResult<()> {
+        executor::block_on(self.async_join())
+    }
+
+    /// Async version of [`Self::join`]
+    pub async fn async_join(self) -> CreateResult<()> {
+        self.done_receiver.await.unwrap_or_else(|_| {
+            Err(CreateError::ResourceDropped {
+                reason: "Spinner task was dropped before finishing".to_string(),
+            })
+        })
+    }
+}
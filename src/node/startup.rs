@@ -0,0 +1,70 @@
+//! Node startup time
+//!
+//! Every Node has builtin endpoints: the `/rosout` writer, the
+//! `/parameter_events` writer, and the Parameter Services. Creating DDS
+//! endpoints takes time, which adds up for short-lived command line tools.
+//!
+//! With [`NodeOptions::lazy_builtin_endpoints`](super::NodeOptions::lazy_builtin_endpoints),
+//! the writers are created only when something is first logged or a
+//! parameter first changes. The Parameter Services are always created only
+//! with the [`Spinner`](super::Spinner).
+//!
+//! [`Node::startup_times`](super::Node::startup_times) tells how long each
+//! part took.
+//!
+//! The writers and the Parameter Services exist only with the Cargo features
+//! `rosout` and `parameters`. Without them, their times are `None`.
+
+use std::time::Duration;
+
+/// Time spent creating a Node and its builtin endpoints
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StartupTimes {
+    /// Creation of the Node itself, including the builtin writers unless they
+    /// are lazy
+    pub node: Duration,
+    /// `/rosout` writer, if created
+    pub rosout_writer: Option<Duration>,
+    /// `/parameter_events` writer, if created
+    pub parameter_events_writer: Option<Duration>,
+    /// Parameter Services, if a Spinner has been created
+    pub parameter_services: Option<Duration>,
+}
+
+#[cfg(all(test, feature = "rosout", feature = "parameters"))]
+mod tests {
+    use crate::{
+        log::LogLevel,
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+        prelude::ParameterValue,
+    };
+
+    #[test]
+    fn lazy_builtin_endpoints() {
+        let context = Context::new().unwrap();
+        let eager = test_node(&context, "test_eager_node", NodeOptions::new());
+        let times = eager.startup_times();
+        assert!(times.rosout_writer.is_some() && times.parameter_events_writer.is_some());
+
+        let node = test_node(
+            &context,
+            "test_lazy_node",
+            NodeOptions::new()
+                .lazy_builtin_endpoints(true)
+                .declare_parameter("foo", ParameterValue::Integer(1)),
+        );
+        let times = node.startup_times();
+        assert_eq!(times.rosout_writer, None);
+        assert_eq!(times.parameter_events_writer, None);
+        assert_eq!(times.parameter_services, None);
+
+        crate::rosout!(node, LogLevel::Info, "hello");
+        assert!(node.startup_times().rosout_writer.is_some());
+        node.set_parameter("foo", ParameterValue::Integer(2))
+            .unwrap();
+        assert!(node.startup_times().parameter_events_writer.is_some());
+    }
+}
@@ -0,0 +1,308 @@
+//! Node self-statistics
+//!
+//! [`Node::statistics`](super::Node::statistics) reports the entity counts of
+//! the Node, its message rates, the number of dropped samples, and how busy
+//! its [`Spinner`](super::Spinner) is. With
+//! [`NodeOptions::statistics_period`](super::NodeOptions::statistics_period)
+//! the Spinner also publishes them periodically on the hidden Topic
+//! `<node name>/_statistics`, as a `diagnostic_msgs/msg/DiagnosticArray`, for
+//! fleet dashboards:
+//!
+//! ```text
+//! ros2 topic echo /my_node/_statistics
+//! ```
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use futures::{future, pin_mut};
+
+use crate::interfaces::diagnostic_msgs::{DiagnosticStatus, KeyValue};
+
+use super::{counters::Bandwidth, local_entities::LocalEndpoints};
+
+/// Base name of the statistics Topic, under the Node name
+pub const STATISTICS_TOPIC_NAME: &str = "_statistics";
+
+// Spinner load is measured over windows of at least this length.
+const LOAD_WINDOW: Duration = Duration::from_secs(1);
+
+/// Statistics of a Node. See the [module documentation](self).
+///
+/// Entity counts include the Parameter Services and the statistics
+/// Publisher, but not the `/rosout` and `/parameter_events` writers.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NodeStatistics {
+    pub publishers: usize,
+    pub subscriptions: usize,
+    pub servers: usize,
+    pub clients: usize,
+    /// Total rate of all Publishers
+    pub published: Bandwidth,
+    /// Total rate of all Subscriptions
+    pub received: Bandwidth,
+    /// Samples rejected, unreadable or failed to write, summed over all
+    /// endpoints since their creation. See
+    /// [`StatusCounters`](super::counters::StatusCounters).
+    pub dropped_samples: u64,
+    /// Fraction of time the Spinner spent processing events, from 0 to 1,
+    /// over the latest window of at least one second. `None` if the Spinner
+    /// is not running.
+    pub spinner_load: Option<f64>,
+}
+
+impl NodeStatistics {
+    pub(crate) fn collect(endpoints: &LocalEndpoints, spinner_load: Option<f64>) -> NodeStatistics {
+        let mut statistics = NodeStatistics {
+            publishers: endpoints.topic_endpoints(false).len(),
+            subscriptions: endpoints.topic_endpoints(true).len(),
+            servers: endpoints.service_endpoints(true).len(),
+            clients: endpoints.service_endpoints(false).len(),
+            spinner_load,
+            ..NodeStatistics::default()
+        };
+        for t in endpoints.topic_bandwidths() {
+            let total = if t.is_publisher {
+                &mut statistics.published
+            } else {
+                &mut statistics.received
+            };
+            total.messages_per_sec += t.bandwidth.messages_per_sec;
+            total.bytes_per_sec += t.bandwidth.bytes_per_sec;
+        }
+        statistics.dropped_samples = endpoints
+            .iter()
+            .filter_map(|e| e.counters.as_ref())
+            .map(|c| {
+                let c = c.snapshot();
+                c.samples_rejected + c.read_errors + c.write_errors
+            })
+            .sum();
+        statistics
+    }
+
+    /// The statistics as a diagnostics status named after the Node. This is
+    /// what is published on the statistics Topic.
+    pub fn to_diagnostic_status(&self, fully_qualified_node_name: &str) -> DiagnosticStatus {
+        let mut values = vec![
+            KeyValue::new("publishers", self.publishers.to_string()),
+            KeyValue::new("subscriptions", self.subscriptions.to_string()),
+            KeyValue::new("servers", self.servers.to_string()),
+            KeyValue::new("clients", self.clients.to_string()),
+            KeyValue::new(
+                "published_messages_per_sec",
+                format!("{:.2}", self.published.messages_per_sec),
+            ),
+            KeyValue::new(
+                "published_bytes_per_sec",
+                format!("{:.0}", self.published.bytes_per_sec),
+            ),
+            KeyValue::new(
+                "received_messages_per_sec",
+                format!("{:.2}", self.received.messages_per_sec),
+            ),
+            KeyValue::new(
+                "received_bytes_per_sec",
+                format!("{:.0}", self.received.bytes_per_sec),
+            ),
+            KeyValue::new("dropped_samples", self.dropped_samples.to_string()),
+        ];
+        if let Some(load) = self.spinner_load {
+            values.push(KeyValue::new("spinner_load", format!("{load:.3}")));
+        }
+        DiagnosticStatus {
+            level: DiagnosticStatus::OK,
+            name: format!("{fully_qualified_node_name}: statistics"),
+            message: String::new(),
+            hardware_id: String::new(),
+            values,
+        }
+    }
+}
+
+// Window over which the latest load is measured
+#[derive(Debug)]
+struct LoadWindow {
+    start: Instant,
+    start_busy_nanos: u64,
+    latest: f64,
+}
+
+// Time spent inside the Spinner future, as opposed to waiting for events
+#[derive(Debug)]
+pub(crate) struct LoadMeter {
+    busy_nanos: AtomicU64,
+    running: AtomicBool,
+    window: Mutex<LoadWindow>,
+}
+
+impl Default for LoadMeter {
+    fn default() -> Self {
+        LoadMeter {
+            busy_nanos: AtomicU64::default(),
+            running: AtomicBool::default(),
+            window: Mutex::new(LoadWindow {
+                start: Instant::now(),
+                start_busy_nanos: 0,
+                latest: 0.0,
+            }),
+        }
+    }
+}
+
+// Clears the running flag also when the measured future is dropped.
+struct Running<'a>(&'a AtomicBool);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+impl LoadMeter {
+    // Runs `future`, counting the time spent polling it as busy time.
+    pub(crate) async fn measure<F: Future>(&self, future: F) -> F::Output {
+        pin_mut!(future);
+        self.running.store(true, Ordering::Relaxed);
+        let _running = Running(&self.running);
+        future::poll_fn(|cx| {
+            let start = Instant::now();
+            let poll = future.as_mut().poll(cx);
+            let busy = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+            self.busy_nanos.fetch_add(busy, Ordering::Relaxed);
+            poll
+        })
+        .await
+    }
+
+    // Load over the latest complete window, if running. A new window starts
+    // when the previous one is at least LOAD_WINDOW long and the load is
+    // asked for.
+    pub(crate) fn load(&self) -> Option<f64> {
+        if !self.running.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(self.load_at(Instant::now(), self.busy_nanos.load(Ordering::Relaxed)))
+    }
+
+    fn load_at(&self, now: Instant, busy_nanos: u64) -> f64 {
+        let mut window = self.window.lock().unwrap();
+        let elapsed = now.saturating_duration_since(window.start);
+        if elapsed >= LOAD_WINDOW {
+            let busy = (busy_nanos - window.start_busy_nanos) as f64 / 1e9;
+            window.latest = (busy / elapsed.as_secs_f64()).min(1.0);
+            window.start = now;
+            window.start_busy_nanos = busy_nanos;
+        }
+        window.latest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::{
+        interfaces::std_msgs::Header,
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+    };
+
+    #[test]
+    fn load() {
+        let meter = LoadMeter::default();
+        assert_eq!(meter.load(), None);
+        let load_inside = block_on(meter.measure(async {
+            std::thread::sleep(Duration::from_millis(5));
+            meter.load()
+        }));
+        assert_eq!(load_inside, Some(0.0)); // Window not complete yet
+        assert_eq!(meter.load(), None);
+        assert!(meter.busy_nanos.load(Ordering::Relaxed) >= 5_000_000);
+
+        let start = meter.window.lock().unwrap().start;
+        let busy = meter.busy_nanos.load(Ordering::Relaxed);
+        let load = meter.load_at(start + Duration::from_secs(2), busy + 500_000_000);
+        assert!((load - 0.25).abs() < 0.01, "{load}");
+        // The latest load stays until the next window completes.
+        assert_eq!(meter.load_at(start + Duration::from_millis(2500), 0), load);
+    }
+
+    #[test]
+    fn diagnostic_status() {
+        let statistics = NodeStatistics {
+            publishers: 2,
+            published: Bandwidth {
+                messages_per_sec: 10.0,
+                bytes_per_sec: 1000.0,
+            },
+            dropped_samples: 3,
+            spinner_load: Some(0.125),
+            ..NodeStatistics::default()
+        };
+        let status = statistics.to_diagnostic_status("/ns/node");
+        assert_eq!(status.name, "/ns/node: statistics");
+        let value = |key: &str| {
+            status
+                .values
+                .iter()
+                .find(|kv| kv.key == key)
+                .map(|kv| kv.value.as_str())
+        };
+        assert_eq!(value("publishers"), Some("2"));
+        assert_eq!(value("published_messages_per_sec"), Some("10.00"));
+        assert_eq!(value("dropped_samples"), Some("3"));
+        assert_eq!(value("spinner_load"), Some("0.125"));
+    }
+
+    #[test]
+    fn node_statistics() {
+        use std::time::{Duration, Instant};
+
+        use crate::interfaces::diagnostic_msgs::DiagnosticArray;
+
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_statistics_node",
+            NodeOptions::new()
+                .enable_rosout(false)
+                .statistics_period(Duration::from_millis(20)),
+        );
+        let _publisher = node.advertise::<Header>("/statistics_test").unwrap();
+        let statistics = node.statistics();
+        assert_eq!(statistics.publishers, 1);
+        assert_eq!(statistics.spinner_load, None);
+
+        let subscription = node
+            .subscribe::<DiagnosticArray>("/rustdds/test_statistics_node/_statistics")
+            .unwrap();
+        let _spin_handle = node.spin_in_background().unwrap();
+        let start = Instant::now();
+        let received = loop {
+            if let Some((message, _)) = subscription.take().unwrap() {
+                break message;
+            }
+            assert!(start.elapsed() < Duration::from_secs(10), "No statistics");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let status = &received.status[0];
+        assert_eq!(status.name, "/rustdds/test_statistics_node: statistics");
+        // Now also the statistics Publisher
+        assert!(status
+            .values
+            .iter()
+            .any(|kv| kv.key == "publishers" && kv.value == "2"));
+        assert!(status.values.iter().any(|kv| kv.key == "spinner_load"));
+        assert_eq!(node.statistics().subscriptions, 1);
+    }
+}
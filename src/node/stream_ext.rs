@@ -0,0 +1,233 @@
+//! Combinators for streams of received messages
+//!
+//! [`MessageStreamExt`] adds timing-related adapters to any Stream of
+//! `ReadResult<(M, MessageInfo)>`, such as [`Subscription::async_stream`] or
+//! [`Subscription::into_stream`]:
+//!
+//! ```ignore
+//! use ros2_client::node::stream_ext::MessageStreamExt;
+//!
+//! let scans = node.subscribe::<LaserScan>("/scan")?.into_stream();
+//! // Process at 10 Hz, whatever the sensor rate, and notice if it stalls
+//! let mut scans = scans
+//!     .latest_every(Duration::from_millis(100))
+//!     .deadline(Duration::from_millis(500));
+//! while let Some(scan) = scans.next().await { ... }
+//! ```
+//!
+//! Timing is based on the message [source timestamps](MessageInfo::source_timestamp)
+//! and on system time, not on simulated ROS time.
+//!
+//! [`Subscription::async_stream`]: super::pubsub::Subscription::async_stream
+//! [`Subscription::into_stream`]: super::pubsub::Subscription::into_stream
+
+use std::{fmt, future::Future, pin::Pin, task::Poll, time::Duration};
+
+use futures::{stream, Stream, StreamExt};
+use futures_timer::Delay;
+use rustdds::{
+    dds::{ReadError, ReadResult},
+    Timestamp,
+};
+
+use crate::{message::message_info::MessageInfo, time::ros_time::ROSTime};
+
+/// Item error of [`MessageStreamExt::deadline`]
+#[derive(Debug)]
+pub enum StreamError {
+    /// Error from the underlying stream
+    Read(ReadError),
+    /// No message arrived for the given time.
+    DeadlineMissed { elapsed: Duration },
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Read(e) => write!(f, "{e}"),
+            StreamError::DeadlineMissed { elapsed } => {
+                write!(f, "No message received for {elapsed:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<ReadError> for StreamError {
+    fn from(e: ReadError) -> StreamError {
+        StreamError::Read(e)
+    }
+}
+
+/// Extension methods for Streams of messages. See the
+/// [module documentation](self).
+pub trait MessageStreamExt<M>: Stream<Item = ReadResult<(M, MessageInfo)>> + Sized {
+    /// Adds the age of each message, i.e. the time from its source
+    /// timestamp to now.
+    ///
+    /// Messages without a source timestamp use the reception time. A source
+    /// clock ahead of the local one gives zero age.
+    fn with_age(self) -> impl Stream<Item = ReadResult<(M, MessageInfo, Duration)>> {
+        self.map(|result| {
+            result.map(|(message, info)| {
+                let age = message_age(&info, Timestamp::now());
+                (message, info, age)
+            })
+        })
+    }
+
+    /// Yields [`StreamError::DeadlineMissed`] each time no message has
+    /// arrived for `max_gap`, counting from the creation of the Stream.
+    ///
+    /// The Stream goes on after a missed deadline, so the caller decides
+    /// whether to stop or to degrade gracefully.
+    fn deadline(
+        self,
+        max_gap: Duration,
+    ) -> impl Stream<Item = Result<(M, MessageInfo), StreamError>> {
+        let mut inner = Box::pin(self);
+        let mut timer = Delay::new(max_gap);
+        stream::poll_fn(move |cx| {
+            if let Poll::Ready(item) = inner.as_mut().poll_next(cx) {
+                timer.reset(max_gap);
+                return Poll::Ready(item.map(|result| result.map_err(StreamError::from)));
+            }
+            match Pin::new(&mut timer).poll(cx) {
+                Poll::Ready(()) => {
+                    timer.reset(max_gap);
+                    Poll::Ready(Some(Err(StreamError::DeadlineMissed { elapsed: max_gap })))
+                }
+                Poll::Pending => Poll::Pending,
+            }
+        })
+    }
+
+    /// Yields the latest message once every `period`, repeating it if no
+    /// newer one has arrived (sample and hold).
+    ///
+    /// Nothing is yielded until the first message arrives. Errors are passed
+    /// on immediately. The Stream ends when the underlying one ends.
+    fn latest_every(self, period: Duration) -> impl Stream<Item = ReadResult<(M, MessageInfo)>>
+    where
+        M: Clone,
+    {
+        let mut inner = Box::pin(self);
+        let mut timer = Delay::new(period);
+        let mut latest: Option<(M, MessageInfo)> = None;
+        stream::poll_fn(move |cx| {
+            loop {
+                match inner.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(Ok(received))) => latest = Some(received),
+                    Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                    Poll::Ready(None) => return Poll::Ready(None),
+                    Poll::Pending => break,
+                }
+            }
+            loop {
+                match Pin::new(&mut timer).poll(cx) {
+                    Poll::Ready(()) => {
+                        timer.reset(period);
+                        if let Some(latest) = &latest {
+                            return Poll::Ready(Some(Ok(latest.clone())));
+                        }
+                    }
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+        })
+    }
+}
+
+impl<M, S> MessageStreamExt<M> for S where S: Stream<Item = ReadResult<(M, MessageInfo)>> {}
+
+fn message_age(info: &MessageInfo, now: Timestamp) -> Duration {
+    let stamp = info.source_timestamp().unwrap_or(info.received_timestamp());
+    match (ROSTime::try_from(stamp), ROSTime::try_from(now)) {
+        (Ok(stamp), Ok(now)) => Duration::try_from(now - stamp).unwrap_or(Duration::ZERO),
+        _ => Duration::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use rustdds::{SequenceNumber, GUID};
+
+    use super::*;
+    use crate::{
+        interfaces::std_msgs::Header,
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+    };
+
+    fn message(n: i64, stamp: ROSTime) -> ReadResult<(i64, MessageInfo)> {
+        Ok((
+            n,
+            MessageInfo::recovered(
+                GUID::from_bytes([1; 16]),
+                SequenceNumber::from(n),
+                Timestamp::from(stamp),
+            ),
+        ))
+    }
+
+    #[test]
+    fn with_age() {
+        let stamp = ROSTime::from_nanos(ROSTime::now().to_nanos() - 2_000_000_000);
+        let mut aged = stream::iter(vec![message(1, stamp)]).with_age();
+        let (n, _info, age) = block_on(aged.next()).unwrap().unwrap();
+        assert_eq!(n, 1);
+        assert!(age >= Duration::from_secs(2) && age < Duration::from_secs(60));
+
+        // Source clock ahead of ours
+        let future = ROSTime::from_nanos(ROSTime::now().to_nanos() + 10_000_000_000);
+        let mut aged = stream::iter(vec![message(2, future)]).with_age();
+        assert_eq!(block_on(aged.next()).unwrap().unwrap().2, Duration::ZERO);
+    }
+
+    #[test]
+    fn deadline() {
+        let silent = stream::iter(vec![message(1, ROSTime::now())]).chain(stream::pending());
+        let mut checked = Box::pin(silent.deadline(Duration::from_millis(20)));
+        assert!(matches!(block_on(checked.next()), Some(Ok((1, _)))));
+        assert!(matches!(
+            block_on(checked.next()),
+            Some(Err(StreamError::DeadlineMissed { .. }))
+        ));
+        assert!(matches!(
+            block_on(checked.next()),
+            Some(Err(StreamError::DeadlineMissed { .. }))
+        ));
+    }
+
+    #[test]
+    fn latest_every() {
+        let now = ROSTime::now();
+        let messages =
+            stream::iter(vec![message(1, now), message(2, now)]).chain(stream::pending());
+        let mut sampled = Box::pin(messages.latest_every(Duration::from_millis(10)));
+        // The latest one, held
+        assert_eq!(block_on(sampled.next()).unwrap().unwrap().0, 2);
+        assert_eq!(block_on(sampled.next()).unwrap().unwrap().0, 2);
+    }
+
+    #[test]
+    fn subscription_into_stream() {
+        use futures::stream::FusedStream;
+
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_into_stream_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let subscription = node.subscribe::<Header>("/into_stream_test").unwrap();
+        let stream = subscription.into_stream();
+        // The stream owns the Subscription, so it can move to another thread.
+        let handle = std::thread::spawn(move || stream.is_terminated());
+        assert!(!handle.join().unwrap());
+    }
+}
@@ -0,0 +1,764 @@
+//! Restarting failed Node tasks
+//!
+//! A [`Supervisor`] owns a set of local Nodes and runs their tasks: the
+//! [`Spinner`](super::Spinner) and application tasks created with
+//! [`Supervisor::add_task`]. When a task fails, i.e. returns an error,
+//! panics, or stops sending heartbeats, it is restarted according to its
+//! [`RestartOptions`]. Restarts are logged to the Node's rosout, and can be
+//! published as diagnostics.
+//!
+//! This is like a small launch supervisor inside one process. Tasks are
+//! created by a factory function, which gets the Node to create its
+//! Publishers and Subscriptions again for each restart.
+//!
+//! The Supervisor has no timers of its own: [`Supervisor::poll`] must be
+//! called periodically, e.g. every 100 ms, to detect missed heartbeats and
+//! start restarts that are due.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use futures::FutureExt;
+//! use ros2_client::{
+//!     interfaces::geometry_msgs::Vector3,
+//!     node::supervisor::{RestartOptions, Supervisor},
+//!     prelude::*,
+//! };
+//!
+//! let context = Context::new().unwrap();
+//! let node = context
+//!     .new_node(NodeName::new("/", "worker").unwrap(), NodeOptions::new())
+//!     .unwrap();
+//!
+//! let mut supervisor = Supervisor::new();
+//! let worker = supervisor
+//!     .add_node(node, Some(RestartOptions::new()))
+//!     .unwrap();
+//! supervisor
+//!     .add_task(
+//!         worker,
+//!         "counter",
+//!         RestartOptions::new().heartbeat_timeout(Duration::from_secs(1)),
+//!         |node, heartbeat| {
+//!             let publisher = node.advertise::<Vector3>("/position")?;
+//!             Ok(async move {
+//!                 for i in 0.. {
+//!                     heartbeat.beat();
+//!                     let position = Vector3 { x: f64::from(i), y: 0.0, z: 0.0 };
+//!                     publisher.async_publish(position).await.map_err(|e| format!("{e:?}"))?;
+//!                 }
+//!                 Ok(())
+//!             }
+//!             .boxed())
+//!         },
+//!     )
+//!     .unwrap();
+//!
+//! loop {
+//!     supervisor.poll();
+//!     std::thread::sleep(Duration::from_millis(100));
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{
+    executor,
+    future::{AbortHandle, Abortable, BoxFuture},
+    FutureExt,
+};
+use log::{error, warn};
+use rustdds::dds::{CreateError, CreateResult};
+
+use super::{pubsub::Publisher, Node};
+use crate::{
+    interfaces::{diagnostic_msgs, std_msgs::Header},
+    log::LogLevel,
+    time::ros_time::ROSTime,
+};
+
+/// Name of the task running the Spinner of a supervised Node
+pub const SPINNER_TASK: &str = "spinner";
+
+/// When to restart a task that has stopped
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Never restart.
+    Never,
+    /// Restart if the task returns an error, panics, or misses its heartbeat.
+    #[default]
+    OnFailure,
+    /// Restart also if the task returns `Ok`.
+    Always,
+}
+
+/// How a task is restarted
+#[derive(Clone, Debug)]
+pub struct RestartOptions {
+    policy: RestartPolicy,
+    max_restarts: u32,
+    restart_window: Duration,
+    restart_delay: Duration,
+    heartbeat_timeout: Option<Duration>,
+}
+
+impl RestartOptions {
+    /// Restart on failure after 1 second, at most 5 times per minute. There is
+    /// no heartbeat timeout.
+    pub fn new() -> RestartOptions {
+        RestartOptions {
+            policy: RestartPolicy::OnFailure,
+            max_restarts: 5,
+            restart_window: Duration::from_secs(60),
+            restart_delay: Duration::from_secs(1),
+            heartbeat_timeout: None,
+        }
+    }
+
+    pub fn policy(self, policy: RestartPolicy) -> RestartOptions {
+        RestartOptions { policy, ..self }
+    }
+
+    /// Give up, if the task would be restarted more than `max_restarts` times
+    /// within `window`.
+    pub fn max_restarts(self, max_restarts: u32, window: Duration) -> RestartOptions {
+        RestartOptions {
+            max_restarts,
+            restart_window: window,
+            ..self
+        }
+    }
+
+    /// Wait for `restart_delay` after a failure before restarting.
+    pub fn restart_delay(self, restart_delay: Duration) -> RestartOptions {
+        RestartOptions {
+            restart_delay,
+            ..self
+        }
+    }
+
+    /// The task has failed, if it has not called [`Heartbeat::beat`] for
+    /// `timeout`. It is then cancelled and handled like any other failure.
+    pub fn heartbeat_timeout(self, timeout: Duration) -> RestartOptions {
+        RestartOptions {
+            heartbeat_timeout: Some(timeout),
+            ..self
+        }
+    }
+}
+
+impl Default for RestartOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Given to each task for telling the [`Supervisor`] that it is alive
+///
+/// Only needed if the task has a
+/// [heartbeat timeout](RestartOptions::heartbeat_timeout).
+#[derive(Clone, Debug)]
+pub struct Heartbeat {
+    last: Arc<Mutex<Instant>>,
+}
+
+impl Heartbeat {
+    fn new() -> Heartbeat {
+        Heartbeat {
+            last: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn beat(&self) {
+        *self.last.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since the last beat, or since the task was started
+    pub fn elapsed(&self) -> Duration {
+        self.last.lock().unwrap().elapsed()
+    }
+}
+
+/// Identifies a Node in a [`Supervisor`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeIndex(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    /// Waiting for the restart delay, or for a cancelled task to end
+    Restarting,
+    /// Ended, and is not restarted
+    Finished,
+    /// Failed, and is not restarted
+    Failed,
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TaskState::Running => "running",
+            TaskState::Restarting => "restarting",
+            TaskState::Finished => "finished",
+            TaskState::Failed => "failed",
+        };
+        f.write_str(s)
+    }
+}
+
+/// State of a supervised task, from [`Supervisor::status`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskStatus {
+    /// Fully qualified name of the Node
+    pub node: String,
+    pub task: String,
+    pub state: TaskState,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+type TaskFactory =
+    Box<dyn FnMut(&mut Node, Heartbeat) -> CreateResult<BoxFuture<'static, Result<(), String>>>>;
+
+struct Task {
+    node: usize,
+    name: String,
+    options: RestartOptions,
+    factory: TaskFactory,
+    state: TaskState,
+    // Some, while the spawned task has not reported its end
+    abort: Option<AbortHandle>,
+    heartbeat: Heartbeat,
+    // Identifies the current run, so that reports from cancelled runs are
+    // recognized
+    run: u64,
+    // Reason for cancelling the current run
+    cancel_reason: Option<String>,
+    restart_at: Option<Instant>,
+    restart_times: VecDeque<Instant>,
+    restarts: u32,
+    last_error: Option<String>,
+}
+
+// End of a spawned task
+struct TaskEnd {
+    task: usize,
+    run: u64,
+    result: Result<(), String>,
+}
+
+/// Runs tasks of local Nodes and restarts them on failure. See the
+/// [module documentation](self).
+pub struct Supervisor {
+    nodes: Vec<Node>,
+    tasks: Vec<Task>,
+    spawn: Box<dyn FnMut(BoxFuture<'static, ()>)>,
+    end_sender: async_channel::Sender<TaskEnd>,
+    end_receiver: async_channel::Receiver<TaskEnd>,
+    diagnostics_publisher: Option<Publisher<diagnostic_msgs::DiagnosticArray>>,
+    liveliness_interval: Option<Duration>,
+    last_liveliness: Option<Instant>,
+}
+
+impl Supervisor {
+    /// Supervisor that runs each task in a thread of its own
+    pub fn new() -> Supervisor {
+        Supervisor::with_spawner(|task| {
+            let builder = std::thread::Builder::new().name("supervised task".to_string());
+            if let Err(e) = builder.spawn(move || executor::block_on(task)) {
+                error!("Cannot start supervised task thread: {e}");
+            }
+        })
+    }
+
+    /// Supervisor that runs tasks with `spawn`, e.g.
+    /// `Supervisor::with_spawner(|task| smol::spawn(task).detach())`
+    pub fn with_spawner<F>(spawn: F) -> Supervisor
+    where
+        F: FnMut(BoxFuture<'static, ()>) + 'static,
+    {
+        let (end_sender, end_receiver) = async_channel::unbounded();
+        Supervisor {
+            nodes: Vec::new(),
+            tasks: Vec::new(),
+            spawn: Box::new(spawn),
+            end_sender,
+            end_receiver,
+            diagnostics_publisher: None,
+            liveliness_interval: None,
+            last_liveliness: None,
+        }
+    }
+
+    /// Publish [`Self::diagnostics`] with `publisher` whenever a task changes
+    /// state. The usual Topic is `/diagnostics`.
+    pub fn publish_diagnostics(&mut self, publisher: Publisher<diagnostic_msgs::DiagnosticArray>) {
+        self.diagnostics_publisher = Some(publisher);
+    }
+
+    /// Assert DDS liveliness every `interval` while no task is failed or
+    /// restarting.
+    ///
+    /// Remote Subscriptions with `ManualByParticipant` Liveliness QoS then
+    /// notice when the tasks of this process stop working, not only when the
+    /// process dies. Note that the Nodes of a Context share one
+    /// DomainParticipant, so liveliness is per Context, not per Node.
+    pub fn assert_liveliness(&mut self, interval: Duration) {
+        self.liveliness_interval = Some(interval);
+    }
+
+    /// Take `node` under supervision.
+    ///
+    /// If `spinner_options` is given, the Spinner of the Node is run as task
+    /// [`SPINNER_TASK`]. The Node must not have a Spinner already.
+    pub fn add_node(
+        &mut self,
+        node: Node,
+        spinner_options: Option<RestartOptions>,
+    ) -> CreateResult<NodeIndex> {
+        let index = NodeIndex(self.nodes.len());
+        self.nodes.push(node);
+        if let Some(options) = spinner_options {
+            self.add_task(index, SPINNER_TASK, options, |node, _heartbeat| {
+                let spinner = node.spinner()?;
+                Ok(async move {
+                    spinner
+                        .spin()
+                        .await
+                        .map_err(|e| format!("Spinner failed: {e:?}"))
+                }
+                .boxed())
+            })?;
+        }
+        Ok(index)
+    }
+
+    pub fn node(&self, index: NodeIndex) -> &Node {
+        &self.nodes[index.0]
+    }
+
+    pub fn node_mut(&mut self, index: NodeIndex) -> &mut Node {
+        &mut self.nodes[index.0]
+    }
+
+    /// Start a task of Node `node`.
+    ///
+    /// `factory` creates the task, now and for each restart. It gets the Node
+    /// and the [`Heartbeat`] of the task.
+    pub fn add_task<F>(
+        &mut self,
+        node: NodeIndex,
+        name: &str,
+        options: RestartOptions,
+        factory: F,
+    ) -> CreateResult<()>
+    where
+        F: FnMut(&mut Node, Heartbeat) -> CreateResult<BoxFuture<'static, Result<(), String>>>
+            + 'static,
+    {
+        if node.0 >= self.nodes.len() {
+            return Err(CreateError::BadParameter {
+                reason: format!("No Node with {node:?}"),
+            });
+        }
+        if self
+            .tasks
+            .iter()
+            .any(|t| t.node == node.0 && t.name == name)
+        {
+            return Err(CreateError::BadParameter {
+                reason: format!("Task {name:?} already exists"),
+            });
+        }
+        self.tasks.push(Task {
+            node: node.0,
+            name: name.to_owned(),
+            options,
+            factory: Box::new(factory),
+            state: TaskState::Restarting,
+            abort: None,
+            heartbeat: Heartbeat::new(),
+            run: 0,
+            cancel_reason: None,
+            restart_at: None,
+            restart_times: VecDeque::new(),
+            restarts: 0,
+            last_error: None,
+        });
+        let index = self.tasks.len() - 1;
+        // A task that cannot be created at all is not added.
+        self.start(index).inspect_err(|_| {
+            self.tasks.pop();
+        })
+    }
+
+    /// Handle ended tasks, missed heartbeats and due restarts.
+    ///
+    /// Returns the number of tasks restarted.
+    pub fn poll(&mut self) -> usize {
+        let mut changed = false;
+
+        while let Ok(end) = self.end_receiver.try_recv() {
+            let task = &mut self.tasks[end.task];
+            if end.run != task.run {
+                continue;
+            }
+            task.abort = None;
+            let result = match task.cancel_reason.take() {
+                Some(reason) => Err(reason),
+                None => end.result,
+            };
+            if task.state == TaskState::Running {
+                self.task_ended(end.task, result);
+                changed = true;
+            }
+        }
+
+        for index in 0..self.tasks.len() {
+            let task = &mut self.tasks[index];
+            let Some(timeout) = task.options.heartbeat_timeout else {
+                continue;
+            };
+            if task.state == TaskState::Running && task.heartbeat.elapsed() > timeout {
+                let reason = format!("No heartbeat for {timeout:?}");
+                if let Some(abort) = &task.abort {
+                    abort.abort();
+                }
+                // The restart is scheduled now, but started only after the
+                // cancelled run has ended.
+                task.cancel_reason = Some(reason.clone());
+                self.task_ended(index, Err(reason));
+                changed = true;
+            }
+        }
+
+        let now = Instant::now();
+        let mut restarted = 0;
+        for index in 0..self.tasks.len() {
+            let task = &self.tasks[index];
+            let due = task.state == TaskState::Restarting
+                && task.abort.is_none()
+                && task.restart_at.is_some_and(|at| at <= now);
+            if !due {
+                continue;
+            }
+            let task = &mut self.tasks[index];
+            task.restarts += 1;
+            task.restart_times.push_back(now);
+            crate::rosout!(
+                self.nodes[task.node],
+                LogLevel::Warn,
+                "Restarting task {:?} (restart {})",
+                task.name,
+                task.restarts
+            );
+            match self.start(index) {
+                Ok(()) => restarted += 1,
+                Err(e) => self.task_ended(index, Err(format!("Cannot create task: {e:?}"))),
+            }
+            changed = true;
+        }
+
+        if changed {
+            if let Some(publisher) = &self.diagnostics_publisher {
+                publisher
+                    .publish(self.diagnostics())
+                    .unwrap_or_else(|e| warn!("Cannot publish supervisor diagnostics: {e:?}"));
+            }
+        }
+        self.maybe_assert_liveliness(now);
+
+        restarted
+    }
+
+    /// State of all tasks
+    pub fn status(&self) -> Vec<TaskStatus> {
+        self.tasks
+            .iter()
+            .map(|t| TaskStatus {
+                node: self.nodes[t.node].fully_qualified_name(),
+                task: t.name.clone(),
+                state: t.state,
+                restarts: t.restarts,
+                last_error: t.last_error.clone(),
+            })
+            .collect()
+    }
+
+    /// True, if no task is running or will be restarted
+    pub fn is_finished(&self) -> bool {
+        self.tasks
+            .iter()
+            .all(|t| matches!(t.state, TaskState::Finished | TaskState::Failed))
+    }
+
+    /// [`Self::status`] as a diagnostics message, e.g. for Topic
+    /// `/diagnostics`
+    ///
+    /// There is one status per task, with values `restarts` and
+    /// `last_error`.
+    pub fn diagnostics(&self) -> diagnostic_msgs::DiagnosticArray {
+        let status = self
+            .status()
+            .into_iter()
+            .map(|s| diagnostic_msgs::DiagnosticStatus {
+                level: match s.state {
+                    TaskState::Running | TaskState::Finished => {
+                        diagnostic_msgs::DiagnosticStatus::OK
+                    }
+                    TaskState::Restarting => diagnostic_msgs::DiagnosticStatus::WARN,
+                    TaskState::Failed => diagnostic_msgs::DiagnosticStatus::ERROR,
+                },
+                name: format!("{}: {}", s.node, s.task),
+                message: s.state.to_string(),
+                hardware_id: String::new(),
+                values: vec![
+                    diagnostic_msgs::KeyValue::new("restarts", s.restarts.to_string()),
+                    diagnostic_msgs::KeyValue::new("last_error", s.last_error.unwrap_or_default()),
+                ],
+            })
+            .collect();
+        diagnostic_msgs::DiagnosticArray {
+            header: Header {
+                stamp: ROSTime::now().into(),
+                frame_id: String::new(),
+            },
+            status,
+        }
+    }
+
+    /// Cancel all tasks. They are not restarted.
+    pub fn stop(&mut self) {
+        for task in &mut self.tasks {
+            if let Some(abort) = &task.abort {
+                abort.abort();
+            }
+            if matches!(task.state, TaskState::Running | TaskState::Restarting) {
+                task.state = TaskState::Finished;
+            }
+        }
+    }
+
+    fn start(&mut self, index: usize) -> CreateResult<()> {
+        let task = &mut self.tasks[index];
+        let heartbeat = Heartbeat::new();
+        let future = (task.factory)(&mut self.nodes[task.node], heartbeat.clone())?;
+        task.run += 1;
+        task.heartbeat = heartbeat;
+        task.state = TaskState::Running;
+        task.restart_at = None;
+
+        let (abort, registration) = AbortHandle::new_pair();
+        task.abort = Some(abort);
+        let end_sender = self.end_sender.clone();
+        let run = task.run;
+        let wrapped = async move {
+            // The task is dropped here, before reporting the end, so that a
+            // restarted Spinner does not find the previous one still alive.
+            let result =
+                match Abortable::new(AssertUnwindSafe(future).catch_unwind(), registration).await {
+                    Ok(Ok(result)) => result,
+                    Ok(Err(_panic)) => Err("Task panicked".to_string()),
+                    Err(_aborted) => Err("Task cancelled".to_string()),
+                };
+            // The Supervisor may have been dropped.
+            let _ = end_sender.try_send(TaskEnd {
+                task: index,
+                run,
+                result,
+            });
+        }
+        .boxed();
+        (self.spawn)(wrapped);
+        Ok(())
+    }
+
+    // Decide what to do with a task whose run has ended or been cancelled.
+    fn task_ended(&mut self, index: usize, result: Result<(), String>) {
+        let task = &mut self.tasks[index];
+        let node = &self.nodes[task.node];
+        let restart = match &result {
+            Ok(()) => task.options.policy == RestartPolicy::Always,
+            Err(e) => {
+                crate::rosout!(node, LogLevel::Error, "Task {:?} failed: {e}", task.name);
+                task.last_error = Some(e.clone());
+                task.options.policy != RestartPolicy::Never
+            }
+        };
+        if !restart {
+            task.state = if result.is_ok() {
+                TaskState::Finished
+            } else {
+                TaskState::Failed
+            };
+            return;
+        }
+
+        let now = Instant::now();
+        let window = task.options.restart_window;
+        while task
+            .restart_times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > window)
+        {
+            task.restart_times.pop_front();
+        }
+        if task.restart_times.len() >= task.options.max_restarts as usize {
+            crate::rosout!(
+                node,
+                LogLevel::Fatal,
+                "Task {:?} restarted {} times within {window:?}, giving up",
+                task.name,
+                task.restart_times.len()
+            );
+            task.state = TaskState::Failed;
+        } else {
+            task.state = TaskState::Restarting;
+            task.restart_at = Some(now + task.options.restart_delay);
+        }
+    }
+
+    fn maybe_assert_liveliness(&mut self, now: Instant) {
+        let Some(interval) = self.liveliness_interval else {
+            return;
+        };
+        if self
+            .last_liveliness
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return;
+        }
+        let healthy = self
+            .tasks
+            .iter()
+            .all(|t| matches!(t.state, TaskState::Running | TaskState::Finished));
+        if healthy {
+            self.last_liveliness = Some(now);
+            // Nodes in the same Context share a DomainParticipant, but they
+            // may be in different Contexts.
+            for node in &self.nodes {
+                node.assert_liveliness()
+                    .unwrap_or_else(|e| warn!("Cannot assert liveliness: {e:?}"));
+            }
+        }
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{
+        context::{tests::test_node, Context},
+        NodeOptions,
+    };
+
+    #[test]
+    fn restarts_tasks() {
+        use std::{
+            sync::atomic::{AtomicU32, Ordering},
+            time::{Duration, Instant},
+        };
+
+        use futures::FutureExt;
+
+        let context = Context::new().unwrap();
+
+        // A Spinner can be created again after the previous one is dropped.
+        let mut node = test_node(&context, "test_respawn_node", NodeOptions::new());
+        drop(node.spinner().unwrap());
+        assert!(node.spinner().is_ok());
+
+        let node = test_node(&context, "test_supervised_node", NodeOptions::new());
+        let mut supervisor = Supervisor::new();
+        let node = supervisor
+            .add_node(node, Some(RestartOptions::new()))
+            .unwrap();
+
+        // Fails twice, then succeeds
+        let attempts = Arc::new(AtomicU32::new(0));
+        let flaky_attempts = Arc::clone(&attempts);
+        supervisor
+            .add_task(
+                node,
+                "flaky",
+                RestartOptions::new().restart_delay(Duration::ZERO),
+                move |_node, _heartbeat| {
+                    let attempt = flaky_attempts.fetch_add(1, Ordering::SeqCst);
+                    Ok(async move {
+                        if attempt < 2 {
+                            Err(format!("attempt {attempt}"))
+                        } else {
+                            Ok(())
+                        }
+                    }
+                    .boxed())
+                },
+            )
+            .unwrap();
+        // Never sends a heartbeat
+        supervisor
+            .add_task(
+                node,
+                "stuck",
+                RestartOptions::new()
+                    .restart_delay(Duration::ZERO)
+                    .max_restarts(1, Duration::from_secs(60))
+                    .heartbeat_timeout(Duration::from_millis(50)),
+                |_node, _heartbeat| Ok(futures::future::pending().boxed()),
+            )
+            .unwrap();
+        assert!(supervisor
+            .add_task(node, "stuck", RestartOptions::new(), |_, _| Ok(
+                futures::future::pending().boxed()
+            ))
+            .is_err());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !supervisor
+            .status()
+            .iter()
+            .filter(|s| s.task != SPINNER_TASK)
+            .all(|s| matches!(s.state, TaskState::Finished | TaskState::Failed))
+        {
+            assert!(Instant::now() < deadline, "{:?}", supervisor.status());
+            supervisor.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let status = supervisor.status();
+        assert_eq!(status[0].task, SPINNER_TASK);
+        assert_eq!(status[0].state, TaskState::Running);
+        assert_eq!(
+            (status[1].state, status[1].restarts),
+            (TaskState::Finished, 2)
+        );
+        assert_eq!(status[1].last_error.as_deref(), Some("attempt 1"));
+        assert_eq!(
+            (status[2].state, status[2].restarts),
+            (TaskState::Failed, 1)
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(supervisor.diagnostics().status.len(), 3);
+        assert!(!supervisor.is_finished());
+        supervisor.stop();
+        assert!(supervisor.is_finished());
+    }
+}
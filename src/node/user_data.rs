@@ -0,0 +1,146 @@
+//! Participant USER_DATA in the ROS 2 key-value format
+//!
+//! ROS 2 implementations put metadata into the DDS USER_DATA QoS policy of
+//! the DomainParticipant, as `key=value;` pairs. The security enclave of the
+//! Context is sent as `enclave=/my/enclave;`, and endpoints carry their type
+//! hash as `typehash=RIHS01_...;`.
+//!
+//! [`UserData`] reads and writes this format. Set it on a Context with
+//! [`ContextOptions::user_data`](super::context::ContextOptions::user_data)
+//! or [`ContextOptions::enclave`](super::context::ContextOptions::enclave),
+//! and read it back with
+//! [`Context::user_data`](super::context::Context::user_data).
+//!
+//! Note: RustDDS 0.11 does not yet send or parse the USER_DATA policy. The
+//! data set here is therefore not visible to peers, and peers' USER_DATA
+//! cannot be read from Discovery. [`UserData::parse`] can still be used on
+//! USER_DATA obtained elsewhere, e.g. from a packet capture.
+
+use std::{collections::BTreeMap, fmt};
+
+/// Key-value pairs of USER_DATA
+///
+/// Keys are unique. Formatting writes them in alphabetical order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserData {
+    entries: BTreeMap<String, String>,
+}
+
+impl UserData {
+    pub fn new() -> UserData {
+        UserData::default()
+    }
+
+    /// Parse USER_DATA bytes.
+    ///
+    /// This is lenient like the parser of `rmw_dds_common`: Items without
+    /// `=` or with an empty key are skipped, and bytes that are not UTF-8
+    /// are replaced.
+    pub fn parse(bytes: &[u8]) -> UserData {
+        let entries = bytes
+            .split(|b| *b == b';')
+            .filter_map(|item| {
+                let eq = item.iter().position(|b| *b == b'=')?;
+                let (key, value) = (&item[..eq], &item[eq + 1..]);
+                (!key.is_empty()).then(|| {
+                    (
+                        String::from_utf8_lossy(key).into_owned(),
+                        String::from_utf8_lossy(value).into_owned(),
+                    )
+                })
+            })
+            .collect();
+        UserData { entries }
+    }
+
+    /// The USER_DATA bytes, e.g. `enclave=/;`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Set `key` to `value`.
+    ///
+    /// Keys must not contain `=` or `;`, and values must not contain `;`,
+    /// otherwise the data would not parse back the same.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if key.is_empty() || key.contains(['=', ';']) {
+            return Err(format!("Bad USER_DATA key {key:?}"));
+        }
+        if value.contains(';') {
+            return Err(format!("USER_DATA value for {key:?} contains ';'"));
+        }
+        self.entries.insert(key.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.entries.remove(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The security enclave, key `enclave`
+    pub fn enclave(&self) -> Option<&str> {
+        self.get("enclave")
+    }
+}
+
+impl fmt::Display for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.entries {
+            write!(f, "{key}={value};")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::context::{Context, ContextOptions};
+
+    #[test]
+    fn parse_and_format() {
+        let data = UserData::parse(b"enclave=/robot/arm;typehash=RIHS01_abc;");
+        assert_eq!(data.enclave(), Some("/robot/arm"));
+        assert_eq!(data.get("typehash"), Some("RIHS01_abc"));
+        assert_eq!(data.to_bytes(), b"enclave=/robot/arm;typehash=RIHS01_abc;");
+
+        // Lenient: junk items are skipped, only the first '=' separates.
+        let data = UserData::parse(b"junk;=x;a=b=c;;empty=");
+        assert_eq!(
+            data.iter().collect::<Vec<_>>(),
+            [("a", "b=c"), ("empty", "")]
+        );
+    }
+
+    #[test]
+    fn set_rejects_separators() {
+        let mut data = UserData::new();
+        data.set("enclave", "/").unwrap();
+        assert!(data.set("a=b", "c").is_err());
+        assert!(data.set("", "c").is_err());
+        assert!(data.set("a", "b;c").is_err());
+        assert_eq!(data.to_string(), "enclave=/;");
+        assert_eq!(data.remove("enclave"), Some("/".to_owned()));
+        assert!(data.to_bytes().is_empty());
+    }
+
+    #[test]
+    fn context_user_data() {
+        let context = Context::with_options(
+            ContextOptions::new()
+                .enclave("/robot/arm")
+                .enclave("bad;enclave"),
+        )
+        .unwrap();
+        assert_eq!(context.user_data().enclave(), Some("/robot/arm"));
+        assert_eq!(Context::new().unwrap().user_data().enclave(), None);
+    }
+}
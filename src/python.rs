@@ -0,0 +1,352 @@
+//! Python bindings
+//!
+//! This is a Python extension module `ros2_client` with Contexts, Nodes,
+//! Publishers and Subscriptions. Messages are Python dicts, converted with
+//! [`DynamicType`](crate::message::dynamic::DynamicType), so no generated
+//! Python message classes are needed. This is meant for scripting and for
+//! testing against the same middleware implementation as Rust Nodes in
+//! production. Build the module with e.g.
+//!
+//! ```text
+//! maturin build --release --features python,pyo3/extension-module
+//! ```
+//!
+//! and use it like this:
+//!
+//! ```python
+//! import ros2_client
+//!
+//! context = ros2_client.Context(domain_id=0)
+//! node = context.create_node("/", "script")
+//! publisher = node.create_publisher("/chatter", "std_msgs/msg/String", "string data")
+//! subscription = node.create_subscription("/chatter", "std_msgs/msg/String", "string data")
+//! publisher.publish({"data": "Hello"})
+//! print(subscription.receive(timeout=1.0))
+//! ```
+//!
+//! The last argument of `create_publisher` and `create_subscription` is the
+//! message definition, in the format described in
+//! [`dynamic`](crate::message::dynamic). Integer fields are Python ints,
+//! `byte` and `uint8` arrays are `bytes`, other arrays are lists, and nested
+//! messages are dicts.
+//!
+//! A Node runs its Spinner in a background thread. `Subscription.receive`
+//! releases the GIL while waiting.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{executor, pin_mut, select, FutureExt, StreamExt};
+use futures_timer::Delay;
+use log::warn;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyBool, PyByteArray, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple},
+    IntoPyObjectExt,
+};
+use rustdds::dds::WriteError;
+
+use crate::{
+    message::dynamic::{DynamicMessage, DynamicType, DynamicValue},
+    node::{
+        context::{Context, ContextOptions, DEFAULT_PUBLISHER_QOS, DEFAULT_SUBSCRIPTION_QOS},
+        pubsub::{Publisher, Subscription},
+        spin_handle::SpinHandle,
+        Node, NodeOptions,
+    },
+    prelude::{MessageTypeName, Name, NodeName},
+};
+
+fn runtime_error(e: impl fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// A ROS 2 Context, i.e. a DDS DomainParticipant
+#[pyclass(name = "Context", module = "ros2_client")]
+struct PyContext {
+    context: Context,
+}
+
+#[pymethods]
+impl PyContext {
+    #[new]
+    #[pyo3(signature = (domain_id = 0))]
+    fn new(domain_id: u16) -> PyResult<PyContext> {
+        Context::with_options(ContextOptions::new().domain_id(domain_id))
+            .map(|context| PyContext { context })
+            .map_err(runtime_error)
+    }
+
+    #[getter]
+    fn domain_id(&self) -> u16 {
+        self.context.domain_id()
+    }
+
+    /// Creates a Node and starts its Spinner.
+    fn create_node(&self, namespace: &str, name: &str) -> PyResult<PyNode> {
+        let node_name =
+            NodeName::new(namespace, name).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut node = self
+            .context
+            .new_node(node_name, NodeOptions::new())
+            .map_err(runtime_error)?;
+        let spin_handle = node.spin_in_background().map_err(runtime_error)?;
+        Ok(PyNode {
+            node: Mutex::new(node),
+            spin_handle: Some(spin_handle),
+        })
+    }
+}
+
+/// A Node with its Spinner running
+#[pyclass(name = "Node", module = "ros2_client")]
+struct PyNode {
+    // Python objects must be Sync.
+    node: Mutex<Node>,
+    spin_handle: Option<SpinHandle>,
+}
+
+// Topic of the given name and type, e.g. "/chatter" and "std_msgs/msg/String"
+fn topic_args(
+    topic_name: &str,
+    type_name: &str,
+    definition: &str,
+) -> PyResult<(Name, MessageTypeName, Arc<DynamicType>)> {
+    let name = Name::parse(topic_name)
+        .map_err(|e| PyValueError::new_err(format!("Topic name {topic_name:?}: {e}")))?;
+    let type_name: MessageTypeName = type_name
+        .parse()
+        .map_err(|e| PyValueError::new_err(format!("Type name {type_name:?}: {e}")))?;
+    let ty = DynamicType::parse(&type_name, definition).map_err(PyValueError::new_err)?;
+    Ok((name, type_name, Arc::new(ty)))
+}
+
+#[pymethods]
+impl PyNode {
+    #[getter]
+    fn name(&self) -> String {
+        self.node.lock().unwrap().fully_qualified_name()
+    }
+
+    /// Creates a Publisher of dicts. `definition` is the message definition.
+    fn create_publisher(
+        &self,
+        topic_name: &str,
+        type_name: &str,
+        definition: &str,
+    ) -> PyResult<PyPublisher> {
+        let (name, type_name, ty) = topic_args(topic_name, type_name, definition)?;
+        let mut node = self.node.lock().unwrap();
+        let publisher = node
+            .create_topic(&name, type_name, &DEFAULT_PUBLISHER_QOS)
+            .and_then(|topic| node.create_publisher(&topic, None))
+            .map_err(runtime_error)?;
+        Ok(PyPublisher { publisher, ty })
+    }
+
+    /// Creates a Subscription delivering dicts. `definition` is the message
+    /// definition.
+    fn create_subscription(
+        &self,
+        topic_name: &str,
+        type_name: &str,
+        definition: &str,
+    ) -> PyResult<PySubscription> {
+        let (name, type_name, ty) = topic_args(topic_name, type_name, definition)?;
+        let mut node = self.node.lock().unwrap();
+        let subscription = node
+            .create_topic(&name, type_name, &DEFAULT_SUBSCRIPTION_QOS)
+            .and_then(|topic| node.create_subscription(&topic, None))
+            .map_err(runtime_error)?;
+        Ok(PySubscription { subscription, ty })
+    }
+}
+
+impl Drop for PyNode {
+    fn drop(&mut self) {
+        if let Some(spin_handle) = self.spin_handle.take() {
+            spin_handle.stop();
+            if let Err(e) = spin_handle.join() {
+                warn!("Spinner of {}: {e}", self.name());
+            }
+        }
+    }
+}
+
+#[pyclass(name = "Publisher", module = "ros2_client")]
+struct PyPublisher {
+    publisher: Publisher<DynamicMessage>,
+    ty: Arc<DynamicType>,
+}
+
+#[pymethods]
+impl PyPublisher {
+    /// Publishes a dict. Missing fields are zero or empty.
+    fn publish(&self, message: &Bound<'_, PyDict>) -> PyResult<()> {
+        let value = to_value(message.as_any())?;
+        let message = DynamicMessage::new(Arc::clone(&self.ty), value);
+        self.publisher.publish(message).map_err(|e| match e {
+            // Type errors in the dict, not failures to write.
+            WriteError::Serialization { reason, .. } => PyValueError::new_err(reason),
+            e => runtime_error(format!("{e:?}")),
+        })
+    }
+}
+
+#[pyclass(name = "Subscription", module = "ros2_client")]
+struct PySubscription {
+    subscription: Subscription<DynamicValue>,
+    ty: Arc<DynamicType>,
+}
+
+#[pymethods]
+impl PySubscription {
+    /// The next received message as a dict, or None if there is none.
+    fn take(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let message = self
+            .subscription
+            .take_seed(self.ty.seed())
+            .map_err(runtime_error)?;
+        message
+            .map(|(value, _info)| to_object(py, &value))
+            .transpose()
+    }
+
+    /// Waits for the next message, at most `timeout` seconds if given.
+    /// Returns None on timeout.
+    #[pyo3(signature = (timeout = None))]
+    fn receive(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<Option<PyObject>> {
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let received = py.allow_threads(|| {
+            executor::block_on(async {
+                let messages = self.subscription.async_stream_seed(self.ty.seed());
+                let deadline = async {
+                    match timeout {
+                        Some(timeout) => Delay::new(timeout).await,
+                        None => futures::future::pending().await,
+                    }
+                }
+                .fuse();
+                pin_mut!(messages, deadline);
+                select! {
+                    message = messages.select_next_some() => message.map(Some),
+                    _ = deadline => Ok(None),
+                }
+            })
+        });
+        let message = received.map_err(runtime_error)?;
+        message
+            .map(|(value, _info)| to_object(py, &value))
+            .transpose()
+    }
+}
+
+fn to_value(object: &Bound<'_, PyAny>) -> PyResult<DynamicValue> {
+    if let Ok(b) = object.downcast::<PyBool>() {
+        Ok(DynamicValue::Bool(b.is_true()))
+    } else if object.is_instance_of::<PyInt>() {
+        match object.extract::<i64>() {
+            Ok(i) => Ok(DynamicValue::Int(i)),
+            Err(_) => object.extract().map(DynamicValue::UInt),
+        }
+    } else if object.is_instance_of::<PyFloat>() {
+        object.extract().map(DynamicValue::Float)
+    } else if object.is_instance_of::<PyString>() {
+        object.extract().map(DynamicValue::String)
+    } else if let Ok(bytes) = object.downcast::<PyBytes>() {
+        Ok(DynamicValue::Bytes(bytes.as_bytes().to_vec()))
+    } else if let Ok(bytes) = object.downcast::<PyByteArray>() {
+        Ok(DynamicValue::Bytes(bytes.to_vec()))
+    } else if let Ok(dict) = object.downcast::<PyDict>() {
+        dict.iter()
+            .map(|(k, v)| Ok((k.extract::<String>()?, to_value(&v)?)))
+            .collect::<PyResult<_>>()
+            .map(DynamicValue::Message)
+    } else if object.is_instance_of::<PyList>() || object.is_instance_of::<PyTuple>() {
+        object
+            .try_iter()?
+            .map(|item| to_value(&item?))
+            .collect::<PyResult<_>>()
+            .map(DynamicValue::List)
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Cannot convert {} to a message field",
+            object.get_type()
+        )))
+    }
+}
+
+fn to_object(py: Python<'_>, value: &DynamicValue) -> PyResult<PyObject> {
+    match value {
+        DynamicValue::Bool(b) => b.into_py_any(py),
+        DynamicValue::Int(i) => i.into_py_any(py),
+        DynamicValue::UInt(u) => u.into_py_any(py),
+        DynamicValue::Float(f) => f.into_py_any(py),
+        DynamicValue::String(s) => s.into_py_any(py),
+        DynamicValue::Bytes(b) => PyBytes::new(py, b).into_py_any(py),
+        DynamicValue::List(values) => {
+            let values = values
+                .iter()
+                .map(|v| to_object(py, v))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, values)?.into_py_any(py)
+        }
+        DynamicValue::Message(fields) => {
+            let dict = PyDict::new(py);
+            for (name, value) in fields {
+                dict.set_item(name, to_object(py, value)?)?;
+            }
+            dict.into_py_any(py)
+        }
+    }
+}
+
+#[pymodule]
+fn ros2_client(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyContext>()?;
+    module.add_class::<PyNode>()?;
+    module.add_class::<PyPublisher>()?;
+    module.add_class::<PySubscription>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dict_round_trip() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dict = PyDict::new(py);
+            dict.set_item("flag", true).unwrap();
+            dict.set_item("count", -3).unwrap();
+            dict.set_item("big", u64::MAX).unwrap();
+            dict.set_item("ratio", 0.5).unwrap();
+            dict.set_item("name", "talker").unwrap();
+            dict.set_item("data", PyBytes::new(py, b"\x01\x02"))
+                .unwrap();
+            dict.set_item("values", vec![1, 2]).unwrap();
+            let header = PyDict::new(py);
+            header.set_item("frame_id", "map").unwrap();
+            dict.set_item("header", header).unwrap();
+
+            let value = to_value(dict.as_any()).unwrap();
+            let DynamicValue::Message(fields) = &value else {
+                panic!("expected a message, got {value:?}");
+            };
+            assert_eq!(fields["big"], DynamicValue::UInt(u64::MAX));
+            assert_eq!(fields["data"], DynamicValue::Bytes(vec![1, 2]));
+
+            let object = to_object(py, &value).unwrap();
+            assert!(object.bind(py).eq(&dict).unwrap());
+        });
+    }
+}
@@ -0,0 +1,192 @@
+//! Helpers for [QoS policies](rustdds::QosPolicies), and checks for policies
+//! that RustDDS implements only partially.
+//!
+//! The policies themselves are defined in [`rustdds::policy`]. See also
+//! [`crate::prelude::dds`].
+//!
+//! ## Prioritizing Topics
+//!
+//! [`LatencyBudget`](rustdds::policy::LatencyBudget) can be given like any
+//! other policy, in the QoS of a Topic, in [`Node::create_publisher`] or
+//! [`Node::create_subscription`], or with
+//! [`NodeOptions::topic_qos_override`]. It is announced in Discovery, so other
+//! DDS implementations can take it into account, and it is checked for
+//! compatibility: A Subscription does not match a Publisher that offers a
+//! larger budget than the Subscription requests. RustDDS itself sends samples
+//! as soon as possible regardless of the budget.
+//!
+//! The TRANSPORT_PRIORITY policy is not available, because RustDDS does not
+//! implement it. Command Topics cannot be given network priority over bulk
+//! telemetry by ros2-client. On a constrained link, use a deeper History and
+//! BestEffort Reliability for the bulk Topics instead, so that their
+//! retransmissions do not compete with the critical Topics.
+//!
+//! [`Node::create_publisher`]: crate::node::Node::create_publisher
+//! [`Node::create_subscription`]: crate::node::Node::create_subscription
+//! [`NodeOptions::topic_qos_override`]: crate::node::NodeOptions::topic_qos_override
+
+use std::fmt;
+
+use log::warn;
+use rustdds::{
+    dds::{CreateError, CreateResult},
+    policy::{Durability, History, ResourceLimits},
+    QosPolicies, QosPolicyBuilder,
+};
+
+/// Special value for [`ResourceLimits`] fields, meaning "no limit".
+pub const LENGTH_UNLIMITED: i32 = -1;
+
+/// Settings of the DDS DURABILITY_SERVICE QoS policy.
+///
+/// These are relevant when [`Durability`] is `Transient` or `Persistent`,
+/// i.e. late-joining Subscriptions should get more than just the latest
+/// sample.
+///
+/// RustDDS does not have a separate durability service, and does not send this
+/// policy in Discovery. Instead, the settings are applied to the writer itself:
+/// it keeps samples according to `history` and `resource_limits`, and delivers
+/// them to late-joining readers as long as the writer exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DurabilityService {
+    /// How many samples are kept for late joiners
+    pub history: History,
+    /// Upper bounds for the kept samples
+    pub resource_limits: ResourceLimits,
+}
+
+impl DurabilityService {
+    /// Durability service that keeps the last `depth` samples.
+    pub fn keep_last(depth: i32) -> Self {
+        DurabilityService {
+            history: History::KeepLast { depth },
+            resource_limits: ResourceLimits {
+                max_samples: depth,
+                max_instances: 1,
+                max_samples_per_instance: depth,
+            },
+        }
+    }
+
+    /// Modify `qos` to have `durability` and these durability service
+    /// settings.
+    pub fn apply(&self, durability: Durability, qos: &QosPolicies) -> QosPolicies {
+        qos.modify_by(
+            &QosPolicyBuilder::new()
+                .durability(durability)
+                .history(self.history)
+                .resource_limits(self.resource_limits)
+                .build(),
+        )
+    }
+}
+
+/// A QoS policy value that RustDDS does not implement as the DDS
+/// specification says.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QosLimitation {
+    /// `Transient` or `Persistent` durability was requested. RustDDS
+    /// implements these as `TransientLocal`: Samples are available to late
+    /// joiners only as long as the original writer exists, and nothing is
+    /// stored across restarts.
+    DurabilityActsAsTransientLocal(Durability),
+}
+
+impl fmt::Display for QosLimitation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QosLimitation::DurabilityActsAsTransientLocal(d) => write!(
+                f,
+                "Durability {d:?} is not fully supported. It behaves as TransientLocal."
+            ),
+        }
+    }
+}
+
+/// Lists the policies in `qos` that will not behave as specified.
+pub fn limitations(qos: &QosPolicies) -> Vec<QosLimitation> {
+    let mut result = Vec::new();
+    if let Some(d @ (Durability::Transient | Durability::Persistent)) = qos.durability() {
+        result.push(QosLimitation::DurabilityActsAsTransientLocal(d));
+    }
+    result
+}
+
+/// Can the history kept according to `qos` grow without bound?
+///
+/// This is the case when [`History::KeepAll`] is used without a limit on
+/// `max_samples_per_instance` in [`ResourceLimits`]. RustDDS uses that field
+/// to bound KeepAll history.
+///
+/// A Subscription with unbounded history will consume memory without limit,
+/// if the application does not take the received samples fast enough.
+pub fn has_unbounded_history(qos: &QosPolicies) -> bool {
+    matches!(qos.history(), Some(History::KeepAll))
+        && qos
+            .resource_limits()
+            .is_none_or(|rl| rl.max_samples_per_instance < 0)
+}
+
+// Used when creating endpoints, so that the user is not silently surprised.
+pub(crate) fn check_endpoint_qos(
+    topic_name: &str,
+    qos: &QosPolicies,
+    reject_unbounded_history: bool,
+) -> CreateResult<()> {
+    for limitation in limitations(qos) {
+        warn!("Topic {topic_name}: {limitation}");
+    }
+    if has_unbounded_history(qos) {
+        if reject_unbounded_history {
+            return Err(CreateError::BadParameter {
+                reason: format!(
+                    "Topic {topic_name}: KeepAll history requires ResourceLimits with bounded \
+                     max_samples_per_instance."
+                ),
+            });
+        }
+        warn!(
+            "Topic {topic_name}: KeepAll history without ResourceLimits. Memory use may grow \
+             without bound."
+        );
+    }
+    Ok(())
+}
+
+/// Does `topic_name` match `pattern`?
+///
+/// Patterns are matched against the absolute ROS name of a Topic, e.g.
+/// `/robot1/camera/image`. In the pattern, `*` matches any characters within
+/// one name token, and `**` matches any characters, including `/`. All other
+/// characters must match exactly.
+///
+/// For example, `/*/camera/*` matches `/robot1/camera/image`, and `/sensors/**`
+/// matches anything under `/sensors`.
+pub fn topic_pattern_matches(pattern: &str, topic_name: &str) -> bool {
+    fn matches(pattern: &[u8], name: &[u8]) -> bool {
+        match pattern {
+            [] => name.is_empty(),
+            [b'*', b'*', rest @ ..] => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            [b'*', rest @ ..] => {
+                let token_len = name.iter().position(|&c| c == b'/').unwrap_or(name.len());
+                (0..=token_len).any(|i| matches(rest, &name[i..]))
+            }
+            [p, rest @ ..] => name.first() == Some(p) && matches(rest, &name[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), topic_name.as_bytes())
+}
+
+#[test]
+fn test_topic_pattern_matches() {
+    assert!(topic_pattern_matches("/chatter", "/chatter"));
+    assert!(!topic_pattern_matches("/chatter", "/chatter2"));
+    assert!(topic_pattern_matches("/*/camera/*", "/robot1/camera/image"));
+    assert!(!topic_pattern_matches("/*/image", "/robot1/camera/image"));
+    assert!(topic_pattern_matches(
+        "/sensors/**",
+        "/sensors/lidar/points"
+    ));
+    assert!(topic_pattern_matches("/**/points", "/sensors/lidar/points"));
+    assert!(topic_pattern_matches("/robot*/odom", "/robot12/odom"));
+}
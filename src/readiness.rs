@@ -0,0 +1,221 @@
+//! Using ros2-client without an async runtime
+//!
+//! Event loops of GUI frameworks and game engines cannot block on an async
+//! executor. Instead, they need to be told when an entity has something to
+//! take, and then take it with the non-blocking calls, such as
+//! [`Subscription::take`](crate::node::pubsub::Subscription::take),
+//! [`Server::receive_request`](crate::service::Server::receive_request) or
+//! [`Client::receive_response`](crate::service::Client::receive_response).
+//! There are three ways to be told:
+//!
+//! * [`Readiness::register_waker`] with any [`Waker`]. [`waker_fn`] makes a
+//!   Waker that calls a closure, e.g. one that posts an event to the GUI
+//!   loop.
+//! * Registering the entity to a `mio` 0.8 `Registry`, as it implements
+//!   `mio::event::Source` with this feature.
+//! * Registering the entity to a `mio` 0.6 `Poll`, as it implements
+//!   `mio::Evented`. This is available without the feature, but only for
+//!   Subscriptions and typed Service Clients and Servers.
+//!
+//! The first two are implemented by Subscriptions, typed and generic Service
+//! Clients and Servers, and Action Clients and Servers. An Action endpoint
+//! registers all its DDS readers with the same token. Publishers do not need
+//! these, as publishing does not wait for anything.
+//!
+//! All three are edge-triggered: They notify when new data arrives, not
+//! while there is data to take. So register first, and then take until
+//! nothing is left:
+//!
+//! ```ignore
+//! let waker = waker_fn(move || event_loop_proxy.send_event(RosEvent));
+//! // on each RosEvent, and once at start:
+//! subscription.register_waker(&waker);
+//! while let Some((message, _info)) = subscription.take()? {
+//!     update_view(message);
+//! }
+//! ```
+//!
+//! The [`Spinner`](crate::node::Spinner) of a Node is a future, and
+//! [`ManualTask`] runs it without an executor:
+//!
+//! ```ignore
+//! let mut spinner_task = None;
+//! let spin_handle = node.spin_with(|task| spinner_task = Some(ManualTask::new(task)))?;
+//! let mut spinner_task = spinner_task.unwrap();
+//! let waker = waker_fn(move || event_loop_proxy.send_event(SpinnerEvent));
+//! // on each SpinnerEvent, and once at start:
+//! spinner_task.poll(&waker);
+//! ```
+
+use std::{
+    fmt,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+use futures::{future::BoxFuture, FutureExt};
+
+/// Entities that can wake a [`Waker`] when they receive something. See the
+/// [module documentation](self).
+pub trait Readiness {
+    /// Wake `waker` once, when new data arrives.
+    ///
+    /// A later registration replaces the earlier one. The entity has one
+    /// Waker slot, which is also used by its async functions and Streams, so
+    /// they should not be used together with this.
+    fn register_waker(&self, waker: &Waker);
+}
+
+struct FnWaker<F>(F);
+
+impl<F> Wake for FnWaker<F>
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    fn wake(self: Arc<Self>) {
+        (self.0)();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        (self.0)();
+    }
+}
+
+/// A Waker that calls `f` when woken.
+///
+/// `f` is called from the thread that wakes, often a RustDDS background
+/// thread, so it should return quickly.
+pub fn waker_fn<F>(f: F) -> Waker
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    Waker::from(Arc::new(FnWaker(f)))
+}
+
+/// A future polled by hand, instead of by an executor
+///
+/// This is meant for the Spinner task given by
+/// [`Node::spin_with`](crate::node::Node::spin_with), but works for any
+/// future.
+pub struct ManualTask {
+    future: Option<BoxFuture<'static, ()>>,
+}
+
+impl ManualTask {
+    pub fn new(future: BoxFuture<'static, ()>) -> ManualTask {
+        ManualTask {
+            future: Some(future),
+        }
+    }
+
+    /// Poll the future once. If it is not finished, `waker` is woken when
+    /// it should be polled again.
+    ///
+    /// Returns `true` when the future has finished. Polling a finished task
+    /// does nothing.
+    pub fn poll(&mut self, waker: &Waker) -> bool {
+        let Some(future) = &mut self.future else {
+            return true;
+        };
+        match future.poll_unpin(&mut Context::from_waker(waker)) {
+            Poll::Ready(()) => {
+                self.future = None;
+                true
+            }
+            Poll::Pending => false,
+        }
+    }
+
+    /// Whether the future has finished
+    pub fn is_finished(&self) -> bool {
+        self.future.is_none()
+    }
+}
+
+impl fmt::Debug for ManualTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManualTask")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::channel::oneshot;
+
+    use super::*;
+    use crate::{
+        interfaces::{builtin_interfaces::Time, std_msgs::Header},
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+    };
+
+    #[test]
+    fn manual_task() {
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let waker = {
+            let wakes = wakes.clone();
+            waker_fn(move || {
+                wakes.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+        let (sender, receiver) = oneshot::channel::<()>();
+        let mut task = ManualTask::new(
+            async move {
+                receiver.await.unwrap();
+            }
+            .boxed(),
+        );
+        assert!(!task.poll(&waker));
+        assert_eq!(wakes.load(Ordering::SeqCst), 0);
+        sender.send(()).unwrap();
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+        assert!(task.poll(&waker));
+        assert!(task.is_finished());
+        assert!(task.poll(&waker));
+    }
+
+    #[test]
+    fn subscription_waker() {
+        use std::{sync::mpsc, time::Duration};
+
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_waker_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let publisher = node.advertise::<Header>("/waker_test").unwrap();
+        let subscription = node.subscribe::<Header>("/waker_test").unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let waker = waker_fn(move || {
+            let _ = sender.send(());
+        });
+
+        let header = Header {
+            stamp: Time::ZERO,
+            frame_id: "waker".to_owned(),
+        };
+        // The Publisher may not be matched yet, so publish until woken.
+        // Wakeups can also be spurious, so register and take after each.
+        let mut received = None;
+        let mut woken = false;
+        for _ in 0..50 {
+            subscription.register_waker(&waker);
+            if let Some((message, _info)) = subscription.take().unwrap() {
+                received = Some(message);
+                break;
+            }
+            publisher.publish(header.clone()).unwrap();
+            woken |= receiver.recv_timeout(Duration::from_millis(100)).is_ok();
+        }
+        assert!(woken);
+        let received = received.unwrap();
+        assert_eq!(received.frame_id, "waker");
+    }
+}
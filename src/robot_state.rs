@@ -0,0 +1,316 @@
+//! Static transforms from a URDF robot description
+//!
+//! [`RobotStatePublisher`] is a minimal version of the ROS 2
+//! `robot_state_publisher`: It subscribes to the robot description, in
+//! [URDF](https://wiki.ros.org/urdf/XML) format, on Topic `/robot_description`,
+//! and publishes the transforms of the fixed joints to `/tf_static`.
+//!
+//! Moving joints are not published, because that would need `/joint_states`
+//! and forward kinematics.
+//!
+//! This requires feature `urdf`.
+//!
+//! ```ignore
+//! let robot_state = RobotStatePublisher::new(&mut node)?;
+//! let _spin_handle = node.spin_in_background()?;
+//! robot_state.run(&node).await?;
+//! ```
+
+use std::fmt;
+
+use log::{info, warn};
+use rustdds::{
+    dds::{CreateResult, ReadResult},
+    policy::{Durability, History, Reliability},
+    QosPolicies, QosPolicyBuilder,
+};
+
+use crate::{
+    interfaces::{
+        builtin_interfaces::Time,
+        geometry_msgs::{Quaternion, Transform, TransformStamped, Vector3},
+        std_msgs::Header,
+        tf2_msgs::TFMessage,
+    },
+    node::{
+        pubsub::{Publisher, Subscription},
+        Node,
+    },
+    prelude::{MessageTypeName, Name},
+};
+
+/// Error in parsing a URDF robot description
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrdfError {
+    /// The description is not well-formed XML.
+    Xml(String),
+    /// The root element is not `<robot>`.
+    NotRobot,
+    /// A required element or attribute is missing. The values are joint name
+    /// and the missing item.
+    Missing(String, &'static str),
+    /// An `xyz` or `rpy` attribute is not three numbers. The values are joint
+    /// name and the attribute value.
+    BadVector(String, String),
+}
+
+impl fmt::Display for UrdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UrdfError::Xml(e) => write!(f, "Bad XML in URDF: {e}"),
+            UrdfError::NotRobot => write!(f, "URDF root element is not <robot>"),
+            UrdfError::Missing(joint, what) => write!(f, "URDF joint {joint:?}: missing {what}"),
+            UrdfError::BadVector(joint, v) => {
+                write!(f, "URDF joint {joint:?}: expected three numbers, got {v:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UrdfError {}
+
+/// A fixed joint of a URDF robot description
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedJoint {
+    pub name: String,
+    /// Parent link, i.e. the frame that `transform` is relative to
+    pub parent: String,
+    /// Child link
+    pub child: String,
+    /// Pose of the child link in the parent link frame, from `<origin>`
+    pub transform: Transform,
+}
+
+impl FixedJoint {
+    /// The joint as a transform from the parent link frame to the child link
+    /// frame.
+    pub fn to_transform_stamped(&self, stamp: Time) -> TransformStamped {
+        TransformStamped {
+            header: Header {
+                stamp,
+                frame_id: self.parent.clone(),
+            },
+            child_frame_id: self.child.clone(),
+            transform: self.transform,
+        }
+    }
+}
+
+/// Finds the joints of type `fixed` in a URDF robot description.
+///
+/// Only the joints are parsed. Links, and joints of other types, are ignored.
+pub fn fixed_joints(urdf: &str) -> Result<Vec<FixedJoint>, UrdfError> {
+    let doc = roxmltree::Document::parse(urdf).map_err(|e| UrdfError::Xml(e.to_string()))?;
+    let robot = doc.root_element();
+    if !robot.has_tag_name("robot") {
+        return Err(UrdfError::NotRobot);
+    }
+
+    let mut result = Vec::new();
+    for joint in robot
+        .children()
+        .filter(|n| n.has_tag_name("joint") && n.attribute("type") == Some("fixed"))
+    {
+        let name = joint
+            .attribute("name")
+            .ok_or_else(|| UrdfError::Missing(String::new(), "name"))?
+            .to_owned();
+        let link = |tag: &'static str| {
+            joint
+                .children()
+                .find(|n| n.has_tag_name(tag))
+                .and_then(|n| n.attribute("link"))
+                .map(str::to_owned)
+                .ok_or_else(|| UrdfError::Missing(name.clone(), tag))
+        };
+        let parent = link("parent")?;
+        let child = link("child")?;
+
+        // <origin> and its attributes are optional, defaulting to zero.
+        let origin = joint.children().find(|n| n.has_tag_name("origin"));
+        let vector = |attr: &str| match origin.and_then(|o| o.attribute(attr)) {
+            None => Ok([0.0; 3]),
+            Some(s) => {
+                parse_vector(s).ok_or_else(|| UrdfError::BadVector(name.clone(), s.to_owned()))
+            }
+        };
+        let [x, y, z] = vector("xyz")?;
+        let [roll, pitch, yaw] = vector("rpy")?;
+
+        result.push(FixedJoint {
+            name,
+            parent,
+            child,
+            transform: Transform {
+                translation: Vector3 { x, y, z },
+                rotation: Quaternion::from_rpy(roll, pitch, yaw),
+            },
+        });
+    }
+    Ok(result)
+}
+
+fn parse_vector(s: &str) -> Option<[f64; 3]> {
+    let mut numbers = s.split_whitespace().map(|n| n.parse::<f64>().ok());
+    let v = [numbers.next()??, numbers.next()??, numbers.next()??];
+    numbers.next().is_none().then_some(v)
+}
+
+/// Publishes the fixed joints of the robot description as static transforms.
+///
+/// Both Topics use Reliable, TransientLocal QoS with history depth 1, as in
+/// ROS 2, so that the latest robot description and transforms are delivered
+/// also to late joiners.
+pub struct RobotStatePublisher {
+    description_subscription: Subscription<String>,
+    tf_static_publisher: Publisher<TFMessage>,
+}
+
+impl RobotStatePublisher {
+    pub fn new(node: &mut Node) -> CreateResult<RobotStatePublisher> {
+        let qos = Self::qos();
+        let description_topic = node.create_topic(
+            &Name::new("/", "robot_description").unwrap(),
+            MessageTypeName::new("std_msgs", "String"),
+            &qos,
+        )?;
+        let tf_static_topic =
+            node.create_topic_for::<TFMessage>(&Name::new("/", "tf_static").unwrap(), &qos)?;
+        Ok(RobotStatePublisher {
+            description_subscription: node.create_subscription(&description_topic, None)?,
+            tf_static_publisher: node.create_publisher(&tf_static_topic, None)?,
+        })
+    }
+
+    /// QoS of `/robot_description` and `/tf_static`
+    pub fn qos() -> QosPolicies {
+        QosPolicyBuilder::new()
+            .durability(Durability::TransientLocal)
+            .reliability(Reliability::Reliable {
+                max_blocking_time: rustdds::Duration::from_millis(100),
+            })
+            .history(History::KeepLast { depth: 1 })
+            .build()
+    }
+
+    /// Parse `urdf` and publish its fixed joints, stamped with `stamp`.
+    ///
+    /// All transforms are published in one message, which replaces any
+    /// previously published ones for late joiners.
+    pub fn publish_description(
+        &self,
+        urdf: &str,
+        stamp: Time,
+    ) -> Result<(), RobotStatePublisherError> {
+        let transforms = fixed_joints(urdf)?
+            .iter()
+            .map(|j| j.to_transform_stamped(stamp))
+            .collect();
+        self.tf_static_publisher
+            .publish(TFMessage { transforms })
+            .map_err(|e| RobotStatePublisherError::Write(e.to_string()))
+    }
+
+    /// Publish static transforms for each robot description received.
+    ///
+    /// Transforms are stamped with the time from `node`. Descriptions that
+    /// cannot be parsed are logged and skipped. This runs until reading the
+    /// robot description fails.
+    pub async fn run(&self, node: &Node) -> ReadResult<()> {
+        loop {
+            let (urdf, _info) = self.description_subscription.async_take().await?;
+            match self.publish_description(&urdf, node.time_now().into()) {
+                Ok(()) => info!("Published static transforms from robot description."),
+                Err(e) => warn!("Robot description not published: {e}"),
+            }
+        }
+    }
+}
+
+/// Error from [`RobotStatePublisher::publish_description`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RobotStatePublisherError {
+    Urdf(UrdfError),
+    /// Publishing to `/tf_static` failed.
+    Write(String),
+}
+
+impl From<UrdfError> for RobotStatePublisherError {
+    fn from(e: UrdfError) -> Self {
+        RobotStatePublisherError::Urdf(e)
+    }
+}
+
+impl fmt::Display for RobotStatePublisherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RobotStatePublisherError::Urdf(e) => write!(f, "{e}"),
+            RobotStatePublisherError::Write(e) => write!(f, "Cannot publish transforms: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RobotStatePublisherError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URDF: &str = r#"<?xml version="1.0"?>
+        <robot name="test">
+          <link name="base_link"/>
+          <link name="laser"/>
+          <link name="wheel"/>
+          <joint name="base_to_laser" type="fixed">
+            <parent link="base_link"/>
+            <child link="laser"/>
+            <origin xyz="0.1 0 0.2" rpy="0 0 1.5707963267948966"/>
+          </joint>
+          <joint name="base_to_wheel" type="continuous">
+            <parent link="base_link"/>
+            <child link="wheel"/>
+          </joint>
+        </robot>"#;
+
+    #[test]
+    fn fixed_joints_only() {
+        let joints = fixed_joints(URDF).unwrap();
+        assert_eq!(joints.len(), 1);
+        let j = &joints[0];
+        assert_eq!(
+            (j.parent.as_str(), j.child.as_str()),
+            ("base_link", "laser")
+        );
+        assert_eq!(
+            j.transform.translation,
+            Vector3 {
+                x: 0.1,
+                y: 0.0,
+                z: 0.2
+            }
+        );
+        let q = j.transform.rotation;
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        assert!(q.x.abs() < 1e-9 && q.y.abs() < 1e-9);
+        assert!((q.z - h).abs() < 1e-9 && (q.w - h).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bad_descriptions() {
+        assert_eq!(fixed_joints("<world/>"), Err(UrdfError::NotRobot));
+        assert!(matches!(fixed_joints("<robot"), Err(UrdfError::Xml(_))));
+        assert_eq!(
+            fixed_joints(
+                r#"<robot><joint name="j" type="fixed"><parent link="a"/></joint></robot>"#
+            ),
+            Err(UrdfError::Missing("j".to_owned(), "child"))
+        );
+        assert_eq!(
+            fixed_joints(
+                r#"<robot><joint name="j" type="fixed"><parent link="a"/><child link="b"/>
+                   <origin xyz="1 2"/></joint></robot>"#
+            ),
+            Err(UrdfError::BadVector("j".to_owned(), "1 2".to_owned()))
+        );
+    }
+}
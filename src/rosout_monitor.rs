@@ -0,0 +1,261 @@
+//! Collecting and filtering `/rosout` messages
+//!
+//! [`RosoutMonitor`] subscribes to `/rosout` and keeps the latest messages of
+//! each logger in a ring buffer. The buffered messages can be filtered by
+//! severity, logger name, and a regular expression on the message text, and
+//! new messages can be followed as an async stream. This is a building block
+//! for log viewers and log forwarders.
+//!
+//! This requires feature `rosout-monitor`.
+//!
+//! ```ignore
+//! let monitor = RosoutMonitor::new(&mut node, 1000)?;
+//! let warnings = monitor.stream(LogFilter::new().min_level(LogLevel::Warn));
+//! // Run the monitor in a task of its own, and consume `warnings` elsewhere.
+//! monitor.run().await?;
+//! ```
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+};
+
+use log::debug;
+use rustdds::{
+    dds::{CreateError, CreateResult, ReadResult},
+    policy::History,
+    QosPolicyBuilder,
+};
+
+use crate::{
+    log::{Log, LogLevel},
+    node::{pubsub::Subscription, Node},
+};
+
+pub use regex::Regex;
+
+/// Selects `/rosout` messages
+///
+/// A default filter accepts all messages. Each setting narrows it down.
+#[derive(Clone, Debug, Default)]
+pub struct LogFilter {
+    min_level: u8,
+    name: Option<String>,
+    message_regex: Option<Regex>,
+}
+
+impl LogFilter {
+    pub fn new() -> LogFilter {
+        LogFilter::default()
+    }
+
+    /// Accept only messages of at least this severity.
+    pub fn min_level(self, level: LogLevel) -> LogFilter {
+        LogFilter {
+            min_level: level as u8,
+            ..self
+        }
+    }
+
+    /// Accept only messages of this logger, or its child loggers.
+    ///
+    /// Logger names are hierarchical with `.` as separator, so e.g. name
+    /// `ns.talker` matches loggers `ns.talker` and `ns.talker.rclcpp`, but not
+    /// `ns.talker2`.
+    pub fn name(self, name: &str) -> LogFilter {
+        LogFilter {
+            name: Some(name.to_owned()),
+            ..self
+        }
+    }
+
+    /// Accept only messages whose text matches the regular expression.
+    pub fn message_regex(self, regex: Regex) -> LogFilter {
+        LogFilter {
+            message_regex: Some(regex),
+            ..self
+        }
+    }
+
+    pub fn matches(&self, log: &Log) -> bool {
+        log.level >= self.min_level
+            && self.name.as_ref().is_none_or(|name| {
+                log.name
+                    .strip_prefix(name.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            && self
+                .message_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&log.msg))
+    }
+}
+
+/// Keeps the latest `/rosout` messages of each logger.
+///
+/// Messages are received only while [`Self::run`] is running.
+pub struct RosoutMonitor {
+    subscription: Subscription<Log>,
+    capacity: usize,
+    // logger name -> latest messages, oldest first
+    buffers: Mutex<BTreeMap<String, VecDeque<Log>>>,
+    listeners: Mutex<Vec<(LogFilter, async_channel::Sender<Log>)>>,
+}
+
+impl RosoutMonitor {
+    /// Subscribe to `/rosout`, keeping up to `capacity` messages per logger.
+    pub fn new(node: &mut Node, capacity: usize) -> CreateResult<RosoutMonitor> {
+        if capacity == 0 {
+            return Err(CreateError::BadParameter {
+                reason: "RosoutMonitor capacity must be positive".to_owned(),
+            });
+        }
+        // The Topic keeps only the latest message, which would lose bursts.
+        let qos = QosPolicyBuilder::new()
+            .history(History::KeepLast {
+                depth: i32::try_from(capacity).unwrap_or(i32::MAX),
+            })
+            .build();
+        Ok(RosoutMonitor {
+            subscription: node.create_subscription(&node.rosout_topic(), Some(qos))?,
+            capacity,
+            buffers: Mutex::new(BTreeMap::new()),
+            listeners: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Receive messages from `/rosout`. This runs until reading fails.
+    pub async fn run(&self) -> ReadResult<()> {
+        loop {
+            let (log, _info) = self.subscription.async_take().await?;
+            self.insert(log);
+        }
+    }
+
+    fn insert(&self, log: Log) {
+        self.listeners.lock().unwrap().retain(|(filter, sender)| {
+            if !filter.matches(&log) {
+                return true;
+            }
+            match sender.try_send(log.clone()) {
+                Ok(()) => true,
+                Err(async_channel::TrySendError::Closed(_)) => false,
+                Err(async_channel::TrySendError::Full(_)) => {
+                    // The receiver is not keeping up. Drop the message for it.
+                    debug!("RosoutMonitor: stream full, message dropped");
+                    true
+                }
+            }
+        });
+
+        let mut buffers = self.buffers.lock().unwrap();
+        let buffer = buffers.entry(log.name.clone()).or_default();
+        if buffer.len() == self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(log);
+    }
+
+    /// Names of the loggers that have sent messages, in alphabetical order
+    pub fn loggers(&self) -> Vec<String> {
+        self.buffers.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Buffered messages accepted by `filter`, oldest first by timestamp
+    pub fn logs(&self, filter: &LogFilter) -> Vec<Log> {
+        let mut logs: Vec<Log> = self
+            .buffers
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .filter(|log| filter.matches(log))
+            .cloned()
+            .collect();
+        logs.sort_by_key(|log| log.timestamp);
+        logs
+    }
+
+    /// Buffered messages of all loggers whose text matches `regex`
+    pub fn search(&self, regex: &Regex) -> Vec<Log> {
+        self.logs(&LogFilter::new().message_regex(regex.clone()))
+    }
+
+    /// Forget all buffered messages.
+    pub fn clear(&self) {
+        self.buffers.lock().unwrap().clear();
+    }
+
+    /// Stream of new messages accepted by `filter`
+    ///
+    /// Messages are delivered as they are received by [`Self::run`]. Buffered
+    /// messages are not included. If the stream is not consumed fast enough,
+    /// messages are dropped from it.
+    pub fn stream(&self, filter: LogFilter) -> async_channel::Receiver<Log> {
+        let (sender, receiver) = async_channel::bounded(self.capacity);
+        self.listeners.lock().unwrap().push((filter, sender));
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::Timestamp;
+
+    use super::*;
+    use crate::node::{
+        context::{tests::test_node, Context},
+        NodeOptions,
+    };
+
+    fn log(name: &str, level: LogLevel, msg: &str, secs: u32) -> Log {
+        Log {
+            timestamp: Timestamp::from_ticks(u64::from(secs) << 32),
+            level: level as u8,
+            name: name.to_owned(),
+            msg: msg.to_owned(),
+            file: String::new(),
+            function: String::new(),
+            line: 0,
+        }
+    }
+
+    #[test]
+    fn filters_and_ring_buffers() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_rosout_monitor",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let monitor = RosoutMonitor::new(&mut node, 2).unwrap();
+        let errors = monitor.stream(LogFilter::new().min_level(LogLevel::Error));
+
+        monitor.insert(log("talker", LogLevel::Info, "hello 1", 1));
+        monitor.insert(log("talker.rclcpp", LogLevel::Error, "disk full", 2));
+        monitor.insert(log("talker2", LogLevel::Warn, "hello 2", 3));
+        monitor.insert(log("talker", LogLevel::Info, "hello 3", 4));
+        monitor.insert(log("talker", LogLevel::Info, "hello 4", 5));
+
+        assert_eq!(monitor.loggers(), ["talker", "talker.rclcpp", "talker2"]);
+        // Oldest message of "talker" was dropped.
+        let talker: Vec<_> = monitor
+            .logs(&LogFilter::new().name("talker"))
+            .into_iter()
+            .map(|l| l.msg)
+            .collect();
+        assert_eq!(talker, ["disk full", "hello 3", "hello 4"]);
+
+        let warnings = monitor.logs(&LogFilter::new().min_level(LogLevel::Warn));
+        assert_eq!(warnings.len(), 2);
+
+        let hits = monitor.search(&Regex::new(r"hello [23]").unwrap());
+        assert_eq!(hits.len(), 2);
+
+        assert_eq!(errors.try_recv().unwrap().msg, "disk full");
+        assert!(errors.try_recv().is_err());
+
+        monitor.clear();
+        assert!(monitor.loggers().is_empty());
+    }
+}
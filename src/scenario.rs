@@ -0,0 +1,667 @@
+//! Recording and replaying traffic for regression tests
+//!
+//! A [`ScenarioRecorder`] records the messages of selected Topics, and the
+//! requests and responses of selected Services, during a run of the real
+//! system. The resulting [`Scenario`] can be saved to a file, and later
+//! replayed by a [`ScenarioPlayer`] against the node under test alone.
+//!
+//! Replay uses virtual time: The player publishes `/clock`, and each recorded
+//! event is delivered when virtual time reaches the time it was recorded at.
+//! The node under test should have parameter `use_sim_time` set, so that its
+//! timers and time stamps follow the replay instead of the wall clock. Time
+//! only moves when the test calls [`ScenarioPlayer::advance`], so the replay
+//! is as fast as the node under test, and runs are repeatable.
+//!
+//! ```ignore
+//! // During the recording run
+//! let mut recorder = ScenarioRecorder::new(node.time_now());
+//! recorder.record_topic(node.create_subscription::<LaserScan>(&scan_topic, None)?);
+//! loop {
+//!     recorder.poll(node.time_now())?;
+//!     // ...
+//! }
+//! recorder.finish().write_to(File::create("scan.scenario")?)?;
+//!
+//! // In the test
+//! let scenario = Scenario::read_from(File::open("scan.scenario")?)?;
+//! let mut player = ScenarioPlayer::new(scenario, ROSTime::ZERO);
+//! player.drive_clock(&mut test_node)?;
+//! player.replay_topic(test_node.create_publisher::<LaserScan>(&scan_topic, None)?);
+//! while !player.is_finished() {
+//!     player.advance(ROSDuration::from_nanos(10_000_000))?;
+//!     // check what the node under test published
+//! }
+//! ```
+//!
+//! Services cannot be recorded by listening, because a Service Client only
+//! receives responses to its own requests. Instead, the node that serves or
+//! calls the Service records the requests and responses it handles, with
+//! [`ScenarioRecorder::record_request`] and
+//! [`ScenarioRecorder::record_response`]. On replay, recorded requests are
+//! sent to the node under test with a Client, and the recorded responses are
+//! available from [`ScenarioPlayer::recorded_responses`] for comparison.
+//!
+//! This requires feature `scenario`.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read, Write},
+    rc::Rc,
+};
+
+use rustdds::{
+    dds::{CreateResult, ReadError},
+    serialization::{deserialize_from_cdr_with_rep_id, to_writer_with_rep_id},
+    RepresentationIdentifier,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    node::{
+        pubsub::{Publisher, Subscription},
+        Node,
+    },
+    service::{client::Client, Service},
+    time::{
+        clock_publisher::ClockPublisher,
+        ros_time::{ROSDuration, ROSTime},
+    },
+};
+
+const MAGIC: &[u8] = b"ROS2SCN\x01";
+
+/// What a [`ScenarioEvent`] carries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    /// A message published to a Topic
+    Message,
+    /// A request sent to a Service
+    Request,
+    /// A response sent by a Service
+    Response,
+}
+
+impl EventKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            EventKind::Message => 0,
+            EventKind::Request => 1,
+            EventKind::Response => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<EventKind> {
+        match byte {
+            0 => Some(EventKind::Message),
+            1 => Some(EventKind::Request),
+            2 => Some(EventKind::Response),
+            _ => None,
+        }
+    }
+}
+
+/// One recorded message, request, or response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioEvent {
+    /// Time since the start of the recording
+    pub time: ROSDuration,
+    /// ROS 2 name of the Topic or Service
+    pub channel: String,
+    pub kind: EventKind,
+    /// CDR payload, starting with the 4-byte encapsulation header
+    pub data: Vec<u8>,
+}
+
+impl ScenarioEvent {
+    /// Deserialize the data, e.g. in a sink of
+    /// [`ScenarioPlayer::replay_with`].
+    pub fn decode<M: DeserializeOwned>(&self) -> Result<M, ScenarioError> {
+        let decode_error = |error| ScenarioError::Decode {
+            channel: self.channel.clone(),
+            error,
+        };
+        if self.data.len() < 4 {
+            return Err(decode_error(rustdds::serialization::Error::Eof));
+        }
+        let representation = RepresentationIdentifier::from_bytes(&self.data[0..2])
+            .map_err(|e| decode_error(e.into()))?;
+        deserialize_from_cdr_with_rep_id(&self.data[4..], representation)
+            .map(|(value, _)| value)
+            .map_err(decode_error)
+    }
+}
+
+/// Recorded events, in time order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scenario {
+    events: Vec<ScenarioEvent>,
+}
+
+impl Scenario {
+    pub fn new() -> Scenario {
+        Scenario::default()
+    }
+
+    pub fn events(&self) -> &[ScenarioEvent] {
+        &self.events
+    }
+
+    /// Time of the last event
+    pub fn duration(&self) -> ROSDuration {
+        self.events
+            .last()
+            .map_or(ROSDuration::from_nanos(0), |e| e.time)
+    }
+
+    /// Add an event. Events with the same time stay in the order they were
+    /// added.
+    pub fn push(&mut self, event: ScenarioEvent) {
+        let index = self.events.partition_point(|e| e.time <= event.time);
+        self.events.insert(index, event);
+    }
+
+    /// Write the scenario in a simple binary format.
+    ///
+    /// The format is the magic bytes `ROS2SCN\x01`, followed by one record per
+    /// event: kind (u8), time in nanoseconds (i64), channel (u32 length and
+    /// UTF-8), and data (u32 length and bytes). Numbers are little-endian.
+    pub fn write_to<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        for event in &self.events {
+            out.write_all(&[event.kind.to_byte()])?;
+            out.write_all(&event.time.to_nanos().to_le_bytes())?;
+            for bytes in [event.channel.as_bytes(), &event.data] {
+                let len = u32::try_from(bytes.len())
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "event too large"))?;
+                out.write_all(&len.to_le_bytes())?;
+                out.write_all(bytes)?;
+            }
+        }
+        out.flush()
+    }
+
+    /// Read a scenario written by [`write_to`](Self::write_to).
+    pub fn read_from<R: Read>(mut input: R) -> io::Result<Scenario> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_owned());
+
+        let mut magic = [0; MAGIC.len()];
+        input.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid("not a scenario file"));
+        }
+        let mut scenario = Scenario::new();
+        loop {
+            let mut kind = [0];
+            if input.read(&mut kind)? == 0 {
+                return Ok(scenario);
+            }
+            let kind =
+                EventKind::from_byte(kind[0]).ok_or_else(|| invalid("unknown event kind"))?;
+            let mut time = [0; 8];
+            input.read_exact(&mut time)?;
+            let channel = String::from_utf8(read_block(&mut input)?)
+                .map_err(|_| invalid("channel name is not UTF-8"))?;
+            let data = read_block(&mut input)?;
+            scenario.push(ScenarioEvent {
+                time: ROSDuration::from_nanos(i64::from_le_bytes(time)),
+                channel,
+                kind,
+                data,
+            });
+        }
+    }
+}
+
+fn read_block<R: Read>(input: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    input.read_exact(&mut len)?;
+    let mut block = vec![0; u32::from_le_bytes(len) as usize];
+    input.read_exact(&mut block)?;
+    Ok(block)
+}
+
+/// Errors in recording and replaying scenarios
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// Reading from a recorded Subscription failed.
+    Read(ReadError),
+    /// A recorded message could not be serialized.
+    Encode {
+        channel: String,
+        error: rustdds::serialization::Error,
+    },
+    /// A recorded event could not be deserialized as the type it is replayed
+    /// as.
+    Decode {
+        channel: String,
+        error: rustdds::serialization::Error,
+    },
+    /// Publishing a replayed event, or `/clock`, failed.
+    Write { channel: String, error: String },
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Read(e) => write!(f, "cannot read recorded Subscription: {e}"),
+            ScenarioError::Encode { channel, error } => {
+                write!(f, "cannot serialize event of {channel}: {error}")
+            }
+            ScenarioError::Decode { channel, error } => {
+                write!(f, "cannot deserialize event of {channel}: {error}")
+            }
+            ScenarioError::Write { channel, error } => {
+                write!(f, "cannot replay event of {channel}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<ReadError> for ScenarioError {
+    fn from(e: ReadError) -> ScenarioError {
+        ScenarioError::Read(e)
+    }
+}
+
+// Drains one recorded Subscription
+type Source = Box<dyn FnMut() -> Result<Vec<(String, Vec<u8>)>, ReadError>>;
+
+/// Records events into a [`Scenario`].
+pub struct ScenarioRecorder {
+    start_time: ROSTime,
+    scenario: Scenario,
+    sources: Vec<Source>,
+}
+
+impl ScenarioRecorder {
+    /// Start recording. Event times are stored relative to `start_time`.
+    pub fn new(start_time: ROSTime) -> ScenarioRecorder {
+        ScenarioRecorder {
+            start_time,
+            scenario: Scenario::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Record all messages received by `subscription`, on each
+    /// [`poll`](Self::poll).
+    ///
+    /// Messages are recorded in serialized form, so they are not modified by
+    /// recording, even if the Rust type does not preserve everything.
+    pub fn record_topic<M: 'static + DeserializeOwned>(&mut self, subscription: Subscription<M>) {
+        let channel = subscription.topic_name();
+        self.sources.push(Box::new(move || {
+            let mut taken = Vec::new();
+            while let Some((data, _info)) = subscription.take_serialized()? {
+                taken.push((channel.clone(), data));
+            }
+            Ok(taken)
+        }));
+    }
+
+    /// Record what the recorded Subscriptions have received, as events at
+    /// time `now`. Returns the number of recorded messages.
+    ///
+    /// Call this often, so that the recorded times are close to reception
+    /// times.
+    pub fn poll(&mut self, now: ROSTime) -> Result<usize, ScenarioError> {
+        let time = now - self.start_time;
+        let mut count = 0;
+        for source in &mut self.sources {
+            for (channel, data) in source()? {
+                self.scenario.push(ScenarioEvent {
+                    time,
+                    channel,
+                    kind: EventKind::Message,
+                    data,
+                });
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Record a message of `channel`, e.g. one published by this node.
+    pub fn record_message<M: Serialize>(
+        &mut self,
+        now: ROSTime,
+        channel: &str,
+        message: &M,
+    ) -> Result<(), ScenarioError> {
+        self.record(now, channel, EventKind::Message, message)
+    }
+
+    /// Record a request to Service `channel`.
+    pub fn record_request<M: Serialize>(
+        &mut self,
+        now: ROSTime,
+        channel: &str,
+        request: &M,
+    ) -> Result<(), ScenarioError> {
+        self.record(now, channel, EventKind::Request, request)
+    }
+
+    /// Record a response of Service `channel`.
+    pub fn record_response<M: Serialize>(
+        &mut self,
+        now: ROSTime,
+        channel: &str,
+        response: &M,
+    ) -> Result<(), ScenarioError> {
+        self.record(now, channel, EventKind::Response, response)
+    }
+
+    fn record<M: Serialize>(
+        &mut self,
+        now: ROSTime,
+        channel: &str,
+        kind: EventKind,
+        value: &M,
+    ) -> Result<(), ScenarioError> {
+        let representation = RepresentationIdentifier::CDR_LE;
+        let mut data = Vec::with_capacity(64);
+        data.extend_from_slice(&representation.to_bytes());
+        data.extend_from_slice(&[0, 0]); // encapsulation options
+        to_writer_with_rep_id(&mut data, value, representation).map_err(|error| {
+            ScenarioError::Encode {
+                channel: channel.to_owned(),
+                error,
+            }
+        })?;
+        self.scenario.push(ScenarioEvent {
+            time: now - self.start_time,
+            channel: channel.to_owned(),
+            kind,
+            data,
+        });
+        Ok(())
+    }
+
+    /// The events recorded so far
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    /// Stop recording.
+    pub fn finish(self) -> Scenario {
+        self.scenario
+    }
+}
+
+// Delivers one replayed event
+type Sink = Box<dyn FnMut(&ScenarioEvent) -> Result<(), ScenarioError>>;
+
+/// Replays a [`Scenario`] in virtual time.
+///
+/// Events are delivered only on channels that have been set up with
+/// [`replay_topic`](Self::replay_topic),
+/// [`replay_service`](Self::replay_service) or
+/// [`replay_with`](Self::replay_with). Events of other channels, and
+/// recorded responses, are skipped.
+pub struct ScenarioPlayer {
+    scenario: Scenario,
+    start_time: ROSTime,
+    position: ROSDuration,
+    next: usize,
+    clock: Option<ClockPublisher>,
+    sinks: BTreeMap<String, Sink>,
+}
+
+impl ScenarioPlayer {
+    /// Prepare to replay `scenario`. The start of the recording is replayed at
+    /// virtual time `start_time`.
+    pub fn new(scenario: Scenario, start_time: ROSTime) -> ScenarioPlayer {
+        ScenarioPlayer {
+            scenario,
+            start_time,
+            position: ROSDuration::from_nanos(0),
+            next: 0,
+            clock: None,
+            sinks: BTreeMap::new(),
+        }
+    }
+
+    /// Publish virtual time to `/clock` from `node`.
+    ///
+    /// The start time is published immediately.
+    pub fn drive_clock(&mut self, node: &mut Node) -> CreateResult<()> {
+        let clock = ClockPublisher::new(node, self.now())?;
+        // Failure here shows up again on the next advance.
+        let _ = clock.publish();
+        self.clock = Some(clock);
+        Ok(())
+    }
+
+    /// Publish the recorded messages of the Topic of `publisher`.
+    pub fn replay_topic<M>(&mut self, publisher: Publisher<M>)
+    where
+        M: 'static + Serialize + DeserializeOwned,
+    {
+        let channel = publisher.topic_name();
+        self.replay_with(&channel, move |event| {
+            if event.kind != EventKind::Message {
+                return Ok(());
+            }
+            publisher
+                .publish(event.decode()?)
+                .map_err(|e| ScenarioError::Write {
+                    channel: event.channel.clone(),
+                    error: e.to_string(),
+                })
+        });
+    }
+
+    /// Send the recorded requests of the Service of `client`.
+    ///
+    /// Responses are received by the Client as usual. Take them from
+    /// `client` to compare them with
+    /// [`recorded_responses`](Self::recorded_responses).
+    pub fn replay_service<S>(&mut self, client: Rc<Client<S>>)
+    where
+        S: 'static + Service,
+    {
+        let channel = client.service_name();
+        self.replay_with(&channel, move |event| {
+            if event.kind != EventKind::Request {
+                return Ok(());
+            }
+            client
+                .send_request(event.decode()?)
+                .map(|_| ())
+                .map_err(|e| ScenarioError::Write {
+                    channel: event.channel.clone(),
+                    error: e.to_string(),
+                })
+        });
+    }
+
+    /// Deliver the events of `channel` to `sink`.
+    ///
+    /// This replaces an earlier sink of the same channel.
+    pub fn replay_with<F>(&mut self, channel: &str, sink: F)
+    where
+        F: 'static + FnMut(&ScenarioEvent) -> Result<(), ScenarioError>,
+    {
+        self.sinks.insert(channel.to_owned(), Box::new(sink));
+    }
+
+    /// The recorded responses of Service `channel`, in recording order
+    pub fn recorded_responses<M: DeserializeOwned>(
+        &self,
+        channel: &str,
+    ) -> Result<Vec<M>, ScenarioError> {
+        self.scenario
+            .events()
+            .iter()
+            .filter(|e| e.kind == EventKind::Response && e.channel == channel)
+            .map(ScenarioEvent::decode)
+            .collect()
+    }
+
+    /// Current virtual time
+    pub fn now(&self) -> ROSTime {
+        self.start_time + self.position
+    }
+
+    /// Have all events been delivered?
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.scenario.events.len()
+    }
+
+    /// Move virtual time forward by `step`. Returns the number of events
+    /// delivered.
+    pub fn advance(&mut self, step: ROSDuration) -> Result<usize, ScenarioError> {
+        self.advance_to(self.position + step)
+    }
+
+    /// Move virtual time to `position` after the start of the recording.
+    ///
+    /// Each event recorded before or at `position` is delivered, after
+    /// `/clock` has been set to the time of the event. Time does not move
+    /// backwards: Positions already passed deliver nothing.
+    ///
+    /// If delivering an event fails, the replay stops just after that event.
+    pub fn advance_to(&mut self, position: ROSDuration) -> Result<usize, ScenarioError> {
+        let mut delivered = 0;
+        while let Some(event) = self.scenario.events.get(self.next) {
+            if event.time > position {
+                break;
+            }
+            self.next += 1;
+            let Some(sink) = self.sinks.get_mut(&event.channel) else {
+                continue;
+            };
+            if event.time > self.position {
+                self.position = event.time;
+                publish_clock(&mut self.clock, self.start_time + self.position)?;
+            }
+            sink(event)?;
+            delivered += 1;
+        }
+        if position > self.position {
+            self.position = position;
+            publish_clock(&mut self.clock, self.start_time + self.position)?;
+        }
+        Ok(delivered)
+    }
+
+    /// Deliver all remaining events.
+    pub fn run_to_end(&mut self) -> Result<usize, ScenarioError> {
+        self.advance_to(self.scenario.duration())
+    }
+}
+
+fn publish_clock(clock: &mut Option<ClockPublisher>, time: ROSTime) -> Result<(), ScenarioError> {
+    match clock {
+        Some(clock) => clock.set_time(time).map_err(|e| ScenarioError::Write {
+            channel: "/clock".to_owned(),
+            error: e.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::interfaces::{builtin_interfaces::Time, std_msgs::Header};
+
+    fn millis(ms: i64) -> ROSDuration {
+        ROSDuration::from_nanos(ms * 1_000_000)
+    }
+
+    fn header(frame_id: &str) -> Header {
+        Header {
+            stamp: Time::from_nanos(5),
+            frame_id: frame_id.to_owned(),
+        }
+    }
+
+    fn recorded() -> Scenario {
+        let start = ROSTime::from_nanos(1_000_000_000);
+        let mut recorder = ScenarioRecorder::new(start);
+        recorder
+            .record_message(start + millis(20), "/b", &header("late"))
+            .unwrap();
+        recorder
+            .record_message(start + millis(10), "/a", &header("first"))
+            .unwrap();
+        recorder
+            .record_request(start + millis(10), "/srv", &(2_i64, 3_i64))
+            .unwrap();
+        recorder
+            .record_response(start + millis(11), "/srv", &5_i64)
+            .unwrap();
+        recorder.finish()
+    }
+
+    #[test]
+    fn events_are_in_time_order() {
+        let scenario = recorded();
+        let order: Vec<_> = scenario
+            .events()
+            .iter()
+            .map(|e| (e.time, e.channel.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (millis(10), "/a", EventKind::Message),
+                (millis(10), "/srv", EventKind::Request),
+                (millis(11), "/srv", EventKind::Response),
+                (millis(20), "/b", EventKind::Message),
+            ]
+        );
+        assert_eq!(scenario.duration(), millis(20));
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let scenario = recorded();
+        let mut file = Vec::new();
+        scenario.write_to(&mut file).unwrap();
+        assert_eq!(Scenario::read_from(file.as_slice()).unwrap(), scenario);
+
+        assert!(Scenario::read_from(&b"ROS2SCN\x02"[..]).is_err());
+        // Truncated event
+        assert!(Scenario::read_from(&file[..file.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn replay_in_virtual_time() {
+        let start = ROSTime::from_nanos(500);
+        let mut player = ScenarioPlayer::new(recorded(), start);
+        let delivered = Rc::new(RefCell::new(Vec::new()));
+        for channel in ["/a", "/b"] {
+            let delivered = Rc::clone(&delivered);
+            player.replay_with(channel, move |event| {
+                delivered
+                    .borrow_mut()
+                    .push(event.decode::<Header>()?.frame_id);
+                Ok(())
+            });
+        }
+
+        assert_eq!(player.advance(millis(5)).unwrap(), 0);
+        assert_eq!(player.now(), start + millis(5));
+        // "/srv" has no sink, so only "/a" counts.
+        assert_eq!(player.advance(millis(10)).unwrap(), 1);
+        assert_eq!(*delivered.borrow(), ["first"]);
+        assert!(!player.is_finished());
+        // Time does not go backwards.
+        assert_eq!(player.advance_to(millis(1)).unwrap(), 0);
+        assert_eq!(player.now(), start + millis(15));
+
+        assert_eq!(player.run_to_end().unwrap(), 1);
+        assert_eq!(*delivered.borrow(), ["first", "late"]);
+        assert!(player.is_finished());
+
+        assert_eq!(player.recorded_responses::<i64>("/srv").unwrap(), [5]);
+        assert!(player.recorded_responses::<String>("/srv").is_err());
+    }
+}
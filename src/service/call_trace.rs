@@ -0,0 +1,235 @@
+//! Tracing Service calls and Action goals for sequence diagrams
+//!
+//! A [`CallTrace`] given to
+//! [`NodeOptions::call_trace`](crate::node::NodeOptions::call_trace) records
+//! every request and response sent or received by the Clients and Servers of
+//! the Node, and the lifecycle of Action goals. Each event is written as one
+//! line of JSON:
+//!
+//! ```text
+//! {"time_ns":1760000000123456789,"node":"/demo/client","event":"request_sent","service":"/add_two_ints","request_id":"010f...0103:42"}
+//! {"time_ns":1760000000124456789,"node":"/demo/server","event":"request_received","service":"/add_two_ints","request_id":"010f...0103:42"}
+//! {"time_ns":1760000001000000000,"node":"/demo/server","event":"goal_accepted","action":"/fibonacci","goal_id":"6a1c..."}
+//! ```
+//!
+//! `request_id` is the [`RmwRequestId`] in its string form, and `goal_id` is
+//! the goal UUID. They are the same on both sides of a call, so the traces
+//! of several Nodes, even in different processes, can be merged and sorted by
+//! `time_ns` to reconstruct the interaction. `time_ns` is system time in
+//! nanoseconds since the Unix epoch, so the clocks of the hosts should be
+//! synchronized.
+//!
+//! Tracing costs a write per event, so it is meant for debugging, not for
+//! production use.
+
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use log::warn;
+
+#[cfg(feature = "actions")]
+use crate::action::goal::GoalId;
+use crate::{service::request_id::RmwRequestId, time::ros_time::ROSTime};
+
+/// A traced event. The string form is used in the `event` field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallEvent {
+    /// A Client sent a request.
+    RequestSent,
+    /// A Server received a request.
+    RequestReceived,
+    /// A Server sent a response.
+    ResponseSent,
+    /// A Client received a response, and delivered it to the application.
+    ResponseReceived,
+    /// An Action Client sent a goal.
+    GoalSent,
+    /// An Action Server accepted a goal.
+    GoalAccepted,
+    /// An Action Server rejected a goal.
+    GoalRejected,
+    /// An Action Server started executing a goal.
+    GoalExecuting,
+    /// An Action Server started canceling a goal.
+    GoalCanceling,
+    /// An Action Server finished a goal.
+    GoalSucceeded,
+    GoalAborted,
+    GoalCanceled,
+}
+
+impl CallEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallEvent::RequestSent => "request_sent",
+            CallEvent::RequestReceived => "request_received",
+            CallEvent::ResponseSent => "response_sent",
+            CallEvent::ResponseReceived => "response_received",
+            CallEvent::GoalSent => "goal_sent",
+            CallEvent::GoalAccepted => "goal_accepted",
+            CallEvent::GoalRejected => "goal_rejected",
+            CallEvent::GoalExecuting => "goal_executing",
+            CallEvent::GoalCanceling => "goal_canceling",
+            CallEvent::GoalSucceeded => "goal_succeeded",
+            CallEvent::GoalAborted => "goal_aborted",
+            CallEvent::GoalCanceled => "goal_canceled",
+        }
+    }
+}
+
+/// Destination of traced events. See the [module documentation](self).
+///
+/// One `CallTrace` can be shared by several Nodes.
+pub struct CallTrace {
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl CallTrace {
+    /// Write events to `output`. Each line is flushed when written.
+    pub fn new<W: Write + Send + 'static>(output: W) -> CallTrace {
+        CallTrace {
+            output: Mutex::new(Box::new(output)),
+        }
+    }
+
+    /// Write events to a new file at `path`.
+    pub fn create_file<P: AsRef<Path>>(path: P) -> io::Result<CallTrace> {
+        Ok(CallTrace::new(BufWriter::new(File::create(path)?)))
+    }
+
+    fn write_line(&self, line: &str) {
+        let mut output = self.output.lock().unwrap();
+        if let Err(e) = output
+            .write_all(line.as_bytes())
+            .and_then(|()| output.flush())
+        {
+            warn!("Cannot write call trace: {e}");
+        }
+    }
+}
+
+// A CallTrace bound to one Service or Action of a Node
+#[derive(Clone)]
+pub(crate) struct CallTracer {
+    trace: Arc<CallTrace>,
+    node: String,
+    name: String,
+}
+
+impl CallTracer {
+    pub(crate) fn new(trace: Arc<CallTrace>, node: String, name: String) -> CallTracer {
+        CallTracer { trace, node, name }
+    }
+
+    pub(crate) fn request(&self, event: CallEvent, request_id: RmwRequestId) {
+        self.trace.write_line(&self.line(
+            event,
+            ROSTime::now(),
+            "service",
+            "request_id",
+            &request_id.to_string(),
+        ));
+    }
+
+    #[cfg(feature = "actions")]
+    pub(crate) fn goal(&self, event: CallEvent, goal_id: GoalId) {
+        self.trace.write_line(&self.line(
+            event,
+            ROSTime::now(),
+            "action",
+            "goal_id",
+            &goal_id.to_string(),
+        ));
+    }
+
+    fn line(&self, event: CallEvent, time: ROSTime, kind: &str, id_key: &str, id: &str) -> String {
+        let mut line = format!("{{\"time_ns\":{},\"node\":", time.to_nanos());
+        push_json_string(&mut line, &self.node);
+        let _ = write!(line, ",\"event\":\"{}\",\"{kind}\":", event.as_str());
+        push_json_string(&mut line, &self.name);
+        let _ = write!(line, ",\"{id_key}\":");
+        push_json_string(&mut line, id);
+        line.push_str("}\n");
+        line
+    }
+}
+
+fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{SequenceNumber, GUID};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_lines() {
+        let buffer = Buffer::default();
+        let tracer = CallTracer::new(
+            Arc::new(CallTrace::new(buffer.clone())),
+            "/demo/client".to_owned(),
+            "/add_two_ints".to_owned(),
+        );
+        let request_id = RmwRequestId {
+            writer_guid: GUID::from_bytes([1, 2, 3, 4, 5, 6, 7, 8, 9, 16, 17, 18, 0, 0, 1, 3]),
+            sequence_number: SequenceNumber::from(42),
+        };
+
+        assert_eq!(
+            tracer.line(
+                CallEvent::RequestSent,
+                ROSTime::from_nanos(1_000),
+                "service",
+                "request_id",
+                &request_id.to_string()
+            ),
+            format!(
+                "{{\"time_ns\":1000,\"node\":\"/demo/client\",\"event\":\"request_sent\",\
+                 \"service\":\"/add_two_ints\",\"request_id\":\"{request_id}\"}}\n"
+            )
+        );
+
+        tracer.request(CallEvent::ResponseReceived, request_id);
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(written.contains("\"event\":\"response_received\""));
+        assert!(written.ends_with("}\n"));
+    }
+
+    #[test]
+    fn json_escaping() {
+        let mut s = String::new();
+        push_json_string(&mut s, "a\"b\\c\nd\u{1}");
+        assert_eq!(s, "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+}
@@ -1,27 +1,45 @@
-use std::{io, sync::atomic};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    sync::{atomic, Arc, Mutex},
+    time::Duration,
+};
 
 use futures::{join, pin_mut, StreamExt};
 
 use mio::{Evented, Poll, PollOpt, Ready, Token};
 use rustdds::{
     dds::{CreateResult, ReadError, ReadResult, WriteError, WriteResult},
-    no_key, read_error_internal,
+    no_key,
+    policy::History,
+    read_error_internal,
     rpc::SampleIdentity,
     QosPolicies, RTPSEntity as _, RepresentationIdentifier, SequenceNumber, Timestamp, Topic,
     TopicDescription, WriteOptionsBuilder, GUID,
 };
 
 use crate::{
+    interfaces::{gid::Gid, names::ros_name_of_dds_topic},
     message::Message,
-    node::Node,
+    node::{
+        counters::{Counters, StatusCounters},
+        EndpointRegistration, Node,
+    },
     prelude::MessageInfo,
+    service::call_trace::{CallEvent, CallTracer},
     service::request_id::RmwRequestId,
     service::wrappers::{
         DataWriterR, RequestWrapper, ResponseWrapper, ServiceDeserializerAdapter,
-        ServiceSerializerAdapter, SimpleDataReaderR,
+        ServiceSerializerAdapter, SimpleDataReaderR, Wrapper as _,
     },
     service::{request_id, Service, ServiceMapping},
+    time::steady_time::SteadyTime,
 };
+#[cfg(feature = "no-default-runtime")]
+use std::task::Waker;
+
+#[cfg(feature = "no-default-runtime")]
+use crate::readiness::Readiness;
 
 /// Client end of a ROS2 Service
 pub struct Client<S>
@@ -35,6 +53,52 @@ where
     response_receiver: SimpleDataReaderR<ResponseWrapper<S::Response>>,
     sequence_number_gen: atomic::AtomicI64, // used by basic and cyclone
     client_guid: GUID,                      // used by the Cyclone ServiceMapping
+    dds_request_topic_name: String,
+    dds_response_topic_name: String,
+    // Requests sent, but not answered or abandoned yet.
+    pending_requests: Mutex<BTreeMap<RmwRequestId, PendingRequest>>,
+    // Answered or abandoned requests, whose (further) responses are to be
+    // discarded.
+    closed_requests: Mutex<BTreeSet<RmwRequestId>>,
+    server_selection: Mutex<ServerSelection>,
+    round_robin_counter: atomic::AtomicUsize,
+    call_tracer: Option<CallTracer>,
+    // KeepLast depth of the response reader, if limited
+    response_history_depth: Option<usize>,
+    // Shared with Node. Tells which Servers are matched.
+    readers_to_remote_writers: Arc<Mutex<BTreeMap<GUID, BTreeSet<GUID>>>>,
+    counters: Counters,
+    // Only held to unregister from the Node on drop
+    _registrations: [EndpointRegistration; 2],
+}
+
+// Limit for remembering closed requests. If responses to older ones arrive,
+// they are no longer recognized as closed.
+const MAX_CLOSED_REQUESTS: usize = 256;
+
+struct PendingRequest {
+    sent: SteadyTime,
+    server: Option<Gid>, // Only this Server may answer
+}
+
+/// How a [`Client`] handles responses, when more than one Server is present.
+///
+/// In DDS, a request is always delivered to all matched Servers, and each of
+/// them may respond. This policy decides which response is delivered to the
+/// application. Servers are identified by the [`Gid`] of their response
+/// writer, as listed by [`Client::matched_servers`] and
+/// [`MessageInfo::writer_gid`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ServerSelection {
+    /// Deliver the first response to each request, and discard the rest.
+    #[default]
+    FirstResponse,
+    /// Deliver only responses from the given Server.
+    Server(Gid),
+    /// Each request is assigned to the next matched Server in turn, and only
+    /// its response is delivered. If no Servers are known to be matched, the
+    /// first response is delivered.
+    RoundRobin,
 }
 
 impl<S> Client<S>
@@ -49,11 +113,11 @@ where
         qos_request: Option<QosPolicies>,
         qos_response: Option<QosPolicies>,
     ) -> CreateResult<Self> {
-        let request_sender =
+        let (request_sender, request_registration) =
       node.create_datawriter
       ::<RequestWrapper<S::Request>, ServiceSerializerAdapter<RequestWrapper<S::Request>>>(
         request_topic, qos_request)?;
-        let response_receiver =
+        let (response_receiver, response_registration) =
       node.create_simpledatareader
       ::<ResponseWrapper<S::Response>, ServiceDeserializerAdapter<ResponseWrapper<S::Response>>>(
         response_topic, qos_response)?;
@@ -64,15 +128,65 @@ where
             response_topic.name()
         );
         let client_guid = request_sender.guid();
+        let response_history_depth = match response_receiver.qos().history() {
+            Some(History::KeepLast { depth }) => Some(depth.max(1) as usize),
+            _ => None,
+        };
         Ok(Client::<S> {
             service_mapping,
             request_sender,
             response_receiver,
             sequence_number_gen: atomic::AtomicI64::new(SequenceNumber::default().into()),
             client_guid,
+            dds_request_topic_name: request_topic.name(),
+            dds_response_topic_name: response_topic.name(),
+            pending_requests: Mutex::new(BTreeMap::new()),
+            closed_requests: Mutex::new(BTreeSet::new()),
+            server_selection: Mutex::new(ServerSelection::default()),
+            round_robin_counter: atomic::AtomicUsize::new(0),
+            call_tracer: node.call_tracer(ros_name_of_dds_topic(&request_topic.name())),
+            response_history_depth,
+            readers_to_remote_writers: node.readers_to_remote_writers(),
+            counters: Counters::default(),
+            _registrations: [request_registration, response_registration],
         })
     }
 
+    /// Remove this Client from DDS and from its Node.
+    ///
+    /// This is the same as dropping the Client, but states the intent.
+    pub fn close(self) {}
+
+    /// Fully qualified ROS 2 Service name, after remapping, e.g.
+    /// `/add_two_ints`
+    pub fn service_name(&self) -> String {
+        ros_name_of_dds_topic(&self.dds_request_topic_name)
+    }
+
+    /// Name of the DDS Topic for requests, e.g. `rq/add_two_intsRequest`
+    pub fn dds_request_topic_name(&self) -> &str {
+        &self.dds_request_topic_name
+    }
+
+    /// Name of the DDS Topic for responses, e.g. `rr/add_two_intsReply`
+    pub fn dds_response_topic_name(&self) -> &str {
+        &self.dds_response_topic_name
+    }
+
+    /// History depth of the response reader, or `None` for KeepAll.
+    ///
+    /// If more requests than this are in flight, responses may be dropped
+    /// before they are read, and the calls waiting for them never complete.
+    /// A warning is logged when this happens.
+    pub fn response_history_depth(&self) -> Option<usize> {
+        self.response_history_depth
+    }
+
+    /// Counts of requests sent and responses received so far.
+    pub fn status_counters(&self) -> StatusCounters {
+        self.counters.snapshot()
+    }
+
     /// Send a request to Service Server.
     /// The returned `RmwRequestId` is a token to identify the correct response.
     pub fn send_request(&self, request: S::Request) -> WriteResult<RmwRequestId, ()> {
@@ -94,16 +208,21 @@ where
         } else {
             write_opts_builder.related_sample_identity(SampleIdentity::from(gen_rmw_req_id))
         };
-        let sent_rmw_req_id = self
+        let bytes = req_wrapper.bytes().len();
+        let result = self
             .request_sender
-            .write_with_options(req_wrapper, write_opts_builder.build())
+            .write_with_options(req_wrapper, write_opts_builder.build());
+        self.counters.count_write(&result, bytes);
+        let sent_rmw_req_id = result
             .map(RmwRequestId::from)
             .map_err(|e| e.forget_data())?;
 
-        match self.service_mapping {
-            ServiceMapping::Enhanced => Ok(sent_rmw_req_id),
-            ServiceMapping::Basic | ServiceMapping::Cyclone => Ok(gen_rmw_req_id),
-        }
+        let req_id = match self.service_mapping {
+            ServiceMapping::Enhanced => sent_rmw_req_id,
+            ServiceMapping::Basic | ServiceMapping::Cyclone => gen_rmw_req_id,
+        };
+        self.add_pending_request(req_id);
+        Ok(req_id)
     }
 
     /// Try to get a response from Server.
@@ -115,20 +234,208 @@ where
     /// a response to the correct request.
     ///
     /// If you get a response for the wrong request, call this again.
+    ///
+    /// Responses to requests abandoned with [`Self::abandon_request`] are
+    /// skipped, as are responses not chosen by the [`ServerSelection`]
+    /// policy.
     pub fn receive_response(&self) -> ReadResult<Option<(RmwRequestId, S::Response)>> {
         self.response_receiver.drain_read_notifications();
-        let dcc_rw: Option<no_key::DeserializedCacheChange<ResponseWrapper<S::Response>>> =
-            self.response_receiver.try_take_one()?;
-
-        match dcc_rw {
-            None => Ok(None),
-            Some(dcc) => {
-                let mi = MessageInfo::from(&dcc);
-                let res_wrapper = dcc.into_value();
-                let (ri, res) = res_wrapper.unwrap(self.service_mapping, mi, self.client_guid)?;
-                Ok(Some((ri, res)))
+        loop {
+            let dcc_rw: Option<no_key::DeserializedCacheChange<ResponseWrapper<S::Response>>> =
+                self.response_receiver
+                    .try_take_one()
+                    .inspect_err(|_| self.counters.count_read_error())?;
+
+            match dcc_rw {
+                None => return Ok(None),
+                Some(dcc) => {
+                    let (ri, res, mi) = self.unwrap_response(dcc)?;
+                    if self.accept_response(ri, mi.writer_gid()) {
+                        return Ok(Some((ri, res)));
+                    }
+                }
+            } // match
+        }
+    }
+
+    /// Requests sent by this Client that have not been answered or abandoned
+    /// yet, with their sending times.
+    pub fn pending_requests(&self) -> Vec<(RmwRequestId, SteadyTime)> {
+        self.pending_requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(r_id, p)| (*r_id, p.sent))
+            .collect()
+    }
+
+    /// Servers currently matched with this Client, identified by the [`Gid`]
+    /// of their response writers.
+    ///
+    /// This is updated by the [`Spinner`](crate::node::Spinner), so it must be
+    /// running.
+    pub fn matched_servers(&self) -> Vec<Gid> {
+        self.readers_to_remote_writers
+            .lock()
+            .unwrap()
+            .get(&self.response_receiver.guid())
+            .map(|writers| writers.iter().copied().map(Gid::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// Set the policy for choosing among responses from several Servers.
+    ///
+    /// This applies to requests sent after the call.
+    pub fn set_server_selection(&self, server_selection: ServerSelection) {
+        *self.server_selection.lock().unwrap() = server_selection;
+    }
+
+    pub fn server_selection(&self) -> ServerSelection {
+        *self.server_selection.lock().unwrap()
+    }
+
+    /// Give up waiting for the response to a request.
+    ///
+    /// The request is removed from [`Self::pending_requests`], and its
+    /// response is discarded if it still arrives. Returns `false` if the
+    /// request was not pending.
+    ///
+    /// This is done automatically, if an [`Self::async_call_service`] future is
+    /// dropped before it completes.
+    pub fn abandon_request(&self, request_id: RmwRequestId) -> bool {
+        if self
+            .pending_requests
+            .lock()
+            .unwrap()
+            .remove(&request_id)
+            .is_none()
+        {
+            return false;
+        }
+        self.close_request(request_id);
+        true
+    }
+
+    /// Abandon all pending requests sent more than `timeout` ago.
+    ///
+    /// Returns the abandoned requests. Age is measured with [`SteadyTime`], so
+    /// this is not affected by wall clock adjustments or simulated time.
+    pub fn abandon_requests_older_than(&self, timeout: Duration) -> Vec<RmwRequestId> {
+        let expired: Vec<RmwRequestId> = self
+            .pending_requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, p)| p.sent.elapsed().as_saturating_duration() > timeout)
+            .map(|(r_id, _)| *r_id)
+            .collect();
+        expired
+            .into_iter()
+            .filter(|r_id| self.abandon_request(*r_id))
+            .collect()
+    }
+
+    fn add_pending_request(&self, request_id: RmwRequestId) {
+        let server = match self.server_selection() {
+            ServerSelection::FirstResponse => None,
+            ServerSelection::Server(gid) => Some(gid),
+            ServerSelection::RoundRobin => {
+                let servers = self.matched_servers();
+                if servers.is_empty() {
+                    None
+                } else {
+                    let turn = self
+                        .round_robin_counter
+                        .fetch_add(1, atomic::Ordering::Relaxed);
+                    Some(servers[turn % servers.len()])
+                }
             }
-        } // match
+        };
+        if let Some(tracer) = &self.call_tracer {
+            tracer.request(CallEvent::RequestSent, request_id);
+        }
+        let mut pending = self.pending_requests.lock().unwrap();
+        pending.insert(
+            request_id,
+            PendingRequest {
+                sent: SteadyTime::now(),
+                server,
+            },
+        );
+        // Warn once each time the limit is crossed, not for every request.
+        if let Some(depth) = self.response_history_depth {
+            if pending.len() == depth + 1 {
+                log::warn!(
+                    "Client {}: {} requests in flight, but response history depth is {depth}. \
+                     Responses may be lost. Increase the depth in response QoS.",
+                    self.service_name(),
+                    pending.len(),
+                );
+            }
+        }
+    }
+
+    fn close_request(&self, request_id: RmwRequestId) {
+        let mut closed = self.closed_requests.lock().unwrap();
+        closed.insert(request_id);
+        if closed.len() > MAX_CLOSED_REQUESTS {
+            closed.pop_first();
+        }
+    }
+
+    // Decodes a received response, counting it as received or as an error.
+    fn unwrap_response(
+        &self,
+        dcc: no_key::DeserializedCacheChange<ResponseWrapper<S::Response>>,
+    ) -> ReadResult<(RmwRequestId, S::Response, MessageInfo)> {
+        let mi = MessageInfo::from(&dcc);
+        let res_wrapper = dcc.into_value();
+        self.counters.count_received(res_wrapper.bytes().len());
+        let (ri, res) = res_wrapper
+            .unwrap(self.service_mapping, mi.clone(), self.client_guid)
+            .inspect_err(|_| self.counters.count_read_error())?;
+        Ok((ri, res, mi))
+    }
+
+    // Should a received response be delivered? Also updates bookkeeping and
+    // counters.
+    fn accept_response(&self, request_id: RmwRequestId, server: Gid) -> bool {
+        let accepted = self.select_response(request_id, server);
+        if accepted {
+            self.counters.count_taken();
+            if let Some(tracer) = &self.call_tracer {
+                tracer.request(CallEvent::ResponseReceived, request_id);
+            }
+        } else {
+            self.counters.count_rejected();
+        }
+        accepted
+    }
+
+    fn select_response(&self, request_id: RmwRequestId, server: Gid) -> bool {
+        if self.closed_requests.lock().unwrap().contains(&request_id) {
+            log::debug!("Discarding response to closed request {request_id:?} from {server}");
+            return false;
+        }
+        let mut pending = self.pending_requests.lock().unwrap();
+        match pending.get(&request_id) {
+            Some(PendingRequest {
+                server: Some(selected),
+                ..
+            }) if *selected != server => {
+                log::debug!("Discarding response to {request_id:?} from unselected {server}");
+                false
+            }
+            Some(_) => {
+                pending.remove(&request_id);
+                drop(pending);
+                self.close_request(request_id);
+                true
+            }
+            // Not sent by us, or response arrived before we could record
+            // sending it.
+            None => true,
+        }
     }
 
     /// Send a request to Service Server asynchronously.
@@ -158,10 +465,13 @@ where
         } else {
             write_opts_builder.related_sample_identity(SampleIdentity::from(gen_rmw_req_id))
         };
-        let sent_rmw_req_id = self
+        let bytes = req_wrapper.bytes().len();
+        let result = self
             .request_sender
             .async_write_with_options(req_wrapper, write_opts_builder.build())
-            .await
+            .await;
+        self.counters.count_write(&result, bytes);
+        let sent_rmw_req_id = result
             .map(RmwRequestId::from)
             .map_err(|e| e.forget_data())?;
 
@@ -169,6 +479,7 @@ where
             ServiceMapping::Enhanced => sent_rmw_req_id,
             ServiceMapping::Basic | ServiceMapping::Cyclone => gen_rmw_req_id,
         };
+        self.add_pending_request(req_id);
         log::debug!(
             "Sent Request {:?} to {:?}",
             req_id,
@@ -184,20 +495,37 @@ where
         &self,
         request_id: RmwRequestId,
     ) -> ReadResult<S::Response> {
+        self.async_receive_response_with_info(request_id)
+            .await
+            .map(|(response, _info)| response)
+    }
+
+    /// Like [`Self::async_receive_response`], but also returns the metadata of
+    /// the response.
+    pub async fn async_receive_response_with_info(
+        &self,
+        request_id: RmwRequestId,
+    ) -> ReadResult<(S::Response, MessageInfo)> {
         let dcc_stream = self.response_receiver.as_async_stream();
         pin_mut!(dcc_stream);
 
         loop {
             match dcc_stream.next().await {
-                Some(Err(e)) => return Err(e),
+                Some(Err(e)) => {
+                    self.counters.count_read_error();
+                    return Err(e);
+                }
                 Some(Ok(dcc)) => {
-                    let mi = MessageInfo::from(&dcc);
-                    let (req_id, response) =
-                        dcc.into_value()
-                            .unwrap(self.service_mapping, mi, self.client_guid)?;
+                    let (req_id, response, mi) = self.unwrap_response(dcc)?;
                     if req_id == request_id {
-                        return Ok(response);
+                        if self.accept_response(req_id, mi.writer_gid()) {
+                            return Ok((response, mi));
+                        }
+                    } else if self.closed_requests.lock().unwrap().contains(&req_id) {
+                        self.counters.count_rejected();
+                        log::debug!("Discarding response to closed request {req_id:?}");
                     } else {
+                        self.counters.count_rejected();
                         log::debug!(
                             "Received response for someone else. expected={:?}  received={:?}",
                             request_id,
@@ -216,14 +544,69 @@ where
         } // loop
     }
 
+    // Receive the next response, regardless of which request it answers.
+    #[cfg(feature = "actions")]
+    pub(crate) async fn async_receive_any_response(
+        &self,
+    ) -> ReadResult<(RmwRequestId, S::Response)> {
+        let dcc_stream = self.response_receiver.as_async_stream();
+        pin_mut!(dcc_stream);
+        loop {
+            match dcc_stream.next().await {
+                Some(Err(e)) => {
+                    self.counters.count_read_error();
+                    return Err(e);
+                }
+                Some(Ok(dcc)) => {
+                    let (req_id, response, mi) = self.unwrap_response(dcc)?;
+                    if self.accept_response(req_id, mi.writer_gid()) {
+                        return Ok((req_id, response));
+                    }
+                }
+                None => {
+                    return read_error_internal!(
+                        "SimpleDataReader value stream unexpectedly ended!"
+                    )
+                }
+            }
+        }
+    }
+
     pub async fn async_call_service(
         &self,
         request: S::Request,
     ) -> Result<S::Response, CallServiceError<()>> {
+        self.async_call_service_with_info(request)
+            .await
+            .map(|(response, _info)| response)
+    }
+
+    /// Like [`Self::async_call_service`], but also returns the metadata of the
+    /// response.
+    ///
+    /// The [`MessageInfo`] tells which Server answered
+    /// ([`MessageInfo::writer_gid`]), and when the response was sent and
+    /// received, e.g. for measuring Server latency.
+    pub async fn async_call_service_with_info(
+        &self,
+        request: S::Request,
+    ) -> Result<(S::Response, MessageInfo), CallServiceError<()>> {
         let req_id = self.async_send_request(request).await?;
-        self.async_receive_response(req_id)
+        // If this future is dropped while waiting, the request is abandoned.
+        let guard = AbandonOnDrop {
+            client: self,
+            request_id: req_id,
+        };
+        let result = self
+            .async_receive_response_with_info(req_id)
             .await
-            .map_err(CallServiceError::from)
+            .map_err(CallServiceError::from);
+        // The request is closed when its response is accepted. On error, the
+        // guard abandons it.
+        if result.is_ok() {
+            std::mem::forget(guard);
+        }
+        result
     }
 
     /// Wait for a Server to be connected to the Request and Response topics.
@@ -252,6 +635,24 @@ where
     }
 }
 
+// Abandons a request when dropped.
+struct AbandonOnDrop<'a, S>
+where
+    S: 'static + Service,
+{
+    client: &'a Client<S>,
+    request_id: RmwRequestId,
+}
+
+impl<S> Drop for AbandonOnDrop<'_, S>
+where
+    S: 'static + Service,
+{
+    fn drop(&mut self) {
+        self.client.abandon_request(self.request_id);
+    }
+}
+
 #[derive(Debug)]
 pub enum CallServiceError<T> {
     WriteError(WriteError<T>),
@@ -297,3 +698,138 @@ where
         self.response_receiver.deregister(poll)
     }
 }
+
+#[cfg(feature = "no-default-runtime")]
+impl<S> Readiness for Client<S>
+where
+    S: 'static + Service,
+{
+    fn register_waker(&self, waker: &Waker) {
+        self.response_receiver.set_waker(Some(waker.clone()));
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl<S> mio_08::event::Source for Client<S>
+where
+    S: 'static + Service,
+{
+    fn register(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::register(&mut self.response_receiver, registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::reregister(&mut self.response_receiver, registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio_08::Registry) -> io::Result<()> {
+        mio_08::event::Source::deregister(&mut self.response_receiver, registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, select, FutureExt};
+    use futures_timer::Delay;
+
+    use super::*;
+    use crate::{
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+        prelude::{Name, ServiceTypeName},
+        service::AService,
+    };
+
+    #[test]
+    fn abandoned_requests() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_abandon_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let _spinner = node.spin_in_background().unwrap();
+        let name = Name::new("/rustdds", "test_abandon").unwrap();
+        let type_name = ServiceTypeName::new("test_msgs", "Abandon");
+        // The Server answers with a type that the Client cannot decode.
+        let client = node
+            .create_client::<AService<u8, i64>>(
+                ServiceMapping::Cyclone,
+                &name,
+                &type_name,
+                None,
+                None,
+            )
+            .unwrap();
+        let server = node
+            .create_server::<AService<u8, u8>>(
+                ServiceMapping::Cyclone,
+                &name,
+                &type_name,
+                None,
+                None,
+            )
+            .unwrap();
+
+        // A call whose future is dropped while waiting
+        {
+            let call = client.async_call_service(1).fuse();
+            pin_mut!(call);
+            block_on(async {
+                select! {
+                    _ = call => panic!("no response expected"),
+                    _ = Delay::new(Duration::from_millis(200)).fuse() => {}
+                }
+            });
+            assert_eq!(client.pending_requests().len(), 1);
+        }
+        assert!(client.pending_requests().is_empty());
+
+        // A call that fails
+        let exchange = async {
+            // wait_for_service can miss a match that happens while it starts.
+            let mut waiting = client.wait_for_service(&node).boxed_local().fuse();
+            loop {
+                select! {
+                    _ = waiting => break,
+                    _ = Delay::new(Duration::from_millis(100)).fuse() => {}
+                }
+            }
+            let serve = async {
+                loop {
+                    let (req_id, request) = server.async_receive_request().await.unwrap();
+                    server.async_send_response(req_id, request).await.unwrap();
+                }
+            };
+            select! {
+                result = client.async_call_service(2).fuse() => result,
+                _ = serve.fuse() => unreachable!(),
+            }
+        }
+        .fuse();
+        let result = block_on(async {
+            pin_mut!(exchange);
+            select! {
+                result = exchange => result,
+                _ = Delay::new(Duration::from_secs(20)).fuse() => panic!("timed out"),
+            }
+        });
+        assert!(
+            matches!(result, Err(CallServiceError::ReadError(_))),
+            "{result:?}"
+        );
+        assert!(client.pending_requests().is_empty());
+    }
+}
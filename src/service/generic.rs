@@ -0,0 +1,484 @@
+//! Service Clients and Servers that do not know the Service type at compile
+//! time.
+//!
+//! [`GenericClient`] and [`GenericServer`] exchange requests and responses in
+//! serialized form, as [`SerializedPayload`]s. The Service type is given at
+//! run time as a [`ServiceTypeName`](crate::prelude::ServiceTypeName), e.g.
+//! parsed from a string. This is useful for generic gateways, e.g. a bridge
+//! from HTTP to any ROS 2 Service found via discovery.
+//!
+//! The payload is the CDR encoding of the request or response message alone,
+//! i.e. without the Service mapping header and without the CDR encapsulation
+//! header.
+//!
+//! [`ServiceMapping::Basic`] is not supported. With it, CDR alignment inside
+//! the payload is relative to the start of the whole DDS message, and the
+//! padding after the mapping header depends on the first field of the
+//! message type, which is not known here.
+
+use std::sync::atomic;
+
+use bytes::Bytes;
+use futures::{pin_mut, StreamExt};
+use rustdds::{
+    dds::{CreateError, CreateResult, ReadError, ReadResult, WriteError, WriteResult},
+    read_error_internal,
+    rpc::SampleIdentity,
+    QosPolicies, RTPSEntity as _, RepresentationIdentifier, SequenceNumber, Timestamp, Topic,
+    TopicDescription, WriteOptionsBuilder, GUID,
+};
+
+use crate::{
+    interfaces::names::ros_name_of_dds_topic,
+    node::{EndpointRegistration, Node},
+    prelude::{MessageInfo, ServiceTypeName},
+    service::{
+        client::CallServiceError,
+        request_id::{self, RmwRequestId},
+        wrappers::{
+            DataWriterR, RequestWrapper, ResponseWrapper, ServiceDeserializerAdapter,
+            ServiceSerializerAdapter, SimpleDataReaderR,
+        },
+        ServiceMapping,
+    },
+};
+#[cfg(feature = "no-default-runtime")]
+use std::{io, task::Waker};
+
+#[cfg(feature = "no-default-runtime")]
+use crate::readiness::Readiness;
+
+/// Serialized Service request or response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializedPayload {
+    /// Encoding of `data`. Only [`RepresentationIdentifier::CDR_LE`] can be
+    /// sent, but received data may also be `CDR_BE`.
+    pub encoding: RepresentationIdentifier,
+    /// CDR-encoded message
+    pub data: Bytes,
+}
+
+impl SerializedPayload {
+    /// Payload with little-endian CDR data
+    pub fn cdr_le(data: impl Into<Bytes>) -> Self {
+        SerializedPayload {
+            encoding: RepresentationIdentifier::CDR_LE,
+            data: data.into(),
+        }
+    }
+}
+
+// Type parameter for wrappers. The message type is unknown.
+type Untyped = ();
+
+fn check_encoding(payload: &SerializedPayload) -> WriteResult<(), ()> {
+    if payload.encoding == RepresentationIdentifier::CDR_LE {
+        Ok(())
+    } else {
+        Err(WriteError::Serialization {
+            reason: format!(
+                "Only CDR_LE payloads can be sent, not {:?}",
+                payload.encoding
+            ),
+            data: (),
+        })
+    }
+}
+
+fn check_mapping(service_mapping: ServiceMapping) -> CreateResult<()> {
+    match service_mapping {
+        ServiceMapping::Basic => Err(CreateError::BadParameter {
+            reason: "ServiceMapping::Basic is not supported with serialized payloads".to_string(),
+        }),
+        ServiceMapping::Enhanced | ServiceMapping::Cyclone => Ok(()),
+    }
+}
+
+/// Client end of a ROS 2 Service, with serialized requests and responses
+pub struct GenericClient {
+    service_mapping: ServiceMapping,
+    service_type_name: ServiceTypeName,
+    request_sender: DataWriterR<RequestWrapper<Untyped>>,
+    response_receiver: SimpleDataReaderR<ResponseWrapper<Untyped>>,
+    sequence_number_gen: atomic::AtomicI64,
+    client_guid: GUID,
+    dds_response_topic_name: String,
+    // Only held to unregister from the Node on drop
+    _registrations: [EndpointRegistration; 2],
+}
+
+impl GenericClient {
+    pub(crate) fn new(
+        service_mapping: ServiceMapping,
+        service_type_name: &ServiceTypeName,
+        node: &mut Node,
+        request_topic: &Topic,
+        response_topic: &Topic,
+        qos_request: Option<QosPolicies>,
+        qos_response: Option<QosPolicies>,
+    ) -> CreateResult<Self> {
+        check_mapping(service_mapping)?;
+        let (request_sender, request_registration) =
+            node.create_datawriter::<_, ServiceSerializerAdapter<_>>(request_topic, qos_request)?;
+        let (response_receiver, response_registration) = node
+            .create_simpledatareader::<_, ServiceDeserializerAdapter<_>>(
+                response_topic,
+                qos_response,
+            )?;
+        log::debug!(
+            "Created new GenericClient: request={} response={}",
+            request_topic.name(),
+            response_topic.name()
+        );
+        let client_guid = request_sender.guid();
+        Ok(GenericClient {
+            service_mapping,
+            service_type_name: service_type_name.clone(),
+            request_sender,
+            response_receiver,
+            sequence_number_gen: atomic::AtomicI64::new(SequenceNumber::default().into()),
+            client_guid,
+            dds_response_topic_name: response_topic.name(),
+            _registrations: [request_registration, response_registration],
+        })
+    }
+
+    pub fn service_type_name(&self) -> &ServiceTypeName {
+        &self.service_type_name
+    }
+
+    /// Fully qualified ROS 2 Service name, after remapping
+    pub fn service_name(&self) -> String {
+        ros_name_of_dds_topic(&self.request_sender.topic().name())
+    }
+
+    /// Name of the DDS Topic for requests
+    pub fn dds_request_topic_name(&self) -> String {
+        self.request_sender.topic().name()
+    }
+
+    /// Name of the DDS Topic for responses
+    pub fn dds_response_topic_name(&self) -> &str {
+        &self.dds_response_topic_name
+    }
+
+    // Returns the request id generated here, and the request to send.
+    fn wrap_request(
+        &self,
+        request: &SerializedPayload,
+    ) -> WriteResult<(RmwRequestId, RequestWrapper<Untyped>), ()> {
+        check_encoding(request)?;
+        let seq = self
+            .sequence_number_gen
+            .fetch_add(1, atomic::Ordering::Acquire)
+            + 1;
+        let gen_rmw_req_id = RmwRequestId {
+            writer_guid: self.client_guid,
+            sequence_number: request_id::SequenceNumber::from(seq),
+        };
+        let wrapper =
+            RequestWrapper::new_serialized(self.service_mapping, gen_rmw_req_id, request)?;
+        Ok((gen_rmw_req_id, wrapper))
+    }
+
+    fn request_write_options(&self, gen_rmw_req_id: RmwRequestId) -> rustdds::WriteOptions {
+        let write_opts_builder = WriteOptionsBuilder::new().source_timestamp(Timestamp::now());
+        if self.service_mapping == ServiceMapping::Enhanced {
+            write_opts_builder
+        } else {
+            write_opts_builder.related_sample_identity(SampleIdentity::from(gen_rmw_req_id))
+        }
+        .build()
+    }
+
+    fn select_request_id(&self, sent: RmwRequestId, generated: RmwRequestId) -> RmwRequestId {
+        match self.service_mapping {
+            ServiceMapping::Enhanced => sent,
+            ServiceMapping::Basic | ServiceMapping::Cyclone => generated,
+        }
+    }
+
+    /// Send a serialized request to Service Server.
+    /// The returned `RmwRequestId` is a token to identify the correct response.
+    pub fn send_request(&self, request: SerializedPayload) -> WriteResult<RmwRequestId, ()> {
+        let (gen_rmw_req_id, wrapper) = self.wrap_request(&request)?;
+        let sent = self
+            .request_sender
+            .write_with_options(wrapper, self.request_write_options(gen_rmw_req_id))
+            .map(RmwRequestId::from)
+            .map_err(|e| e.forget_data())?;
+        Ok(self.select_request_id(sent, gen_rmw_req_id))
+    }
+
+    /// Send a serialized request to Service Server asynchronously.
+    pub async fn async_send_request(
+        &self,
+        request: SerializedPayload,
+    ) -> WriteResult<RmwRequestId, ()> {
+        let (gen_rmw_req_id, wrapper) = self.wrap_request(&request)?;
+        let sent = self
+            .request_sender
+            .async_write_with_options(wrapper, self.request_write_options(gen_rmw_req_id))
+            .await
+            .map(RmwRequestId::from)
+            .map_err(|e| e.forget_data())?;
+        Ok(self.select_request_id(sent, gen_rmw_req_id))
+    }
+
+    /// Try to get a response from Server.
+    ///
+    /// Returns `Ok(None)` if no new responses have arrived. As with
+    /// [`Client::receive_response`](crate::service::Client::receive_response),
+    /// the response may be to someone else's request.
+    pub fn receive_response(&self) -> ReadResult<Option<(RmwRequestId, SerializedPayload)>> {
+        self.response_receiver.drain_read_notifications();
+        match self.response_receiver.try_take_one()? {
+            None => Ok(None),
+            Some(dcc) => {
+                let mi = MessageInfo::from(&dcc);
+                dcc.into_value()
+                    .unwrap_serialized(self.service_mapping, mi, self.client_guid)
+                    .map(Some)
+            }
+        }
+    }
+
+    /// Wait for the response to the given request.
+    pub async fn async_receive_response(
+        &self,
+        request_id: RmwRequestId,
+    ) -> ReadResult<SerializedPayload> {
+        let dcc_stream = self.response_receiver.as_async_stream();
+        pin_mut!(dcc_stream);
+        loop {
+            match dcc_stream.next().await {
+                Some(Err(e)) => return Err(e),
+                Some(Ok(dcc)) => {
+                    let mi = MessageInfo::from(&dcc);
+                    let (req_id, response) = dcc.into_value().unwrap_serialized(
+                        self.service_mapping,
+                        mi,
+                        self.client_guid,
+                    )?;
+                    if req_id == request_id {
+                        return Ok(response);
+                    }
+                }
+                None => {
+                    return read_error_internal!(
+                        "SimpleDataReader value stream unexpectedly ended!"
+                    )
+                }
+            }
+        }
+    }
+
+    pub async fn async_call_service(
+        &self,
+        request: SerializedPayload,
+    ) -> Result<SerializedPayload, CallServiceError<()>> {
+        let req_id = self.async_send_request(request).await?;
+        self.async_receive_response(req_id)
+            .await
+            .map_err(CallServiceError::from)
+    }
+}
+
+/// Server end of a ROS 2 Service, with serialized requests and responses
+pub struct GenericServer {
+    service_mapping: ServiceMapping,
+    service_type_name: ServiceTypeName,
+    request_receiver: SimpleDataReaderR<RequestWrapper<Untyped>>,
+    response_sender: DataWriterR<ResponseWrapper<Untyped>>,
+    dds_request_topic_name: String,
+    // Only held to unregister from the Node on drop
+    _registrations: [EndpointRegistration; 2],
+}
+
+impl GenericServer {
+    pub(crate) fn new(
+        service_mapping: ServiceMapping,
+        service_type_name: &ServiceTypeName,
+        node: &mut Node,
+        request_topic: &Topic,
+        response_topic: &Topic,
+        qos_request: Option<QosPolicies>,
+        qos_response: Option<QosPolicies>,
+    ) -> CreateResult<Self> {
+        check_mapping(service_mapping)?;
+        let (request_receiver, request_registration) = node
+            .create_simpledatareader::<_, ServiceDeserializerAdapter<_>>(
+                request_topic,
+                qos_request,
+            )?;
+        let (response_sender, response_registration) =
+            node.create_datawriter::<_, ServiceSerializerAdapter<_>>(response_topic, qos_response)?;
+        log::debug!(
+            "Created new GenericServer: requests={} response={}",
+            request_topic.name(),
+            response_topic.name()
+        );
+        Ok(GenericServer {
+            service_mapping,
+            service_type_name: service_type_name.clone(),
+            request_receiver,
+            response_sender,
+            dds_request_topic_name: request_topic.name(),
+            _registrations: [request_registration, response_registration],
+        })
+    }
+
+    pub fn service_type_name(&self) -> &ServiceTypeName {
+        &self.service_type_name
+    }
+
+    /// Fully qualified ROS 2 Service name, after remapping
+    pub fn service_name(&self) -> String {
+        ros_name_of_dds_topic(&self.dds_request_topic_name)
+    }
+
+    /// Name of the DDS Topic for requests
+    pub fn dds_request_topic_name(&self) -> &str {
+        &self.dds_request_topic_name
+    }
+
+    /// Name of the DDS Topic for responses
+    pub fn dds_response_topic_name(&self) -> String {
+        self.response_sender.topic().name()
+    }
+
+    /// Receive a request from Client.
+    /// Returns `Ok(None)` if no new requests have arrived.
+    pub fn receive_request(&self) -> ReadResult<Option<(RmwRequestId, SerializedPayload)>> {
+        self.request_receiver.drain_read_notifications();
+        match self.request_receiver.try_take_one()? {
+            None => Ok(None),
+            Some(dcc) => {
+                let mi = MessageInfo::from(&dcc);
+                dcc.into_value()
+                    .unwrap_serialized(self.service_mapping, &mi)
+                    .map(Some)
+            }
+        }
+    }
+
+    /// Wait for the next request.
+    pub async fn async_receive_request(&self) -> ReadResult<(RmwRequestId, SerializedPayload)> {
+        let dcc_stream = self.request_receiver.as_async_stream();
+        pin_mut!(dcc_stream);
+        match dcc_stream.next().await {
+            Some(Err(e)) => Err(e),
+            Some(Ok(dcc)) => {
+                let mi = MessageInfo::from(&dcc);
+                dcc.into_value()
+                    .unwrap_serialized(self.service_mapping, &mi)
+            }
+            None => read_error_internal!("SimpleDataReader value stream unexpectedly ended!"),
+        }
+    }
+
+    fn wrap_response(
+        &self,
+        rmw_req_id: RmwRequestId,
+        response: &SerializedPayload,
+    ) -> WriteResult<(ResponseWrapper<Untyped>, rustdds::WriteOptions), ()> {
+        check_encoding(response)?;
+        let wrapper = ResponseWrapper::new_serialized(self.service_mapping, rmw_req_id, response)?;
+        let write_opts = WriteOptionsBuilder::new()
+            .source_timestamp(Timestamp::now())
+            .related_sample_identity(SampleIdentity::from(rmw_req_id))
+            .build();
+        Ok((wrapper, write_opts))
+    }
+
+    /// Send a serialized response to the request identified by `rmw_req_id`.
+    pub fn send_response(
+        &self,
+        rmw_req_id: RmwRequestId,
+        response: SerializedPayload,
+    ) -> WriteResult<(), ()> {
+        let (wrapper, write_opts) = self.wrap_response(rmw_req_id, &response)?;
+        self.response_sender
+            .write_with_options(wrapper, write_opts)
+            .map(|_| ())
+            .map_err(|e| e.forget_data())
+    }
+
+    /// Asynchronous response sending
+    pub async fn async_send_response(
+        &self,
+        rmw_req_id: RmwRequestId,
+        response: SerializedPayload,
+    ) -> WriteResult<(), ()> {
+        let (wrapper, write_opts) = self.wrap_response(rmw_req_id, &response)?;
+        self.response_sender
+            .async_write_with_options(wrapper, write_opts)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.forget_data())
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl Readiness for GenericClient {
+    fn register_waker(&self, waker: &Waker) {
+        self.response_receiver.set_waker(Some(waker.clone()));
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl mio_08::event::Source for GenericClient {
+    fn register(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::register(&mut self.response_receiver, registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::reregister(&mut self.response_receiver, registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio_08::Registry) -> io::Result<()> {
+        mio_08::event::Source::deregister(&mut self.response_receiver, registry)
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl Readiness for GenericServer {
+    fn register_waker(&self, waker: &Waker) {
+        self.request_receiver.set_waker(Some(waker.clone()));
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl mio_08::event::Source for GenericServer {
+    fn register(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::register(&mut self.request_receiver, registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::reregister(&mut self.request_receiver, registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio_08::Registry) -> io::Result<()> {
+        mio_08::event::Source::deregister(&mut self.request_receiver, registry)
+    }
+}
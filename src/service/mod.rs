@@ -1,15 +1,17 @@
 //! Implementation of ROS 2 [Services](https://docs.ros.org/en/rolling/Tutorials/Beginner-CLI-Tools/Understanding-ROS2-Services/Understanding-ROS2-Services.html)
 use std::marker::PhantomData;
 
-use crate::message::Message;
+use crate::{interfaces::names::ServiceTypeName, message::Message};
 
+pub mod call_trace;
 pub mod client;
+pub mod generic;
 pub mod parameters;
 pub mod request_id;
 pub mod server;
 pub mod wrappers;
 
-pub use client::Client;
+pub use client::{Client, ServerSelection};
 pub use server::Server;
 
 // --------------------------------------------
@@ -72,6 +74,60 @@ pub trait Service {
     fn response_type_name(&self) -> &str;
 }
 
+/// A [`Service`] that knows its ROS 2 type name.
+///
+/// This allows creating Clients and Servers with
+/// [`Node::create_client_for`](crate::node::Node::create_client_for) and
+/// [`Node::create_server_for`](crate::node::Node::create_server_for), so that the
+/// type name is written only once, in the trait impl.
+///
+/// ```
+/// # use ros2_client::prelude::*;
+/// # use serde::{Deserialize, Serialize};
+/// use ros2_client::service::NamedService;
+///
+/// # #[derive(Deserialize, Serialize)]
+/// # struct AddTwoIntsRequest { a: i64, b: i64 }
+/// # #[derive(Deserialize, Serialize)]
+/// # struct AddTwoIntsResponse { sum: i64 }
+/// # impl Message for AddTwoIntsRequest {}
+/// # impl Message for AddTwoIntsResponse {}
+/// struct AddTwoInts;
+///
+/// impl Service for AddTwoInts {
+///     type Request = AddTwoIntsRequest;
+///     type Response = AddTwoIntsResponse;
+///
+///     fn request_type_name(&self) -> &str {
+///         "AddTwoIntsRequest"
+///     }
+///
+///     fn response_type_name(&self) -> &str {
+///         "AddTwoIntsResponse"
+///     }
+/// }
+///
+/// impl NamedService for AddTwoInts {
+///     const PACKAGE_NAME: &'static str = "example_interfaces";
+///     const TYPE_NAME: &'static str = "AddTwoInts";
+/// }
+///
+/// assert_eq!(
+///     AddTwoInts::service_type_name().dds_request_type(),
+///     "example_interfaces::srv::dds_::AddTwoInts_Request_"
+/// );
+/// ```
+pub trait NamedService: Service {
+    /// ROS 2 package, e.g. `example_interfaces`
+    const PACKAGE_NAME: &'static str;
+    /// Type name within the package, e.g. `AddTwoInts`
+    const TYPE_NAME: &'static str;
+
+    fn service_type_name() -> ServiceTypeName {
+        ServiceTypeName::new(Self::PACKAGE_NAME, Self::TYPE_NAME)
+    }
+}
+
 // --------------------------------------------
 // --------------------------------------------
 
@@ -141,8 +197,10 @@ where
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ServiceMapping {
     /// "Basic" service mapping from RPC over DDS specification.
-    /// * RTI Connext with `RMW_CONNEXT_REQUEST_REPLY_MAPPING=basic`, but this is
-    ///   not tested, so may not work.
+    /// * RTI Connext with `RMW_CONNEXT_REQUEST_REPLY_MAPPING=basic`.
+    ///
+    /// Requests and responses carry the request id in a header, which is
+    /// followed by the message in the same CDR stream.
     Basic,
 
     /// "Enhanced" service mapping from RPC over DDS specification.
@@ -155,7 +213,60 @@ pub enum ServiceMapping {
     /// CycloneDDS-specific service mapping.
     /// Specification for this mapping is unknown, technical details are
     /// reverse-engineered from ROS2 sources.
-    /// * ROS2 Galactic with CycloneDDS - Seems to work on the same host only, not
-    ///   over actual network.
+    /// * ROS2 with CycloneDDS. Requests and responses are correlated using the
+    ///   client identifier and sequence number in the Cyclone message header,
+    ///   so this does not depend on the hosts of the Client and Server.
     Cyclone,
 }
+
+/// Chooses the [`ServiceMapping`] by Service name.
+///
+/// This is useful when ROS 2 nodes using different DDS implementations must be
+/// reached from the same Node, e.g. during a migration. Rules are patterns as
+/// in [`crate::qos::topic_pattern_matches`], matched against the absolute
+/// Service name, after remapping. The first matching rule is used, or the
+/// default mapping if none match.
+///
+/// ```
+/// # use ros2_client::service::{ServiceMapping, ServiceMappingTable};
+/// let table = ServiceMappingTable::new(ServiceMapping::Enhanced)
+///     .rule("/legacy/**", ServiceMapping::Cyclone);
+/// assert_eq!(table.mapping_for("/legacy/add_two_ints"), ServiceMapping::Cyclone);
+/// assert_eq!(table.mapping_for("/add_two_ints"), ServiceMapping::Enhanced);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ServiceMappingTable {
+    rules: Vec<(String, ServiceMapping)>, // (pattern, mapping)
+    default: ServiceMapping,
+}
+
+impl ServiceMappingTable {
+    /// A table with no rules, always giving `default`.
+    pub fn new(default: ServiceMapping) -> Self {
+        ServiceMappingTable {
+            rules: Vec::new(),
+            default,
+        }
+    }
+
+    /// Use `mapping` for Services whose name matches `pattern`.
+    pub fn rule(mut self, pattern: &str, mapping: ServiceMapping) -> Self {
+        self.rules.push((pattern.to_owned(), mapping));
+        self
+    }
+
+    /// Mapping for the Service with the given absolute name
+    pub fn mapping_for(&self, service_name: &str) -> ServiceMapping {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| crate::qos::topic_pattern_matches(pattern, service_name))
+            .map_or(self.default, |(_, mapping)| *mapping)
+    }
+}
+
+/// Enhanced mapping for all Services
+impl Default for ServiceMappingTable {
+    fn default() -> Self {
+        ServiceMappingTable::new(ServiceMapping::Enhanced)
+    }
+}
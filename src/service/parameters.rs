@@ -7,9 +7,23 @@
 //! Paramters can be queried and set remotely using e.g. the `ros2 param` or
 //! `rqt` tools from ROS 2. This only works for [`Node`](crate::Node)s that have
 //! enabled Parameter Services and are running a `Spinner`.
+//!
+//! Parameter changes of all Nodes can be followed with
+//! [`ParameterEventsSubscription`].
+
+use futures::{stream::FusedStream, StreamExt};
+use log::warn;
+use rustdds::dds::{CreateResult, ReadResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    message::message_info::MessageInfo,
+    node::{pubsub::Subscription, Node},
+    time::ros_time::ROSTime,
+};
 
 /// Named parameter
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Parameter {
     pub name: String,
     pub value: ParameterValue,
@@ -17,7 +31,11 @@ pub struct Parameter {
 
 /// Rust-like representation of ROS2
 /// [ParameterValue](https://github.com/ros2/rcl_interfaces/blob/master/rcl_interfaces/msg/ParameterValue.msg)
-#[derive(Debug, Clone)]
+///
+/// Values convert from and to the corresponding Rust types with [`From`] and
+/// [`TryFrom`]. Conversion to a Rust type fails if the value is of another
+/// type, and returns the value back as the error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ParameterValue {
     NotSet,
     Boolean(bool),
@@ -66,6 +84,107 @@ impl ParameterValue {
     pub fn to_parameter_type_raw(p: &ParameterValue) -> u8 {
         Self::to_parameter_type(p) as u8
     }
+
+    /// Parse a value the way `ros2 param set` does.
+    ///
+    /// The text is read as a YAML scalar or flow sequence, and typed like
+    /// the ROS 2 command line tools do:
+    ///
+    /// * `true`, `no`, `On` etc. are [`ParameterValue::Boolean`]
+    /// * `3`, `-0x1f`, `010` (octal) are [`ParameterValue::Integer`]
+    /// * `3.0`, `1.5e+3`, `.inf` are [`ParameterValue::Double`]
+    /// * `[1, 2]` is an [`ParameterValue::IntegerArray`], and similarly for
+    ///   the other array types. An empty list is a
+    ///   [`ParameterValue::BooleanArray`].
+    /// * Anything else, including lists of mixed types, is a
+    ///   [`ParameterValue::String`] of the whole text, quotes included.
+    ///
+    /// The command line cannot express a [`ParameterValue::ByteArray`].
+    /// Sexagesimal numbers of YAML 1.1 are not supported.
+    pub fn from_cli_str(text: &str) -> ParameterValue {
+        let string = || ParameterValue::String(text.to_owned());
+        let trimmed = text.trim();
+        if let Some(items) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let Some(items) = cli::split_flow_sequence(items) else {
+                return string();
+            };
+            let items: Vec<cli::Item> = items.iter().map(|item| cli::Item::parse(item)).collect();
+            if items.iter().all(|i| matches!(i, cli::Item::Bool(_))) {
+                ParameterValue::BooleanArray(items.iter().filter_map(cli::Item::as_bool).collect())
+            } else if items
+                .iter()
+                .all(|i| matches!(i, cli::Item::Bool(_) | cli::Item::Int(_)))
+            {
+                // As in Python, booleans are also integers.
+                ParameterValue::IntegerArray(items.iter().filter_map(cli::Item::as_int).collect())
+            } else if items.iter().all(|i| matches!(i, cli::Item::Double(_))) {
+                ParameterValue::DoubleArray(items.iter().filter_map(cli::Item::as_double).collect())
+            } else if items.iter().all(|i| matches!(i, cli::Item::Str(_))) {
+                ParameterValue::StringArray(
+                    items
+                        .into_iter()
+                        .filter_map(|i| match i {
+                            cli::Item::Str(s) => Some(s),
+                            _ => None,
+                        })
+                        .collect(),
+                )
+            } else {
+                string()
+            }
+        } else {
+            match cli::Item::parse(trimmed) {
+                cli::Item::Bool(b) => ParameterValue::Boolean(b),
+                cli::Item::Int(i) => ParameterValue::Integer(i),
+                cli::Item::Double(d) => ParameterValue::Double(d),
+                cli::Item::Str(_) | cli::Item::Other => string(),
+            }
+        }
+    }
+}
+
+macro_rules! parameter_value_conversions {
+    ($($variant:ident($ty:ty)),*) => {
+        $(
+            impl From<$ty> for ParameterValue {
+                fn from(value: $ty) -> ParameterValue {
+                    ParameterValue::$variant(value)
+                }
+            }
+
+            impl TryFrom<ParameterValue> for $ty {
+                type Error = ParameterValue;
+
+                fn try_from(value: ParameterValue) -> Result<$ty, ParameterValue> {
+                    match value {
+                        ParameterValue::$variant(v) => Ok(v),
+                        other => Err(other),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+parameter_value_conversions!(
+    Boolean(bool),
+    Integer(i64),
+    Double(f64),
+    String(String),
+    ByteArray(Vec<u8>),
+    BooleanArray(Vec<bool>),
+    IntegerArray(Vec<i64>),
+    DoubleArray(Vec<f64>),
+    StringArray(Vec<String>)
+);
+
+impl From<&str> for ParameterValue {
+    fn from(value: &str) -> ParameterValue {
+        ParameterValue::String(value.to_owned())
+    }
 }
 
 impl From<raw::Parameter> for Parameter {
@@ -92,10 +211,8 @@ impl From<raw::ParameterValue> for ParameterValue {
             raw::ParameterType::DOUBLE_ARRAY => ParameterValue::DoubleArray(rpv.double_array),
             raw::ParameterType::STRING_ARRAY => ParameterValue::StringArray(rpv.string_array),
 
-            _ =>
-            // This may be an unspecified case.
-            // TODO: Do something better, at least log a warning.
-            {
+            ptype => {
+                warn!("Unknown ParameterValue type {ptype}, treating as not set");
                 ParameterValue::NotSet
             }
         }
@@ -285,6 +402,282 @@ impl From<ParameterDescriptor> for raw::ParameterDescriptor {
     }
 }
 
+/// Rust-like representation of ROS2
+/// [ParameterEvent](https://github.com/ros2/rcl_interfaces/blob/master/rcl_interfaces/msg/ParameterEvent.msg)
+///
+/// Nodes publish these to Topic `/parameter_events` when their Parameters
+/// change.
+#[derive(Debug, Clone)]
+pub struct ParameterEvent {
+    /// [`ROSTime::ZERO`] if the sender gave an invalid timestamp
+    pub timestamp: ROSTime,
+    /// Fully qualified name of the Node whose Parameters changed
+    pub node: String,
+    pub new_parameters: Vec<Parameter>,
+    pub changed_parameters: Vec<Parameter>,
+    pub deleted_parameters: Vec<Parameter>,
+}
+
+impl From<raw::ParameterEvent> for ParameterEvent {
+    fn from(e: raw::ParameterEvent) -> Self {
+        let parameters = |ps: Vec<raw::Parameter>| ps.into_iter().map(Parameter::from).collect();
+        ParameterEvent {
+            timestamp: ROSTime::try_from(e.timestamp).unwrap_or(ROSTime::ZERO),
+            node: e.node,
+            new_parameters: parameters(e.new_parameters),
+            changed_parameters: parameters(e.changed_parameters),
+            deleted_parameters: parameters(e.deleted_parameters),
+        }
+    }
+}
+
+/// Subscription to the [`ParameterEvent`]s of all Nodes
+///
+/// This uses the builtin `/parameter_events` Topic and its QoS, so nothing
+/// needs to be declared by the application.
+pub struct ParameterEventsSubscription {
+    subscription: Subscription<raw::ParameterEvent>,
+}
+
+impl ParameterEventsSubscription {
+    pub fn new(node: &mut Node) -> CreateResult<ParameterEventsSubscription> {
+        let topic = node.ros_context.get_parameter_events_topic();
+        Ok(ParameterEventsSubscription {
+            subscription: node.create_subscription(&topic, None)?,
+        })
+    }
+
+    pub fn take(&self) -> ReadResult<Option<(ParameterEvent, MessageInfo)>> {
+        Ok(self
+            .subscription
+            .take()?
+            .map(|(event, info)| (event.into(), info)))
+    }
+
+    pub async fn async_take(&self) -> ReadResult<(ParameterEvent, MessageInfo)> {
+        let (event, info) = self.subscription.async_take().await?;
+        Ok((event.into(), info))
+    }
+
+    /// Returns an async Stream of events with MessageInfo metadata
+    pub fn async_stream(
+        &self,
+    ) -> impl FusedStream<Item = ReadResult<(ParameterEvent, MessageInfo)>> + '_ {
+        self.subscription
+            .async_stream()
+            .map(|result| result.map(|(event, info)| (event.into(), info)))
+    }
+
+    /// The underlying Subscription, with events in wire format
+    pub fn subscription(&self) -> &Subscription<raw::ParameterEvent> {
+        &self.subscription
+    }
+}
+
+// Typing of command line values like `ros2 param set`, which reads them with
+// PyYAML, i.e. YAML 1.1 rules.
+mod cli {
+    #[derive(Debug)]
+    pub(super) enum Item {
+        Bool(bool),
+        Int(i64),
+        Double(f64),
+        Str(String),
+        // null, nested collections etc.
+        Other,
+    }
+
+    impl Item {
+        pub(super) fn parse(text: &str) -> Item {
+            if let Some(s) = quoted(text, '"') {
+                return unescape_double_quoted(s).map_or(Item::Other, Item::Str);
+            }
+            if let Some(s) = quoted(text, '\'') {
+                return Item::Str(s.replace("''", "'"));
+            }
+            match text {
+                "" | "~" | "null" | "Null" | "NULL" => return Item::Other,
+                "true" | "True" | "TRUE" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" => {
+                    return Item::Bool(true)
+                }
+                "false" | "False" | "FALSE" | "no" | "No" | "NO" | "off" | "Off" | "OFF" => {
+                    return Item::Bool(false)
+                }
+                _ => (),
+            }
+            if let Some(i) = parse_int(text) {
+                Item::Int(i)
+            } else if let Some(d) = parse_float(text) {
+                Item::Double(d)
+            } else if text.starts_with(['[', '{']) {
+                Item::Other
+            } else {
+                Item::Str(text.to_owned())
+            }
+        }
+
+        pub(super) fn as_bool(&self) -> Option<bool> {
+            match self {
+                Item::Bool(b) => Some(*b),
+                _ => None,
+            }
+        }
+
+        pub(super) fn as_int(&self) -> Option<i64> {
+            match self {
+                Item::Bool(b) => Some(i64::from(*b)),
+                Item::Int(i) => Some(*i),
+                _ => None,
+            }
+        }
+
+        pub(super) fn as_double(&self) -> Option<f64> {
+            match self {
+                Item::Double(d) => Some(*d),
+                _ => None,
+            }
+        }
+    }
+
+    fn quoted(text: &str, quote: char) -> Option<&str> {
+        text.strip_prefix(quote)?.strip_suffix(quote)
+    }
+
+    fn unescape_double_quoted(s: &str) -> Option<String> {
+        let mut out = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            out.push(match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                c @ ('"' | '\\' | '/' | ' ') => c,
+                _ => return None,
+            });
+        }
+        Some(out)
+    }
+
+    // Splits the inside of a flow sequence `[...]` into items. Returns `None`
+    // if the brackets or quotes do not balance, which is a YAML syntax error.
+    pub(super) fn split_flow_sequence(inner: &str) -> Option<Vec<String>> {
+        let mut items = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0_usize;
+        let mut quote = None;
+        for c in inner.chars() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => (),
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '[' | '{') => depth += 1,
+                (None, ']' | '}') => depth = depth.checked_sub(1)?,
+                (None, ',') if depth == 0 => {
+                    items.push(current.trim().to_owned());
+                    current.clear();
+                    continue;
+                }
+                _ => (),
+            }
+            current.push(c);
+        }
+        if quote.is_some() || depth != 0 {
+            return None;
+        }
+        // A trailing comma is allowed, and so is an empty sequence.
+        let last = current.trim();
+        if !last.is_empty() {
+            items.push(last.to_owned());
+        }
+        if items.iter().any(String::is_empty) {
+            return None;
+        }
+        Some(items)
+    }
+
+    fn split_sign(text: &str) -> (bool, &str) {
+        match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        }
+    }
+
+    fn parse_int(text: &str) -> Option<i64> {
+        let (negative, digits) = split_sign(text);
+        let (radix, digits) = if let Some(d) = digits.strip_prefix("0b") {
+            (2, d)
+        } else if let Some(d) = digits.strip_prefix("0x") {
+            (16, d)
+        } else if digits.len() > 1 && digits.starts_with('0') {
+            (8, &digits[1..])
+        } else {
+            (10, digits)
+        };
+        if digits.is_empty() || digits.starts_with('_') {
+            return None;
+        }
+        let digits = digits.replace('_', "");
+        if !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        let magnitude = i128::from_str_radix(&digits, radix).ok()?;
+        i64::try_from(if negative { -magnitude } else { magnitude }).ok()
+    }
+
+    fn parse_float(text: &str) -> Option<f64> {
+        let (negative, rest) = split_sign(text);
+        let sign = if negative { -1.0 } else { 1.0 };
+        match rest {
+            ".inf" | ".Inf" | ".INF" => return Some(sign * f64::INFINITY),
+            ".nan" | ".NaN" | ".NAN" if rest.len() == text.len() => return Some(f64::NAN),
+            _ => (),
+        }
+        let (mantissa, exponent) = match rest.find(['e', 'E']) {
+            Some(pos) => (&rest[..pos], Some(&rest[pos + 1..])),
+            None => (rest, None),
+        };
+        let (int_part, frac_part) = mantissa.split_once('.')?;
+        let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit() || c == '_');
+        let valid_mantissa = if int_part.is_empty() {
+            // `.5` is a float, but `-.5` is not.
+            rest.len() == text.len() && !frac_part.is_empty() && digits(frac_part)
+        } else {
+            int_part.starts_with(|c: char| c.is_ascii_digit())
+                && digits(int_part)
+                && digits(frac_part)
+        };
+        // The exponent must have a sign.
+        let valid_exponent = exponent.is_none_or(|e| {
+            e.len() > 1 && e.starts_with(['+', '-']) && e[1..].chars().all(|c| c.is_ascii_digit())
+        });
+        if !(valid_mantissa && valid_exponent) {
+            return None;
+        }
+        let mut number = format!(
+            "{}.{}",
+            int_part.replace('_', ""),
+            frac_part.replace('_', "")
+        );
+        if number.starts_with('.') {
+            number.insert(0, '0');
+        }
+        if number.ends_with('.') {
+            number.push('0');
+        }
+        if let Some(e) = exponent {
+            number.push('e');
+            number.push_str(e);
+        }
+        number.parse::<f64>().ok().map(|d| sign * d)
+    }
+}
+
 /// Raw, ROS2-compatible Parameters for sending over the wire.
 /// Not for use in a Rust application.
 pub mod raw {
@@ -380,3 +773,292 @@ pub mod raw {
         pub step: f64,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rustdds::{
+        no_key::SerializerAdapter, serialization::deserialize_from_cdr_with_rep_id,
+        CDRSerializerAdapter, RepresentationIdentifier,
+    };
+
+    use super::*;
+    #[cfg(feature = "parameters")]
+    use crate::{
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+        prelude::{Name, ServiceMapping, ServiceTypeName},
+    };
+
+    fn all_types() -> Vec<ParameterValue> {
+        vec![
+            ParameterValue::NotSet,
+            true.into(),
+            (-7_i64).into(),
+            2.5.into(),
+            "text".into(),
+            vec![0_u8, 255].into(),
+            vec![true, false].into(),
+            vec![1_i64, i64::MAX].into(),
+            vec![0.5, -1e300].into(),
+            vec!["a".to_owned(), String::new()].into(),
+        ]
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        for value in all_types() {
+            let raw_value = raw::ParameterValue::from(value.clone());
+            assert_eq!(
+                raw_value.ptype,
+                ParameterValue::to_parameter_type_raw(&value)
+            );
+            let bytes = CDRSerializerAdapter::<raw::ParameterValue>::to_bytes(&raw_value).unwrap();
+            let (decoded, _) = deserialize_from_cdr_with_rep_id::<raw::ParameterValue>(
+                &bytes,
+                RepresentationIdentifier::CDR_LE,
+            )
+            .unwrap();
+            assert_eq!(ParameterValue::from(decoded), value);
+        }
+    }
+
+    #[test]
+    fn rust_conversions() {
+        assert_eq!(i64::try_from(ParameterValue::Integer(3)), Ok(3));
+        assert_eq!(
+            Vec::<u8>::try_from(ParameterValue::ByteArray(vec![1])),
+            Ok(vec![1])
+        );
+        assert_eq!(
+            bool::try_from(ParameterValue::Integer(1)),
+            Err(ParameterValue::Integer(1))
+        );
+    }
+
+    // Expected types are as given by `ros2 param set` followed by
+    // `ros2 param get`.
+    #[test]
+    fn cli_values() {
+        use ParameterValue::*;
+        let string = |s: &str| String(s.to_owned());
+        let cases = [
+            ("true", Boolean(true)),
+            ("Off", Boolean(false)),
+            ("yes", Boolean(true)),
+            ("42", Integer(42)),
+            ("-0x1f", Integer(-31)),
+            ("010", Integer(8)),
+            ("0o17", string("0o17")),
+            ("1_000", Integer(1000)),
+            ("3.0", Double(3.0)),
+            ("3.", Double(3.0)),
+            (".5", Double(0.5)),
+            ("-1.5e+3", Double(-1500.0)),
+            ("-.inf", Double(f64::NEG_INFINITY)),
+            ("1e3", string("1e3")),
+            ("-.5", string("-.5")),
+            ("hello world", string("hello world")),
+            ("'3'", string("'3'")),
+            ("null", string("null")),
+            ("a: 1", string("a: 1")),
+            ("[]", BooleanArray(vec![])),
+            ("[true, no]", BooleanArray(vec![true, false])),
+            ("[1, 2, 3,]", IntegerArray(vec![1, 2, 3])),
+            ("[1, true]", IntegerArray(vec![1, 1])),
+            ("[1.0, 2.5]", DoubleArray(vec![1.0, 2.5])),
+            ("[1, 2.5]", string("[1, 2.5]")),
+            (
+                r#"[a, 'b, c', "d\n"]"#,
+                StringArray(vec!["a".into(), "b, c".into(), "d\n".into()]),
+            ),
+            ("[a, [b]]", string("[a, [b]]")),
+            ("[1, 2", string("[1, 2")),
+            ("[1,, 2]", string("[1,, 2]")),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(ParameterValue::from_cli_str(text), expected, "{text}");
+        }
+        assert!(matches!(
+            ParameterValue::from_cli_str(".NaN"),
+            Double(d) if d.is_nan()
+        ));
+    }
+
+    #[cfg(feature = "parameters")]
+    #[test]
+    fn read_only_parameters() {
+        let context = Context::new().unwrap();
+        let node = test_node(
+            &context,
+            "test_read_only_node",
+            NodeOptions::new()
+                .declare_parameter("foo", ParameterValue::Boolean(true))
+                .declare_read_only_parameter("bar", ParameterValue::Integer(1)),
+        );
+        assert!(node.is_parameter_read_only("bar"));
+        assert!(node
+            .set_parameter("bar", ParameterValue::Integer(2))
+            .is_err());
+        assert!(node
+            .set_parameter("foo", ParameterValue::Boolean(false))
+            .is_ok());
+
+        node.freeze_parameters();
+        assert!(node
+            .set_parameter("foo", ParameterValue::Boolean(true))
+            .is_err());
+        assert!(matches!(
+            node.get_parameter("foo"),
+            Some(ParameterValue::Boolean(false))
+        ));
+    }
+
+    #[cfg(feature = "parameters")]
+    #[test]
+    fn parameter_set_validator() {
+        let context = Context::new().unwrap();
+        let node = test_node(
+            &context,
+            "test_set_validator_node",
+            NodeOptions::new()
+                .declare_parameter("min_speed", ParameterValue::Double(1.0))
+                .declare_parameter("max_speed", ParameterValue::Double(2.0))
+                .parameter_set_validator(Box::new(|params| {
+                    match (params.get("min_speed"), params.get("max_speed")) {
+                        (Some(ParameterValue::Double(min)), Some(ParameterValue::Double(max)))
+                            if min <= max =>
+                        {
+                            Ok(())
+                        }
+                        _ => Err("max_speed must be >= min_speed".to_owned()),
+                    }
+                })),
+        );
+        assert!(node
+            .set_parameter("min_speed", ParameterValue::Double(3.0))
+            .is_err());
+        assert!(node
+            .set_parameters_atomically(vec![
+                Parameter {
+                    name: "max_speed".to_owned(),
+                    value: ParameterValue::Double(4.0)
+                },
+                Parameter {
+                    name: "min_speed".to_owned(),
+                    value: ParameterValue::Double(3.0)
+                },
+            ])
+            .is_ok());
+        assert!(matches!(
+            node.get_parameter("min_speed"),
+            Some(ParameterValue::Double(v)) if v == 3.0
+        ));
+    }
+
+    #[cfg(feature = "parameters")]
+    #[test]
+    fn parameter_services() {
+        use std::time::Duration;
+
+        use futures::{executor::block_on, pin_mut, select, FutureExt};
+        use futures_timer::Delay;
+
+        use crate::{
+            interfaces::rcl_interfaces::{
+                GetParametersRequest, GetParametersService, SetParametersRequest,
+                SetParametersService,
+            },
+            service::parameters::raw,
+        };
+
+        // The client and server are in separate Contexts, as over the network.
+        let mut server_node = test_node(
+            &Context::new().unwrap(),
+            "test_param_server_node",
+            NodeOptions::new()
+                .enable_rosout(false)
+                .declare_parameter("foo", ParameterValue::Integer(1)),
+        );
+        let _server_spinner = server_node.spin_in_background().unwrap();
+        let mut client_node = test_node(
+            &Context::new().unwrap(),
+            "test_param_client_node",
+            NodeOptions::new().enable_rosout(false),
+        );
+        let _client_spinner = client_node.spin_in_background().unwrap();
+
+        let service_name = |base| Name::new("/rustdds/test_param_server_node", base).unwrap();
+        let get_client = client_node
+            .create_client::<GetParametersService>(
+                ServiceMapping::Enhanced,
+                &service_name("get_parameters"),
+                &ServiceTypeName::new("rcl_interfaces", "GetParameters"),
+                None,
+                None,
+            )
+            .unwrap();
+        let set_client = client_node
+            .create_client::<SetParametersService>(
+                ServiceMapping::Enhanced,
+                &service_name("set_parameters"),
+                &ServiceTypeName::new("rcl_interfaces", "SetParameters"),
+                None,
+                None,
+            )
+            .unwrap();
+
+        let exchange = async {
+            // wait_for_service can miss a match that happens while it starts.
+            for waiting in [
+                get_client.wait_for_service(&client_node).boxed_local(),
+                set_client.wait_for_service(&client_node).boxed_local(),
+            ] {
+                let mut waiting = waiting.fuse();
+                loop {
+                    select! {
+                        _ = waiting => break,
+                        _ = Delay::new(Duration::from_millis(100)).fuse() => {}
+                    }
+                }
+            }
+            let set = set_client
+                .async_call_service(SetParametersRequest {
+                    parameter: vec![raw::Parameter::from(Parameter {
+                        name: "foo".to_owned(),
+                        value: ParameterValue::Integer(2),
+                    })],
+                })
+                .await
+                .unwrap();
+            let get = get_client
+                .async_call_service(GetParametersRequest {
+                    names: vec!["foo".to_owned()],
+                })
+                .await
+                .unwrap();
+            (set, get)
+        }
+        .fuse();
+        let (set, get) = block_on(async {
+            pin_mut!(exchange);
+            select! {
+                result = exchange => result,
+                _ = Delay::new(Duration::from_secs(20)).fuse() => panic!("timed out"),
+            }
+        });
+        assert!(set.results.iter().all(|r| r.successful));
+        assert_eq!(
+            get.values
+                .into_iter()
+                .map(ParameterValue::from)
+                .collect::<Vec<_>>(),
+            vec![ParameterValue::Integer(2)]
+        );
+        assert_eq!(
+            server_node.get_parameter("foo"),
+            Some(ParameterValue::Integer(2))
+        );
+    }
+}
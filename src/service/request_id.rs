@@ -1,16 +1,89 @@
+use std::{fmt, str::FromStr};
+
 pub use rustdds::SequenceNumber;
 use rustdds::{rpc::*, GUID};
 use serde::{Deserialize, Serialize};
 
+use crate::interfaces::gid::{Gid, ParseIdError};
+
+/// Identifies a Service request, and the response to it.
+///
 /// [Original](https://docs.ros2.org/foxy/api/rmw/structrmw__request__id__t.html)
 /// This structure seems to be identical in structure and function to
 /// SampleIdentity defined by the RPC over DDS Spec.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+///
+/// The string form, from `Display`, is the writer [`Gid`] in hex, a colon,
+/// and the sequence number, e.g. `010f3a...0103:42`. This format is stable
+/// and can be parsed back with `FromStr`, so request ids can be stored e.g.
+/// in a job queue that survives a restart. For that purpose, use module
+/// [`as_string`] with serde, because the derived `Serialize` impl produces a
+/// structured (binary-friendly) representation.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 pub struct RmwRequestId {
+    /// GUID of the DataWriter that sent the request, i.e. identifies the
+    /// Client.
     pub writer_guid: GUID,
+    /// Sequence number of the request. These increase by one for each request
+    /// from the same Client.
     pub sequence_number: SequenceNumber,
 }
 
+impl fmt::Display for RmwRequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}",
+            Gid::from(self.writer_guid),
+            i64::from(self.sequence_number)
+        )
+    }
+}
+
+impl FromStr for RmwRequestId {
+    type Err = ParseIdError;
+    fn from_str(s: &str) -> Result<RmwRequestId, ParseIdError> {
+        let (gid, sn) = s
+            .split_once(':')
+            .ok_or_else(|| ParseIdError::new(format!("Missing ':' in request id {s:?}")))?;
+        let sn: i64 = sn
+            .parse()
+            .map_err(|e| ParseIdError::new(format!("Bad sequence number in {s:?}: {e}")))?;
+        Ok(RmwRequestId {
+            writer_guid: gid.parse::<Gid>()?.into(),
+            sequence_number: SequenceNumber::from(sn),
+        })
+    }
+}
+
+/// Serialize and deserialize using the string form, e.g. with
+/// `#[serde(with = "ros2_client::service::request_id::as_string")]`.
+///
+/// This works for [`RmwRequestId`] and [`Gid`].
+pub mod as_string {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
 impl From<RmwRequestId> for SampleIdentity {
     fn from(
         RmwRequestId {
@@ -47,3 +120,15 @@ impl From<SampleIdentity> for RmwRequestId {
 //   pub received_timestamp: RmwTimePointValue,
 //   pub request_id: RmwRequestId,
 // }
+
+#[test]
+fn request_id_string_round_trip() {
+    let r_id = RmwRequestId {
+        writer_guid: GUID::from_bytes(std::array::from_fn(|i| i as u8)),
+        sequence_number: SequenceNumber::from(42),
+    };
+    let s = r_id.to_string();
+    assert!(s.ends_with(":42"));
+    assert_eq!(s.parse::<RmwRequestId>(), Ok(r_id));
+    assert!("xyz:1".parse::<RmwRequestId>().is_err());
+}
@@ -1,8 +1,19 @@
-use std::io;
-
-use futures::{pin_mut, stream::FusedStream, StreamExt};
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::{Arc, Mutex, Weak},
+    task::{self, Waker},
+    time::Duration,
+};
 
-use log::debug;
+use futures::{
+    future::{self, BoxFuture},
+    pin_mut, select,
+    stream::FusedStream,
+    FutureExt, StreamExt,
+};
+use futures_timer::Delay;
+use log::{debug, warn};
 use mio::{Evented, Poll, PollOpt, Ready, Token};
 use rustdds::{
     dds::{CreateResult, ReadError, ReadResult, WriteResult},
@@ -11,16 +22,24 @@ use rustdds::{
     QosPolicies, RepresentationIdentifier, Timestamp, Topic, TopicDescription, WriteOptionsBuilder,
 };
 
+#[cfg(feature = "no-default-runtime")]
+use crate::readiness::Readiness;
 use crate::{
+    interfaces::names::ros_name_of_dds_topic,
     message::Message,
-    node::Node,
+    node::{
+        counters::{Counters, StatusCounters},
+        EndpointRegistration, Node,
+    },
     prelude::MessageInfo,
+    service::call_trace::{CallEvent, CallTracer},
     service::request_id::RmwRequestId,
     service::wrappers::{
         DataWriterR, RequestWrapper, ResponseWrapper, ServiceDeserializerAdapter,
-        ServiceSerializerAdapter, SimpleDataReaderR,
+        ServiceSerializerAdapter, SimpleDataReaderR, Wrapper as _,
     },
     service::{Service, ServiceMapping},
+    time::steady_time::SteadyTime,
 };
 
 // --------------------------------------------
@@ -34,7 +53,156 @@ where
 {
     service_mapping: ServiceMapping,
     request_receiver: SimpleDataReaderR<RequestWrapper<S::Request>>,
-    response_sender: DataWriterR<ResponseWrapper<S::Response>>,
+    // Shared with the Context, which drains it on shutdown
+    responder: Arc<Responder<S::Response>>,
+    dds_request_topic_name: String,
+    dds_response_topic_name: String,
+    deduplication: Option<RequestDeduplication>,
+    call_tracer: Option<CallTracer>,
+    counters: Counters,
+    // Only held to unregister from the Node on drop
+    _registrations: [EndpointRegistration; 2],
+}
+
+// Remembers recently received requests
+struct RequestDeduplication {
+    window: Duration,
+    seen: Mutex<BTreeMap<RmwRequestId, SteadyTime>>,
+}
+
+impl RequestDeduplication {
+    // Returns true if `request_id` was already seen within the window.
+    fn is_duplicate(&self, request_id: RmwRequestId) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, received| received.elapsed().as_saturating_duration() <= self.window);
+        seen.insert(request_id, SteadyTime::now()).is_some()
+    }
+}
+
+const ACK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Clients give up on requests long before this, so these need no draining.
+const IN_FLIGHT_EXPIRY: Duration = Duration::from_secs(60);
+const MAX_IN_FLIGHT_REQUESTS: usize = 1024;
+
+// A Server that can be drained on shutdown, without knowing its Service type
+pub(crate) trait DrainServer: Send + Sync {
+    fn drain(&self) -> BoxFuture<'_, bool>;
+}
+
+// The response writer of a Server, and the requests it has yet to answer
+pub(crate) struct Responder<R: Message> {
+    writer: DataWriterR<ResponseWrapper<R>>,
+    service_name: String,
+    in_flight: Mutex<InFlight>,
+}
+
+// Requests delivered to the application and not yet responded to
+#[derive(Default)]
+struct InFlight {
+    // Draining is off without a timeout, and then requests are not tracked.
+    timeout: Option<Duration>,
+    requests: BTreeMap<RmwRequestId, SteadyTime>,
+    // Task waiting in `drain` for `requests` to become empty
+    waker: Option<Waker>,
+    // Set when a drain starts. New requests are then discarded.
+    draining: bool,
+}
+
+impl InFlight {
+    fn received(&mut self, request_id: RmwRequestId) {
+        if self.timeout.is_none() {
+            return;
+        }
+        // Forget requests the application does not answer at all.
+        self.requests
+            .retain(|_, received| received.elapsed().as_saturating_duration() <= IN_FLIGHT_EXPIRY);
+        if self.requests.len() >= MAX_IN_FLIGHT_REQUESTS {
+            let oldest = self
+                .requests
+                .iter()
+                .min_by_key(|(_, received)| **received)
+                .map(|(request_id, _)| *request_id);
+            if let Some(oldest) = oldest {
+                self.requests.remove(&oldest);
+            }
+        }
+        self.requests.insert(request_id, SteadyTime::now());
+    }
+
+    fn answered(&mut self, request_id: RmwRequestId) {
+        if self.requests.remove(&request_id).is_some() && self.requests.is_empty() {
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<R> Responder<R>
+where
+    R: Message,
+{
+    // Returns the drain timeout.
+    fn start_draining(&self) -> Option<Duration> {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        in_flight.draining = true;
+        in_flight.timeout
+    }
+
+    async fn drain(&self) -> bool {
+        let Some(timeout) = self.start_draining() else {
+            return true;
+        };
+        let answered = future::poll_fn(|cx| {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            if in_flight.requests.is_empty() {
+                task::Poll::Ready(())
+            } else {
+                in_flight.waker = Some(cx.waker().clone());
+                task::Poll::Pending
+            }
+        });
+        let acknowledged = async {
+            answered.await;
+            // The RustDDS future does not register a waker before its first
+            // acknowledgment check, so poll it periodically.
+            let acked = self.writer.async_wait_for_acknowledgments();
+            pin_mut!(acked);
+            let mut tick = Delay::new(ACK_POLL_INTERVAL);
+            future::poll_fn(|cx| {
+                if let task::Poll::Ready(result) = acked.poll_unpin(cx) {
+                    return task::Poll::Ready(result);
+                }
+                while tick.poll_unpin(cx).is_ready() {
+                    tick.reset(ACK_POLL_INTERVAL);
+                }
+                task::Poll::Pending
+            })
+            .await
+        };
+        let drained = select! {
+            result = acknowledged.fuse() => matches!(result, Ok(true)),
+            () = Delay::new(timeout).fuse() => false,
+        };
+        if !drained {
+            warn!(
+                "Server {}: drain timed out with {} requests unanswered",
+                self.service_name,
+                self.in_flight.lock().unwrap().requests.len()
+            );
+        }
+        drained
+    }
+}
+
+impl<R> DrainServer for Responder<R>
+where
+    R: Message,
+{
+    fn drain(&self) -> BoxFuture<'_, bool> {
+        Box::pin(Responder::drain(self))
+    }
 }
 
 impl<S> Server<S>
@@ -49,11 +217,11 @@ where
         qos_request: Option<QosPolicies>,
         qos_response: Option<QosPolicies>,
     ) -> CreateResult<Self> {
-        let request_receiver =
+        let (request_receiver, request_registration) =
       node.create_simpledatareader
       ::<RequestWrapper<S::Request>, ServiceDeserializerAdapter<RequestWrapper<S::Request>>>(
         request_topic, qos_request)?;
-        let response_sender =
+        let (writer, response_registration) =
       node.create_datawriter
       ::<ResponseWrapper<S::Response>, ServiceSerializerAdapter<ResponseWrapper<S::Response>>>(
         response_topic, qos_response)?;
@@ -64,29 +232,188 @@ where
             response_topic.name()
         );
 
+        let responder = Arc::new(Responder {
+            writer,
+            service_name: ros_name_of_dds_topic(&request_topic.name()),
+            in_flight: Mutex::new(InFlight {
+                timeout: node.server_drain_timeout(),
+                ..InFlight::default()
+            }),
+        });
+        node.register_server(Arc::downgrade(&responder) as Weak<dyn DrainServer>);
+
         Ok(Server::<S> {
             service_mapping,
             request_receiver,
-            response_sender,
+            responder,
+            dds_request_topic_name: request_topic.name(),
+            dds_response_topic_name: response_topic.name(),
+            deduplication: None,
+            call_tracer: node.call_tracer(ros_name_of_dds_topic(&request_topic.name())),
+            counters: Counters::default(),
+            _registrations: [request_registration, response_registration],
         })
     }
 
+    /// Discard requests with the same [`RmwRequestId`] as one received within
+    /// the last `window`. `None` turns deduplication off, which is the
+    /// default.
+    ///
+    /// This protects non-idempotent Services from executing a retransmitted
+    /// or retried request twice. A duplicate is not delivered to the
+    /// application, so it is not answered again either.
+    pub fn set_request_deduplication(&mut self, window: Option<Duration>) {
+        self.deduplication = window.map(|window| RequestDeduplication {
+            window,
+            seen: Mutex::new(BTreeMap::new()),
+        });
+    }
+
+    /// How long to wait for responses to requests already received, when
+    /// shutting down. `None` turns draining off, which is the default unless
+    /// set with [`NodeOptions::server_drain_timeout`].
+    ///
+    /// With a drain timeout, [`drain`](Self::drain) waits until all requests
+    /// received so far have been responded to, and the responses have been
+    /// acknowledged by the Clients. [`Node::drain_servers`] and
+    /// [`Context::drain_servers`] drain all Servers of a Node or a Context.
+    /// Await one of them before dropping the Servers, so that Clients do not
+    /// wait for lost responses until their own timeout, e.g. during rolling
+    /// restarts.
+    ///
+    /// Only requests received after setting the timeout are tracked, and
+    /// requests not answered within a minute are forgotten.
+    ///
+    /// [`NodeOptions::server_drain_timeout`]: crate::node::NodeOptions::server_drain_timeout
+    /// [`Context::drain_servers`]: crate::node::context::Context::drain_servers
+    pub fn set_drain_timeout(&mut self, timeout: Option<Duration>) {
+        let mut in_flight = self.responder.in_flight.lock().unwrap();
+        in_flight.timeout = timeout;
+        if timeout.is_none() {
+            in_flight.requests.clear();
+        }
+    }
+
+    /// Number of requests received but not yet responded to. Tracked only with
+    /// a [drain timeout](Self::set_drain_timeout).
+    pub fn pending_requests(&self) -> usize {
+        self.responder.in_flight.lock().unwrap().requests.len()
+    }
+
+    /// Waits until all received requests have been responded to and the
+    /// responses acknowledged, at most for the
+    /// [drain timeout](Self::set_drain_timeout). Returns `true` if all of them
+    /// made it in time.
+    ///
+    /// This is meant for shutdown: keep responding to the requests received,
+    /// and await this concurrently. Once a drain has started, new requests
+    /// are discarded, so that they cannot keep the drain from completing:
+    /// [`receive_request`](Self::receive_request) returns `None`, and the
+    /// async receive functions do not deliver any more requests. Returns
+    /// `true` immediately if there is no drain timeout.
+    pub async fn drain(&self) -> bool {
+        self.responder.drain().await
+    }
+
+    // Requests are discarded if they are duplicates, or if the Server is
+    // being drained. Also counts the request as taken or rejected.
+    fn discard_request(&self, request_id: RmwRequestId) -> bool {
+        let duplicate = self
+            .deduplication
+            .as_ref()
+            .is_some_and(|d| d.is_duplicate(request_id));
+        let draining = self.responder.in_flight.lock().unwrap().draining;
+        if duplicate || draining {
+            if draining {
+                debug!("Discarding request {request_id:?} received while draining");
+            } else {
+                debug!("Discarding duplicate request {request_id:?}");
+            }
+            self.counters.count_rejected();
+        } else {
+            self.counters.count_taken();
+            if let Some(tracer) = &self.call_tracer {
+                tracer.request(CallEvent::RequestReceived, request_id);
+            }
+            self.responder
+                .in_flight
+                .lock()
+                .unwrap()
+                .received(request_id);
+        }
+        duplicate || draining
+    }
+
+    fn response_sent(&self, rmw_req_id: RmwRequestId, result: &WriteResult<(), ()>) {
+        if let (Some(tracer), Ok(_)) = (&self.call_tracer, result) {
+            tracer.request(CallEvent::ResponseSent, rmw_req_id);
+        }
+        self.responder
+            .in_flight
+            .lock()
+            .unwrap()
+            .answered(rmw_req_id);
+    }
+
+    // Decodes a received request, counting it as received or as an error.
+    fn unwrap_request(
+        &self,
+        dcc: no_key::DeserializedCacheChange<RequestWrapper<S::Request>>,
+    ) -> ReadResult<(RmwRequestId, S::Request)> {
+        let mi = MessageInfo::from(&dcc);
+        let req_wrapper = dcc.into_value();
+        self.counters.count_received(req_wrapper.bytes().len());
+        req_wrapper
+            .unwrap(self.service_mapping, &mi)
+            .inspect_err(|_| self.counters.count_read_error())
+    }
+
+    /// Counts of requests received and responses sent so far.
+    pub fn status_counters(&self) -> StatusCounters {
+        self.counters.snapshot()
+    }
+
+    /// Remove this Server from DDS and from its Node.
+    ///
+    /// This is the same as dropping the Server, but states the intent.
+    pub fn close(self) {}
+
+    /// Fully qualified ROS 2 Service name, after remapping, e.g.
+    /// `/add_two_ints`
+    pub fn service_name(&self) -> String {
+        ros_name_of_dds_topic(&self.dds_request_topic_name)
+    }
+
+    /// Name of the DDS Topic for requests, e.g. `rq/add_two_intsRequest`
+    pub fn dds_request_topic_name(&self) -> &str {
+        &self.dds_request_topic_name
+    }
+
+    /// Name of the DDS Topic for responses, e.g. `rr/add_two_intsReply`
+    pub fn dds_response_topic_name(&self) -> &str {
+        &self.dds_response_topic_name
+    }
+
     /// Receive a request from Client.
     /// Returns `Ok(None)` if no new requests have arrived.
     pub fn receive_request(&self) -> ReadResult<Option<(RmwRequestId, S::Request)>> {
         self.request_receiver.drain_read_notifications();
-        let dcc_rw: Option<no_key::DeserializedCacheChange<RequestWrapper<S::Request>>> =
-            self.request_receiver.try_take_one()?;
-
-        match dcc_rw {
-            None => Ok(None),
-            Some(dcc) => {
-                let mi = MessageInfo::from(&dcc);
-                let req_wrapper = dcc.into_value();
-                let (ri, req) = req_wrapper.unwrap(self.service_mapping, &mi)?;
-                Ok(Some((ri, req)))
-            }
-        } // match
+        loop {
+            let dcc_rw: Option<no_key::DeserializedCacheChange<RequestWrapper<S::Request>>> = self
+                .request_receiver
+                .try_take_one()
+                .inspect_err(|_| self.counters.count_read_error())?;
+
+            match dcc_rw {
+                None => return Ok(None),
+                Some(dcc) => {
+                    let (ri, req) = self.unwrap_request(dcc)?;
+                    if !self.discard_request(ri) {
+                        return Ok(Some((ri, req)));
+                    }
+                }
+            } // match
+        }
     }
 
     /// Send response to request by Client.
@@ -110,10 +437,15 @@ where
             // WriteOptions (QoS ParameterList), but within data payload.
             // But maybe it is not harmful to send it in both?
             .build();
-        self.response_sender
-            .write_with_options(resp_wrapper, write_opts)
-            .map(|_| ())
-            .map_err(|e| e.forget_data()) // lose SampleIdentity result
+        let bytes = resp_wrapper.bytes().len();
+        let result = self
+            .responder
+            .writer
+            .write_with_options(resp_wrapper, write_opts);
+        self.counters.count_write(&result, bytes);
+        let result = result.map(|_| ()).map_err(|e| e.forget_data()); // lose SampleIdentity result
+        self.response_sent(rmw_req_id, &result);
+        result
     }
 
     /// The request_id must be sent back with the response to identify which
@@ -122,18 +454,27 @@ where
         let dcc_stream = self.request_receiver.as_async_stream();
         pin_mut!(dcc_stream);
 
-        match dcc_stream.next().await {
-            Some(Err(e)) => Err(e),
-            Some(Ok(dcc)) => {
-                let mi = MessageInfo::from(&dcc);
-                let req_wrapper = dcc.into_value();
-                let (ri, req) = req_wrapper.unwrap(self.service_mapping, &mi)?;
-                debug!("async_receive_request: {ri:?}");
-                Ok((ri, req))
-            }
-            // This should never occur, because topic do not "end".
-            None => read_error_internal!("SimpleDataReader value stream unexpectedly ended!"),
-        } // match
+        loop {
+            match dcc_stream.next().await {
+                Some(Err(e)) => {
+                    self.counters.count_read_error();
+                    return Err(e);
+                }
+                Some(Ok(dcc)) => {
+                    let (ri, req) = self.unwrap_request(dcc)?;
+                    debug!("async_receive_request: {ri:?}");
+                    if !self.discard_request(ri) {
+                        return Ok((ri, req));
+                    }
+                }
+                // This should never occur, because topic do not "end".
+                None => {
+                    return read_error_internal!(
+                        "SimpleDataReader value stream unexpectedly ended!"
+                    )
+                }
+            } // match
+        }
     }
 
     /// Returns a never-ending stream of (request_id, request)
@@ -142,19 +483,24 @@ where
     pub fn receive_request_stream(
         &self,
     ) -> impl FusedStream<Item = ReadResult<(RmwRequestId, S::Request)>> + '_ {
-        Box::pin(self.request_receiver.as_async_stream().then(
-            move |dcc_r| async move {
-                match dcc_r {
-                    Err(e) => Err(e),
-                    Ok(dcc) => {
-                        let mi = MessageInfo::from(&dcc);
-                        let req_wrapper = dcc.into_value();
-                        debug!("receive_request_stream: messageinfo={mi:?}");
-                        req_wrapper.unwrap(self.service_mapping, &mi)
-                    }
-                } // match
-            }, // async
-        ))
+        Box::pin(
+            self.request_receiver
+                .as_async_stream()
+                .then(
+                    move |dcc_r| async move {
+                        match dcc_r {
+                            Err(e) => {
+                                self.counters.count_read_error();
+                                Err(e)
+                            }
+                            Ok(dcc) => self.unwrap_request(dcc),
+                        } // match
+                    }, // async
+                )
+                .filter(move |r| {
+                    future::ready(!matches!(r, Ok((ri, _)) if self.discard_request(*ri)))
+                }),
+        )
     }
 
     /// Asynchronous response sending
@@ -182,11 +528,33 @@ where
             // WriteOptions (QoS ParameterList), but within data payload.
             // But maybe it is not harmful to send it in both?
             .build();
-        self.response_sender
+        let bytes = resp_wrapper.bytes().len();
+        let result = self
+            .responder
+            .writer
             .async_write_with_options(resp_wrapper, write_opts)
-            .await
-            .map(|_| ())
-            .map_err(|e| e.forget_data()) // lose SampleIdentity result
+            .await;
+        self.counters.count_write(&result, bytes);
+        let result = result.map(|_| ()).map_err(|e| e.forget_data()); // lose SampleIdentity result
+        self.response_sent(rmw_req_id, &result);
+        result
+    }
+}
+
+impl<S> Drop for Server<S>
+where
+    S: Service,
+    S::Request: Message,
+    S::Response: Message,
+{
+    fn drop(&mut self) {
+        let unanswered = self.responder.in_flight.lock().unwrap().requests.len();
+        if unanswered > 0 {
+            warn!(
+                "Server {}: dropped with {unanswered} requests unanswered",
+                self.responder.service_name
+            );
+        }
     }
 }
 
@@ -219,3 +587,165 @@ where
         self.request_receiver.deregister(poll)
     }
 }
+
+#[cfg(feature = "no-default-runtime")]
+impl<S> Readiness for Server<S>
+where
+    S: 'static + Service,
+{
+    fn register_waker(&self, waker: &Waker) {
+        self.request_receiver.set_waker(Some(waker.clone()));
+    }
+}
+
+#[cfg(feature = "no-default-runtime")]
+impl<S> mio_08::event::Source for Server<S>
+where
+    S: 'static + Service,
+{
+    fn register(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::register(&mut self.request_receiver, registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio_08::Registry,
+        token: mio_08::Token,
+        interests: mio_08::Interest,
+    ) -> io::Result<()> {
+        mio_08::event::Source::reregister(&mut self.request_receiver, registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio_08::Registry) -> io::Result<()> {
+        mio_08::event::Source::deregister(&mut self.request_receiver, registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, join};
+    use rustdds::{SequenceNumber, GUID};
+
+    use super::*;
+    use crate::{
+        interfaces::lifecycle_msgs::{EmptyRequest, GetState, GetStateResponse},
+        node::{
+            context::{tests::test_node, Context},
+            NodeOptions,
+        },
+        prelude::Name,
+        service::Client,
+        time::steady_time::TimeDiff,
+    };
+
+    fn request_id(n: i64) -> RmwRequestId {
+        RmwRequestId {
+            writer_guid: GUID::from_bytes([1; 16]),
+            sequence_number: SequenceNumber::from(n),
+        }
+    }
+
+    #[test]
+    fn in_flight_is_bounded() {
+        let mut in_flight = InFlight {
+            timeout: Some(Duration::from_secs(1)),
+            ..InFlight::default()
+        };
+        in_flight.requests.insert(
+            request_id(0),
+            SteadyTime::now() - TimeDiff::from_secs(2 * IN_FLIGHT_EXPIRY.as_secs() as i64),
+        );
+        for n in 1..=MAX_IN_FLIGHT_REQUESTS as i64 + 1 {
+            in_flight.received(request_id(n));
+        }
+        assert_eq!(in_flight.requests.len(), MAX_IN_FLIGHT_REQUESTS);
+        assert!(!in_flight.requests.contains_key(&request_id(0)));
+        in_flight.answered(request_id(5));
+        assert_eq!(in_flight.requests.len(), MAX_IN_FLIGHT_REQUESTS - 1);
+
+        let mut untracked = InFlight::default();
+        untracked.received(request_id(1));
+        assert!(untracked.requests.is_empty());
+    }
+
+    #[test]
+    fn drain_servers() {
+        let context = Context::new().unwrap();
+        let mut node = test_node(
+            &context,
+            "test_drain_node",
+            NodeOptions::new()
+                .enable_rosout(false)
+                .server_drain_timeout(Duration::from_millis(500)),
+        );
+        let name = Name::new("/rustdds", "test_drain").unwrap();
+        let server = node.create_server_for::<GetState>(&name).unwrap();
+        let client = node.create_client_for::<GetState>(&name).unwrap();
+        // Not drained with `node`
+        let mut other_node = test_node(
+            &context,
+            "test_drain_other_node",
+            NodeOptions::new()
+                .enable_rosout(false)
+                .server_drain_timeout(Duration::from_millis(500)),
+        );
+        let other_name = Name::new("/rustdds", "test_drain_other").unwrap();
+        let other_server = other_node
+            .create_server_for::<GetState>(&other_name)
+            .unwrap();
+        let other_client = other_node
+            .create_client_for::<GetState>(&other_name)
+            .unwrap();
+
+        // Receives a request, after discovery has matched the Client.
+        async fn receive(client: &Client<GetState>, server: &Server<GetState>) -> RmwRequestId {
+            loop {
+                client.send_request(EmptyRequest::default()).unwrap();
+                select! {
+                    received = server.async_receive_request().fuse() => break received.unwrap().0,
+                    () = Delay::new(Duration::from_millis(100)).fuse() => {}
+                }
+            }
+        }
+
+        block_on(async {
+            // A request in flight is answered while draining, but one that
+            // arrives during the drain is not delivered.
+            let request_id = receive(&client, &server).await;
+            assert_eq!(server.pending_requests(), 1);
+            let (drained, ()) = join!(node.drain_servers(), async {
+                client.send_request(EmptyRequest::default()).unwrap();
+                for _ in 0..40 {
+                    assert!(server.receive_request().unwrap().is_none());
+                    if server.status_counters().samples_rejected > 0 {
+                        break;
+                    }
+                    Delay::new(Duration::from_millis(10)).await;
+                }
+                server
+                    .async_send_response(request_id, GetStateResponse::default())
+                    .await
+                    .unwrap();
+            });
+            assert!(drained);
+            assert_eq!(server.status_counters().samples_rejected, 1);
+            assert_eq!(server.pending_requests(), 0);
+
+            // The Context drains the other Node, with a request in flight
+            // that is never answered.
+            receive(&other_client, &other_server).await;
+            let started = SteadyTime::now();
+            assert!(!context.drain_servers().await);
+            assert!(started.elapsed().as_saturating_duration() >= Duration::from_millis(500));
+        });
+
+        // Dropped Servers are not drained.
+        drop(other_server);
+        assert!(block_on(context.drain_servers()));
+    }
+}
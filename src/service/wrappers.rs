@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{io::Write as _, marker::PhantomData};
 
 use bytes::{BufMut, Bytes, BytesMut};
 
@@ -6,7 +6,6 @@ use log::warn;
 use rustdds::{
     dds::{ReadError, ReadResult, WriteError, WriteResult},
     no_key, read_error_deserialization,
-    rpc::SampleIdentity,
     serialization::{self, deserialize_from_cdr_with_rep_id},
     RepresentationIdentifier, SequenceNumber, GUID,
 };
@@ -15,7 +14,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     message::Message,
     prelude::MessageInfo,
-    service::{request_id, request_id::RmwRequestId, ServiceMapping},
+    service::{generic::SerializedPayload, request_id, request_id::RmwRequestId, ServiceMapping},
 };
 
 // trait Wrapper is for interfacing to Service-specific (De)SerializerAdapter.
@@ -56,58 +55,92 @@ impl<R: Message> RequestWrapper<R> {
     ) -> ReadResult<(RmwRequestId, R)> {
         match service_mapping {
             ServiceMapping::Basic => {
-                // 1. decode "RequestHeader" and
-                // 2. decode Request
-                let mut bytes = self.serialized_message.clone(); // ref copy only
-                let (header, header_size) =
-                    deserialize_from_cdr_with_rep_id::<BasicRequestHeader>(&bytes, self.encoding)?;
-                if bytes.len() < header_size {
-                    read_error_deserialization!("Service request too short")
-                } else {
-                    let _header_bytes = bytes.split_off(header_size);
-                    let (request, _request_bytes) =
-                        deserialize_from_cdr_with_rep_id::<R>(&bytes, self.encoding)?;
-                    Ok((RmwRequestId::from(header.request_id), request))
-                }
+                // Decode "RequestHeader" and Request together, because CDR
+                // alignment of the Request is relative to the start of the
+                // whole message.
+                let ((header, request), _bytes) =
+                    deserialize_from_cdr_with_rep_id::<(BasicRequestHeader, R)>(
+                        &self.serialized_message,
+                        self.encoding,
+                    )?;
+                Ok((header.request_id.into(), request))
             }
             ServiceMapping::Enhanced => {
                 // Enhanced mode does not use any header in the DDS payload.
                 // Therefore, we use a wrapper that is identical to the payload.
                 let (request, _request_bytes) =
                     deserialize_from_cdr_with_rep_id::<R>(&self.serialized_message, self.encoding)?;
-                let mut rmw_req_id = RmwRequestId::from(
-          message_info.related_sample_identity()
-            .unwrap_or_else(|| {
-              // ServiceMapping::Enhanced is supposed to contain related sample identity as 
-              // inline QoS parameter.
-              //
-              // Use the identity of the incoming request as a default, if there was no
-              // related sample identity specified in inline QoS.
-              let backup_identity = message_info.sample_identity();
-              warn!("RequestWrapper::unwrap: related_sample_identity missing. Using sample_identity = {backup_identity:?}");
-              backup_identity
-            })
-        );
-
-                // Logic added for eProsima FastDDS compatibility:
-                //
-                // If the SequenceNumber in related_sample_identity (presumable from inline QoS)
-                // is SEQUENCENUMBER_UNKNOWN, then it cannot refer to a real and valid DATA submessage.
-                // We patch the situation by using the actual SequenceNumber of the Request DATA submessage.
-                //
-                // Maybe FastDDS just forgets to set the field in RELATED_SAMPLE_IDENTITY inline QoS parameter?
-                if rmw_req_id.sequence_number == SequenceNumber::UNKNOWN {
-                    rmw_req_id.sequence_number = message_info.sample_identity().sequence_number;
-                }
-
-                Ok((rmw_req_id, request))
+                Ok((enhanced_request_id(message_info), request))
+            }
+            ServiceMapping::Cyclone => {
+                let (header, body) =
+                    split_header::<CycloneHeader>(&self.serialized_message, self.encoding)?;
+                let (request, _request_bytes) =
+                    deserialize_from_cdr_with_rep_id::<R>(&body, self.encoding)?;
+                Ok((
+                    header.request_id_at_server(message_info.writer_guid()),
+                    request,
+                ))
             }
-            ServiceMapping::Cyclone => cyclone_unwrap::<R>(
+        }
+    }
+
+    // Like unwrap, but leaves the request serialized.
+    pub(super) fn unwrap_serialized(
+        &self,
+        service_mapping: ServiceMapping,
+        message_info: &MessageInfo,
+    ) -> ReadResult<(RmwRequestId, SerializedPayload)> {
+        let (r_id, data) = match service_mapping {
+            ServiceMapping::Basic => return basic_unsupported_read(),
+            ServiceMapping::Enhanced => (
+                enhanced_request_id(message_info),
                 self.serialized_message.clone(),
-                message_info.writer_guid(),
-                self.encoding,
             ),
+            ServiceMapping::Cyclone => {
+                let (header, body) =
+                    split_header::<CycloneHeader>(&self.serialized_message, self.encoding)?;
+                (
+                    header.request_id_at_server(message_info.writer_guid()),
+                    body,
+                )
+            }
+        };
+        Ok((
+            r_id,
+            SerializedPayload {
+                encoding: self.encoding,
+                data,
+            },
+        ))
+    }
+
+    // Like new, but the request is already serialized.
+    pub(super) fn new_serialized(
+        service_mapping: ServiceMapping,
+        r_id: RmwRequestId,
+        payload: &SerializedPayload,
+    ) -> WriteResult<Self, ()> {
+        let mut ser_buffer = BytesMut::with_capacity(payload.data.len() + 32).writer();
+        match service_mapping {
+            ServiceMapping::Basic => return basic_unsupported_write(),
+            ServiceMapping::Enhanced => {}
+            ServiceMapping::Cyclone => {
+                let cyclone_header = CycloneHeader::for_request(r_id);
+                serialization::to_writer_with_rep_id(
+                    &mut ser_buffer,
+                    &cyclone_header,
+                    payload.encoding,
+                )?;
+            }
         }
+        // Writing to BytesMut cannot fail
+        let _ = ser_buffer.write_all(&payload.data);
+        Ok(RequestWrapper {
+            serialized_message: ser_buffer.into_inner().freeze(),
+            encoding: payload.encoding,
+            phantom: PhantomData,
+        })
     }
 
     // Client creates new RequestWrappers from Requests
@@ -119,22 +152,29 @@ impl<R: Message> RequestWrapper<R> {
     ) -> WriteResult<Self, ()> {
         let mut ser_buffer = BytesMut::with_capacity(std::mem::size_of::<R>() * 3 / 2).writer();
 
-        // First, write header
         match service_mapping {
             ServiceMapping::Basic => {
-                let basic_header = BasicRequestHeader::new(r_id.into());
-                serialization::to_writer_with_rep_id(&mut ser_buffer, &basic_header, encoding)?;
+                // Header and request as one CDR stream, so that alignment
+                // continues from the header.
+                let basic_header = BasicRequestHeader::new(r_id);
+                serialization::to_writer_with_rep_id(
+                    &mut ser_buffer,
+                    &(basic_header, &request),
+                    encoding,
+                )?;
             }
             ServiceMapping::Enhanced => {
-                // This mapping does not use any header, so nothing to do here.
+                // This mapping does not use any header
+                serialization::to_writer_with_rep_id(&mut ser_buffer, &request, encoding)?;
             }
             ServiceMapping::Cyclone => {
-                let cyclone_header = CycloneHeader::new(r_id);
+                // The header is 16 bytes, so alignment of the request is the
+                // same whether it is serialized separately or not.
+                let cyclone_header = CycloneHeader::for_request(r_id);
                 serialization::to_writer_with_rep_id(&mut ser_buffer, &cyclone_header, encoding)?;
+                serialization::to_writer_with_rep_id(&mut ser_buffer, &request, encoding)?;
             }
         }
-        // Second, write request
-        serialization::to_writer_with_rep_id(&mut ser_buffer, &request, encoding)?;
         // Ok, assemble result
         Ok(RequestWrapper {
             serialized_message: ser_buffer.into_inner().freeze(),
@@ -147,7 +187,8 @@ impl<R: Message> RequestWrapper<R> {
 pub(crate) struct ResponseWrapper<R> {
     serialized_message: Bytes,
     encoding: RepresentationIdentifier,
-    phantom: PhantomData<R>,
+    // Holds no R, so it is Send and Sync regardless of R.
+    phantom: PhantomData<fn() -> R>,
 }
 
 impl<R: Message> Wrapper for ResponseWrapper<R> {
@@ -174,17 +215,13 @@ impl<R: Message> ResponseWrapper<R> {
     ) -> ReadResult<(RmwRequestId, R)> {
         match service_mapping {
             ServiceMapping::Basic => {
-                let mut bytes = self.serialized_message.clone(); // ref copy only
-                let (header, header_size) =
-                    deserialize_from_cdr_with_rep_id::<BasicReplyHeader>(&bytes, self.encoding)?;
-                if bytes.len() < header_size {
-                    read_error_deserialization!("Service response too short")
-                } else {
-                    let _header_bytes = bytes.split_off(header_size);
-                    let (response, _bytes) =
-                        deserialize_from_cdr_with_rep_id::<R>(&bytes, self.encoding)?;
-                    Ok((RmwRequestId::from(header.related_request_id), response))
-                }
+                // Decode "ReplyHeader" and Response together, like in requests.
+                let ((header, response), _bytes) =
+                    deserialize_from_cdr_with_rep_id::<(BasicReplyHeader, R)>(
+                        &self.serialized_message,
+                        self.encoding,
+                    )?;
+                Ok((header.related_request_id.into(), response))
             }
             ServiceMapping::Enhanced => {
                 // Enhanced mode does not use any header in the DDS payload.
@@ -200,24 +237,73 @@ impl<R: Message> ResponseWrapper<R> {
                 Ok((RmwRequestId::from(related_sample_identity), response))
             }
             ServiceMapping::Cyclone => {
-                // Cyclone constructs the client GUID from two parts
-                let mut client_guid_bytes = [0; 16];
-                {
-                    let (first_half, second_half) = client_guid_bytes.split_at_mut(8);
-
-                    // This seems a bit odd, but source is
-                    // https://github.com/ros2/rmw_connextdds/blob/master/rmw_connextdds_common/src/common/rmw_impl.cpp
-                    // function take_response()
-                    first_half.copy_from_slice(&client_guid.to_bytes().as_slice()[0..8]);
-
-                    // This is received in the wrapper header
-                    second_half.copy_from_slice(&message_info.writer_guid().to_bytes()[8..16]);
+                let (header, body) =
+                    split_header::<CycloneHeader>(&self.serialized_message, self.encoding)?;
+                let (response, _response_bytes) =
+                    deserialize_from_cdr_with_rep_id::<R>(&body, self.encoding)?;
+                Ok((header.request_id_at_client(client_guid), response))
+            }
+        }
+    }
+
+    // Like unwrap, but leaves the response serialized.
+    pub(super) fn unwrap_serialized(
+        &self,
+        service_mapping: ServiceMapping,
+        message_info: MessageInfo,
+        client_guid: GUID,
+    ) -> ReadResult<(RmwRequestId, SerializedPayload)> {
+        let (r_id, data) = match service_mapping {
+            ServiceMapping::Basic => return basic_unsupported_read(),
+            ServiceMapping::Enhanced => match message_info.related_sample_identity() {
+                Some(rsi) => (RmwRequestId::from(rsi), self.serialized_message.clone()),
+                None => {
+                    return read_error_deserialization!(
+                        "ServiceMapping=Enhanced, but response message did not have related_sample_identity parameter!"
+                    )
                 }
-                let client_guid = GUID::from_bytes(client_guid_bytes);
+            },
+            ServiceMapping::Cyclone => {
+                let (header, body) =
+                    split_header::<CycloneHeader>(&self.serialized_message, self.encoding)?;
+                (header.request_id_at_client(client_guid), body)
+            }
+        };
+        Ok((
+            r_id,
+            SerializedPayload {
+                encoding: self.encoding,
+                data,
+            },
+        ))
+    }
 
-                cyclone_unwrap::<R>(self.serialized_message.clone(), client_guid, self.encoding)
+    // Like new, but the response is already serialized.
+    pub(super) fn new_serialized(
+        service_mapping: ServiceMapping,
+        r_id: RmwRequestId,
+        payload: &SerializedPayload,
+    ) -> WriteResult<Self, ()> {
+        let mut ser_buffer = BytesMut::with_capacity(payload.data.len() + 32).writer();
+        match service_mapping {
+            ServiceMapping::Basic => return basic_unsupported_write(),
+            ServiceMapping::Enhanced => {}
+            ServiceMapping::Cyclone => {
+                let cyclone_header = CycloneHeader::for_response(r_id);
+                serialization::to_writer_with_rep_id(
+                    &mut ser_buffer,
+                    &cyclone_header,
+                    payload.encoding,
+                )?;
             }
         }
+        // Writing to BytesMut cannot fail
+        let _ = ser_buffer.write_all(&payload.data);
+        Ok(ResponseWrapper {
+            serialized_message: ser_buffer.into_inner().freeze(),
+            encoding: payload.encoding,
+            phantom: PhantomData,
+        })
     }
 
     // Server creates new ResponseWrapper from Response
@@ -230,18 +316,23 @@ impl<R: Message> ResponseWrapper<R> {
         let mut ser_buffer = BytesMut::with_capacity(std::mem::size_of::<R>() * 3 / 2).writer();
         match service_mapping {
             ServiceMapping::Basic => {
-                let basic_header = BasicReplyHeader::new(r_id.into());
-                serialization::to_writer_with_rep_id(&mut ser_buffer, &basic_header, encoding)?;
+                let basic_header = BasicReplyHeader::new(r_id);
+                serialization::to_writer_with_rep_id(
+                    &mut ser_buffer,
+                    &(basic_header, &response),
+                    encoding,
+                )?;
             }
             ServiceMapping::Enhanced => {
-                // No header, nothing to write here.
+                // No header
+                serialization::to_writer_with_rep_id(&mut ser_buffer, &response, encoding)?;
             }
             ServiceMapping::Cyclone => {
-                let cyclone_header = CycloneHeader::new(r_id);
+                let cyclone_header = CycloneHeader::for_response(r_id);
                 serialization::to_writer_with_rep_id(&mut ser_buffer, &cyclone_header, encoding)?;
+                serialization::to_writer_with_rep_id(&mut ser_buffer, &response, encoding)?;
             }
         }
-        serialization::to_writer_with_rep_id(&mut ser_buffer, &response, encoding)?;
         let serialized_message = ser_buffer.into_inner().freeze();
         Ok(ResponseWrapper {
             serialized_message,
@@ -256,13 +347,13 @@ impl<R: Message> ResponseWrapper<R> {
 #[derive(Serialize, Deserialize)]
 pub struct BasicRequestHeader {
     // "struct RequestHeader":
-    request_id: SampleIdentity,
+    request_id: BasicSampleIdentity,
     instance_name: String, // This is apparently not used: Always sent as empty string.
 }
 impl BasicRequestHeader {
-    fn new(request_id: SampleIdentity) -> Self {
+    fn new(request_id: RmwRequestId) -> Self {
         BasicRequestHeader {
-            request_id,
+            request_id: request_id.into(),
             instance_name: "".to_string(),
         }
     }
@@ -272,20 +363,53 @@ impl Message for BasicRequestHeader {}
 #[derive(Serialize, Deserialize)]
 pub struct BasicReplyHeader {
     // "struct ReplyHeader":
-    related_request_id: SampleIdentity,
+    related_request_id: BasicSampleIdentity,
     remote_exception_code: u32, /* It is uncertain if this is ever used. Transmitted as zero
                                  * ("REMOTE_EX_OK"). */
 }
 impl BasicReplyHeader {
-    fn new(related_request_id: SampleIdentity) -> Self {
+    fn new(related_request_id: RmwRequestId) -> Self {
         BasicReplyHeader {
-            related_request_id,
+            related_request_id: related_request_id.into(),
             remote_exception_code: 0,
         }
     }
 }
 impl Message for BasicReplyHeader {}
 
+// "struct SampleIdentity" as in the RPC over DDS specification. The sequence
+// number is a struct of high and low halves, which is not the same as an
+// i64 in little-endian CDR.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct BasicSampleIdentity {
+    writer_guid: GUID,
+    sequence_number_high: i32,
+    sequence_number_low: u32,
+}
+
+impl From<RmwRequestId> for BasicSampleIdentity {
+    fn from(r_id: RmwRequestId) -> Self {
+        let sn = r_id.sequence_number;
+        BasicSampleIdentity {
+            writer_guid: r_id.writer_guid,
+            sequence_number_high: sn.high(),
+            sequence_number_low: sn.low(),
+        }
+    }
+}
+
+impl From<BasicSampleIdentity> for RmwRequestId {
+    fn from(si: BasicSampleIdentity) -> Self {
+        RmwRequestId {
+            writer_guid: si.writer_guid,
+            sequence_number: request_id::SequenceNumber::from_high_low(
+                si.sequence_number_high,
+                si.sequence_number_low,
+            ),
+        }
+    }
+}
+
 // Cyclone mode header
 //
 // This is reverse-engineered from
@@ -293,56 +417,138 @@ impl Message for BasicReplyHeader {}
 // https://github.com/ros2/rmw_cyclonedds/blob/master/rmw_cyclonedds_cpp/src/serdata.hpp
 // This is a header that Cyclone puts in DDS messages. Same header is used for
 // Request and Response.
-#[derive(Serialize, Deserialize)]
+//
+// In rmw_cyclonedds this is `cdds_request_header_t { uint64_t guid; int64_t
+// seq; }`. The "guid" is an opaque 8-byte client identifier (the instance
+// handle of the request writer), which the server echoes back in the
+// response. Clients use it to pick their own responses from the shared
+// response topic, so it must be unique among the Clients of a Service, also
+// across hosts.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct CycloneHeader {
-    guid_second_half: [u8; 8], // CycloneDDS RMW only sends last 8 bytes of client GUID
-    sequence_number_high: i32,
-    sequence_number_low: u32,
+    client_id: [u8; 8],
+    sequence_number: i64,
 }
 impl CycloneHeader {
-    fn new(r_id: RmwRequestId) -> Self {
-        let sn = r_id.sequence_number;
-        let mut guid_second_half = [0; 8];
-        // writer_guid means client GUID (i.e. request writer)
-        guid_second_half.copy_from_slice(&r_id.writer_guid.to_bytes()[8..16]);
+    // Client sends request `r_id`, where `writer_guid` is its own GUID.
+    fn for_request(r_id: RmwRequestId) -> Self {
+        CycloneHeader {
+            client_id: cyclone_client_id(r_id.writer_guid),
+            sequence_number: r_id.sequence_number.into(),
+        }
+    }
 
+    // Server responds to `r_id`, which it got from `request_id_at_server`.
+    fn for_response(r_id: RmwRequestId) -> Self {
+        let mut client_id = [0; 8];
+        client_id.copy_from_slice(&r_id.writer_guid.to_bytes()[0..8]);
         CycloneHeader {
-            guid_second_half,
-            sequence_number_high: sn.high(),
-            sequence_number_low: sn.low(),
+            client_id,
+            sequence_number: r_id.sequence_number.into(),
+        }
+    }
+
+    // Request id of a received request, as seen by the Server.
+    //
+    // The client id is kept in the first half of `writer_guid`, so that it can
+    // be echoed back in the response. The second half comes from the request
+    // writer GUID, like rmw_cyclonedds does with its publication handle.
+    fn request_id_at_server(&self, request_writer: GUID) -> RmwRequestId {
+        let mut guid_bytes = [0; 16];
+        guid_bytes[0..8].copy_from_slice(&self.client_id);
+        guid_bytes[8..16].copy_from_slice(&request_writer.to_bytes()[8..16]);
+        RmwRequestId {
+            writer_guid: GUID::from_bytes(guid_bytes),
+            sequence_number: request_id::SequenceNumber::from(self.sequence_number),
+        }
+    }
+
+    // Request id of a received response, as seen by the Client with GUID
+    // `client_guid`. If the response is to some other Client, the resulting
+    // `writer_guid` will not match `client_guid`.
+    fn request_id_at_client(&self, client_guid: GUID) -> RmwRequestId {
+        let writer_guid = if self.client_id == cyclone_client_id(client_guid) {
+            client_guid
+        } else {
+            let mut guid_bytes = [0; 16];
+            guid_bytes[0..8].copy_from_slice(&self.client_id);
+            GUID::from_bytes(guid_bytes)
+        };
+        RmwRequestId {
+            writer_guid,
+            sequence_number: request_id::SequenceNumber::from(self.sequence_number),
         }
     }
 }
 impl Message for CycloneHeader {}
 
-// helper function, because Cyclone Request and Response unwrapping/decoding are
-// the same.
-fn cyclone_unwrap<R: Message>(
-    serialized_message: Bytes,
-    writer_guid: GUID,
+// 8-byte Client identifier for the Cyclone header, derived from the full
+// Client GUID. Truncating the GUID would not do, because then Clients on
+// different hosts could get the same identifier.
+fn cyclone_client_id(client_guid: GUID) -> [u8; 8] {
+    // FNV-1a
+    let hash = client_guid
+        .to_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+        });
+    hash.to_le_bytes()
+}
+
+// Request id of an incoming request in the Enhanced mapping
+fn enhanced_request_id(message_info: &MessageInfo) -> RmwRequestId {
+    let mut rmw_req_id = RmwRequestId::from(
+          message_info.related_sample_identity()
+            .unwrap_or_else(|| {
+              // ServiceMapping::Enhanced is supposed to contain related sample identity as 
+              // inline QoS parameter.
+              //
+              // Use the identity of the incoming request as a default, if there was no
+              // related sample identity specified in inline QoS.
+              let backup_identity = message_info.sample_identity();
+              warn!("RequestWrapper::unwrap: related_sample_identity missing. Using sample_identity = {backup_identity:?}");
+              backup_identity
+            })
+        );
+
+    // Logic added for eProsima FastDDS compatibility:
+    //
+    // If the SequenceNumber in related_sample_identity (presumable from inline QoS)
+    // is SEQUENCENUMBER_UNKNOWN, then it cannot refer to a real and valid DATA submessage.
+    // We patch the situation by using the actual SequenceNumber of the Request DATA submessage.
+    //
+    // Maybe FastDDS just forgets to set the field in RELATED_SAMPLE_IDENTITY inline QoS parameter?
+    if rmw_req_id.sequence_number == SequenceNumber::UNKNOWN {
+        rmw_req_id.sequence_number = message_info.sample_identity().sequence_number;
+    }
+    rmw_req_id
+}
+
+// Decode header H, and return it with the rest of the message
+// With the Basic mapping, the padding between the header and the body depends
+// on the message type, so serialized bodies cannot be split off or attached.
+fn basic_unsupported_read<T>() -> ReadResult<T> {
+    read_error_deserialization!("ServiceMapping::Basic is not supported with serialized payloads")
+}
+
+fn basic_unsupported_write<T>() -> WriteResult<T, ()> {
+    Err(WriteError::Serialization {
+        reason: "ServiceMapping::Basic is not supported with serialized payloads".to_string(),
+        data: (),
+    })
+}
+
+fn split_header<H: Message>(
+    serialized_message: &Bytes,
     encoding: RepresentationIdentifier,
-) -> ReadResult<(RmwRequestId, R)> {
-    // 1. decode "CycloneHeader" and
-    // 2. decode Request/response
-    let mut bytes = serialized_message; // ref copy only, to make "mutable"
+) -> ReadResult<(H, Bytes)> {
     let (header, header_size) =
-        deserialize_from_cdr_with_rep_id::<CycloneHeader>(&bytes, encoding)?;
-    if bytes.len() < header_size {
+        deserialize_from_cdr_with_rep_id::<H>(serialized_message, encoding)?;
+    if serialized_message.len() < header_size {
         read_error_deserialization!("Service message too short")
     } else {
-        let _header_bytes = bytes.split_off(header_size);
-        let (response, _response_bytes) = deserialize_from_cdr_with_rep_id::<R>(&bytes, encoding)?;
-        let req_id = RmwRequestId {
-            writer_guid, // TODO: This seems to be completely wrong!!!
-            // When we are the client, we get half of Client GUID on the CycloneHeader, other half from
-            // Client State when we are the server, we get half of Client GUID on the CycloneHeader,
-            // other half from writer_guid.
-            sequence_number: request_id::SequenceNumber::from_high_low(
-                header.sequence_number_high,
-                header.sequence_number_low,
-            ),
-        };
-        Ok((req_id, response))
+        Ok((header, serialized_message.slice(header_size..)))
     }
 }
 
@@ -354,7 +560,7 @@ pub(super) struct ServiceDeserializerAdapter<RW> {
     phantom: PhantomData<RW>,
 }
 pub(super) struct ServiceSerializerAdapter<RW> {
-    phantom: PhantomData<RW>,
+    phantom: PhantomData<fn() -> RW>,
 }
 
 impl<RW> ServiceDeserializerAdapter<RW> {
@@ -410,3 +616,178 @@ impl<RW: Wrapper> no_key::SerializerAdapter<RW> for ServiceSerializerAdapter<RW>
         Ok(value.bytes())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_request_response_layout() {
+        let r_id = RmwRequestId {
+            writer_guid: GUID::from_bytes([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 0, 0, 4, 3]),
+            sequence_number: request_id::SequenceNumber::from(5),
+        };
+        let request = RequestWrapper::<i64>::new(
+            ServiceMapping::Basic,
+            r_id,
+            RepresentationIdentifier::CDR_LE,
+            7,
+        )
+        .unwrap();
+        let bytes = request.bytes();
+        // GUID, sequence number high and low, empty instance_name
+        assert_eq!(bytes[16..20], 0i32.to_le_bytes());
+        assert_eq!(bytes[20..24], 5u32.to_le_bytes());
+        assert_eq!(bytes[24..29], [1, 0, 0, 0, 0]);
+        // Request is aligned relative to the start of the message
+        assert_eq!(bytes[32..], 7i64.to_le_bytes());
+        let ((header, value), _) = deserialize_from_cdr_with_rep_id::<(BasicRequestHeader, i64)>(
+            &bytes,
+            RepresentationIdentifier::CDR_LE,
+        )
+        .unwrap();
+        assert_eq!((RmwRequestId::from(header.request_id), value), (r_id, 7));
+
+        let response = ResponseWrapper::<i64>::new(
+            ServiceMapping::Basic,
+            r_id,
+            RepresentationIdentifier::CDR_LE,
+            8,
+        )
+        .unwrap();
+        let bytes = response.bytes();
+        assert_eq!(bytes[24..28], 0u32.to_le_bytes()); // remote_exception_code
+        assert_eq!(bytes[32..], 8i64.to_le_bytes());
+    }
+
+    #[test]
+    fn cyclone_request_response_round_trip() {
+        let client_guid = GUID::from_bytes([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 0, 0, 4, 3]);
+        let request_writer = client_guid;
+        let r_id = RmwRequestId {
+            writer_guid: client_guid,
+            sequence_number: request_id::SequenceNumber::from(5),
+        };
+
+        // Client sends a request
+        let request = RequestWrapper::<i64>::new(
+            ServiceMapping::Cyclone,
+            r_id,
+            RepresentationIdentifier::CDR_LE,
+            7,
+        )
+        .unwrap();
+        let bytes = request.bytes();
+        assert_eq!(bytes[0..8], cyclone_client_id(client_guid));
+        assert_eq!(bytes[8..16], 5i64.to_le_bytes());
+        assert_eq!(bytes[16..24], 7i64.to_le_bytes());
+
+        // Server decodes it, and responds
+        let (header, body) =
+            split_header::<CycloneHeader>(&bytes, RepresentationIdentifier::CDR_LE).unwrap();
+        let (value, _) =
+            deserialize_from_cdr_with_rep_id::<i64>(&body, RepresentationIdentifier::CDR_LE)
+                .unwrap();
+        assert_eq!(value, 7);
+        let server_r_id = header.request_id_at_server(request_writer);
+        let response = ResponseWrapper::<i64>::new(
+            ServiceMapping::Cyclone,
+            server_r_id,
+            RepresentationIdentifier::CDR_LE,
+            8,
+        )
+        .unwrap();
+
+        // Client recognizes its own response, but others do not.
+        let (header, _body) =
+            split_header::<CycloneHeader>(&response.bytes(), RepresentationIdentifier::CDR_LE)
+                .unwrap();
+        assert_eq!(header.request_id_at_client(client_guid), r_id);
+        let other_client = GUID::from_bytes([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 0, 0, 4, 3]);
+        assert_ne!(
+            header.request_id_at_client(other_client).writer_guid,
+            other_client
+        );
+    }
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        short: u16,
+        long: u32,
+    }
+    impl Message for Sample {}
+
+    #[test]
+    fn serialized_round_trip() {
+        let client_guid = GUID::from_bytes([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 0, 0, 4, 3]);
+        let r_id = RmwRequestId {
+            writer_guid: client_guid,
+            sequence_number: request_id::SequenceNumber::from(5),
+        };
+        let message_info = MessageInfo::recovered(
+            client_guid,
+            SequenceNumber::from(5),
+            rustdds::Timestamp::now(),
+        );
+        let sample = || Sample {
+            short: 0x0102,
+            long: 0x03040506,
+        };
+
+        for mapping in [ServiceMapping::Enhanced, ServiceMapping::Cyclone] {
+            // Typed request, forwarded as a serialized payload
+            let request = RequestWrapper::<Sample>::new(
+                mapping,
+                r_id,
+                RepresentationIdentifier::CDR_LE,
+                sample(),
+            )
+            .unwrap();
+            let (_, payload) = request.unwrap_serialized(mapping, &message_info).unwrap();
+            let forwarded =
+                RequestWrapper::<Sample>::new_serialized(mapping, r_id, &payload).unwrap();
+            assert_eq!(forwarded.bytes(), request.bytes());
+            let (_, decoded) = forwarded.unwrap(mapping, &message_info).unwrap();
+            assert_eq!(decoded, sample());
+        }
+
+        // Responses with the Cyclone mapping, which has a header
+        let response = ResponseWrapper::<Sample>::new(
+            ServiceMapping::Cyclone,
+            r_id,
+            RepresentationIdentifier::CDR_LE,
+            sample(),
+        )
+        .unwrap();
+        let (_, payload) = response
+            .unwrap_serialized(ServiceMapping::Cyclone, message_info.clone(), client_guid)
+            .unwrap();
+        let forwarded =
+            ResponseWrapper::<Sample>::new_serialized(ServiceMapping::Cyclone, r_id, &payload)
+                .unwrap();
+        assert_eq!(forwarded.bytes(), response.bytes());
+        let (_, decoded) = forwarded
+            .unwrap(ServiceMapping::Cyclone, message_info.clone(), client_guid)
+            .unwrap();
+        assert_eq!(decoded, sample());
+
+        // The padding after the Basic header depends on the message type.
+        assert!(
+            RequestWrapper::<Sample>::new_serialized(ServiceMapping::Basic, r_id, &payload)
+                .is_err()
+        );
+        assert!(
+            ResponseWrapper::<Sample>::new_serialized(ServiceMapping::Basic, r_id, &payload)
+                .is_err()
+        );
+        let basic = RequestWrapper::<Sample>::new(
+            ServiceMapping::Basic,
+            r_id,
+            RepresentationIdentifier::CDR_LE,
+            sample(),
+        )
+        .unwrap();
+        assert!(basic
+            .unwrap_serialized(ServiceMapping::Basic, &message_info)
+            .is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Publishing simulated time
+//!
+//! A simulator, or any other program that drives simulated time, publishes
+//! the current time to Topic `/clock`. Nodes that have parameter
+//! `use_sim_time` set to `true` then use that as their time source, see
+//! [`Node::time_now`].
+
+use rustdds::dds::{CreateResult, WriteResult};
+
+use crate::{
+    interfaces::rosgraph_msgs,
+    node::{context::DEFAULT_PUBLISHER_QOS, pubsub::Publisher, Node},
+    prelude::Name,
+    time::ros_time::{ROSDuration, ROSTime},
+};
+
+/// Publishes simulated time to Topic `/clock`.
+///
+/// The publisher keeps the current simulated time, and publishes it whenever
+/// it is changed with [`Self::set_time`] or [`Self::advance`]. Publishing is
+/// Reliable with history depth 1, so that both Reliable and BestEffort
+/// subscribers will receive it.
+pub struct ClockPublisher {
+    publisher: Publisher<rosgraph_msgs::Clock>,
+    time: ROSTime,
+}
+
+impl ClockPublisher {
+    /// Create a publisher to `/clock`, with initial time `start_time`.
+    ///
+    /// Nothing is published until the time is set or advanced.
+    pub fn new(node: &mut Node, start_time: ROSTime) -> CreateResult<ClockPublisher> {
+        let topic = node.create_topic_for::<rosgraph_msgs::Clock>(
+            &Name::new("/", "clock").unwrap(),
+            &DEFAULT_PUBLISHER_QOS,
+        )?;
+        let publisher = node.create_publisher(&topic, None)?;
+        Ok(ClockPublisher {
+            publisher,
+            time: start_time,
+        })
+    }
+
+    /// The latest simulated time.
+    pub fn time(&self) -> ROSTime {
+        self.time
+    }
+
+    /// Set and publish simulated time.
+    ///
+    /// Time may also be set backwards, e.g. when a simulation is reset.
+    pub fn set_time(&mut self, time: ROSTime) -> WriteResult<(), rosgraph_msgs::Clock> {
+        self.time = time;
+        self.publish()
+    }
+
+    /// Move simulated time forward by `step`, and publish it.
+    pub fn advance(&mut self, step: ROSDuration) -> WriteResult<(), rosgraph_msgs::Clock> {
+        self.set_time(self.time + step)
+    }
+
+    /// Publish the current simulated time again.
+    pub fn publish(&self) -> WriteResult<(), rosgraph_msgs::Clock> {
+        self.publisher.publish(self.time.into())
+    }
+}
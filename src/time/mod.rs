@@ -1,2 +1,3 @@
+pub mod clock_publisher;
 pub mod ros_time;
 pub mod steady_time;
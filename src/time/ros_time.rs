@@ -1,7 +1,7 @@
 use std::{
     convert::TryFrom,
     ops::{Add, Sub},
-    time::Duration,
+    time::{self, Duration},
 };
 
 use chrono::{DateTime, Utc};
@@ -9,6 +9,8 @@ use log::error;
 use rustdds::Timestamp;
 use serde::{Deserialize, Serialize};
 
+use crate::interfaces::std_msgs::Header;
+
 /// ROS Time with nanosecond precision
 ///
 /// This is the in-memory representation of builtin_interfaces::Time
@@ -37,6 +39,27 @@ impl ROSTime {
             nanos_since_epoch: nanos_since_unix_epoch,
         }
     }
+
+    /// Timestamp of a message [`Header`]
+    pub fn from_header(header: &Header) -> Self {
+        header.stamp.into()
+    }
+
+    /// Add a duration, or `None` if the result is not representable.
+    pub const fn checked_add(self, d: ROSDuration) -> Option<ROSTime> {
+        match self.nanos_since_epoch.checked_add(d.diff) {
+            Some(nanos) => Some(ROSTime::from_nanos(nanos)),
+            None => None,
+        }
+    }
+
+    /// Subtract a duration, or `None` if the result is not representable.
+    pub const fn checked_sub(self, d: ROSDuration) -> Option<ROSTime> {
+        match self.nanos_since_epoch.checked_sub(d.diff) {
+            Some(nanos) => Some(ROSTime::from_nanos(nanos)),
+            None => None,
+        }
+    }
 }
 
 /// Overflow/underflow in timestamp conversion
@@ -77,6 +100,36 @@ impl From<ROSTime> for chrono::DateTime<Utc> {
     }
 }
 
+// std::time::SystemTime <-> ROSTime
+
+/// Fails if `system_time` is more than ~292 years away from the Unix epoch.
+impl TryFrom<time::SystemTime> for ROSTime {
+    type Error = OutOfRangeError;
+
+    fn try_from(system_time: time::SystemTime) -> Result<ROSTime, OutOfRangeError> {
+        let nanos = match system_time.duration_since(time::UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_nanos()).ok(),
+            Err(before) => i64::try_from(before.duration().as_nanos()).ok().map(|n| -n),
+        };
+        nanos.map(ROSTime::from_nanos).ok_or(OutOfRangeError {})
+    }
+}
+
+/// Fails only if the platform cannot represent the time.
+impl TryFrom<ROSTime> for time::SystemTime {
+    type Error = OutOfRangeError;
+
+    fn try_from(rt: ROSTime) -> Result<time::SystemTime, OutOfRangeError> {
+        let offset = Duration::from_nanos(rt.to_nanos().unsigned_abs());
+        if rt.to_nanos() >= 0 {
+            time::UNIX_EPOCH.checked_add(offset)
+        } else {
+            time::UNIX_EPOCH.checked_sub(offset)
+        }
+        .ok_or(OutOfRangeError {})
+    }
+}
+
 // rustDDS::Timestamp <-> ROSTime
 
 impl From<ROSTime> for Timestamp {
@@ -160,6 +213,7 @@ impl Add<ROSDuration> for ROSTime {
 /// Supports conversions to/from
 /// * [`std::time::Duration`]
 /// * [`chrono::Duration`]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug)]
 pub struct ROSDuration {
     diff: i64,
 }
@@ -255,12 +309,25 @@ pub struct SystemTime {
 
 #[cfg(test)]
 mod test {
-    //use rustdds::Timestamp;
+    use std::time;
+
+    use chrono::{DateTime, Utc};
 
-    //use super::ROSTime;
+    use super::{ROSDuration, ROSTime};
 
     #[test]
     fn conversion() {
-        //TODO
+        for nanos in [0, 1, -1, 1_700_000_000_123_456_789, -1_500_000_000] {
+            let rt = ROSTime::from_nanos(nanos);
+            let st = time::SystemTime::try_from(rt).unwrap();
+            assert_eq!(ROSTime::try_from(st).unwrap(), rt);
+            let ct = DateTime::<Utc>::from(rt);
+            assert_eq!(ROSTime::try_from(ct).unwrap(), rt);
+        }
+        assert!(ROSTime::try_from(DateTime::<Utc>::MAX_UTC).is_err());
+        assert_eq!(
+            ROSTime::from_nanos(i64::MAX).checked_add(ROSDuration::from_nanos(1)),
+            None
+        );
     }
 }
@@ -37,6 +37,12 @@ pub struct Time {
     instant: Instant,
 }
 
+/// [`Time`] under a name that does not collide with other time types.
+///
+/// Use this for measuring durations, e.g. timeouts and rates, which should
+/// not be affected by wall clock adjustments or simulated time.
+pub type SteadyTime = Time;
+
 impl Time {
     pub fn now() -> Time {
         Self {
@@ -44,6 +50,11 @@ impl Time {
         }
     }
 
+    /// Time passed since `self`.
+    pub fn elapsed(&self) -> TimeDiff {
+        Self::now() - *self
+    }
+
     /// returns the current time in two formats: Time and ROSTime
     pub fn now_with_ros_time() -> (Time, ROSTime) {
         let (st, ct) = Self::now_with_utc();
@@ -99,11 +110,11 @@ impl Sub<TimeDiff> for Time {
     fn sub(self, diff: TimeDiff) -> Time {
         if diff.is_negative {
             Time {
-                instant: self.instant - diff.duration,
+                instant: self.instant + diff.duration,
             }
         } else {
             Time {
-                instant: self.instant + diff.duration,
+                instant: self.instant - diff.duration,
             }
         }
     }
@@ -116,11 +127,11 @@ impl Add<TimeDiff> for Time {
     fn add(self, diff: TimeDiff) -> Time {
         if diff.is_negative {
             Time {
-                instant: self.instant + diff.duration,
+                instant: self.instant - diff.duration,
             }
         } else {
             Time {
-                instant: self.instant - diff.duration,
+                instant: self.instant + diff.duration,
             }
         }
     }
@@ -236,3 +247,20 @@ impl Sub for TimeDiff {
         Self::from_nanos(self.as_nanos() - other.as_nanos())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Time, TimeDiff};
+
+    #[test]
+    fn arithmetic() {
+        let t0 = Time::now();
+        let step = TimeDiff::from_millis(1500);
+        let t1 = t0 + step;
+        assert!(t1 > t0);
+        assert_eq!(t1 - t0, step);
+        assert_eq!(t0 - t1, TimeDiff::from_millis(-1500));
+        assert_eq!(t1 - step, t0);
+        assert_eq!(t0 - TimeDiff::from_millis(-1500), t1);
+    }
+}